@@ -0,0 +1,120 @@
+//! Prints the virtual device state published by Rust vJoy Manager through shared memory.
+//!
+//! Enable "Publish outputs to shared memory" in the System menu, then run
+//! `cargo run --example shared_memory_reader`. The layout is documented in
+//! `src/input/shared_memory.rs` and duplicated here, as an external program would.
+
+#[cfg(windows)]
+mod reader {
+    use std::{
+        ffi::c_void,
+        sync::atomic::{fence, AtomicU32, Ordering},
+        time::Duration,
+    };
+
+    const NAME: &str = "Local\\RustVJoyManagerOutput";
+    const MAGIC: u32 = u32::from_le_bytes(*b"RVJM");
+    const VERSION: u32 = 1;
+    const FILE_MAP_READ: u32 = 0x0004;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct Device {
+        id: u32,
+        num_buttons: u32,
+        num_axes: u32,
+        num_hats: u32,
+        axes: [i32; 8],
+        hats: [i32; 4],
+        buttons: [u32; 4],
+    }
+
+    #[repr(C)]
+    struct Block {
+        magic: u32,
+        version: u32,
+        sequence: AtomicU32,
+        num_devices: u32,
+        devices: [Device; 16],
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn OpenFileMappingW(access: u32, inherit: i32, name: *const u16) -> isize;
+        fn MapViewOfFile(
+            mapping: isize,
+            access: u32,
+            offset_high: u32,
+            offset_low: u32,
+            size: usize,
+        ) -> *mut c_void;
+        fn UnmapViewOfFile(address: *const c_void) -> i32;
+        fn CloseHandle(handle: isize) -> i32;
+    }
+
+    /// Seqlock read: retry while a write is in progress or happened during the copy
+    unsafe fn read(block: *const Block) -> (u32, [Device; 16]) {
+        loop {
+            let start = (*block).sequence.load(Ordering::Acquire);
+            if start % 2 == 1 {
+                std::hint::spin_loop();
+                continue;
+            }
+            let num_devices = std::ptr::addr_of!((*block).num_devices).read_volatile();
+            let devices = std::ptr::addr_of!((*block).devices).read_volatile();
+            fence(Ordering::Acquire);
+            if (*block).sequence.load(Ordering::Relaxed) == start {
+                return (num_devices.min(16), devices);
+            }
+        }
+    }
+
+    pub fn run() {
+        let name: Vec<u16> = NAME.encode_utf16().chain(std::iter::once(0)).collect();
+        unsafe {
+            let handle = OpenFileMappingW(FILE_MAP_READ, 0, name.as_ptr());
+            if handle == 0 {
+                eprintln!("{NAME} not found, is publishing enabled?");
+                return;
+            }
+            let size = std::mem::size_of::<Block>();
+            let block = MapViewOfFile(handle, FILE_MAP_READ, 0, 0, size) as *const Block;
+            if block.is_null() {
+                eprintln!("failed to map {NAME}");
+                CloseHandle(handle);
+                return;
+            }
+            if (*block).magic != MAGIC || (*block).version != VERSION {
+                eprintln!("unexpected layout version {}", (*block).version);
+            } else {
+                for _ in 0..100 {
+                    let (num_devices, devices) = read(block);
+                    for device in devices.iter().take(num_devices as usize) {
+                        let axes = &device.axes[..device.num_axes.min(8) as usize];
+                        let hats = &device.hats[..device.num_hats.min(4) as usize];
+                        let pressed: Vec<u32> = (1..=device.num_buttons.min(128))
+                            .filter(|b| {
+                                device.buttons[(b - 1) as usize / 32] & (1 << ((b - 1) % 32)) != 0
+                            })
+                            .collect();
+                        println!(
+                            "vJoy {}: axes {axes:?} hats {hats:?} pressed {pressed:?}",
+                            device.id
+                        );
+                    }
+                    std::thread::sleep(Duration::from_millis(100));
+                }
+            }
+            UnmapViewOfFile(block as *const c_void);
+            CloseHandle(handle);
+        }
+    }
+}
+
+fn main() {
+    #[cfg(windows)]
+    reader::run();
+
+    #[cfg(not(windows))]
+    eprintln!("the shared memory block is only published on Windows");
+}
@@ -0,0 +1,276 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+
+/// Name of the value in the Run key of the current user
+const VALUE_NAME: &str = "RustvJoyManager";
+
+/// Registration to start with Windows as read from the registry, never cached so the settings
+/// show the actual state even if it was changed outside the app.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Registration {
+    /// Not a Windows build
+    Unsupported,
+    NotRegistered,
+    /// Registered with the current executable and config
+    Registered,
+    /// Registered with another command, e.g. from before the executable was moved
+    Stale {
+        command: String,
+        exe_moved: bool,
+    },
+}
+
+impl Registration {
+    /// Compares the `registered` command with the one [`register`] would write now
+    pub fn from_command(registered: Option<&str>, expected: &str) -> Self {
+        let Some(registered) = registered else {
+            return Self::NotRegistered;
+        };
+        if registered == expected {
+            return Self::Registered;
+        }
+        Self::Stale {
+            command: registered.to_string(),
+            exe_moved: command_exe(registered) != command_exe(expected),
+        }
+    }
+}
+
+/// Command line of the Run entry: minimized with `config` or the last config
+pub fn command_line(exe: &Path, config: Option<&Path>) -> String {
+    let mut command = format!("\"{}\" --start-minimized", exe.display());
+    if let Some(config) = config {
+        command += &format!(" --config \"{}\"", config.display());
+    }
+    command
+}
+
+/// Executable of a command line, quoted or up to the first space
+fn command_exe(command: &str) -> Option<PathBuf> {
+    let command = command.trim_start();
+    let exe = match command.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next()?,
+        None => command.split_whitespace().next()?,
+    };
+    Some(PathBuf::from(exe))
+}
+
+pub fn status(config: Option<&Path>) -> Result<Registration, Error> {
+    if !cfg!(windows) {
+        return Ok(Registration::Unsupported);
+    }
+    let expected = command_line(&std::env::current_exe()?, config);
+    let registered = registry::read(VALUE_NAME)?;
+    Ok(Registration::from_command(registered.as_deref(), &expected))
+}
+
+/// Registers the running executable, replacing a stale registration
+pub fn register(config: Option<&Path>) -> Result<(), Error> {
+    if !cfg!(windows) {
+        return Ok(());
+    }
+    let command = command_line(&std::env::current_exe()?, config);
+    registry::write(VALUE_NAME, &command)
+}
+
+pub fn unregister() -> Result<(), Error> {
+    if !cfg!(windows) {
+        return Ok(());
+    }
+    registry::delete(VALUE_NAME)
+}
+
+#[cfg(windows)]
+mod registry {
+    use crate::error::Error;
+
+    const HKEY_CURRENT_USER: isize = 0x8000_0001_u32 as i32 as isize;
+    const RUN_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+    const KEY_QUERY_VALUE: u32 = 0x0001;
+    const KEY_SET_VALUE: u32 = 0x0002;
+    const REG_SZ: u32 = 1;
+    const ERROR_SUCCESS: i32 = 0;
+    const ERROR_FILE_NOT_FOUND: i32 = 2;
+
+    #[link(name = "advapi32")]
+    extern "system" {
+        fn RegOpenKeyExW(
+            key: isize,
+            sub_key: *const u16,
+            options: u32,
+            access: u32,
+            result: *mut isize,
+        ) -> i32;
+        fn RegQueryValueExW(
+            key: isize,
+            name: *const u16,
+            reserved: *mut u32,
+            kind: *mut u32,
+            data: *mut u8,
+            size: *mut u32,
+        ) -> i32;
+        fn RegSetValueExW(
+            key: isize,
+            name: *const u16,
+            reserved: u32,
+            kind: u32,
+            data: *const u8,
+            size: u32,
+        ) -> i32;
+        fn RegDeleteValueW(key: isize, name: *const u16) -> i32;
+        fn RegCloseKey(key: isize) -> i32;
+    }
+
+    fn wide(string: &str) -> Vec<u16> {
+        string.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    fn check(status: i32) -> Result<(), Error> {
+        match status {
+            ERROR_SUCCESS => Ok(()),
+            code => Err(std::io::Error::from_raw_os_error(code).into()),
+        }
+    }
+
+    /// Opens the Run key, closes it after `f`
+    fn with_run_key<T>(access: u32, f: impl FnOnce(isize) -> Result<T, Error>) -> Result<T, Error> {
+        let mut key = 0;
+        // SAFETY: plain Win32 calls with valid nul terminated strings, the key is closed below
+        unsafe {
+            check(RegOpenKeyExW(
+                HKEY_CURRENT_USER,
+                wide(RUN_KEY).as_ptr(),
+                0,
+                access,
+                &mut key,
+            ))?;
+            let result = f(key);
+            RegCloseKey(key);
+            result
+        }
+    }
+
+    pub fn read(name: &str) -> Result<Option<String>, Error> {
+        let name = wide(name);
+        with_run_key(KEY_QUERY_VALUE, |key| {
+            let mut kind = 0;
+            let mut size = 0;
+            // SAFETY: the first call only reads the size, the buffer of the second one is as large
+            unsafe {
+                let status = RegQueryValueExW(
+                    key,
+                    name.as_ptr(),
+                    std::ptr::null_mut(),
+                    &mut kind,
+                    std::ptr::null_mut(),
+                    &mut size,
+                );
+                if status == ERROR_FILE_NOT_FOUND {
+                    return Ok(None);
+                }
+                check(status)?;
+                let mut data = vec![0_u16; size as usize / 2 + 1];
+                check(RegQueryValueExW(
+                    key,
+                    name.as_ptr(),
+                    std::ptr::null_mut(),
+                    &mut kind,
+                    data.as_mut_ptr() as *mut u8,
+                    &mut size,
+                ))?;
+                data.truncate(size as usize / 2);
+                let end = data.iter().position(|c| *c == 0).unwrap_or(data.len());
+                Ok(Some(String::from_utf16_lossy(&data[..end])))
+            }
+        })
+    }
+
+    pub fn write(name: &str, value: &str) -> Result<(), Error> {
+        let name = wide(name);
+        let value = wide(value);
+        with_run_key(KEY_SET_VALUE, |key| {
+            // SAFETY: the size includes the nul terminator as required for REG_SZ
+            unsafe {
+                check(RegSetValueExW(
+                    key,
+                    name.as_ptr(),
+                    0,
+                    REG_SZ,
+                    value.as_ptr() as *const u8,
+                    (value.len() * 2) as u32,
+                ))
+            }
+        })
+    }
+
+    pub fn delete(name: &str) -> Result<(), Error> {
+        let name = wide(name);
+        with_run_key(KEY_SET_VALUE, |key| {
+            // SAFETY: plain Win32 call, a missing value is no error
+            match unsafe { RegDeleteValueW(key, name.as_ptr()) } {
+                ERROR_FILE_NOT_FOUND => Ok(()),
+                status => check(status),
+            }
+        })
+    }
+}
+
+#[cfg(not(windows))]
+mod registry {
+    use crate::error::Error;
+
+    pub fn read(_name: &str) -> Result<Option<String>, Error> {
+        Ok(None)
+    }
+
+    pub fn write(_name: &str, _value: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    pub fn delete(_name: &str) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::autostart::{command_line, Registration};
+
+    #[test]
+    fn moved_executables_are_stale() {
+        let config = Path::new(r"C:\Cfg\my config.toml");
+        let expected = command_line(Path::new(r"C:\Apps\vjoy manager.exe"), Some(config));
+        assert_eq!(
+            expected,
+            r#""C:\Apps\vjoy manager.exe" --start-minimized --config "C:\Cfg\my config.toml""#
+        );
+        assert_eq!(
+            Registration::from_command(None, &expected),
+            Registration::NotRegistered
+        );
+        assert_eq!(
+            Registration::from_command(Some(&expected), &expected),
+            Registration::Registered
+        );
+
+        let moved = command_line(Path::new(r"D:\Old\vjoy manager.exe"), Some(config));
+        assert!(matches!(
+            Registration::from_command(Some(&moved), &expected),
+            Registration::Stale {
+                exe_moved: true,
+                ..
+            }
+        ));
+        let other_config = command_line(Path::new(r"C:\Apps\vjoy manager.exe"), None);
+        assert!(matches!(
+            Registration::from_command(Some(&other_config), &expected),
+            Registration::Stale {
+                exe_moved: false,
+                ..
+            }
+        ));
+    }
+}
@@ -1,29 +1,187 @@
-use std::path::Path;
+use std::{
+    collections::{btree_map::Entry, BTreeMap, HashSet},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 
+use crate::input::button_labels::ButtonLabelMap;
+use crate::input::device_transform::DeviceTransform;
+use crate::input::normalize_guid;
+use crate::rebind::axis_gain::ButtonSource;
 use crate::rebind::button_to_button::ButtonToButtonModifier;
 use crate::rebind::logical_rebind::LogicalRebind;
-use crate::rebind::merge_axes::MergeAxesModifier;
+use crate::rebind::merge_axes::{AxisSource, MergeAxesModifier};
+use crate::rebind::midi_feedback::MidiFeedback;
+use crate::rebind::mirror::Mirror;
+use crate::rebind::output_smoothing::AxisSmoothing;
+use crate::rebind::overlay::{resolve_override_names, Overlay};
 use crate::rebind::reroute_rebind::RerouteRebind;
 use crate::rebind::two_buttons_to_axis::TwoButtonsToAxisModifier;
 use crate::rebind::virtual_rebind::VirtualRebind;
+use crate::rebind::{axis_neutral::AxisNeutral, mode_presets::ModeAxisPreset};
 use crate::{
     error::Error,
+    paths,
     rebind::{
         axis_to_axis::{AxisParams, AxisToAxisModifier},
         hat_to_hat::HatToHatModifier,
+        rebind_id::RebindId,
+        rebind_viewer::DeviceInfo,
         shift_mode_mask::ShiftModeMask,
         virtual_axis_trim::{VirtualAxisTrimModifier, VirtualAxisTrimParams},
         {Rebind, RebindType},
     },
 };
-use log::info;
-use serde::{Deserialize, Serialize};
+use log::{info, warn};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct Config {
     pub name: String,
     pub default_shift_mode: ShiftModeMask,
     pub rebinds: Vec<Rebind>,
+
+    #[serde(default)]
+    pub axis_smoothing: Vec<AxisSmoothing>,
+
+    /// Neutral values of virtual axes that don't rest centered
+    #[serde(default)]
+    pub axis_neutrals: Vec<AxisNeutral>,
+
+    /// Virtual axis values applied when the shift mode switches into a mode
+    #[serde(default)]
+    pub mode_axis_presets: Vec<ModeAxisPreset>,
+
+    #[serde(default)]
+    pub mirror: Option<Mirror>,
+
+    #[serde(default)]
+    pub overlays: Vec<Overlay>,
+
+    /// MIDI messages sent when the shift mode or virtual buttons change
+    #[serde(default)]
+    pub midi_feedback: Vec<MidiFeedback>,
+
+    /// Device settings found in a profile from before they moved to their own file, taken over
+    /// into the [`DeviceSettings`] when the config is loaded
+    #[serde(skip)]
+    pub legacy_device_settings: DeviceSettings,
+}
+
+/// Machine-local settings of physical devices, stored in `devices.toml` in the config dir and merged with the
+/// loaded profile. Unlike a profile they are not meant to be shared, GUIDs differ between machines.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct DeviceSettings {
+    /// Button labels of physical devices, keyed by GUID
+    #[serde(default)]
+    pub button_labels: BTreeMap<String, ButtonLabelMap>,
+
+    /// Axis corrections of physical devices, keyed by GUID
+    #[serde(default)]
+    pub device_transforms: BTreeMap<String, DeviceTransform>,
+}
+
+impl DeviceSettings {
+    /// Keys the settings had in the single-file config format
+    const LEGACY_KEYS: [&'static str; 2] = ["button_labels", "device_transforms"];
+
+    pub fn default_path() -> Result<PathBuf, Error> {
+        Ok(paths::config_dir()?.join("devices.toml"))
+    }
+
+    pub fn write_to_path(&self, path: &Path) -> Result<(), Error> {
+        paths::write_file(path, &toml::to_string_pretty(self)?)
+    }
+
+    pub fn read_from_path(path: &Path) -> Result<Self, Error> {
+        let string = std::fs::read_to_string(path)?;
+        let mut settings: Self = from_toml_str_checked(&string, |_, _| Ok(()))?;
+        for normalized in settings.normalize_guids() {
+            warn!("Device GUIDs of {normalized} normalized to lowercase without surrounding whitespace");
+        }
+        Ok(settings)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.button_labels.is_empty() && self.device_transforms.is_empty()
+    }
+
+    /// Removes the device settings of the single-file format from a parsed profile
+    fn take_legacy(source: &mut toml::Value) -> Result<Self, Error> {
+        let mut legacy = toml::value::Table::new();
+        if let Some(table) = source.as_table_mut() {
+            for key in Self::LEGACY_KEYS {
+                if let Some(value) = table.remove(key) {
+                    legacy.insert(key.to_string(), value);
+                }
+            }
+        }
+        toml::Value::Table(legacy)
+            .try_into()
+            .map_err(|e| Error::Deserialization { source: e })
+    }
+
+    /// Adds the entries of `other` this has no entry for yet and returns how many were added
+    pub fn merge(&mut self, other: DeviceSettings) -> usize {
+        let mut added = 0;
+        for (guid, labels) in other.button_labels {
+            if let Entry::Vacant(entry) = self.button_labels.entry(guid) {
+                entry.insert(labels);
+                added += 1;
+            }
+        }
+        for (guid, transform) in other.device_transforms {
+            if let Entry::Vacant(entry) = self.device_transforms.entry(guid) {
+                entry.insert(transform);
+                added += 1;
+            }
+        }
+        added
+    }
+
+    /// Normalizes the GUID keys, see [`normalize_guid`], and returns what changed
+    pub fn normalize_guids(&mut self) -> Vec<String> {
+        let mut normalized = Vec::new();
+        if normalize_keys(&mut self.button_labels) {
+            normalized.push("the button labels".to_string());
+        }
+        if normalize_keys(&mut self.device_transforms) {
+            normalized.push("the device transforms".to_string());
+        }
+        normalized
+    }
+}
+
+/// Rebinds that apply regardless of the loaded config, stored in `global.toml` in the config dir.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct GlobalRebinds {
+    #[serde(default)]
+    pub rebinds: Vec<Rebind>,
+}
+
+impl GlobalRebinds {
+    pub fn default_path() -> Result<PathBuf, Error> {
+        Ok(paths::config_dir()?.join("global.toml"))
+    }
+
+    pub fn write_to_path(&self, path: &Path) -> Result<(), Error> {
+        paths::write_file(path, &toml::to_string_pretty(self)?)
+    }
+
+    pub fn read_from_path(path: &Path) -> Result<Self, Error> {
+        let string = std::fs::read_to_string(path)?;
+        let mut global: Self =
+            toml::from_str(&string).map_err(|e| Error::Deserialization { source: e })?;
+        for rebind in global.rebinds.iter_mut() {
+            if normalize_rebind_guids(&mut rebind.rebind_type) {
+                warn!(
+                    "Device GUIDs of global rebind `{}` normalized to lowercase without surrounding whitespace",
+                    rebind.name
+                );
+            }
+        }
+        Ok(global)
+    }
 }
 
 impl Default for Config {
@@ -32,32 +190,163 @@ impl Default for Config {
             name: "Default config".to_string(),
             default_shift_mode: Default::default(),
             rebinds: Default::default(),
+            axis_smoothing: Default::default(),
+            axis_neutrals: Default::default(),
+            mode_axis_presets: Default::default(),
+            mirror: None,
+            overlays: Vec::new(),
+            midi_feedback: Vec::new(),
+            legacy_device_settings: Default::default(),
         }
     }
 }
 
 impl Config {
     pub fn write_to_path(&self, path: &Path) -> Result<(), Error> {
-        let ser_toml = toml::to_string_pretty(&self)?;
+        let ser_toml = self.to_toml_string()?;
         info!("Successfully serialized config file");
-        std::fs::write(path, ser_toml)?;
-
-        Ok(())
+        paths::write_file(path, &ser_toml)
     }
 
     pub fn read_from_path(path: &Path) -> Result<Self, Error> {
         match std::fs::read_to_string(path) {
-            Ok(string) => match toml::from_str(&string) {
-                Ok(config) => {
-                    info!("Successfully deserialized config file");
-                    Ok(config)
-                }
-                Err(e) => Err(Error::Deserialization { source: e }),
-            },
+            Ok(string) => {
+                let config = Self::from_toml_str(&string)?;
+                info!("Successfully deserialized config file");
+                Ok(config)
+            }
             Err(e) => Err(Error::IO { source: e }),
         }
     }
 
+    /// Modification time of the file at `path`, `None` if it does not exist or the platform has none.
+    pub fn modified_time(path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    pub fn to_toml_string(&self) -> Result<String, Error> {
+        Ok(toml::to_string_pretty(&self)?)
+    }
+
+    /// Keys serde would silently ignore, e.g. a misspelled `src_buton`, are an error naming the key.
+    /// `deny_unknown_fields` does not work with the flattened rebind types, so the keys are
+    /// compared against the config serialized again instead.
+    ///
+    /// Device settings of the single-file format end up in [`Config::legacy_device_settings`].
+    pub fn from_toml_str(string: &str) -> Result<Self, Error> {
+        let mut config: Self = from_toml_str_checked(string, |config: &mut Self, source| {
+            config.legacy_device_settings = DeviceSettings::take_legacy(source)?;
+            Ok(())
+        })?;
+        for normalized in config.normalize_guids() {
+            warn!("Device GUIDs of {normalized} normalized to lowercase without surrounding whitespace");
+        }
+        for name in config.make_rebind_ids_unique() {
+            warn!("Rebind `{name}` had the id of another rebind and got a new one");
+        }
+        resolve_override_names(&mut config.overlays, &config.rebinds);
+        Ok(config)
+    }
+
+    /// Gives every rebind that repeats the id of an earlier one a new id, e.g. after copying
+    /// rebinds between config files by hand. Returns the names of the changed rebinds.
+    pub fn make_rebind_ids_unique(&mut self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        let mut changed = Vec::new();
+        for rebind in self.rebinds.iter_mut() {
+            if !seen.insert(rebind.id) {
+                rebind.id = RebindId::new();
+                changed.push(rebind.name.clone());
+            }
+        }
+        changed
+    }
+
+    /// Lowercases and trims every physical device GUID, see [`normalize_guid`]. Returns what
+    /// changed, one entry per rebind.
+    pub fn normalize_guids(&mut self) -> Vec<String> {
+        let mut normalized = Vec::new();
+        for rebind in self.rebinds.iter_mut() {
+            if normalize_rebind_guids(&mut rebind.rebind_type) {
+                normalized.push(format!("rebind `{}`", rebind.name));
+            }
+        }
+        for overlay in self.overlays.iter_mut() {
+            for (index, override_) in overlay.overrides.iter_mut().enumerate() {
+                if normalize_rebind_guids(&mut override_.rebind_type) {
+                    normalized.push(format!("overlay `{}` override {}", overlay.name, index + 1));
+                }
+            }
+        }
+        if let Some(mirror) = self.mirror.as_mut() {
+            if normalize_in_place(&mut mirror.src_guid) {
+                normalized.push("the mirror".to_string());
+            }
+        }
+        normalized.extend(self.legacy_device_settings.normalize_guids());
+        normalized
+    }
+
+    /// Routes every button, axis and hat of `src` 1:1 to the virtual device `dst_device`, as far as
+    /// `dst` has them.
+    pub fn passthrough(
+        src_device: &str,
+        src: &DeviceInfo,
+        dst_device: u32,
+        dst: &DeviceInfo,
+    ) -> Self {
+        let rebind = |name: String, rebind: RerouteRebind| {
+            Rebind::new(
+                name,
+                ShiftModeMask(0b00000000),
+                RebindType::Reroute { rebind },
+            )
+        };
+
+        let buttons = (1..=src.num_buttons.min(dst.num_buttons) as u32).map(|i| {
+            rebind(
+                format!("Button_{}_To_{}", i, i),
+                RerouteRebind::ButtonToButton {
+                    src: ButtonSource::physical(src_device.to_string(), i),
+                    dst_device,
+                    dst_button: i,
+                    modifier: ButtonToButtonModifier::Simple,
+                },
+            )
+        });
+        let hats = (1..=src.num_hats.min(dst.num_hats) as u32).map(|i| {
+            rebind(
+                format!("Hat_{}_To_{}", i, i),
+                RerouteRebind::HatToHat {
+                    src_device: src_device.to_string(),
+                    src_hat: i,
+                    dst_device,
+                    dst_hat: i,
+                    modifier: HatToHatModifier::default(),
+                },
+            )
+        });
+        let axes = (1..=src.num_axes.min(dst.num_axes) as u32).map(|i| {
+            rebind(
+                format!("Axis_{}_To_{}", i, i),
+                RerouteRebind::AxisToAxis {
+                    src: AxisSource::physical(src_device.to_string(), i),
+                    dst_device,
+                    dst_axis: i,
+                    modifier: AxisToAxisModifier::default(),
+                    freeze: None,
+                    gain: None,
+                },
+            )
+        });
+
+        Self {
+            name: format!("{} passthrough", src.name),
+            rebinds: buttons.chain(hats).chain(axes).collect(),
+            ..Default::default()
+        }
+    }
+
     pub fn read_from_path_or_default(path: &Path) -> Self {
         match Self::read_from_path(path) {
             Ok(config) => config,
@@ -69,94 +358,98 @@ impl Config {
         let guid = "030003f05e0400008e02000000007200".to_string();
         let mut rebinds = Vec::new();
 
-        rebinds.push(Rebind {
-            name: "Enable_Shift_0b10000000".to_string(),
-            mode_mask: ShiftModeMask(0b00000000),
-            rebind_type: RebindType::Logical {
+        rebinds.push(Rebind::new(
+            "Enable_Shift_0b10000000".to_string(),
+            ShiftModeMask(0b00000000),
+            RebindType::Logical {
                 rebind: LogicalRebind::MomentaryEnableShiftMode {
                     src_device: guid.clone(),
                     src_button: 1,
                     shift_mask: ShiftModeMask(0b00000001),
                 },
             },
-        });
+        ));
 
         let mut buttons: Vec<Rebind> = (2..=10)
-            .map(|i| Rebind {
-                name: format!("Button_{}_To_{}", i, i),
-                mode_mask: ShiftModeMask(0b00000000),
-                rebind_type: RebindType::Reroute {
-                    rebind: RerouteRebind::ButtonToButton {
-                        src_device: guid.clone(),
-                        src_button: i,
-                        dst_device: 1,
-                        dst_button: i,
-                        modifier: ButtonToButtonModifier::Simple,
+            .map(|i| {
+                Rebind::new(
+                    format!("Button_{}_To_{}", i, i),
+                    ShiftModeMask(0b00000000),
+                    RebindType::Reroute {
+                        rebind: RerouteRebind::ButtonToButton {
+                            src: ButtonSource::physical(guid.clone(), i),
+                            dst_device: 1,
+                            dst_button: i,
+                            modifier: ButtonToButtonModifier::Simple,
+                        },
                     },
-                },
+                )
             })
             .collect();
 
         let mut hats: Vec<Rebind> = (1..=1)
-            .map(|i| Rebind {
-                name: format!("Hat_{}_To_{}", i, i),
-                mode_mask: ShiftModeMask(0b00000000),
-                rebind_type: RebindType::Reroute {
-                    rebind: RerouteRebind::HatToHat {
-                        src_device: guid.clone(),
-                        src_hat: i,
-                        dst_device: 1,
-                        dst_hat: i,
-                        modifier: HatToHatModifier::Simple,
+            .map(|i| {
+                Rebind::new(
+                    format!("Hat_{}_To_{}", i, i),
+                    ShiftModeMask(0b00000000),
+                    RebindType::Reroute {
+                        rebind: RerouteRebind::HatToHat {
+                            src_device: guid.clone(),
+                            src_hat: i,
+                            dst_device: 1,
+                            dst_hat: i,
+                            modifier: HatToHatModifier::default(),
+                        },
                     },
-                },
+                )
             })
             .collect();
 
         let axis_params = AxisParams::new(0.00, 0.00, 1.0, false, 2.0, 0.0, 1);
 
         let mut axes: Vec<Rebind> = (1..=6)
-            .map(|i| Rebind {
-                name: format!("Axis_{}_To_{}", i, i),
-                mode_mask: ShiftModeMask(0b00000000),
-                rebind_type: RebindType::Reroute {
-                    rebind: RerouteRebind::AxisToAxis {
-                        src_device: guid.clone(),
-                        src_axis: i,
-                        dst_device: 1,
-                        dst_axis: i,
-                        modifier: AxisToAxisModifier::Parameterized {
-                            params: axis_params.clone(),
+            .map(|i| {
+                Rebind::new(
+                    format!("Axis_{}_To_{}", i, i),
+                    ShiftModeMask(0b00000000),
+                    RebindType::Reroute {
+                        rebind: RerouteRebind::AxisToAxis {
+                            src: AxisSource::physical(guid.clone(), i),
+                            dst_device: 1,
+                            dst_axis: i,
+                            modifier: AxisToAxisModifier::Parameterized {
+                                params: axis_params.clone(),
+                            },
+                            freeze: None,
+                            gain: None,
                         },
                     },
-                },
+                )
             })
             .collect();
 
-        axes.push(Rebind {
-            name: format!("Merge_Axes_{}_And_{}_To_{}", 1, 2, 9),
-            mode_mask: ShiftModeMask(0b00000000),
-            rebind_type: RebindType::Reroute {
+        axes.push(Rebind::new(
+            format!("Merge_Axes_{}_And_{}_To_{}", 1, 2, 9),
+            ShiftModeMask(0b00000000),
+            RebindType::Reroute {
                 rebind: RerouteRebind::MergeAxes {
-                    src_0_device: guid.clone(),
-                    src_0_axis: 1,
-                    src_1_device: guid.clone(),
-                    src_1_axis: 2,
+                    src_0: AxisSource::physical(guid.clone(), 1),
+                    src_1: AxisSource::physical(guid.clone(), 2),
                     dst_device: 1,
                     dst_axis: 9,
                     modifier: MergeAxesModifier::Add,
                 },
             },
-        });
+        ));
 
         rebinds.append(&mut buttons);
         rebinds.append(&mut hats);
         rebinds.append(&mut axes);
 
-        rebinds.push(Rebind {
-            name: "Buttons_3_4_To_Axis_10".to_owned(),
-            mode_mask: ShiftModeMask(0b00000000),
-            rebind_type: RebindType::Reroute {
+        rebinds.push(Rebind::new(
+            "Buttons_3_4_To_Axis_10".to_owned(),
+            ShiftModeMask(0b00000000),
+            RebindType::Reroute {
                 rebind: RerouteRebind::TwoButtonsToAxis {
                     src_neg_device: guid.clone(),
                     src_neg_button: 3,
@@ -170,12 +463,12 @@ impl Config {
                     },
                 },
             },
-        });
+        ));
 
-        let virtual_axis_1_trim = Rebind {
-            name: "Virtual_Axis_1_Button_Trim".to_owned(),
-            mode_mask: ShiftModeMask(0b00000000),
-            rebind_type: RebindType::Virtual {
+        let virtual_axis_1_trim = Rebind::new(
+            "Virtual_Axis_1_Button_Trim".to_owned(),
+            ShiftModeMask(0b00000000),
+            RebindType::Virtual {
                 rebind: VirtualRebind::VirtualAxisApplyButtonTrim {
                     axis_device: 1,
                     axis: 1,
@@ -189,33 +482,624 @@ impl Config {
                         params: VirtualAxisTrimParams::new(0.05),
                     },
                 },
+                phase: Default::default(),
             },
-        };
+        );
         rebinds.push(virtual_axis_1_trim);
 
         Config {
             name: "Default Config".to_string(),
             default_shift_mode: ShiftModeMask(0b00000000),
             rebinds,
+            axis_smoothing: Vec::new(),
+            axis_neutrals: Vec::new(),
+            mode_axis_presets: Vec::new(),
+            mirror: None,
+            overlays: Vec::new(),
+            midi_feedback: Vec::new(),
+            legacy_device_settings: Default::default(),
         }
     }
 }
 
+/// Normalizes `guid` and returns whether it changed
+fn normalize_in_place(guid: &mut String) -> bool {
+    let normalized = normalize_guid(guid);
+    let changed = *guid != normalized;
+    *guid = normalized;
+    changed
+}
+
+fn normalize_rebind_guids(rebind_type: &mut RebindType) -> bool {
+    rebind_type
+        .physical_devices_mut()
+        .into_iter()
+        .fold(false, |changed, guid| normalize_in_place(guid) | changed)
+}
+
+fn normalize_keys<T>(map: &mut BTreeMap<String, T>) -> bool {
+    if map.keys().all(|guid| *guid == normalize_guid(guid)) {
+        return false;
+    }
+    *map = std::mem::take(map)
+        .into_iter()
+        .map(|(guid, value)| (normalize_guid(&guid), value))
+        .collect();
+    true
+}
+
+/// Parses `string` and fails with [`Error::UnknownConfigKey`] on keys serde ignored.
+/// `take` runs before the check and may move parts of the source out of it.
+fn from_toml_str_checked<T: DeserializeOwned + Serialize>(
+    string: &str,
+    take: impl FnOnce(&mut T, &mut toml::Value) -> Result<(), Error>,
+) -> Result<T, Error> {
+    let mut parsed: T = toml::from_str(string).map_err(|e| Error::Deserialization { source: e })?;
+    let mut source: toml::Value =
+        toml::from_str(string).map_err(|e| Error::Deserialization { source: e })?;
+    take(&mut parsed, &mut source)?;
+    let known = toml::Value::try_from(&parsed)?;
+    match unknown_key(&source, &known, String::new()) {
+        Some(key) => Err(Error::UnknownConfigKey(key)),
+        None => Ok(parsed),
+    }
+}
+
+/// Path of the first key in `source` that `known` lacks, like `rebinds[2].src_buton`
+fn unknown_key(source: &toml::Value, known: &toml::Value, path: String) -> Option<String> {
+    match (source, known) {
+        (toml::Value::Table(source), toml::Value::Table(known)) => {
+            source.iter().find_map(|(key, value)| {
+                let path = match path.is_empty() {
+                    true => key.clone(),
+                    false => format!("{path}.{key}"),
+                };
+                match known.get(key) {
+                    Some(known) => unknown_key(value, known, path),
+                    None => Some(path),
+                }
+            })
+        }
+        (toml::Value::Array(source), toml::Value::Array(known)) => source
+            .iter()
+            .zip(known.iter())
+            .enumerate()
+            .find_map(|(index, (value, known))| {
+                unknown_key(value, known, format!("{path}[{index}]"))
+            }),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::config::Config;
-    use std::path::Path;
+    use crate::{
+        config::{Config, DeviceSettings},
+        input::{
+            button_labels::ButtonLabelMap,
+            device_transform::{AxisCalibration, AxisTransform, DeviceTransform},
+        },
+        rebind::{
+            axis_gain::{AxisGain, ButtonSource},
+            axis_neutral::AxisNeutral,
+            axis_to_axis::{AxisFreeze, AxisToAxisModifier},
+            axis_to_button::AxisToButtonModifier,
+            button_to_button::ButtonToButtonModifier,
+            four_buttons_to_hat::FourButtonsToHatModifier,
+            hat_to_hat::HatToHatModifier,
+            keyboard_rebind::{ButtonToKeyModifier, Key, KeyboardRebind},
+            logical_rebind::LogicalRebind,
+            merge_axes::{AxisSource, MergeAxesModifier},
+            multi_action::MacroAction,
+            overlay::{Overlay, OverlayOverride},
+            rebind_viewer::DeviceInfo,
+            reroute_rebind::RerouteRebind,
+            two_buttons_to_axis::TwoButtonsToAxisModifier,
+            virtual_axis_trim::VirtualAxisTrimModifier,
+            virtual_rebind::{ProcessingPhase, VirtualRebind},
+            Rebind, RebindType,
+        },
+    };
+    use std::collections::BTreeMap;
+    use strum::IntoEnumIterator;
+
+    /// Every rebind type, modifier and macro action, with arbitrary non-default ids
+    fn every_rebind_variant() -> Vec<RebindType> {
+        let reroute = |rebind| RebindType::Reroute { rebind };
+        let mut types: Vec<RebindType> = LogicalRebind::iter()
+            .map(|rebind| RebindType::Logical { rebind })
+            .chain(RerouteRebind::iter().map(reroute))
+            .chain(VirtualRebind::iter().map(|rebind| RebindType::Virtual {
+                rebind,
+                phase: Default::default(),
+            }))
+            .chain(KeyboardRebind::iter().map(|rebind| RebindType::Keyboard { rebind }))
+            .collect();
+
+        types.extend(ButtonToButtonModifier::iter().map(|modifier| {
+            reroute(RerouteRebind::ButtonToButton {
+                src: ButtonSource::physical("stick".to_string(), 2),
+                dst_device: 1,
+                dst_button: 3,
+                modifier,
+            })
+        }));
+        types.push(reroute(RerouteRebind::ButtonToButton {
+            src: ButtonSource::Virtual {
+                device: 2,
+                button: 7,
+            },
+            dst_device: 1,
+            dst_button: 3,
+            modifier: Default::default(),
+        }));
+        types.extend(TwoButtonsToAxisModifier::iter().map(|modifier| {
+            reroute(RerouteRebind::TwoButtonsToAxis {
+                src_neg_device: "stick".to_string(),
+                src_neg_button: 1,
+                src_pos_device: "stick".to_string(),
+                src_pos_button: 2,
+                dst_device: 1,
+                dst_axis: 2,
+                modifier,
+            })
+        }));
+        types.extend(
+            ButtonToKeyModifier::iter().map(|modifier| RebindType::Keyboard {
+                rebind: KeyboardRebind::ButtonToKey {
+                    src_device: "stick".to_string(),
+                    src_button: 4,
+                    key: Key::NumpadEnter,
+                    ctrl: true,
+                    shift: true,
+                    alt: false,
+                    modifier,
+                },
+            }),
+        );
+        types.extend(HatToHatModifier::iter().map(|modifier| {
+            reroute(RerouteRebind::HatToHat {
+                src_device: "stick".to_string(),
+                src_hat: 1,
+                dst_device: 1,
+                dst_hat: 1,
+                modifier,
+            })
+        }));
+        types.push(reroute(RerouteRebind::HatToHat {
+            src_device: "stick".to_string(),
+            src_hat: 1,
+            dst_device: 1,
+            dst_hat: 1,
+            modifier: HatToHatModifier::Simple {
+                hysteresis: 25.0,
+                reported: None,
+            },
+        }));
+        types.extend(FourButtonsToHatModifier::iter().map(|modifier| {
+            reroute(RerouteRebind::FourButtonsToHat {
+                src_device: "stick".to_string(),
+                src_button_north: 5,
+                src_button_east: 6,
+                src_button_south: 7,
+                src_button_west: 8,
+                dst_device: 1,
+                dst_hat: 2,
+                modifier,
+            })
+        }));
+        types.extend(
+            AxisToButtonModifier::iter()
+                .chain([AxisToButtonModifier::Detents {
+                    low: -0.75,
+                    high: 0.5,
+                    hysteresis: 0.125,
+                    high_button: 6,
+                    low_pressed: false,
+                    high_pressed: false,
+                }])
+                .map(|modifier| {
+                    reroute(RerouteRebind::AxisToButton {
+                        src_device: "stick".to_string(),
+                        src_axis: 3,
+                        dst_device: 1,
+                        dst_button: 5,
+                        modifier,
+                    })
+                }),
+        );
+        types.extend(AxisToAxisModifier::iter().map(|modifier| {
+            reroute(RerouteRebind::AxisToAxis {
+                src: AxisSource::physical("stick".to_string(), 1),
+                dst_device: 1,
+                dst_axis: 1,
+                modifier,
+                freeze: Some(AxisFreeze::default()),
+                gain: Some(AxisGain::new(
+                    ButtonSource::Virtual {
+                        device: 1,
+                        button: 5,
+                    },
+                    0.5,
+                    0.3,
+                )),
+            })
+        }));
+        types.push(reroute(RerouteRebind::AxisToAxis {
+            src: AxisSource::Virtual { device: 1, axis: 4 },
+            dst_device: 2,
+            dst_axis: 4,
+            modifier: Default::default(),
+            freeze: None,
+            gain: None,
+        }));
+        types.extend(MergeAxesModifier::iter().map(|modifier| {
+            reroute(RerouteRebind::MergeAxes {
+                src_0: AxisSource::Physical {
+                    device: "stick".to_string(),
+                    axis: 1,
+                },
+                src_1: AxisSource::Virtual { device: 1, axis: 2 },
+                dst_device: 1,
+                dst_axis: 3,
+                modifier,
+            })
+        }));
+        types.push(reroute(RerouteRebind::MultiAction {
+            src_device: "stick".to_string(),
+            src_button: 4,
+            actions: MacroAction::iter().collect(),
+            state: Default::default(),
+        }));
+        types.extend(
+            VirtualAxisTrimModifier::iter().map(|modifier| RebindType::Virtual {
+                rebind: VirtualRebind::VirtualAxisApplyButtonTrim {
+                    axis_device: 1,
+                    axis: 1,
+                    trim_neg_device: 1,
+                    trim_neg_button: 1,
+                    trim_pos_device: 1,
+                    trim_pos_button: 2,
+                    trim_reset_device: 1,
+                    trim_reset_button: 3,
+                    modifier,
+                },
+                phase: ProcessingPhase::PreReroute,
+            }),
+        );
+        types
+    }
 
     #[test]
-    #[allow(unused_must_use)]
     fn default_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("default_config.toml");
         let config = Config::debug_xbox360_config();
-        config
-            .write_to_path(Path::new("Cfg/test_config.toml"))
-            .unwrap();
+        config.write_to_path(&path).unwrap();
 
-        let config_readback = Config::read_from_path(Path::new("Cfg/test_config.toml")).unwrap();
+        let config_readback = Config::read_from_path(&path).unwrap();
 
         assert_eq!(config, config_readback);
     }
+
+    #[test]
+    fn every_rebind_variant_round_trips() {
+        for rebind_type in every_rebind_variant() {
+            let config = Config {
+                rebinds: vec![Rebind::new(
+                    rebind_type.as_ref().to_string(),
+                    Default::default(),
+                    rebind_type,
+                )],
+                ..Default::default()
+            };
+            let toml = config
+                .to_toml_string()
+                .unwrap_or_else(|e| panic!("{:?}: {e}", config.rebinds[0].rebind_type));
+            let readback = Config::from_toml_str(&toml).unwrap_or_else(|e| panic!("{toml}\n{e}"));
+            assert_eq!(readback, config, "{toml}");
+        }
+    }
+
+    #[test]
+    fn axis_neutrals_round_trip() {
+        let config = Config {
+            axis_neutrals: vec![AxisNeutral::new(1, 3, 0), AxisNeutral::new(2, 1, 32767)],
+            ..Default::default()
+        };
+        let toml = config.to_toml_string().unwrap();
+        assert_eq!(Config::from_toml_str(&toml).unwrap(), config);
+
+        // Configs from before axis neutrals existed center all axes
+        let mut old: toml::Value = toml::from_str(&toml).unwrap();
+        old.as_table_mut().unwrap().remove("axis_neutrals");
+        let old = toml::to_string(&old).unwrap();
+        assert!(Config::from_toml_str(&old)
+            .unwrap()
+            .axis_neutrals
+            .is_empty());
+    }
+
+    #[test]
+    fn flat_reroute_sources_are_physical_unless_virtual() {
+        // Configs from before virtual sources only have the flat physical source keys
+        let config = Config::debug_xbox360_config();
+        let mut value = toml::Value::try_from(&config).unwrap();
+        let rebind = value["rebinds"][1].as_table_mut().unwrap();
+        assert!(rebind.contains_key("src_device"));
+        assert!(rebind.contains_key("src_button"));
+        assert_eq!(
+            Config::from_toml_str(&toml::to_string(&value).unwrap()).unwrap(),
+            config
+        );
+
+        let rebind = value["rebinds"][1].as_table_mut().unwrap();
+        rebind.remove("src_device");
+        rebind.insert("src_virtual_device".to_string(), toml::Value::Integer(2));
+        let readback = Config::from_toml_str(&toml::to_string(&value).unwrap()).unwrap();
+        let RebindType::Reroute {
+            rebind: RerouteRebind::ButtonToButton { src, .. },
+        } = &readback.rebinds[1].rebind_type
+        else {
+            panic!("expected a button to button rebind");
+        };
+        assert_eq!(
+            *src,
+            ButtonSource::Virtual {
+                device: 2,
+                button: 2
+            }
+        );
+    }
+
+    #[test]
+    fn merge_axes_loads_flat_sources() {
+        // Merge axes rebinds from before virtual sources have flat keys per source
+        let toml = r#"
+            name = "Old"
+            default_shift_mode = 0
+
+            [[rebinds]]
+            name = "Merge"
+            mode_mask = 0
+            rebind_type = "Reroute"
+            variant = "MergeAxes"
+            src_0_device = "stick"
+            src_0_axis = 1
+            src_1_device = "throttle"
+            src_1_axis = 2
+            dst_device = 1
+            dst_axis = 9
+            modifier = "Add"
+        "#;
+        let config = Config::from_toml_str(toml).unwrap();
+        let RebindType::Reroute {
+            rebind: RerouteRebind::MergeAxes { src_0, src_1, .. },
+        } = &config.rebinds[0].rebind_type
+        else {
+            panic!("expected a merge axes rebind");
+        };
+        assert_eq!(*src_0, AxisSource::physical("stick".to_string(), 1));
+        assert_eq!(*src_1, AxisSource::physical("throttle".to_string(), 2));
+
+        let toml = config.to_toml_string().unwrap();
+        assert!(toml.contains("src_0_device = \"stick\""), "{toml}");
+        assert_eq!(Config::from_toml_str(&toml).unwrap(), config);
+    }
+
+    #[test]
+    fn overrides_by_name_load_as_ids() {
+        let mut config = Config::debug_xbox360_config();
+        let rebind = &config.rebinds[1];
+        config.overlays.push(Overlay {
+            name: "Alt".to_string(),
+            overrides: vec![OverlayOverride {
+                rebind_id: None,
+                rebind_name: rebind.name.clone(),
+                rebind_type: rebind.rebind_type.clone(),
+                resolved: Default::default(),
+            }],
+        });
+        let toml = config.to_toml_string().unwrap();
+        assert!(!toml.contains("rebind_id"));
+
+        let readback = Config::from_toml_str(&toml).unwrap();
+        let override_ = &readback.overlays[0].overrides[0];
+        assert_eq!(override_.rebind_id, Some(config.rebinds[1].id));
+        assert!(override_.rebind_name.is_empty());
+        let toml = readback.to_toml_string().unwrap();
+        assert_eq!(Config::from_toml_str(&toml).unwrap(), readback);
+    }
+
+    #[test]
+    fn repeated_rebind_ids_are_replaced_on_load() {
+        let mut config = Config::debug_xbox360_config();
+        config.rebinds[2].id = config.rebinds[0].id;
+        let readback = Config::from_toml_str(&config.to_toml_string().unwrap()).unwrap();
+        assert_eq!(readback.rebinds[0].id, config.rebinds[0].id);
+        assert_ne!(readback.rebinds[2].id, config.rebinds[0].id);
+    }
+
+    #[test]
+    fn multi_line_notes_round_trip() {
+        let mut config = Config::debug_xbox360_config();
+        let toml = config.to_toml_string().unwrap();
+        assert!(!toml.contains("notes"));
+
+        config.rebinds[0].notes = Some("Matches the UFC\nUpdate the kneeboard too".to_string());
+        let toml = config.to_toml_string().unwrap();
+        assert_eq!(Config::from_toml_str(&toml).unwrap(), config);
+    }
+
+    #[test]
+    fn adversarial_rebind_names_round_trip() {
+        let long = "ä".repeat(1000);
+        let names = [
+            "He said \"hi\"",
+            "it's",
+            "'''",
+            "\"\"\"",
+            "ends with a quote\"",
+            "multi\nline\r\nname\n",
+            "'''\n\"\"\"\n'",
+            "tab\tbell\u{7}nul\u{0}",
+            "back\\slash \\n",
+            "🛩️ 👨‍👩‍👧 e\u{301} שלום",
+            "# [rebinds] = 1",
+            "",
+            &long,
+        ];
+        let mut config = Config::debug_xbox360_config();
+        config.name = names[6].to_string();
+        let template = config.rebinds[0].clone();
+        // Every name twice, duplicates are no keys and keep their order
+        config.rebinds = names
+            .iter()
+            .chain(names.iter())
+            .map(|name| Rebind {
+                name: name.to_string(),
+                notes: Some(name.to_string()).filter(|n| !n.is_empty()),
+                ..template.clone()
+            })
+            .collect();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("adversarial_names.toml");
+        config.write_to_path(&path).unwrap();
+        let readback = Config::read_from_path(&path);
+        assert_eq!(readback.unwrap(), config);
+
+        let toml = toml::to_string(&config).unwrap();
+        assert_eq!(Config::from_toml_str(&toml).unwrap(), config);
+    }
+
+    #[test]
+    fn unknown_key_is_named_in_the_error() {
+        let config = Config::debug_xbox360_config();
+        let toml = config.to_toml_string().unwrap().replacen(
+            "src_button = 1",
+            "src_button = 1\nsrc_buton = 2",
+            1,
+        );
+
+        let error = Config::from_toml_str(&toml).unwrap_err();
+        assert!(
+            error.to_string().contains("`rebinds[0].src_buton`"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn mixed_case_guids_load_normalized() {
+        let mut config = Config::debug_xbox360_config();
+        config.rebinds[0].name = "First".to_string();
+        for guid in config.rebinds[0].rebind_type.physical_devices_mut() {
+            *guid = format!(" {} ", guid.to_uppercase());
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mixed_case_guids.toml");
+        config.write_to_path(&path).unwrap();
+
+        let mut readback = Config::read_from_path(&path).unwrap();
+
+        assert_eq!(readback.normalize_guids(), Vec::<String>::new());
+        assert_eq!(config.normalize_guids(), ["rebind `First`"]);
+        assert_eq!(readback, config);
+    }
+
+    fn device_settings() -> DeviceSettings {
+        let mut labels = ButtonLabelMap::default();
+        labels.set(1, "Trigger");
+        DeviceSettings {
+            button_labels: BTreeMap::from([("AbC0".to_string(), labels)]),
+            device_transforms: BTreeMap::from([(
+                "def1".to_string(),
+                DeviceTransform {
+                    device_name: "Pedals".to_string(),
+                    axes: vec![AxisTransform {
+                        source: 1,
+                        invert: true,
+                        calibration: Some(AxisCalibration {
+                            min: -31000,
+                            center: -31000,
+                            max: 30500,
+                        }),
+                    }],
+                },
+            )]),
+        }
+    }
+
+    #[test]
+    fn device_settings_round_trip_normalized() {
+        let settings = device_settings();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("devices.toml");
+        settings.write_to_path(&path).unwrap();
+        let readback = DeviceSettings::read_from_path(&path).unwrap();
+
+        let mut expected = settings;
+        assert_eq!(expected.normalize_guids(), ["the button labels"]);
+        assert_eq!(readback, expected);
+    }
+
+    #[test]
+    fn legacy_device_settings_move_out_of_the_profile() {
+        let config = Config::debug_xbox360_config();
+        let mut legacy = toml::Value::try_from(&config).unwrap();
+        let settings = toml::Value::try_from(device_settings()).unwrap();
+        for (key, value) in settings.as_table().unwrap() {
+            legacy
+                .as_table_mut()
+                .unwrap()
+                .insert(key.clone(), value.clone());
+        }
+
+        let mut readback = Config::from_toml_str(&toml::to_string(&legacy).unwrap()).unwrap();
+        let mut expected = device_settings();
+        expected.normalize_guids();
+        assert_eq!(readback.legacy_device_settings, expected);
+
+        let profile = readback.to_toml_string().unwrap();
+        assert!(!profile.contains("button_labels") && !profile.contains("device_transforms"));
+        readback.legacy_device_settings = Default::default();
+        assert_eq!(readback, config);
+
+        // Settings already in the device settings file win over the migrated ones
+        let mut current = DeviceSettings::default();
+        current
+            .button_labels
+            .insert("abc0".to_string(), ButtonLabelMap::default());
+        assert_eq!(current.merge(expected), 1);
+        assert_eq!(current.button_labels["abc0"], ButtonLabelMap::default());
+        assert!(current.device_transforms.contains_key("def1"));
+    }
+
+    #[test]
+    fn passthrough_is_limited_by_the_virtual_device() {
+        let device = |num_buttons, num_axes, num_hats| DeviceInfo {
+            name: "Stick".to_string(),
+            num_buttons,
+            num_axes,
+            num_hats,
+            axis_kinds: Vec::new(),
+            axis_names: Vec::new(),
+            button_labels: Default::default(),
+        };
+        let config = Config::passthrough("guid", &device(12, 4, 1), 1, &device(8, 8, 0));
+
+        let count = |prefix| {
+            config
+                .rebinds
+                .iter()
+                .filter(|r| r.name.starts_with(prefix))
+                .count()
+        };
+        assert_eq!((count("Button"), count("Axis"), count("Hat")), (8, 4, 0));
+        assert!(config
+            .rebinds
+            .iter()
+            .all(|r| matches!(r.rebind_type, RebindType::Reroute { .. })));
+        assert_eq!(config.name, "Stick passthrough");
+    }
 }
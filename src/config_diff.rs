@@ -0,0 +1,243 @@
+//! Differences between the live config and a config about to replace it.
+//!
+//! Rebind ids are generated anew for configs from before ids and take no part in the comparison,
+//! so rebinds are matched in passes: identical rebinds first, wherever they are in the list, then
+//! by name, then renamed rebinds by their unchanged content. Runtime state is not serialized and
+//! takes no part in the comparison either.
+//!
+//! The live side of a diff is kept by value, the live config can change while the diff is shown.
+
+use crate::{
+    config::Config,
+    rebind::{rebind_id::RebindId, Rebind},
+};
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ConfigDiff {
+    /// Rebinds only in the live config, lost by loading, as they were when diffing
+    pub removed: Vec<Rebind>,
+    /// Rebinds only in the loaded config. Indices into the loaded config.
+    pub added: Vec<usize>,
+    /// Same name, different settings. Indices into the loaded config.
+    pub changed: Vec<usize>,
+    /// Same settings, different name, as (live name, index into the loaded config)
+    pub renamed: Vec<(String, usize)>,
+    /// The rebinds present in both are processed in a different order
+    pub reordered: bool,
+    /// Anything besides the rebinds differs, e.g. smoothing, labels or overlays
+    pub settings_changed: bool,
+}
+
+impl ConfigDiff {
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty()
+            && self.added.is_empty()
+            && self.changed.is_empty()
+            && self.renamed.is_empty()
+            && !self.reordered
+            && !self.settings_changed
+    }
+}
+
+/// Serialized form, which leaves out runtime state and the id
+fn content(rebind: &Rebind) -> Option<String> {
+    toml::to_string(&Rebind {
+        id: RebindId(0),
+        ..rebind.clone()
+    })
+    .ok()
+}
+
+fn same_content(a: &Rebind, b: &Rebind) -> bool {
+    match (content(a), content(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn same_content_ignoring_name(a: &Rebind, b: &Rebind) -> bool {
+    let renamed = Rebind {
+        name: b.name.clone(),
+        ..a.clone()
+    };
+    same_content(&renamed, b)
+}
+
+/// Serialized form without the rebinds. Overrides refer to their rebind by its name.
+fn settings(config: &Config) -> Option<String> {
+    let mut settings = Config {
+        rebinds: Vec::new(),
+        ..config.clone()
+    };
+    for override_ in settings
+        .overlays
+        .iter_mut()
+        .flat_map(|overlay| overlay.overrides.iter_mut())
+    {
+        if let Some(id) = override_.rebind_id.take() {
+            if let Some(rebind) = config.rebinds.iter().find(|rebind| rebind.id == id) {
+                override_.rebind_name = rebind.name.clone();
+            }
+        }
+    }
+    settings.to_toml_string().ok()
+}
+
+/// Changes from `live` to `loaded`
+pub fn diff_configs(live: &Config, loaded: &Config) -> ConfigDiff {
+    let mut live_matched: Vec<Option<usize>> = vec![None; live.rebinds.len()];
+    let mut loaded_taken = vec![false; loaded.rebinds.len()];
+    let mut changed = Vec::new();
+    let mut renamed = Vec::new();
+    let mut diff = ConfigDiff::default();
+
+    let passes: [fn(&Rebind, &Rebind) -> bool; 3] = [
+        same_content,
+        |a, b| a.name == b.name,
+        same_content_ignoring_name,
+    ];
+    for (pass, matches) in passes.iter().enumerate() {
+        for (live_index, rebind) in live.rebinds.iter().enumerate() {
+            if live_matched[live_index].is_some() {
+                continue;
+            }
+            let found = loaded
+                .rebinds
+                .iter()
+                .enumerate()
+                .find(|(index, other)| !loaded_taken[*index] && matches(rebind, other));
+            if let Some((loaded_index, _)) = found {
+                live_matched[live_index] = Some(loaded_index);
+                loaded_taken[loaded_index] = true;
+                match pass {
+                    0 => (),
+                    1 => changed.push((live_index, loaded_index)),
+                    _ => renamed.push((live_index, loaded_index)),
+                }
+            }
+        }
+    }
+
+    changed.sort();
+    renamed.sort();
+    diff.changed = changed.into_iter().map(|(_, loaded)| loaded).collect();
+    diff.renamed = renamed
+        .into_iter()
+        .map(|(live_index, loaded)| (live.rebinds[live_index].name.clone(), loaded))
+        .collect();
+    diff.removed = live
+        .rebinds
+        .iter()
+        .zip(&live_matched)
+        .filter(|(_, matched)| matched.is_none())
+        .map(|(rebind, _)| rebind.clone())
+        .collect();
+    diff.added = (0..loaded.rebinds.len())
+        .filter(|index| !loaded_taken[*index])
+        .collect();
+    let order: Vec<usize> = live_matched.iter().flatten().copied().collect();
+    diff.reordered = order.windows(2).any(|pair| pair[0] > pair[1]);
+    diff.settings_changed = settings(live) != settings(loaded);
+
+    diff
+}
+
+/// `loaded` with the rebinds only the live config had appended, so local additions survive the load
+pub fn merge_configs(loaded: &Config, diff: &ConfigDiff) -> Config {
+    let mut merged = loaded.clone();
+    merged.rebinds.extend(diff.removed.iter().cloned());
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::Config,
+        config_diff::{diff_configs, merge_configs},
+        rebind::{
+            axis_gain::ButtonSource, button_to_button::ButtonToButtonModifier, rebind_id::RebindId,
+            reroute_rebind::RerouteRebind, Rebind, RebindType,
+        },
+    };
+
+    fn fire(name: &str, src_button: u32, modifier: ButtonToButtonModifier) -> Rebind {
+        Rebind::new(
+            name.to_string(),
+            Default::default(),
+            RebindType::Reroute {
+                rebind: RerouteRebind::ButtonToButton {
+                    src: ButtonSource::physical("stick".to_string(), src_button),
+                    dst_device: 1,
+                    dst_button: src_button,
+                    modifier,
+                },
+            },
+        )
+    }
+
+    fn config(rebinds: Vec<Rebind>) -> Config {
+        Config {
+            rebinds,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn identical_configs_have_no_diff() {
+        let live = config(vec![fire("a", 1, ButtonToButtonModifier::Simple)]);
+        assert!(diff_configs(&live, &live.clone()).is_empty());
+    }
+
+    #[test]
+    fn regenerated_ids_are_no_change() {
+        let live = config(vec![fire("a", 1, ButtonToButtonModifier::Simple)]);
+        let mut loaded = live.clone();
+        loaded.rebinds[0].id = RebindId::new();
+        assert!(diff_configs(&live, &loaded).is_empty());
+    }
+
+    #[test]
+    fn reordering_is_no_change() {
+        let a = fire("a", 1, ButtonToButtonModifier::Simple);
+        let b = fire("b", 2, ButtonToButtonModifier::Simple);
+        let diff = diff_configs(&config(vec![a.clone(), b.clone()]), &config(vec![b, a]));
+        assert!(diff.reordered);
+        assert!(diff.changed.is_empty() && diff.added.is_empty() && diff.removed.is_empty());
+    }
+
+    #[test]
+    fn modifier_only_change_and_rename() {
+        let live = config(vec![
+            fire("a", 1, ButtonToButtonModifier::Simple),
+            fire("b", 2, ButtonToButtonModifier::Simple),
+            fire("local", 3, ButtonToButtonModifier::Simple),
+        ]);
+        let loaded = config(vec![
+            fire("a", 1, ButtonToButtonModifier::Toggle { last_input: false }),
+            fire("b renamed", 2, ButtonToButtonModifier::Simple),
+            fire("new", 4, ButtonToButtonModifier::Simple),
+        ]);
+
+        let diff = diff_configs(&live, &loaded);
+        assert_eq!(diff.changed, vec![0]);
+        assert_eq!(diff.renamed, vec![("b".to_string(), 1)]);
+        assert_eq!(diff.removed, vec![live.rebinds[2].clone()]);
+        assert_eq!(diff.added, vec![2]);
+        assert!(!diff.reordered);
+        assert!(!diff.settings_changed);
+
+        let merged = merge_configs(&loaded, &diff);
+        let names: Vec<&str> = merged.rebinds.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b renamed", "new", "local"]);
+    }
+
+    #[test]
+    fn settings_outside_the_rebinds_count() {
+        let live = config(Vec::new());
+        let loaded = Config {
+            name: "Other".to_string(),
+            ..live.clone()
+        };
+        assert!(diff_configs(&live, &loaded).settings_changed);
+    }
+}
@@ -0,0 +1,229 @@
+use egui::{
+    text::LayoutJob, Align, Button, Color32, FontId, Layout, RichText, ScrollArea, TextEdit,
+    TextFormat, TextStyle, Ui, Visuals,
+};
+use log::{error, info};
+
+use crate::{config::Config, error::Error, input::Input};
+
+/// Raw TOML view of the live config.
+///
+/// Edits are only applied after they parse through [`Config::from_toml_str`].
+/// The config replaced by the last apply is kept as a single undo snapshot.
+#[derive(Default)]
+pub struct ConfigEditor {
+    pub text: String,
+    pub dirty: bool,
+    pub confirm_refresh: bool,
+    pub error: Option<String>,
+    pub undo: Option<Config>,
+}
+
+impl ConfigEditor {
+    /// Called when switching into the editor tab, prompts instead of discarding unapplied edits.
+    pub fn enter(&mut self, input: &Input) {
+        match self.dirty {
+            true => self.confirm_refresh = true,
+            false => self.refresh(input),
+        }
+    }
+
+    pub fn refresh(&mut self, input: &Input) {
+        match input.get_config().to_toml_string() {
+            Ok(text) => {
+                self.text = text;
+                self.dirty = false;
+                self.error = None;
+            }
+            Err(e) => error!("Failed to serialize config for the editor. Reason: {e}"),
+        }
+        self.confirm_refresh = false;
+    }
+
+    /// Returns true if the live config was replaced.
+    pub fn apply(&mut self, input: &mut Input) -> bool {
+        match Config::from_toml_str(&self.text) {
+            Ok(config) => {
+                self.undo = Some(input.replace_config(config));
+                self.dirty = false;
+                self.error = None;
+                info!("Applied config from the editor");
+                true
+            }
+            Err(e) => {
+                self.error = Some(describe_parse_error(&self.text, &e));
+                false
+            }
+        }
+    }
+
+    /// Restores the config replaced by the last apply. Returns true if the live config was replaced.
+    pub fn undo(&mut self, input: &mut Input) -> bool {
+        let Some(config) = self.undo.take() else {
+            return false;
+        };
+
+        input.replace_config(config);
+        self.refresh(input);
+        info!("Restored config from before the last editor apply");
+        true
+    }
+}
+
+/// Parse error message prefixed with the 1-based line number if the error has a location.
+pub fn describe_parse_error(text: &str, error: &Error) -> String {
+    let Error::Deserialization { source } = error else {
+        return error.to_string();
+    };
+
+    let message = source.message().to_string();
+    match source.span() {
+        Some(span) => {
+            let line = text[..span.start.min(text.len())].lines().count().max(1);
+            format!("Line {line}: {message}")
+        }
+        None => message,
+    }
+}
+
+/// Line based highlighting for tables, keys, strings, numbers/booleans and comments.
+pub fn highlight_toml(visuals: &Visuals, text: &str) -> LayoutJob {
+    let font_id = FontId::monospace(14.0);
+    let default = visuals.text_color();
+    let (table, key, string, literal, comment) = match visuals.dark_mode {
+        true => (
+            Color32::from_rgb(230, 180, 80),
+            Color32::from_rgb(120, 180, 240),
+            Color32::from_rgb(150, 200, 120),
+            Color32::from_rgb(210, 140, 220),
+            Color32::GRAY,
+        ),
+        false => (
+            Color32::from_rgb(160, 100, 0),
+            Color32::from_rgb(20, 90, 180),
+            Color32::from_rgb(40, 120, 30),
+            Color32::from_rgb(140, 40, 160),
+            Color32::DARK_GRAY,
+        ),
+    };
+
+    let mut job = LayoutJob::default();
+    let append = |job: &mut LayoutJob, text: &str, color: Color32| {
+        job.append(text, 0.0, TextFormat::simple(font_id.clone(), color));
+    };
+
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('#') {
+            append(&mut job, line, comment);
+        } else if trimmed.starts_with('[') {
+            append(&mut job, line, table);
+        } else if let Some((lhs, rhs)) = line.split_once('=') {
+            append(&mut job, lhs, key);
+            append(&mut job, "=", default);
+            let value = rhs.trim();
+            let color = if value.starts_with('"') || value.starts_with('\'') {
+                string
+            } else if value.starts_with('[') || value.starts_with('{') {
+                default
+            } else {
+                literal
+            };
+            append(&mut job, rhs, color);
+        } else {
+            append(&mut job, line, default);
+        }
+    }
+
+    job
+}
+
+/// Returns true if the live config was replaced, i.e. the capabilities need to be checked again.
+pub fn build_ui(editor: &mut ConfigEditor, input: &mut Input, ui: &mut Ui) -> bool {
+    let mut replaced = false;
+
+    ui.horizontal(|ui| {
+        if ui.button("Apply").clicked() {
+            replaced |= editor.apply(input);
+        }
+        if ui
+            .add_enabled(editor.undo.is_some(), Button::new("Undo apply"))
+            .clicked()
+        {
+            replaced |= editor.undo(input);
+        }
+        if ui.button("Reload from live config").clicked() {
+            editor.enter(input);
+        }
+        if editor.dirty {
+            ui.label(RichText::new("unapplied edits").strong());
+        }
+    });
+
+    if editor.confirm_refresh {
+        ui.horizontal(|ui| {
+            ui.label("The editor has unapplied edits. Discard them and reload the live config?");
+            if ui.button("Discard and reload").clicked() {
+                editor.refresh(input);
+            }
+            if ui.button("Keep edits").clicked() {
+                editor.confirm_refresh = false;
+            }
+        });
+    }
+
+    if let Some(e) = &editor.error {
+        ui.label(RichText::new(e).color(ui.visuals().error_fg_color));
+    }
+
+    ui.separator();
+
+    let visuals = ui.visuals().clone();
+    let mut layouter = |ui: &Ui, text: &str, wrap_width: f32| {
+        let mut job = highlight_toml(&visuals, text);
+        job.wrap.max_width = wrap_width;
+        ui.fonts(|f| f.layout_job(job))
+    };
+
+    ScrollArea::vertical().show(ui, |ui| {
+        ui.with_layout(
+            Layout::top_down(Align::LEFT).with_cross_justify(true),
+            |ui| {
+                let response = ui.add(
+                    TextEdit::multiline(&mut editor.text)
+                        .font(TextStyle::Monospace)
+                        .code_editor()
+                        .desired_rows(40)
+                        .layouter(&mut layouter),
+                );
+                if response.changed() {
+                    editor.dirty = true;
+                }
+            },
+        );
+    });
+
+    replaced
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::Config,
+        config_editor::{describe_parse_error, highlight_toml},
+    };
+
+    #[test]
+    fn parse_error_reports_line() {
+        let text = "name = \"test\"\ndefault_shift_mode = 0\nrebinds = [\n";
+        let error = Config::from_toml_str(text).unwrap_err();
+        assert!(describe_parse_error(text, &error).starts_with("Line 3"));
+    }
+
+    #[test]
+    fn highlighting_keeps_text() {
+        let text = Config::debug_xbox360_config().to_toml_string().unwrap();
+        let job = highlight_toml(&egui::Visuals::dark(), &text);
+        assert_eq!(job.text, text);
+    }
+}
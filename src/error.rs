@@ -1,6 +1,40 @@
 use egui_winit::winit::error::OsError;
+use std::{fmt::Display, path::PathBuf};
 use thiserror::Error;
 
+/// Physical device an error is about. The name is known while the device is or was connected
+/// this session, the GUID alone otherwise.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DeviceContext {
+    pub guid: String,
+    pub name: Option<String>,
+}
+
+impl DeviceContext {
+    pub fn new(guid: &str, name: Option<String>) -> Self {
+        Self {
+            guid: guid.to_string(),
+            name,
+        }
+    }
+
+    /// Name for messages, the GUID if the name is unknown
+    pub fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.guid)
+    }
+}
+
+impl Display for DeviceContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{name} ({})", self.guid),
+            None => f.write_str(&self.guid),
+        }
+    }
+}
+
+/// `Display` is the technical message for logs. User facing surfaces show
+/// [`Error::user_message`] with the [`Error::code`] to search the docs and issues for.
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("rebind is empty.")]
@@ -9,14 +43,20 @@ pub enum Error {
     #[error("processing rebind failed. Rebind name: {0}")]
     RebindProcessingFailed(String),
 
+    #[error("validating rebind failed. Physical src device is disconnected: {0}")]
+    SourceDeviceDisconnected(DeviceContext),
+
     #[error("validating rebind failed. Physical src device: {0} | src button: {1}")]
-    RebindValidatePhysicalButtonFailed(String, u32),
+    RebindValidatePhysicalButtonFailed(DeviceContext, u32),
 
     #[error("validating rebind failed. Physical src device: {0} | src hat: {1}")]
-    RebindValidatePhysicalHatFailed(String, u32),
+    RebindValidatePhysicalHatFailed(DeviceContext, u32),
 
     #[error("validating rebind failed. Physical src device: {0} | src axis: {1}")]
-    RebindValidatePhysicalAxisFailed(String, u32),
+    RebindValidatePhysicalAxisFailed(DeviceContext, u32),
+
+    #[error("validating rebind failed. Virtual device: {0}")]
+    RebindValidateVirtualDeviceFailed(u32),
 
     #[error("validating rebind failed. Virtual src device: {0} | src button: {1}")]
     RebindValidateVirtualButtonFailed(u32, u32),
@@ -51,6 +91,9 @@ pub enum Error {
         source: vjoy::Error,
     },
 
+    #[error("vJoy is not loaded")]
+    VJoyNotLoaded,
+
     #[error("sdl2 error. Reason: {}", source)]
     SDL2 {
         #[from]
@@ -75,12 +118,235 @@ pub enum Error {
         source: toml::de::Error,
     },
 
+    #[error("unknown key `{0}` in config file")]
+    UnknownConfigKey(String),
+
+    #[error("config file {0:?} was modified on disk since it was last loaded or saved")]
+    ConfigModifiedOnDisk(PathBuf),
+
+    #[error("failed to write {path:?}. Reason: {source}")]
+    WriteFailed {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
     #[error("untyped error. Reason: {0}")]
     Catch(String),
 }
 
+impl Error {
+    /// Stable short code. Codes are never reused for another error, new variants get new codes.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::EmptyRebindOrInvalidID() => "E100",
+            Error::RebindProcessingFailed(_) => "E101",
+            Error::SourceDeviceDisconnected(_) => "E110",
+            Error::RebindValidatePhysicalButtonFailed(..) => "E111",
+            Error::RebindValidatePhysicalHatFailed(..) => "E112",
+            Error::RebindValidatePhysicalAxisFailed(..) => "E113",
+            Error::RebindValidateVirtualDeviceFailed(_) => "E120",
+            Error::RebindValidateVirtualButtonFailed(..) => "E121",
+            Error::RebindValidateVirtualHatFailed(..) => "E122",
+            Error::RebindValidateVirtualAxisFailed(..) => "E123",
+            Error::VJoy { .. } => "E200",
+            Error::VJoyNotLoaded => "E201",
+            Error::SDL2 { .. } => "E210",
+            Error::WindowCreateFailed { .. } => "E220",
+            Error::Vku { .. } => "E221",
+            Error::Vk { .. } => "E222",
+            Error::IO { .. } => "E300",
+            Error::WriteFailed { .. } => "E301",
+            Error::ConfigModifiedOnDisk(_) => "E302",
+            Error::Serialization { .. } => "E310",
+            Error::Deserialization { .. } => "E311",
+            Error::UnknownConfigKey(_) => "E312",
+            Error::Catch(_) => "E900",
+        }
+    }
+
+    /// What went wrong and what to do about it, for toasts, dialogs and the exit message
+    pub fn user_message(&self) -> String {
+        const VJOY_HINT: &str = "Install vJoy and enable at least one device in vJoyConf.";
+        match self {
+            Error::EmptyRebindOrInvalidID() => {
+                "A rebind has no device or control selected. Pick them in the rebind editor."
+                    .to_string()
+            }
+            Error::RebindProcessingFailed(name) => {
+                format!("Rebind {name} failed. The log has the details.")
+            }
+            Error::SourceDeviceDisconnected(device) => format!(
+                "{} is not connected. Rebinds reading it resume once it is plugged in.",
+                device.label()
+            ),
+            Error::RebindValidatePhysicalButtonFailed(device, button) => format!(
+                "{} has no button {button}. Pick a button the device has.",
+                device.label()
+            ),
+            Error::RebindValidatePhysicalHatFailed(device, hat) => format!(
+                "{} has no hat {hat}. Pick a hat the device has.",
+                device.label()
+            ),
+            Error::RebindValidatePhysicalAxisFailed(device, axis) => format!(
+                "{} has no axis {axis}. Pick an axis the device has.",
+                device.label()
+            ),
+            Error::RebindValidateVirtualDeviceFailed(id) => {
+                format!("vJoy device {id} is not enabled. Enable it in vJoyConf or pick another device.")
+            }
+            Error::RebindValidateVirtualButtonFailed(id, button) => format!(
+                "vJoy device {id} has no button {button}. Add buttons in vJoyConf or pick another button."
+            ),
+            Error::RebindValidateVirtualHatFailed(id, hat) => format!(
+                "vJoy device {id} has no hat {hat}. Add hats in vJoyConf or pick another hat."
+            ),
+            Error::RebindValidateVirtualAxisFailed(id, axis) => format!(
+                "vJoy device {id} has no axis {axis}. Enable the axis in vJoyConf or pick another axis."
+            ),
+            Error::VJoy { source } => format!("vJoy failed: {source}. {VJOY_HINT}"),
+            Error::VJoyNotLoaded => format!("vJoy is not loaded. {VJOY_HINT}"),
+            Error::SDL2 { source } => format!(
+                "Joystick input could not be started: {source}. Check that SDL2.dll is next to the executable."
+            ),
+            Error::WindowCreateFailed { source } => {
+                format!("The window could not be created: {source}")
+            }
+            Error::Vku { source } => format!(
+                "Vulkan could not be started: {source}. Update the graphics driver."
+            ),
+            Error::Vk { source } => format!(
+                "Vulkan reported an error: {source}. Update the graphics driver."
+            ),
+            Error::IO { source } => format!("A file could not be read or written: {source}"),
+            Error::WriteFailed { path, source } => format!(
+                "Could not write {}: {source}. Check that the folder exists and is writable, or save somewhere else.",
+                path.display()
+            ),
+            Error::ConfigModifiedOnDisk(path) => format!(
+                "{} was changed by another program since it was loaded. Reload it or save again to overwrite.",
+                path.display()
+            ),
+            Error::Serialization { source } => {
+                format!("The config could not be converted to TOML: {source}")
+            }
+            Error::Deserialization { source } => format!(
+                "The file is not a valid config: {}. Fix it in a text editor or load another config.",
+                source.message()
+            ),
+            Error::UnknownConfigKey(key) => format!(
+                "The config has an unknown setting `{key}`. It may be from a newer version, remove the line to load it here."
+            ),
+            Error::Catch(reason) => reason.clone(),
+        }
+    }
+
+    /// User message followed by the code, e.g. for toasts
+    pub fn report(&self) -> String {
+        format!("{} ({})", self.user_message(), self.code())
+    }
+}
+
 impl From<String> for Error {
     fn from(value: String) -> Self {
         Self::Catch(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, path::PathBuf};
+
+    use crate::{
+        config::Config,
+        error::{DeviceContext, Error},
+    };
+
+    fn stick() -> DeviceContext {
+        DeviceContext::new(
+            "030000004f0400000ab1000000000000",
+            Some("T.16000M".to_string()),
+        )
+    }
+
+    #[test]
+    fn codes_are_unique() {
+        let errors = [
+            Error::EmptyRebindOrInvalidID(),
+            Error::RebindProcessingFailed("a".to_string()),
+            Error::SourceDeviceDisconnected(stick()),
+            Error::RebindValidatePhysicalButtonFailed(stick(), 1),
+            Error::RebindValidatePhysicalHatFailed(stick(), 1),
+            Error::RebindValidatePhysicalAxisFailed(stick(), 1),
+            Error::RebindValidateVirtualDeviceFailed(1),
+            Error::RebindValidateVirtualButtonFailed(1, 1),
+            Error::RebindValidateVirtualHatFailed(1, 1),
+            Error::RebindValidateVirtualAxisFailed(1, 1),
+            Error::VJoyNotLoaded,
+            Error::SDL2 {
+                source: sdl2::IntegerOrSdlError::SdlError("no joystick subsystem".to_string()),
+            },
+            Error::Vk {
+                source: vku::ash::vk::Result::ERROR_DEVICE_LOST,
+            },
+            Error::IO {
+                source: std::io::ErrorKind::NotFound.into(),
+            },
+            Error::WriteFailed {
+                path: PathBuf::from("a.toml"),
+                source: std::io::ErrorKind::PermissionDenied.into(),
+            },
+            Error::ConfigModifiedOnDisk(PathBuf::from("a.toml")),
+            Error::UnknownConfigKey("a".to_string()),
+            Error::Catch("a".to_string()),
+        ];
+        let codes: HashSet<&str> = errors.iter().map(|e| e.code()).collect();
+        assert_eq!(codes.len(), errors.len());
+    }
+
+    #[test]
+    fn missing_vjoy_points_at_vjoyconf() {
+        let error = Error::VJoyNotLoaded;
+        assert_eq!(
+            error.report(),
+            "vJoy is not loaded. Install vJoy and enable at least one device in vJoyConf. (E201)"
+        );
+        assert!(Error::RebindValidateVirtualDeviceFailed(2)
+            .user_message()
+            .contains("vJoyConf"));
+    }
+
+    #[test]
+    fn bad_configs_name_the_problem() {
+        let error = Config::from_toml_str("name = [").unwrap_err();
+        assert_eq!(error.code(), "E311");
+        assert!(error
+            .user_message()
+            .starts_with("The file is not a valid config: "));
+        // The technical message keeps the position for the log
+        assert!(error.to_string().contains("line 1"));
+
+        let toml = "speed = 3\n".to_string() + &Config::default().to_toml_string().unwrap();
+        let error = Config::from_toml_str(&toml).unwrap_err();
+        assert_eq!(error.code(), "E312");
+        assert!(error.user_message().contains("`speed`"));
+    }
+
+    #[test]
+    fn missing_devices_are_named() {
+        let error = Error::SourceDeviceDisconnected(stick());
+        assert_eq!(
+            error.user_message(),
+            "T.16000M is not connected. Rebinds reading it resume once it is plugged in."
+        );
+        assert!(error
+            .to_string()
+            .ends_with("T.16000M (030000004f0400000ab1000000000000)"));
+
+        let unknown = DeviceContext::new("0300abcd", None);
+        let error = Error::RebindValidatePhysicalButtonFailed(unknown, 40);
+        assert_eq!(
+            error.user_message(),
+            "0300abcd has no button 40. Pick a button the device has."
+        );
+    }
+}
@@ -415,6 +415,32 @@ fn pixel_test(ui: &mut Ui) {
     }
 }
 
+/// Paints a grid of 1 physical pixel wide lines over the whole window. Every line has to be
+/// sharp; a blurred or doubled line means points aren't mapped to whole pixels.
+pub fn paint_pixel_grid(ctx: &Context) {
+    const SPACING_PIXELS: usize = 8;
+
+    let pixels_per_point = ctx.pixels_per_point();
+    let screen = ctx.screen_rect();
+    let painter = ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("pixel_grid")));
+    let color = Color32::from_rgba_unmultiplied(255, 0, 255, 160);
+    let pixel = 1.0 / pixels_per_point;
+
+    let width_pixels = (screen.width() * pixels_per_point) as usize;
+    for x in (0..width_pixels).step_by(SPACING_PIXELS) {
+        let x = x as f32 / pixels_per_point;
+        let line = Rect::from_min_max(pos2(x, screen.top()), pos2(x + pixel, screen.bottom()));
+        painter.rect_filled(line, 0.0, color);
+    }
+
+    let height_pixels = (screen.height() * pixels_per_point) as usize;
+    for y in (0..height_pixels).step_by(SPACING_PIXELS) {
+        let y = y as f32 / pixels_per_point;
+        let line = Rect::from_min_max(pos2(screen.left(), y), pos2(screen.right(), y + pixel));
+        painter.rect_filled(line, 0.0, color);
+    }
+}
+
 fn blending_and_feathering_test(ui: &mut Ui) {
     let size = vec2(512.0, 512.0);
     let (response, painter) = ui.allocate_painter(size, Sense::hover());
@@ -281,6 +281,7 @@ impl EguiRenderer {
         vk_init: &VkInit,
         cmd_buffer: &CommandBuffer,
         ui_to_ndc_mat: Mat4,
+        pixels_per_point: f32,
         frame: usize,
         swapchain_index: usize,
     ) -> Result<(), Error> {
@@ -353,17 +354,8 @@ impl EguiRenderer {
                 .expect("Texture no longer exists");
 
             //Set clip rect
-            let scissor = Rect2D {
-                offset: Offset2D {
-                    x: info.rect.min.x.max(0.0) as _,
-                    y: info.rect.min.y.max(0.0) as _,
-                },
-                extent: Extent2D {
-                    width: info.rect.width() as _,
-                    height: info.rect.height() as _,
-                },
-            };
             let current_extent = vk_init.head().surface_info.current_extent;
+            let scissor = clip_rect_to_scissor(info.rect, pixels_per_point, current_extent);
             let viewport = Viewport::builder()
                 .width(current_extent.width as f32)
                 .height(current_extent.height as f32)
@@ -640,3 +632,85 @@ impl EguiRenderer {
         Ok((img, img_desc_set))
     }
 }
+
+/// Maps egui's logical points to NDC. `physical_size` is the window's inner size in pixels.
+pub(crate) fn ui_to_ndc(physical_size: [u32; 2], pixels_per_point: f32) -> Mat4 {
+    let width = physical_size[0] as f32 / pixels_per_point;
+    let height = physical_size[1] as f32 / pixels_per_point;
+    nalgebra_glm::ortho(0.0, width, 0.0, height, -1.0, 1.0)
+}
+
+/// Converts a clip rect in points to a scissor in physical pixels, clamped to the framebuffer.
+fn clip_rect_to_scissor(rect: Rect, pixels_per_point: f32, extent: Extent2D) -> Rect2D {
+    let min_x = (rect.min.x * pixels_per_point)
+        .round()
+        .clamp(0.0, extent.width as f32);
+    let min_y = (rect.min.y * pixels_per_point)
+        .round()
+        .clamp(0.0, extent.height as f32);
+    let max_x = (rect.max.x * pixels_per_point)
+        .round()
+        .clamp(min_x, extent.width as f32);
+    let max_y = (rect.max.y * pixels_per_point)
+        .round()
+        .clamp(min_y, extent.height as f32);
+
+    Rect2D {
+        offset: Offset2D {
+            x: min_x as i32,
+            y: min_y as i32,
+        },
+        extent: Extent2D {
+            width: (max_x - min_x) as u32,
+            height: (max_y - min_y) as u32,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::{pos2, Rect};
+    use nalgebra_glm::vec4;
+    use vku::ash::vk::{Extent2D, Offset2D};
+
+    use crate::graphics_backend::egui_renderer::{clip_rect_to_scissor, ui_to_ndc};
+
+    #[test]
+    fn logical_window_corner_maps_to_ndc_corner() {
+        for pixels_per_point in [1.0, 1.25, 1.5, 2.0] {
+            let size = [1920, 1080];
+            let mat = ui_to_ndc(size, pixels_per_point);
+            let corner = vec4(
+                size[0] as f32 / pixels_per_point,
+                size[1] as f32 / pixels_per_point,
+                0.0,
+                1.0,
+            );
+            let ndc = mat * corner;
+            assert!((ndc.x - 1.0).abs() < 1e-5 && (ndc.y - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn scissor_is_scaled_and_clamped() {
+        let extent = Extent2D {
+            width: 300,
+            height: 200,
+        };
+        let rect = Rect::from_min_max(pos2(10.0, 20.0), pos2(110.0, 220.0));
+
+        let scissor = clip_rect_to_scissor(rect, 1.5, extent);
+        assert_eq!(scissor.offset, Offset2D { x: 15, y: 30 });
+        assert_eq!(
+            scissor.extent,
+            Extent2D {
+                width: 150,
+                height: 170
+            }
+        );
+
+        let outside = Rect::from_min_max(pos2(-50.0, 400.0), pos2(-10.0, 500.0));
+        let scissor = clip_rect_to_scissor(outside, 2.0, extent);
+        assert_eq!((scissor.extent.width, scissor.extent.height), (0, 0));
+    }
+}
@@ -12,6 +12,7 @@ use winit::window::Window;
 pub mod egui_color_test;
 pub mod egui_renderer;
 pub mod push_constants;
+pub mod resize;
 pub mod vertex;
 pub use egui_color_test::ColorTest;
 
@@ -170,6 +171,7 @@ impl Graphics {
         images_delta: TexturesDelta,
         clipped_primitives: Vec<ClippedPrimitive>,
         ui_to_ndc: Mat4,
+        pixels_per_point: f32,
     ) -> Result<(), Error> {
         let img_acquired_sem = self.image_acquired_semaphores[self.frame];
         let in_flight_fence = self.in_flight_fences[self.frame];
@@ -206,6 +208,7 @@ impl Graphics {
             &self.vk_init,
             &graphics_cmd_buffer,
             ui_to_ndc,
+            pixels_per_point,
             self.frame,
             swapchain_image_index,
         )?;
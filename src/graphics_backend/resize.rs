@@ -0,0 +1,128 @@
+use std::time::{Duration, Instant};
+
+/// A requested size has to stay unchanged this long before the swapchain is recreated
+pub const RESIZE_SETTLE: Duration = Duration::from_millis(100);
+/// Longest a request waits while the window edge keeps moving, so a slow drag still shows the UI
+pub const RESIZE_MAX_DELAY: Duration = Duration::from_millis(250);
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ResizeStep {
+    /// The swapchain matches the window
+    Idle,
+    /// The window is being resized, skip rendering until the size settles
+    Wait,
+    /// Recreate the swapchain at this size and render
+    Recreate([u32; 2]),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PendingResize {
+    size: [u32; 2],
+    since: Instant,
+    changed: Instant,
+}
+
+/// Coalesces the resize events of dragging a window edge into one swapchain recreation.
+#[derive(Debug)]
+pub struct ResizeDebounce {
+    /// Size of the current swapchain
+    current: [u32; 2],
+    pending: Option<PendingResize>,
+}
+
+impl ResizeDebounce {
+    pub fn new(current: [u32; 2]) -> Self {
+        Self {
+            current,
+            pending: None,
+        }
+    }
+
+    /// Records the latest window size. Minimized windows report a zero size and are ignored, the
+    /// size of the current swapchain cancels a pending recreation.
+    pub fn request(&mut self, size: [u32; 2], now: Instant) {
+        if size.contains(&0) {
+            return;
+        }
+        if size == self.current {
+            self.pending = None;
+            return;
+        }
+        match self.pending.as_mut() {
+            Some(pending) if pending.size == size => {}
+            Some(pending) => {
+                pending.size = size;
+                pending.changed = now;
+            }
+            None => {
+                self.pending = Some(PendingResize {
+                    size,
+                    since: now,
+                    changed: now,
+                })
+            }
+        }
+    }
+
+    /// Called before each frame
+    pub fn poll(&mut self, now: Instant) -> ResizeStep {
+        let Some(pending) = self.pending else {
+            return ResizeStep::Idle;
+        };
+        if now.duration_since(pending.changed) < RESIZE_SETTLE
+            && now.duration_since(pending.since) < RESIZE_MAX_DELAY
+        {
+            return ResizeStep::Wait;
+        }
+        self.pending = None;
+        self.current = pending.size;
+        ResizeStep::Recreate(pending.size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::graphics_backend::resize::{
+        ResizeDebounce, ResizeStep, RESIZE_MAX_DELAY, RESIZE_SETTLE,
+    };
+
+    #[test]
+    fn a_drag_recreates_once_after_settling() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut resize = ResizeDebounce::new([800, 600]);
+
+        for (ms, width) in [(0, 810), (10, 820), (20, 830)] {
+            resize.request([width, 600], at(ms));
+            assert_eq!(resize.poll(at(ms)), ResizeStep::Wait);
+        }
+        assert_eq!(
+            resize.poll(at(20) + RESIZE_SETTLE),
+            ResizeStep::Recreate([830, 600])
+        );
+        assert_eq!(resize.poll(at(500)), ResizeStep::Idle);
+
+        // Back and forth to the same size and minimizing never recreate
+        resize.request([900, 600], at(600));
+        resize.request([830, 600], at(610));
+        resize.request([0, 0], at(620));
+        assert_eq!(resize.poll(at(1000)), ResizeStep::Idle);
+    }
+
+    #[test]
+    fn a_long_drag_still_recreates() {
+        let start = Instant::now();
+        let mut resize = ResizeDebounce::new([800, 600]);
+        let mut now = start;
+        let mut width = 800;
+        while now.duration_since(start) < RESIZE_MAX_DELAY {
+            width += 10;
+            resize.request([width, 600], now);
+            assert_eq!(resize.poll(now), ResizeStep::Wait);
+            now += Duration::from_millis(20);
+        }
+        assert_eq!(resize.poll(now), ResizeStep::Recreate([width, 600]));
+    }
+}
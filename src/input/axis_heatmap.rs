@@ -0,0 +1,228 @@
+use std::path::{Path, PathBuf};
+
+use egui::{epaint::Hsva, Color32, ColorImage, ComboBox, Image, TextureHandle, TextureOptions, Ui};
+use log::info;
+
+use super::PhysicalDevice;
+use crate::{error::Error, modal::Modals, paths};
+
+pub const HEATMAP_BINS: usize = 64;
+pub const HEATMAP_REFRESH_INTERVAL: f64 = 1.0;
+
+/// Session histogram of the position of two axes of a physical device, e.g. stick X/Y.
+pub struct AxisHeatmap {
+    pub enabled: bool,
+    pub x_axis: u32,
+    pub y_axis: u32,
+    bins: Vec<u32>,
+    max: u32,
+    samples: u64,
+}
+
+impl Default for AxisHeatmap {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            x_axis: 1,
+            y_axis: 2,
+            bins: vec![0; HEATMAP_BINS * HEATMAP_BINS],
+            max: 0,
+            samples: 0,
+        }
+    }
+}
+
+impl AxisHeatmap {
+    /// Bins one sample of two physical axis values (-32768..=32767).
+    pub fn record(&mut self, x: i32, y: i32) {
+        let index = bin(y) * HEATMAP_BINS + bin(x);
+        self.bins[index] += 1;
+        self.max = self.max.max(self.bins[index]);
+        self.samples += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.bins.iter_mut().for_each(|b| *b = 0);
+        self.max = 0;
+        self.samples = 0;
+    }
+
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    pub fn to_color_image(&self) -> ColorImage {
+        let pixels = self.bins.iter().map(|count| heat_color(*count, self.max));
+        ColorImage {
+            size: [HEATMAP_BINS, HEATMAP_BINS],
+            pixels: pixels.collect(),
+        }
+    }
+
+    /// Writes the heatmap as PNG and creates its dir, failures name the attempted path.
+    pub fn export_png(&self, path: &Path) -> Result<(), Error> {
+        let image = self.to_color_image();
+        let rgba: Vec<u8> = image.pixels.iter().flat_map(|c| c.to_array()).collect();
+        let write = || {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            image::save_buffer(
+                path,
+                &rgba,
+                HEATMAP_BINS as u32,
+                HEATMAP_BINS as u32,
+                image::ColorType::Rgba8,
+            )
+            .map_err(|e| match e {
+                image::ImageError::IoError(e) => e,
+                e => std::io::Error::new(std::io::ErrorKind::Other, e),
+            })
+        };
+        write().map_err(|source| Error::WriteFailed {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+/// `heatmaps/` in the config dir, the working directory is not writable when installed
+fn export_path(guid: &str) -> Result<PathBuf, Error> {
+    Ok(paths::config_dir()?
+        .join("heatmaps")
+        .join(format!("heatmap_{guid}.png")))
+}
+
+fn bin(value: i32) -> usize {
+    let normalized = (value as i64 + 32768) as usize;
+    (normalized * HEATMAP_BINS / 65536).min(HEATMAP_BINS - 1)
+}
+
+/// Log-scaled blue -> red color ramp, empty bins are transparent
+fn heat_color(count: u32, max: u32) -> Color32 {
+    if count == 0 || max == 0 {
+        return Color32::TRANSPARENT;
+    }
+
+    let t = (1.0 + count as f32).ln() / (1.0 + max as f32).ln();
+    Hsva::new(0.66 * (1.0 - t), 0.9, 0.4 + 0.6 * t, 1.0).into()
+}
+
+/// Axis selection, heatmap image, reset and export for one physical device.
+///
+/// The texture is regenerated on the UI side at most once per [`HEATMAP_REFRESH_INTERVAL`].
+pub fn heatmap_widget(
+    ui: &mut Ui,
+    device: &mut PhysicalDevice,
+    texture: &mut Option<(TextureHandle, f64)>,
+    modals: &mut Modals,
+) {
+    let num_axes = device.num_axes() as u32;
+    let heatmap = &mut device.heatmap;
+
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut heatmap.enabled, "Record");
+        ui.label("X:");
+        ComboBox::from_id_source("heatmap_x_axis")
+            .selected_text(heatmap.x_axis.to_string())
+            .show_ui(ui, |ui| {
+                for i in 1..=num_axes {
+                    ui.selectable_value(&mut heatmap.x_axis, i, i.to_string());
+                }
+            });
+        ui.label("Y:");
+        ComboBox::from_id_source("heatmap_y_axis")
+            .selected_text(heatmap.y_axis.to_string())
+            .show_ui(ui, |ui| {
+                for i in 1..=num_axes {
+                    ui.selectable_value(&mut heatmap.y_axis, i, i.to_string());
+                }
+            });
+        if ui.button("Clear").clicked() {
+            heatmap.clear();
+            *texture = None;
+        }
+        if ui.button("Export PNG").clicked() {
+            let exported = export_path(&device.guid).and_then(|path| {
+                heatmap.export_png(&path)?;
+                Ok(path)
+            });
+            match exported {
+                Ok(path) => info!("Exported heatmap to {:?}", path),
+                Err(e) => modals.report_save_error("heatmap", e),
+            }
+        }
+    });
+
+    let now = ui.input(|i| i.time);
+    let outdated = match texture {
+        Some((_, last_update)) => now - *last_update >= HEATMAP_REFRESH_INTERVAL,
+        None => true,
+    };
+    if outdated {
+        let image = heatmap.to_color_image();
+        match texture {
+            Some((handle, last_update)) => {
+                handle.set(image, TextureOptions::NEAREST);
+                *last_update = now;
+            }
+            None => {
+                let handle = ui.ctx().load_texture(
+                    format!("heatmap_{}", device.guid),
+                    image,
+                    TextureOptions::NEAREST,
+                );
+                *texture = Some((handle, now));
+            }
+        }
+    }
+
+    if let Some((handle, _)) = texture {
+        ui.label(format!("{} samples", heatmap.samples()));
+        ui.add(Image::new(handle.id(), [192.0, 192.0]).bg_fill(ui.visuals().extreme_bg_color));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::Error,
+        input::axis_heatmap::{bin, AxisHeatmap, HEATMAP_BINS},
+    };
+
+    #[test]
+    fn axis_extremes_map_to_edge_bins() {
+        assert_eq!(bin(-32768), 0);
+        assert_eq!(bin(0), HEATMAP_BINS / 2);
+        assert_eq!(bin(32767), HEATMAP_BINS - 1);
+    }
+
+    #[test]
+    fn record_and_clear() {
+        let mut heatmap = AxisHeatmap::default();
+        heatmap.record(0, 0);
+        heatmap.record(0, 0);
+        heatmap.record(-32768, 32767);
+        assert_eq!(heatmap.samples(), 3);
+        assert_eq!(heatmap.max, 2);
+
+        heatmap.clear();
+        assert_eq!(heatmap.samples(), 0);
+        assert!(heatmap.bins.iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn failed_export_names_the_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let blocker = dir.path().join("file");
+        std::fs::write(&blocker, "").unwrap();
+        let path = blocker.join("heatmap.png");
+
+        let error = AxisHeatmap::default().export_png(&path).unwrap_err();
+        assert!(matches!(error, Error::WriteFailed { path: failed, .. } if failed == path));
+
+        let path = dir.path().join("heatmaps").join("heatmap.png");
+        AxisHeatmap::default().export_png(&path).unwrap();
+        assert!(path.exists());
+    }
+}
@@ -0,0 +1,412 @@
+use sdl2::{
+    joystick::{HatState, Joystick, PowerLevel},
+    JoystickSubsystem, Sdl,
+};
+use vjoy::VJoy;
+
+use super::{hid::DeviceBackend, virtual_state::VirtualState};
+use crate::error::Error;
+
+/// Source of physical joysticks. SDL in the app, a scripted fake in tests.
+pub trait JoystickBackend {
+    /// Number of attached joysticks, including the vJoy devices
+    fn num_joysticks(&self) -> Result<u32, Error>;
+    fn device_guid(&self, index: u32) -> Option<String>;
+    fn open(&self, index: u32) -> Result<Box<dyn JoystickHandle>, Error>;
+    /// Pulls pending device state so the opened handles report fresh values.
+    fn update(&mut self);
+}
+
+/// An opened physical joystick
+pub trait JoystickHandle {
+    fn name(&self) -> String;
+    fn guid(&self) -> String;
+    /// SDL instance id, unique for as long as the device stays connected
+    fn instance_id(&self) -> u32;
+    fn num_buttons(&self) -> u32;
+    fn num_axes(&self) -> u32;
+    fn num_hats(&self) -> u32;
+    fn button(&self, index: u32) -> Result<bool, Error>;
+    fn axis(&self, index: u32) -> Result<i16, Error>;
+    fn hat(&self, index: u32) -> Result<HatState, Error>;
+    /// Battery level, [`PowerLevel::Unknown`] if the device doesn't report one
+    fn power_level(&self) -> Result<PowerLevel, Error>;
+    /// Reads pending device state. SDL handles are updated by [`JoystickBackend::update`].
+    fn poll(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn backend(&self) -> DeviceBackend {
+        DeviceBackend::Sdl
+    }
+}
+
+/// Destination of the virtual device states. vJoy in the app, a recording fake in tests.
+pub trait OutputBackend {
+    /// Neutral state of every enabled virtual device
+    fn devices(&self) -> Vec<VirtualState>;
+    fn write(&mut self, state: &VirtualState) -> Result<(), Error>;
+    /// Enumerates the devices again, e.g. after devices were enabled in vJoyConf while running
+    fn rescan(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    /// Version reported by the driver behind the output, e.g. "2.2.1"
+    fn driver_version(&self) -> Option<&str> {
+        None
+    }
+}
+
+pub struct SdlBackend {
+    _sdl2: Sdl,
+    joystick_system: JoystickSubsystem,
+}
+
+impl SdlBackend {
+    pub fn new() -> Result<Self, Error> {
+        let sdl2 = sdl2::init()?;
+        let joystick_system = sdl2.joystick()?;
+        Ok(Self {
+            _sdl2: sdl2,
+            joystick_system,
+        })
+    }
+}
+
+impl JoystickBackend for SdlBackend {
+    fn num_joysticks(&self) -> Result<u32, Error> {
+        Ok(self.joystick_system.num_joysticks()?)
+    }
+
+    fn device_guid(&self, index: u32) -> Option<String> {
+        self.joystick_system
+            .device_guid(index)
+            .ok()
+            .map(|guid| guid.to_string())
+    }
+
+    fn open(&self, index: u32) -> Result<Box<dyn JoystickHandle>, Error> {
+        Ok(Box::new(self.joystick_system.open(index)?))
+    }
+
+    fn update(&mut self) {
+        self.joystick_system.update();
+    }
+}
+
+impl JoystickHandle for Joystick {
+    fn name(&self) -> String {
+        Joystick::name(self)
+    }
+
+    fn guid(&self) -> String {
+        Joystick::guid(self).to_string()
+    }
+
+    fn instance_id(&self) -> u32 {
+        Joystick::instance_id(self)
+    }
+
+    fn num_buttons(&self) -> u32 {
+        Joystick::num_buttons(self)
+    }
+
+    fn num_axes(&self) -> u32 {
+        Joystick::num_axes(self)
+    }
+
+    fn num_hats(&self) -> u32 {
+        Joystick::num_hats(self)
+    }
+
+    fn button(&self, index: u32) -> Result<bool, Error> {
+        Ok(Joystick::button(self, index)?)
+    }
+
+    fn axis(&self, index: u32) -> Result<i16, Error> {
+        Ok(Joystick::axis(self, index)?)
+    }
+
+    fn hat(&self, index: u32) -> Result<HatState, Error> {
+        Ok(Joystick::hat(self, index)?)
+    }
+
+    fn power_level(&self) -> Result<PowerLevel, Error> {
+        Ok(Joystick::power_level(self)?)
+    }
+}
+
+/// vJoy enumerates and acquires its devices only when loaded, so a rescan loads it again.
+pub struct VJoyOutput {
+    vjoy: Option<VJoy>,
+    driver_version: Option<String>,
+}
+
+/// Same DLL the vjoy crate loads
+const VJOY_DLL: &str = "C:\\Program Files\\vJoy\\x64\\vJoyInterface.dll";
+
+impl VJoyOutput {
+    pub fn new() -> Result<Self, Error> {
+        Ok(Self {
+            vjoy: Some(VJoy::from_default_dll_location()?),
+            driver_version: query_driver_version(),
+        })
+    }
+}
+
+/// Asks the vJoy DLL for the version of the installed driver, encoded as 0x0221 for 2.2.1
+fn query_driver_version() -> Option<String> {
+    // SAFETY: loads the same DLL as the vjoy crate, GetvJoyVersion takes no arguments
+    let version = unsafe {
+        let interface = vjoy_sys::vJoyInterface::new(VJOY_DLL).ok()?;
+        interface.GetvJoyVersion() as u16
+    };
+    if version == 0 {
+        return None;
+    }
+    Some(format!(
+        "{}.{}.{}",
+        version >> 8,
+        (version >> 4) & 0xF,
+        version & 0xF
+    ))
+}
+
+impl OutputBackend for VJoyOutput {
+    fn devices(&self) -> Vec<VirtualState> {
+        match &self.vjoy {
+            Some(vjoy) => vjoy.devices().map(VirtualState::from_vjoy).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn write(&mut self, state: &VirtualState) -> Result<(), Error> {
+        let Some(vjoy) = self.vjoy.as_mut() else {
+            return Err(Error::VJoyNotLoaded);
+        };
+        let mut device = vjoy.get_device_state(state.id())?;
+        state.apply_to_vjoy(&mut device);
+        vjoy.update_device_state(&device)?;
+        Ok(())
+    }
+
+    fn rescan(&mut self) -> Result<(), Error> {
+        // Release the devices first, a second instance would lose them when the first is dropped
+        self.vjoy = None;
+        self.vjoy = Some(VJoy::from_default_dll_location()?);
+        Ok(())
+    }
+
+    fn driver_version(&self) -> Option<&str> {
+        self.driver_version.as_deref()
+    }
+}
+
+/// Stands in for the output after [`crate::input::Input::shutdown`] released the virtual devices.
+pub struct ReleasedOutput;
+
+impl OutputBackend for ReleasedOutput {
+    fn devices(&self) -> Vec<VirtualState> {
+        Vec::new()
+    }
+
+    fn write(&mut self, state: &VirtualState) -> Result<(), Error> {
+        Err(Error::Catch(format!(
+            "virtual device {} was already released",
+            state.id()
+        )))
+    }
+}
+
+/// Scripted stand-ins for SDL and vJoy, so the whole update loop runs without hardware.
+#[cfg(test)]
+pub mod fake {
+    use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+    use sdl2::joystick::{HatState, PowerLevel};
+    use vjoy::HatState as VJoyHatState;
+
+    use super::{JoystickBackend, JoystickHandle, OutputBackend};
+    use crate::{error::Error, input::virtual_state::VirtualState};
+
+    /// SDL GUID the real backend uses to tell vJoy devices apart from physical ones
+    pub const VJOY_GUID: &str = "0300f80034120000adbe000000000000";
+
+    #[derive(Debug, Clone)]
+    pub struct FakeJoystickState {
+        pub buttons: Vec<bool>,
+        pub axes: Vec<i16>,
+        pub hats: Vec<HatState>,
+        pub power: PowerLevel,
+    }
+
+    /// A joystick whose state the test can change while it is opened by [`crate::input::Input`].
+    #[derive(Clone)]
+    pub struct FakeJoystick {
+        pub name: String,
+        pub guid: String,
+        pub state: Rc<RefCell<FakeJoystickState>>,
+    }
+
+    impl FakeJoystick {
+        pub fn new(guid: &str, num_buttons: usize, num_axes: usize, num_hats: usize) -> Self {
+            Self {
+                name: format!("Fake {guid}"),
+                guid: guid.to_string(),
+                state: Rc::new(RefCell::new(FakeJoystickState {
+                    buttons: vec![false; num_buttons],
+                    axes: vec![0; num_axes],
+                    hats: vec![HatState::Centered; num_hats],
+                    power: PowerLevel::Wired,
+                })),
+            }
+        }
+
+        /// The SDL side of a vJoy device, skipped by the input enumeration
+        pub fn vjoy() -> Self {
+            Self::new(VJOY_GUID, 0, 0, 0)
+        }
+
+        pub fn set_button(&self, index: usize, pressed: bool) {
+            self.state.borrow_mut().buttons[index] = pressed;
+        }
+
+        pub fn set_axis(&self, index: usize, value: i16) {
+            self.state.borrow_mut().axes[index] = value;
+        }
+
+        pub fn set_hat(&self, index: usize, state: HatState) {
+            self.state.borrow_mut().hats[index] = state;
+        }
+
+        pub fn set_power(&self, level: PowerLevel) {
+            self.state.borrow_mut().power = level;
+        }
+    }
+
+    impl JoystickHandle for FakeJoystick {
+        fn name(&self) -> String {
+            self.name.clone()
+        }
+
+        fn guid(&self) -> String {
+            self.guid.clone()
+        }
+
+        fn instance_id(&self) -> u32 {
+            0
+        }
+
+        fn num_buttons(&self) -> u32 {
+            self.state.borrow().buttons.len() as u32
+        }
+
+        fn num_axes(&self) -> u32 {
+            self.state.borrow().axes.len() as u32
+        }
+
+        fn num_hats(&self) -> u32 {
+            self.state.borrow().hats.len() as u32
+        }
+
+        fn button(&self, index: u32) -> Result<bool, Error> {
+            Ok(self.state.borrow().buttons[index as usize])
+        }
+
+        fn axis(&self, index: u32) -> Result<i16, Error> {
+            Ok(self.state.borrow().axes[index as usize])
+        }
+
+        fn hat(&self, index: u32) -> Result<HatState, Error> {
+            Ok(self.state.borrow().hats[index as usize])
+        }
+
+        fn power_level(&self) -> Result<PowerLevel, Error> {
+            Ok(self.state.borrow().power)
+        }
+    }
+
+    /// Joysticks in SDL enumeration order. Shared, so tests can plug devices in and out.
+    #[derive(Clone, Default)]
+    pub struct FakeJoystickBackend {
+        pub joysticks: Rc<RefCell<Vec<FakeJoystick>>>,
+    }
+
+    impl FakeJoystickBackend {
+        pub fn new(joysticks: Vec<FakeJoystick>) -> Self {
+            Self {
+                joysticks: Rc::new(RefCell::new(joysticks)),
+            }
+        }
+    }
+
+    impl JoystickBackend for FakeJoystickBackend {
+        fn num_joysticks(&self) -> Result<u32, Error> {
+            Ok(self.joysticks.borrow().len() as u32)
+        }
+
+        fn device_guid(&self, index: u32) -> Option<String> {
+            let joysticks = self.joysticks.borrow();
+            joysticks.get(index as usize).map(|j| j.guid.clone())
+        }
+
+        fn open(&self, index: u32) -> Result<Box<dyn JoystickHandle>, Error> {
+            match self.joysticks.borrow().get(index as usize) {
+                Some(joystick) => Ok(Box::new(joystick.clone())),
+                None => Err(Error::Catch(format!("no fake joystick at index {index}"))),
+            }
+        }
+
+        fn update(&mut self) {}
+    }
+
+    /// Virtual devices with fixed layouts. Every write is recorded per device id.
+    #[derive(Clone, Default)]
+    pub struct FakeOutput {
+        pub devices: Vec<VirtualState>,
+        pub written: Rc<RefCell<HashMap<u32, VirtualState>>>,
+        /// Enabled devices that only show up after the next rescan
+        pub enabled_later: Rc<RefCell<Vec<VirtualState>>>,
+    }
+
+    impl FakeOutput {
+        pub fn new(layouts: &[(u32, usize, usize, usize)]) -> Self {
+            let devices = layouts
+                .iter()
+                .map(|(id, buttons, axes, hats)| {
+                    VirtualState::new(*id, *buttons, *axes, *hats, VJoyHatState::default())
+                })
+                .collect();
+            Self {
+                devices,
+                written: Default::default(),
+                enabled_later: Default::default(),
+            }
+        }
+
+        /// Enables a device while the app is running, like vJoyConf does
+        pub fn enable_later(&self, (id, buttons, axes, hats): (u32, usize, usize, usize)) {
+            let state = VirtualState::new(id, buttons, axes, hats, VJoyHatState::default());
+            self.enabled_later.borrow_mut().push(state);
+        }
+
+        /// Last written state of a virtual device
+        pub fn last(&self, id: u32) -> VirtualState {
+            self.written.borrow()[&id].clone()
+        }
+    }
+
+    impl OutputBackend for FakeOutput {
+        fn devices(&self) -> Vec<VirtualState> {
+            self.devices.clone()
+        }
+
+        fn write(&mut self, state: &VirtualState) -> Result<(), Error> {
+            self.written.borrow_mut().insert(state.id(), state.clone());
+            Ok(())
+        }
+
+        fn rescan(&mut self) -> Result<(), Error> {
+            self.devices.append(&mut self.enabled_later.borrow_mut());
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use std::path::Path;
+
+use egui::{CollapsingHeader, Context, Grid, TextEdit, Ui};
+use egui_file::FileDialog;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+
+use super::Input;
+use crate::{error::Error, ui_data::UIData};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ButtonLabel {
+    pub button: u32,
+    pub label: String,
+}
+
+/// Custom labels for the buttons of one physical device, e.g. the names printed on the device.
+///
+/// Rebinds keep storing raw SDL button indices, labels are only used for display.
+/// A map can be exported on its own to share it for a device model.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct ButtonLabelMap {
+    pub device_name: String,
+    pub labels: Vec<ButtonLabel>,
+}
+
+impl ButtonLabelMap {
+    pub fn get(&self, button: u32) -> Option<&str> {
+        self.labels
+            .iter()
+            .find(|l| l.button == button)
+            .map(|l| l.label.as_str())
+    }
+
+    /// Sets the label of `button`, an empty label removes it.
+    pub fn set(&mut self, button: u32, label: &str) {
+        self.labels.retain(|l| l.button != button);
+        if !label.is_empty() {
+            self.labels.push(ButtonLabel {
+                button,
+                label: label.to_string(),
+            });
+            self.labels.sort_by_key(|l| l.button);
+        }
+    }
+
+    /// Label of `button` with the raw index as fallback
+    pub fn display(&self, button: u32) -> String {
+        match self.get(button) {
+            Some(label) => label.to_string(),
+            None => button.to_string(),
+        }
+    }
+
+    pub fn write_to_path(&self, path: &Path) -> Result<(), Error> {
+        std::fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn read_from_path(path: &Path) -> Result<Self, Error> {
+        let string = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&string)?)
+    }
+}
+
+/// Table with one label text field per button, empty fields fall back to the raw index.
+pub fn button_labels_widget(ui: &mut Ui, map: &mut ButtonLabelMap, num_buttons: usize) {
+    Grid::new("button_labels_grid")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            for button in 1..=num_buttons as u32 {
+                ui.label(button.to_string());
+                let mut label = map.get(button).unwrap_or_default().to_string();
+                let response = ui.add(TextEdit::singleline(&mut label).desired_width(60.0));
+                // Trimmed once editing ends, trimming every keystroke would eat typed spaces
+                if response.changed() {
+                    map.set(button, &label);
+                }
+                if response.lost_focus() {
+                    map.set(button, label.trim());
+                }
+                ui.end_row();
+            }
+        });
+}
+
+/// Side panel section with the label table, import and export per connected physical device.
+pub fn button_labels_section(ui: &mut Ui, input: &mut Input, ui_data: &mut UIData) {
+    let devices: Vec<(String, String, usize)> = input
+        .physical_devices()
+        .map(|d| (d.guid.clone(), d.name(), d.num_buttons()))
+        .collect();
+
+    for (guid, name, num_buttons) in devices {
+        CollapsingHeader::new(&name)
+            .id_source(format!("{guid}_button_labels"))
+            .show(ui, |ui| {
+                ui.push_id(&guid, |ui| {
+                    let labels = input.get_button_labels_mut();
+                    let mut map = labels
+                        .get(&guid)
+                        .cloned()
+                        .unwrap_or_else(|| ButtonLabelMap {
+                            device_name: name.clone(),
+                            ..Default::default()
+                        });
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Import").clicked() {
+                            let mut dialog = FileDialog::open_file(None).filter(Box::new(
+                                |path| matches!(path.extension(), Some(ext) if ext.eq("toml")),
+                            ));
+                            dialog.open();
+                            ui_data.button_labels_import_dialog = Some((guid.clone(), dialog));
+                        }
+                        if ui.button("Export").clicked() {
+                            let mut dialog = FileDialog::save_file(None).filter(Box::new(
+                                |path| matches!(path.extension(), Some(ext) if ext.eq("toml")),
+                            ));
+                            dialog.open();
+                            ui_data.button_labels_export_dialog = Some((guid.clone(), dialog));
+                        }
+                    });
+
+                    let before = map.clone();
+                    button_labels_widget(ui, &mut map, num_buttons);
+                    if map != before {
+                        match map.labels.is_empty() {
+                            true => labels.remove(&guid),
+                            false => labels.insert(guid.clone(), map),
+                        };
+                    }
+                });
+            });
+    }
+}
+
+pub fn update_button_labels_dialogs(ctx: &Context, input: &mut Input, ui_data: &mut UIData) {
+    if let Some((guid, dialog)) = &mut ui_data.button_labels_import_dialog {
+        if dialog.show(ctx).selected() {
+            if let Some(path) = dialog.path() {
+                match ButtonLabelMap::read_from_path(&path) {
+                    Ok(map) => {
+                        info!("Imported button labels from {:?}", path);
+                        input.get_button_labels_mut().insert(guid.clone(), map);
+                    }
+                    Err(e) => error!(
+                        "Failed to import button labels from {:?}. Reason: {e}",
+                        path
+                    ),
+                }
+            }
+            ui_data.button_labels_import_dialog = None;
+        }
+    }
+
+    if let Some((guid, dialog)) = &mut ui_data.button_labels_export_dialog {
+        if dialog.show(ctx).selected() {
+            if let Some(path) = dialog.path() {
+                let map = input
+                    .get_button_labels()
+                    .get(guid)
+                    .cloned()
+                    .unwrap_or_default();
+                match map.write_to_path(&path) {
+                    Ok(_) => info!("Exported button labels to {:?}", path),
+                    Err(e) => error!("Failed to export button labels to {:?}. Reason: {e}", path),
+                }
+            }
+            ui_data.button_labels_export_dialog = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::button_labels::ButtonLabelMap;
+
+    #[test]
+    fn set_get_and_remove() {
+        let mut map = ButtonLabelMap::default();
+        map.set(3, "B7");
+        map.set(1, "B5");
+        assert_eq!(map.get(1), Some("B5"));
+        assert_eq!(map.display(3), "B7");
+        assert_eq!(map.display(2), "2");
+        assert_eq!(map.labels[0].button, 1);
+
+        map.set(1, "");
+        assert_eq!(map.get(1), None);
+        assert_eq!(map.labels.len(), 1);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut map = ButtonLabelMap {
+            device_name: "VPC Throttle".to_string(),
+            ..Default::default()
+        };
+        map.set(1, "B5");
+        let string = toml::to_string_pretty(&map).unwrap();
+        let readback: ButtonLabelMap = toml::from_str(&string).unwrap();
+        assert_eq!(map, readback);
+    }
+}
@@ -0,0 +1,479 @@
+use std::collections::BTreeSet;
+
+use egui::{
+    output::OpenUrl, pos2, vec2, CollapsingHeader, ImageButton, Label, Rect, Response, RichText,
+    Sense, Ui,
+};
+
+use super::{
+    button_labels::button_labels_section,
+    identifiers::identifiers_grid,
+    output_rate::{OutputRate, ACTIVITY_BUCKETS},
+    output_status::OutputStatus,
+    power::PowerStatus,
+    Input,
+};
+use crate::{
+    rebind::{
+        axis_neutral::axis_neutrals_widget,
+        mode_chips::{self, mode_color},
+    },
+    ui_data::UIData,
+};
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub enum DeviceKey {
+    Physical(String),
+    Virtual(u32),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum SelectionMode {
+    /// Selecting a device deselects all others
+    Single,
+    #[default]
+    Multi,
+}
+
+/// Devices shown in the input viewer, owned by the UI instead of the devices themselves.
+#[derive(Debug, Default)]
+pub struct DeviceSelection {
+    mode: SelectionMode,
+    selected: BTreeSet<DeviceKey>,
+}
+
+impl DeviceSelection {
+    pub fn mode(&self) -> SelectionMode {
+        self.mode
+    }
+
+    /// Switching to single selection keeps only the first selected device.
+    pub fn set_mode(&mut self, mode: SelectionMode) {
+        self.mode = mode;
+        if mode == SelectionMode::Single {
+            let first = self.selected.iter().next().cloned();
+            self.selected = first.into_iter().collect();
+        }
+    }
+
+    pub fn is_selected(&self, key: &DeviceKey) -> bool {
+        self.selected.contains(key)
+    }
+
+    pub fn is_physical_selected(&self, guid: &str) -> bool {
+        self.selected
+            .iter()
+            .any(|key| matches!(key, DeviceKey::Physical(g) if g == guid))
+    }
+
+    pub fn is_virtual_selected(&self, id: u32) -> bool {
+        self.selected.contains(&DeviceKey::Virtual(id))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.selected.is_empty()
+    }
+
+    pub fn toggle(&mut self, key: DeviceKey) {
+        if self.selected.remove(&key) {
+            return;
+        }
+
+        if self.mode == SelectionMode::Single {
+            self.selected.clear();
+        }
+        self.selected.insert(key);
+    }
+
+    /// Selects all `available` devices, in single selection only the first one.
+    pub fn select_all(&mut self, available: &[DeviceKey]) {
+        let take = match self.mode {
+            SelectionMode::Single => 1,
+            SelectionMode::Multi => available.len(),
+        };
+        self.selected = available.iter().take(take).cloned().collect();
+    }
+
+    pub fn select_none(&mut self) {
+        self.selected.clear();
+    }
+
+    /// Drops selected devices that are no longer connected.
+    pub fn retain_available(&mut self, available: &[DeviceKey]) {
+        self.selected.retain(|key| available.contains(key));
+    }
+}
+
+/// Short age for the devices list, e.g. `0.4 s` or `12 min`
+fn format_age(seconds: f64) -> String {
+    match seconds {
+        s if s < 10.0 => format!("{s:.1} s"),
+        s if s < 120.0 => format!("{s:.0} s"),
+        s if s < 7200.0 => format!("{:.0} min", s / 60.0),
+        s => format!("{:.0} h", s / 3600.0),
+    }
+}
+
+/// Bars of the state changes per bucket, scaled to the busiest one
+fn activity_sparkline(ui: &mut Ui, activity: &[u32; ACTIVITY_BUCKETS]) -> Response {
+    let (rect, response) =
+        ui.allocate_exact_size(vec2(ACTIVITY_BUCKETS as f32 * 2.0, 12.0), Sense::click());
+    let painter = ui.painter();
+    painter.rect_filled(rect, 1.0, ui.visuals().extreme_bg_color);
+    let busiest = activity.iter().copied().max().unwrap_or(0).max(1) as f32;
+    let width = rect.width() / ACTIVITY_BUCKETS as f32;
+    for (index, count) in activity.iter().enumerate().filter(|(_, c)| **c > 0) {
+        let height = rect.height() * *count as f32 / busiest;
+        let left = rect.left() + index as f32 * width;
+        painter.rect_filled(
+            Rect::from_min_max(
+                pos2(left, rect.bottom() - height),
+                pos2(left + width, rect.bottom()),
+            ),
+            0.0,
+            ui.visuals().selection.bg_fill,
+        );
+    }
+    response
+}
+
+/// Left side panel: device lists with selection, button labels, processing state and repo link.
+pub fn build_ui(input: &mut Input, ui: &mut Ui, ui_data: &mut UIData) {
+    let physical: Vec<(DeviceKey, String)> = input
+        .physical_devices()
+        .map(|d| {
+            let name = match (d.connected, d.is_initializing()) {
+                (false, _) => format!("{} (disconnected)", d.name()),
+                (true, true) => format!("{} (initializing)", d.name()),
+                (true, false) => d.name(),
+            };
+            (DeviceKey::Physical(d.guid.clone()), name)
+        })
+        .collect();
+    let physical_power: Vec<PowerStatus> = input.physical_devices().map(|d| d.power).collect();
+    let physical_transformed: Vec<bool> = input
+        .physical_devices()
+        .map(|d| !d.input_state.transform().is_identity())
+        .collect();
+    let virtual_devices: Vec<(DeviceKey, String)> = input
+        .virtual_devices()
+        .map(|d| (DeviceKey::Virtual(d.id), d.name()))
+        .collect();
+    let virtual_status: Vec<OutputStatus> = input
+        .virtual_devices()
+        .map(|d| d.output_status.clone())
+        .collect();
+    let virtual_rates: Vec<OutputRate> = input
+        .virtual_devices()
+        .map(|d| d.output_rate.clone())
+        .collect();
+    let time = input.get_last_poll_time();
+    let output_paused = input.is_output_paused();
+    let available: Vec<DeviceKey> = physical
+        .iter()
+        .chain(virtual_devices.iter())
+        .map(|(key, _)| key.clone())
+        .collect();
+
+    let selection = &mut ui_data.device_selection;
+    selection.retain_available(&available);
+
+    ui.horizontal(|ui| {
+        let mut mode = selection.mode();
+        ui.selectable_value(&mut mode, SelectionMode::Single, "Single");
+        ui.selectable_value(&mut mode, SelectionMode::Multi, "Multi");
+        if mode != selection.mode() {
+            selection.set_mode(mode);
+        }
+        ui.separator();
+        if ui.button("All").clicked() {
+            selection.select_all(&available);
+        }
+        if ui.button("None").clicked() {
+            selection.select_none();
+        }
+    });
+
+    ui.separator();
+
+    ui.horizontal(|ui| {
+        ui.label(format!("Physical devices: {}", physical.len()));
+        let duplicates = input.get_duplicate_guids();
+        if !duplicates.is_empty() {
+            ui.label(RichText::new("⚠").color(ui.visuals().warn_fg_color))
+                .on_hover_text(format!(
+                    "Several devices report the same GUID. \
+                    Later devices are keyed by enumeration order: {}",
+                    duplicates.join(", ")
+                ));
+        }
+    });
+
+    ui.separator();
+
+    ui.vertical(|ui| {
+        let mut edit_transform = None;
+        for (index, (((key, name), power), transformed)) in physical
+            .into_iter()
+            .zip(physical_power)
+            .zip(physical_transformed)
+            .enumerate()
+        {
+            ui.horizontal(|ui| {
+                let selected = selection.is_selected(&key);
+                if ui
+                    .selectable_label(selected, format!("{}: {}", index, name))
+                    .clicked()
+                {
+                    selection.toggle(key.clone());
+                }
+                let text = match transformed {
+                    true => RichText::new("⚙").color(ui.visuals().warn_fg_color),
+                    false => RichText::new("⚙"),
+                };
+                if ui
+                    .small_button(text)
+                    .on_hover_text("Device settings: input backend, swapped or inverted axes")
+                    .clicked()
+                {
+                    if let DeviceKey::Physical(guid) = key {
+                        edit_transform = Some(guid);
+                    }
+                }
+                if let Some(label) = power.battery_label() {
+                    let color = match power.is_low() {
+                        true => ui.visuals().warn_fg_color,
+                        false => ui.visuals().weak_text_color(),
+                    };
+                    ui.label(RichText::new(label).small().color(color));
+                }
+            });
+        }
+        if edit_transform.is_some() {
+            ui_data.device_transform_editor = edit_transform;
+        }
+    });
+
+    CollapsingHeader::new("Button labels").show(ui, |ui| {
+        button_labels_section(ui, input, ui_data);
+    });
+
+    ui.add_space(10.0);
+
+    ui.vertical(|ui| {
+        ui.horizontal(|ui| {
+            ui.label("Virtual devices:");
+            ui.label(virtual_devices.len().to_string());
+        });
+        ui.horizontal(|ui| {
+            ui.label("Active mode:");
+            let mode = input.get_active_shift_mode();
+            ui.label(mode.to_string());
+            mode_chips::chip(ui, mode, mode_color(ui_data.visualization.palette, mode));
+        });
+        if !input.manual_overrides().is_empty() {
+            ui.label(
+                RichText::new("TEST PANEL OVERRIDE")
+                    .strong()
+                    .color(ui.visuals().warn_fg_color),
+            )
+            .on_hover_text("Controls held in the test panel override the rebind output");
+        }
+        if !input.is_output_enabled() {
+            ui.label(
+                RichText::new("OUTPUT INHIBITED")
+                    .strong()
+                    .color(ui.visuals().error_fg_color),
+            );
+        }
+        let budget = input.get_processing_budget();
+        if budget.safe_mode {
+            ui.label(
+                RichText::new("SAFE MODE")
+                    .strong()
+                    .color(ui.visuals().warn_fg_color),
+            )
+            .on_hover_text(format!(
+                "Disabled rebinds: {}",
+                budget
+                    .flagged()
+                    .iter()
+                    .map(|(_, name)| name.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            ));
+        }
+    });
+
+    ui.separator();
+
+    ui.vertical(|ui| {
+        let selection = &mut ui_data.device_selection;
+        let mut test_panel = None;
+        for (index, (((key, name), status), rate)) in virtual_devices
+            .into_iter()
+            .zip(virtual_status.iter())
+            .zip(virtual_rates.iter())
+            .enumerate()
+        {
+            ui.horizontal(|ui| {
+                let selected = selection.is_selected(&key);
+                if ui
+                    .selectable_label(selected, format!("{}: {}", index, name))
+                    .clicked()
+                {
+                    selection.toggle(key.clone());
+                }
+                if ui
+                    .small_button("🖐")
+                    .on_hover_text("Test panel: press buttons and move axes of this device by hand")
+                    .clicked()
+                {
+                    if let DeviceKey::Virtual(id) = key {
+                        test_panel = Some(id);
+                    }
+                }
+                if status.is_failing() {
+                    let color = match status.is_escalated() {
+                        true => ui.visuals().error_fg_color,
+                        false => ui.visuals().warn_fg_color,
+                    };
+                    ui.label(RichText::new("⚠").color(color))
+                        .on_hover_text(status.summary());
+                }
+            });
+            ui.horizontal(|ui| {
+                let updates = rate.updates_per_second(time);
+                let since_change = rate.since_last_change(time);
+                let text = match since_change {
+                    Some(age) => format!("{updates:.0}/s · changed {} ago", format_age(age)),
+                    None => format!("{updates:.0}/s · unchanged"),
+                };
+                let hover = format!(
+                    "{updates:.0} updates sent to vJoy per second{}, one per processed tick\n\
+                    Bars: state changes over the last {} s\n\
+                    Click to show the device in the input viewer",
+                    if output_paused {
+                        " (output paused)"
+                    } else {
+                        ""
+                    },
+                    ACTIVITY_BUCKETS / 4,
+                );
+                let label = ui
+                    .add(Label::new(RichText::new(text).small().weak()).sense(Sense::click()))
+                    .on_hover_text(&hover);
+                let sparkline = activity_sparkline(ui, &rate.activity(time)).on_hover_text(&hover);
+                if (label.clicked() || sparkline.clicked()) && !selection.is_selected(&key) {
+                    selection.toggle(key);
+                }
+            });
+        }
+        if test_panel.is_some() {
+            ui_data.test_panel = test_panel;
+        }
+    });
+
+    CollapsingHeader::new("Axis neutrals").show(ui, |ui| {
+        ui.label("Value each virtual axis returns to when its output is reset.");
+        let devices: Vec<(u32, String, u32)> = input
+            .virtual_devices()
+            .map(|d| (d.id, d.name(), d.num_axes() as u32))
+            .collect();
+        axis_neutrals_widget(ui, input.get_axis_neutrals_mut(), &devices);
+    });
+
+    CollapsingHeader::new("Identifiers").show(ui, |ui| {
+        identifiers_grid(ui, input, &mut ui_data.toast);
+    });
+
+    let spacing = ui.available_height() - 50.0;
+
+    ui.add_space(spacing);
+
+    ui.horizontal(|ui| {
+        if ui
+            .add(
+                ImageButton::new(
+                    ui_data.ferris.id(),
+                    [50.0 * ui_data.ferris.aspect_ratio(), 50.0],
+                )
+                .frame(false),
+            )
+            .clicked()
+        {
+            ui.ctx().output_mut(|o| {
+                o.open_url = Some(OpenUrl {
+                    url: "https://github.com/ArrowMaxGithub/Rust-vJoy-Manager".to_string(),
+                    new_tab: true,
+                });
+            });
+        }
+        ui.label("Click to \nopen repo");
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::device_panel::{DeviceKey, DeviceSelection, SelectionMode};
+
+    fn snapshot(selection: &DeviceSelection) -> String {
+        let keys: Vec<String> = selection
+            .selected
+            .iter()
+            .map(|key| match key {
+                DeviceKey::Physical(guid) => format!("p:{guid}"),
+                DeviceKey::Virtual(id) => format!("v:{id}"),
+            })
+            .collect();
+        format!("{:?} [{}]", selection.mode(), keys.join(", "))
+    }
+
+    fn available() -> Vec<DeviceKey> {
+        vec![
+            DeviceKey::Physical("a".to_string()),
+            DeviceKey::Physical("b".to_string()),
+            DeviceKey::Virtual(1),
+        ]
+    }
+
+    #[test]
+    fn multi_select_toggles_independently() {
+        let mut selection = DeviceSelection::default();
+        selection.toggle(DeviceKey::Physical("a".to_string()));
+        selection.toggle(DeviceKey::Virtual(1));
+        assert_eq!(snapshot(&selection), "Multi [p:a, v:1]");
+
+        selection.toggle(DeviceKey::Physical("a".to_string()));
+        assert_eq!(snapshot(&selection), "Multi [v:1]");
+        assert!(selection.is_virtual_selected(1));
+        assert!(!selection.is_physical_selected("a"));
+    }
+
+    #[test]
+    fn single_select_replaces_selection() {
+        let mut selection = DeviceSelection::default();
+        selection.select_all(&available());
+        assert_eq!(snapshot(&selection), "Multi [p:a, p:b, v:1]");
+
+        selection.set_mode(SelectionMode::Single);
+        assert_eq!(snapshot(&selection), "Single [p:a]");
+
+        selection.toggle(DeviceKey::Virtual(1));
+        assert_eq!(snapshot(&selection), "Single [v:1]");
+
+        selection.select_all(&available());
+        assert_eq!(snapshot(&selection), "Single [p:a]");
+
+        selection.select_none();
+        assert_eq!(snapshot(&selection), "Single []");
+    }
+
+    #[test]
+    fn disconnected_devices_are_dropped() {
+        let mut selection = DeviceSelection::default();
+        selection.select_all(&available());
+        selection.retain_available(&[DeviceKey::Physical("b".to_string())]);
+        assert_eq!(snapshot(&selection), "Multi [p:b]");
+    }
+}
@@ -0,0 +1,423 @@
+use egui::{Checkbox, ComboBox, Context, DragValue, Grid, RichText, Ui};
+use serde::{Deserialize, Serialize};
+
+use log::error;
+
+use super::{
+    hid::{DeviceBackend, HID_AVAILABLE},
+    input_state::AxisKind,
+    Input,
+};
+use crate::{previous::Previous, ui_data::UIData};
+
+/// Raw values of the ends and the rest position of an axis, e.g. for a worn potentiometer that
+/// no longer reaches the full range
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AxisCalibration {
+    pub min: i32,
+    pub center: i32,
+    pub max: i32,
+}
+
+impl Default for AxisCalibration {
+    fn default() -> Self {
+        Self {
+            min: i16::MIN as i32,
+            center: 0,
+            max: i16::MAX as i32,
+        }
+    }
+}
+
+impl AxisCalibration {
+    /// Maps `min..=center` to the lower and `center..=max` to the upper half of the full range.
+    /// Values beyond the ends are clamped, a collapsed half reads as its end.
+    pub fn apply(&self, raw: i32) -> i32 {
+        let (full_min, full_max) = (i16::MIN as i64, i16::MAX as i64);
+        let (min, center, max, raw) = (
+            self.min as i64,
+            self.center as i64,
+            self.max as i64,
+            raw as i64,
+        );
+        let value = if raw < center {
+            match center - min {
+                span if span <= 0 => full_min,
+                span => (raw - center) * -full_min / span,
+            }
+        } else {
+            match max - center {
+                span if span <= 0 => full_max,
+                span => (raw - center) * full_max / span,
+            }
+        };
+        value.clamp(full_min, full_max) as i32
+    }
+}
+
+/// Source of one corrected axis
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AxisTransform {
+    /// Raw axis the corrected axis reads from, starting at 1
+    pub source: u32,
+    pub invert: bool,
+    /// Applied to the raw value before the inversion
+    #[serde(default)]
+    pub calibration: Option<AxisCalibration>,
+}
+
+/// Corrects swapped or inverted axes of one physical device before any rebind sees them, e.g. for
+/// devices that enumerate differently depending on their firmware mode.
+///
+/// Corrected axes without an entry read their own raw axis.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct DeviceTransform {
+    pub device_name: String,
+    /// Per corrected axis, in order
+    pub axes: Vec<AxisTransform>,
+}
+
+impl DeviceTransform {
+    pub fn axis(&self, index: usize) -> AxisTransform {
+        self.axes.get(index).copied().unwrap_or(AxisTransform {
+            source: index as u32 + 1,
+            invert: false,
+            calibration: None,
+        })
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.axes.iter().enumerate().all(|(index, axis)| {
+            axis.source == index as u32 + 1 && !axis.invert && axis.calibration.is_none()
+        })
+    }
+
+    /// Writes the corrected value of every axis in `axes`. Missing sources read as centered.
+    pub fn apply(&self, raw: &[i32], axes: &mut [i32]) {
+        for (index, value) in axes.iter_mut().enumerate() {
+            let axis = self.axis(index);
+            let raw_value = axis
+                .source
+                .checked_sub(1)
+                .and_then(|source| raw.get(source as usize))
+                .copied()
+                .unwrap_or_default();
+            let raw_value = axis.calibration.map_or(raw_value, |c| c.apply(raw_value));
+            *value = match axis.invert {
+                true => (-raw_value).clamp(i16::MIN as i32, i16::MAX as i32),
+                false => raw_value,
+            };
+        }
+    }
+
+    /// Kinds of the corrected axes from the kinds of the raw axes
+    pub fn apply_kinds(&self, raw: &[AxisKind]) -> Vec<AxisKind> {
+        (0..raw.len())
+            .map(|index| {
+                let axis = self.axis(index);
+                let kind = axis
+                    .source
+                    .checked_sub(1)
+                    .and_then(|source| raw.get(source as usize))
+                    .copied()
+                    .unwrap_or(AxisKind::Stick);
+                match (kind, axis.invert) {
+                    (AxisKind::Trigger { rest_at_max }, true) => AxisKind::Trigger {
+                        rest_at_max: !rest_at_max,
+                    },
+                    (kind, _) => kind,
+                }
+            })
+            .collect()
+    }
+
+    /// Raw axes read by more than one corrected axis
+    pub fn duplicate_sources(&self, num_axes: usize) -> Vec<u32> {
+        let mut sources: Vec<u32> = (0..num_axes).map(|i| self.axis(i).source).collect();
+        sources.sort_unstable();
+        let mut duplicates: Vec<u32> = sources
+            .windows(2)
+            .filter(|pair| pair[0] == pair[1])
+            .map(|pair| pair[0])
+            .collect();
+        duplicates.dedup();
+        duplicates
+    }
+}
+
+/// Settings popup of the device in [`UIData::device_transform_editor`]: input backend, axis
+/// transform and calibration.
+pub fn update_device_transform_window(
+    ctx: &Context,
+    previous: &mut Previous,
+    input: &mut Input,
+    ui_data: &mut UIData,
+) {
+    let Some(guid) = ui_data.device_transform_editor.clone() else {
+        return;
+    };
+    let Some((name, raw_axes, backend)) =
+        input.physical_devices().find(|d| d.guid == guid).map(|d| {
+            (
+                d.name(),
+                d.input_state.raw_axes().copied().collect::<Vec<i32>>(),
+                d.handle.backend(),
+            )
+        })
+    else {
+        ui_data.device_transform_editor = None;
+        return;
+    };
+
+    let mut use_hid = input.is_hid_enabled(&guid);
+
+    let mut transform = input
+        .get_device_transforms_mut()
+        .get(&guid)
+        .cloned()
+        .unwrap_or_else(|| DeviceTransform {
+            device_name: name.clone(),
+            ..Default::default()
+        });
+    let num_axes = raw_axes.len();
+    transform.axes = (0..num_axes).map(|index| transform.axis(index)).collect();
+
+    let mut open = true;
+    egui::Window::new(format!("Device settings: {name}"))
+        .id(egui::Id::new(("device_transform", &guid)))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Read through:").on_hover_text(
+                    "Raw HID reads all controls of devices with more than 32 buttons or 8 axes",
+                );
+                ui.add_enabled_ui(HID_AVAILABLE, |ui| {
+                    ComboBox::from_id_source("device_backend")
+                        .selected_text(match use_hid {
+                            true => DeviceBackend::Hid.name(),
+                            false => DeviceBackend::Sdl.name(),
+                        })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut use_hid, false, DeviceBackend::Sdl.name());
+                            ui.selectable_value(&mut use_hid, true, DeviceBackend::Hid.name());
+                        });
+                })
+                .response
+                .on_disabled_hover_text("Built without the hid feature");
+                if use_hid && backend != DeviceBackend::Hid {
+                    ui.label(RichText::new("failed, using SDL").color(ui.visuals().warn_fg_color))
+                        .on_hover_text("See the log for the reason");
+                }
+            });
+            ui.add_enabled(false, Checkbox::new(&mut false, "Exclusive access"))
+                .on_disabled_hover_text(backend.exclusive_unavailable_reason());
+            ui.separator();
+
+            ui.label("Axis transform, rebinds and the input viewer see the corrected axes.");
+            Grid::new("device_transform_grid")
+                .num_columns(5)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label(RichText::new("Axis").strong());
+                    ui.label(RichText::new("Reads raw axis").strong());
+                    ui.label(RichText::new("Invert").strong());
+                    ui.label(RichText::new("Calibration").strong())
+                        .on_hover_text(
+                        "Raw values of the ends and the rest position, stretched to the full range",
+                    );
+                    ui.label(RichText::new("Value").strong());
+                    ui.end_row();
+
+                    let mut corrected = vec![0; num_axes];
+                    transform.apply(&raw_axes, &mut corrected);
+                    for (index, axis) in transform.axes.iter_mut().enumerate() {
+                        ui.label((index + 1).to_string());
+                        ComboBox::from_id_source(("device_transform_source", index))
+                            .selected_text(axis.source.to_string())
+                            .show_ui(ui, |ui| {
+                                for (source, value) in raw_axes.iter().enumerate() {
+                                    let source = source as u32 + 1;
+                                    ui.selectable_value(
+                                        &mut axis.source,
+                                        source,
+                                        format!("{source} (raw {value})"),
+                                    );
+                                }
+                            });
+                        ui.add(Checkbox::new(&mut axis.invert, ""));
+                        let raw_value = axis
+                            .source
+                            .checked_sub(1)
+                            .and_then(|source| raw_axes.get(source as usize))
+                            .copied()
+                            .unwrap_or_default();
+                        ui.push_id(("device_transform_calibration", index), |ui| {
+                            calibration_widget(ui, &mut axis.calibration, raw_value)
+                        });
+                        ui.label(corrected[index].to_string());
+                        ui.end_row();
+                    }
+                });
+
+            let duplicates = transform.duplicate_sources(num_axes);
+            if !duplicates.is_empty() {
+                ui.label(
+                    RichText::new(format!("Raw axes read twice: {duplicates:?}"))
+                        .color(ui.visuals().warn_fg_color),
+                );
+            }
+            if ui.button("Reset").clicked() {
+                transform.axes.clear();
+            }
+        });
+
+    let transforms = input.get_device_transforms_mut();
+    match transform.is_identity() {
+        true => transforms.remove(&guid),
+        false => transforms.insert(guid.clone(), transform),
+    };
+
+    if use_hid != input.is_hid_enabled(&guid) {
+        match input.set_hid_enabled(&guid, use_hid) {
+            Ok(_) => {
+                previous.hid_devices.retain(|g| *g != guid);
+                if use_hid {
+                    previous.hid_devices.push(guid);
+                }
+            }
+            Err(e) => error!("Failed to reopen devices. Reason: {e}"),
+        }
+    }
+    if !open {
+        ui_data.device_transform_editor = None;
+    }
+}
+
+fn calibration_widget(ui: &mut Ui, calibration: &mut Option<AxisCalibration>, raw: i32) {
+    ui.horizontal(|ui| {
+        let mut enabled = calibration.is_some();
+        if ui.checkbox(&mut enabled, "").changed() {
+            *calibration = enabled.then(AxisCalibration::default);
+        }
+        let Some(calibration) = calibration else {
+            return;
+        };
+        let range = i16::MIN as i32..=i16::MAX as i32;
+        for (value, prefix) in [
+            (&mut calibration.min, "min "),
+            (&mut calibration.center, "center "),
+            (&mut calibration.max, "max "),
+        ] {
+            ui.add(
+                DragValue::new(value)
+                    .clamp_range(range.clone())
+                    .prefix(prefix),
+            )
+            .on_hover_text("Right click to use the current raw value")
+            .context_menu(|ui| {
+                if ui.button(format!("Set to {raw}")).clicked() {
+                    *value = raw;
+                    ui.close_menu();
+                }
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::{
+        device_transform::{AxisCalibration, AxisTransform, DeviceTransform},
+        input_state::AxisKind,
+    };
+
+    fn transform(axes: &[(u32, bool)]) -> DeviceTransform {
+        DeviceTransform {
+            axes: axes
+                .iter()
+                .map(|&(source, invert)| AxisTransform {
+                    source,
+                    invert,
+                    calibration: None,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn swaps_and_inverts_axes() {
+        let swapped = transform(&[(2, false), (1, true)]);
+        assert!(!swapped.is_identity());
+
+        let mut axes = [0; 3];
+        swapped.apply(&[100, -32768, 7], &mut axes);
+        assert_eq!(axes, [-32768, -100, 7]);
+
+        swapped.apply(&[-32768, 0, 0], &mut axes);
+        assert_eq!(axes[1], 32767);
+
+        let kinds = swapped.apply_kinds(&[
+            AxisKind::Trigger { rest_at_max: false },
+            AxisKind::Slider,
+            AxisKind::Stick,
+        ]);
+        assert_eq!(
+            kinds,
+            vec![
+                AxisKind::Slider,
+                AxisKind::Trigger { rest_at_max: true },
+                AxisKind::Stick
+            ]
+        );
+    }
+
+    #[test]
+    fn identity_and_duplicate_sources() {
+        assert!(DeviceTransform::default().is_identity());
+        assert!(transform(&[(1, false), (2, false)]).is_identity());
+
+        let duplicated = transform(&[(2, false), (2, true)]);
+        assert_eq!(duplicated.duplicate_sources(3), vec![2]);
+        assert!(duplicated.duplicate_sources(1).is_empty());
+    }
+
+    #[test]
+    fn calibration_stretches_both_halves() {
+        let calibration = AxisCalibration {
+            min: -30000,
+            center: 1000,
+            max: 20000,
+        };
+        assert_eq!(calibration.apply(-30000), -32768);
+        assert_eq!(calibration.apply(-40000), -32768);
+        assert_eq!(calibration.apply(1000), 0);
+        assert_eq!(calibration.apply(-14500), -16384);
+        assert_eq!(calibration.apply(20000), 32767);
+        assert_eq!(calibration.apply(32767), 32767);
+        assert_eq!(AxisCalibration::default().apply(-32768), -32768);
+
+        // A pedal resting at its minimum has no lower half
+        let pedal = AxisCalibration {
+            min: -32000,
+            center: -32000,
+            max: 30000,
+        };
+        assert_eq!(pedal.apply(-32768), -32768);
+        assert_eq!(pedal.apply(30000), 32767);
+
+        let mut transform = transform(&[(1, true)]);
+        assert!(!transform.is_identity());
+        transform.axes[0].invert = false;
+        transform.axes[0].calibration = Some(calibration);
+        assert!(!transform.is_identity());
+
+        // Calibrated before inverted
+        transform.axes[0].invert = true;
+        let mut axes = [0; 1];
+        transform.apply(&[20000], &mut axes);
+        assert_eq!(axes, [-32767]);
+    }
+}
@@ -0,0 +1,274 @@
+use std::{collections::VecDeque, fmt::Display};
+
+use egui::{ComboBox, Context, ScrollArea, TextStyle};
+
+/// Events kept in the scrollback, the oldest are dropped first
+pub const EVENT_CONSOLE_CAPACITY: usize = 5000;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ControlKind {
+    Button,
+    Axis,
+    Hat,
+}
+
+impl Display for ControlKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ControlKind::Button => f.write_str("button"),
+            ControlKind::Axis => f.write_str("axis"),
+            ControlKind::Hat => f.write_str("hat"),
+        }
+    }
+}
+
+/// Change of one control between two polls. Axes are raw SDL values, hats are angles with -1 for
+/// centered.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ControlChange {
+    pub kind: ControlKind,
+    /// 1-based like everywhere in the UI
+    pub index: u32,
+    pub old: i32,
+    pub new: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct InputEvent {
+    pub time: f64,
+    pub device: String,
+    pub change: ControlChange,
+}
+
+impl Display for InputEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ControlChange {
+            kind,
+            index,
+            old,
+            new,
+        } = self.change;
+        write!(f, "{:10.4} s  {}  {kind} {index}: ", self.time, self.device)?;
+        match kind {
+            ControlKind::Button => write!(f, "{} → {}", old != 0, new != 0),
+            ControlKind::Axis | ControlKind::Hat => write!(f, "{old} → {new}"),
+        }
+    }
+}
+
+/// Scrollback of every change of the physical inputs, for devices with ghost presses or axis
+/// spikes. Changes are only recorded while the console is open and not paused, and only the
+/// visible lines are formatted.
+#[derive(Debug)]
+pub struct EventConsole {
+    pub open: bool,
+    pub paused: bool,
+    events: VecDeque<InputEvent>,
+    /// Device names seen in the scrollback, for the filter
+    devices: Vec<String>,
+    filter_device: Option<String>,
+    show_buttons: bool,
+    show_axes: bool,
+    show_hats: bool,
+}
+
+impl Default for EventConsole {
+    fn default() -> Self {
+        Self {
+            open: false,
+            paused: false,
+            events: VecDeque::new(),
+            devices: Vec::new(),
+            filter_device: None,
+            show_buttons: true,
+            show_axes: true,
+            show_hats: true,
+        }
+    }
+}
+
+impl EventConsole {
+    pub fn is_recording(&self) -> bool {
+        self.open && !self.paused
+    }
+
+    pub fn record(
+        &mut self,
+        device: &str,
+        time: f64,
+        changes: impl IntoIterator<Item = ControlChange>,
+    ) {
+        if !self.is_recording() {
+            return;
+        }
+        if !self.devices.iter().any(|d| d == device) {
+            self.devices.push(device.to_string());
+        }
+        for change in changes {
+            if self.events.len() == EVENT_CONSOLE_CAPACITY {
+                self.events.pop_front();
+            }
+            self.events.push_back(InputEvent {
+                time,
+                device: device.to_string(),
+                change,
+            });
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+        self.devices.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    fn is_visible(&self, event: &InputEvent) -> bool {
+        let kind_shown = match event.change.kind {
+            ControlKind::Button => self.show_buttons,
+            ControlKind::Axis => self.show_axes,
+            ControlKind::Hat => self.show_hats,
+        };
+        kind_shown
+            && self
+                .filter_device
+                .iter()
+                .all(|device| *device == event.device)
+    }
+
+    /// Events passing the device and control type filters, oldest first
+    pub fn visible(&self) -> impl Iterator<Item = &InputEvent> {
+        self.events.iter().filter(|event| self.is_visible(event))
+    }
+
+    /// Visible events, one per line
+    pub fn visible_text(&self) -> String {
+        self.visible()
+            .map(|event| event.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn update_window(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = true;
+        egui::Window::new("Input event console")
+            .open(&mut open)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    let pause_text = match self.paused {
+                        true => "Resume",
+                        false => "Pause",
+                    };
+                    if ui.button(pause_text).clicked() {
+                        self.paused = !self.paused;
+                    }
+                    if ui.button("Clear").clicked() {
+                        self.clear();
+                    }
+                    if ui
+                        .button("Copy")
+                        .on_hover_text("Copies the lines passing the filters")
+                        .clicked()
+                    {
+                        let text = self.visible_text();
+                        ui.output_mut(|o| o.copied_text = text);
+                    }
+                    ui.separator();
+                    let selected = self.filter_device.as_deref().unwrap_or("All devices");
+                    ComboBox::from_id_source("EventConsoleDevice")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.filter_device, None, "All devices");
+                            for device in self.devices.iter() {
+                                ui.selectable_value(
+                                    &mut self.filter_device,
+                                    Some(device.clone()),
+                                    device,
+                                );
+                            }
+                        });
+                    ui.checkbox(&mut self.show_buttons, "Buttons");
+                    ui.checkbox(&mut self.show_axes, "Axes");
+                    ui.checkbox(&mut self.show_hats, "Hats");
+                });
+                ui.label(format!(
+                    "{} of {} events, the last {EVENT_CONSOLE_CAPACITY} are kept",
+                    self.visible().count(),
+                    self.len()
+                ));
+                ui.separator();
+
+                let visible: Vec<&InputEvent> = self.visible().collect();
+                let row_height = ui.text_style_height(&TextStyle::Monospace);
+                ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .stick_to_bottom(true)
+                    .show_rows(ui, row_height, visible.len(), |ui, rows| {
+                        for event in &visible[rows] {
+                            ui.monospace(event.to_string());
+                        }
+                    });
+            });
+        self.open = open;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::event_console::{
+        ControlChange, ControlKind, EventConsole, EVENT_CONSOLE_CAPACITY,
+    };
+
+    fn change(kind: ControlKind, old: i32, new: i32) -> ControlChange {
+        ControlChange {
+            kind,
+            index: 1,
+            old,
+            new,
+        }
+    }
+
+    #[test]
+    fn scrollback_is_bounded_filtered_and_paused() {
+        let mut console = EventConsole::default();
+        console.record("stick", 0.0, [change(ControlKind::Button, 0, 1)]);
+        assert!(console.is_empty());
+
+        console.open = true;
+        for value in 0..EVENT_CONSOLE_CAPACITY as i32 {
+            console.record("stick", 1.0, [change(ControlKind::Axis, value, value + 1)]);
+        }
+        console.record(
+            "pedals",
+            2.0,
+            [
+                change(ControlKind::Button, 0, 1),
+                change(ControlKind::Hat, -1, 90),
+            ],
+        );
+        assert_eq!(console.len(), EVENT_CONSOLE_CAPACITY);
+        assert_eq!(console.visible().next().unwrap().change.old, 2);
+
+        console.filter_device = Some("pedals".to_string());
+        console.show_hats = false;
+        assert_eq!(
+            console.visible_text(),
+            "    2.0000 s  pedals  button 1: false → true"
+        );
+
+        console.paused = true;
+        console.record("pedals", 3.0, [change(ControlKind::Button, 1, 0)]);
+        assert_eq!(console.visible().count(), 1);
+    }
+}
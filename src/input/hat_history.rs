@@ -0,0 +1,110 @@
+use egui::{epaint::Hsva, Color32, Rect, Sense, Stroke, Ui, Vec2};
+use ringbuffer::{AllocRingBuffer, RingBufferExt, RingBufferWrite};
+
+const HAT_HISTORY_CAPACITY: usize = 64;
+const HAT_STRIP_ROW_HEIGHT: f32 = 8.0;
+const HAT_STRIP_ROW_SPACING: f32 = 2.0;
+
+/// Recent direction changes of one hat, stored as (time, angle in degrees, -1 = centered)
+pub struct HatHistory {
+    events: AllocRingBuffer<(f64, i32)>,
+    last: i32,
+}
+
+impl Default for HatHistory {
+    fn default() -> Self {
+        Self {
+            events: AllocRingBuffer::with_capacity(HAT_HISTORY_CAPACITY),
+            last: -1,
+        }
+    }
+}
+
+impl HatHistory {
+    /// Only changes are stored, so calling this every poll is cheap.
+    pub fn record(&mut self, time: f64, angle: i32) {
+        if angle == self.last {
+            return;
+        }
+
+        self.events.push((time, angle));
+        self.last = angle;
+    }
+
+    /// Segments of constant direction clipped to `x_bounds`, centered segments are skipped
+    pub fn segments(&self, x_bounds: [f64; 2]) -> Vec<(f64, f64, i32)> {
+        let events = self.events.to_vec();
+        events
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, angle))| *angle != -1)
+            .filter_map(|(index, (start, angle))| {
+                let end = events.get(index + 1).map_or(x_bounds[1], |next| next.0);
+                let start = start.max(x_bounds[0]);
+                let end = end.min(x_bounds[1]);
+                (start < end).then_some((start, end, *angle))
+            })
+            .collect()
+    }
+}
+
+fn direction_color(angle: i32) -> Color32 {
+    Hsva::new(angle.rem_euclid(360) as f32 / 360.0, 0.8, 0.8, 1.0).into()
+}
+
+/// Horizontal strip with one row per hat, sharing the x-bounds of the axis plot above it.
+pub fn hat_history_strip(ui: &mut Ui, histories: &[HatHistory], x_bounds: [f64; 2]) {
+    if histories.is_empty() {
+        return;
+    }
+
+    let height = histories.len() as f32 * (HAT_STRIP_ROW_HEIGHT + HAT_STRIP_ROW_SPACING);
+    let (rect, response) =
+        ui.allocate_exact_size(Vec2::new(ui.available_width(), height), Sense::hover());
+    if !ui.is_rect_visible(rect) {
+        return;
+    }
+
+    let painter = ui.painter_at(rect);
+    let visuals = ui.style().noninteractive();
+    painter.rect(rect, 0.0, visuals.weak_bg_fill, Stroke::NONE);
+
+    let span = (x_bounds[1] - x_bounds[0]).max(f64::EPSILON);
+    let to_x = |time: f64| rect.left() + ((time - x_bounds[0]) / span) as f32 * rect.width();
+
+    for (row, history) in histories.iter().enumerate() {
+        let top = rect.top() + row as f32 * (HAT_STRIP_ROW_HEIGHT + HAT_STRIP_ROW_SPACING);
+        for (start, end, angle) in history.segments(x_bounds) {
+            let segment = Rect::from_min_max(
+                [to_x(start), top].into(),
+                [to_x(end), top + HAT_STRIP_ROW_HEIGHT].into(),
+            );
+            painter.rect_filled(segment, 0.0, direction_color(angle));
+        }
+    }
+
+    response.on_hover_text("Hat history, one row per hat. Hue encodes the direction.");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::hat_history::HatHistory;
+
+    #[test]
+    fn segments_skip_centered_and_clip_to_bounds() {
+        let mut history = HatHistory::default();
+        history.record(1.0, 0);
+        history.record(2.0, 0);
+        history.record(3.0, -1);
+        history.record(4.0, 90);
+
+        assert_eq!(
+            history.segments([0.0, 10.0]),
+            vec![(1.0, 3.0, 0), (4.0, 10.0, 90)]
+        );
+        assert_eq!(
+            history.segments([2.5, 5.0]),
+            vec![(2.5, 3.0, 0), (4.0, 5.0, 90)]
+        );
+    }
+}
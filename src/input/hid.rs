@@ -0,0 +1,354 @@
+//! Raw HID fallback for devices with more controls than SDL exposes.
+//!
+//! Opted in devices are still opened through SDL for their name, instance id and power level,
+//! but their buttons, axes and hats are read from the HID input reports instead. The SDL state of
+//! these devices is never read. Requires the `hid` feature.
+
+use log::{info, warn};
+use sdl2::joystick::{HatState, PowerLevel};
+
+use super::{
+    backend::JoystickHandle,
+    hid_descriptor::{HidControl, HidLayout},
+};
+use crate::error::Error;
+
+/// Most reports drained per poll, so a flooding device can't stall the input loop
+const MAX_REPORTS_PER_POLL: usize = 64;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DeviceBackend {
+    Sdl,
+    Hid,
+}
+
+impl DeviceBackend {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DeviceBackend::Sdl => "SDL",
+            DeviceBackend::Hid => "Raw HID",
+        }
+    }
+
+    /// Why the backend can't hide the device from other programs. Neither backend opens devices
+    /// exclusively on Windows, so exclusive capture is left to HidHide.
+    pub fn exclusive_unavailable_reason(&self) -> &'static str {
+        match self {
+            DeviceBackend::Sdl => "SDL opens joysticks shared without an option for exclusive access, games keep seeing the device. Hide it with HidHide instead.",
+            DeviceBackend::Hid => "hidapi opens devices shared on Windows, games keep seeing the device. Hide it with HidHide instead.",
+        }
+    }
+}
+
+/// Whether this build can read devices through raw HID
+pub const HID_AVAILABLE: bool = cfg!(feature = "hid");
+
+/// USB vendor and product id encoded in an SDL GUID, `None` for GUIDs without them
+pub fn vendor_product_from_guid(guid: &str) -> Option<(u16, u16)> {
+    if guid.len() < 24 {
+        return None;
+    }
+    let word = |index: usize| u16::from_str_radix(guid.get(index * 4..index * 4 + 4)?, 16).ok();
+    let le = |word: u16| word.swap_bytes();
+    // Layout: bus, crc, vendor, 0, product, 0, version, driver data
+    let (bus, vendor, zero_a, product, zero_b) = (word(0)?, word(2)?, word(3)?, word(4)?, word(5)?);
+    match bus != 0 && zero_a == 0 && zero_b == 0 {
+        true => Some((le(vendor), le(product))),
+        false => None,
+    }
+}
+
+/// Non-blocking source of raw input reports
+pub trait HidReportSource {
+    /// Next pending report into `buf`, `Ok(0)` if there is none
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error>;
+}
+
+pub struct HidJoystick<R: HidReportSource> {
+    sdl: Box<dyn JoystickHandle>,
+    source: R,
+    layout: HidLayout,
+    buttons: Vec<bool>,
+    axes: Vec<i16>,
+    hats: Vec<HatState>,
+}
+
+impl<R: HidReportSource> HidJoystick<R> {
+    pub fn new(sdl: Box<dyn JoystickHandle>, source: R, layout: HidLayout) -> Self {
+        Self {
+            sdl,
+            source,
+            buttons: vec![false; layout.count(HidControl::Button)],
+            axes: vec![0; layout.count(HidControl::Axis)],
+            hats: vec![HatState::Centered; layout.count(HidControl::Hat)],
+            layout,
+        }
+    }
+
+    fn apply_report(&mut self, report: &[u8]) {
+        let (report_id, body) = match self.layout.uses_report_ids {
+            true => match report.split_first() {
+                Some((id, body)) => (*id, body),
+                None => return,
+            },
+            false => (0, report),
+        };
+
+        let (mut button, mut axis, mut hat) = (0, 0, 0);
+        for field in self.layout.fields.iter() {
+            let index = match field.control {
+                HidControl::Button => &mut button,
+                HidControl::Axis => &mut axis,
+                HidControl::Hat => &mut hat,
+            };
+            let current = *index;
+            *index += 1;
+            if field.report_id != report_id {
+                continue;
+            }
+            let Some(raw) = field.read(body) else {
+                continue;
+            };
+            match field.control {
+                HidControl::Button => self.buttons[current] = raw != 0,
+                HidControl::Axis => self.axes[current] = field.axis_value(raw),
+                HidControl::Hat => self.hats[current] = field.hat_value(raw),
+            }
+        }
+    }
+}
+
+impl<R: HidReportSource> JoystickHandle for HidJoystick<R> {
+    fn name(&self) -> String {
+        self.sdl.name()
+    }
+
+    fn guid(&self) -> String {
+        self.sdl.guid()
+    }
+
+    fn instance_id(&self) -> u32 {
+        self.sdl.instance_id()
+    }
+
+    fn num_buttons(&self) -> u32 {
+        self.buttons.len() as u32
+    }
+
+    fn num_axes(&self) -> u32 {
+        self.axes.len() as u32
+    }
+
+    fn num_hats(&self) -> u32 {
+        self.hats.len() as u32
+    }
+
+    fn button(&self, index: u32) -> Result<bool, Error> {
+        self.buttons
+            .get(index as usize)
+            .copied()
+            .ok_or_else(|| format!("HID button index {index} out of range").into())
+    }
+
+    fn axis(&self, index: u32) -> Result<i16, Error> {
+        self.axes
+            .get(index as usize)
+            .copied()
+            .ok_or_else(|| format!("HID axis index {index} out of range").into())
+    }
+
+    fn hat(&self, index: u32) -> Result<HatState, Error> {
+        self.hats
+            .get(index as usize)
+            .copied()
+            .ok_or_else(|| format!("HID hat index {index} out of range").into())
+    }
+
+    fn power_level(&self) -> Result<PowerLevel, Error> {
+        self.sdl.power_level()
+    }
+
+    fn poll(&mut self) -> Result<(), Error> {
+        let mut buf = [0u8; 256];
+        for _ in 0..MAX_REPORTS_PER_POLL {
+            let len = self.source.read(&mut buf)?;
+            if len == 0 {
+                break;
+            }
+            self.apply_report(&buf[..len.min(buf.len())]);
+        }
+        Ok(())
+    }
+
+    fn backend(&self) -> DeviceBackend {
+        DeviceBackend::Hid
+    }
+}
+
+/// Replaces the SDL state of the device with its raw HID reports. Falls back to `sdl` if the
+/// device can't be opened or its descriptor has no usable controls.
+pub fn open(sdl: Box<dyn JoystickHandle>, occurrence: u32) -> Box<dyn JoystickHandle> {
+    let name = sdl.name();
+    match open_device(&sdl.guid(), occurrence) {
+        Ok(Some((source, layout))) => {
+            info!(
+                "Reading {name} through raw HID: {} buttons, {} axes, {} hats",
+                layout.count(HidControl::Button),
+                layout.count(HidControl::Axis),
+                layout.count(HidControl::Hat)
+            );
+            Box::new(HidJoystick::new(sdl, source, layout))
+        }
+        Ok(None) => {
+            warn!("Raw HID is not available in this build, reading {name} through SDL");
+            sdl
+        }
+        Err(e) => {
+            warn!("Failed to open {name} through raw HID, reading it through SDL. Reason: {e}");
+            sdl
+        }
+    }
+}
+
+#[cfg(feature = "hid")]
+impl HidReportSource for hidapi::HidDevice {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        hidapi::HidDevice::read(self, buf).map_err(|e| format!("hidapi: {e}").into())
+    }
+}
+
+/// Opens the `occurrence`th joystick collection with the vendor and product id of the GUID
+#[cfg(feature = "hid")]
+fn open_device(
+    guid: &str,
+    occurrence: u32,
+) -> Result<Option<(hidapi::HidDevice, HidLayout)>, Error> {
+    use super::hid_descriptor;
+
+    let (vendor, product) = vendor_product_from_guid(guid)
+        .ok_or_else(|| format!("GUID {guid} has no USB vendor and product id"))?;
+    let api = hidapi::HidApi::new().map_err(|e| format!("hidapi: {e}"))?;
+    let info = api
+        .device_list()
+        .filter(|d| d.vendor_id() == vendor && d.product_id() == product)
+        .filter(|d| d.usage_page() == 0x01 && matches!(d.usage(), 0x04 | 0x05 | 0x08))
+        .nth(occurrence.saturating_sub(1) as usize)
+        .ok_or_else(|| format!("no HID joystick {vendor:04x}:{product:04x}"))?;
+
+    let device = info.open_device(&api).map_err(|e| format!("hidapi: {e}"))?;
+    device
+        .set_blocking_mode(false)
+        .map_err(|e| format!("hidapi: {e}"))?;
+    let mut descriptor = [0u8; 4096];
+    let len = device
+        .get_report_descriptor(&mut descriptor)
+        .map_err(|e| format!("hidapi: {e}"))?;
+    let layout = hid_descriptor::parse(&descriptor[..len])?;
+    if layout.fields.is_empty() {
+        return Err(format!("no buttons, axes or hats in the report descriptor of {guid}").into());
+    }
+
+    Ok(Some((device, layout)))
+}
+
+#[cfg(not(feature = "hid"))]
+fn open_device(_guid: &str, _occurrence: u32) -> Result<Option<(NoHid, HidLayout)>, Error> {
+    Ok(None)
+}
+
+/// Report source of builds without the `hid` feature
+#[cfg(not(feature = "hid"))]
+struct NoHid;
+
+#[cfg(not(feature = "hid"))]
+impl HidReportSource for NoHid {
+    fn read(&mut self, _buf: &mut [u8]) -> Result<usize, Error> {
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use sdl2::joystick::HatState;
+
+    use crate::{
+        error::Error,
+        input::{
+            backend::{fake::FakeJoystick, JoystickHandle},
+            hid::{vendor_product_from_guid, HidJoystick, HidReportSource},
+            hid_descriptor::{HidControl, HidField, HidLayout},
+        },
+    };
+
+    struct Reports(VecDeque<Vec<u8>>);
+
+    impl HidReportSource for Reports {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+            match self.0.pop_front() {
+                Some(report) => {
+                    buf[..report.len()].copy_from_slice(&report);
+                    Ok(report.len())
+                }
+                None => Ok(0),
+            }
+        }
+    }
+
+    fn field(control: HidControl, report_id: u8, bit_offset: u32, bit_size: u32) -> HidField {
+        HidField {
+            control,
+            report_id,
+            bit_offset,
+            bit_size,
+            logical_min: 0,
+            logical_max: match control {
+                HidControl::Button => 1,
+                HidControl::Axis => 255,
+                HidControl::Hat => 7,
+            },
+        }
+    }
+
+    #[test]
+    fn guid_vendor_and_product() {
+        assert_eq!(
+            vendor_product_from_guid("030000005e0400008e02000000007200"),
+            Some((0x045e, 0x028e))
+        );
+        assert_eq!(vendor_product_from_guid("stick"), None);
+        assert_eq!(
+            vendor_product_from_guid("00000000000000000000000000000000"),
+            None
+        );
+    }
+
+    #[test]
+    fn reports_update_only_their_own_fields() {
+        // Report 1: 40 buttons, report 2: one axis and one hat
+        let mut fields: Vec<HidField> = (0..40)
+            .map(|b| field(HidControl::Button, 1, b, 1))
+            .collect();
+        fields.push(field(HidControl::Axis, 2, 0, 8));
+        fields.push(field(HidControl::Hat, 2, 8, 4));
+        let layout = HidLayout {
+            fields,
+            uses_report_ids: true,
+        };
+        let reports = Reports(VecDeque::from([
+            vec![1, 0, 0, 0, 0, 0b1000_0000],
+            vec![2, 255, 4],
+        ]));
+        let sdl = Box::new(FakeJoystick::new("box", 32, 0, 0));
+        let mut joystick = HidJoystick::new(sdl, reports, layout);
+        assert_eq!(joystick.num_buttons(), 40);
+
+        joystick.poll().unwrap();
+        assert!(joystick.button(39).unwrap());
+        assert!(!joystick.button(0).unwrap());
+        assert_eq!(joystick.axis(0).unwrap(), i16::MAX);
+        assert_eq!(joystick.hat(0).unwrap(), HatState::Down);
+        assert!(joystick.button(40).is_err());
+    }
+}
@@ -0,0 +1,411 @@
+//! Just enough of the HID report descriptor format to read joystick input reports: buttons,
+//! axes and hat switches of the input reports. Output and feature reports are skipped.
+
+use sdl2::joystick::HatState;
+
+const PAGE_GENERIC_DESKTOP: u32 = 0x01;
+const PAGE_SIMULATION: u32 = 0x02;
+const PAGE_BUTTON: u32 = 0x09;
+const USAGE_HAT_SWITCH: u32 = 0x39;
+/// Largest input report read, descriptors claiming more are malformed
+const MAX_REPORT_BITS: u32 = 4096 * 8;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum HidControl {
+    Button,
+    Axis,
+    Hat,
+}
+
+/// One value in an input report
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct HidField {
+    pub control: HidControl,
+    pub report_id: u8,
+    /// Offset after the report id byte
+    pub bit_offset: u32,
+    pub bit_size: u32,
+    pub logical_min: i32,
+    pub logical_max: i32,
+}
+
+impl HidField {
+    /// Raw value of the field in `report`, which starts after the report id byte
+    pub fn read(&self, report: &[u8]) -> Option<i32> {
+        if self.bit_size == 0 || self.bit_size > 32 {
+            return None;
+        }
+        let mut value: u32 = 0;
+        for bit in 0..self.bit_size {
+            let position = self.bit_offset + bit;
+            let byte = report.get(position as usize / 8)?;
+            if byte >> (position % 8) & 1 == 1 {
+                value |= 1 << bit;
+            }
+        }
+        match self.logical_min < 0 && self.bit_size < 32 && value >> (self.bit_size - 1) & 1 == 1 {
+            true => Some((value | !0 << self.bit_size) as i32),
+            false => Some(value as i32),
+        }
+    }
+
+    /// Logical range scaled to the SDL axis range
+    pub fn axis_value(&self, raw: i32) -> i16 {
+        let (min, max) = (self.logical_min as i64, self.logical_max as i64);
+        if max <= min {
+            return 0;
+        }
+        let clamped = (raw as i64).clamp(min, max);
+        (i16::MIN as i64 + (clamped - min) * 65535 / (max - min)) as i16
+    }
+
+    /// Hats report one step per position clockwise from north, out of range values are centered.
+    pub fn hat_value(&self, raw: i32) -> HatState {
+        let positions = self.logical_max - self.logical_min + 1;
+        if raw < self.logical_min || raw > self.logical_max || !matches!(positions, 4 | 8) {
+            return HatState::Centered;
+        }
+        let angle = (raw - self.logical_min) * 360 / positions;
+        match angle {
+            0 => HatState::Up,
+            45 => HatState::RightUp,
+            90 => HatState::Right,
+            135 => HatState::RightDown,
+            180 => HatState::Down,
+            225 => HatState::LeftDown,
+            270 => HatState::Left,
+            _ => HatState::LeftUp,
+        }
+    }
+}
+
+/// Input report layout of one device
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct HidLayout {
+    pub fields: Vec<HidField>,
+    /// Reports start with a report id byte
+    pub uses_report_ids: bool,
+}
+
+impl HidLayout {
+    pub fn count(&self, control: HidControl) -> usize {
+        self.fields.iter().filter(|f| f.control == control).count()
+    }
+
+    /// Fields of `control` in report order
+    pub fn fields_of(&self, control: HidControl) -> impl Iterator<Item = &HidField> {
+        self.fields.iter().filter(move |f| f.control == control)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct GlobalState {
+    usage_page: u32,
+    logical_min: i32,
+    logical_max: i32,
+    report_size: u32,
+    report_count: u32,
+    report_id: u8,
+}
+
+/// Usages with the usage page in the upper 16 bits
+#[derive(Debug, Default)]
+struct LocalState {
+    usages: Vec<u32>,
+    usage_min: Option<u32>,
+    usage_max: Option<u32>,
+}
+
+impl LocalState {
+    /// Usage of the `index`th value of a main item, the last usage repeats
+    fn usage(&self, index: u32) -> Option<u32> {
+        if let Some(usage) = self.usages.get(index as usize) {
+            return Some(*usage);
+        }
+        if let (Some(min), Some(max)) = (self.usage_min, self.usage_max) {
+            return Some((min + index).min(max));
+        }
+        self.usages.last().copied()
+    }
+}
+
+fn item_data(data: &[u8]) -> u32 {
+    data.iter().enumerate().fold(0, |value, (index, byte)| {
+        value | (*byte as u32) << (8 * index)
+    })
+}
+
+/// Sign-extended item data, used for the logical range
+fn item_data_signed(data: &[u8]) -> i32 {
+    match data.len() {
+        1 => data[0] as i8 as i32,
+        2 => i16::from_le_bytes([data[0], data[1]]) as i32,
+        4 => i32::from_le_bytes([data[0], data[1], data[2], data[3]]),
+        _ => 0,
+    }
+}
+
+fn control_of(usage_page: u32, usage: u32) -> Option<HidControl> {
+    match (usage_page, usage) {
+        (PAGE_BUTTON, _) => Some(HidControl::Button),
+        (PAGE_GENERIC_DESKTOP, USAGE_HAT_SWITCH) => Some(HidControl::Hat),
+        // X, Y, Z, Rx, Ry, Rz, slider, dial, wheel
+        (PAGE_GENERIC_DESKTOP, 0x30..=0x38) => Some(HidControl::Axis),
+        // Rudder, throttle, accelerator, brake, clutch, steering and friends
+        (PAGE_SIMULATION, 0xB0..=0xCC) => Some(HidControl::Axis),
+        _ => None,
+    }
+}
+
+/// Input fields of a report descriptor. Constant fields are padding and only advance the offset.
+/// Array fields report the indices of the pressed usages instead of values and are skipped too.
+pub fn parse(descriptor: &[u8]) -> Result<HidLayout, String> {
+    let mut layout = HidLayout::default();
+    let mut global = GlobalState::default();
+    let mut stack: Vec<GlobalState> = Vec::new();
+    let mut local = LocalState::default();
+    let mut offsets: Vec<(u8, u32)> = Vec::new();
+
+    let mut position = 0;
+    while position < descriptor.len() {
+        let prefix = descriptor[position];
+        if prefix == 0xFE {
+            // Long item, size in the next byte
+            let size = *descriptor.get(position + 1).ok_or("truncated long item")? as usize;
+            position += 3 + size;
+            continue;
+        }
+
+        let item_start = position;
+        let size = match prefix & 0b11 {
+            3 => 4,
+            size => size as usize,
+        };
+        let data = descriptor
+            .get(position + 1..position + 1 + size)
+            .ok_or_else(|| format!("truncated item at byte {position}"))?;
+        position += 1 + size;
+
+        let tag = prefix >> 4;
+        match (prefix >> 2) & 0b11 {
+            // Main
+            0 => {
+                if tag == 0x8 {
+                    let slot = match offsets.iter().position(|(id, _)| *id == global.report_id) {
+                        Some(slot) => slot,
+                        None => {
+                            offsets.push((global.report_id, 0));
+                            offsets.len() - 1
+                        }
+                    };
+                    let offset = &mut offsets[slot].1;
+                    global
+                        .report_size
+                        .checked_mul(global.report_count)
+                        .and_then(|bits| offset.checked_add(bits))
+                        .filter(|end| *end <= MAX_REPORT_BITS)
+                        .ok_or_else(|| {
+                            format!("input item at byte {item_start} is larger than a report")
+                        })?;
+                    let flags = item_data(data);
+                    let (constant, variable) = (flags & 1 == 1, flags & 0b10 != 0);
+                    for index in 0..global.report_count {
+                        let control = match constant || !variable {
+                            true => None,
+                            false => local
+                                .usage(index)
+                                .and_then(|usage| control_of(usage >> 16, usage & 0xFFFF)),
+                        };
+                        if let Some(control) = control {
+                            layout.fields.push(HidField {
+                                control,
+                                report_id: global.report_id,
+                                bit_offset: *offset,
+                                bit_size: global.report_size,
+                                logical_min: global.logical_min,
+                                logical_max: global.logical_max,
+                            });
+                        }
+                        *offset += global.report_size;
+                    }
+                }
+                local = LocalState::default();
+            }
+            // Global
+            1 => match tag {
+                0x0 => global.usage_page = item_data(data),
+                0x1 => global.logical_min = item_data_signed(data),
+                0x2 => {
+                    global.logical_max = match global.logical_min < 0 {
+                        true => item_data_signed(data),
+                        false => item_data(data) as i32,
+                    }
+                }
+                0x7 => global.report_size = item_data(data),
+                0x8 => {
+                    global.report_id = item_data(data) as u8;
+                    layout.uses_report_ids = true;
+                }
+                0x9 => global.report_count = item_data(data),
+                0xA => stack.push(global),
+                0xB => global = stack.pop().ok_or("pop without push")?,
+                _ => {}
+            },
+            // Local
+            2 => {
+                // Extended usages carry their page, short ones use the current page
+                let usage = match size {
+                    4 => item_data(data),
+                    _ => global.usage_page << 16 | item_data(data),
+                };
+                match tag {
+                    0x0 => local.usages.push(usage),
+                    0x1 => local.usage_min = Some(usage),
+                    0x2 => local.usage_max = Some(usage),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(layout)
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::joystick::HatState;
+
+    use crate::input::hid_descriptor::{parse, HidControl};
+
+    /// Report id 1: 64 buttons, a 4 bit hat with 4 bits padding, X/Y as 16 bit and a signed 8 bit
+    /// rudder
+    const BUTTON_BOX: &[u8] = &[
+        0x05, 0x01, // Usage page (generic desktop)
+        0x09, 0x04, // Usage (joystick)
+        0xA1, 0x01, // Collection (application)
+        0x85, 0x01, //   Report id (1)
+        0x05, 0x09, //   Usage page (button)
+        0x19, 0x01, //   Usage minimum (1)
+        0x29, 0x40, //   Usage maximum (64)
+        0x15, 0x00, //   Logical minimum (0)
+        0x25, 0x01, //   Logical maximum (1)
+        0x75, 0x01, //   Report size (1)
+        0x95, 0x40, //   Report count (64)
+        0x81, 0x02, //   Input (data, variable, absolute)
+        0x05, 0x01, //   Usage page (generic desktop)
+        0x09, 0x39, //   Usage (hat switch)
+        0x25, 0x07, //   Logical maximum (7)
+        0x75, 0x04, //   Report size (4)
+        0x95, 0x01, //   Report count (1)
+        0x81, 0x42, //   Input (data, variable, absolute, null state)
+        0x81, 0x01, //   Input (constant), 4 bit padding
+        0x09, 0x30, //   Usage (X)
+        0x09, 0x31, //   Usage (Y)
+        0x27, 0xFF, 0xFF, 0x00, 0x00, // Logical maximum (65535)
+        0x75, 0x10, //   Report size (16)
+        0x95, 0x02, //   Report count (2)
+        0x81, 0x02, //   Input (data, variable, absolute)
+        0x05, 0x02, //   Usage page (simulation)
+        0x09, 0xBA, //   Usage (rudder)
+        0x15, 0x81, //   Logical minimum (-127)
+        0x25, 0x7F, //   Logical maximum (127)
+        0x75, 0x08, //   Report size (8)
+        0x95, 0x01, //   Report count (1)
+        0x81, 0x02, //   Input (data, variable, absolute)
+        0xC0, // End collection
+    ];
+
+    #[test]
+    fn parses_controls_beyond_sdl_limits() {
+        let layout = parse(BUTTON_BOX).unwrap();
+        assert!(layout.uses_report_ids);
+        assert_eq!(layout.count(HidControl::Button), 64);
+        assert_eq!(layout.count(HidControl::Hat), 1);
+        assert_eq!(layout.count(HidControl::Axis), 3);
+
+        let axes: Vec<u32> = layout
+            .fields_of(HidControl::Axis)
+            .map(|f| f.bit_offset)
+            .collect();
+        assert_eq!(axes, vec![72, 88, 104]);
+    }
+
+    #[test]
+    fn reads_report_values() {
+        let layout = parse(BUTTON_BOX).unwrap();
+        let mut report = [0u8; 14];
+        report[7] = 0b1000_0000; // button 64
+        report[8] = 0x02; // hat east
+        report[9..11].copy_from_slice(&0xFFFFu16.to_le_bytes());
+        report[11..13].copy_from_slice(&0x8000u16.to_le_bytes());
+        report[13] = (-127i8) as u8;
+
+        let buttons: Vec<i32> = layout
+            .fields_of(HidControl::Button)
+            .map(|f| f.read(&report).unwrap())
+            .collect();
+        assert_eq!(buttons.iter().sum::<i32>(), 1);
+        assert_eq!(buttons[63], 1);
+
+        let hat = layout.fields_of(HidControl::Hat).next().unwrap();
+        assert_eq!(hat.hat_value(hat.read(&report).unwrap()), HatState::Right);
+        assert_eq!(hat.hat_value(15), HatState::Centered);
+
+        let axes: Vec<i16> = layout
+            .fields_of(HidControl::Axis)
+            .map(|f| f.axis_value(f.read(&report).unwrap()))
+            .collect();
+        assert_eq!(axes, vec![i16::MAX, 0, i16::MIN]);
+    }
+
+    #[test]
+    fn truncated_descriptor_is_an_error() {
+        assert!(parse(&BUTTON_BOX[..BUTTON_BOX.len() - 3]).is_ok());
+        assert!(parse(&[0x05, 0x01, 0x26, 0xFF]).is_err());
+    }
+
+    #[test]
+    fn oversized_input_item_is_an_error() {
+        let huge_count = [
+            0x05, 0x09, // Usage page (button)
+            0x09, 0x01, // Usage (1)
+            0x75, 0x01, // Report size (1)
+            0x97, 0xFF, 0xFF, 0xFF, 0xFF, // Report count (0xFFFFFFFF)
+            0x81, 0x02, // Input (data, variable, absolute)
+        ];
+        assert!(parse(&huge_count).is_err());
+
+        let overflowing_offset = [
+            0x05, 0x09, // Usage page (button)
+            0x09, 0x01, // Usage (1)
+            0x77, 0xFF, 0xFF, 0x00, 0x00, // Report size (65535)
+            0x95, 0x01, // Report count (1)
+            0x81, 0x02, // Input (data, variable, absolute)
+        ];
+        assert!(parse(&overflowing_offset).is_err());
+    }
+
+    #[test]
+    fn array_inputs_only_advance_the_offset() {
+        let descriptor = [
+            0x05, 0x09, // Usage page (button)
+            0x19, 0x01, // Usage minimum (1)
+            0x29, 0x08, // Usage maximum (8)
+            0x75, 0x08, // Report size (8)
+            0x95, 0x02, // Report count (2)
+            0x81, 0x00, // Input (data, array, absolute)
+            0x05, 0x01, // Usage page (generic desktop)
+            0x09, 0x30, // Usage (X)
+            0x95, 0x01, // Report count (1)
+            0x81, 0x02, // Input (data, variable, absolute)
+        ];
+        let layout = parse(&descriptor).unwrap();
+        assert_eq!(layout.count(HidControl::Button), 0);
+        let axes: Vec<u32> = layout
+            .fields_of(HidControl::Axis)
+            .map(|f| f.bit_offset)
+            .collect();
+        assert_eq!(axes, vec![16]);
+    }
+}
@@ -0,0 +1,137 @@
+use egui::{Grid, RichText, Ui};
+
+use super::{Input, GUID_OCCURRENCE_SEPARATOR};
+use crate::ui_data::Toast;
+
+/// SDL bus type of USB devices, the first 16 bits of the GUID
+const SDL_BUS_USB: u16 = 0x0003;
+
+/// USB vendor and product id encoded in an SDL joystick GUID.
+///
+/// SDL builds GUIDs of USB devices as bus, crc, vendor, 0, product, 0, version, driver data
+/// (all little endian u16). Other GUIDs (e.g. name based ones) don't carry the ids.
+pub fn usb_ids_from_guid(guid: &str) -> Option<(u16, u16)> {
+    let guid = guid.split(GUID_OCCURRENCE_SEPARATOR).next()?;
+    if guid.len() != 32 || !guid.is_ascii() {
+        return None;
+    }
+
+    let word = |index: usize| {
+        let hex = &guid[index * 4..index * 4 + 4];
+        let bytes = u16::from_str_radix(hex, 16).ok()?.to_be_bytes();
+        Some(u16::from_le_bytes(bytes))
+    };
+    if word(0)? != SDL_BUS_USB || word(3)? != 0 || word(5)? != 0 {
+        return None;
+    }
+
+    match (word(2)?, word(4)?) {
+        (0, 0) => None,
+        ids => Some(ids),
+    }
+}
+
+/// Capability summary of a device, e.g. "32 buttons, 8 axes, 1 hats"
+pub fn capability_summary(num_buttons: usize, num_axes: usize, num_hats: usize) -> String {
+    format!("{num_buttons} buttons, {num_axes} axes, {num_hats} hats")
+}
+
+/// Monospace value with a button that copies it to the clipboard.
+pub fn copy_label(ui: &mut Ui, toast: &mut Option<Toast>, what: &str, value: &str) {
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(value).monospace());
+        if ui
+            .small_button("📋")
+            .on_hover_text(format!("Copy {what}"))
+            .clicked()
+        {
+            ui.output_mut(|o| o.copied_text = value.to_string());
+            let time = ui.input(|i| i.time);
+            *toast = Some(Toast::new(format!("Copied {what}"), time));
+        }
+    });
+}
+
+/// Identifiers of all connected devices as they appear in config files and bug reports.
+pub fn identifiers_grid(ui: &mut Ui, input: &Input, toast: &mut Option<Toast>) {
+    Grid::new("PhysicalIdentifiers")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            for device in input.physical_devices() {
+                ui.label(RichText::new(device.name()).strong());
+                ui.end_row();
+
+                ui.label("GUID:");
+                copy_label(ui, toast, "GUID", &device.guid);
+                ui.end_row();
+
+                ui.label("SDL instance:");
+                copy_label(
+                    ui,
+                    toast,
+                    "SDL instance id",
+                    &device.instance_id().to_string(),
+                );
+                ui.end_row();
+
+                ui.label("USB VID:PID:");
+                match usb_ids_from_guid(&device.guid) {
+                    Some((vid, pid)) => {
+                        copy_label(ui, toast, "VID:PID", &format!("{vid:04x}:{pid:04x}"));
+                    }
+                    None => {
+                        ui.label("unknown");
+                    }
+                }
+                ui.end_row();
+
+                ui.label("Power:");
+                ui.label(device.power.describe());
+                ui.end_row();
+            }
+        });
+
+    ui.separator();
+
+    Grid::new("VirtualIdentifiers")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            for device in input.virtual_devices() {
+                ui.label(RichText::new(device.name()).strong());
+                ui.end_row();
+
+                ui.label("vJoy id:");
+                copy_label(ui, toast, "vJoy id", &device.id.to_string());
+                ui.end_row();
+
+                ui.label("Capabilities:");
+                let summary =
+                    capability_summary(device.num_buttons(), device.num_axes(), device.num_hats());
+                copy_label(ui, toast, "capabilities", &summary);
+                ui.end_row();
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::identifiers::usb_ids_from_guid;
+
+    #[test]
+    fn usb_ids_are_read_from_the_guid() {
+        assert_eq!(
+            usb_ids_from_guid("0300f80034120000adbe000000000000"),
+            Some((0x1234, 0xbead))
+        );
+        assert_eq!(
+            usb_ids_from_guid("030000006d0400001dc2000000000000#2"),
+            Some((0x046d, 0xc21d))
+        );
+        // Bluetooth bus and name based GUIDs don't carry usable ids
+        assert_eq!(usb_ids_from_guid("050000006d0400001dc2000000000000"), None);
+        assert_eq!(usb_ids_from_guid("4c6f6769746563682045787472656d65"), None);
+        assert_eq!(usb_ids_from_guid("not a guid"), None);
+    }
+}
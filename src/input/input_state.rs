@@ -1,23 +1,161 @@
-use crate::error::Error;
-use sdl2::joystick::{HatState, Joystick};
+use std::fmt::Display;
+
+use crate::{
+    error::Error,
+    input::{
+        backend::JoystickHandle,
+        device_transform::DeviceTransform,
+        event_console::{ControlChange, ControlKind},
+    },
+};
+use sdl2::joystick::HatState;
+
+/// Physical axis values within this distance of the center/end count as resting there
+const AXIS_REST_TOLERANCE: i32 = 4096;
+
+/// Axis movement smaller than this since the last activity is treated as noise
+const ACTIVITY_AXIS_TOLERANCE: i32 = 512;
+
+/// Classification of a physical axis by its rest position at open time.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AxisKind {
+    /// Rests at the center, e.g. stick X/Y
+    Stick,
+    /// Rests at one end of its range, e.g. gamepad triggers
+    Trigger { rest_at_max: bool },
+    /// Rests anywhere in between, e.g. throttles and sliders
+    Slider,
+}
+
+impl AxisKind {
+    /// SDL doesn't report the axis type, the value at open time is used as a heuristic.
+    pub fn classify(initial_value: i32) -> Self {
+        if initial_value.abs() <= AXIS_REST_TOLERANCE {
+            AxisKind::Stick
+        } else if initial_value <= i16::MIN as i32 + AXIS_REST_TOLERANCE {
+            AxisKind::Trigger { rest_at_max: false }
+        } else if initial_value >= i16::MAX as i32 - AXIS_REST_TOLERANCE {
+            AxisKind::Trigger { rest_at_max: true }
+        } else {
+            AxisKind::Slider
+        }
+    }
+}
+
+impl Display for AxisKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AxisKind::Stick => f.write_str("stick"),
+            AxisKind::Trigger { .. } => f.write_str("trigger"),
+            AxisKind::Slider => f.write_str("slider"),
+        }
+    }
+}
+
+/// Last time any button, axis or hat of a device changed.
+#[derive(Debug, Clone, Default)]
+pub struct Activity {
+    reference: Option<(Vec<bool>, Vec<i32>, Vec<i32>)>,
+    last: Option<f64>,
+}
+
+impl Activity {
+    /// The first update only records the reference values, so connecting a device isn't activity.
+    pub fn update(&mut self, buttons: &[bool], axes: &[i32], hats: &[i32], time: f64) {
+        let changed = match &self.reference {
+            None => false,
+            Some((ref_buttons, ref_axes, ref_hats)) => {
+                ref_buttons.as_slice() != buttons
+                    || ref_hats.as_slice() != hats
+                    || ref_axes.len() != axes.len()
+                    || ref_axes.iter().zip(axes).any(|(reference, value)| {
+                        (reference - value).abs() > ACTIVITY_AXIS_TOLERANCE
+                    })
+            }
+        };
+
+        if changed {
+            self.last = Some(time);
+        }
+        if changed || self.reference.is_none() {
+            self.reference = Some((buttons.to_vec(), axes.to_vec(), hats.to_vec()));
+        }
+    }
+
+    pub fn last(&self) -> Option<f64> {
+        self.last
+    }
+
+    /// True if there was activity within the last `timeout` seconds.
+    pub fn is_active_within(&self, timeout: f64, time: f64) -> bool {
+        self.last.is_some_and(|last| time - last <= timeout)
+    }
+}
+
+/// Lowest and highest value per axis since the start or the last reset, to check whether a
+/// control reaches full deflection
+#[derive(Debug, Clone, Default)]
+pub struct AxisRanges {
+    ranges: Vec<Option<(i32, i32)>>,
+}
+
+impl AxisRanges {
+    pub fn update(&mut self, axes: &[i32]) {
+        self.ranges.resize(axes.len(), None);
+        for (range, value) in self.ranges.iter_mut().zip(axes) {
+            *range = Some(match range {
+                Some((min, max)) => ((*min).min(*value), (*max).max(*value)),
+                None => (*value, *value),
+            });
+        }
+    }
+
+    /// Observed (min, max) of the axis, `None` before its first update
+    pub fn get(&self, index: usize) -> Option<(i32, i32)> {
+        self.ranges.get(index).copied().flatten()
+    }
+
+    pub fn reset(&mut self) {
+        self.ranges.clear();
+    }
+}
 
 pub struct InputState {
     buttons: Vec<bool>,
+    /// Axes after the device transform
     axes: Vec<i32>,
+    raw_axes: Vec<i32>,
+    axis_kinds: Vec<AxisKind>,
+    raw_axis_kinds: Vec<AxisKind>,
     hats: Vec<i32>,
+    activity: Activity,
+    transform: DeviceTransform,
+    axis_ranges: AxisRanges,
+    /// False until the first update, whose values are no changes
+    polled: bool,
 }
 
 impl InputState {
     #[profiling::function]
-    pub fn new(device: &Joystick) -> Self {
+    pub fn new(device: &dyn JoystickHandle) -> Self {
         let buttons = (0..device.num_buttons()).map(|_| bool::default()).collect();
-        let axes = (0..device.num_axes()).map(|_| 0).collect();
+        let axes: Vec<i32> = (0..device.num_axes()).map(|_| 0).collect();
+        let axis_kinds: Vec<AxisKind> = (0..device.num_axes())
+            .map(|index| AxisKind::classify(device.axis(index).unwrap_or_default() as i32))
+            .collect();
         let hat_switches = (0..device.num_hats()).map(|_| -1).collect();
 
         Self {
             buttons,
+            raw_axes: axes.clone(),
             axes,
+            raw_axis_kinds: axis_kinds.clone(),
+            axis_kinds,
             hats: hat_switches,
+            activity: Activity::default(),
+            transform: DeviceTransform::default(),
+            axis_ranges: AxisRanges::default(),
+            polled: false,
         }
     }
 
@@ -36,11 +174,42 @@ impl InputState {
         self.axes.iter()
     }
 
+    /// Axes as reported by SDL, before the device transform
+    #[profiling::function]
+    pub fn raw_axes(&self) -> std::slice::Iter<i32> {
+        self.raw_axes.iter()
+    }
+
     #[profiling::function]
     pub fn num_axes(&self) -> usize {
         self.axes.len()
     }
 
+    #[profiling::function]
+    pub fn axis_kinds(&self) -> std::slice::Iter<AxisKind> {
+        self.axis_kinds.iter()
+    }
+
+    /// Classifies the axes again from settled values, e.g. after a device warm-up.
+    pub fn reclassify_axes(&mut self, values: &[i32]) {
+        self.raw_axis_kinds = values.iter().map(|v| AxisKind::classify(*v)).collect();
+        self.axis_kinds = self.transform.apply_kinds(&self.raw_axis_kinds);
+    }
+
+    pub fn transform(&self) -> &DeviceTransform {
+        &self.transform
+    }
+
+    /// Applies from the next update on
+    pub fn set_transform(&mut self, transform: &DeviceTransform) {
+        if self.transform != *transform {
+            self.transform = transform.clone();
+            self.axis_kinds = self.transform.apply_kinds(&self.raw_axis_kinds);
+            // Ranges seen through the old transform don't apply to the new one
+            self.axis_ranges.reset();
+        }
+    }
+
     #[profiling::function]
     pub fn hats(&self) -> std::slice::Iter<i32> {
         self.hats.iter()
@@ -51,18 +220,73 @@ impl InputState {
         self.hats.len()
     }
 
+    pub fn activity(&self) -> &Activity {
+        &self.activity
+    }
+
+    /// Ranges of the axes after the device transform
+    pub fn axis_ranges(&self) -> &AxisRanges {
+        &self.axis_ranges
+    }
+
+    pub fn reset_axis_ranges(&mut self) {
+        self.axis_ranges.reset();
+    }
+
+    /// Overwrites one control until the next update, e.g. with the synthetic input of a test
+    /// script. Axes are taken as already transformed. False if the device has no such control.
+    pub fn inject(&mut self, kind: ControlKind, id: u32, value: i32) -> bool {
+        let Some(index) = id.checked_sub(1).map(|index| index as usize) else {
+            return false;
+        };
+        match kind {
+            ControlKind::Button => self.buttons.get_mut(index).map(|b| *b = value != 0),
+            ControlKind::Axis => self.axes.get_mut(index).map(|a| *a = value),
+            ControlKind::Hat => self.hats.get_mut(index).map(|h| *h = value),
+        }
+        .is_some()
+    }
+
+    /// Reads all controls of `device`. The changes against the previous values, with raw axes, are
+    /// appended to `changes` if given.
     #[profiling::function]
-    pub fn update(&mut self, device: &Joystick) -> Result<(), Error> {
+    pub fn update(
+        &mut self,
+        device: &dyn JoystickHandle,
+        time: f64,
+        mut changes: Option<&mut Vec<ControlChange>>,
+    ) -> Result<(), Error> {
+        if !self.polled {
+            self.polled = true;
+            changes = None;
+        }
+        let mut record = |kind, index: usize, old: i32, new: i32| {
+            if let Some(changes) = changes.as_deref_mut().filter(|_| old != new) {
+                changes.push(ControlChange {
+                    kind,
+                    index: index as u32 + 1,
+                    old,
+                    new,
+                });
+            }
+        };
+
         for (index, button) in self.buttons.iter_mut().enumerate() {
-            *button = device.button(index as u32)?;
+            let new = device.button(index as u32)?;
+            record(ControlKind::Button, index, *button as i32, new as i32);
+            *button = new;
         }
 
-        for (index, axis) in self.axes.iter_mut().enumerate() {
-            *axis = device.axis(index as u32)? as i32;
+        for (index, axis) in self.raw_axes.iter_mut().enumerate() {
+            let new = device.axis(index as u32)? as i32;
+            record(ControlKind::Axis, index, *axis, new);
+            *axis = new;
         }
+        self.transform.apply(&self.raw_axes, &mut self.axes);
+        self.axis_ranges.update(&self.axes);
 
         for (index, hat) in self.hats.iter_mut().enumerate() {
-            *hat = match device.hat(index as u32)? {
+            let new = match device.hat(index as u32)? {
                 HatState::Centered => -1,
                 HatState::Up => 0,
                 HatState::Right => 90,
@@ -72,9 +296,73 @@ impl InputState {
                 HatState::RightDown => 135,
                 HatState::LeftUp => 315,
                 HatState::LeftDown => 225,
-            }
+            };
+            record(ControlKind::Hat, index, *hat, new);
+            *hat = new;
         }
 
+        self.activity
+            .update(&self.buttons, &self.axes, &self.hats, time);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::input::input_state::{Activity, AxisKind, AxisRanges};
+
+    #[test]
+    fn classify_by_rest_position() {
+        assert_eq!(AxisKind::classify(0), AxisKind::Stick);
+        assert_eq!(AxisKind::classify(-128), AxisKind::Stick);
+        assert_eq!(
+            AxisKind::classify(-32768),
+            AxisKind::Trigger { rest_at_max: false }
+        );
+        assert_eq!(
+            AxisKind::classify(32767),
+            AxisKind::Trigger { rest_at_max: true }
+        );
+        assert_eq!(AxisKind::classify(16000), AxisKind::Slider);
+    }
+
+    #[test]
+    fn activity_times_out_and_reactivates() {
+        let mut activity = Activity::default();
+        activity.update(&[false], &[-32768], &[-1], 0.0);
+        assert_eq!(activity.last(), None);
+
+        // Axis noise below the tolerance, even when it drifts over many polls
+        for poll in 1..=10 {
+            activity.update(&[false], &[-32768 + poll * 40], &[-1], poll as f64);
+        }
+        assert_eq!(activity.last(), None);
+        activity.update(&[false], &[-32768 + 600], &[-1], 11.0);
+        assert_eq!(activity.last(), Some(11.0));
+
+        assert!(activity.is_active_within(5.0, 16.0));
+        assert!(!activity.is_active_within(5.0, 16.5));
+
+        activity.update(&[true], &[-32768 + 600], &[-1], 20.0);
+        assert!(activity.is_active_within(5.0, 20.0));
+        activity.update(&[true], &[-32768 + 600], &[90], 24.0);
+        assert!(activity.is_active_within(5.0, 28.0));
+    }
+
+    #[test]
+    fn axis_ranges_track_extremes_until_reset() {
+        let mut ranges = AxisRanges::default();
+        assert_eq!(ranges.get(0), None);
+
+        ranges.update(&[0, -32768]);
+        ranges.update(&[12000, 0]);
+        ranges.update(&[-500, 32767]);
+        assert_eq!(ranges.get(0), Some((-500, 12000)));
+        assert_eq!(ranges.get(1), Some((-32768, 32767)));
+        assert_eq!(ranges.get(2), None);
+
+        ranges.reset();
+        ranges.update(&[100, 0]);
+        assert_eq!(ranges.get(0), Some((100, 100)));
+    }
+}
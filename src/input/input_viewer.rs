@@ -1,209 +1,457 @@
-use crate::{auto_color, input::Input, ui_data::UIData};
+use super::{
+    axis_heatmap::heatmap_widget,
+    hat_history::hat_history_strip,
+    plot_legend::{plot_legend_widget, PlotLegend},
+};
+use crate::{
+    input::Input, rebind::hat_to_hat::vjoy_hat_degrees, ui_data::UIData, visualization::Palette,
+};
 use egui::{
-    plot::{Line, Plot, PlotBounds},
-    Image, RichText, ScrollArea, Sense, TextStyle, Ui, Widget, WidgetText,
+    plot::{Line, Plot, PlotBounds, PlotPoint, PlotPoints, PlotUi, Polygon, Text},
+    pos2, vec2, Align2, CollapsingHeader, Color32, Frame, Image, Rect, RichText, ScrollArea, Sense,
+    Stroke, TextStyle, TextureHandle, Ui, Vec2, Widget, WidgetText,
 };
-use vjoy::{ButtonState, FourWayHat, HatState};
+use std::{collections::HashMap, hash::Hash};
+use vjoy::ButtonState;
+
+const PLOT_HEIGHT: f32 = 200.0;
+const AXIS_BAR_SIZE: Vec2 = vec2(80.0, 6.0);
 
 #[profiling::function]
-pub(crate) fn build_ui(input: &Input, ui: &mut Ui, ui_data: &mut UIData) {
+pub(crate) fn build_ui(input: &mut Input, ui: &mut Ui, ui_data: &mut UIData) {
     ui.set_height(ui.available_height());
 
-    let mut selected_physical_devices = input.selected_physical_devices().peekable();
-    let mut selected_virtual_devices = input.selected_virtual_devices().peekable();
+    ui.checkbox(&mut ui_data.device_colors, "Device colors")
+        .on_hover_text("Tint each device's plot and alternate the section backgrounds");
 
-    if selected_physical_devices.peek().is_none() && selected_virtual_devices.peek().is_none() {
+    if ui_data.device_selection.is_empty() {
         ui.label("no active plot - select a device from the list");
         return;
     }
 
+    let (physical_min_bound, physical_max_bound) = input.get_plot_bounds_physical();
+    let (virtual_min_bound, virtual_max_bound) = input.get_plot_bounds_virtual();
+    let device_colors = ui_data.device_colors;
+    let visualization = ui_data.visualization.clone();
+    let dark_mode = ui.visuals().dark_mode;
+    let mut section = 0;
+
     ui.vertical(|ui| {
         ScrollArea::vertical().show(ui, |ui| {
-            for device in selected_physical_devices {
-                ui.label(device.name());
-
-                ui.separator();
-
-                ui.horizontal(|ui| {
-                    ui.vertical(|ui| {
-                        ui.set_min_width(80.0);
-                        for (index, axis_data) in device.input_state.axes().enumerate() {
+            for (device, labels) in input.selected_physical_devices_mut(&ui_data.device_selection) {
+                let color = device_color(&device.guid, visualization.palette);
+                section_frame(ui, section, device_colors).show(ui, |ui| {
+                    let transform = device.input_state.transform();
+                    let transformed = !transform.is_identity();
+                    ui.horizontal(|ui| {
+                        ui.label(device.name());
+                        if transformed {
                             ui.label(
-                                RichText::new(format!("Axis {}: {}", index + 1, axis_data))
-                                    .color(auto_color(index))
-                                    .strong(),
-                            );
+                                RichText::new("axis transform active")
+                                    .small()
+                                    .color(ui.visuals().warn_fg_color),
+                            )
+                            .on_hover_text("Axes are shown corrected, as rebinds see them");
                         }
                     });
 
                     ui.separator();
 
-                    ui.vertical(|ui| {
-                        if device.input_state.hats().len() > 0 {
-                            ui.set_max_width(ui.available_width() - 75.0);
-                        }
-                        ui.horizontal_wrapped(|ui| {
-                            for (index, button_state) in device.input_state.buttons().enumerate() {
-                                InputButton::new((index + 1).to_string(), *button_state).ui(ui);
+                    let mut reset_ranges = false;
+                    ui.horizontal(|ui| {
+                        ui.vertical(|ui| {
+                            ui.set_min_width(80.0);
+                            let ranges = device.input_state.axis_ranges();
+                            let axes = device.input_state.axes();
+                            let kinds = device.input_state.axis_kinds();
+                            let raw: Vec<i32> = device.input_state.raw_axes().copied().collect();
+                            for (index, (axis_data, kind)) in axes.zip(kinds).enumerate() {
+                                let response = ui.label(
+                                    RichText::new(format!(
+                                        "Axis {} ({kind}): {axis_data}",
+                                        index + 1
+                                    ))
+                                    .color(visualization.color(index))
+                                    .strong(),
+                                );
+                                let axis = transform.axis(index);
+                                if transformed {
+                                    let source = axis.source as usize;
+                                    let raw_value = raw.get(source - 1).copied().unwrap_or(0);
+                                    let inverted = if axis.invert { ", inverted" } else { "" };
+                                    response.on_hover_text(format!(
+                                        "Raw axis {source}{inverted}: {raw_value}"
+                                    ));
+                                }
+                                axis_range_bar(
+                                    ui,
+                                    *axis_data,
+                                    ranges.get(index),
+                                    visualization.color(index),
+                                );
+                            }
+                            if device.input_state.num_axes() > 0
+                                && ui
+                                    .small_button("Reset range")
+                                    .on_hover_text("Forget the lowest and highest values seen")
+                                    .clicked()
+                            {
+                                reset_ranges = true;
                             }
                         });
-                    });
 
-                    ui.separator();
+                        ui.separator();
 
-                    ui.vertical(|ui| {
                         ui.vertical(|ui| {
-                            for (index, hat_state) in device.input_state.hats().enumerate() {
-                                let rounded = if hat_state == &-1 {
-                                    -1
-                                } else {
-                                    (hat_state / 45) * 45
-                                };
-
-                                ui.vertical(|ui| {
-                                    if let Some(texture_handle) = ui_data.hat_switches.get(&rounded)
-                                    {
-                                        let color = auto_color(index);
-                                        ui.label(
-                                            RichText::new(format!("Hat {index}")).color(color),
-                                        );
-                                        ui.add_space(5.0);
-                                        ui.add(
-                                            Image::new(texture_handle.id(), [50.0, 50.0])
-                                                .tint(color),
-                                        );
-                                    }
-                                });
+                            if device.input_state.hats().len() > 0 {
+                                ui.set_max_width(ui.available_width() - 75.0);
                             }
+                            ui.horizontal_wrapped(|ui| {
+                                for (index, button_state) in
+                                    device.input_state.buttons().enumerate()
+                                {
+                                    let button = index as u32 + 1;
+                                    let text = match labels {
+                                        Some(labels) => labels.display(button),
+                                        None => button.to_string(),
+                                    };
+                                    InputButton::new(
+                                        text,
+                                        *button_state,
+                                        visualization.filled_buttons,
+                                    )
+                                    .ui(ui);
+                                }
+                            });
                         });
-                    });
-                });
 
-                let plot = Plot::new(format!("{}_axes_plot", device.guid))
-                    .allow_scroll(false)
-                    .allow_zoom(false)
-                    .allow_drag(false)
-                    .allow_boxed_zoom(false)
-                    .height(200.0);
-
-                plot.show(ui, |plot_ui| {
-                    let plot_axes_data = device.axes_plot_data();
-                    for (index, data) in plot_axes_data.into_iter().enumerate() {
-                        let line = Line::new(data).width(2.0).color(auto_color(index));
-                        plot_ui.line(line);
-                    }
-                    let (min_bound, max_bound) = input.get_plot_bounds_physical();
-                    plot_ui.set_plot_bounds(PlotBounds::from_min_max(min_bound, max_bound));
-                });
-                ui.add_space(10.0);
-            }
+                        ui.separator();
 
-            for device in selected_virtual_devices {
-                ui.label(device.name());
+                        ui.vertical(|ui| {
+                            ui.vertical(|ui| {
+                                for (index, hat_state) in device.input_state.hats().enumerate() {
+                                    let degrees = (*hat_state != -1).then_some(*hat_state as f32);
+                                    let color = visualization.color(index);
+                                    hat_widget(ui, &ui_data.hat_switches, index, degrees, color);
+                                }
+                            });
+                        });
+                    });
+                    if reset_ranges {
+                        device.input_state.reset_axis_ranges();
+                    }
 
-                ui.separator();
+                    let legend = ui_data.plot_legends.entry(device.guid.clone()).or_default();
+                    let entries: Vec<(String, Color32)> = device
+                        .input_state
+                        .axis_kinds()
+                        .enumerate()
+                        .map(|(index, kind)| {
+                            (
+                                format!("Axis {} ({kind})", index + 1),
+                                visualization.color(index),
+                            )
+                        })
+                        .collect();
+                    plot_legend_widget(ui, legend, &entries);
 
-                ui.horizontal(|ui| {
-                    ui.vertical(|ui| {
-                        ui.set_min_width(80.0);
-                        for (index, axis) in device.handle.axes().enumerate() {
-                            ui.label(
-                                RichText::new(format!("Axis {}: {}", index + 1, axis.get()))
-                                    .color(auto_color(index))
-                                    .strong(),
+                    show_static_plot(ui, format!("{}_axes_plot", device.guid), |plot_ui| {
+                        if device_colors {
+                            plot_device_decoration(
+                                plot_ui,
+                                &device.name(),
+                                color,
+                                dark_mode,
+                                [physical_min_bound, physical_max_bound],
                             );
                         }
+                        plot_axes_lines(plot_ui, device.axes_plot_data(), legend, &entries);
+                        plot_ui.set_plot_bounds(PlotBounds::from_min_max(
+                            physical_min_bound,
+                            physical_max_bound,
+                        ));
                     });
+                    hat_history_strip(
+                        ui,
+                        &device.hats_history,
+                        [physical_min_bound[0], physical_max_bound[0]],
+                    );
+
+                    CollapsingHeader::new("Heatmap")
+                        .id_source(format!("{}_heatmap", device.guid))
+                        .show(ui, |ui| {
+                            ui.push_id(format!("{}_heatmap_widget", device.guid), |ui| {
+                                let texture = ui_data
+                                    .heatmap_textures
+                                    .entry(device.guid.clone())
+                                    .or_default();
+                                heatmap_widget(ui, device, texture, &mut ui_data.modals);
+                            });
+                        });
+                });
+                section += 1;
+                ui.add_space(10.0);
+            }
+
+            for device in input.selected_virtual_devices(&ui_data.device_selection) {
+                let color = device_color(&device.name(), visualization.palette);
+                section_frame(ui, section, device_colors).show(ui, |ui| {
+                    ui.label(device.name());
 
                     ui.separator();
 
-                    ui.vertical(|ui| {
-                        if device.handle.hats().len() > 0 {
-                            ui.set_max_width(ui.available_width() - 75.0);
-                        }
-                        ui.horizontal_wrapped(|ui| {
-                            for (index, button) in device.handle.buttons().enumerate() {
-                                let state = match button.get() {
-                                    ButtonState::Pressed => true,
-                                    ButtonState::Released => false,
-                                };
-                                InputButton::new((index + 1).to_string(), state).ui(ui);
+                    ui.horizontal(|ui| {
+                        ui.vertical(|ui| {
+                            ui.set_min_width(80.0);
+                            for (index, axis) in device.handle.axes().enumerate() {
+                                ui.label(
+                                    RichText::new(format!("Axis {}: {}", index + 1, axis.get()))
+                                        .color(visualization.color(index))
+                                        .strong(),
+                                );
                             }
                         });
-                    });
 
-                    ui.separator();
+                        ui.separator();
 
-                    ui.vertical(|ui| {
                         ui.vertical(|ui| {
-                            for (index, hat) in device.handle.hats().enumerate() {
-                                let rounded: i32 = match hat.get() {
-                                    HatState::Continuous(value) => {
-                                        if value == u32::MAX {
-                                            -1
-                                        } else {
-                                            (value as i32 / 100 / 45) * 45
-                                        }
-                                    }
-                                    HatState::Discrete(fourway) => match fourway {
-                                        FourWayHat::Centered => -1,
-                                        FourWayHat::North => 0,
-                                        FourWayHat::East => 90,
-                                        FourWayHat::South => 180,
-                                        FourWayHat::West => 270,
-                                    },
-                                };
-
-                                ui.vertical(|ui| {
-                                    if let Some(texture_handle) = ui_data.hat_switches.get(&rounded)
-                                    {
-                                        let color = auto_color(index);
-                                        ui.label(
-                                            RichText::new(format!("Hat {index}")).color(color),
-                                        );
-                                        ui.add_space(5.0);
-                                        ui.add(
-                                            Image::new(texture_handle.id(), [50.0, 50.0])
-                                                .tint(color),
-                                        );
-                                    }
-                                });
+                            if device.handle.hats().len() > 0 {
+                                ui.set_max_width(ui.available_width() - 75.0);
                             }
+                            ui.horizontal_wrapped(|ui| {
+                                for (index, button) in device.handle.buttons().enumerate() {
+                                    let state = match button.get() {
+                                        ButtonState::Pressed => true,
+                                        ButtonState::Released => false,
+                                    };
+                                    InputButton::new(
+                                        (index + 1).to_string(),
+                                        state,
+                                        visualization.filled_buttons,
+                                    )
+                                    .ui(ui);
+                                }
+                            });
+                        });
+
+                        ui.separator();
+
+                        ui.vertical(|ui| {
+                            ui.vertical(|ui| {
+                                for (index, hat) in device.handle.hats().enumerate() {
+                                    let degrees = vjoy_hat_degrees(hat.get());
+                                    let color = visualization.color(index);
+                                    hat_widget(ui, &ui_data.hat_switches, index, degrees, color);
+                                }
+                            });
                         });
                     });
-                });
 
-                let plot = Plot::new(format!("{}_axes_plot", device.name()))
-                    .allow_scroll(false)
-                    .allow_zoom(false)
-                    .allow_drag(false)
-                    .allow_boxed_zoom(false)
-                    .height(200.0);
-
-                plot.show(ui, |plot_ui| {
-                    let plot_axes_data = device.axes_plot_data();
-                    for (index, data) in plot_axes_data.into_iter().enumerate() {
-                        let line = Line::new(data).width(2.0).color(auto_color(index));
-                        plot_ui.line(line);
-                    }
-                    let (min_bound, max_bound) = input.get_plot_bounds_virtual();
-                    plot_ui.set_plot_bounds(PlotBounds::from_min_max(min_bound, max_bound));
+                    let legend = ui_data.plot_legends.entry(device.name()).or_default();
+                    let entries: Vec<(String, Color32)> = (0..device.handle.axes().len())
+                        .map(|index| {
+                            let name = match device.handle.axis_name(index) {
+                                Some(name) => format!("Axis {} ({name})", index + 1),
+                                None => format!("Axis {}", index + 1),
+                            };
+                            (name, visualization.color(index))
+                        })
+                        .collect();
+                    plot_legend_widget(ui, legend, &entries);
+
+                    show_static_plot(ui, format!("{}_axes_plot", device.name()), |plot_ui| {
+                        if device_colors {
+                            plot_device_decoration(
+                                plot_ui,
+                                &device.name(),
+                                color,
+                                dark_mode,
+                                [virtual_min_bound, virtual_max_bound],
+                            );
+                        }
+                        plot_axes_lines(plot_ui, device.axes_plot_data(), legend, &entries);
+                        plot_ui.set_plot_bounds(PlotBounds::from_min_max(
+                            virtual_min_bound,
+                            virtual_max_bound,
+                        ));
+                    });
+                    hat_history_strip(
+                        ui,
+                        &device.hats_history,
+                        [virtual_min_bound[0], virtual_max_bound[0]],
+                    );
                 });
+                section += 1;
                 ui.add_space(10.0);
             }
         });
     });
 }
 
+/// Axes plot with every plot interaction disabled. Returns the rect taken by the plot.
+///
+/// egui's plot senses drags on its rect expanded by half the item spacing even with dragging
+/// disabled, which can cover the edge of neighbouring widgets. Showing it in a child ui clipped to
+/// its own rect keeps clicks with them, and with zoom and scroll off the wheel reaches the outer
+/// ScrollArea. Hover readouts (`show_x`/`show_y`) only paint and don't change this.
+fn show_static_plot(ui: &mut Ui, id_source: impl Hash, build_fn: impl FnOnce(&mut PlotUi)) -> Rect {
+    let plot = Plot::new(id_source)
+        .allow_scroll(false)
+        .allow_zoom(false)
+        .allow_drag(false)
+        .allow_boxed_zoom(false)
+        .allow_double_click_reset(false)
+        .height(PLOT_HEIGHT);
+
+    let (rect, _) = ui.allocate_exact_size(vec2(ui.available_width(), PLOT_HEIGHT), Sense::hover());
+    let mut plot_ui = ui.child_ui(rect, *ui.layout());
+    plot_ui.set_clip_rect(rect.intersect(ui.clip_rect()));
+    plot.show(&mut plot_ui, build_fn);
+    rect
+}
+
+/// One line per axis, styled by the legend's highlight
+fn plot_axes_lines(
+    plot_ui: &mut PlotUi,
+    plot_axes_data: Vec<PlotPoints>,
+    legend: &PlotLegend,
+    entries: &[(String, Color32)],
+) {
+    for (index, (data, (name, color))) in plot_axes_data.into_iter().zip(entries).enumerate() {
+        if let Some((width, color)) = legend.stroke(index, *color) {
+            plot_ui.line(Line::new(data).name(name).width(width).color(color));
+        }
+    }
+}
+
+/// Position of a physical axis, with tick marks at the lowest and highest value seen
+fn axis_range_bar(ui: &mut Ui, value: i32, range: Option<(i32, i32)>, color: Color32) {
+    let (rect, response) = ui.allocate_exact_size(AXIS_BAR_SIZE, Sense::hover());
+    let x = |v: i32| rect.left() + rect.width() * (v - i16::MIN as i32) as f32 / u16::MAX as f32;
+    let painter = ui.painter();
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+    if let Some((min, max)) = range {
+        painter.rect_filled(
+            Rect::from_x_y_ranges(x(min)..=x(max), rect.y_range()),
+            0.0,
+            color.gamma_multiply(0.3),
+        );
+        for tick in [min, max] {
+            painter.vline(x(tick), rect.y_range(), Stroke::new(1.5, color));
+        }
+    }
+    painter.circle_filled(pos2(x(value), rect.center().y), 3.0, color);
+
+    response.on_hover_text(match range {
+        Some((min, max)) => format!("Lowest: {min}\nHighest: {max}"),
+        None => "No values yet".to_string(),
+    });
+}
+
+/// Stable color per device from its GUID, or its name for virtual devices
+fn device_color(key: &str, palette: Palette) -> Color32 {
+    // FNV-1a, unlike DefaultHasher it is guaranteed to be the same across runs and Rust versions
+    let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    palette.color((hash % 1024) as usize)
+}
+
+/// Every other device section gets the faint background so neighbouring devices are separated
+fn section_frame(ui: &Ui, section: usize, enabled: bool) -> Frame {
+    let fill = match enabled && section % 2 == 1 {
+        true => ui.visuals().faint_bg_color,
+        false => Color32::TRANSPARENT,
+    };
+    Frame::none().fill(fill).inner_margin(4.0)
+}
+
+/// Device tinted plot background and the device name in the top left corner
+fn plot_device_decoration(
+    plot_ui: &mut PlotUi,
+    name: &str,
+    color: Color32,
+    dark_mode: bool,
+    [min, max]: [[f64; 2]; 2],
+) {
+    let alpha = match dark_mode {
+        true => 0.08,
+        false => 0.15,
+    };
+    let background = vec![
+        [min[0], min[1]],
+        [max[0], min[1]],
+        [max[0], max[1]],
+        [min[0], max[1]],
+    ];
+    plot_ui.polygon(
+        Polygon::new(background)
+            .color(color)
+            .fill_alpha(alpha)
+            .width(0.0),
+    );
+    plot_ui.text(
+        Text::new(PlotPoint::new(min[0], max[1]), RichText::new(name).strong())
+            .color(color)
+            .anchor(Align2::LEFT_TOP),
+    );
+}
+
+/// Image of a hat state, `-1` for centered, and the angle of a needle drawn over it.
+///
+/// Angles on the 45° steps of the images are shown by the image alone. Continuous hats in between
+/// show the centered image with a needle at the exact angle instead of snapping to a direction.
+fn hat_image(degrees: Option<f32>) -> (i32, Option<f32>) {
+    match degrees {
+        None => (-1, None),
+        Some(degrees) if degrees % 45.0 == 0.0 => ((degrees as i32 / 45 * 45) % 360, None),
+        Some(degrees) => (-1, Some(degrees)),
+    }
+}
+
+fn hat_widget(
+    ui: &mut Ui,
+    hat_switches: &HashMap<i32, TextureHandle>,
+    index: usize,
+    degrees: Option<f32>,
+    color: Color32,
+) {
+    let (image, needle) = hat_image(degrees);
+    ui.vertical(|ui| {
+        let Some(texture_handle) = hat_switches.get(&image) else {
+            return;
+        };
+        ui.label(RichText::new(format!("Hat {index}")).color(color));
+        ui.add_space(5.0);
+        let rect = ui
+            .add(Image::new(texture_handle.id(), [50.0, 50.0]).tint(color))
+            .rect;
+        if let Some(needle) = needle {
+            // 0° points up, angles grow clockwise
+            let direction = Vec2::angled((needle - 90.0).to_radians());
+            let tip = rect.center() + direction * rect.width() * 0.45;
+            ui.painter()
+                .line_segment([rect.center(), tip], Stroke::new(2.0, color));
+            ui.painter().circle_filled(tip, 3.0, color);
+            ui.label(RichText::new(format!("{needle:.2}°")).color(color));
+        }
+    });
+}
+
 struct InputButton {
     text: String,
     state: bool,
+    filled: bool,
 }
 
 impl InputButton {
     #[profiling::function]
-    pub fn new(text: String, state: bool) -> Self {
-        Self { text, state }
+    pub fn new(text: String, state: bool, filled: bool) -> Self {
+        Self {
+            text,
+            state,
+            filled,
+        }
     }
 }
 
@@ -224,10 +472,10 @@ impl Widget for InputButton {
             WidgetText::RichText(RichText::new(self.text).color(text_color).size(11.0));
         let galley_text = widget_text.into_galley(ui, Some(false), 10.0, TextStyle::Button);
         if ui.is_rect_visible(rect) {
-            let fill = visuals.weak_bg_fill;
-            let stroke = match self.state {
-                true => visuals.fg_stroke,
-                false => visuals.bg_stroke,
+            let (fill, stroke) = match (self.state, self.filled) {
+                (true, true) => (ui.visuals().selection.bg_fill, visuals.bg_stroke),
+                (true, false) => (visuals.weak_bg_fill, visuals.fg_stroke),
+                (false, _) => (visuals.weak_bg_fill, visuals.bg_stroke),
             };
             let rounding = visuals.rounding;
             ui.painter().rect(rect, rounding, fill, stroke);
@@ -242,3 +490,83 @@ impl Widget for InputButton {
         response
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use egui::{pos2, vec2, CentralPanel, Context, Event, RawInput, Rect, ScrollArea};
+
+    use vjoy::{FourWayHat, HatState};
+
+    use crate::{
+        input::input_viewer::{hat_image, show_static_plot, PLOT_HEIGHT},
+        rebind::hat_to_hat::vjoy_hat_degrees,
+    };
+
+    #[test]
+    fn continuous_angles_between_the_images_get_a_needle() {
+        assert_eq!(hat_image(None), (-1, None));
+        assert_eq!(hat_image(Some(0.0)), (0, None));
+        assert_eq!(hat_image(Some(225.0)), (225, None));
+        assert_eq!(hat_image(Some(100.0)), (-1, Some(100.0)));
+
+        let virtual_hat = |state| hat_image(vjoy_hat_degrees(state));
+        assert_eq!(
+            virtual_hat(HatState::Discrete(FourWayHat::West)),
+            (270, None)
+        );
+        assert_eq!(virtual_hat(HatState::Continuous(u32::MAX)), (-1, None));
+        assert_eq!(virtual_hat(HatState::Continuous(9000)), (90, None));
+        assert_eq!(virtual_hat(HatState::Continuous(1234)), (-1, Some(12.34)));
+    }
+
+    #[test]
+    fn plot_stays_inside_nested_layouts() {
+        egui::__run_test_ui(|ui| {
+            ScrollArea::vertical().show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.vertical(|ui| {
+                        let available = ui.available_rect_before_wrap();
+                        let rect = show_static_plot(ui, "nested_plot", |_| {});
+                        assert_eq!(rect.height(), PLOT_HEIGHT);
+                        assert!(
+                            rect.left() >= available.left() && rect.right() <= available.right()
+                        );
+                        assert!(ui.min_rect().contains_rect(rect));
+                    });
+                });
+            });
+        });
+    }
+
+    #[test]
+    fn wheel_over_plot_scrolls_outer_area() {
+        let ctx = Context::default();
+        let screen_rect = Rect::from_min_size(pos2(0.0, 0.0), vec2(400.0, 300.0));
+        let mut offset = 0.0;
+        let mut run_frame = |events: Vec<Event>| {
+            let input = RawInput {
+                screen_rect: Some(screen_rect),
+                events,
+                ..Default::default()
+            };
+            let _ = ctx.run(input, |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    let output = ScrollArea::vertical().show(ui, |ui| {
+                        for index in 0..3 {
+                            let _ = ui.button("toggle");
+                            show_static_plot(ui, index, |_| {});
+                        }
+                    });
+                    offset = output.state.offset.y;
+                });
+            });
+        };
+
+        run_frame(Vec::new());
+        run_frame(vec![
+            Event::PointerMoved(pos2(200.0, 150.0)),
+            Event::Scroll(vec2(0.0, -50.0)),
+        ]);
+        assert!(offset > 0.0);
+    }
+}
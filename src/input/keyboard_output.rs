@@ -0,0 +1,226 @@
+//! Presses and releases the keys of keyboard rebinds with synthesized key events.
+//!
+//! The rebinds report the key chords to hold each tick. Only the keys that changed since the last
+//! update are sent, so a key no rebind holds anymore, e.g. after a shift mode change, is released
+//! on the next tick. Keys are sent as scan codes with `SendInput`, other platforms fail to send.
+
+use crate::{
+    error::Error,
+    rebind::keyboard_rebind::{Key, KeyChord},
+};
+
+/// One synthesized key press or release
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct KeyEvent {
+    pub key: Key,
+    pub pressed: bool,
+}
+
+pub trait KeySink {
+    /// Sends the events in order
+    fn send(&mut self, events: &[KeyEvent]) -> Result<(), Error>;
+}
+
+pub struct KeyboardOutput {
+    sink: Box<dyn KeySink>,
+    /// Keys currently held down, in the order they were pressed
+    held: Vec<Key>,
+}
+
+impl Default for KeyboardOutput {
+    fn default() -> Self {
+        Self::with_sink(Box::new(SystemKeySink))
+    }
+}
+
+impl KeyboardOutput {
+    pub fn with_sink(sink: Box<dyn KeySink>) -> Self {
+        Self {
+            sink,
+            held: Vec::new(),
+        }
+    }
+
+    /// Holds exactly the keys of `chords`. Modifiers are pressed before and released after the
+    /// keys they go with. A failed send is retried on the next update.
+    pub fn update(&mut self, chords: &[KeyChord]) -> Result<(), Error> {
+        let mut target: Vec<Key> = Vec::new();
+        for key in chords.iter().flat_map(KeyChord::keys) {
+            if !target.contains(&key) {
+                target.push(key);
+            }
+        }
+
+        let releases = self
+            .held
+            .iter()
+            .rev()
+            .filter(|key| !target.contains(key))
+            .map(|&key| KeyEvent {
+                key,
+                pressed: false,
+            });
+        let presses = target
+            .iter()
+            .filter(|key| !self.held.contains(key))
+            .map(|&key| KeyEvent { key, pressed: true });
+        let events: Vec<KeyEvent> = releases.chain(presses).collect();
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        self.sink.send(&events)?;
+        self.held.retain(|key| target.contains(key));
+        for key in target {
+            if !self.held.contains(&key) {
+                self.held.push(key);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sends to the focused window through the OS input queue
+struct SystemKeySink;
+
+#[cfg(windows)]
+mod ffi {
+    pub const INPUT_KEYBOARD: u32 = 1;
+    pub const KEYEVENTF_EXTENDEDKEY: u32 = 0x0001;
+    pub const KEYEVENTF_KEYUP: u32 = 0x0002;
+    pub const KEYEVENTF_SCANCODE: u32 = 0x0008;
+
+    #[repr(C)]
+    pub struct KeybdInput {
+        pub vk: u16,
+        pub scan: u16,
+        pub flags: u32,
+        pub time: u32,
+        pub extra_info: usize,
+    }
+
+    /// `INPUT` with the keyboard member of the union, padded to the size of the mouse member
+    #[repr(C)]
+    pub struct Input {
+        pub kind: u32,
+        pub ki: KeybdInput,
+        pub padding: [u8; 8],
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        pub fn SendInput(count: u32, inputs: *const Input, size: i32) -> u32;
+    }
+}
+
+#[cfg(windows)]
+impl KeySink for SystemKeySink {
+    fn send(&mut self, events: &[KeyEvent]) -> Result<(), Error> {
+        let inputs: Vec<ffi::Input> = events
+            .iter()
+            .map(|event| {
+                let (scan, extended) = event.key.scan_code();
+                let mut flags = ffi::KEYEVENTF_SCANCODE;
+                if extended {
+                    flags |= ffi::KEYEVENTF_EXTENDEDKEY;
+                }
+                if !event.pressed {
+                    flags |= ffi::KEYEVENTF_KEYUP;
+                }
+                ffi::Input {
+                    kind: ffi::INPUT_KEYBOARD,
+                    ki: ffi::KeybdInput {
+                        vk: 0,
+                        scan,
+                        flags,
+                        time: 0,
+                        extra_info: 0,
+                    },
+                    padding: [0; 8],
+                }
+            })
+            .collect();
+        // SAFETY: the pointer and count describe `inputs`, which outlives the call
+        let sent = unsafe {
+            ffi::SendInput(
+                inputs.len() as u32,
+                inputs.as_ptr(),
+                std::mem::size_of::<ffi::Input>() as i32,
+            )
+        };
+        if sent as usize != inputs.len() {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(windows))]
+impl KeySink for SystemKeySink {
+    fn send(&mut self, _events: &[KeyEvent]) -> Result<(), Error> {
+        Err("keyboard output is only supported on Windows"
+            .to_string()
+            .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        error::Error,
+        input::keyboard_output::{KeyEvent, KeySink, KeyboardOutput},
+        rebind::keyboard_rebind::{Key, KeyChord},
+    };
+
+    #[derive(Default, Clone)]
+    struct Recorder(Rc<RefCell<Vec<(Key, bool)>>>);
+
+    impl Recorder {
+        fn take(&self) -> Vec<(Key, bool)> {
+            std::mem::take(&mut self.0.borrow_mut())
+        }
+    }
+
+    impl KeySink for Recorder {
+        fn send(&mut self, events: &[KeyEvent]) -> Result<(), Error> {
+            let events = events.iter().map(|e| (e.key, e.pressed));
+            self.0.borrow_mut().extend(events);
+            Ok(())
+        }
+    }
+
+    fn chord(key: Key, ctrl: bool) -> KeyChord {
+        KeyChord {
+            key,
+            ctrl,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    #[test]
+    fn sends_only_changes_with_modifiers_around_keys() {
+        let recorder = Recorder::default();
+        let mut output = KeyboardOutput::with_sink(Box::new(recorder.clone()));
+
+        output.update(&[chord(Key::F1, true)]).unwrap();
+        assert_eq!(recorder.take(), [(Key::LeftCtrl, true), (Key::F1, true)]);
+
+        // Held keys are not sent again, a shared modifier stays down
+        output
+            .update(&[chord(Key::F1, true), chord(Key::F2, true)])
+            .unwrap();
+        assert_eq!(recorder.take(), [(Key::F2, true)]);
+
+        output.update(&[chord(Key::F2, true)]).unwrap();
+        assert_eq!(recorder.take(), [(Key::F1, false)]);
+
+        output.update(&[]).unwrap();
+        assert_eq!(recorder.take(), [(Key::F2, false), (Key::LeftCtrl, false)]);
+        assert!(output.held.is_empty());
+        output.update(&[]).unwrap();
+        assert!(recorder.take().is_empty());
+    }
+}
@@ -0,0 +1,260 @@
+//! Sends the MIDI feedback table of the config to a MIDI output port, e.g. to drive the LEDs of a
+//! button box.
+//!
+//! The table is evaluated after each processing tick. A message is only sent when an entry
+//! changes state or is edited, so a port that drops messages is resynced by the next transition.
+//! Requires the `midi` feature, builds without it list no ports.
+
+use log::info;
+
+use crate::{
+    error::Error,
+    input::VirtualDevice,
+    rebind::{midi_feedback::MidiFeedback, shift_mode_mask::ShiftModeMask},
+};
+
+/// Whether this build can send MIDI
+pub const MIDI_AVAILABLE: bool = cfg!(feature = "midi");
+
+/// Client and connection name shown by the MIDI drivers
+#[cfg(feature = "midi")]
+const MIDI_CLIENT_NAME: &str = "Rust vJoy Manager";
+
+/// All feedback entries are sent as active for this long after a test send, in seconds
+const MIDI_TEST_DURATION: f64 = 1.0;
+
+pub trait MidiSink {
+    fn send(&mut self, message: &[u8]) -> Result<(), Error>;
+}
+
+/// Names of the MIDI output ports, empty if there are none or MIDI is unavailable
+#[cfg(feature = "midi")]
+pub fn output_ports() -> Vec<String> {
+    let Ok(output) = midir::MidiOutput::new(MIDI_CLIENT_NAME) else {
+        return Vec::new();
+    };
+    output
+        .ports()
+        .iter()
+        .filter_map(|port| output.port_name(port).ok())
+        .collect()
+}
+
+#[cfg(not(feature = "midi"))]
+pub fn output_ports() -> Vec<String> {
+    Vec::new()
+}
+
+#[cfg(feature = "midi")]
+fn open_port(port: &str) -> Result<Box<dyn MidiSink>, Error> {
+    let output = midir::MidiOutput::new(MIDI_CLIENT_NAME).map_err(|e| format!("midir: {e}"))?;
+    let found = output
+        .ports()
+        .into_iter()
+        .find(|p| output.port_name(p).map_or(false, |name| name == port))
+        .ok_or_else(|| format!("no MIDI output port {port}"))?;
+    let connection = output
+        .connect(&found, MIDI_CLIENT_NAME)
+        .map_err(|e| format!("midir: {e}"))?;
+    Ok(Box::new(connection))
+}
+
+#[cfg(not(feature = "midi"))]
+fn open_port(_port: &str) -> Result<Box<dyn MidiSink>, Error> {
+    Err("this build has no MIDI support, enable the midi feature"
+        .to_string()
+        .into())
+}
+
+#[cfg(feature = "midi")]
+impl MidiSink for midir::MidiOutputConnection {
+    fn send(&mut self, message: &[u8]) -> Result<(), Error> {
+        midir::MidiOutputConnection::send(self, message).map_err(|e| format!("midir: {e}").into())
+    }
+}
+
+pub struct MidiOutput {
+    port: String,
+    sink: Box<dyn MidiSink>,
+    /// Entry and state last sent per row of the feedback table, `None` until sent once
+    sent: Vec<Option<(MidiFeedback, bool)>>,
+    test_until: Option<f64>,
+}
+
+impl MidiOutput {
+    pub fn connect(port: &str) -> Result<Self, Error> {
+        let sink = open_port(port)?;
+        info!("Sending MIDI feedback to {port}");
+        Ok(Self::with_sink(port, sink))
+    }
+
+    pub fn with_sink(port: &str, sink: Box<dyn MidiSink>) -> Self {
+        Self {
+            port: port.to_string(),
+            sink,
+            sent: Vec::new(),
+            test_until: None,
+        }
+    }
+
+    pub fn port(&self) -> &str {
+        &self.port
+    }
+
+    /// Sends every entry as active for a moment, then returns to the actual states
+    pub fn start_test(&mut self, time: f64) {
+        self.test_until = Some(time + MIDI_TEST_DURATION);
+    }
+
+    /// Sends the entries of `feedback` that changed state or were edited since the last update.
+    /// A failed send is retried on the next update.
+    pub fn update(
+        &mut self,
+        feedback: &[MidiFeedback],
+        shift_mode: ShiftModeMask,
+        virtual_devices: &[VirtualDevice],
+        time: f64,
+    ) -> Result<(), Error> {
+        let testing = self.test_until.map_or(false, |until| time < until);
+        if !testing {
+            self.test_until = None;
+        }
+        self.sent.resize(feedback.len(), None);
+        for (entry, sent) in feedback.iter().zip(self.sent.iter_mut()) {
+            let active = testing || entry.is_active(shift_mode, virtual_devices);
+            if *sent == Some((*entry, active)) {
+                continue;
+            }
+            self.sink.send(&entry.message(active))?;
+            *sent = Some((*entry, active));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        error::Error,
+        input::midi_output::{MidiOutput, MidiSink},
+        rebind::{
+            midi_feedback::{MidiEvent, MidiFeedback, MidiMessageKind},
+            shift_mode_mask::ShiftModeMask,
+        },
+    };
+
+    #[derive(Default, Clone)]
+    struct Recorder {
+        sent: Rc<RefCell<Vec<Vec<u8>>>>,
+        fail: Rc<RefCell<bool>>,
+    }
+
+    impl Recorder {
+        fn take(&self) -> Vec<Vec<u8>> {
+            std::mem::take(&mut self.sent.borrow_mut())
+        }
+    }
+
+    impl MidiSink for Recorder {
+        fn send(&mut self, message: &[u8]) -> Result<(), Error> {
+            if *self.fail.borrow() {
+                return Err("port closed".to_string().into());
+            }
+            self.sent.borrow_mut().push(message.to_vec());
+            Ok(())
+        }
+    }
+
+    fn mode_led(bit: u8, note: u8) -> MidiFeedback {
+        MidiFeedback {
+            event: MidiEvent::ShiftMode {
+                mode_mask: ShiftModeMask(bit),
+            },
+            channel: 1,
+            kind: MidiMessageKind::Note,
+            number: note,
+            on_value: 127,
+            off_value: 0,
+        }
+    }
+
+    #[test]
+    fn sends_only_on_transitions_and_edits() {
+        let recorder = Recorder::default();
+        let mut output = MidiOutput::with_sink("Box", Box::new(recorder.clone()));
+        let mut feedback = vec![mode_led(0b01, 10), mode_led(0b10, 11)];
+
+        // The first update syncs every entry
+        output
+            .update(&feedback, ShiftModeMask(0b01), &[], 0.0)
+            .unwrap();
+        assert_eq!(
+            recorder.take(),
+            vec![vec![0x90, 10, 127], vec![0x80, 11, 0]]
+        );
+
+        output
+            .update(&feedback, ShiftModeMask(0b01), &[], 0.1)
+            .unwrap();
+        assert!(recorder.take().is_empty());
+
+        output
+            .update(&feedback, ShiftModeMask(0b10), &[], 0.2)
+            .unwrap();
+        assert_eq!(
+            recorder.take(),
+            vec![vec![0x80, 10, 0], vec![0x90, 11, 127]]
+        );
+
+        feedback[1].on_value = 64;
+        feedback.push(mode_led(0b11, 12));
+        output
+            .update(&feedback, ShiftModeMask(0b10), &[], 0.3)
+            .unwrap();
+        assert_eq!(recorder.take(), vec![vec![0x90, 11, 64], vec![0x80, 12, 0]]);
+    }
+
+    #[test]
+    fn failed_sends_are_retried() {
+        let recorder = Recorder::default();
+        let mut output = MidiOutput::with_sink("Box", Box::new(recorder.clone()));
+        let feedback = vec![mode_led(0b01, 10)];
+
+        *recorder.fail.borrow_mut() = true;
+        assert!(output
+            .update(&feedback, ShiftModeMask(0b01), &[], 0.0)
+            .is_err());
+        *recorder.fail.borrow_mut() = false;
+        output
+            .update(&feedback, ShiftModeMask(0b01), &[], 0.1)
+            .unwrap();
+        assert_eq!(recorder.take(), vec![vec![0x90, 10, 127]]);
+    }
+
+    #[test]
+    fn test_send_lights_everything_for_a_moment() {
+        let recorder = Recorder::default();
+        let mut output = MidiOutput::with_sink("Box", Box::new(recorder.clone()));
+        let feedback = vec![mode_led(0b01, 10), mode_led(0b10, 11)];
+        output
+            .update(&feedback, ShiftModeMask(0), &[], 0.0)
+            .unwrap();
+        recorder.take();
+
+        output.start_test(1.0);
+        output
+            .update(&feedback, ShiftModeMask(0), &[], 1.1)
+            .unwrap();
+        assert_eq!(
+            recorder.take(),
+            vec![vec![0x90, 10, 127], vec![0x90, 11, 127]]
+        );
+
+        output
+            .update(&feedback, ShiftModeMask(0), &[], 2.1)
+            .unwrap();
+        assert_eq!(recorder.take(), vec![vec![0x80, 10, 0], vec![0x80, 11, 0]]);
+    }
+}
@@ -1,38 +1,160 @@
+pub mod axis_heatmap;
+pub mod backend;
+pub mod button_labels;
+pub mod device_panel;
+pub mod device_transform;
+pub mod event_console;
+pub mod hat_history;
+pub mod hid;
+pub mod hid_descriptor;
+pub mod identifiers;
 pub mod input_state;
 pub mod input_viewer;
-
-use std::path::Path;
+pub mod keyboard_output;
+pub mod midi_output;
+pub mod output_rate;
+pub mod output_status;
+pub mod plot_legend;
+pub mod poll_timer;
+pub mod power;
+pub mod shared_memory;
+pub mod stick_navigation;
+pub mod test_panel;
+pub mod test_script;
+pub mod virtual_state;
+pub mod warm_up;
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::Path,
+    time::Instant,
+};
 
 use egui::plot::{PlotPoint, PlotPoints};
 use indexmap::IndexMap;
-use log::trace;
-use ringbuffer::{AllocRingBuffer, RingBufferExt, RingBufferWrite};
-use sdl2::{joystick::Joystick, JoystickSubsystem, Sdl};
-use vjoy::{Device, VJoy};
+use log::{error, info, trace, warn};
+use ringbuffer::{AllocRingBuffer, RingBuffer, RingBufferExt, RingBufferWrite};
+use sdl2::joystick::PowerLevel;
 
 use crate::{
+    config::Config,
     error::Error,
+    launch::LaunchOptions,
+    performance::TimingHistory,
     previous::Previous,
+    rate_limited_log::RateLimitedLog,
     rebind::{
-        rebind_processor::RebindProcessor, rebind_viewer::DeviceInfo,
-        shift_mode_mask::ShiftModeMask, Rebind,
+        axis_neutral::AxisNeutral,
+        capabilities::{missing_virtual_capabilities, required_virtual_devices},
+        hat_to_hat::convert_vjoy_hat_to_angle,
+        midi_feedback::MidiFeedback,
+        mirror::Mirror,
+        mode_presets::ModeAxisPreset,
+        output_smoothing::AxisSmoothing,
+        overlay::Overlay,
+        processing_budget::ProcessingBudget,
+        rebind_processor::{write_neutral_outputs, RebindProcessor},
+        rebind_viewer::DeviceInfo,
+        shift_conflicts::ShiftConflict,
+        shift_mode_mask::ShiftModeMask,
+        Rebind, RebindScope, TickContext, VirtualControl, VirtualUsage,
     },
 };
 
-use self::input_state::InputState;
+use self::{
+    axis_heatmap::AxisHeatmap,
+    backend::{
+        JoystickBackend, JoystickHandle, OutputBackend, ReleasedOutput, SdlBackend, VJoyOutput,
+    },
+    button_labels::ButtonLabelMap,
+    device_panel::DeviceSelection,
+    device_transform::DeviceTransform,
+    event_console::{ControlChange, EventConsole},
+    hat_history::HatHistory,
+    input_state::InputState,
+    keyboard_output::KeyboardOutput,
+    midi_output::MidiOutput,
+    output_rate::OutputRate,
+    output_status::OutputStatus,
+    poll_timer::PollTimer,
+    power::{PowerStatus, POWER_POLL_INTERVAL},
+    shared_memory::{SharedMemoryPublisher, SHARED_MEMORY_NAME},
+    test_panel::ManualOverrides,
+    test_script::{ScriptRun, ScriptStep},
+    virtual_state::VirtualState,
+    warm_up::WarmUp,
+};
 
 pub const INPUT_POLL_INTERVAL: f64 = 0.001;
 pub const INPUT_PLOT_INTERVAL: f64 = 0.02;
+/// Seconds between checks for vJoy devices enabled or disabled while running
+pub const VIRTUAL_DEVICE_SCAN_INTERVAL: f64 = 3.0;
+/// Rescans after a device change or output failure until the vJoy devices match the ones SDL sees
+const VIRTUAL_DEVICE_RESCAN_RETRIES: u32 = 5;
+pub const GUID_OCCURRENCE_SEPARATOR: char = '#';
+
+/// GUID as compared against configs: lowercase without surrounding whitespace, so hand-edited
+/// or copied GUIDs still match the connected device
+pub fn normalize_guid(guid: &str) -> String {
+    guid.trim().to_lowercase()
+}
 
 pub struct PhysicalDevice {
+    /// SDL GUID, with [`GUID_OCCURRENCE_SEPARATOR`] and the occurrence appended for duplicates
     pub guid: String,
-    pub handle: Joystick,
+    pub occurrence: u32,
+    pub handle: Box<dyn JoystickHandle>,
     pub input_state: InputState,
     pub axes_plot_data: Vec<AllocRingBuffer<PlotPoint>>,
-    pub selected: bool,
+    pub heatmap: AxisHeatmap,
+    pub hats_history: Vec<HatHistory>,
+    pub warm_up: WarmUp,
+    pub power: PowerStatus,
+    /// False while unplugged. The device keeps its place in the list and its history, rebinds
+    /// reading it skip processing until it is back.
+    pub connected: bool,
 }
 
 impl PhysicalDevice {
+    pub(crate) fn new(guid: String, occurrence: u32, handle: Box<dyn JoystickHandle>) -> Self {
+        let input_state = InputState::new(handle.as_ref());
+        let axes_plot_data = input_state
+            .axes()
+            .map(|_| AllocRingBuffer::with_capacity(512))
+            .collect();
+        let hats_history = input_state.hats().map(|_| HatHistory::default()).collect();
+        Self {
+            guid,
+            occurrence,
+            handle,
+            input_state,
+            axes_plot_data,
+            heatmap: AxisHeatmap::default(),
+            hats_history,
+            warm_up: WarmUp::default(),
+            power: PowerStatus::default(),
+            connected: true,
+        }
+    }
+
+    /// Takes over the handle of the returning device. The history is kept unless the layout
+    /// changed, the device warms up again like a new one.
+    fn reconnect(&mut self, handle: Box<dyn JoystickHandle>) {
+        let input_state = InputState::new(handle.as_ref());
+        if input_state.num_axes() != self.num_axes() || input_state.num_hats() != self.num_hats() {
+            self.axes_plot_data = input_state
+                .axes()
+                .map(|_| AllocRingBuffer::with_capacity(512))
+                .collect();
+            self.hats_history = input_state.hats().map(|_| HatHistory::default()).collect();
+        }
+        self.handle = handle;
+        self.input_state = input_state;
+        self.warm_up = WarmUp::default();
+        self.power = PowerStatus::default();
+        self.connected = true;
+    }
+
     #[profiling::function]
     pub fn axes_plot_data(&self) -> Vec<PlotPoints> {
         self.axes_plot_data
@@ -43,7 +165,14 @@ impl PhysicalDevice {
 
     #[profiling::function]
     pub fn name(&self) -> String {
-        self.handle.name()
+        match self.occurrence {
+            0 | 1 => self.handle.name(),
+            n => format!("{} #{n}", self.handle.name()),
+        }
+    }
+
+    pub fn instance_id(&self) -> u32 {
+        self.handle.instance_id()
     }
 
     #[profiling::function]
@@ -62,10 +191,59 @@ impl PhysicalDevice {
     }
 
     #[profiling::function]
-    pub fn update(&mut self, plot: bool, time: f64) -> Result<(), Error> {
-        self.input_state.update(&self.handle)?;
+    pub fn is_initializing(&self) -> bool {
+        self.warm_up.is_initializing()
+    }
+
+    /// Polls the device, control changes are appended to `changes` if given
+    #[profiling::function]
+    pub fn update(
+        &mut self,
+        time: f64,
+        changes: Option<&mut Vec<ControlChange>>,
+    ) -> Result<(), Error> {
+        self.handle.poll()?;
+        if self.warm_up.is_initializing() {
+            let axes = (0..self.handle.num_axes())
+                .map(|index| self.handle.axis(index).map(i32::from))
+                .collect::<Result<Vec<i32>, _>>()?;
+            if self.warm_up.update(&axes, time) {
+                return Ok(());
+            }
+            self.input_state.reclassify_axes(&axes);
+            trace!("device warmed up: {}", self.name());
+        }
+
+        self.input_state.update(self.handle.as_ref(), time, changes)
+    }
+
+    /// Records the polled state for the input viewer: heatmap, hat history and, with `plot`, the
+    /// axes plot
+    #[profiling::function]
+    pub fn record_history(&mut self, plot: bool, time: f64) {
+        if self.is_initializing() {
+            return;
+        }
+
+        if self.heatmap.enabled {
+            let axis_value = |axis: u32| {
+                let index = axis.checked_sub(1)? as usize;
+                self.input_state.axes().nth(index).copied()
+            };
+            if let (Some(x), Some(y)) = (
+                axis_value(self.heatmap.x_axis),
+                axis_value(self.heatmap.y_axis),
+            ) {
+                self.heatmap.record(x, y);
+            }
+        }
+
+        for (hat_index, hat) in self.input_state.hats().enumerate() {
+            self.hats_history[hat_index].record(time, *hat);
+        }
+
         if !plot {
-            return Ok(());
+            return;
         }
 
         for (axis_index, axis) in self.input_state.axes().enumerate() {
@@ -74,19 +252,51 @@ impl PhysicalDevice {
                 y: *axis as f64,
             });
         }
+    }
 
-        Ok(())
+    /// Drops the axes plot and hat history, the heatmap is kept
+    pub fn clear_history(&mut self) {
+        self.axes_plot_data
+            .iter_mut()
+            .for_each(|buffer| buffer.clear());
+        self.hats_history.fill_with(HatHistory::default);
     }
 }
 
 pub struct VirtualDevice {
     pub id: u32,
-    pub handle: Device,
+    pub handle: VirtualState,
     pub axes_plot_data: Vec<AllocRingBuffer<PlotPoint>>,
-    pub selected: bool,
+    pub hats_history: Vec<HatHistory>,
+    pub output_status: OutputStatus,
+    pub output_rate: OutputRate,
 }
 
 impl VirtualDevice {
+    pub(crate) fn new(handle: VirtualState) -> Self {
+        let axes_plot_data = handle
+            .axes()
+            .map(|_| AllocRingBuffer::with_capacity(1024))
+            .collect();
+        let hats_history = handle.hats().map(|_| HatHistory::default()).collect();
+        Self {
+            id: handle.id(),
+            handle,
+            axes_plot_data,
+            hats_history,
+            output_status: OutputStatus::default(),
+            output_rate: OutputRate::default(),
+        }
+    }
+
+    /// Same id and number of controls
+    fn same_layout(&self, other: &VirtualState) -> bool {
+        self.id == other.id()
+            && self.handle.buttons().count() == other.buttons().count()
+            && self.handle.axes().count() == other.axes().count()
+            && self.handle.hats().count() == other.hats().count()
+    }
+
     #[profiling::function]
     pub fn name(&self) -> String {
         format!("vJoy device {}", self.id)
@@ -117,6 +327,10 @@ impl VirtualDevice {
 
     #[profiling::function]
     pub fn update(&mut self, plot: bool, time: f64) -> Result<(), Error> {
+        for (hat_index, hat) in self.handle.hats().enumerate() {
+            self.hats_history[hat_index].record(time, convert_vjoy_hat_to_angle(hat.get()));
+        }
+
         if !plot {
             return Ok(());
         }
@@ -130,101 +344,361 @@ impl VirtualDevice {
 
         Ok(())
     }
+
+    pub fn clear_history(&mut self) {
+        self.axes_plot_data
+            .iter_mut()
+            .for_each(|buffer| buffer.clear());
+        self.hats_history.fill_with(HatHistory::default);
+    }
 }
 
 pub struct Input {
-    vjoy: VJoy,
-    _sdl2: Sdl,
-    joystick_systen: JoystickSubsystem,
+    joysticks: Box<dyn JoystickBackend>,
+    output: Box<dyn OutputBackend>,
+    /// Devices in the order they were first seen, including the ones unplugged since
     connected_physical_devices: Vec<PhysicalDevice>,
     active_virtual_devices: Vec<VirtualDevice>,
+    /// Joysticks SDL reported when the devices were last fetched
+    num_joysticks: Option<u32>,
+    /// vJoy devices among them
+    num_sdl_virtual_devices: usize,
+    last_virtual_scan_time: f64,
+    virtual_scan_pending: bool,
+    virtual_rescan_retries: u32,
+    output_failed: bool,
+    virtual_devices_changed: bool,
     rebind_processor: RebindProcessor,
     x_bound_min: f64,
     x_bound_max: f64,
     last_poll_time: f64,
     last_plot_time: f64,
+    last_power_poll_time: f64,
+    power_warnings: Vec<String>,
+    tick: u64,
+    log: RateLimitedLog,
+    event_console: EventConsole,
+    poll_timer: PollTimer,
+    processing_times: TimingHistory,
+    /// Duration of the whole tick from polling to output
+    tick_times: TimingHistory,
+    /// Mean tick duration in the other mode, taken when performance mode was last toggled
+    other_mode_tick_s: Option<f64>,
+    /// Ticks only poll, process and output. Nothing is recorded for the input viewer.
+    performance_mode: bool,
+    /// Nothing is written to vJoy or shared memory, the virtual devices keep their last state
+    output_paused: bool,
+    manual_overrides: ManualOverrides,
+    duplicate_guids: Vec<String>,
+    shared_memory: Option<SharedMemoryPublisher>,
+    midi_output: Option<MidiOutput>,
+    keyboard_output: KeyboardOutput,
+    test_script: Option<ScriptRun>,
+    /// GUIDs of the devices read through raw HID instead of SDL
+    hid_devices: BTreeSet<String>,
+    shut_down: bool,
 }
 
 impl Input {
     #[profiling::function]
-    pub fn new(previous: &Previous) -> Result<Self, Error> {
-        let sdl2 = sdl2::init()?;
-        let joystick_systen = sdl2.joystick()?;
-        let vjoy = VJoy::from_default_dll_location()?;
-        let active_virtual_devices = Vec::new();
-
-        let rebind_processor = RebindProcessor::new(previous)?;
+    pub fn new(previous: &Previous, launch: &LaunchOptions) -> Result<Self, Error> {
+        let joysticks = Box::new(SdlBackend::new()?);
+        let output = Box::new(VJoyOutput::new()?);
+        let rebind_processor = RebindProcessor::new(previous, launch)?;
+
+        let mut input = Self::with_backends(joysticks, output, rebind_processor);
+        input.hid_devices = previous
+            .hid_devices
+            .iter()
+            .map(|g| normalize_guid(g))
+            .collect();
+        input.output_paused = launch.safe_mode;
+        Ok(input)
+    }
 
-        Ok(Self {
-            vjoy,
-            _sdl2: sdl2,
-            joystick_systen,
+    pub fn with_backends(
+        joysticks: Box<dyn JoystickBackend>,
+        output: Box<dyn OutputBackend>,
+        rebind_processor: RebindProcessor,
+    ) -> Self {
+        Self {
+            joysticks,
+            output,
             connected_physical_devices: Vec::new(),
-            active_virtual_devices,
+            active_virtual_devices: Vec::new(),
+            num_joysticks: None,
+            num_sdl_virtual_devices: 0,
+            last_virtual_scan_time: 0.0,
+            virtual_scan_pending: false,
+            virtual_rescan_retries: 0,
+            output_failed: false,
+            virtual_devices_changed: false,
             rebind_processor,
             x_bound_min: 0.0,
             x_bound_max: 0.0,
             last_poll_time: 0.0,
             last_plot_time: 0.0,
-        })
+            last_power_poll_time: 0.0,
+            power_warnings: Vec::new(),
+            tick: 0,
+            log: RateLimitedLog::default(),
+            event_console: EventConsole::default(),
+            poll_timer: PollTimer::new(Instant::now()),
+            processing_times: TimingHistory::default(),
+            tick_times: TimingHistory::default(),
+            other_mode_tick_s: None,
+            performance_mode: false,
+            output_paused: false,
+            manual_overrides: ManualOverrides::default(),
+            duplicate_guids: Vec::new(),
+            shared_memory: None,
+            midi_output: None,
+            keyboard_output: KeyboardOutput::default(),
+            test_script: None,
+            hid_devices: BTreeSet::new(),
+            shut_down: false,
+        }
     }
 
     pub fn update(&mut self, time: f64) -> Result<(), Error> {
-        let num_connected_devices_total = self.joystick_systen.num_joysticks()?;
-        if num_connected_devices_total
-            != self.connected_physical_devices.len() as u32
-                + self.active_virtual_devices.len() as u32
-        {
+        if self.shut_down {
+            return Ok(());
+        }
+
+        let num_joysticks = self.joysticks.num_joysticks()?;
+        if self.num_joysticks != Some(num_joysticks) {
             trace!("number of connected devices changed");
+            self.num_joysticks = Some(num_joysticks);
             self.fetch_connected_devices()?;
         }
+        if self.virtual_scan_pending
+            || time - self.last_virtual_scan_time >= VIRTUAL_DEVICE_SCAN_INTERVAL
+        {
+            self.scan_virtual_devices(time);
+        }
 
         let delta_t = time - self.last_poll_time;
         let delta_plot = time - self.last_plot_time;
 
-        if delta_t <= INPUT_POLL_INTERVAL {
+        // The poll timer paces the ticks, this only drops calls that come back well before it
+        if delta_t < INPUT_POLL_INTERVAL * 0.5 {
             return Ok(());
         }
 
+        let tick_start = Instant::now();
         let plot = delta_plot >= INPUT_PLOT_INTERVAL;
 
         //update sdl2 joystick system
-        self.joystick_systen.update();
+        self.joysticks.update();
 
         //poll sdl2 input state into cached state for all physical devices
         self.poll_connected_physical_devices(time, plot)?;
+        self.poll_power_levels(time);
+
+        //drive the devices with the synthetic input of a running test script
+        if let Some(script) = &mut self.test_script {
+            if let Some(mode) = script.begin_tick(time, &mut self.connected_physical_devices) {
+                self.rebind_processor.set_active_shift_mode(mode);
+            }
+        }
 
         //process rebinds
+        self.tick += 1;
+        let ctx = TickContext {
+            tick: self.tick,
+            time,
+            delta_t,
+        };
+        let start = Instant::now();
+        self.manual_overrides
+            .reset_released(&mut self.active_virtual_devices);
         self.rebind_processor.process(
             &mut self.connected_physical_devices,
             &mut self.active_virtual_devices,
-            time,
-            delta_t,
+            ctx,
         )?;
+        self.manual_overrides
+            .apply(&mut self.active_virtual_devices);
+        if let Some(script) = &mut self.test_script {
+            script.end_tick(
+                &self.active_virtual_devices,
+                self.rebind_processor.get_active_shift_mode(),
+            );
+        }
+        let processing_s = start.elapsed().as_secs_f64();
+        self.rebind_processor.record_processing_time(processing_s);
+        self.processing_times.record(time, processing_s);
+
+        //Mirror the state to MIDI feedback hardware
+        if let Some(midi_output) = &mut self.midi_output {
+            if let Err(e) = midi_output.update(
+                self.rebind_processor.get_midi_feedback(),
+                self.rebind_processor.get_active_shift_mode(),
+                &self.active_virtual_devices,
+                time,
+            ) {
+                self.log.warn(("midi", 0), time, || {
+                    format!("MIDI feedback to {} failed: {e}", midi_output.port())
+                });
+            }
+        }
+
+        //Press and release the keys of keyboard rebinds, all are released while output is paused
+        let held_keys = match self.output_paused {
+            true => &[][..],
+            false => self.rebind_processor.held_keys(),
+        };
+        if let Err(e) = self.keyboard_output.update(held_keys) {
+            self.log.warn(("keyboard", 0), time, || {
+                format!("keyboard output failed: {e}")
+            });
+        }
 
         //record axes data for virtual devices into plot data
-        self.plot_active_virtual_devices(time, plot)?;
+        if !self.performance_mode {
+            self.plot_active_virtual_devices(time, plot)?;
+        }
 
         //Output cached vjoy state to other programs
-        {
+        if !self.output_paused {
             profiling::scope!("RebindProcessor::process::output");
-            for vdevice in self.active_virtual_devices.iter() {
-                self.vjoy.update_device_state(&vdevice.handle)?;
+            for vdevice in self.active_virtual_devices.iter_mut() {
+                match self.output.write(&vdevice.handle) {
+                    Ok(()) => {
+                        vdevice.output_status.record_success();
+                        vdevice.output_rate.record(&vdevice.handle, time);
+                    }
+                    Err(e) => {
+                        self.log.warn(("output", vdevice.id as usize), time, || {
+                            format!("updating {} failed: {e}", vdevice.name())
+                        });
+                        if !self.output_failed {
+                            self.output_failed = true;
+                            self.virtual_rescan_retries = VIRTUAL_DEVICE_RESCAN_RETRIES;
+                        }
+                        if vdevice.output_status.record_failure(e.to_string()) {
+                            error!("{}: {}", vdevice.name(), vdevice.output_status.summary());
+                        }
+                    }
+                }
             }
         }
 
+        //Publish the same state to external readers
+        if let (false, Some(shared_memory)) = (self.output_paused, &mut self.shared_memory) {
+            shared_memory.publish(self.active_virtual_devices.iter().map(|d| &d.handle));
+        }
+
         self.x_bound_max = time;
         self.x_bound_min = time - 10.0;
         if plot {
             self.last_plot_time = time;
         }
         self.last_poll_time = time;
+        self.tick_times
+            .record(time, tick_start.elapsed().as_secs_f64());
         Ok(())
     }
 
+    /// Writes neutral state to every virtual device unless output is paused, then releases them.
+    /// Input stops updating afterwards. Runs once, later calls do nothing.
     #[profiling::function]
-    pub fn save_rebinds(&mut self, path: &Path) -> Result<(), Error> {
-        self.rebind_processor.save_rebinds(path)
+    pub fn shutdown(&mut self) {
+        if self.shut_down {
+            return;
+        }
+        self.shut_down = true;
+
+        if !self.output_paused {
+            write_neutral_outputs(&mut self.active_virtual_devices);
+            for vdevice in self.active_virtual_devices.iter() {
+                if let Err(e) = self.output.write(&vdevice.handle) {
+                    error!("Failed to reset {} to neutral. Reason: {e}", vdevice.name());
+                }
+            }
+        }
+        if let Err(e) = self.keyboard_output.update(&[]) {
+            error!("Failed to release the keys of keyboard rebinds. Reason: {e}");
+        }
+        if let Err(e) = self.set_shared_memory_enabled(false) {
+            error!("Failed to close the shared memory block. Reason: {e}");
+        }
+
+        // vJoy relinquishes its devices when dropped
+        drop(std::mem::replace(
+            &mut self.output,
+            Box::new(ReleasedOutput),
+        ));
+        self.active_virtual_devices.clear();
+        info!("Released virtual devices");
+    }
+
+    /// Low battery warnings raised since the last call
+    pub fn take_power_warnings(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.power_warnings)
+    }
+
+    /// Whether vJoy devices appeared or disappeared since the last call
+    pub fn take_virtual_devices_changed(&mut self) -> bool {
+        std::mem::take(&mut self.virtual_devices_changed)
+    }
+
+    /// Blocks until the next input tick is due, see [`PollTimer`]
+    pub fn wait_for_tick(&mut self) {
+        self.poll_timer.wait();
+    }
+
+    /// Mean interval between the poll timer's ticks and its mean absolute deviation, in seconds
+    pub fn get_poll_timing(&self) -> (f64, f64) {
+        self.poll_timer.timing()
+    }
+
+    /// Rebind processing time of the recent ticks, for the performance tab
+    pub fn processing_times(&self) -> &TimingHistory {
+        &self.processing_times
+    }
+
+    pub fn is_performance_mode(&self) -> bool {
+        self.performance_mode
+    }
+
+    /// Switches between ticks that record for the input viewer and ticks that only poll, process
+    /// and output. The plots start empty when leaving performance mode.
+    pub fn set_performance_mode(&mut self, enabled: bool) {
+        if enabled == self.performance_mode {
+            return;
+        }
+        self.other_mode_tick_s = self
+            .tick_times
+            .summary(self.last_poll_time)
+            .map(|summary| summary.mean);
+        self.tick_times.clear();
+        self.performance_mode = enabled;
+        info!("Performance mode {}", if enabled { "on" } else { "off" });
+
+        if !enabled {
+            for device in self.connected_physical_devices.iter_mut() {
+                device.clear_history();
+            }
+            for device in self.active_virtual_devices.iter_mut() {
+                device.clear_history();
+            }
+        }
+    }
+
+    /// Mean tick duration in seconds in the current mode and in the other mode before the last
+    /// toggle
+    pub fn tick_time_comparison(&self) -> (Option<f64>, Option<f64>) {
+        let current = self
+            .tick_times
+            .summary(self.last_poll_time)
+            .map(|summary| summary.mean);
+        (current, self.other_mode_tick_s)
+    }
+
+    #[profiling::function]
+    pub fn save_rebinds(&mut self, path: &Path, overwrite: bool) -> Result<(), Error> {
+        self.rebind_processor.save_rebinds(path, overwrite)
     }
 
     #[profiling::function]
@@ -232,11 +706,30 @@ impl Input {
         self.rebind_processor.load_rebinds(path)
     }
 
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.rebind_processor.has_unsaved_changes()
+    }
+
+    #[profiling::function]
+    pub fn replace_config(&mut self, config: Config) -> Config {
+        self.rebind_processor.replace_config(config)
+    }
+
+    #[profiling::function]
+    pub fn get_config(&self) -> &Config {
+        self.rebind_processor.get_config()
+    }
+
     #[profiling::function]
     pub fn get_physical_device_info_map(&self) -> IndexMap<String, DeviceInfo> {
+        let button_labels = self.rebind_processor.get_button_labels();
         self.connected_physical_devices
             .iter()
-            .map(|d| (d.guid.to_owned(), DeviceInfo::from_physical(d)))
+            .filter(|d| d.connected)
+            .map(|d| {
+                let labels = button_labels.get(&d.guid).cloned().unwrap_or_default();
+                (d.guid.to_owned(), DeviceInfo::from_physical(d, labels))
+            })
             .collect()
     }
 
@@ -250,7 +743,16 @@ impl Input {
 
     #[profiling::function]
     pub fn physical_devices_count(&self) -> usize {
-        self.connected_physical_devices.len()
+        self.connected_physical_devices
+            .iter()
+            .filter(|d| d.connected)
+            .count()
+    }
+
+    /// Device keys of all physical devices that share their GUID with an earlier device
+    #[profiling::function]
+    pub fn get_duplicate_guids(&self) -> &[String] {
+        &self.duplicate_guids
     }
 
     #[profiling::function]
@@ -264,10 +766,29 @@ impl Input {
     }
 
     #[profiling::function]
-    pub fn selected_physical_devices(&self) -> impl Iterator<Item = &PhysicalDevice> {
+    pub fn selected_physical_devices<'a>(
+        &'a self,
+        selection: &'a DeviceSelection,
+    ) -> impl Iterator<Item = &'a PhysicalDevice> {
         self.connected_physical_devices
             .iter()
-            .filter(|device| device.selected)
+            .filter(|device| selection.is_physical_selected(&device.guid))
+    }
+
+    /// Selected physical devices along with their button labels
+    #[profiling::function]
+    pub fn selected_physical_devices_mut<'a>(
+        &'a mut self,
+        selection: &'a DeviceSelection,
+    ) -> impl Iterator<Item = (&'a mut PhysicalDevice, Option<&'a ButtonLabelMap>)> {
+        let labels = self.rebind_processor.get_button_labels();
+        self.connected_physical_devices
+            .iter_mut()
+            .filter(|d| selection.is_physical_selected(&d.guid))
+            .map(move |d| {
+                let device_labels = labels.get(&d.guid);
+                (d, device_labels)
+            })
     }
 
     #[profiling::function]
@@ -285,11 +806,34 @@ impl Input {
         self.active_virtual_devices.iter_mut()
     }
 
+    pub fn event_console_mut(&mut self) -> &mut EventConsole {
+        &mut self.event_console
+    }
+
+    /// Devices whose vJoy updates kept failing past the escalation threshold.
     #[profiling::function]
-    pub fn selected_virtual_devices(&self) -> impl Iterator<Item = &VirtualDevice> {
+    pub fn output_failures(&self) -> Vec<String> {
         self.active_virtual_devices
             .iter()
-            .filter(|device| device.selected)
+            .filter(|device| device.output_status.is_escalated())
+            .map(|device| format!("{}: {}", device.name(), device.output_status.summary()))
+            .collect()
+    }
+
+    pub fn acknowledge_output_failures(&mut self) {
+        for device in self.active_virtual_devices.iter_mut() {
+            device.output_status.acknowledge();
+        }
+    }
+
+    #[profiling::function]
+    pub fn selected_virtual_devices<'a>(
+        &'a self,
+        selection: &'a DeviceSelection,
+    ) -> impl Iterator<Item = &'a VirtualDevice> {
+        self.active_virtual_devices
+            .iter()
+            .filter(|device| selection.is_virtual_selected(device.id))
     }
 
     #[profiling::function]
@@ -311,28 +855,258 @@ impl Input {
     }
 
     #[profiling::function]
-    pub fn get_active_rebinds(&mut self) -> std::slice::IterMut<Rebind> {
-        self.rebind_processor.get_active_rebinds()
+    pub fn set_active_shift_mode(&mut self, mode: ShiftModeMask) {
+        self.rebind_processor.set_active_shift_mode(mode)
+    }
+
+    #[profiling::function]
+    pub fn is_output_enabled(&self) -> bool {
+        self.rebind_processor.is_output_enabled()
+    }
+
+    /// Virtual controls held from the test panel
+    pub fn manual_overrides(&self) -> &ManualOverrides {
+        &self.manual_overrides
+    }
+
+    pub fn manual_overrides_mut(&mut self) -> &mut ManualOverrides {
+        &mut self.manual_overrides
+    }
+
+    /// Runs `steps` against the live pipeline from the next tick on, replacing any running script
+    pub fn start_test_script(&mut self, steps: Vec<ScriptStep>) {
+        self.test_script = Some(ScriptRun::new(steps));
+    }
+
+    /// Stops the running script and its synthetic input, the results are kept
+    pub fn stop_test_script(&mut self) {
+        if let Some(script) = &mut self.test_script {
+            script.stop();
+        }
+    }
+
+    pub fn test_script(&self) -> Option<&ScriptRun> {
+        self.test_script.as_ref()
+    }
+
+    /// Time of the last processed tick, on the clock passed to [`Self::update`]
+    pub fn get_last_poll_time(&self) -> f64 {
+        self.last_poll_time
+    }
+
+    pub fn is_output_paused(&self) -> bool {
+        self.output_paused
+    }
+
+    pub fn set_output_paused(&mut self, paused: bool) {
+        info!("Output {}", if paused { "paused" } else { "resumed" });
+        self.output_paused = paused;
+    }
+
+    #[profiling::function]
+    pub fn get_config_path(&self) -> Option<&Path> {
+        self.rebind_processor.get_config_path()
+    }
+
+    #[profiling::function]
+    pub fn get_config_name(&self) -> &str {
+        self.rebind_processor.get_config_name()
     }
 
     #[profiling::function]
-    pub fn add_rebind(&mut self, rebind: Rebind) {
-        self.rebind_processor.add_rebind(rebind);
+    pub fn get_config_name_mut(&mut self) -> &mut String {
+        self.rebind_processor.get_config_name_mut()
     }
 
     #[profiling::function]
-    pub fn remove_rebinds_from_keep(&mut self, keep: &[bool]) {
-        self.rebind_processor.remove_rebinds_from_keep(keep);
+    pub fn get_default_shift_mode_mut(&mut self) -> &mut ShiftModeMask {
+        self.rebind_processor.get_default_shift_mode_mut()
     }
 
+    /// Describes every virtual device the loaded config references but vJoy doesn't provide
     #[profiling::function]
-    pub fn duplicate_rebinds_from_copy(&mut self, copy: Vec<Rebind>) {
-        self.rebind_processor.duplicate_rebinds_from_copy(copy);
+    pub fn check_virtual_capabilities(&self) -> Vec<String> {
+        let required = required_virtual_devices(self.rebind_processor.get_all_rebinds());
+        missing_virtual_capabilities(&required, &self.get_virtual_device_info_map())
     }
 
     #[profiling::function]
-    pub fn move_rebind(&mut self, index: usize, mov: isize) {
-        self.rebind_processor.move_rebind(index, mov);
+    pub fn get_virtual_usage(&self, current_mode_only: bool) -> VirtualUsage {
+        self.rebind_processor.virtual_usage(current_mode_only)
+    }
+
+    #[profiling::function]
+    pub fn get_read_order_warnings(&self, scope: RebindScope) -> HashMap<usize, String> {
+        self.rebind_processor.read_order_warnings(scope)
+    }
+
+    pub fn get_shift_conflicts(&self, scope: RebindScope) -> HashMap<usize, ShiftConflict> {
+        self.rebind_processor.shift_conflicts(scope)
+    }
+
+    #[profiling::function]
+    pub fn get_active_rebinds(&mut self, scope: RebindScope) -> std::slice::IterMut<Rebind> {
+        self.rebind_processor.get_active_rebinds(scope)
+    }
+
+    #[profiling::function]
+    pub fn save_global_rebinds(&self) -> Result<(), Error> {
+        self.rebind_processor.save_global_rebinds()
+    }
+
+    #[profiling::function]
+    pub fn get_button_labels(&self) -> &BTreeMap<String, ButtonLabelMap> {
+        self.rebind_processor.get_button_labels()
+    }
+
+    #[profiling::function]
+    pub fn get_button_labels_mut(&mut self) -> &mut BTreeMap<String, ButtonLabelMap> {
+        self.rebind_processor.get_button_labels_mut()
+    }
+
+    pub fn is_hid_enabled(&self, guid: &str) -> bool {
+        self.hid_devices.contains(guid)
+    }
+
+    /// Reads the device through raw HID instead of SDL. Reopens the device.
+    pub fn set_hid_enabled(&mut self, guid: &str, enabled: bool) -> Result<(), Error> {
+        let changed = match enabled {
+            true => self.hid_devices.insert(guid.to_string()),
+            false => self.hid_devices.remove(guid),
+        };
+        if changed {
+            // Opened again through the other backend like a returning device
+            for device in self
+                .connected_physical_devices
+                .iter_mut()
+                .filter(|d| d.guid == guid)
+            {
+                device.connected = false;
+            }
+            self.fetch_connected_devices()?;
+        }
+        Ok(())
+    }
+
+    #[profiling::function]
+    pub fn get_device_transforms_mut(&mut self) -> &mut BTreeMap<String, DeviceTransform> {
+        self.rebind_processor.get_device_transforms_mut()
+    }
+
+    pub fn get_axis_smoothing_mut(&mut self) -> &mut Vec<AxisSmoothing> {
+        self.rebind_processor.get_axis_smoothing_mut()
+    }
+
+    pub fn get_axis_neutrals_mut(&mut self) -> &mut Vec<AxisNeutral> {
+        self.rebind_processor.get_axis_neutrals_mut()
+    }
+
+    pub fn get_mode_axis_presets_mut(&mut self) -> &mut Vec<ModeAxisPreset> {
+        self.rebind_processor.get_mode_axis_presets_mut()
+    }
+
+    pub fn get_mirror_mut(&mut self) -> &mut Option<Mirror> {
+        self.rebind_processor.get_mirror_mut()
+    }
+
+    /// Version of the vJoy driver, `None` if the DLL doesn't report one
+    pub fn get_driver_version(&self) -> Option<&str> {
+        self.output.driver_version()
+    }
+
+    pub fn get_mirror_status(&self) -> Option<(Vec<VirtualControl>, Vec<VirtualControl>)> {
+        self.rebind_processor.mirror_status()
+    }
+
+    pub fn get_overlays_mut(&mut self) -> &mut Vec<Overlay> {
+        self.rebind_processor.get_overlays_mut()
+    }
+
+    pub fn get_overlays(&self) -> &[Overlay] {
+        self.rebind_processor.get_overlays()
+    }
+
+    pub fn get_active_overlay(&self) -> Option<&str> {
+        self.rebind_processor.get_active_overlay()
+    }
+
+    pub fn is_shared_memory_enabled(&self) -> bool {
+        self.shared_memory.is_some()
+    }
+
+    /// Creates or removes the shared memory block the virtual device state is published to.
+    pub fn set_shared_memory_enabled(&mut self, enabled: bool) -> Result<(), Error> {
+        match (enabled, self.shared_memory.is_some()) {
+            (true, false) => {
+                self.shared_memory = Some(SharedMemoryPublisher::create()?);
+                info!("Publishing virtual devices to shared memory {SHARED_MEMORY_NAME}");
+            }
+            (false, true) => {
+                self.shared_memory = None;
+                info!("Stopped publishing virtual devices to shared memory");
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    pub fn midi_port(&self) -> Option<&str> {
+        self.midi_output.as_ref().map(|m| m.port())
+    }
+
+    /// Connects the MIDI feedback to `port` or disconnects it for `None`. The previous port stays
+    /// disconnected if connecting fails.
+    pub fn set_midi_port(&mut self, port: Option<&str>) -> Result<(), Error> {
+        if self.midi_port() == port {
+            return Ok(());
+        }
+        self.midi_output = None;
+        if let Some(port) = port {
+            self.midi_output = Some(MidiOutput::connect(port)?);
+        } else {
+            info!("Stopped sending MIDI feedback");
+        }
+        Ok(())
+    }
+
+    /// Sends every MIDI feedback entry as active for a moment
+    pub fn start_midi_test(&mut self) {
+        if let Some(midi_output) = &mut self.midi_output {
+            midi_output.start_test(self.last_poll_time);
+        }
+    }
+
+    pub fn get_midi_feedback_mut(&mut self) -> &mut Vec<MidiFeedback> {
+        self.rebind_processor.get_midi_feedback_mut()
+    }
+
+    pub fn get_processing_budget(&self) -> &ProcessingBudget {
+        self.rebind_processor.get_processing_budget()
+    }
+
+    pub fn get_processing_budget_mut(&mut self) -> &mut ProcessingBudget {
+        self.rebind_processor.get_processing_budget_mut()
+    }
+
+    #[profiling::function]
+    pub fn add_rebind(&mut self, scope: RebindScope, rebind: Rebind) {
+        self.rebind_processor.add_rebind(scope, rebind);
+    }
+
+    #[profiling::function]
+    pub fn remove_rebinds_from_keep(&mut self, scope: RebindScope, keep: &[bool]) {
+        self.rebind_processor.remove_rebinds_from_keep(scope, keep);
+    }
+
+    #[profiling::function]
+    pub fn duplicate_rebinds_from_copy(&mut self, scope: RebindScope, copy: Vec<Rebind>) {
+        self.rebind_processor
+            .duplicate_rebinds_from_copy(scope, copy);
+    }
+
+    #[profiling::function]
+    pub fn move_rebind(&mut self, scope: RebindScope, index: usize, mov: isize) {
+        self.rebind_processor.move_rebind(scope, index, mov);
     }
 
     #[profiling::function]
@@ -341,33 +1115,14 @@ impl Input {
     }
 
     fn fetch_connected_devices(&mut self) -> Result<(), Error> {
-        let num_devices_total = self.joystick_systen.num_joysticks()?;
+        let num_devices_total = self.joysticks.num_joysticks()?;
         let mut num_virtual_devices_found = 0;
 
-        self.active_virtual_devices = self
-            .vjoy
-            .devices_cloned()
-            .into_iter()
-            .map(|vd| {
-                let axes_plot_data = vd
-                    .axes()
-                    .map(|_| AllocRingBuffer::with_capacity(1024))
-                    .collect();
-
-                VirtualDevice {
-                    id: vd.id(),
-                    handle: vd,
-                    axes_plot_data,
-                    selected: false,
-                }
-            })
-            .collect();
-
-        self.connected_physical_devices = (0..num_devices_total)
+        let physical_sources: Vec<(u32, String)> = (0..num_devices_total)
             .filter_map(|index| {
-                match self.joystick_systen.device_guid(index).ok() {
-                    Some(guid) => {
-                        let guid_str = guid.to_string();
+                match self.joysticks.device_guid(index) {
+                    Some(guid_str) => {
+                        let guid_str = normalize_guid(&guid_str);
                         // Skip known vjoy guid
                         if guid_str.eq(&"0300f80034120000adbe000000000000")
                             || guid_str.eq(&"0300000034120000adbe000000000000")
@@ -381,43 +1136,199 @@ impl Input {
                     None => None,
                 }
             })
-            .filter_map(|(index, guid)| {
-                let handle = self.joystick_systen.open(index);
-                match handle {
-                    Ok(handle) => {
-                        let input_state = InputState::new(&handle);
-                        let axes_plot_data = input_state
-                            .axes()
-                            .map(|_| AllocRingBuffer::with_capacity(512))
-                            .collect();
-                        trace!("adding device: {} | GUID: {}", handle.name(), handle.guid());
-
-                        Some(PhysicalDevice {
-                            guid,
-                            handle,
-                            input_state,
-                            selected: false,
-                            axes_plot_data,
-                        })
-                    }
-                    Err(_) => None,
-                }
-            })
             .collect();
 
-        assert_eq!(self.active_virtual_devices.len(), num_virtual_devices_found);
+        let keys = disambiguate_guids(physical_sources.iter().map(|(_, guid)| guid.as_str()));
+        self.duplicate_guids = keys
+            .iter()
+            .filter(|(_, occurrence)| *occurrence > 1)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in self.duplicate_guids.iter() {
+            warn!("Duplicate device GUID from SDL, device is keyed as {key}");
+        }
+
+        for device in self.connected_physical_devices.iter_mut() {
+            if device.connected && !keys.iter().any(|(guid, _)| *guid == device.guid) {
+                device.connected = false;
+                info!("{} disconnected", device.name());
+            }
+        }
+
+        for ((index, _), (guid, occurrence)) in physical_sources.into_iter().zip(keys) {
+            let Ok(handle) = self.joysticks.open(index) else {
+                continue;
+            };
+            let slot = self
+                .connected_physical_devices
+                .iter()
+                .position(|d| d.guid == guid);
+            // Devices that stayed connected keep their handle. Among duplicate GUIDs the key can
+            // move to another device, the instance id tells them apart.
+            if let Some(slot) = slot {
+                let device = &self.connected_physical_devices[slot];
+                if device.connected && device.instance_id() == handle.instance_id() {
+                    continue;
+                }
+            }
+
+            let handle = match self.hid_devices.contains(&guid) {
+                true => hid::open(handle, occurrence),
+                false => handle,
+            };
+            match slot {
+                Some(slot) => {
+                    let device = &mut self.connected_physical_devices[slot];
+                    device.reconnect(handle);
+                    info!("{} connected again", device.name());
+                }
+                None => {
+                    trace!("adding device: {} | GUID: {}", handle.name(), handle.guid());
+                    self.connected_physical_devices
+                        .push(PhysicalDevice::new(guid, occurrence, handle));
+                }
+            }
+        }
+
+        // vJoy devices enabled or disabled show up in SDL as well
+        self.num_sdl_virtual_devices = num_virtual_devices_found;
+        self.virtual_scan_pending = true;
+        self.virtual_rescan_retries = VIRTUAL_DEVICE_RESCAN_RETRIES;
+        self.rebind_processor.invalidate_resolved_slots();
         Ok(())
     }
 
+    /// Picks up vJoy devices enabled or disabled while running. vJoy is only enumerated again
+    /// while it has fewer or more devices than SDL sees or after output failures, and only a few
+    /// times, since that briefly releases the devices.
+    fn scan_virtual_devices(&mut self, time: f64) {
+        self.last_virtual_scan_time = time;
+        self.virtual_scan_pending = false;
+
+        let mut changed = self.sync_virtual_devices();
+        let mismatch = self.active_virtual_devices.len() != self.num_sdl_virtual_devices;
+        if self.virtual_rescan_retries > 0 && (mismatch || self.output_failed) {
+            self.virtual_rescan_retries -= 1;
+            trace!("enumerating the vJoy devices again");
+            match self.output.rescan() {
+                Ok(()) => changed |= self.sync_virtual_devices(),
+                Err(e) => warn!("Failed to enumerate the vJoy devices again. Reason: {e}"),
+            }
+        }
+        self.output_failed = false;
+
+        if changed {
+            self.rebind_processor.invalidate_resolved_slots();
+            self.virtual_devices_changed = true;
+        }
+    }
+
+    /// Adds the devices the output backend reports and drops the ones it no longer does. Returns
+    /// whether anything changed.
+    fn sync_virtual_devices(&mut self) -> bool {
+        let available = self.output.devices();
+        let before = self.active_virtual_devices.len();
+        self.active_virtual_devices.retain(|device| {
+            let keep = available.iter().any(|state| device.same_layout(state));
+            if !keep {
+                info!("{} is no longer available", device.name());
+            }
+            keep
+        });
+        let mut changed = self.active_virtual_devices.len() != before;
+
+        for state in available {
+            if self
+                .active_virtual_devices
+                .iter()
+                .any(|d| d.id == state.id())
+            {
+                continue;
+            }
+            let device = VirtualDevice::new(state);
+            info!(
+                "{} is available, rebinds writing to it start",
+                device.name()
+            );
+            self.active_virtual_devices.push(device);
+            changed = true;
+        }
+        self.active_virtual_devices.sort_by_key(|d| d.id);
+        changed
+    }
+
     #[profiling::function]
     fn poll_connected_physical_devices(&mut self, time: f64, plot: bool) -> Result<(), Error> {
-        for device in self.connected_physical_devices.iter_mut() {
-            device.update(plot, time)?;
+        let transforms = self.rebind_processor.get_device_transforms();
+        let recording = self.event_console.is_recording() && !self.performance_mode;
+        let mut changes = Vec::new();
+        for (index, device) in self
+            .connected_physical_devices
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, d)| d.connected)
+        {
+            match transforms.get(&device.guid) {
+                Some(transform) => device.input_state.set_transform(transform),
+                None => device
+                    .input_state
+                    .set_transform(&DeviceTransform::default()),
+            }
+            match device.update(time, recording.then_some(&mut changes)) {
+                Ok(()) if !self.performance_mode => device.record_history(plot, time),
+                Ok(()) => (),
+                Err(e) => self.log.warn(("poll", index), time, || {
+                    format!("polling device {} failed: {e}", device.name())
+                }),
+            }
+            if !changes.is_empty() {
+                self.event_console
+                    .record(&device.name(), time, changes.drain(..));
+            }
         }
 
         Ok(())
     }
 
+    /// Queries the power level of new devices right away and of all devices at a low rate
+    #[profiling::function]
+    fn poll_power_levels(&mut self, time: f64) {
+        let due = time - self.last_power_poll_time >= POWER_POLL_INTERVAL;
+        if due {
+            self.last_power_poll_time = time;
+        }
+
+        for (index, device) in self
+            .connected_physical_devices
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, d)| d.connected)
+        {
+            if !due && device.power.level().is_some() {
+                continue;
+            }
+            match device.handle.power_level() {
+                Ok(level) => {
+                    if device.power.update(level) {
+                        let message = format!(
+                            "Low battery: {} ({})",
+                            device.name(),
+                            device.power.describe()
+                        );
+                        warn!("{message}");
+                        self.power_warnings.push(message);
+                    }
+                }
+                Err(e) => {
+                    self.log.warn(("power", index), time, || {
+                        format!("reading power level of {} failed: {e}", device.name())
+                    });
+                    device.power.update(PowerLevel::Unknown);
+                }
+            }
+        }
+    }
+
     #[profiling::function]
     fn plot_active_virtual_devices(&mut self, time: f64, plot: bool) -> Result<(), Error> {
         for device in self.active_virtual_devices.iter_mut() {
@@ -427,3 +1338,1099 @@ impl Input {
         Ok(())
     }
 }
+
+/// Device key and 1-based occurrence for every GUID in enumeration order.
+///
+/// SDL can report the same GUID for several devices (e.g. identical adapters). The first
+/// occurrence keeps the plain GUID so existing configs still match, later ones get
+/// "GUID#2", "GUID#3", ... appended.
+pub fn disambiguate_guids<'a>(guids: impl Iterator<Item = &'a str>) -> Vec<(String, u32)> {
+    let mut seen: HashMap<&str, u32> = HashMap::new();
+    guids
+        .map(|guid| {
+            let occurrence = seen.entry(guid).or_insert(0);
+            *occurrence += 1;
+            match *occurrence {
+                1 => (guid.to_string(), 1),
+                n => (format!("{guid}{GUID_OCCURRENCE_SEPARATOR}{n}"), n),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+    use sdl2::joystick::{HatState, PowerLevel};
+    use vjoy::{ButtonState, FourWayHat, HatState as VJoyHatState};
+
+    use crate::{
+        config::Config,
+        input::{
+            backend::fake::{FakeJoystick, FakeJoystickBackend, FakeOutput},
+            device_transform::{AxisCalibration, AxisTransform, DeviceTransform},
+            disambiguate_guids,
+            event_console::{ControlChange, ControlKind},
+            keyboard_output::{KeyEvent, KeySink, KeyboardOutput},
+            midi_output::{MidiOutput, MidiSink},
+            test_script::{parse_script, StepStatus},
+            virtual_state::VirtualState,
+            Input,
+        },
+        rebind::{
+            axis_gain::ButtonSource,
+            axis_neutral::{AxisNeutral, AXIS_CENTER},
+            axis_to_axis::AxisFreeze,
+            axis_to_button::AxisToButtonModifier,
+            button_to_button::ButtonToButtonModifier,
+            hat_select::HatDiagonals,
+            hat_to_hat::HatToHatModifier,
+            keyboard_rebind::{ButtonToKeyModifier, Key, KeyboardRebind},
+            logical_rebind::LogicalRebind,
+            merge_axes::AxisSource,
+            midi_feedback::{MidiEvent, MidiFeedback, MidiMessageKind},
+            output_smoothing::AxisSmoothing,
+            overlay::{Overlay, OverlayOverride},
+            rebind_processor::RebindProcessor,
+            reroute_rebind::RerouteRebind,
+            shift_mode_mask::ShiftModeMask,
+            source_lost::SourceLostPolicy,
+            two_buttons_to_axis::TwoButtonsToAxisModifier,
+            virtual_rebind::{ProcessingPhase, VirtualRebind},
+            Rebind, RebindType, VirtualControl,
+        },
+    };
+
+    const STICK: &str = "stick";
+
+    /// Input with one fake stick (4 buttons, 2 axes, 1 hat) and vJoy device 1
+    struct Rig {
+        input: Input,
+        stick: FakeJoystick,
+        joysticks: FakeJoystickBackend,
+        output: FakeOutput,
+        time: f64,
+    }
+
+    impl Rig {
+        fn new(rebinds: Vec<Rebind>) -> Self {
+            Self::with_stick_guid(STICK, rebinds)
+        }
+
+        fn with_stick_guid(guid: &str, rebinds: Vec<Rebind>) -> Self {
+            let stick = FakeJoystick::new(guid, 4, 2, 1);
+            let joysticks = FakeJoystickBackend::new(vec![stick.clone(), FakeJoystick::vjoy()]);
+            let output = FakeOutput::new(&[(1, 8, 2, 1)]);
+            let config = Config {
+                rebinds,
+                ..Default::default()
+            };
+            let input = Input::with_backends(
+                Box::new(joysticks.clone()),
+                Box::new(output.clone()),
+                RebindProcessor::from_config(config),
+            );
+
+            let mut rig = Self {
+                input,
+                stick,
+                joysticks,
+                output,
+                time: 0.0,
+            };
+            // Let the stick finish its warm-up
+            for _ in 0..20 {
+                rig.tick();
+            }
+            rig
+        }
+
+        /// One poll 10 ms after the last, returns what was written to vJoy device 1
+        fn tick(&mut self) -> VirtualState {
+            self.time += 0.01;
+            self.input.update(self.time).unwrap();
+            self.output.last(1)
+        }
+    }
+
+    fn rebind(mode_mask: u8, rebind_type: RebindType) -> Rebind {
+        Rebind::new(String::new(), ShiftModeMask(mode_mask), rebind_type)
+    }
+
+    fn button_to_button(
+        src_button: u32,
+        dst_button: u32,
+        modifier: ButtonToButtonModifier,
+    ) -> RebindType {
+        RebindType::Reroute {
+            rebind: RerouteRebind::ButtonToButton {
+                src: ButtonSource::physical(STICK.to_string(), src_button),
+                dst_device: 1,
+                dst_button,
+                modifier,
+            },
+        }
+    }
+
+    fn button(state: &VirtualState, button: usize) -> ButtonState {
+        state.buttons().nth(button - 1).unwrap().get()
+    }
+
+    #[test]
+    fn toggle_and_axis_conversion_over_ticks() {
+        let mut rig = Rig::new(vec![
+            rebind(
+                0,
+                button_to_button(1, 1, ButtonToButtonModifier::Toggle { last_input: false }),
+            ),
+            rebind(
+                0,
+                RebindType::Reroute {
+                    rebind: RerouteRebind::AxisToAxis {
+                        src: AxisSource::physical(STICK.to_string(), 2),
+                        dst_device: 1,
+                        dst_axis: 1,
+                        modifier: Default::default(),
+                        freeze: None,
+                        gain: None,
+                    },
+                },
+            ),
+            rebind(
+                0,
+                RebindType::Reroute {
+                    rebind: RerouteRebind::HatToHat {
+                        src_device: STICK.to_string(),
+                        src_hat: 1,
+                        dst_device: 1,
+                        dst_hat: 1,
+                        modifier: HatToHatModifier::default(),
+                    },
+                },
+            ),
+        ]);
+
+        assert_eq!(button(&rig.tick(), 1), ButtonState::Released);
+
+        let presses = [true, false, false, true, false];
+        let expected = [true, true, true, false, false];
+        for (pressed, expected) in presses.into_iter().zip(expected) {
+            rig.stick.set_button(0, pressed);
+            let state = rig.tick();
+            let expected = match expected {
+                true => ButtonState::Pressed,
+                false => ButtonState::Released,
+            };
+            assert_eq!(button(&state, 1), expected);
+        }
+
+        for (input, output) in [(i16::MIN, 0), (i16::MAX, 32767)] {
+            rig.stick.set_axis(1, input);
+            assert_eq!(rig.tick().axes().next().unwrap().get(), output);
+        }
+
+        rig.stick.set_hat(0, HatState::Right);
+        assert_eq!(
+            rig.tick().hats().next().unwrap().get(),
+            VJoyHatState::Discrete(FourWayHat::East)
+        );
+    }
+
+    #[test]
+    fn mixed_case_guids_match_after_a_config_round_trip() {
+        let config = Config {
+            rebinds: vec![rebind(
+                0,
+                RebindType::Reroute {
+                    rebind: RerouteRebind::ButtonToButton {
+                        src: ButtonSource::physical(" StiCK\t".to_string(), 1),
+                        dst_device: 1,
+                        dst_button: 1,
+                        modifier: ButtonToButtonModifier::Simple,
+                    },
+                },
+            )],
+            ..Default::default()
+        };
+        let config = Config::from_toml_str(&config.to_toml_string().unwrap()).unwrap();
+
+        let mut rig = Rig::with_stick_guid("STICK", config.rebinds);
+        assert_eq!(rig.input.physical_devices().next().unwrap().guid, STICK);
+        rig.stick.set_button(0, true);
+        assert_eq!(button(&rig.tick(), 1), ButtonState::Pressed);
+    }
+
+    #[test]
+    fn manual_overrides_win_until_released() {
+        let mut rig = Rig::new(vec![rebind(
+            0,
+            button_to_button(1, 1, ButtonToButtonModifier::Simple),
+        )]);
+        let button_1 = VirtualControl::Button {
+            device: 1,
+            button: 1,
+        };
+        let button_2 = VirtualControl::Button {
+            device: 1,
+            button: 2,
+        };
+        let axis_1 = VirtualControl::Axis { device: 1, axis: 1 };
+
+        rig.input.manual_overrides_mut().replace(HashMap::from([
+            (button_1, 1),
+            (button_2, 1),
+            (axis_1, 1000),
+        ]));
+        let state = rig.tick();
+        assert_eq!(button(&state, 1), ButtonState::Pressed);
+        assert_eq!(button(&state, 2), ButtonState::Pressed);
+        assert_eq!(state.axes().next().unwrap().get(), 1000);
+
+        // The rebind takes the button over again, unwritten controls return to neutral
+        rig.stick.set_button(0, true);
+        rig.input.manual_overrides_mut().clear();
+        let state = rig.tick();
+        assert_eq!(button(&state, 1), ButtonState::Pressed);
+        assert_eq!(button(&state, 2), ButtonState::Released);
+        assert_eq!(state.axes().next().unwrap().get(), AXIS_CENTER);
+        rig.stick.set_button(0, false);
+        assert_eq!(button(&rig.tick(), 1), ButtonState::Released);
+    }
+
+    #[test]
+    fn shift_mode_gates_rebinds() {
+        let mut rig = Rig::new(vec![
+            rebind(
+                0,
+                RebindType::Logical {
+                    rebind: LogicalRebind::MomentaryEnableShiftMode {
+                        src_device: STICK.to_string(),
+                        src_button: 4,
+                        shift_mask: ShiftModeMask(0b00000001),
+                    },
+                },
+            ),
+            rebind(0, button_to_button(1, 1, ButtonToButtonModifier::Simple)),
+            rebind(
+                0b00000001,
+                button_to_button(1, 2, ButtonToButtonModifier::Simple),
+            ),
+        ]);
+
+        rig.stick.set_button(0, true);
+        let state = rig.tick();
+        assert_eq!(button(&state, 1), ButtonState::Pressed);
+        assert_eq!(button(&state, 2), ButtonState::Released);
+
+        rig.stick.set_button(3, true);
+        let state = rig.tick();
+        assert_eq!(button(&state, 1), ButtonState::Pressed);
+        assert_eq!(button(&state, 2), ButtonState::Pressed);
+
+        // Outside its mode the rebind doesn't write, the output keeps its last value
+        rig.stick.set_button(3, false);
+        rig.stick.set_button(0, false);
+        let state = rig.tick();
+        assert_eq!(button(&state, 1), ButtonState::Released);
+        assert_eq!(button(&state, 2), ButtonState::Pressed);
+    }
+
+    #[test]
+    fn trimmed_axis_still_ramps_when_its_reroute_changes() {
+        let axis_to_axis = |src_axis| RebindType::Reroute {
+            rebind: RerouteRebind::AxisToAxis {
+                src: AxisSource::physical(STICK.to_string(), src_axis),
+                dst_device: 1,
+                dst_axis: 1,
+                modifier: Default::default(),
+                freeze: None,
+                gain: None,
+            },
+        };
+        let mut rig = Rig::new(vec![
+            rebind(
+                0,
+                RebindType::Logical {
+                    rebind: LogicalRebind::MomentaryEnableShiftMode {
+                        src_device: STICK.to_string(),
+                        src_button: 4,
+                        shift_mask: ShiftModeMask(0b00000001),
+                    },
+                },
+            ),
+            rebind(0, axis_to_axis(1)),
+            rebind(0b00000001, axis_to_axis(2)),
+            rebind(
+                0,
+                RebindType::Virtual {
+                    rebind: VirtualRebind::VirtualAxisApplyButtonTrim {
+                        axis_device: 1,
+                        axis: 1,
+                        trim_neg_device: 1,
+                        trim_neg_button: 1,
+                        trim_pos_device: 1,
+                        trim_pos_button: 2,
+                        trim_reset_device: 1,
+                        trim_reset_button: 3,
+                        modifier: Default::default(),
+                    },
+                    phase: ProcessingPhase::PostReroute,
+                },
+            ),
+        ]);
+        rig.input
+            .get_axis_smoothing_mut()
+            .push(AxisSmoothing::new(1, 1, 1.0));
+
+        rig.stick.set_axis(0, i16::MIN);
+        rig.stick.set_axis(1, i16::MAX);
+        assert_eq!(rig.tick().axes().next().unwrap().get(), 0);
+
+        // The writer changes from the first to the second reroute, the trim after them does not hide it
+        rig.stick.set_button(3, true);
+        let ramped = rig.tick().axes().next().unwrap().get();
+        assert!(ramped > 0 && ramped < 32767, "{ramped}");
+    }
+
+    #[test]
+    fn axis_presses_detent_buttons() {
+        let mut rig = Rig::new(vec![rebind(
+            0,
+            RebindType::Reroute {
+                rebind: RerouteRebind::AxisToButton {
+                    src_device: STICK.to_string(),
+                    src_axis: 1,
+                    dst_device: 1,
+                    dst_button: 1,
+                    modifier: AxisToButtonModifier::Detents {
+                        low: -0.5,
+                        high: 0.5,
+                        hysteresis: 0.25,
+                        high_button: 2,
+                        low_pressed: false,
+                        high_pressed: false,
+                    },
+                },
+            },
+        )]);
+
+        let steps = [
+            (0, [false, false]),
+            (-20000, [true, false]),
+            // Inside the hysteresis band the low button stays pressed
+            (-10000, [true, false]),
+            (0, [false, false]),
+            (20000, [false, true]),
+            (10000, [false, true]),
+            (5000, [false, false]),
+        ];
+        for (axis, expected) in steps {
+            rig.stick.set_axis(0, axis);
+            let state = rig.tick();
+            let pressed = [1, 2].map(|b| button(&state, b) == ButtonState::Pressed);
+            assert_eq!(pressed, expected, "axis {axis}");
+        }
+    }
+
+    #[test]
+    fn test_script_drives_the_pipeline() {
+        let mut rig = Rig::new(vec![
+            rebind(0, button_to_button(1, 1, ButtonToButtonModifier::Simple)),
+            rebind(
+                0b00000010,
+                button_to_button(2, 2, ButtonToButtonModifier::Simple),
+            ),
+        ]);
+        let script = "press dev 'Fake stick' button 1 for 50ms
+                      expect vjoy1 button 1 pressed
+                      wait 100ms
+                      expect button 1 released
+                      set mode 0b10
+                      press dev STICK button 2
+                      expect button 2 pressed; expect mode 0b10
+                      expect button 3 pressed
+                      release dev stick button 2
+                      expect button 2 released
+                      press dev 'Missing' button 1";
+        rig.input.start_test_script(parse_script(script).unwrap());
+        for _ in 0..50 {
+            rig.tick();
+        }
+
+        let run = rig.input.test_script().unwrap();
+        assert!(run.is_finished());
+        let statuses: Vec<StepStatus> = run.results().map(|(_, s)| s.clone()).collect();
+        let failed = |message: &str| StepStatus::Failed(message.to_string());
+        assert_eq!(
+            statuses,
+            [
+                vec![StepStatus::Passed; 8],
+                vec![failed("was 0")],
+                vec![StepStatus::Passed; 2],
+                vec![failed("no connected device 'Missing'")],
+            ]
+            .concat()
+        );
+        assert_eq!(run.counts(), (10, 2));
+        assert_eq!(rig.input.get_active_shift_mode(), ShiftModeMask(0b10));
+    }
+
+    #[test]
+    fn overlay_replaces_rebind_while_held() {
+        let mut fire = rebind(0, button_to_button(1, 1, ButtonToButtonModifier::Simple));
+        fire.name = "Fire".to_string();
+        let fire_id = fire.id;
+        let mut rig = Rig::new(vec![
+            rebind(
+                0,
+                RebindType::Logical {
+                    rebind: LogicalRebind::MomentaryOverlay {
+                        src_device: STICK.to_string(),
+                        src_button: 4,
+                        overlay: "Alt".to_string(),
+                    },
+                },
+            ),
+            fire,
+        ]);
+        rig.input.get_overlays_mut().push(Overlay {
+            name: "Alt".to_string(),
+            overrides: vec![OverlayOverride {
+                rebind_id: Some(fire_id),
+                rebind_name: String::new(),
+                rebind_type: button_to_button(1, 2, ButtonToButtonModifier::Simple),
+                resolved: Default::default(),
+            }],
+        });
+
+        rig.stick.set_button(0, true);
+        let state = rig.tick();
+        assert_eq!(button(&state, 1), ButtonState::Pressed);
+        assert_eq!(button(&state, 2), ButtonState::Released);
+
+        // Like a gated rebind, the replaced instance stops writing and its output holds
+        rig.stick.set_button(3, true);
+        rig.stick.set_button(0, false);
+        let state = rig.tick();
+        assert_eq!(rig.input.get_active_overlay(), Some("Alt"));
+        assert_eq!(button(&state, 1), ButtonState::Pressed);
+        assert_eq!(button(&state, 2), ButtonState::Released);
+
+        rig.stick.set_button(0, true);
+        assert_eq!(button(&rig.tick(), 2), ButtonState::Pressed);
+
+        rig.stick.set_button(3, false);
+        rig.stick.set_button(0, false);
+        let state = rig.tick();
+        assert_eq!(rig.input.get_active_overlay(), None);
+        assert_eq!(button(&state, 1), ButtonState::Released);
+        assert_eq!(button(&state, 2), ButtonState::Pressed);
+    }
+
+    #[test]
+    fn overlay_only_replaces_the_rebind_it_was_made_for() {
+        let mut fire = rebind(0, button_to_button(1, 1, ButtonToButtonModifier::Simple));
+        let mut namesake = rebind(0, button_to_button(2, 2, ButtonToButtonModifier::Simple));
+        fire.name = "Fire".to_string();
+        namesake.name = "Fire".to_string();
+        let fire_id = fire.id;
+        let mut rig = Rig::new(vec![
+            rebind(
+                0,
+                RebindType::Logical {
+                    rebind: LogicalRebind::MomentaryOverlay {
+                        src_device: STICK.to_string(),
+                        src_button: 4,
+                        overlay: "Alt".to_string(),
+                    },
+                },
+            ),
+            fire,
+            namesake,
+        ]);
+        rig.input.get_overlays_mut().push(Overlay {
+            name: "Alt".to_string(),
+            overrides: vec![OverlayOverride {
+                rebind_id: Some(fire_id),
+                rebind_name: String::new(),
+                rebind_type: button_to_button(1, 3, ButtonToButtonModifier::Simple),
+                resolved: Default::default(),
+            }],
+        });
+
+        rig.stick.set_button(3, true);
+        rig.tick();
+        rig.stick.set_button(0, true);
+        rig.stick.set_button(1, true);
+        let state = rig.tick();
+        assert_eq!(button(&state, 1), ButtonState::Released);
+        assert_eq!(button(&state, 2), ButtonState::Pressed);
+        assert_eq!(button(&state, 3), ButtonState::Pressed);
+    }
+
+    fn hat_select(latch: bool, diagonals: HatDiagonals) -> Rebind {
+        rebind(
+            0,
+            RebindType::Logical {
+                rebind: LogicalRebind::HatSelectShiftMode {
+                    src_device: STICK.to_string(),
+                    src_hat: 1,
+                    north_mask: ShiftModeMask(0b00000001),
+                    east_mask: ShiftModeMask(0b00000010),
+                    south_mask: ShiftModeMask(0b00000100),
+                    west_mask: ShiftModeMask(0b00001000),
+                    centered_mask: ShiftModeMask(0b00010000),
+                    latch,
+                    diagonals,
+                    selected: Default::default(),
+                },
+            },
+        )
+    }
+
+    #[test]
+    fn hat_selects_shift_mode_with_rounded_diagonals() {
+        let mut rig = Rig::new(vec![hat_select(false, HatDiagonals::Round)]);
+        rig.input.set_active_shift_mode(ShiftModeMask(0b10000000));
+
+        let steps = [
+            (HatState::Centered, 0b10010000),
+            (HatState::Up, 0b10000001),
+            (HatState::RightUp, 0b10000010),
+            (HatState::LeftDown, 0b10001000),
+            (HatState::Centered, 0b10010000),
+        ];
+        for (hat, expected) in steps {
+            rig.stick.set_hat(0, hat);
+            rig.tick();
+            assert_eq!(rig.input.get_active_shift_mode(), ShiftModeMask(expected));
+        }
+    }
+
+    #[test]
+    fn hat_select_ignores_diagonals_and_latches() {
+        let mut rig = Rig::new(vec![hat_select(true, HatDiagonals::Ignore)]);
+
+        let steps = [
+            (HatState::Centered, 0b00010000),
+            (HatState::Up, 0b00000001),
+            (HatState::RightUp, 0b00000001),
+            (HatState::Right, 0b00000010),
+            (HatState::Centered, 0b00000010),
+        ];
+        for (hat, expected) in steps {
+            rig.stick.set_hat(0, hat);
+            rig.tick();
+            assert_eq!(rig.input.get_active_shift_mode(), ShiftModeMask(expected));
+        }
+    }
+
+    #[test]
+    fn power_level_is_polled_at_low_rate_and_warns_once() {
+        let mut rig = Rig::new(Vec::new());
+        let power = |rig: &Rig| rig.input.physical_devices().next().unwrap().power.level();
+        assert_eq!(power(&rig), Some(PowerLevel::Wired));
+
+        rig.stick.set_power(PowerLevel::Low);
+        rig.tick();
+        assert_eq!(power(&rig), Some(PowerLevel::Wired));
+
+        // 10 ms per tick
+        for _ in 0..500 {
+            rig.tick();
+        }
+        assert_eq!(power(&rig), Some(PowerLevel::Low));
+        let warnings = rig.input.take_power_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("Fake stick"));
+        assert!(rig.input.take_power_warnings().is_empty());
+    }
+
+    #[test]
+    fn device_transform_applies_before_rebinds() {
+        let mut rig = Rig::new(vec![rebind(
+            0,
+            RebindType::Reroute {
+                rebind: RerouteRebind::AxisToAxis {
+                    src: AxisSource::physical(STICK.to_string(), 1),
+                    dst_device: 1,
+                    dst_axis: 1,
+                    modifier: Default::default(),
+                    freeze: None,
+                    gain: None,
+                },
+            },
+        )]);
+        let transform = DeviceTransform {
+            device_name: STICK.to_string(),
+            axes: vec![AxisTransform {
+                source: 2,
+                invert: true,
+                calibration: None,
+            }],
+        };
+        rig.input
+            .get_device_transforms_mut()
+            .insert(STICK.to_string(), transform);
+
+        rig.stick.set_axis(0, i16::MIN);
+        rig.stick.set_axis(1, i16::MIN);
+        rig.tick();
+        assert_eq!(rig.tick().axes().next().unwrap().get(), 32767);
+
+        let device = rig.input.physical_devices().next().unwrap();
+        let raw: Vec<i32> = device.input_state.raw_axes().copied().collect();
+        assert_eq!(raw, vec![i16::MIN as i32; 2]);
+
+        // A stick that only reaches half way is stretched to the full range, the viewer and
+        // every rebind see the calibrated value
+        rig.input.get_device_transforms_mut().insert(
+            STICK.to_string(),
+            DeviceTransform {
+                device_name: STICK.to_string(),
+                axes: vec![AxisTransform {
+                    source: 1,
+                    invert: false,
+                    calibration: Some(AxisCalibration {
+                        min: -16384,
+                        center: 0,
+                        max: 16384,
+                    }),
+                }],
+            },
+        );
+        rig.stick.set_axis(0, 16384);
+        rig.tick();
+        assert_eq!(rig.tick().axes().next().unwrap().get(), 32767);
+        let device = rig.input.physical_devices().next().unwrap();
+        assert_eq!(device.input_state.axes().next(), Some(&32767));
+    }
+
+    #[test]
+    fn shutdown_writes_neutral_state_and_releases_output() {
+        let mut rig = Rig::new(vec![rebind(
+            0,
+            button_to_button(1, 1, ButtonToButtonModifier::Simple),
+        )]);
+        rig.stick.set_button(0, true);
+        rig.stick.set_axis(0, i16::MAX);
+        assert_eq!(button(&rig.tick(), 1), ButtonState::Pressed);
+
+        rig.input.shutdown();
+        let state = rig.output.last(1);
+        assert_eq!(button(&state, 1), ButtonState::Released);
+        assert_eq!(state.axes().next().unwrap().get(), 16384);
+        assert_eq!(Rc::strong_count(&rig.output.written), 1);
+
+        // Nothing is written after the shutdown
+        rig.output.written.borrow_mut().clear();
+        rig.input.update(rig.time + 0.01).unwrap();
+        rig.input.shutdown();
+        assert!(rig.output.written.borrow().is_empty());
+    }
+
+    #[test]
+    fn devices_enabled_while_running_are_picked_up() {
+        let mut rig = Rig::new(vec![rebind(
+            0,
+            RebindType::Reroute {
+                rebind: RerouteRebind::ButtonToButton {
+                    src: ButtonSource::physical(STICK.to_string(), 1),
+                    dst_device: 2,
+                    dst_button: 1,
+                    modifier: ButtonToButtonModifier::Simple,
+                },
+            },
+        )]);
+        rig.stick.set_button(0, true);
+        rig.tick();
+        assert!(!rig.output.written.borrow().contains_key(&2));
+
+        // vJoyConf enables device 2, SDL sees it before vJoy is enumerated again
+        rig.joysticks
+            .joysticks
+            .borrow_mut()
+            .push(FakeJoystick::vjoy());
+        rig.output.enable_later((2, 8, 2, 1));
+        rig.tick();
+        assert!(rig.input.take_virtual_devices_changed());
+        assert_eq!(rig.input.virtual_devices().count(), 2);
+        assert_eq!(button(&rig.tick(), 1), ButtonState::Released);
+        assert_eq!(button(&rig.output.last(2), 1), ButtonState::Pressed);
+        assert!(!rig.input.take_virtual_devices_changed());
+    }
+
+    #[test]
+    fn source_lost_policy_applies_while_the_stick_is_unplugged() {
+        let throttle = rebind(
+            0,
+            RebindType::Reroute {
+                rebind: RerouteRebind::AxisToAxis {
+                    src: AxisSource::physical(STICK.to_string(), 1),
+                    dst_device: 1,
+                    dst_axis: 1,
+                    modifier: Default::default(),
+                    freeze: None,
+                    gain: None,
+                },
+            },
+        );
+        let fire = rebind(0, button_to_button(1, 1, ButtonToButtonModifier::Simple));
+        let mut held = rebind(0, button_to_button(2, 2, ButtonToButtonModifier::Simple));
+        held.on_source_lost = Some(SourceLostPolicy::FreezeOutput);
+        let mut rig = Rig::new(vec![throttle, fire, held]);
+
+        rig.stick.set_axis(0, 32767);
+        rig.stick.set_button(0, true);
+        rig.stick.set_button(1, true);
+        let state = rig.tick();
+        assert_eq!(state.axes().next().unwrap().get(), 32767);
+        assert_eq!(button(&state, 1), ButtonState::Pressed);
+
+        // Axes freeze and buttons release by default, the held button is set to freeze
+        let stick = rig.joysticks.joysticks.borrow_mut().remove(0);
+        for _ in 0..3 {
+            let state = rig.tick();
+            assert_eq!(state.axes().next().unwrap().get(), 32767);
+            assert_eq!(button(&state, 1), ButtonState::Released);
+            assert_eq!(button(&state, 2), ButtonState::Pressed);
+        }
+
+        // Plugged back in, the outputs follow the stick again after its warm-up
+        rig.stick.set_axis(0, -32768);
+        rig.stick.set_button(1, false);
+        rig.joysticks.joysticks.borrow_mut().insert(0, stick);
+        for _ in 0..20 {
+            rig.tick();
+        }
+        let state = rig.tick();
+        assert_eq!(state.axes().next().unwrap().get(), 0);
+        assert_eq!(button(&state, 1), ButtonState::Pressed);
+        assert_eq!(button(&state, 2), ButtonState::Released);
+    }
+
+    #[test]
+    fn replugged_devices_keep_their_place_and_history() {
+        let mut rig = Rig::new(Vec::new());
+        let pedals = FakeJoystick::new("pedals", 0, 3, 0);
+        rig.joysticks.joysticks.borrow_mut().push(pedals);
+        rig.tick();
+        let guids = |input: &Input| -> Vec<(String, bool)> {
+            input
+                .physical_devices()
+                .map(|d| (d.guid.clone(), d.connected))
+                .collect()
+        };
+        let plotted = |input: &Input| input.connected_physical_devices[0].axes_plot_data[0].len();
+        assert_eq!(
+            guids(&rig.input),
+            vec![(STICK.to_string(), true), ("pedals".to_string(), true)]
+        );
+        let history = plotted(&rig.input);
+        assert!(history > 0);
+
+        let stick = rig.joysticks.joysticks.borrow_mut().remove(0);
+        rig.tick();
+        assert_eq!(
+            guids(&rig.input),
+            vec![(STICK.to_string(), false), ("pedals".to_string(), true)]
+        );
+        assert_eq!(rig.input.physical_devices_count(), 1);
+        assert!(!rig.input.get_physical_device_info_map().contains_key(STICK));
+        assert_eq!(plotted(&rig.input), history);
+
+        // SDL enumerates the returning stick last, it still takes its old place
+        rig.joysticks.joysticks.borrow_mut().push(stick);
+        rig.tick();
+        assert_eq!(
+            guids(&rig.input),
+            vec![(STICK.to_string(), true), ("pedals".to_string(), true)]
+        );
+        assert!(rig.input.connected_physical_devices[0].is_initializing());
+        assert_eq!(plotted(&rig.input), history);
+    }
+
+    #[test]
+    fn unset_freeze_leaves_the_axis_alone() {
+        let mut rig = Rig::new(vec![rebind(
+            0,
+            RebindType::Reroute {
+                rebind: RerouteRebind::AxisToAxis {
+                    src: AxisSource::physical(STICK.to_string(), 1),
+                    dst_device: 1,
+                    dst_axis: 1,
+                    modifier: Default::default(),
+                    freeze: Some(AxisFreeze::default()),
+                    gain: None,
+                },
+            },
+        )]);
+        rig.stick.set_axis(0, 32767);
+        assert_eq!(rig.tick().axes().next().unwrap().get(), 32767);
+    }
+
+    #[test]
+    fn other_source_errors_leave_the_outputs_alone() {
+        // Button 9 doesn't exist on the connected stick, that is no disconnect
+        let mut rig = Rig::new(vec![
+            rebind(0, button_to_button(1, 3, ButtonToButtonModifier::Simple)),
+            rebind(0, button_to_button(9, 3, ButtonToButtonModifier::Simple)),
+        ]);
+        rig.stick.set_button(0, true);
+        assert_eq!(button(&rig.tick(), 3), ButtonState::Pressed);
+    }
+
+    #[test]
+    fn event_console_records_changes_only_while_open() {
+        let mut rig = Rig::new(Vec::new());
+        rig.stick.set_button(0, true);
+        rig.tick();
+        assert!(rig.input.event_console_mut().is_empty());
+
+        rig.input.event_console_mut().open = true;
+        rig.stick.set_button(0, false);
+        rig.stick.set_hat(0, HatState::Left);
+        rig.tick();
+        rig.tick();
+        let console = rig.input.event_console_mut();
+        let changes: Vec<_> = console.visible().map(|event| event.change).collect();
+        assert_eq!(
+            changes,
+            vec![
+                ControlChange {
+                    kind: ControlKind::Button,
+                    index: 1,
+                    old: 1,
+                    new: 0
+                },
+                ControlChange {
+                    kind: ControlKind::Hat,
+                    index: 1,
+                    old: -1,
+                    new: 270
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn performance_mode_stops_plotting_and_clears_the_plots_on_leaving() {
+        let mut rig = Rig::new(Vec::new());
+        let plotted = |input: &Input| {
+            (
+                input.connected_physical_devices[0].axes_plot_data[0].len(),
+                input.active_virtual_devices[0].axes_plot_data[0].len(),
+            )
+        };
+        for _ in 0..4 {
+            rig.tick();
+        }
+        let before = plotted(&rig.input);
+        assert!(before.0 > 0 && before.1 > 0);
+
+        rig.input.set_performance_mode(true);
+        for _ in 0..4 {
+            rig.tick();
+        }
+        assert_eq!(plotted(&rig.input), before);
+        let (current, normal) = rig.input.tick_time_comparison();
+        assert!(current.is_some() && normal.is_some());
+
+        rig.input.set_performance_mode(false);
+        assert_eq!(plotted(&rig.input), (0, 0));
+        rig.tick();
+        rig.tick();
+        assert!(plotted(&rig.input).0 > 0);
+    }
+
+    #[test]
+    fn reset_paths_use_configured_axis_neutrals() {
+        let two_buttons = |dst_axis| {
+            rebind(
+                0,
+                RebindType::Reroute {
+                    rebind: RerouteRebind::TwoButtonsToAxis {
+                        src_neg_device: STICK.to_string(),
+                        src_neg_button: 1,
+                        src_pos_device: STICK.to_string(),
+                        src_pos_button: 2,
+                        dst_device: 1,
+                        dst_axis,
+                        modifier: TwoButtonsToAxisModifier::Absolute,
+                    },
+                },
+            )
+        };
+        let inhibit = rebind(
+            0,
+            RebindType::Logical {
+                rebind: LogicalRebind::OutputEnableSwitch {
+                    src_device: STICK.to_string(),
+                    src_button: 4,
+                    invert: true,
+                },
+            },
+        );
+        let neutrals = vec![AxisNeutral::new(1, 1, 0), AxisNeutral::new(1, 2, 32767)];
+        let mut rig = Rig::new(vec![inhibit, two_buttons(1)]);
+        *rig.input.get_axis_neutrals_mut() = neutrals.clone();
+        let axes = |state: VirtualState| state.axes().map(|a| a.get()).collect::<Vec<i32>>();
+
+        // Neither button pressed
+        assert_eq!(axes(rig.tick())[0], 0);
+        rig.stick.set_button(1, true);
+        assert_eq!(axes(rig.tick())[0], 32767);
+
+        // Output inhibited
+        rig.stick.set_button(3, true);
+        assert_eq!(axes(rig.tick()), vec![0, 32767]);
+        rig.stick.set_button(3, false);
+        assert_eq!(axes(rig.tick())[0], 32767);
+
+        // Loading a config resets the outputs of the previous one
+        rig.stick.set_button(1, false);
+        rig.stick.set_button(0, true);
+        rig.input.replace_config(Config {
+            rebinds: vec![two_buttons(2)],
+            axis_neutrals: neutrals,
+            ..Default::default()
+        });
+        assert_eq!(axes(rig.tick()), vec![0, 0]);
+
+        rig.input.shutdown();
+        assert_eq!(axes(rig.output.last(1)), vec![0, 32767]);
+    }
+
+    struct MidiMessages(Rc<RefCell<Vec<Vec<u8>>>>);
+
+    impl MidiSink for MidiMessages {
+        fn send(&mut self, message: &[u8]) -> Result<(), crate::error::Error> {
+            self.0.borrow_mut().push(message.to_vec());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn midi_feedback_follows_toggled_virtual_buttons() {
+        let mut rig = Rig::new(vec![rebind(
+            0,
+            button_to_button(1, 3, ButtonToButtonModifier::Toggle { last_input: false }),
+        )]);
+        rig.input.get_midi_feedback_mut().push(MidiFeedback {
+            event: MidiEvent::VirtualButton {
+                device: 1,
+                button: 3,
+            },
+            channel: 2,
+            kind: MidiMessageKind::ControlChange,
+            number: 20,
+            on_value: 127,
+            off_value: 0,
+        });
+        let sent = Rc::new(RefCell::new(Vec::new()));
+        rig.input.midi_output = Some(MidiOutput::with_sink(
+            "Button box",
+            Box::new(MidiMessages(sent.clone())),
+        ));
+
+        rig.tick();
+        assert_eq!(sent.take(), vec![vec![0xB1, 20, 0]]);
+
+        // Only the toggle flips are sent, not every tick the button is held
+        for pressed in [true, true, false, false, true, false] {
+            rig.stick.set_button(0, pressed);
+            rig.tick();
+        }
+        assert_eq!(sent.take(), vec![vec![0xB1, 20, 127], vec![0xB1, 20, 0]]);
+
+        // No port is no error
+        rig.input.set_midi_port(None).unwrap();
+        rig.tick();
+        assert!(sent.take().is_empty());
+    }
+
+    struct KeyEvents(Rc<RefCell<Vec<(Key, bool)>>>);
+
+    impl KeySink for KeyEvents {
+        fn send(&mut self, events: &[KeyEvent]) -> Result<(), crate::error::Error> {
+            let events = events.iter().map(|e| (e.key, e.pressed));
+            self.0.borrow_mut().extend(events);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn keyboard_rebind_holds_keys_in_its_shift_mode() {
+        let mut rig = Rig::new(vec![
+            rebind(
+                0,
+                RebindType::Logical {
+                    rebind: LogicalRebind::MomentaryEnableShiftMode {
+                        src_device: STICK.to_string(),
+                        src_button: 2,
+                        shift_mask: ShiftModeMask(0b00000001),
+                    },
+                },
+            ),
+            rebind(
+                0b00000001,
+                RebindType::Keyboard {
+                    rebind: KeyboardRebind::ButtonToKey {
+                        src_device: STICK.to_string(),
+                        src_button: 1,
+                        key: Key::F1,
+                        ctrl: true,
+                        shift: false,
+                        alt: false,
+                        modifier: ButtonToKeyModifier::Hold,
+                    },
+                },
+            ),
+        ]);
+        let sent = Rc::new(RefCell::new(Vec::new()));
+        rig.input.keyboard_output = KeyboardOutput::with_sink(Box::new(KeyEvents(sent.clone())));
+
+        // Nothing outside the rebind's shift mode
+        rig.stick.set_button(0, true);
+        rig.tick();
+        assert!(sent.take().is_empty());
+
+        // Pressed once while held, released when the mode is left with the button still held
+        rig.stick.set_button(1, true);
+        rig.tick();
+        rig.tick();
+        assert_eq!(sent.take(), vec![(Key::LeftCtrl, true), (Key::F1, true)]);
+        rig.stick.set_button(1, false);
+        rig.tick();
+        assert_eq!(sent.take(), vec![(Key::F1, false), (Key::LeftCtrl, false)]);
+
+        // Nothing stays pressed after shutting down
+        rig.stick.set_button(1, true);
+        rig.tick();
+        rig.input.shutdown();
+        assert_eq!(
+            sent.take(),
+            vec![
+                (Key::LeftCtrl, true),
+                (Key::F1, true),
+                (Key::F1, false),
+                (Key::LeftCtrl, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicate_guids_get_occurrence_suffix() {
+        let keys = disambiguate_guids(["a", "b", "a", "a"].into_iter());
+        assert_eq!(
+            keys,
+            vec![
+                ("a".to_string(), 1),
+                ("b".to_string(), 1),
+                ("a#2".to_string(), 2),
+                ("a#3".to_string(), 3),
+            ]
+        );
+    }
+}
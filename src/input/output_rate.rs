@@ -0,0 +1,142 @@
+use std::collections::VecDeque;
+
+use super::virtual_state::VirtualState;
+
+/// Window the update rate is counted over, in seconds
+const RATE_WINDOW_S: f64 = 1.0;
+
+/// Length of one bar of the activity sparkline, in seconds
+const ACTIVITY_BUCKET_S: f64 = 0.25;
+
+/// Bars of the activity sparkline, the last 6 seconds
+pub const ACTIVITY_BUCKETS: usize = 24;
+
+/// Updates of a virtual device sent to vJoy and how often its state changed, to confirm the
+/// manager is feeding the game.
+///
+/// Every tick sends the full state, so the updates sent equal the ticks processed unless output
+/// is paused or failing.
+#[derive(Debug, Clone, Default)]
+pub struct OutputRate {
+    /// Times of the updates sent within the rate window
+    sent: VecDeque<f64>,
+    last_state: Option<VirtualState>,
+    last_change: Option<f64>,
+    /// (bucket, state changes) of the recent buckets with changes, oldest first
+    changes: VecDeque<(i64, u32)>,
+}
+
+fn bucket(time: f64) -> i64 {
+    (time / ACTIVITY_BUCKET_S).floor() as i64
+}
+
+impl OutputRate {
+    /// Records a successful update with `state`. The first update only sets the reference state.
+    pub fn record(&mut self, state: &VirtualState, time: f64) {
+        self.sent.push_back(time);
+        while self
+            .sent
+            .front()
+            .is_some_and(|sent| time - sent >= RATE_WINDOW_S)
+        {
+            self.sent.pop_front();
+        }
+
+        if self.last_state.as_ref() == Some(state) {
+            return;
+        }
+        if self.last_state.is_some() {
+            self.last_change = Some(time);
+            let current = bucket(time);
+            match self.changes.back_mut() {
+                Some((last, count)) if *last == current => *count += 1,
+                _ => self.changes.push_back((current, 1)),
+            }
+            while self
+                .changes
+                .front()
+                .is_some_and(|(first, _)| current - first >= ACTIVITY_BUCKETS as i64)
+            {
+                self.changes.pop_front();
+            }
+        }
+        self.last_state = Some(state.clone());
+    }
+
+    pub fn updates_per_second(&self, time: f64) -> f64 {
+        let recent = self
+            .sent
+            .iter()
+            .filter(|sent| time - **sent < RATE_WINDOW_S)
+            .count();
+        recent as f64 / RATE_WINDOW_S
+    }
+
+    /// Seconds since the sent state last changed, `None` if it never did
+    pub fn since_last_change(&self, time: f64) -> Option<f64> {
+        self.last_change.map(|change| time - change)
+    }
+
+    /// State changes per sparkline bar up to `time`, oldest first
+    pub fn activity(&self, time: f64) -> [u32; ACTIVITY_BUCKETS] {
+        let current = bucket(time);
+        let mut activity = [0; ACTIVITY_BUCKETS];
+        for (index, count) in self.changes.iter() {
+            let age = current - index;
+            if (0..ACTIVITY_BUCKETS as i64).contains(&age) {
+                activity[ACTIVITY_BUCKETS - 1 - age as usize] = *count;
+            }
+        }
+        activity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vjoy::{ButtonState, HatState};
+
+    use crate::input::{
+        output_rate::{OutputRate, ACTIVITY_BUCKETS},
+        virtual_state::VirtualState,
+    };
+
+    #[test]
+    fn counts_updates_and_state_changes() {
+        let mut rate = OutputRate::default();
+        let mut state = VirtualState::new(1, 1, 1, 0, HatState::Continuous(0));
+
+        // 100 updates in a second, the button flips every 10th
+        for tick in 0..100 {
+            if tick % 10 == 3 {
+                let button = state.buttons_mut().next().unwrap();
+                let flipped = match button.get() {
+                    ButtonState::Pressed => ButtonState::Released,
+                    ButtonState::Released => ButtonState::Pressed,
+                };
+                button.set(flipped);
+            }
+            rate.record(&state, 10.0 + tick as f64 * 0.01);
+        }
+        assert_eq!(rate.updates_per_second(10.995), 100.0);
+        assert!((rate.since_last_change(10.995).unwrap() - 0.065).abs() < 1e-9);
+
+        // Four bars of a quarter second each
+        let activity = rate.activity(10.995);
+        assert_eq!(activity[..ACTIVITY_BUCKETS - 4], [0; ACTIVITY_BUCKETS - 4]);
+        assert_eq!(activity[ACTIVITY_BUCKETS - 4..], [3, 2, 3, 2]);
+
+        // Nothing sent while paused
+        assert_eq!(rate.updates_per_second(12.0), 0.0);
+        assert_eq!(rate.activity(20.0), [0; ACTIVITY_BUCKETS]);
+    }
+
+    #[test]
+    fn first_update_is_no_change() {
+        let mut rate = OutputRate::default();
+        let state = VirtualState::new(1, 1, 1, 0, HatState::Continuous(0));
+        rate.record(&state, 1.0);
+        rate.record(&state, 1.01);
+        assert_eq!(rate.since_last_change(2.0), None);
+        assert_eq!(rate.activity(1.01), [0; ACTIVITY_BUCKETS]);
+    }
+}
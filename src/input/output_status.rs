@@ -0,0 +1,93 @@
+/// Consecutive failed vJoy updates after which a device's failure is shown to the user
+pub const OUTPUT_FAILURE_ESCALATION: u32 = 250;
+
+/// Result history of pushing a virtual device's state to the vJoy driver.
+///
+/// A single failed update is usually transient (driver busy, device briefly reacquired), so
+/// failures are only counted and logged until [`OUTPUT_FAILURE_ESCALATION`] happen in a row.
+#[derive(Debug, Clone, Default)]
+pub struct OutputStatus {
+    consecutive_failures: u32,
+    total_failures: u64,
+    last_error: Option<String>,
+    escalated: bool,
+}
+
+impl OutputStatus {
+    pub fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+        self.escalated = false;
+    }
+
+    /// Returns true once when the consecutive failures reach the escalation threshold.
+    pub fn record_failure(&mut self, error: String) -> bool {
+        self.consecutive_failures += 1;
+        self.total_failures += 1;
+        self.last_error = Some(error);
+
+        let escalate = self.consecutive_failures == OUTPUT_FAILURE_ESCALATION;
+        self.escalated |= escalate;
+        escalate
+    }
+
+    /// Hides the escalation until the device recovers and fails again.
+    pub fn acknowledge(&mut self) {
+        self.escalated = false;
+    }
+
+    pub fn is_failing(&self) -> bool {
+        self.consecutive_failures > 0
+    }
+
+    pub fn is_escalated(&self) -> bool {
+        self.escalated
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} consecutive failed updates ({} total). Last error: {}",
+            self.consecutive_failures,
+            self.total_failures,
+            self.last_error.as_deref().unwrap_or("-")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::output_status::{OutputStatus, OUTPUT_FAILURE_ESCALATION};
+
+    #[test]
+    fn escalates_once_after_consecutive_failures() {
+        let mut status = OutputStatus::default();
+        for _ in 0..OUTPUT_FAILURE_ESCALATION - 1 {
+            assert!(!status.record_failure("busy".to_string()));
+        }
+        assert!(status.is_failing() && !status.is_escalated());
+
+        assert!(status.record_failure("busy".to_string()));
+        assert!(!status.record_failure("busy".to_string()));
+        assert!(status.is_escalated());
+
+        status.acknowledge();
+        assert!(!status.record_failure("busy".to_string()));
+        assert!(!status.is_escalated());
+
+        status.record_success();
+        assert!(!status.is_failing());
+        assert!(status
+            .summary()
+            .starts_with("0 consecutive failed updates (252 total)"));
+    }
+
+    #[test]
+    fn success_resets_the_streak() {
+        let mut status = OutputStatus::default();
+        for _ in 0..OUTPUT_FAILURE_ESCALATION - 1 {
+            status.record_failure("busy".to_string());
+        }
+        status.record_success();
+        assert!(!status.record_failure("busy".to_string()));
+        assert!(!status.is_escalated());
+    }
+}
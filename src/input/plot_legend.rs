@@ -0,0 +1,124 @@
+use egui::{Color32, Label, RichText, Sense, Ui};
+
+const LINE_WIDTH: f32 = 2.0;
+const HIGHLIGHTED_LINE_WIDTH: f32 = 3.5;
+const DIMMED_LINE_WIDTH: f32 = 1.0;
+const DIMMED_ALPHA: f32 = 0.3;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum LegendFocus {
+    /// Drawn thicker, the other lines are dimmed
+    Highlight(usize),
+    /// Only this line is drawn
+    Solo(usize),
+}
+
+/// Highlighted or soloed line of one device's axes plot
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct PlotLegend {
+    focus: Option<LegendFocus>,
+}
+
+impl PlotLegend {
+    /// Highlights the line, or clears the focus if it already was highlighted
+    pub fn click(&mut self, index: usize) {
+        self.focus = match self.focus {
+            Some(LegendFocus::Highlight(highlighted)) if highlighted == index => None,
+            _ => Some(LegendFocus::Highlight(index)),
+        };
+    }
+
+    /// Shows only this line, or all lines again if it already was soloed
+    pub fn double_click(&mut self, index: usize) {
+        self.focus = match self.focus {
+            Some(LegendFocus::Solo(soloed)) if soloed == index => None,
+            _ => Some(LegendFocus::Solo(index)),
+        };
+    }
+
+    /// Width and color of the line, `None` if it is hidden
+    pub fn stroke(&self, index: usize, color: Color32) -> Option<(f32, Color32)> {
+        match self.focus {
+            None => Some((LINE_WIDTH, color)),
+            Some(LegendFocus::Highlight(highlighted)) if highlighted == index => {
+                Some((HIGHLIGHTED_LINE_WIDTH, color))
+            }
+            Some(LegendFocus::Highlight(_)) => {
+                Some((DIMMED_LINE_WIDTH, color.gamma_multiply(DIMMED_ALPHA)))
+            }
+            Some(LegendFocus::Solo(soloed)) if soloed == index => Some((LINE_WIDTH, color)),
+            Some(LegendFocus::Solo(_)) => None,
+        }
+    }
+
+    pub fn is_focused(&self, index: usize) -> bool {
+        matches!(
+            self.focus,
+            Some(LegendFocus::Highlight(focused) | LegendFocus::Solo(focused)) if focused == index
+        )
+    }
+
+    /// Drops a focus on a line the device no longer has
+    fn retain(&mut self, num_lines: usize) {
+        if let Some(LegendFocus::Highlight(index) | LegendFocus::Solo(index)) = self.focus {
+            if index >= num_lines {
+                self.focus = None;
+            }
+        }
+    }
+}
+
+/// Names and colors of the plot's lines. Clicking an entry highlights its line, double clicking
+/// shows only that line.
+pub fn plot_legend_widget(ui: &mut Ui, legend: &mut PlotLegend, entries: &[(String, Color32)]) {
+    legend.retain(entries.len());
+    ui.horizontal_wrapped(|ui| {
+        for (index, (name, color)) in entries.iter().enumerate() {
+            let color = match legend.stroke(index, *color) {
+                Some((_, color)) => color,
+                None => color.gamma_multiply(DIMMED_ALPHA),
+            };
+            let mut text = RichText::new(format!("━ {name}")).color(color);
+            if legend.is_focused(index) {
+                text = text.strong();
+            }
+            let response = ui
+                .add(Label::new(text).sense(Sense::click()))
+                .on_hover_text("Click to highlight, double click to show only this axis");
+            if response.double_clicked() {
+                legend.double_click(index);
+            } else if response.clicked() {
+                legend.click(index);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Color32;
+
+    use crate::input::plot_legend::PlotLegend;
+
+    #[test]
+    fn highlight_and_solo_toggle() {
+        let mut legend = PlotLegend::default();
+        let color = Color32::RED;
+        assert_eq!(legend.stroke(1, color), Some((2.0, color)));
+
+        legend.click(1);
+        assert_eq!(legend.stroke(1, color).unwrap().0, 3.5);
+        assert_ne!(legend.stroke(0, color).unwrap().1, color);
+        legend.click(1);
+        assert_eq!(legend.stroke(0, color), Some((2.0, color)));
+
+        legend.double_click(2);
+        assert_eq!(legend.stroke(2, color), Some((2.0, color)));
+        assert_eq!(legend.stroke(0, color), None);
+        legend.click(0);
+        assert!(legend.stroke(2, color).is_some());
+        legend.double_click(0);
+        legend.double_click(0);
+        assert_eq!(legend.stroke(2, color), Some((2.0, color)));
+    }
+}
@@ -0,0 +1,90 @@
+use std::time::{Duration, Instant};
+
+use ringbuffer::{AllocRingBuffer, RingBufferExt, RingBufferWrite};
+use spin_sleep::SpinSleeper;
+
+use super::INPUT_POLL_INTERVAL;
+
+/// Paces the input ticks independently of the UI event loop, which only wakes at the coarse
+/// OS timer resolution while the window is in the background.
+///
+/// Sleeps natively for most of the wait and spins the rest, so ticks stay 1 ms apart.
+pub struct PollTimer {
+    sleeper: SpinSleeper,
+    interval: Duration,
+    next_tick: Instant,
+    last_wake: Option<Instant>,
+    /// Measured intervals between the recent wake-ups, in seconds
+    intervals: AllocRingBuffer<f64>,
+}
+
+impl PollTimer {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            sleeper: SpinSleeper::default(),
+            interval: Duration::from_secs_f64(INPUT_POLL_INTERVAL),
+            next_tick: now,
+            last_wake: None,
+            intervals: AllocRingBuffer::with_capacity(512),
+        }
+    }
+
+    /// Blocks until the next tick is due. Returns at once when the caller is already late.
+    #[profiling::function]
+    pub fn wait(&mut self) {
+        if let Some(remaining) = self.next_tick.checked_duration_since(Instant::now()) {
+            self.sleeper.sleep(remaining);
+        }
+        self.woke(Instant::now());
+    }
+
+    /// Records the wake-up and schedules the next tick. Ticks missed while the caller was busy
+    /// are dropped instead of caught up in a burst.
+    fn woke(&mut self, now: Instant) {
+        if let Some(last) = self.last_wake {
+            self.intervals.push((now - last).as_secs_f64());
+        }
+        self.last_wake = Some(now);
+        self.next_tick += self.interval;
+        if self.next_tick <= now {
+            self.next_tick = now + self.interval;
+        }
+    }
+
+    /// Mean interval between the timer's wake-ups and its mean absolute deviation, in seconds
+    pub fn timing(&self) -> (f64, f64) {
+        let count = self.intervals.len().max(1) as f64;
+        let mean = self.intervals.iter().sum::<f64>() / count;
+        let jitter = self
+            .intervals
+            .iter()
+            .map(|interval| (interval - mean).abs())
+            .sum::<f64>()
+            / count;
+        (mean, jitter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::poll_timer::PollTimer;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn late_wake_ups_skip_missed_ticks() {
+        let start = Instant::now();
+        let mut timer = PollTimer::new(start);
+        let ms = Duration::from_millis(1);
+
+        timer.woke(start);
+        assert_eq!(timer.next_tick, start + ms);
+        timer.woke(start + ms);
+        assert_eq!(timer.next_tick, start + 2 * ms);
+
+        timer.woke(start + 20 * ms);
+        assert_eq!(timer.next_tick, start + 21 * ms);
+        let (mean, jitter) = timer.timing();
+        assert!((mean - 0.01).abs() < 1e-9);
+        assert!((jitter - 0.009).abs() < 1e-9);
+    }
+}
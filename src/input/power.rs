@@ -0,0 +1,107 @@
+use sdl2::joystick::PowerLevel;
+
+/// Seconds between power level queries. SDL itself refreshes them only every few seconds.
+pub const POWER_POLL_INTERVAL: f64 = 5.0;
+
+/// Last power level SDL reported for a physical device.
+///
+/// SDL only reports coarse levels: empty is up to 5%, low up to 20%, medium up to 70%.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PowerStatus {
+    level: Option<PowerLevel>,
+}
+
+impl PowerStatus {
+    /// `None` until the first query
+    pub fn level(&self) -> Option<PowerLevel> {
+        self.level
+    }
+
+    /// Records a new reading. Returns true if the battery just dropped to low or from low to empty.
+    /// An unknown reading keeps a level that was known before.
+    pub fn update(&mut self, level: PowerLevel) -> bool {
+        if level == PowerLevel::Unknown && self.level.is_some() {
+            return false;
+        }
+        let previous = self.level.map_or(u8::MAX, rank);
+        self.level = Some(level);
+        is_low(level) && rank(level) < previous
+    }
+
+    pub fn is_low(&self) -> bool {
+        self.level.is_some_and(is_low)
+    }
+
+    /// Battery level of wireless devices, `None` for wired devices and unknown levels
+    pub fn battery_label(&self) -> Option<&'static str> {
+        match self.level? {
+            PowerLevel::Empty => Some("battery ≤5%"),
+            PowerLevel::Low => Some("battery ≤20%"),
+            PowerLevel::Medium => Some("battery ≤70%"),
+            PowerLevel::Full => Some("battery full"),
+            PowerLevel::Wired | PowerLevel::Unknown => None,
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self.level {
+            None => "not queried yet",
+            Some(PowerLevel::Wired) => "wired",
+            Some(PowerLevel::Unknown) => "unknown",
+            Some(_) => self.battery_label().unwrap_or("unknown"),
+        }
+    }
+}
+
+fn is_low(level: PowerLevel) -> bool {
+    matches!(level, PowerLevel::Empty | PowerLevel::Low)
+}
+
+/// Order of the battery levels, wired and unknown rank above all of them
+fn rank(level: PowerLevel) -> u8 {
+    match level {
+        PowerLevel::Empty => 0,
+        PowerLevel::Low => 1,
+        PowerLevel::Medium => 2,
+        PowerLevel::Full => 3,
+        PowerLevel::Wired | PowerLevel::Unknown => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::joystick::PowerLevel;
+
+    use crate::input::power::PowerStatus;
+
+    #[test]
+    fn warns_once_per_drop_into_low_levels() {
+        let mut status = PowerStatus::default();
+        assert_eq!(status.describe(), "not queried yet");
+
+        assert!(!status.update(PowerLevel::Full));
+        assert!(!status.update(PowerLevel::Medium));
+        assert!(status.update(PowerLevel::Low));
+        assert!(!status.update(PowerLevel::Low));
+        assert!(status.update(PowerLevel::Empty));
+        assert!(!status.update(PowerLevel::Low));
+        assert!(status.is_low());
+
+        assert!(!status.update(PowerLevel::Unknown));
+        assert_eq!(status.battery_label(), Some("battery ≤20%"));
+        assert!(status.update(PowerLevel::Empty));
+    }
+
+    #[test]
+    fn wired_and_unknown_have_no_battery_label() {
+        let mut status = PowerStatus::default();
+        status.update(PowerLevel::Unknown);
+        assert_eq!(status.battery_label(), None);
+        assert_eq!(status.describe(), "unknown");
+        status.update(PowerLevel::Wired);
+        assert_eq!(status.battery_label(), None);
+        assert_eq!(status.describe(), "wired");
+        status.update(PowerLevel::Medium);
+        assert_eq!(status.battery_label(), Some("battery ≤70%"));
+    }
+}
@@ -0,0 +1,326 @@
+//! Publishes the final virtual device state to a named shared memory block.
+//!
+//! External programs (motion platforms, dashboards) can read the outputs from there without
+//! polling vJoy. The block is created with the name [`SHARED_MEMORY_NAME`] and has the layout of
+//! [`SharedBlock`], all fields are little endian and `#[repr(C)]`:
+//!
+//! | offset | size       | field                                                     |
+//! |--------|------------|-----------------------------------------------------------|
+//! | 0      | 4          | magic, `b"RVJM"`                                          |
+//! | 4      | 4          | layout version, [`SHARED_MEMORY_VERSION`]                 |
+//! | 8      | 4          | sequence counter                                          |
+//! | 12     | 4          | number of used device slots                               |
+//! | 16     | 80 * 16    | device slots, see [`SharedDevice`]                        |
+//!
+//! The writer updates the block once per tick after the output phase using a seqlock:
+//! the sequence is odd while a write is in progress and advances by 2 per completed write.
+//! Readers copy the block and retry if the sequence was odd or changed during the copy.
+//! `examples/shared_memory_reader.rs` shows a complete reader.
+
+use std::{
+    ptr::NonNull,
+    sync::atomic::{fence, AtomicU32, Ordering},
+};
+
+use vjoy::ButtonState;
+
+use super::virtual_state::VirtualState;
+use crate::{error::Error, rebind::hat_to_hat::convert_vjoy_hat_to_angle};
+
+pub const SHARED_MEMORY_NAME: &str = "Local\\RustVJoyManagerOutput";
+pub const SHARED_MEMORY_MAGIC: u32 = u32::from_le_bytes(*b"RVJM");
+pub const SHARED_MEMORY_VERSION: u32 = 1;
+
+pub const MAX_DEVICES: usize = 16;
+pub const MAX_AXES: usize = 8;
+pub const MAX_HATS: usize = 4;
+pub const MAX_BUTTONS: usize = 128;
+
+/// One virtual device. Unused slots and controls are zeroed.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SharedDevice {
+    /// vJoy device id, 0 for an unused slot
+    pub id: u32,
+    pub num_buttons: u32,
+    pub num_axes: u32,
+    pub num_hats: u32,
+    /// vJoy axis values, 0..=32767
+    pub axes: [i32; MAX_AXES],
+    /// Hat angles in degrees, -1 while centered
+    pub hats: [i32; MAX_HATS],
+    /// Button `n` (1-based) is bit `(n - 1) % 32` of word `(n - 1) / 32`
+    pub buttons: [u32; MAX_BUTTONS / 32],
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct SharedBlock {
+    pub magic: u32,
+    pub version: u32,
+    pub sequence: AtomicU32,
+    pub num_devices: u32,
+    pub devices: [SharedDevice; MAX_DEVICES],
+}
+
+impl SharedDevice {
+    fn from_state(state: &VirtualState) -> Self {
+        let mut device = SharedDevice {
+            id: state.id(),
+            num_buttons: state.num_buttons().min(MAX_BUTTONS) as u32,
+            num_axes: state.num_axes().min(MAX_AXES) as u32,
+            num_hats: state.num_hats().min(MAX_HATS) as u32,
+            ..Default::default()
+        };
+        for (output, axis) in device.axes.iter_mut().zip(state.axes()) {
+            *output = axis.get();
+        }
+        for (output, hat) in device.hats.iter_mut().zip(state.hats()) {
+            *output = convert_vjoy_hat_to_angle(hat.get());
+        }
+        for (index, button) in state.buttons().take(MAX_BUTTONS).enumerate() {
+            if button.get() == ButtonState::Pressed {
+                device.buttons[index / 32] |= 1 << (index % 32);
+            }
+        }
+        device
+    }
+
+    pub fn is_pressed(&self, button: u32) -> bool {
+        let index = button.saturating_sub(1) as usize;
+        button > 0 && index < MAX_BUTTONS && self.buttons[index / 32] & (1 << (index % 32)) != 0
+    }
+}
+
+/// Writer side of the shared memory block. Unmaps the block when dropped.
+pub struct SharedMemoryPublisher {
+    block: NonNull<SharedBlock>,
+    _mapping: Mapping,
+}
+
+impl SharedMemoryPublisher {
+    pub fn create() -> Result<Self, Error> {
+        let mapping = Mapping::create(SHARED_MEMORY_NAME)?;
+        Ok(Self::init(mapping))
+    }
+
+    fn init(mapping: Mapping) -> Self {
+        let block = mapping.block();
+        // SAFETY: the mapping is at least size_of::<SharedBlock>() bytes and zero-initialized
+        unsafe {
+            let block = block.as_ptr();
+            std::ptr::addr_of_mut!((*block).magic).write_volatile(SHARED_MEMORY_MAGIC);
+            std::ptr::addr_of_mut!((*block).version).write_volatile(SHARED_MEMORY_VERSION);
+        }
+        Self {
+            block,
+            _mapping: mapping,
+        }
+    }
+
+    /// Writes the state of all virtual devices. Devices past [`MAX_DEVICES`] are left out.
+    pub fn publish<'a>(&mut self, states: impl Iterator<Item = &'a VirtualState>) {
+        let mut devices = [SharedDevice::default(); MAX_DEVICES];
+        let mut num_devices = 0;
+        for (slot, state) in devices.iter_mut().zip(states) {
+            *slot = SharedDevice::from_state(state);
+            num_devices += 1;
+        }
+
+        let block = self.block.as_ptr();
+        // SAFETY: only this publisher writes the block, readers follow the seqlock protocol
+        unsafe {
+            let sequence = &(*block).sequence;
+            let start = sequence.load(Ordering::Relaxed);
+            sequence.store(start.wrapping_add(1), Ordering::Relaxed);
+            fence(Ordering::Release);
+            std::ptr::addr_of_mut!((*block).num_devices).write_volatile(num_devices);
+            std::ptr::addr_of_mut!((*block).devices).write_volatile(devices);
+            sequence.store(start.wrapping_add(2), Ordering::Release);
+        }
+    }
+}
+
+/// Consistent copy of the device slots, `None` if a write was in progress.
+///
+/// # Safety
+/// `block` must point to a mapped [`SharedBlock`].
+pub unsafe fn read_block(block: *const SharedBlock) -> Option<(u32, Vec<SharedDevice>)> {
+    let sequence = &(*block).sequence;
+    let start = sequence.load(Ordering::Acquire);
+    if start % 2 == 1 {
+        return None;
+    }
+    let num_devices = std::ptr::addr_of!((*block).num_devices).read_volatile();
+    let devices = std::ptr::addr_of!((*block).devices).read_volatile();
+    fence(Ordering::Acquire);
+    if sequence.load(Ordering::Relaxed) != start {
+        return None;
+    }
+
+    let num_devices = (num_devices as usize).min(MAX_DEVICES);
+    Some((start, devices[..num_devices].to_vec()))
+}
+
+#[cfg(windows)]
+struct Mapping {
+    handle: isize,
+    view: NonNull<SharedBlock>,
+}
+
+#[cfg(windows)]
+mod ffi {
+    use std::ffi::c_void;
+
+    pub const INVALID_HANDLE_VALUE: isize = -1;
+    pub const PAGE_READWRITE: u32 = 0x04;
+    pub const FILE_MAP_ALL_ACCESS: u32 = 0x000f_001f;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        pub fn CreateFileMappingW(
+            file: isize,
+            attributes: *const c_void,
+            protect: u32,
+            size_high: u32,
+            size_low: u32,
+            name: *const u16,
+        ) -> isize;
+        pub fn MapViewOfFile(
+            mapping: isize,
+            access: u32,
+            offset_high: u32,
+            offset_low: u32,
+            size: usize,
+        ) -> *mut c_void;
+        pub fn UnmapViewOfFile(address: *const c_void) -> i32;
+        pub fn CloseHandle(handle: isize) -> i32;
+    }
+}
+
+#[cfg(windows)]
+impl Mapping {
+    fn create(name: &str) -> Result<Self, Error> {
+        let name: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+        let size = std::mem::size_of::<SharedBlock>() as u32;
+        // SAFETY: plain Win32 calls, a pagefile backed mapping starts zeroed
+        unsafe {
+            let handle = ffi::CreateFileMappingW(
+                ffi::INVALID_HANDLE_VALUE,
+                std::ptr::null(),
+                ffi::PAGE_READWRITE,
+                0,
+                size,
+                name.as_ptr(),
+            );
+            if handle == 0 {
+                return Err(std::io::Error::last_os_error().into());
+            }
+            let view = ffi::MapViewOfFile(handle, ffi::FILE_MAP_ALL_ACCESS, 0, 0, size as usize);
+            let Some(view) = NonNull::new(view as *mut SharedBlock) else {
+                let error = std::io::Error::last_os_error();
+                ffi::CloseHandle(handle);
+                return Err(error.into());
+            };
+            Ok(Self { handle, view })
+        }
+    }
+
+    fn block(&self) -> NonNull<SharedBlock> {
+        self.view
+    }
+}
+
+#[cfg(windows)]
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        // SAFETY: view and handle come from a successful create and are released only here
+        unsafe {
+            ffi::UnmapViewOfFile(self.view.as_ptr() as *const _);
+            ffi::CloseHandle(self.handle);
+        }
+    }
+}
+
+/// Zeroed heap block standing in for the OS mapping
+#[cfg(not(windows))]
+struct Mapping {
+    block: Box<SharedBlock>,
+}
+
+#[cfg(not(windows))]
+impl Mapping {
+    fn create(_name: &str) -> Result<Self, Error> {
+        Err(Error::Catch(
+            "shared memory publishing is only supported on Windows".to_string(),
+        ))
+    }
+
+    fn block(&self) -> NonNull<SharedBlock> {
+        NonNull::from(self.block.as_ref())
+    }
+}
+
+#[cfg(all(test, not(windows)))]
+mod tests {
+    use vjoy::{ButtonState, FourWayHat, HatState};
+
+    use crate::input::{
+        shared_memory::{
+            read_block, Mapping, SharedBlock, SharedDevice, SharedMemoryPublisher, MAX_DEVICES,
+            SHARED_MEMORY_MAGIC,
+        },
+        virtual_state::VirtualState,
+    };
+
+    fn heap_publisher() -> SharedMemoryPublisher {
+        // SAFETY: all fields of SharedBlock are valid when zeroed
+        let block: Box<SharedBlock> = unsafe { Box::new(std::mem::zeroed()) };
+        SharedMemoryPublisher::init(Mapping { block })
+    }
+
+    #[test]
+    fn layout_matches_the_documentation() {
+        assert_eq!(std::mem::size_of::<SharedDevice>(), 80);
+        assert_eq!(std::mem::size_of::<SharedBlock>(), 16 + 80 * MAX_DEVICES);
+    }
+
+    #[test]
+    fn published_state_reads_back_consistently() {
+        let mut publisher = heap_publisher();
+        let mut state = VirtualState::new(2, 40, 2, 1, HatState::Discrete(FourWayHat::Centered));
+        state
+            .buttons_mut()
+            .nth(34)
+            .unwrap()
+            .set(ButtonState::Pressed);
+        state.axes_mut().next().unwrap().set(123);
+        state
+            .hats_mut()
+            .next()
+            .unwrap()
+            .set(HatState::Discrete(FourWayHat::West));
+
+        publisher.publish([&state].into_iter());
+        publisher.publish([&state].into_iter());
+
+        let block = publisher.block.as_ptr();
+        assert_eq!(unsafe { (*block).magic }, SHARED_MEMORY_MAGIC);
+        let (sequence, devices) = unsafe { read_block(block) }.unwrap();
+        assert_eq!(sequence, 4);
+        assert_eq!(devices.len(), 1);
+        let device = devices[0];
+        assert_eq!((device.id, device.num_buttons, device.num_axes), (2, 40, 2));
+        assert!(device.is_pressed(35) && !device.is_pressed(34) && !device.is_pressed(0));
+        assert_eq!(device.axes[..2], [123, 16384]);
+        assert_eq!(device.hats[0], 270);
+
+        // A reader that overlaps a write sees an odd sequence and retries
+        unsafe {
+            (*block)
+                .sequence
+                .store(5, std::sync::atomic::Ordering::Relaxed)
+        };
+        assert!(unsafe { read_block(block) }.is_none());
+    }
+}
@@ -0,0 +1,186 @@
+use egui::{Event, Key, Modifiers};
+use serde::{Deserialize, Serialize};
+
+use super::PhysicalDevice;
+
+/// Seconds Escape has to be held to turn stick navigation off
+pub const ESCAPE_HOLD_S: f64 = 1.0;
+
+/// Hat and buttons of a physical device that navigate the UI, for cockpits without a mouse in
+/// reach. The controls keep driving their rebinds as well.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StickNavigationSettings {
+    pub enabled: bool,
+    /// GUID of the physical device
+    pub device: Option<String>,
+    pub hat: u32,
+    /// Clicks the focused widget
+    pub activate_button: u32,
+    /// Closes menus and popups
+    pub back_button: u32,
+}
+
+impl Default for StickNavigationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            device: None,
+            hat: 1,
+            activate_button: 1,
+            back_button: 2,
+        }
+    }
+}
+
+/// Previous state of the navigation controls, key events are only sent when they change
+#[derive(Debug, Default)]
+pub struct StickNavigation {
+    hat: Option<i32>,
+    activate: bool,
+    back: bool,
+    escape_down_since: Option<f64>,
+}
+
+impl StickNavigation {
+    /// Key events for the configured controls, none while disabled or the device is missing
+    pub fn poll<'a>(
+        &mut self,
+        settings: &StickNavigationSettings,
+        devices: impl IntoIterator<Item = &'a PhysicalDevice>,
+    ) -> Vec<Event> {
+        let device = match (settings.enabled, &settings.device) {
+            (true, Some(guid)) => devices.into_iter().find(|d| d.guid == *guid && d.connected),
+            _ => None,
+        };
+        let Some(device) = device else {
+            *self = Self {
+                escape_down_since: self.escape_down_since,
+                ..Default::default()
+            };
+            return Vec::new();
+        };
+
+        let state = &device.input_state;
+        let button = |number: u32| {
+            state
+                .buttons()
+                .nth(number.saturating_sub(1) as usize)
+                .copied()
+                .unwrap_or_default()
+        };
+        let hat = state
+            .hats()
+            .nth(settings.hat.saturating_sub(1) as usize)
+            .copied()
+            .unwrap_or(-1);
+        self.events(
+            hat,
+            button(settings.activate_button),
+            button(settings.back_button),
+        )
+    }
+
+    /// Up and left move the focus back, down and right forward. Activate presses Enter, back
+    /// presses Escape.
+    pub fn events(&mut self, hat: i32, activate: bool, back: bool) -> Vec<Event> {
+        let mut events = Vec::new();
+
+        if self.hat != Some(hat) {
+            match hat {
+                0 | 270 | 315 => push_key(&mut events, Key::Tab, Modifiers::SHIFT),
+                90 | 135 | 180 => push_key(&mut events, Key::Tab, Modifiers::NONE),
+                _ => (),
+            }
+            self.hat = Some(hat);
+        }
+        if activate && !self.activate {
+            push_key(&mut events, Key::Enter, Modifiers::NONE);
+        }
+        if back && !self.back {
+            push_key(&mut events, Key::Escape, Modifiers::NONE);
+        }
+        self.activate = activate;
+        self.back = back;
+
+        events
+    }
+
+    /// Whether Escape was held for [`ESCAPE_HOLD_S`] without interruption
+    pub fn escape_held(&mut self, down: bool, time: f64) -> bool {
+        match (down, self.escape_down_since) {
+            (false, _) => {
+                self.escape_down_since = None;
+                false
+            }
+            (true, None) => {
+                self.escape_down_since = Some(time);
+                false
+            }
+            (true, Some(since)) => time - since >= ESCAPE_HOLD_S,
+        }
+    }
+}
+
+/// Press and release in one frame, which egui counts as one key press
+fn push_key(events: &mut Vec<Event>, key: Key, modifiers: Modifiers) {
+    for pressed in [true, false] {
+        events.push(Event::Key {
+            key,
+            pressed,
+            repeat: false,
+            modifiers,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::{Event, Key, Modifiers};
+
+    use crate::input::stick_navigation::StickNavigation;
+
+    fn pressed_keys(events: &[Event]) -> Vec<(Key, Modifiers)> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } => Some((*key, *modifiers)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn controls_send_keys_on_change_only() {
+        let mut navigation = StickNavigation::default();
+        assert!(navigation.events(-1, false, false).is_empty());
+
+        let down = navigation.events(180, false, false);
+        assert_eq!(pressed_keys(&down), vec![(Key::Tab, Modifiers::NONE)]);
+        assert!(navigation.events(180, false, false).is_empty());
+
+        let up = navigation.events(0, true, false);
+        assert_eq!(
+            pressed_keys(&up),
+            vec![(Key::Tab, Modifiers::SHIFT), (Key::Enter, Modifiers::NONE)]
+        );
+        assert!(navigation.events(0, true, false).is_empty());
+
+        let back = navigation.events(-1, false, true);
+        assert_eq!(pressed_keys(&back), vec![(Key::Escape, Modifiers::NONE)]);
+    }
+
+    #[test]
+    fn escape_must_be_held_without_interruption() {
+        let mut navigation = StickNavigation::default();
+        assert!(!navigation.escape_held(true, 0.0));
+        assert!(!navigation.escape_held(true, 0.5));
+        assert!(!navigation.escape_held(false, 0.6));
+        assert!(!navigation.escape_held(true, 0.7));
+        assert!(navigation.escape_held(true, 1.7));
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+use egui::{Context, Grid, RichText, Slider, Ui};
+use vjoy::{ButtonState, HatState};
+
+use super::{Input, VirtualDevice};
+use crate::{
+    rebind::{
+        hat_to_hat::convert_vjoy_hat_to_angle,
+        rebind_processor::{write_control, write_neutral_control},
+        VirtualControl,
+    },
+    ui_data::UIData,
+};
+
+/// Virtual controls driven by hand from the test panel instead of the rebinds.
+///
+/// Held values are written after rebind processing, so they win over rebind output. A released
+/// control is reset to neutral once before the next processing, rebinds writing it take over again.
+#[derive(Debug, Default)]
+pub struct ManualOverrides {
+    /// Value per control as taken by [`write_control`]
+    held: HashMap<VirtualControl, i32>,
+    released: Vec<VirtualControl>,
+}
+
+impl ManualOverrides {
+    pub fn is_empty(&self) -> bool {
+        self.held.is_empty()
+    }
+
+    /// Holds exactly the controls in `held` and releases all others
+    pub fn replace(&mut self, held: HashMap<VirtualControl, i32>) {
+        self.released.extend(
+            self.held
+                .keys()
+                .filter(|control| !held.contains_key(control)),
+        );
+        self.released.retain(|control| !held.contains_key(control));
+        self.held = held;
+    }
+
+    pub fn clear(&mut self) {
+        self.replace(HashMap::new());
+    }
+
+    /// Resets the controls released since the last tick, before the rebinds are processed
+    pub fn reset_released(&mut self, virtual_devices: &mut [VirtualDevice]) {
+        for control in self.released.drain(..) {
+            write_neutral_control(virtual_devices, control);
+        }
+    }
+
+    /// Writes the held values over the rebind output
+    pub fn apply(&self, virtual_devices: &mut [VirtualDevice]) {
+        for (control, value) in self.held.iter() {
+            write_control(virtual_devices, *control, *value);
+        }
+    }
+}
+
+/// Test panel of the virtual device in [`UIData::test_panel`]. Controls are only overridden while
+/// their widget is held, closing the panel releases all of them.
+pub fn update_test_panel_window(ctx: &Context, input: &mut Input, ui_data: &mut UIData) {
+    let Some(id) = ui_data.test_panel else {
+        return;
+    };
+    let Some(device) = input.virtual_devices().find(|d| d.id == id) else {
+        ui_data.test_panel = None;
+        input.manual_overrides_mut().clear();
+        return;
+    };
+    let buttons: Vec<bool> = device
+        .handle
+        .buttons()
+        .map(|b| b.get() == ButtonState::Pressed)
+        .collect();
+    let axes: Vec<(String, i32)> = device
+        .handle
+        .axes()
+        .enumerate()
+        .map(|(index, axis)| {
+            let name = device
+                .handle
+                .axis_name(index)
+                .map_or_else(|| format!("Axis {}", index + 1), str::to_string);
+            (name, axis.get())
+        })
+        .collect();
+    let hats: Vec<(i32, bool)> = device
+        .handle
+        .hats()
+        .map(|hat| {
+            let continuous = matches!(hat.get(), HatState::Continuous(_));
+            (convert_vjoy_hat_to_angle(hat.get()), continuous)
+        })
+        .collect();
+    let output_paused = input.is_output_paused();
+
+    let mut held = HashMap::new();
+    let mut open = true;
+    egui::Window::new(format!("Test vJoy device {id}"))
+        .id(egui::Id::new(("test_panel", id)))
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            ui.label(
+                RichText::new(
+                    "Test feature: controls held here override the rebind output. \
+                    Closing the panel releases all of them.",
+                )
+                .color(ui.visuals().warn_fg_color),
+            );
+            if output_paused {
+                ui.label("Output is paused, nothing reaches vJoy until it is resumed.");
+            }
+            ui.separator();
+            build_ui(ui, id, &buttons, &axes, &hats, &mut held);
+        });
+
+    match open {
+        true => input.manual_overrides_mut().replace(held),
+        false => {
+            ui_data.test_panel = None;
+            input.manual_overrides_mut().clear();
+        }
+    }
+}
+
+fn build_ui(
+    ui: &mut Ui,
+    device: u32,
+    buttons: &[bool],
+    axes: &[(String, i32)],
+    hats: &[(i32, bool)],
+    held: &mut HashMap<VirtualControl, i32>,
+) {
+    ui.label("Buttons");
+    ui.horizontal_wrapped(|ui| {
+        for (index, pressed) in buttons.iter().enumerate() {
+            let button = index as u32 + 1;
+            if ui
+                .selectable_label(*pressed, button.to_string())
+                .is_pointer_button_down_on()
+            {
+                held.insert(VirtualControl::Button { device, button }, 1);
+            }
+        }
+    });
+
+    ui.separator();
+    Grid::new("TestPanelAxes").num_columns(2).show(ui, |ui| {
+        for (index, (name, value)) in axes.iter().enumerate() {
+            let mut value = *value;
+            ui.label(name);
+            let response = ui.add(Slider::new(&mut value, 0..=32767));
+            if response.is_pointer_button_down_on() || response.dragged() {
+                let axis = index as u32 + 1;
+                held.insert(VirtualControl::Axis { device, axis }, value);
+            }
+            ui.end_row();
+        }
+    });
+
+    for (index, (angle, continuous)) in hats.iter().enumerate() {
+        ui.separator();
+        ui.label(format!("Hat {}", index + 1));
+        let hat = index as u32 + 1;
+        if let Some(angle) = hat_rose(ui, hat, *angle, *continuous) {
+            held.insert(VirtualControl::Hat { device, hat }, angle);
+        }
+    }
+}
+
+/// Direction buttons around the center, diagonals only for continuous hats. Returns the angle of
+/// the held direction.
+fn hat_rose(ui: &mut Ui, hat: u32, angle: i32, continuous: bool) -> Option<i32> {
+    const ROSE: [[Option<(&str, i32)>; 3]; 3] = [
+        [Some(("↖", 315)), Some(("⬆", 0)), Some(("↗", 45))],
+        [Some(("⬅", 270)), None, Some(("➡", 90))],
+        [Some(("↙", 225)), Some(("⬇", 180)), Some(("↘", 135))],
+    ];
+
+    let mut held = None;
+    Grid::new(("TestPanelHat", hat)).show(ui, |ui| {
+        for row in ROSE {
+            for cell in row {
+                let Some((text, direction)) = cell else {
+                    ui.label("");
+                    continue;
+                };
+                let enabled = continuous || direction % 90 == 0;
+                let response =
+                    ui.add_enabled_ui(enabled, |ui| ui.selectable_label(angle == direction, text));
+                if response.inner.is_pointer_button_down_on() {
+                    held = Some(direction);
+                }
+            }
+            ui.end_row();
+        }
+    });
+    held
+}
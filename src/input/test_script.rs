@@ -0,0 +1,879 @@
+//! Test scripts: short text steps that press physical buttons, move axes and switch the shift mode
+//! with synthetic input, then check the virtual output of the live processing pipeline.
+//!
+//! ```text
+//! press dev 'Throttle' button 4 for 200ms
+//! expect vjoy1 button 12 pressed
+//! set mode 0b10
+//! expect axis 5 > 20000
+//! ```
+//!
+//! Statements are separated by new lines or `;`, `#` starts a comment. A running script takes one
+//! step per tick: actions are written over the polled input before the rebinds are processed,
+//! expectations are checked after processing and the manual overrides of the test panel.
+//! Synthetic input stays until it is released, its `for` duration ends or the script stops.
+
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use egui::{Button, Color32, Context, Grid, RichText, ScrollArea, TextEdit, Ui};
+use log::{error, info};
+use vjoy::ButtonState;
+
+use crate::{
+    error::Error,
+    input::{event_console::ControlKind, normalize_guid, Input, PhysicalDevice, VirtualDevice},
+    paths,
+    rebind::{
+        hat_to_hat::convert_vjoy_hat_to_angle, shift_mode_mask::ShiftModeMask, VirtualControl,
+    },
+    ui_data::{Toast, UIData},
+};
+
+/// Hint shown in the empty editor
+const EXAMPLE_SCRIPT: &str = "press dev 'Throttle' button 4 for 200ms
+expect vjoy1 button 12 pressed
+set mode 0b10
+expect axis 5 > 20000";
+
+/// State of the test script window
+#[derive(Debug, Default)]
+pub struct TestScriptEditor {
+    pub open: bool,
+    pub text: String,
+    /// Parse error of the last run
+    pub error: Option<String>,
+}
+
+/// Script file of the config at `config_path`, stored next to it
+pub fn script_path(config_path: &Path) -> PathBuf {
+    config_path.with_extension("test.txt")
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    fn parse(token: &str) -> Option<Self> {
+        match token {
+            "<" => Some(Comparison::Less),
+            "<=" => Some(Comparison::LessOrEqual),
+            ">" => Some(Comparison::Greater),
+            ">=" => Some(Comparison::GreaterOrEqual),
+            "=" | "==" => Some(Comparison::Equal),
+            "!=" => Some(Comparison::NotEqual),
+            _ => None,
+        }
+    }
+
+    pub fn holds(self, actual: i32, expected: i32) -> bool {
+        match self {
+            Comparison::Less => actual < expected,
+            Comparison::LessOrEqual => actual <= expected,
+            Comparison::Greater => actual > expected,
+            Comparison::GreaterOrEqual => actual >= expected,
+            Comparison::Equal => actual == expected,
+            Comparison::NotEqual => actual != expected,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Step {
+    /// Holds a physical button, released after `duration_s` if given. Doesn't wait for it.
+    Press {
+        device: String,
+        button: u32,
+        duration_s: Option<f64>,
+    },
+    /// Gives a pressed button back to the device
+    Release {
+        device: String,
+        button: u32,
+    },
+    /// Holds a physical axis at an SDL value or a hat at an angle, -1 for centered
+    Set {
+        device: String,
+        kind: ControlKind,
+        id: u32,
+        value: i32,
+    },
+    SetMode(ShiftModeMask),
+    Wait(f64),
+    /// Buttons read 1 if pressed, hats their angle or -1 for centered
+    Expect {
+        control: VirtualControl,
+        comparison: Comparison,
+        value: i32,
+    },
+    ExpectMode(ShiftModeMask),
+}
+
+/// One statement of a script with the line it was written on
+#[derive(Debug, PartialEq, Clone)]
+pub struct ScriptStep {
+    pub line: usize,
+    pub text: String,
+    pub step: Step,
+}
+
+/// Parses `text` into steps, the error names the line of the first invalid statement.
+///
+/// Expectations without a vJoy device check the last one mentioned, vJoy device 1 at first.
+pub fn parse_script(text: &str) -> Result<Vec<ScriptStep>, String> {
+    let mut default_device = 1;
+    statements(text)?
+        .into_iter()
+        .map(|(line, text, tokens)| {
+            let step = parse_statement(&tokens, &mut default_device)
+                .map_err(|e| format!("line {line}: {e}"))?;
+            Ok(ScriptStep { line, text, step })
+        })
+        .collect()
+}
+
+fn flush_token(token: &mut String, quoted: &mut bool, tokens: &mut Vec<String>) {
+    if !token.is_empty() || *quoted {
+        tokens.push(std::mem::take(token));
+        *quoted = false;
+    }
+}
+
+/// Splits `text` into (line, statement text, tokens). Quotes keep a name with spaces together.
+fn statements(text: &str) -> Result<Vec<(usize, String, Vec<String>)>, String> {
+    let mut statements = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let mut chars = line.char_indices();
+        let mut tokens = Vec::new();
+        let mut token = String::new();
+        let mut quote = None;
+        let mut quoted = false;
+        let mut start = 0;
+        loop {
+            let next = chars.next();
+            match (quote, next) {
+                (Some(q), Some((_, c))) if c == q => quote = None,
+                (Some(_), Some((_, c))) => token.push(c),
+                (Some(_), None) => return Err(format!("line {line_number}: unclosed quote")),
+                (None, Some((_, c @ ('\'' | '"')))) => {
+                    quote = Some(c);
+                    quoted = true;
+                }
+                (None, Some((_, c))) if c.is_whitespace() => {
+                    flush_token(&mut token, &mut quoted, &mut tokens)
+                }
+                (None, None | Some((_, ';' | '#'))) => {
+                    flush_token(&mut token, &mut quoted, &mut tokens);
+                    let end = next.map_or(line.len(), |(i, _)| i);
+                    if !tokens.is_empty() {
+                        let text = line[start..end].trim().to_string();
+                        statements.push((line_number, text, std::mem::take(&mut tokens)));
+                    }
+                    start = end + 1;
+                    if !matches!(next, Some((_, ';'))) {
+                        break;
+                    }
+                }
+                (None, Some((_, c))) => token.push(c),
+            }
+        }
+    }
+    Ok(statements)
+}
+
+struct Cursor<'a> {
+    tokens: &'a [String],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn next(&mut self, what: &str) -> Result<&'a str, String> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .ok_or_else(|| format!("expected {what}"))?;
+        self.pos += 1;
+        Ok(token.as_str())
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    /// Takes the next token if it is `keyword`, ignoring case
+    fn accept(&mut self, keyword: &str) -> bool {
+        let found = self
+            .peek()
+            .map_or(false, |token| token.eq_ignore_ascii_case(keyword));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<(), String> {
+        let token = self.next(&format!("`{keyword}`"))?;
+        match token.eq_ignore_ascii_case(keyword) {
+            true => Ok(()),
+            false => Err(format!("expected `{keyword}`, found `{token}`")),
+        }
+    }
+
+    fn number<T: FromStr>(&mut self, what: &str) -> Result<T, String> {
+        let token = self.next(what)?;
+        token
+            .parse()
+            .map_err(|_| format!("expected {what}, found `{token}`"))
+    }
+
+    fn finish(&self) -> Result<(), String> {
+        match self.peek() {
+            Some(token) => Err(format!("unexpected `{token}`")),
+            None => Ok(()),
+        }
+    }
+
+    /// `dev '<name or GUID>'`
+    fn physical_device(&mut self) -> Result<String, String> {
+        if !self.accept("dev") && !self.accept("device") {
+            return Err("expected `dev '<device name>'`".to_string());
+        }
+        Ok(self.next("a device name")?.to_string())
+    }
+
+    /// `vjoy1` or `vjoy 1`, `None` if the next token isn't a vJoy device
+    fn virtual_device(&mut self) -> Result<Option<u32>, String> {
+        let Some(rest) = self.peek().and_then(|token| {
+            token
+                .to_ascii_lowercase()
+                .strip_prefix("vjoy")
+                .map(str::to_string)
+        }) else {
+            return Ok(None);
+        };
+        self.pos += 1;
+        match rest.is_empty() {
+            true => self.number("a vJoy device number").map(Some),
+            false => rest
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("expected a vJoy device like vjoy1, found `vjoy{rest}`")),
+        }
+    }
+
+    /// `200ms`, `1.5s` or plain seconds
+    fn duration(&mut self) -> Result<f64, String> {
+        let token = self.next("a duration like 200ms")?;
+        let lower = token.to_ascii_lowercase();
+        let (number, per_second) = match (lower.strip_suffix("ms"), lower.strip_suffix('s')) {
+            (Some(number), _) => (number, 1000.0),
+            (None, Some(number)) => (number, 1.0),
+            (None, None) => (lower.as_str(), 1.0),
+        };
+        match number.trim().parse::<f64>() {
+            Ok(value) if value.is_finite() && value >= 0.0 => Ok(value / per_second),
+            _ => Err(format!("expected a duration like 200ms, found `{token}`")),
+        }
+    }
+}
+
+/// `0b10`, `0x02` or decimal
+fn parse_mode(token: &str) -> Result<ShiftModeMask, String> {
+    let lower = token.to_ascii_lowercase();
+    let mode = match (lower.strip_prefix("0b"), lower.strip_prefix("0x")) {
+        (Some(bits), _) => u8::from_str_radix(bits, 2),
+        (None, Some(hex)) => u8::from_str_radix(hex, 16),
+        (None, None) => lower.parse(),
+    };
+    mode.map(ShiftModeMask)
+        .map_err(|_| format!("expected a shift mode like 0b10, found `{token}`"))
+}
+
+/// Angle in degrees, or -1 for `centered`
+fn parse_hat_angle(token: &str) -> Result<i32, String> {
+    if token.eq_ignore_ascii_case("centered") || token.eq_ignore_ascii_case("center") {
+        return Ok(-1);
+    }
+    match token.parse() {
+        Ok(angle) if (0..360).contains(&angle) => Ok(angle),
+        _ => Err(format!(
+            "expected a hat angle 0 to 359 or `centered`, found `{token}`"
+        )),
+    }
+}
+
+fn parse_statement(tokens: &[String], default_device: &mut u32) -> Result<Step, String> {
+    let mut cursor = Cursor { tokens, pos: 0 };
+    let command = cursor.next("a command")?.to_ascii_lowercase();
+    let step = match command.as_str() {
+        "press" => {
+            let device = cursor.physical_device()?;
+            cursor.keyword("button")?;
+            let button = cursor.number("a button number")?;
+            let duration_s = match cursor.accept("for") {
+                true => Some(cursor.duration()?),
+                false => None,
+            };
+            Step::Press {
+                device,
+                button,
+                duration_s,
+            }
+        }
+        "release" => {
+            let device = cursor.physical_device()?;
+            cursor.keyword("button")?;
+            let button = cursor.number("a button number")?;
+            Step::Release { device, button }
+        }
+        "set" if cursor.accept("mode") => Step::SetMode(parse_mode(cursor.next("a shift mode")?)?),
+        "set" => {
+            let device = cursor.physical_device()?;
+            let kind = match cursor.next("`axis` or `hat`")? {
+                token if token.eq_ignore_ascii_case("axis") => ControlKind::Axis,
+                token if token.eq_ignore_ascii_case("hat") => ControlKind::Hat,
+                token => return Err(format!("expected `axis` or `hat`, found `{token}`")),
+            };
+            let id = cursor.number(&format!("a {kind} number"))?;
+            cursor.accept("to");
+            let value = match kind {
+                ControlKind::Hat => parse_hat_angle(cursor.next("a hat angle")?)?,
+                _ => cursor.number::<i16>("an axis value from -32768 to 32767")? as i32,
+            };
+            Step::Set {
+                device,
+                kind,
+                id,
+                value,
+            }
+        }
+        "wait" => Step::Wait(cursor.duration()?),
+        "expect" if cursor.accept("mode") => {
+            Step::ExpectMode(parse_mode(cursor.next("a shift mode")?)?)
+        }
+        "expect" => {
+            let device = cursor.virtual_device()?.unwrap_or(*default_device);
+            *default_device = device;
+            let kind = cursor
+                .next("`button`, `axis` or `hat`")?
+                .to_ascii_lowercase();
+            if !["button", "axis", "hat"].contains(&kind.as_str()) {
+                return Err(format!(
+                    "expected `button`, `axis` or `hat`, found `{kind}`"
+                ));
+            }
+            let id = cursor.number(&format!("a {kind} number"))?;
+            let (control, comparison, value) = match kind.as_str() {
+                "button" => {
+                    let pressed = match cursor.next("`pressed` or `released`")? {
+                        token if token.eq_ignore_ascii_case("pressed") => 1,
+                        token if token.eq_ignore_ascii_case("released") => 0,
+                        token => {
+                            return Err(format!(
+                                "expected `pressed` or `released`, found `{token}`"
+                            ))
+                        }
+                    };
+                    let control = VirtualControl::Button { device, button: id };
+                    (control, Comparison::Equal, pressed)
+                }
+                "axis" => {
+                    let token = cursor.next("a comparison like >")?;
+                    let comparison = Comparison::parse(token)
+                        .ok_or_else(|| format!("expected a comparison like >, found `{token}`"))?;
+                    let control = VirtualControl::Axis { device, axis: id };
+                    (control, comparison, cursor.number("an axis value")?)
+                }
+                "hat" => {
+                    let token = cursor.next("a hat angle")?;
+                    let (comparison, token) = match Comparison::parse(token) {
+                        Some(comparison) => (comparison, cursor.next("a hat angle")?),
+                        None => (Comparison::Equal, token),
+                    };
+                    let control = VirtualControl::Hat { device, hat: id };
+                    (control, comparison, parse_hat_angle(token)?)
+                }
+                _ => unreachable!(),
+            };
+            Step::Expect {
+                control,
+                comparison,
+                value,
+            }
+        }
+        _ => return Err(format!("unknown command `{command}`")),
+    };
+    cursor.finish()?;
+    Ok(step)
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum StepStatus {
+    Pending,
+    Running,
+    Passed,
+    Failed(String),
+}
+
+/// Synthetic value written over a physical control each tick
+struct Injection {
+    guid: String,
+    kind: ControlKind,
+    id: u32,
+    value: i32,
+    until: Option<f64>,
+}
+
+/// Connected device whose GUID or name is `spec`, ignoring case
+fn find_device<'a>(
+    devices: &'a mut [PhysicalDevice],
+    spec: &str,
+) -> Option<&'a mut PhysicalDevice> {
+    let guid = normalize_guid(spec);
+    devices
+        .iter_mut()
+        .find(|d| d.connected && (d.guid == guid || d.name().eq_ignore_ascii_case(spec.trim())))
+}
+
+/// Value of a virtual control as compared by [`Step::Expect`]
+fn read_control(virtual_devices: &[VirtualDevice], control: VirtualControl) -> Option<i32> {
+    let (device, index) = match control {
+        VirtualControl::Button { device, button: id }
+        | VirtualControl::Axis { device, axis: id }
+        | VirtualControl::Hat { device, hat: id } => (device, id.checked_sub(1)? as usize),
+    };
+    let handle = &virtual_devices.iter().find(|d| d.id == device)?.handle;
+    match control {
+        VirtualControl::Button { .. } => handle
+            .buttons()
+            .nth(index)
+            .map(|b| i32::from(b.get() == ButtonState::Pressed)),
+        VirtualControl::Axis { .. } => handle.axes().nth(index).map(|a| a.get()),
+        VirtualControl::Hat { .. } => handle
+            .hats()
+            .nth(index)
+            .map(|h| convert_vjoy_hat_to_angle(h.get())),
+    }
+}
+
+/// A script running against the live pipeline, driven by [`Input::update`]
+pub struct ScriptRun {
+    steps: Vec<ScriptStep>,
+    status: Vec<StepStatus>,
+    next: usize,
+    wait_until: Option<f64>,
+    injections: Vec<Injection>,
+}
+
+impl ScriptRun {
+    pub fn new(steps: Vec<ScriptStep>) -> Self {
+        Self {
+            status: vec![StepStatus::Pending; steps.len()],
+            steps,
+            next: 0,
+            wait_until: None,
+            injections: Vec::new(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.steps.len()
+    }
+
+    pub fn results(&self) -> impl Iterator<Item = (&ScriptStep, &StepStatus)> {
+        self.steps.iter().zip(self.status.iter())
+    }
+
+    /// Number of (passed, failed) steps
+    pub fn counts(&self) -> (usize, usize) {
+        let passed = self
+            .status
+            .iter()
+            .filter(|s| **s == StepStatus::Passed)
+            .count();
+        let failed = self
+            .status
+            .iter()
+            .filter(|s| matches!(s, StepStatus::Failed(_)))
+            .count();
+        (passed, failed)
+    }
+
+    /// Ends the run and its synthetic input, the steps not reached stay pending
+    pub fn stop(&mut self) {
+        if let Some(status) = self.status.get_mut(self.next) {
+            if *status == StepStatus::Running {
+                *status = StepStatus::Failed("stopped".to_string());
+            }
+        }
+        self.next = self.steps.len();
+        self.wait_until = None;
+        self.injections.clear();
+    }
+
+    fn finish_step(&mut self, status: StepStatus) {
+        self.status[self.next] = status;
+        self.next += 1;
+    }
+
+    fn inject(
+        &mut self,
+        devices: &mut [PhysicalDevice],
+        device: &str,
+        kind: ControlKind,
+        id: u32,
+        value: i32,
+        until: Option<f64>,
+    ) -> StepStatus {
+        let Some(physical) = find_device(devices, device) else {
+            return StepStatus::Failed(format!("no connected device '{device}'"));
+        };
+        if !physical.input_state.inject(kind, id, value) {
+            return StepStatus::Failed(format!("{} has no {kind} {id}", physical.name()));
+        }
+        self.injections
+            .retain(|i| !(i.guid == physical.guid && i.kind == kind && i.id == id));
+        self.injections.push(Injection {
+            guid: physical.guid.clone(),
+            kind,
+            id,
+            value,
+            until,
+        });
+        StepStatus::Passed
+    }
+
+    /// Takes the next action step unless waiting and writes the synthetic input over the polled
+    /// values. Called between polling and processing, returns the shift mode to switch to.
+    pub fn begin_tick(
+        &mut self,
+        time: f64,
+        devices: &mut [PhysicalDevice],
+    ) -> Option<ShiftModeMask> {
+        self.injections
+            .retain(|i| i.until.map_or(true, |until| time < until));
+        if let Some(until) = self.wait_until {
+            if time < until {
+                self.apply_injections(devices);
+                return None;
+            }
+            self.wait_until = None;
+            self.finish_step(StepStatus::Passed);
+        }
+
+        let mut mode = None;
+        if let Some(step) = self.steps.get(self.next).map(|s| s.step.clone()) {
+            self.status[self.next] = StepStatus::Running;
+            match step {
+                Step::Press {
+                    device,
+                    button,
+                    duration_s,
+                } => {
+                    let until = duration_s.map(|duration| time + duration);
+                    let status =
+                        self.inject(devices, &device, ControlKind::Button, button, 1, until);
+                    self.finish_step(status);
+                }
+                Step::Release { device, button } => {
+                    let status = match find_device(devices, &device) {
+                        Some(physical) => {
+                            let guid = physical.guid.clone();
+                            self.injections.retain(|i| {
+                                !(i.guid == guid && i.kind == ControlKind::Button && i.id == button)
+                            });
+                            StepStatus::Passed
+                        }
+                        None => StepStatus::Failed(format!("no connected device '{device}'")),
+                    };
+                    self.finish_step(status);
+                }
+                Step::Set {
+                    device,
+                    kind,
+                    id,
+                    value,
+                } => {
+                    let status = self.inject(devices, &device, kind, id, value, None);
+                    self.finish_step(status);
+                }
+                Step::SetMode(shift_mode) => {
+                    mode = Some(shift_mode);
+                    self.finish_step(StepStatus::Passed);
+                }
+                Step::Wait(duration) => self.wait_until = Some(time + duration),
+                // Checked after processing
+                Step::Expect { .. } | Step::ExpectMode(_) => {}
+            }
+        } else {
+            self.injections.clear();
+        }
+
+        self.apply_injections(devices);
+        mode
+    }
+
+    fn apply_injections(&self, devices: &mut [PhysicalDevice]) {
+        for injection in self.injections.iter() {
+            if let Some(physical) = devices
+                .iter_mut()
+                .find(|d| d.connected && d.guid == injection.guid)
+            {
+                physical
+                    .input_state
+                    .inject(injection.kind, injection.id, injection.value);
+            }
+        }
+    }
+
+    /// Checks a running expectation against the processed output
+    pub fn end_tick(&mut self, virtual_devices: &[VirtualDevice], shift_mode: ShiftModeMask) {
+        if self.status.get(self.next) != Some(&StepStatus::Running) {
+            return;
+        }
+        let status = match self.steps[self.next].step {
+            Step::Expect {
+                control,
+                comparison,
+                value,
+            } => match read_control(virtual_devices, control) {
+                Some(actual) if comparison.holds(actual, value) => StepStatus::Passed,
+                Some(actual) => StepStatus::Failed(format!("was {actual}")),
+                None => StepStatus::Failed("no such vJoy control".to_string()),
+            },
+            Step::ExpectMode(mode) if mode == shift_mode => StepStatus::Passed,
+            Step::ExpectMode(_) => StepStatus::Failed(format!("was {:#010b}", shift_mode.0)),
+            _ => return,
+        };
+        self.finish_step(status);
+    }
+}
+
+pub fn update_window(ctx: &Context, input: &mut Input, ui_data: &mut UIData) {
+    if !ui_data.test_script.open {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new("Test script")
+        .open(&mut open)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            build_ui(ui, input, ui_data);
+        });
+    ui_data.test_script.open = open;
+    if !open {
+        input.stop_test_script();
+    }
+}
+
+fn build_ui(ui: &mut Ui, input: &mut Input, ui_data: &mut UIData) {
+    let path = input.get_config_path().map(script_path);
+    let running = input.test_script().map_or(false, |run| !run.is_finished());
+    ui.horizontal(|ui| {
+        if ui.add_enabled(!running, Button::new("Run")).clicked() {
+            match parse_script(&ui_data.test_script.text) {
+                Ok(steps) => {
+                    ui_data.test_script.error = None;
+                    input.start_test_script(steps);
+                }
+                Err(e) => ui_data.test_script.error = Some(e),
+            }
+        }
+        if ui.add_enabled(running, Button::new("Stop")).clicked() {
+            input.stop_test_script();
+        }
+        ui.separator();
+
+        let file_hover = path.as_ref().map_or_else(
+            || "Save the config first, scripts are stored next to it".to_string(),
+            |path| path.display().to_string(),
+        );
+        let load = ui
+            .add_enabled(path.is_some(), Button::new("Load"))
+            .on_hover_text(&file_hover)
+            .on_disabled_hover_text(&file_hover);
+        if let (true, Some(path)) = (load.clicked(), &path) {
+            match std::fs::read_to_string(path) {
+                Ok(text) => {
+                    ui_data.test_script.text = text;
+                    ui_data.test_script.error = None;
+                }
+                Err(e) => {
+                    let e = Error::from(e);
+                    error!("Failed to load test script {}: {e}", path.display());
+                    ui_data.toast = Some(Toast::error(&e, ui.input(|i| i.time)));
+                }
+            }
+        }
+        let save = ui
+            .add_enabled(path.is_some(), Button::new("Save"))
+            .on_hover_text(&file_hover)
+            .on_disabled_hover_text(&file_hover);
+        if let (true, Some(path)) = (save.clicked(), &path) {
+            match paths::write_file(path, &ui_data.test_script.text) {
+                Ok(()) => info!("Saved test script to {}", path.display()),
+                Err(e) => ui_data.modals.report_save_error("test script", e),
+            }
+        }
+    });
+
+    ui.add(
+        TextEdit::multiline(&mut ui_data.test_script.text)
+            .code_editor()
+            .desired_rows(8)
+            .desired_width(f32::INFINITY)
+            .hint_text(EXAMPLE_SCRIPT),
+    );
+    if let Some(error) = &ui_data.test_script.error {
+        ui.colored_label(ui.visuals().error_fg_color, error);
+    }
+
+    let Some(run) = input.test_script() else {
+        return;
+    };
+    ui.separator();
+    let (passed, failed) = run.counts();
+    let suffix = if running { ", running" } else { "" };
+    ui.label(format!("{passed} passed, {failed} failed{suffix}"));
+    ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+        Grid::new("test_script_results")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                for (step, status) in run.results() {
+                    let (icon, color, message) = match status {
+                        StepStatus::Pending => ("·", ui.visuals().weak_text_color(), ""),
+                        StepStatus::Running => ("…", ui.visuals().text_color(), ""),
+                        StepStatus::Passed => ("✔", Color32::GREEN, ""),
+                        StepStatus::Failed(message) => {
+                            ("✘", ui.visuals().error_fg_color, message.as_str())
+                        }
+                    };
+                    ui.label(RichText::new(icon).color(color));
+                    ui.label(step.line.to_string());
+                    ui.label(RichText::new(&step.text).monospace());
+                    ui.label(message);
+                    ui.end_row();
+                }
+            });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        input::{
+            event_console::ControlKind,
+            test_script::{parse_script, Comparison, Step},
+        },
+        rebind::{shift_mode_mask::ShiftModeMask, VirtualControl},
+    };
+
+    fn steps(text: &str) -> Vec<Step> {
+        parse_script(text)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.step)
+            .collect()
+    }
+
+    #[test]
+    fn parses_the_example_steps() {
+        let script = "press dev 'Throttle' button 4 for 200ms; expect vJoy1 button 12 pressed\n\
+                      set mode 0b10 # enter the second mode\n\
+                      expect axis 5 > 20000";
+        assert_eq!(
+            steps(script),
+            vec![
+                Step::Press {
+                    device: "Throttle".to_string(),
+                    button: 4,
+                    duration_s: Some(0.2),
+                },
+                Step::Expect {
+                    control: VirtualControl::Button {
+                        device: 1,
+                        button: 12
+                    },
+                    comparison: Comparison::Equal,
+                    value: 1,
+                },
+                Step::SetMode(ShiftModeMask(0b10)),
+                Step::Expect {
+                    control: VirtualControl::Axis { device: 1, axis: 5 },
+                    comparison: Comparison::Greater,
+                    value: 20000,
+                },
+            ]
+        );
+        let lines: Vec<usize> = parse_script(script)
+            .unwrap()
+            .iter()
+            .map(|s| s.line)
+            .collect();
+        assert_eq!(lines, vec![1, 1, 2, 3]);
+        assert_eq!(parse_script(script).unwrap()[2].text, "set mode 0b10");
+    }
+
+    #[test]
+    fn virtual_device_carries_over_and_hats_take_angles() {
+        let script = "set DEV \"Left stick; grip\" hat 1 to 90\n\
+                      expect vjoy 2 hat 1 centered\n\
+                      expect hat 1 != 90\n\
+                      wait 1.5s\n\
+                      release dev abc button 2";
+        assert_eq!(
+            steps(script),
+            vec![
+                Step::Set {
+                    device: "Left stick; grip".to_string(),
+                    kind: ControlKind::Hat,
+                    id: 1,
+                    value: 90,
+                },
+                Step::Expect {
+                    control: VirtualControl::Hat { device: 2, hat: 1 },
+                    comparison: Comparison::Equal,
+                    value: -1,
+                },
+                Step::Expect {
+                    control: VirtualControl::Hat { device: 2, hat: 1 },
+                    comparison: Comparison::NotEqual,
+                    value: 90,
+                },
+                Step::Wait(1.5),
+                Step::Release {
+                    device: "abc".to_string(),
+                    button: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        for (script, error) in [
+            ("wait 1s\njump 3", "line 2: unknown command `jump`"),
+            ("press dev x button", "line 1: expected a button number"),
+            ("set dev x axis 1 40000", "line 1: expected an axis value"),
+            ("expect axis 1 ~ 5", "line 1: expected a comparison like >"),
+            ("set mode 0b2", "line 1: expected a shift mode like 0b10"),
+            ("press dev 'x button 1", "line 1: unclosed quote"),
+            ("wait 1s 2s", "line 1: unexpected `2s`"),
+        ] {
+            let e = parse_script(script).unwrap_err();
+            assert!(e.starts_with(error), "{e}");
+        }
+    }
+}
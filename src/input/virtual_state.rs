@@ -0,0 +1,193 @@
+use vjoy::{ButtonState, Device, FourWayHat, HatState};
+
+use crate::rebind::axis_neutral::AXIS_CENTER;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VirtualButton {
+    state: ButtonState,
+}
+
+impl VirtualButton {
+    pub fn get(&self) -> ButtonState {
+        self.state
+    }
+
+    pub fn set(&mut self, state: ButtonState) {
+        self.state = state;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VirtualAxis {
+    value: i32,
+    neutral: i32,
+}
+
+impl VirtualAxis {
+    pub fn get(&self) -> i32 {
+        self.value
+    }
+
+    pub fn set(&mut self, value: i32) {
+        self.value = value;
+    }
+
+    /// Value the axis returns to when its output is reset
+    pub fn neutral(&self) -> i32 {
+        self.neutral
+    }
+
+    pub fn set_neutral(&mut self, neutral: i32) {
+        self.neutral = neutral;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VirtualHat {
+    state: HatState,
+}
+
+impl VirtualHat {
+    pub fn get(&self) -> HatState {
+        self.state
+    }
+
+    pub fn set(&mut self, state: HatState) {
+        self.state = state;
+    }
+}
+
+/// Cached output state of one virtual device.
+///
+/// Rebinds write into this instead of the vJoy device directly, the [`OutputBackend`] pushes it
+/// out once per tick.
+///
+/// [`OutputBackend`]: super::backend::OutputBackend
+#[derive(Debug, Clone, PartialEq)]
+pub struct VirtualState {
+    id: u32,
+    buttons: Vec<VirtualButton>,
+    axes: Vec<VirtualAxis>,
+    hats: Vec<VirtualHat>,
+    /// vJoy display name per axis, e.g. "Rx". Empty for states not created from a vJoy device.
+    axis_names: Vec<String>,
+}
+
+impl VirtualState {
+    /// Neutral state: buttons released, axes centered and hats of `hat_type` centered
+    pub fn new(
+        id: u32,
+        num_buttons: usize,
+        num_axes: usize,
+        num_hats: usize,
+        hat_type: HatState,
+    ) -> Self {
+        let hat_state = match hat_type {
+            HatState::Discrete(_) => HatState::Discrete(FourWayHat::Centered),
+            HatState::Continuous(_) => HatState::Continuous(u32::MAX),
+        };
+
+        Self {
+            id,
+            buttons: vec![
+                VirtualButton {
+                    state: ButtonState::Released
+                };
+                num_buttons
+            ],
+            axes: vec![
+                VirtualAxis {
+                    value: AXIS_CENTER,
+                    neutral: AXIS_CENTER,
+                };
+                num_axes
+            ],
+            hats: vec![VirtualHat { state: hat_state }; num_hats],
+            axis_names: Vec::new(),
+        }
+    }
+
+    pub fn from_vjoy(device: &Device) -> Self {
+        Self {
+            id: device.id(),
+            buttons: device
+                .buttons()
+                .map(|button| VirtualButton {
+                    state: button.get(),
+                })
+                .collect(),
+            axes: device
+                .axes()
+                .map(|axis| VirtualAxis {
+                    value: axis.get(),
+                    neutral: AXIS_CENTER,
+                })
+                .collect(),
+            hats: device
+                .hats()
+                .map(|hat| VirtualHat { state: hat.get() })
+                .collect(),
+            axis_names: device
+                .axes()
+                .map(|axis| axis.clone().display_name())
+                .collect(),
+        }
+    }
+
+    /// Copies the cached state into a vJoy device with the same layout.
+    pub fn apply_to_vjoy(&self, device: &mut Device) {
+        for (output, button) in device.buttons_mut().zip(self.buttons.iter()) {
+            output.set(button.get());
+        }
+        for (output, axis) in device.axes_mut().zip(self.axes.iter()) {
+            output.set(axis.get());
+        }
+        for (output, hat) in device.hats_mut().zip(self.hats.iter()) {
+            output.set(hat.get());
+        }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn buttons(&self) -> std::slice::Iter<VirtualButton> {
+        self.buttons.iter()
+    }
+
+    pub fn buttons_mut(&mut self) -> std::slice::IterMut<VirtualButton> {
+        self.buttons.iter_mut()
+    }
+
+    pub fn axes(&self) -> std::slice::Iter<VirtualAxis> {
+        self.axes.iter()
+    }
+
+    pub fn axes_mut(&mut self) -> std::slice::IterMut<VirtualAxis> {
+        self.axes.iter_mut()
+    }
+
+    pub fn axis_name(&self, index: usize) -> Option<&str> {
+        self.axis_names.get(index).map(String::as_str)
+    }
+
+    pub fn hats(&self) -> std::slice::Iter<VirtualHat> {
+        self.hats.iter()
+    }
+
+    pub fn hats_mut(&mut self) -> std::slice::IterMut<VirtualHat> {
+        self.hats.iter_mut()
+    }
+
+    pub fn num_buttons(&self) -> usize {
+        self.buttons.len()
+    }
+
+    pub fn num_axes(&self) -> usize {
+        self.axes.len()
+    }
+
+    pub fn num_hats(&self) -> usize {
+        self.hats.len()
+    }
+}
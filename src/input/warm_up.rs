@@ -0,0 +1,109 @@
+/// Polls of a newly opened device that are always ignored
+const WARM_UP_MIN_POLLS: u32 = 5;
+/// Axes have to stay within [`WARM_UP_AXIS_TOLERANCE`] for this long before the device is used
+const WARM_UP_STABLE_DURATION: f64 = 0.1;
+const WARM_UP_AXIS_TOLERANCE: i32 = 1024;
+/// Noisy axes may never settle, the device is used after this long regardless
+const WARM_UP_MAX_DURATION: f64 = 1.0;
+
+/// Warm-up of a newly opened physical device.
+///
+/// SDL often reports garbage axis values (all -32768 or stale values) for the first polls after
+/// hot-plug. Until the raw axes have settled, the device's input state is left neutral so rebinds,
+/// plots and histories don't pick up the spike.
+#[derive(Debug, Clone, Default)]
+pub struct WarmUp {
+    polls: u32,
+    started: Option<f64>,
+    stable_since: f64,
+    reference: Vec<i32>,
+    done: bool,
+}
+
+impl WarmUp {
+    /// For devices that were already connected before the device list was fetched again
+    pub fn finished() -> Self {
+        Self {
+            done: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_initializing(&self) -> bool {
+        !self.done
+    }
+
+    /// Feeds one poll of raw axis values. Returns true while the device is still initializing.
+    pub fn update(&mut self, axes: &[i32], time: f64) -> bool {
+        if self.done {
+            return false;
+        }
+
+        let started = *self.started.get_or_insert(time);
+        self.polls += 1;
+
+        let stable = self.reference.len() == axes.len()
+            && self
+                .reference
+                .iter()
+                .zip(axes)
+                .all(|(reference, value)| (reference - value).abs() <= WARM_UP_AXIS_TOLERANCE);
+        if !stable {
+            self.reference = axes.to_vec();
+            self.stable_since = time;
+        }
+
+        self.done = self.polls >= WARM_UP_MIN_POLLS
+            && (time - self.stable_since >= WARM_UP_STABLE_DURATION
+                || time - started >= WARM_UP_MAX_DURATION);
+        !self.done
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::warm_up::WarmUp;
+
+    #[test]
+    fn spike_after_hot_plug_is_skipped() {
+        let mut warm_up = WarmUp::default();
+        let mut time = 0.0;
+        let mut poll = |warm_up: &mut WarmUp, axes: &[i32]| {
+            time += 1.0 / 64.0;
+            warm_up.update(axes, time)
+        };
+
+        for _ in 0..3 {
+            assert!(poll(&mut warm_up, &[-32768, -32768, -32768]));
+        }
+        // Real values arrive, still initializing until they were stable for a while
+        for _ in 0..7 {
+            assert!(poll(&mut warm_up, &[120, -80, 32767]));
+        }
+        assert!(!poll(&mut warm_up, &[100, -60, 32767]));
+        assert!(!warm_up.is_initializing());
+
+        assert!(!poll(&mut warm_up, &[-32768, -32768, -32768]));
+    }
+
+    #[test]
+    fn noisy_axes_finish_after_max_duration() {
+        let mut warm_up = WarmUp::default();
+        let mut initializing = true;
+        for poll in 0..200 {
+            let value = if poll % 2 == 0 { -20000 } else { 20000 };
+            initializing = warm_up.update(&[value], poll as f64 / 64.0);
+            if !initializing {
+                assert_eq!(poll, 64);
+                break;
+            }
+        }
+        assert!(!initializing);
+    }
+
+    #[test]
+    fn known_devices_skip_warm_up() {
+        let mut warm_up = WarmUp::finished();
+        assert!(!warm_up.update(&[-32768], 0.0));
+    }
+}
@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+
+/// Startup options from the command line and the keyboard
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LaunchOptions {
+    /// Starts with an empty config and paused output instead of loading the last config
+    pub safe_mode: bool,
+    /// Starts with the built-in Xbox 360 test config instead of the last config
+    pub debug_config: bool,
+    /// Starts with the window minimized, e.g. when started with Windows
+    pub start_minimized: bool,
+    /// Config to load instead of the last config
+    pub config: Option<PathBuf>,
+}
+
+impl LaunchOptions {
+    pub fn from_args(args: &[String]) -> Self {
+        Self {
+            safe_mode: args.iter().any(|arg| arg == "--safe-mode"),
+            debug_config: args.iter().any(|arg| arg == "--debug-config"),
+            start_minimized: args.iter().any(|arg| arg == "--start-minimized"),
+            config: args
+                .iter()
+                .position(|arg| arg == "--config")
+                .and_then(|index| args.get(index + 1))
+                .map(PathBuf::from),
+        }
+    }
+
+    /// Options from `args`, with safe mode also entered by holding Shift while starting
+    pub fn detect(args: &[String]) -> Self {
+        let mut options = Self::from_args(args);
+        options.safe_mode |= shift_held();
+        options
+    }
+}
+
+#[cfg(windows)]
+fn shift_held() -> bool {
+    const VK_SHIFT: i32 = 0x10;
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetAsyncKeyState(key: i32) -> i16;
+    }
+
+    // SAFETY: plain Win32 call without pointers, the sign bit is set while the key is down
+    unsafe { GetAsyncKeyState(VK_SHIFT) < 0 }
+}
+
+#[cfg(not(windows))]
+fn shift_held() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::launch::LaunchOptions;
+
+    #[test]
+    fn flags_are_read_from_the_arguments() {
+        let args = |args: &[&str]| {
+            LaunchOptions::from_args(&args.iter().map(|a| a.to_string()).collect::<Vec<_>>())
+        };
+        assert_eq!(args(&[]), LaunchOptions::default());
+        assert!(args(&["--safe-mode"]).safe_mode);
+        assert!(!args(&["--safe-mode"]).debug_config);
+        assert!(args(&["--debug-config"]).debug_config);
+
+        let autostart = args(&["--start-minimized", "--config", "C:\\Cfg\\a b.toml"]);
+        assert!(autostart.start_minimized);
+        assert_eq!(autostart.config, Some(PathBuf::from("C:\\Cfg\\a b.toml")));
+        assert_eq!(args(&["--config"]).config, None);
+    }
+}
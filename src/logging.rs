@@ -0,0 +1,274 @@
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use env_logger::{fmt::Formatter, Logger, Target, WriteStyle};
+use log::{info, warn, LevelFilter, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+
+/// Size after which the current log file is closed and a new one is started
+const LOG_FILE_MAX_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Number of log files kept in the log dir, older files are deleted on rotation
+const LOG_FILES_KEPT: usize = 5;
+
+const LOG_FILE_PREFIX: &str = "rust-vjoy-manager";
+
+/// File log sink settings, applied on the next start.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct FileLogSettings {
+    pub enabled: bool,
+    pub level: String,
+}
+
+impl Default for FileLogSettings {
+    fn default() -> Self {
+        Self {
+            enabled: !cfg!(debug_assertions),
+            level: "info".to_string(),
+        }
+    }
+}
+
+impl FileLogSettings {
+    pub fn level_filter(&self) -> LevelFilter {
+        self.level.parse().unwrap_or(LevelFilter::Info)
+    }
+}
+
+/// `logs/` in the config dir.
+pub fn log_dir() -> PathBuf {
+    paths::config_dir().unwrap_or_default().join("logs")
+}
+
+/// Opens the log dir in the file explorer.
+pub fn open_log_dir() {
+    let dir = log_dir();
+    if let Err(e) = std::process::Command::new("explorer").arg(&dir).spawn() {
+        log::error!("Failed to open log folder {:?}. Reason: {}", dir, e);
+    }
+}
+
+/// Stderr logger configured by `RUST_LOG` and an optional file logger with its own level.
+pub fn init_logger(settings: &FileLogSettings) {
+    std::env::set_var("RUST_BACKTRACE", "1");
+    let env = env_logger::Env::default()
+        .write_style_or("RUST_LOG_STYLE", "always")
+        .filter_or("RUST_LOG", "trace");
+
+    let stderr = env_logger::Builder::from_env(env)
+        .target(Target::Stderr)
+        .format(format_record)
+        .build();
+
+    let mut file_error = None;
+    let file = match settings.enabled {
+        false => None,
+        true => match RotatingLogFile::open(log_dir()) {
+            Ok(file) => Some(
+                env_logger::Builder::new()
+                    .filter_level(settings.level_filter())
+                    .write_style(WriteStyle::Never)
+                    .target(Target::Pipe(Box::new(file)))
+                    .format(format_record)
+                    .build(),
+            ),
+            Err(e) => {
+                file_error = Some(e);
+                None
+            }
+        },
+    };
+
+    let max_level = stderr
+        .filter()
+        .max(file.as_ref().map_or(LevelFilter::Off, |f| f.filter()));
+    log::set_max_level(max_level);
+    if log::set_boxed_logger(Box::new(SplitLogger { stderr, file })).is_err() {
+        return;
+    }
+
+    info!("Logger init for stderr");
+    match file_error {
+        Some(e) => warn!("Failed to open log file in {:?}. Reason: {}", log_dir(), e),
+        None if settings.enabled => info!("Logger init for {:?}", log_dir()),
+        None => {}
+    }
+}
+
+fn format_record(buf: &mut Formatter, record: &Record) -> std::io::Result<()> {
+    let mut style = buf.style();
+
+    match record.level() {
+        log::Level::Info => style.set_color(env_logger::fmt::Color::Green),
+        log::Level::Warn => style.set_color(env_logger::fmt::Color::Yellow),
+        log::Level::Error => style.set_color(env_logger::fmt::Color::Red),
+        _ => style.set_color(env_logger::fmt::Color::White),
+    };
+
+    let timestamp = buf.timestamp();
+
+    writeln!(
+        buf,
+        "{:<20} : {:<5} : {}",
+        timestamp,
+        style.value(record.level()),
+        record.args()
+    )
+}
+
+struct SplitLogger {
+    stderr: Logger,
+    file: Option<Logger>,
+}
+
+impl Log for SplitLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.stderr.enabled(metadata) || self.file.as_ref().is_some_and(|f| f.enabled(metadata))
+    }
+
+    fn log(&self, record: &Record) {
+        self.stderr.log(record);
+        if let Some(file) = &self.file {
+            file.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.stderr.flush();
+        if let Some(file) = &self.file {
+            file.flush();
+        }
+    }
+}
+
+/// Date-stamped log files (`rust-vjoy-manager_2023-05-01_000.log`) rotated by size.
+struct RotatingLogFile {
+    dir: PathBuf,
+    file: File,
+    written: u64,
+}
+
+impl RotatingLogFile {
+    fn open(dir: PathBuf) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        let file = File::create(next_log_path(&dir, SystemTime::now()))?;
+        prune_log_files(&dir, LOG_FILES_KEPT)?;
+        Ok(Self {
+            dir,
+            file,
+            written: 0,
+        })
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+        self.file = File::create(next_log_path(&self.dir, SystemTime::now()))?;
+        self.written = 0;
+        prune_log_files(&self.dir, LOG_FILES_KEPT)
+    }
+}
+
+impl Write for RotatingLogFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > LOG_FILE_MAX_BYTES {
+            self.rotate()?;
+        }
+
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// First unused `<prefix>_<date>_<index>.log` in `dir` for the UTC date of `now`.
+fn next_log_path(dir: &Path, now: SystemTime) -> PathBuf {
+    let (year, month, day) = utc_date(now);
+    let stem = format!("{LOG_FILE_PREFIX}_{year:04}-{month:02}-{day:02}");
+    (0..)
+        .map(|index| dir.join(format!("{stem}_{index:03}.log")))
+        .find(|path| !path.exists())
+        .unwrap()
+}
+
+/// Deletes all but the newest `keep` log files. Names sort chronologically.
+fn prune_log_files(dir: &Path, keep: usize) -> std::io::Result<()> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "log")
+                && path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with(LOG_FILE_PREFIX))
+        })
+        .collect();
+    files.sort();
+
+    let remove = files.len().saturating_sub(keep);
+    for path in files.into_iter().take(remove) {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// (year, month, day) in UTC, civil-from-days without a date crate.
+fn utc_date(time: SystemTime) -> (i64, u32, u32) {
+    let secs = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+    let days = secs.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use crate::logging::{next_log_path, prune_log_files, utc_date};
+
+    #[test]
+    fn utc_dates() {
+        let at = |secs: u64| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        assert_eq!(utc_date(at(0)), (1970, 1, 1));
+        assert_eq!(utc_date(at(951_782_400)), (2000, 2, 29));
+        assert_eq!(utc_date(at(1_682_899_199)), (2023, 4, 30));
+    }
+
+    #[test]
+    fn rotation_keeps_newest_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_682_899_199);
+        let paths: Vec<_> = (0..4)
+            .map(|_| {
+                let path = next_log_path(&dir, now);
+                std::fs::write(&path, "").unwrap();
+                path
+            })
+            .collect();
+        assert!(paths[3].ends_with("rust-vjoy-manager_2023-04-30_003.log"));
+
+        prune_log_files(dir, 2).unwrap();
+        assert!(!paths[1].exists());
+        assert!(paths[2].exists() && paths[3].exists());
+    }
+}
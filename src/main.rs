@@ -1,16 +1,34 @@
+pub mod autostart;
 pub mod config;
+pub mod config_diff;
+pub mod config_editor;
 pub mod error;
 pub mod graphics_backend;
 pub mod input;
+pub mod launch;
+pub mod logging;
 pub mod manager;
+pub mod modal;
+pub mod paths;
+pub mod performance;
 pub mod previous;
+pub mod rate_limited_log;
 pub mod rebind;
+pub mod setup_wizard;
+pub mod split_view;
 pub mod ui_data;
+pub mod update_check;
+pub mod validate;
+pub mod view_state;
+pub mod visualization;
 
 use egui::{epaint::Hsva, Color32};
 use error::Error;
+use launch::LaunchOptions;
 use log::{error, info};
 use manager::Manager;
+use paths::Migration;
+use previous::Previous;
 
 // use profiling::tracy_client;
 use winit::{
@@ -19,20 +37,42 @@ use winit::{
     window::{Window, WindowBuilder},
 };
 
+pub const WINDOW_TITLE: &str = "Rust vJoy Manager";
+
 pub fn auto_color(i: usize) -> Color32 {
     Hsva::new(i as f32 * 0.618034, 0.85, 0.5, 1.0).into()
 }
 
 fn main() -> Result<(), Error> {
-    init_logger();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "--validate") {
+        std::process::exit(validate::run_cli(&args[1..]));
+    }
+
+    let launch = LaunchOptions::detect(&args);
+    let migration = Migration::run_default();
+    let mut previous = Previous::read_or_default();
+    previous.remap_migrated(&migration);
+    logging::init_logger(&previous.file_log);
     info!("Startup");
+    migration.log();
+    paths::log_paths();
+    if launch.safe_mode {
+        info!("Starting in safe mode");
+    }
     #[cfg(feature = "profile")]
     {
         profiling::tracy_client::Client::start();
         profiling::register_thread!("Main Thread");
     }
-    let (window, event_loop) = create_window("Rust vJoy Manager", [800, 600])?;
-    let manager = Manager::new(&window, &event_loop)?;
+    let window_size = previous.window_size.unwrap_or([800, 600]);
+    let (window, event_loop) =
+        create_window(WINDOW_TITLE, window_size).unwrap_or_else(|e| print_error_and_exit(&e));
+    if launch.start_minimized {
+        window.set_minimized(true);
+    }
+    let manager = Manager::new(&window, &event_loop, previous, launch)
+        .unwrap_or_else(|e| print_error_and_exit(&e));
     manager.run(window, event_loop)
 }
 
@@ -51,45 +91,13 @@ fn create_window(title: &str, size: [u32; 2]) -> Result<(Window, EventLoop<()>),
     Ok((window, event_loop))
 }
 
-fn init_logger() {
-    use std::env;
-    use std::io::Write;
-    env::set_var("RUST_BACKTRACE", "1");
-    let env = env_logger::Env::default()
-        .write_style_or("RUST_LOG_STYLE", "always")
-        .filter_or("RUST_LOG", "trace");
-
-    env_logger::Builder::from_env(env)
-        .target(env_logger::Target::Stderr)
-        .format(|buf, record| {
-            let mut style = buf.style();
-
-            match record.level() {
-                log::Level::Info => style.set_color(env_logger::fmt::Color::Green),
-                log::Level::Warn => style.set_color(env_logger::fmt::Color::Yellow),
-                log::Level::Error => style.set_color(env_logger::fmt::Color::Red),
-                _ => style.set_color(env_logger::fmt::Color::White),
-            };
-
-            let timestamp = buf.timestamp();
-
-            writeln!(
-                buf,
-                "{:<20} : {:<5} : {}",
-                timestamp,
-                style.value(record.level()),
-                record.args()
-            )
-        })
-        .init();
-    info!("Logger init for stderr");
-}
-
-/// Print error with source and exit.
-pub(crate) fn print_error_and_exit(err: Box<dyn std::error::Error>) -> ! {
-    error!("{}", err);
-    if let Some(src) = err.source() {
+/// Print error with code, source and what to do about it, then exit.
+pub(crate) fn print_error_and_exit(err: &Error) -> ! {
+    error!("[{}] {}", err.code(), err);
+    if let Some(src) = std::error::Error::source(err) {
         error!("Source: {}", src);
     }
+    error!("{}", err.user_message());
+    eprintln!("{}", err.report());
     std::process::exit(1);
 }
@@ -1,21 +1,56 @@
 use crate::{
+    autostart::{self, Registration},
+    config::Config,
+    config_diff::{diff_configs, merge_configs, ConfigDiff},
+    config_editor,
     error::Error,
-    graphics_backend::Graphics,
-    input::{input_viewer, Input},
+    graphics_backend::{
+        egui_color_test::paint_pixel_grid,
+        egui_renderer,
+        resize::{ResizeDebounce, ResizeStep},
+        Graphics,
+    },
+    input::{
+        button_labels::update_button_labels_dialogs,
+        device_panel,
+        device_transform::update_device_transform_window,
+        identifiers::identifiers_grid,
+        input_viewer,
+        midi_output::{self, MIDI_AVAILABLE},
+        stick_navigation::ESCAPE_HOLD_S,
+        test_panel::update_test_panel_window,
+        test_script, Input,
+    },
+    launch::LaunchOptions,
+    logging,
+    modal::Modal,
+    paths,
+    performance::{self, PerformanceStats},
     previous::Previous,
-    rebind::rebind_viewer,
-    ui_data::{ActiveTab, UIData},
+    rebind::{
+        batch_rename,
+        capabilities::find_vjoy_conf,
+        device_swap,
+        processing_budget::{default_budget_s, OVERRUN_TICKS},
+        quick_map, rebind_viewer,
+    },
+    setup_wizard::{self, SetupWizard},
+    split_view::SplitSide,
+    ui_data::{ActiveTab, Toast, UIData},
+    update_check::{update_changelog_window, UPDATE_CHECK_AVAILABLE},
+    view_state::ViewState,
+    WINDOW_TITLE,
 };
 use egui::{
-    output::OpenUrl, Align, CentralPanel, Context, FullOutput, ImageButton, Label, Layout,
-    RawInput, RichText, Visuals,
+    Align, Align2, Button, CentralPanel, Checkbox, CollapsingHeader, ComboBox, Context, DragValue,
+    Frame, FullOutput, Key, Label, Layout, Modifiers, RawInput, RichText, ScrollArea, Ui, Visuals,
 };
 use egui_file::FileDialog;
 use egui_winit::State;
-use log::{error, info};
+use log::{error, info, trace, warn};
 use ringbuffer::{RingBuffer, RingBufferExt, RingBufferWrite};
 use std::{
-    ops::Add,
+    path::{Path, PathBuf},
     time::{Duration, Instant},
 };
 use winit::{
@@ -24,6 +59,9 @@ use winit::{
     window::Window,
 };
 
+/// UI refresh interval while the window is unfocused and background mode is enabled
+const BACKGROUND_UI_INTERVAL: Duration = Duration::from_millis(100);
+
 pub struct Manager {
     start: Instant,
     last_frame: Instant,
@@ -33,19 +71,48 @@ pub struct Manager {
     ui_data: UIData,
     input: Input,
     previous: Previous,
+    window_title: String,
+    focused: bool,
+    last_render: Instant,
+    resize: ResizeDebounce,
 }
 
 impl Manager {
     #[profiling::function]
-    pub fn new(window: &Window, event_loop: &EventLoop<()>) -> Result<Self, Error> {
+    pub fn new(
+        window: &Window,
+        event_loop: &EventLoop<()>,
+        mut previous: Previous,
+        launch: LaunchOptions,
+    ) -> Result<Self, Error> {
         let start = Instant::now();
         let last_frame = Instant::now();
         let graphics = Graphics::new(window)?;
         let ctx = Context::default();
         let state = State::new(event_loop);
-        let ui_data = UIData::new(&ctx);
-        let previous = Previous::read_or_default();
-        let input = Input::new(&previous)?;
+        let mut ui_data = UIData::new(&ctx);
+        let mut input = Input::new(&previous, &launch)?;
+        if let Err(e) = input.set_shared_memory_enabled(previous.shared_memory) {
+            error!("Failed to create the shared memory block. Reason: {e}");
+        }
+        if let Err(e) = input.set_midi_port(previous.midi_port.as_deref()) {
+            warn!("Failed to open the MIDI feedback port. Reason: {e}");
+        }
+        input.set_performance_mode(previous.performance_mode);
+        if !previous.setup_wizard_done {
+            ui_data.setup_wizard = Some(SetupWizard::default());
+        }
+        ui_data.performance = PerformanceStats::new(start);
+        ui_data.visualization = previous.visualization.clone();
+        ui_data.axis_roles = previous.axis_roles.clone();
+        ui_data.output_range = previous.output_range.clone();
+        ui_data.split_view = previous.split_view.clone();
+        ui_data.safe_edit = previous.safe_edit;
+        ui_data.safe_mode = launch.safe_mode;
+        previous.view_state.restore(&mut ui_data, &input);
+        ui_data
+            .update_check
+            .start_if_due(&mut previous.update_check);
 
         Ok(Self {
             start,
@@ -56,22 +123,21 @@ impl Manager {
             ui_data,
             input,
             previous,
+            window_title: String::new(),
+            focused: true,
+            last_render: Instant::now(),
+            resize: ResizeDebounce::new(window.inner_size().into()),
         })
     }
 
     #[profiling::function]
     pub fn run(mut self, window: Window, event_loop: EventLoop<()>) -> ! {
         event_loop.run(move |new_event, _target, control_flow| {
-            if window.inner_size().width == 0 || window.inner_size().height == 0 {
-                *control_flow = ControlFlow::WaitUntil(
-                    Instant::now().add(Duration::from_secs_f64(crate::input::INPUT_POLL_INTERVAL)),
-                )
-            } else {
-                *control_flow = ControlFlow::Poll;
-            }
+            // The input's poll timer paces the loop, WaitUntil only wakes at the OS timer resolution
+            *control_flow = ControlFlow::Poll;
 
             let result = match new_event {
-                Event::LoopDestroyed => self.quit(),
+                Event::LoopDestroyed => self.quit(&window),
 
                 Event::NewEvents(_) => self.begin_new_frame(control_flow),
 
@@ -85,25 +151,49 @@ impl Manager {
             };
 
             if let Err(err) = result {
-                crate::print_error_and_exit(Box::new(err));
+                self.input.shutdown();
+                crate::print_error_and_exit(&err);
             }
         })
     }
 
     #[profiling::function]
-    fn quit(&mut self) -> Result<(), Error> {
+    fn quit(&mut self, window: &Window) -> Result<(), Error> {
+        self.previous.visualization = self.ui_data.visualization.clone();
+        self.previous.axis_roles = self.ui_data.axis_roles.clone();
+        self.previous.output_range = self.ui_data.output_range.clone();
+        self.previous.split_view = self.ui_data.split_view.clone();
+        self.previous.safe_edit = self.ui_data.safe_edit;
+        self.previous.performance_mode = self.input.is_performance_mode();
+        self.previous.view_state = ViewState::capture(&self.ui_data, self.input.get_config_path());
+        let size = window.inner_size();
+        if size.width > 0 && size.height > 0 {
+            self.previous.window_size = Some([size.width, size.height]);
+        }
         self.previous.write()?;
+        self.input.shutdown();
         self.graphics.destroy()?;
         info!("Shutdown");
         std::process::exit(0);
     }
 
+    /// Unfocused window: keep polling input at the poll interval but only refresh the UI at [`BACKGROUND_UI_INTERVAL`]
+    fn in_background_mode(&self) -> bool {
+        !self.focused && self.ui_data.background_mode_enabled
+    }
+
     #[profiling::function]
     fn begin_new_frame(&mut self, control_flow: &mut ControlFlow) -> Result<(), Error> {
         profiling::finish_frame!();
-        self.ui_data
-            .frame_s_buffer
-            .push(Some(self.last_frame.elapsed().as_secs_f64()));
+        if self.ui_data.should_close {
+            *control_flow = ControlFlow::Exit;
+        }
+        if self.in_background_mode() {
+            return Ok(());
+        }
+        let frame_s = self.last_frame.elapsed().as_secs_f64();
+        self.ui_data.frame_s_buffer.push(Some(frame_s));
+        self.ui_data.performance.record_frame(frame_s);
         let count = self.ui_data.frame_s_buffer.len() as f64;
         self.ui_data.frame_s = self
             .ui_data
@@ -112,9 +202,6 @@ impl Manager {
             .fold(0.0, |acc, &v| acc + v.unwrap_or(0.0))
             / count;
         self.last_frame = Instant::now();
-        if self.ui_data.should_close {
-            *control_flow = ControlFlow::Exit;
-        }
         Ok(())
     }
 
@@ -130,11 +217,15 @@ impl Manager {
         }
 
         match event {
-            WindowEvent::Resized(new_size) => {
-                //Ignore invalid resize events during startup
-                if new_size == window.inner_size() && new_size.height > 0 && new_size.width > 0 {
-                    self.graphics.on_resize(window, new_size.into())?;
-                }
+            WindowEvent::Resized(_) => {
+                // Startup can report stale sizes, the window's current size is always up to date,
+                // including right after maximizing
+                self.resize
+                    .request(window.inner_size().into(), Instant::now());
+            }
+
+            WindowEvent::Focused(focused) => {
+                self.focused = focused;
             }
 
             WindowEvent::CloseRequested => {
@@ -148,6 +239,11 @@ impl Manager {
                             self.ui_data.active_tab = ActiveTab::InputViewerRebind;
                         }
 
+                        (VirtualKeyCode::F2, ElementState::Pressed) => {
+                            let enabled = !self.input.is_performance_mode();
+                            self.input.set_performance_mode(enabled);
+                        }
+
                         _ => (),
                     }
                 }
@@ -161,16 +257,66 @@ impl Manager {
 
     #[profiling::function]
     fn update(&mut self, window: &Window) -> Result<(), Error> {
+        self.input.wait_for_tick();
         self.input.update(self.start.elapsed().as_secs_f64())?;
 
-        if window.inner_size().height == 0 || window.inner_size().height == 0 {
+        for warning in self.input.take_power_warnings() {
+            let time = self.ctx.input(|i| i.time);
+            self.ui_data.toast = Some(Toast::warning(warning, time));
+        }
+
+        // Rebinds writing to a device that was just enabled or disabled change their issues
+        if self.input.take_virtual_devices_changed() {
+            self.ui_data.check_capabilities = true;
+        }
+
+        if self.ui_data.check_capabilities {
+            self.ui_data.capability_issues = self.input.check_virtual_capabilities();
+            self.ui_data.check_capabilities = false;
+            for issue in self.ui_data.capability_issues.iter() {
+                warn!("{issue}");
+            }
+        }
+
+        if window.inner_size().width == 0 || window.inner_size().height == 0 {
             return Ok(());
         }
 
-        let raw_input = {
+        match self.resize.poll(Instant::now()) {
+            ResizeStep::Idle => {}
+            ResizeStep::Wait => return Ok(()),
+            ResizeStep::Recreate(size) => {
+                self.graphics.on_resize(window, size)?;
+                self.ui_data.performance.mark("Swapchain recreated");
+            }
+        }
+
+        if self.in_background_mode() && self.last_render.elapsed() < BACKGROUND_UI_INTERVAL {
+            return Ok(());
+        }
+        self.last_render = Instant::now();
+        self.ui_data.background_mode = self.in_background_mode();
+
+        let time = self.ctx.input(|i| i.time);
+        let escape_down = self.ctx.input(|i| i.key_down(Key::Escape));
+        let navigation = &mut self.previous.stick_navigation;
+        if self.ui_data.stick_navigation.escape_held(escape_down, time) && navigation.enabled {
+            navigation.enabled = false;
+            self.ui_data.toast = Some(Toast::new("Stick navigation disabled".to_string(), time));
+        }
+
+        let mut raw_input = {
             profiling::scope!("egui_winit::State::take_egui_input");
             self.state.take_egui_input(window)
         };
+        // Key events only reach the focused window, the stick must not navigate a background UI
+        if self.focused {
+            let events = self
+                .ui_data
+                .stick_navigation
+                .poll(navigation, self.input.physical_devices());
+            raw_input.events.extend(events);
+        }
 
         let full_output = Self::build_ui(
             &mut self.previous,
@@ -180,6 +326,12 @@ impl Manager {
             &mut self.ui_data,
         );
 
+        let window_title = format!("{WINDOW_TITLE} — {}", self.input.get_config_name());
+        if window_title != self.window_title {
+            window.set_title(&window_title);
+            self.window_title = window_title;
+        }
+
         {
             profiling::scope!("egui_winit::State::handle_platform_output");
             self.state
@@ -191,14 +343,15 @@ impl Manager {
             self.ctx.tessellate(full_output.shapes)
         };
 
-        let window_size = [
-            window.inner_size().width as f32,
-            window.inner_size().height as f32,
-        ];
-
-        let ui_to_ndc = nalgebra_glm::ortho(0.0, window_size[0], 0.0, window_size[1], -1.0, 1.0);
-        self.graphics
-            .update(full_output.textures_delta, clipped_primitives, ui_to_ndc)?;
+        let window_size = [window.inner_size().width, window.inner_size().height];
+        let pixels_per_point = self.ctx.pixels_per_point();
+        let ui_to_ndc = egui_renderer::ui_to_ndc(window_size, pixels_per_point);
+        self.graphics.update(
+            full_output.textures_delta,
+            clipped_primitives,
+            ui_to_ndc,
+            pixels_per_point,
+        )?;
         Ok(())
     }
 
@@ -210,6 +363,7 @@ impl Manager {
         ui_data: &mut UIData,
     ) -> FullOutput {
         ctx.run(raw_input, |ctx| {
+            ui_data.update_check.poll(&mut previous.update_check);
             egui::TopBottomPanel::top("top bar").show(ctx, |ui| {
                 egui::menu::bar(ui, |ui| {
                     let mut style = (*ctx.style()).clone();
@@ -231,17 +385,164 @@ impl Manager {
                             }
                             ui.close_menu();
                         }
-                        if ui.button("Save config").clicked() {
+                        ui.menu_button("Recent configs", |ui| {
+                            previous.prune_recent_cfg_paths();
+                            if previous.recent_cfg_paths.is_empty() {
+                                ui.label("none");
+                            }
+                            for path in previous.recent_cfg_paths.clone() {
+                                if ui.button(&path).clicked() {
+                                    load_config(Path::new(&path), previous, input, ui_data);
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                        let config_path = input.get_config_path().map(Path::to_path_buf);
+                        if ui
+                            .add_enabled(config_path.is_some(), Button::new("Save config"))
+                            .clicked()
+                        {
+                            if let Some(path) = config_path.as_deref() {
+                                save_config(path, false, previous, input, ui_data);
+                            }
+                            ui.close_menu();
+                        }
+                        if ui.button("Save config as…").clicked() {
                             if let Err(e) = open_save_dialog(ui_data) {
                                 error!("{e}");
                             }
                             ui.close_menu();
                         }
+                        let config_path_text = match &config_path {
+                            Some(path) => path.display().to_string(),
+                            None => "built-in config".to_string(),
+                        };
+                        ui.add_enabled(false, Button::new(config_path_text));
+                        ui.separator();
                         #[cfg(debug_assertions)]
                         if ui.button("Color test").clicked() {
                             ui_data.active_tab = ActiveTab::ColorTest;
                             ui.close_menu();
                         }
+                        #[cfg(debug_assertions)]
+                        ui.checkbox(&mut ui_data.pixel_grid, "Pixel grid overlay");
+                        ui.checkbox(
+                            &mut ui_data.background_mode_enabled,
+                            "Background mode when unfocused",
+                        );
+                        if ui
+                            .checkbox(&mut ui_data.show_performance_tab, "Performance tab")
+                            .on_hover_text("Frame and input processing time graphs")
+                            .changed()
+                            && !ui_data.show_performance_tab
+                            && ui_data.active_tab == ActiveTab::Performance
+                        {
+                            ui_data.active_tab = ActiveTab::InputViewerRebind;
+                        }
+                        let mut performance_mode = input.is_performance_mode();
+                        if ui
+                            .checkbox(&mut performance_mode, "Performance mode (F2)")
+                            .on_hover_text(
+                                "Only poll, process and output, without plots and viewers",
+                            )
+                            .changed()
+                        {
+                            input.set_performance_mode(performance_mode);
+                        }
+                        ui.checkbox(&mut input.event_console_mut().open, "Input event console")
+                            .on_hover_text(
+                                "Every change of the physical inputs, e.g. to find ghost presses",
+                            );
+                        ui.menu_button("Visualization", |ui| {
+                            ui_data.visualization.settings_ui(ui);
+                        });
+                        ui.menu_button("Axis roles", |ui| {
+                            ui_data.axis_roles.settings_ui(ui);
+                        });
+                        ui.menu_button("Output range warnings", |ui| {
+                            ui_data.output_range.settings_ui(ui);
+                        });
+                        let mut shared_memory = previous.shared_memory;
+                        if ui
+                            .checkbox(&mut shared_memory, "Publish outputs to shared memory")
+                            .on_hover_text(
+                                "For external programs that read the virtual devices without vJoy",
+                            )
+                            .changed()
+                        {
+                            match input.set_shared_memory_enabled(shared_memory) {
+                                Ok(()) => previous.shared_memory = shared_memory,
+                                Err(e) => {
+                                    error!("Failed to create the shared memory block. Reason: {e}");
+                                    let time = ui.input(|i| i.time);
+                                    ui_data.toast = Some(Toast::error(&e, time));
+                                }
+                            }
+                        }
+                        ui.menu_button("MIDI feedback", |ui| {
+                            midi_feedback_menu(
+                                ui,
+                                previous,
+                                input,
+                                &mut ui_data.midi_ports,
+                                &mut ui_data.toast,
+                            );
+                        });
+                        ui.menu_button("Processing budget", |ui| {
+                            processing_budget_menu(ui, previous, input);
+                        });
+                        ui.menu_button("Stick navigation", |ui| {
+                            stick_navigation_menu(ui, previous, input);
+                        });
+                        ui.menu_button("File logging", |ui| {
+                            let settings = &mut previous.file_log;
+                            ui.checkbox(&mut settings.enabled, "Write log files");
+                            ComboBox::from_label("Level")
+                                .selected_text(&settings.level)
+                                .show_ui(ui, |ui| {
+                                    for level in ["error", "warn", "info", "debug", "trace"] {
+                                        ui.selectable_value(
+                                            &mut settings.level,
+                                            level.to_string(),
+                                            level,
+                                        );
+                                    }
+                                });
+                            ui.label("Applies on the next start");
+                            if ui.button("Open log folder").clicked() {
+                                logging::open_log_dir();
+                                ui.close_menu();
+                            }
+                        });
+                        ui.menu_button("Start with Windows", |ui| {
+                            autostart_menu(ui, input);
+                        });
+                        let update_check =
+                            Checkbox::new(&mut previous.update_check.enabled, "Check for updates");
+                        if ui
+                            .add_enabled(UPDATE_CHECK_AVAILABLE, update_check)
+                            .on_hover_text(
+                                "Asks GitHub for the latest release at most once per day",
+                            )
+                            .on_disabled_hover_text("This build has no update check")
+                            .changed()
+                        {
+                            ui_data
+                                .update_check
+                                .start_if_due(&mut previous.update_check);
+                        }
+                        if ui.button("Setup wizard").clicked() {
+                            ui_data.setup_wizard = Some(SetupWizard::default());
+                            ui.close_menu();
+                        }
+                        if ui.button("About").clicked() {
+                            ui_data.show_about = true;
+                            ui.close_menu();
+                        }
+                        if ui.button("Changelog").clicked() {
+                            ui_data.show_changelog = true;
+                            ui.close_menu();
+                        }
                         if ui.button("Exit application").clicked() {
                             ui_data.should_close = true;
                             ui.close_menu();
@@ -253,6 +554,15 @@ impl Manager {
                     if ui.button("Input viewer | Rebind").clicked() {
                         ui_data.active_tab = ActiveTab::InputViewerRebind;
                     }
+                    if ui.button("Config editor").clicked()
+                        && ui_data.active_tab != ActiveTab::ConfigEditor
+                    {
+                        ui_data.config_editor.enter(input);
+                        ui_data.active_tab = ActiveTab::ConfigEditor;
+                    }
+                    if ui_data.show_performance_tab && ui.button("Performance").clicked() {
+                        ui_data.active_tab = ActiveTab::Performance;
+                    }
 
                     ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
                         let fps = Label::new(
@@ -267,158 +577,811 @@ impl Manager {
                             ),
                         );
 
+                        if let Some(release) = previous.update_check.available_update() {
+                            ui.hyperlink_to(
+                                RichText::new(format!("⬆ {} available", release.version))
+                                    .color(ui.visuals().warn_fg_color),
+                                &release.url,
+                            )
+                            .on_hover_text("Open the release page");
+                            ui.separator();
+                        }
                         ui.add(fps);
                         ui.separator();
                         ui.add(ms);
-                    });
-                })
-            });
-
-            egui::SidePanel::left("devices")
-                .default_width(100.0)
-                .show(ctx, |ui| {
-                    ui.horizontal(|ui| {
-                        ui.label(format!(
-                            "Physical devices: {}",
-                            input.physical_devices_count()
-                        ));
-                    });
-
-                    ui.separator();
+                        ui.separator();
 
-                    ui.vertical(|ui| {
-                        for (index, device) in input.physical_devices_mut().enumerate() {
-                            let name = device.name();
-                            ui.toggle_value(&mut device.selected, format!("{}: {}", index, name));
+                        let (poll_interval, poll_jitter) = input.get_poll_timing();
+                        ui.label(
+                            RichText::new(format!(
+                                "poll {:4.2} ms ± {:4.2} ms",
+                                poll_interval * 1000.0,
+                                poll_jitter * 1000.0
+                            ))
+                            .color(ui.style().noninteractive().text_color().gamma_multiply(0.5)),
+                        );
+                        if ui_data.background_mode {
+                            ui.separator();
+                            ui.label(RichText::new("background mode").strong());
                         }
-                    });
-
-                    ui.add_space(10.0);
-
-                    ui.vertical(|ui| {
-                        ui.horizontal(|ui| {
-                            ui.label("Virtual devices:");
-                            ui.label(input.virtual_devices_count().to_string());
-                        });
-                        ui.horizontal(|ui| {
-                            ui.label("Active mode:");
-                            ui.label(input.get_active_shift_mode().to_string());
-                        });
-                    });
-
-                    ui.separator();
-
-                    ui.vertical(|ui| {
-                        for (index, device) in input.virtual_devices_mut().enumerate() {
-                            let name = device.name();
-                            ui.toggle_value(&mut device.selected, format!("{}: {}", index, name));
+                        if input.is_performance_mode() {
+                            ui.separator();
+                            ui.label(RichText::new("performance mode").strong());
                         }
                     });
+                })
+            });
 
-                    let spacing = ui.available_height() - 50.0;
+            if ui_data.safe_mode {
+                update_safe_mode_banner(ctx, input, ui_data);
+            }
 
-                    ui.add_space(spacing);
+            if !input.is_performance_mode() {
+                egui::SidePanel::left("devices")
+                    .default_width(100.0)
+                    .show(ctx, |ui| {
+                        device_panel::build_ui(input, ui, ui_data);
+                    });
+            }
 
-                    ui.horizontal(|ui| {
-                        if ui
-                            .add(
-                                ImageButton::new(
-                                    ui_data.ferris.id(),
-                                    [50.0 * ui_data.ferris.aspect_ratio(), 50.0],
-                                )
-                                .frame(false),
-                            )
-                            .clicked()
-                        {
-                            ui.ctx().output_mut(|o| {
-                                o.open_url = Some(OpenUrl {
-                                    url: "https://github.com/ArrowMaxGithub/Rust-vJoy-Manager"
-                                        .to_string(),
-                                    new_tab: true,
-                                });
-                            });
-                        }
-                        ui.label("Click to \nopen repo");
-                    })
-                });
+            update_button_labels_dialogs(ctx, input, ui_data);
+            update_device_transform_window(ctx, previous, input, ui_data);
+            update_about_window(ctx, input, ui_data);
+            update_changelog_window(ctx, &mut ui_data.show_changelog);
+            update_capabilities_window(ctx, ui_data);
+            update_processing_overrun_window(ctx, input);
+            setup_wizard::update_window(ctx, previous, input, ui_data);
+            quick_map::update_window(ctx, input, ui_data);
+            batch_rename::update_window(ctx, input, ui_data);
+            device_swap::update_window(ctx, input, ui_data);
+            test_script::update_window(ctx, input, ui_data);
+            update_test_panel_window(ctx, input, ui_data);
+            update_toast(ctx, ui_data);
+            update_output_failures_window(ctx, input);
+            input.event_console_mut().update_window(ctx);
+            update_modals(previous, ctx, input, ui_data);
 
-            update_load_dialog(previous, ctx, input, ui_data).unwrap();
-            update_save_dialog(ctx, input, ui_data).unwrap();
+            if ui_data.pixel_grid {
+                paint_pixel_grid(ctx);
+            }
 
             match ui_data.active_tab {
                 #[cfg(debug_assertions)]
                 ActiveTab::ColorTest => ui_data.color_test.build_ui(input, ctx),
+                ActiveTab::InputViewerRebind if input.is_performance_mode() => {
+                    CentralPanel::default().show(ctx, |ui| {
+                        let tick_times = input.tick_time_comparison();
+                        if performance::performance_mode_ui(ui, tick_times) {
+                            input.set_performance_mode(false);
+                        }
+                    });
+                }
                 ActiveTab::InputViewerRebind => {
+                    let mut split_view = ui_data.split_view.clone();
+                    split_view.handle_shortcuts(ctx);
                     CentralPanel::default().show(ctx, |ui| {
-                        ui.with_layout(Layout::left_to_right(Align::TOP), |ui| {
-                            rebind_viewer::build_ui(input, ui, ui_data);
-                            input_viewer::build_ui(input, ui, ui_data);
+                        split_view.show(ui, |ui, side| match side {
+                            SplitSide::Rebinds => rebind_viewer::build_ui(input, ui, ui_data),
+                            SplitSide::Inputs => input_viewer::build_ui(input, ui, ui_data),
                         });
                     });
+                    ui_data.split_view = split_view;
+                }
+                ActiveTab::Performance => {
+                    CentralPanel::default().show(ctx, |ui| {
+                        performance::build_ui(ui, &ui_data.performance, input.processing_times());
+                    });
+                }
+                ActiveTab::ConfigEditor => {
+                    CentralPanel::default().show(ctx, |ui| {
+                        if config_editor::build_ui(&mut ui_data.config_editor, input, ui) {
+                            ui_data.check_capabilities = true;
+                        }
+                    });
                 }
             }
         })
     }
 }
 
+/// The config dir, so saved configs end up next to the app settings by default
+fn dialog_dir() -> Option<PathBuf> {
+    paths::config_dir()
+        .map_err(|e| warn!("Failed to locate the config dir. Reason: {e}"))
+        .ok()
+}
+
 fn open_load_dialog(ui_data: &mut UIData) -> Result<(), Error> {
-    let mut dialog = FileDialog::open_file(None).filter(Box::new(|path| match path.extension() {
-        Some(os_ext) => os_ext.eq("toml"),
-        None => false,
-    }));
+    let mut dialog =
+        FileDialog::open_file(dialog_dir()).filter(Box::new(|path| match path.extension() {
+            Some(os_ext) => os_ext.eq("toml"),
+            None => false,
+        }));
     dialog.open();
-    ui_data.load_file_dialog = Some(dialog);
+    ui_data.modals.push(Modal::LoadConfig(dialog));
 
     Ok(())
 }
 
-fn update_load_dialog(
+/// Asks before discarding unsaved changes that differ from the config at `path`
+fn load_config(path: &Path, previous: &mut Previous, input: &mut Input, ui_data: &mut UIData) {
+    if input.has_unsaved_changes() {
+        // A file that fails to load is reported by the load itself
+        if let Ok(loaded) = Config::read_from_path(path) {
+            let diff = diff_configs(input.get_config(), &loaded);
+            if !diff.is_empty() {
+                ui_data.modals.push(Modal::LoadDiff {
+                    path: path.to_path_buf(),
+                    loaded: Box::new(loaded),
+                    diff,
+                });
+                return;
+            }
+        }
+    }
+    discard_and_load_config(path, previous, input, ui_data);
+}
+
+/// Returns true if the config was loaded
+fn discard_and_load_config(
+    path: &Path,
     previous: &mut Previous,
-    ctx: &Context,
     input: &mut Input,
     ui_data: &mut UIData,
-) -> Result<(), Error> {
-    if let Some(dialog) = &mut ui_data.load_file_dialog {
-        if dialog.show(ctx).selected() {
-            if let Some(path) = dialog.path() {
-                match input.load_rebinds(&path) {
-                    Err(e) => error!("Failed to load rebinds from {:?}. Reason: {}", path, e),
-                    Ok(_) => {
-                        info!("Sucessfully loaded config from {:?}", path);
-                        previous.load_cfg_path = Some(path.to_str().unwrap().to_owned());
-                    }
-                }
+) -> bool {
+    match input.load_rebinds(path) {
+        Err(e) => {
+            ui_data.modals.report_load_error(path, e);
+            false
+        }
+        Ok(_) => {
+            info!("Sucessfully loaded config from {:?}", path);
+            ui_data.performance.mark("Config loaded");
+            ui_data.check_capabilities = true;
+            previous.set_current_cfg_path(path);
+            write_previous(previous);
+            true
+        }
+    }
+}
+
+fn save_config(
+    path: &Path,
+    overwrite: bool,
+    previous: &mut Previous,
+    input: &mut Input,
+    ui_data: &mut UIData,
+) {
+    match input.save_rebinds(path, overwrite) {
+        Err(Error::ConfigModifiedOnDisk(path)) => {
+            warn!(
+                "Config {:?} was modified on disk, asking before overwriting",
+                path
+            );
+            ui_data.modals.push(Modal::SaveConflict(path));
+        }
+        Err(e) => ui_data
+            .modals
+            .report_save_error(&format!("rebinds to {:?}", path), e),
+        Ok(_) => {
+            info!("Sucessfully saved config to {:?}", path);
+            previous.set_current_cfg_path(path);
+            write_previous(previous);
+            if let Err(e) = input.save_global_rebinds() {
+                ui_data.modals.report_save_error("global rebinds", e);
             }
-            ui_data.load_file_dialog = None;
         }
     }
+}
 
-    Ok(())
+/// Persists the session right away, so a crash does not lose the config to load on the next start
+fn write_previous(previous: &Previous) {
+    if let Err(e) = previous.write() {
+        error!("Failed to save the previous session. Reason: {}", e);
+    }
 }
 
 fn open_save_dialog(ui_data: &mut UIData) -> Result<(), Error> {
-    let mut dialog = FileDialog::save_file(None).filter(Box::new(|path| match path.extension() {
-        Some(os_ext) => os_ext.eq("toml"),
-        None => false,
-    }));
+    let mut dialog =
+        FileDialog::save_file(dialog_dir()).filter(Box::new(|path| match path.extension() {
+            Some(os_ext) => os_ext.eq("toml"),
+            None => false,
+        }));
     dialog.open();
-    ui_data.save_file_dialog = Some(dialog);
+    ui_data.modals.push(Modal::SaveConfig(dialog));
 
     Ok(())
 }
 
-fn update_save_dialog(ctx: &Context, input: &mut Input, ui_data: &mut UIData) -> Result<(), Error> {
-    if let Some(dialog) = &mut ui_data.save_file_dialog {
-        if dialog.show(ctx).selected() {
-            if let Some(path) = dialog.path() {
-                match input.save_rebinds(&path) {
-                    Err(e) => error!("Failed to save rebinds to {:?}. Reason: {}", path, e),
-                    Ok(_) => info!("Sucessfully saved config to {:?}", path),
+/// Renders all open modals. Escape closes only the topmost one and is consumed before the file
+/// dialogs see it, so the ones below stay open.
+fn update_modals(previous: &mut Previous, ctx: &Context, input: &mut Input, ui_data: &mut UIData) {
+    if !ui_data.modals.is_empty() && ctx.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Escape))
+    {
+        if let Some(modal) = ui_data.modals.close_top() {
+            trace!("closed modal '{}' with Escape", modal.name());
+        }
+    }
+
+    let mut kept = Vec::new();
+    for mut modal in ui_data.modals.take() {
+        let keep = match &mut modal {
+            Modal::LoadConfig(dialog) => {
+                let selected = dialog.show(ctx).selected();
+                if selected {
+                    if let Some(path) = dialog.path() {
+                        load_config(&path, previous, input, ui_data);
+                    }
                 }
+                !selected && dialog.visible()
+            }
+            Modal::SaveConfig(dialog) => {
+                let selected = dialog.show(ctx).selected();
+                if selected {
+                    if let Some(path) = dialog.path() {
+                        save_config(&path, false, previous, input, ui_data);
+                    }
+                }
+                !selected && dialog.visible()
+            }
+            Modal::SaveConflict(path) => {
+                save_conflict_window(path.clone(), previous, ctx, input, ui_data)
+            }
+            Modal::LoadDiff { path, loaded, diff } => {
+                load_diff_window(path, loaded, diff, previous, ctx, input, ui_data)
             }
-            ui_data.save_file_dialog = None;
+            Modal::WriteFailed { message, .. } => write_failed_window(message, ctx, ui_data),
+            Modal::LoadFailed { path, message } => load_failed_window(path, message, ctx),
+        };
+        if keep {
+            kept.push(modal);
         }
     }
+    ui_data.modals.restore(kept);
+}
 
-    Ok(())
+/// Returns false once dismissed
+fn write_failed_window(message: &str, ctx: &Context, ui_data: &mut UIData) -> bool {
+    let mut open = true;
+    let mut close = false;
+    egui::Window::new("Save failed")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(RichText::new(message).color(ui.visuals().warn_fg_color));
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                if ui.button("Save as…").clicked() {
+                    close = true;
+                    if let Err(e) = open_save_dialog(ui_data) {
+                        error!("{e}");
+                    }
+                }
+                if ui.button("Close").clicked() {
+                    close = true;
+                }
+            });
+        });
+
+    open && !close
+}
+
+/// Returns false once dismissed
+fn load_failed_window(path: &Path, message: &str, ctx: &Context) -> bool {
+    let mut open = true;
+    let mut close = false;
+    egui::Window::new("Load failed")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!("Could not load {}.", path.display()));
+            ui.label(RichText::new(message).color(ui.visuals().warn_fg_color));
+            ui.add_space(10.0);
+            if ui.button("Close").clicked() {
+                close = true;
+            }
+        });
+
+    open && !close
+}
+
+/// Returns false once the conflict is resolved or dismissed
+fn save_conflict_window(
+    path: PathBuf,
+    previous: &mut Previous,
+    ctx: &Context,
+    input: &mut Input,
+    ui_data: &mut UIData,
+) -> bool {
+    let mut open = true;
+    let mut close = false;
+    egui::Window::new("Config changed on disk")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "{} was modified outside of the application since it was last loaded or saved.",
+                path.display()
+            ));
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                if ui.button("Overwrite").clicked() {
+                    close = true;
+                    save_config(&path, true, previous, input, ui_data);
+                }
+                if ui
+                    .button("Load from disk")
+                    .on_hover_text("Discards the unsaved changes made in the application")
+                    .clicked()
+                {
+                    close = true;
+                    discard_and_load_config(&path, previous, input, ui_data);
+                }
+                if ui.button("Save as…").clicked() {
+                    close = true;
+                    if let Err(e) = open_save_dialog(ui_data) {
+                        error!("{e}");
+                    }
+                }
+            });
+        });
+
+    open && !close
+}
+
+/// Returns false once the load is confirmed, merged or dismissed
+fn load_diff_window(
+    path: &Path,
+    loaded: &Config,
+    diff: &ConfigDiff,
+    previous: &mut Previous,
+    ctx: &Context,
+    input: &mut Input,
+    ui_data: &mut UIData,
+) -> bool {
+    let mut open = true;
+    let mut close = false;
+    egui::Window::new("Unsaved changes")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let live = input.get_config();
+            ui.label(format!(
+                "Loading {} discards the unsaved changes to '{}':",
+                path.display(),
+                live.name
+            ));
+            ui.add_space(10.0);
+            ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                let warn = ui.visuals().warn_fg_color;
+                for rebind in diff.removed.iter() {
+                    ui.label(
+                        RichText::new(format!("− {} (only in the app)", rebind.name)).color(warn),
+                    );
+                }
+                for index in diff.added.iter() {
+                    ui.label(format!(
+                        "+ {} (only in the file)",
+                        loaded.rebinds[*index].name
+                    ));
+                }
+                for index in diff.changed.iter() {
+                    ui.label(format!(
+                        "~ {} (settings differ)",
+                        loaded.rebinds[*index].name
+                    ));
+                }
+                for (live_name, index) in diff.renamed.iter() {
+                    ui.label(format!(
+                        "~ {} renamed to {}",
+                        live_name, loaded.rebinds[*index].name
+                    ));
+                }
+                if diff.reordered {
+                    ui.label("~ rebind order differs");
+                }
+                if diff.settings_changed {
+                    ui.label("~ settings besides the rebinds differ");
+                }
+            });
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                if ui.button("Load").clicked() {
+                    close = true;
+                    discard_and_load_config(path, previous, input, ui_data);
+                }
+                if ui
+                    .add_enabled(!diff.removed.is_empty(), Button::new("Merge"))
+                    .on_hover_text("Loads the file and keeps the rebinds only the app has")
+                    .clicked()
+                {
+                    close = true;
+                    let merged = merge_configs(loaded, diff);
+                    // Loaded first, so the merged rebinds count as unsaved changes to the file
+                    if discard_and_load_config(path, previous, input, ui_data) {
+                        input.replace_config(merged);
+                    }
+                }
+                if ui.button("Cancel").clicked() {
+                    close = true;
+                }
+            });
+        });
+
+    open && !close
+}
+
+fn about_text(input: &Input) -> String {
+    let config_path = match input.get_config_path() {
+        Some(path) => path.display().to_string(),
+        None => "built-in".to_string(),
+    };
+
+    format!(
+        "Rust vJoy Manager {}\n\
+        Git hash: {}\n\
+        Build date: {}\n\
+        vJoy driver: {}\n\
+        vjoy crate: {}\n\
+        SDL: {}\n\
+        Config: {}\n\
+        Config path: {}",
+        env!("CARGO_PKG_VERSION"),
+        env!("BUILD_GIT_HASH"),
+        env!("BUILD_DATE"),
+        input.get_driver_version().unwrap_or("unknown"),
+        env!("BUILD_VJOY_CRATE_VERSION"),
+        sdl2::version::version(),
+        input.get_config_name(),
+        config_path,
+    )
+}
+
+fn update_about_window(ctx: &Context, input: &Input, ui_data: &mut UIData) {
+    let mut open = ui_data.show_about;
+    egui::Window::new("About")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let text = about_text(input);
+            ui.label(RichText::new(&text).monospace());
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                if ui.button("Copy to clipboard").clicked() {
+                    ui.output_mut(|o| o.copied_text = format!("```\n{text}\n```"));
+                }
+                if ui.button("Open log folder").clicked() {
+                    logging::open_log_dir();
+                }
+            });
+            ui.add_space(10.0);
+            CollapsingHeader::new("Device identifiers").show(ui, |ui| {
+                identifiers_grid(ui, input, &mut ui_data.toast);
+            });
+        });
+    ui_data.show_about = open;
+}
+
+fn processing_budget_menu(ui: &mut Ui, previous: &mut Previous, input: &mut Input) {
+    let budget = input.get_processing_budget_mut();
+    let mut budget_ms = budget.budget_s * 1000.0;
+    ui.horizontal(|ui| {
+        ui.label("Budget per tick:");
+        if ui
+            .add(
+                DragValue::new(&mut budget_ms)
+                    .speed(0.01)
+                    .clamp_range(0.01..=100.0)
+                    .suffix(" ms"),
+            )
+            .changed()
+        {
+            previous.processing_budget_ms = Some(budget_ms);
+        }
+        if ui.button("Default").clicked() {
+            previous.processing_budget_ms = None;
+            budget_ms = default_budget_s() * 1000.0;
+        }
+    });
+    budget.budget_s = budget_ms / 1000.0;
+    ui.checkbox(&mut budget.per_rebind_timing, "Per-rebind timing")
+        .on_hover_text("Times every rebind to name the most expensive ones on an overrun");
+    ui.add_enabled(
+        !budget.flagged().is_empty(),
+        Checkbox::new(&mut budget.safe_mode, "Safe mode"),
+    )
+    .on_hover_text("Disables the rebinds flagged by the last overrun");
+
+    ui.horizontal(|ui| {
+        let mut limited = budget.rewrite_limit.is_some();
+        let mut limit = budget.rewrite_limit.unwrap_or(4);
+        ui.checkbox(&mut limited, "Limit writes per control:")
+            .on_hover_text("Skips further rebinds writing a virtual control that was already written this many times in a tick");
+        ui.add_enabled(limited, DragValue::new(&mut limit).clamp_range(1..=64));
+        let rewrite_limit = limited.then_some(limit);
+        if rewrite_limit != budget.rewrite_limit {
+            budget.rewrite_limit = rewrite_limit;
+            previous.rewrite_limit = rewrite_limit;
+        }
+    });
+}
+
+/// Reads the registration every frame the menu is open, so changes made outside the app show up
+fn autostart_menu(ui: &mut Ui, input: &Input) {
+    let config = input.get_config_path();
+    let registration = match autostart::status(config) {
+        Ok(registration) => registration,
+        Err(e) => {
+            ui.label(format!("Failed to read the registration. Reason: {e}"));
+            return;
+        }
+    };
+    let result = match registration {
+        Registration::Unsupported => {
+            ui.label("Only available on Windows");
+            return;
+        }
+        Registration::NotRegistered | Registration::Registered => {
+            let mut enabled = registration == Registration::Registered;
+            if !ui
+                .checkbox(&mut enabled, "Start with Windows")
+                .on_hover_text("Starts minimized with the current config after logging in")
+                .changed()
+            {
+                return;
+            }
+            match enabled {
+                true => autostart::register(config),
+                false => autostart::unregister(),
+            }
+        }
+        Registration::Stale { command, exe_moved } => {
+            let reason = match exe_moved {
+                true => "Registered for an executable that was moved",
+                false => "Registered with another config",
+            };
+            ui.label(RichText::new(reason).color(ui.visuals().warn_fg_color))
+                .on_hover_text(command);
+            let fix = ui
+                .button("Fix")
+                .on_hover_text("Register this executable with the current config");
+            let remove = ui.button("Remove");
+            match (fix.clicked(), remove.clicked()) {
+                (true, _) => autostart::register(config),
+                (_, true) => autostart::unregister(),
+                _ => return,
+            }
+        }
+    };
+    if let Err(e) = result {
+        error!("Failed to change the start with Windows registration. Reason: {e}");
+    }
+}
+
+fn midi_feedback_menu(
+    ui: &mut Ui,
+    previous: &mut Previous,
+    input: &mut Input,
+    ports: &mut Option<Vec<String>>,
+    toast: &mut Option<Toast>,
+) {
+    if !MIDI_AVAILABLE {
+        ui.label("This build has no MIDI support");
+        return;
+    }
+
+    let ports = ports.get_or_insert_with(midi_output::output_ports);
+    let mut selected = input.midi_port().map(str::to_string);
+    ComboBox::from_label("Port")
+        .selected_text(selected.as_deref().unwrap_or("none"))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut selected, None, "none");
+            for port in ports.iter() {
+                ui.selectable_value(&mut selected, Some(port.clone()), port);
+            }
+        });
+    if selected.as_deref() != input.midi_port() {
+        match input.set_midi_port(selected.as_deref()) {
+            Ok(()) => previous.midi_port = selected,
+            Err(e) => {
+                error!("Failed to open the MIDI feedback port. Reason: {e}");
+                *toast = Some(Toast::error(&e, ui.input(|i| i.time)));
+            }
+        }
+    }
+    if ports.is_empty() {
+        ui.label("No MIDI output ports found");
+    }
+
+    ui.horizontal(|ui| {
+        if ui.button("Refresh").clicked() {
+            *ports = midi_output::output_ports();
+        }
+        if ui
+            .add_enabled(input.midi_port().is_some(), Button::new("Test"))
+            .on_hover_text("Sends every feedback entry as active for a second")
+            .clicked()
+        {
+            input.start_midi_test();
+        }
+    });
+}
+
+fn stick_navigation_menu(ui: &mut Ui, previous: &mut Previous, input: &Input) {
+    let settings = &mut previous.stick_navigation;
+    ui.checkbox(&mut settings.enabled, "Navigate the UI with a stick")
+        .on_hover_text(format!(
+            "Hat moves the focus, the buttons click and go back. Hold Escape for {ESCAPE_HOLD_S} s to turn this off."
+        ));
+
+    let devices = input.get_physical_device_info_map();
+    let selected = settings
+        .device
+        .as_ref()
+        .map(|guid| match devices.get(guid) {
+            Some(info) => info.name.clone(),
+            None => format!("{guid} (disconnected)"),
+        })
+        .unwrap_or_else(|| "none".to_string());
+    ComboBox::from_label("Device")
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            for (guid, info) in devices.iter() {
+                ui.selectable_value(&mut settings.device, Some(guid.clone()), &info.name);
+            }
+        });
+
+    let limits = settings
+        .device
+        .as_ref()
+        .and_then(|guid| devices.get(guid))
+        .map_or((1, 1), |info| {
+            (info.num_hats.max(1), info.num_buttons.max(1))
+        });
+    for (label, value, max) in [
+        ("Hat:", &mut settings.hat, limits.0),
+        ("Activate button:", &mut settings.activate_button, limits.1),
+        ("Back button:", &mut settings.back_button, limits.1),
+    ] {
+        ui.horizontal(|ui| {
+            ui.label(label);
+            ui.add(DragValue::new(value).clamp_range(1..=max));
+        });
+    }
+}
+
+fn update_processing_overrun_window(ctx: &Context, input: &mut Input) {
+    let budget = input.get_processing_budget_mut();
+    let Some(overrun) = budget.overrun().cloned() else {
+        return;
+    };
+
+    let mut open = true;
+    egui::Window::new("Rebind processing overrun")
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "Processing the rebinds took {:.3} ms per tick for {OVERRUN_TICKS} ticks in a row, the budget is {:.3} ms. Input latency grows while this lasts.",
+                overrun.mean_s * 1000.0,
+                overrun.budget_s * 1000.0
+            ));
+            ui.add_space(5.0);
+            if overrun.rebinds.is_empty() {
+                ui.label("Enable per-rebind timing in System > Processing budget to find the most expensive rebinds.");
+                ui.checkbox(&mut budget.per_rebind_timing, "Per-rebind timing");
+                return;
+            }
+            for (index, name, time) in overrun.rebinds.iter() {
+                ui.label(format!("{index}: {name} ({:.3} ms)", time * 1000.0));
+            }
+            ui.add_space(5.0);
+            ui.checkbox(&mut budget.safe_mode, "Safe mode: disable these rebinds");
+        });
+
+    if !open {
+        budget.acknowledge();
+    }
+}
+
+/// Safe mode starts without a config and with output paused, so restarting mid-flight cannot
+/// disturb the virtual devices
+fn update_safe_mode_banner(ctx: &Context, input: &mut Input, ui_data: &mut UIData) {
+    egui::TopBottomPanel::top("safe mode banner").show(ctx, |ui| {
+        ui.horizontal_wrapped(|ui| {
+            ui.label(
+                RichText::new("SAFE MODE")
+                    .strong()
+                    .color(ui.visuals().warn_fg_color),
+            );
+            ui.label(
+                "No config was loaded and nothing is written to vJoy. Load a config from the \
+                 System menu if needed, then resume output to leave safe mode.",
+            );
+            if ui.button("Resume output").clicked() {
+                input.set_output_paused(false);
+                ui_data.safe_mode = false;
+            }
+        });
+    });
+}
+
+fn update_toast(ctx: &Context, ui_data: &mut UIData) {
+    let Some(toast) = &ui_data.toast else {
+        return;
+    };
+    if ctx.input(|i| i.time) > toast.until {
+        ui_data.toast = None;
+        return;
+    }
+
+    egui::Area::new("Toast")
+        .anchor(Align2::CENTER_BOTTOM, [0.0, -40.0])
+        .interactable(false)
+        .show(ctx, |ui| {
+            Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(&toast.text);
+            });
+        });
+}
+
+fn update_capabilities_window(ctx: &Context, ui_data: &mut UIData) {
+    if ui_data.capability_issues.is_empty() {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new("Missing virtual devices")
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            for issue in ui_data.capability_issues.iter() {
+                ui.label(issue);
+            }
+            ui.add_space(10.0);
+            ui.horizontal(|ui| match find_vjoy_conf() {
+                Some(path) => {
+                    if ui.button("Open vJoyConf").clicked() {
+                        if let Err(e) = std::process::Command::new(&path).spawn() {
+                            error!("Failed to launch {:?}. Reason: {}", path, e);
+                        }
+                    }
+                }
+                None => {
+                    ui.label("Configure the devices with vJoyConf, then reload the config.");
+                }
+            });
+        });
+
+    if !open {
+        ui_data.capability_issues.clear();
+    }
+}
+
+fn update_output_failures_window(ctx: &Context, input: &mut Input) {
+    let failures = input.output_failures();
+    if failures.is_empty() {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new("vJoy output failing")
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            ui.label("The vJoy driver keeps rejecting updates. Other programs see stale values.");
+            ui.add_space(5.0);
+            for failure in failures.iter() {
+                ui.label(failure);
+            }
+        });
+
+    if !open {
+        input.acknowledge_output_failures();
+    }
 }
@@ -0,0 +1,164 @@
+//! Dialogs waiting for a user decision.
+//!
+//! Modals are queued in [`UIData::modals`](crate::ui_data::UIData) and rendered in one place at
+//! the end of the UI frame. They only block other dialogs, never the input loop:
+//! [`Input::update`](crate::input::Input::update) runs before and independently of the UI, so
+//! rebinds keep processing gamepad input while a modal is open, even while the UI is skipped in
+//! background mode.
+
+use std::path::{Path, PathBuf};
+
+use egui_file::FileDialog;
+
+use log::error;
+
+use crate::{config::Config, config_diff::ConfigDiff, error::Error};
+
+pub enum Modal {
+    LoadConfig(FileDialog),
+    SaveConfig(FileDialog),
+    /// The config at the path changed on disk since it was last loaded or saved
+    SaveConflict(PathBuf),
+    /// Loading the config at the path would discard unsaved changes
+    LoadDiff {
+        path: PathBuf,
+        loaded: Box<Config>,
+        diff: ConfigDiff,
+    },
+    /// Saving failed because the file at the path could not be written
+    WriteFailed {
+        path: PathBuf,
+        /// [`Error::report`] of the failed write
+        message: String,
+    },
+    /// The config at the path could not be read or is not valid
+    LoadFailed {
+        path: PathBuf,
+        /// [`Error::report`] of the failed load
+        message: String,
+    },
+}
+
+impl Modal {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Modal::LoadConfig(_) => "Load config",
+            Modal::SaveConfig(_) => "Save config",
+            Modal::SaveConflict(_) => "Config changed on disk",
+            Modal::LoadDiff { .. } => "Unsaved changes",
+            Modal::WriteFailed { .. } => "Save failed",
+            Modal::LoadFailed { .. } => "Load failed",
+        }
+    }
+}
+
+/// Open modals, the last one is the topmost and the one Escape closes.
+#[derive(Default)]
+pub struct Modals {
+    stack: Vec<Modal>,
+}
+
+impl Modals {
+    pub fn push(&mut self, modal: Modal) {
+        self.stack.push(modal);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    pub fn top(&self) -> Option<&Modal> {
+        self.stack.last()
+    }
+
+    pub fn close_top(&mut self) -> Option<Modal> {
+        self.stack.pop()
+    }
+
+    /// Logs a failed save of `what` and shows failed writes with the attempted path
+    pub fn report_save_error(&mut self, what: &str, error: Error) {
+        error!("Failed to save {what}. Reason: {error}");
+        if let Error::WriteFailed { path, .. } = &error {
+            self.push(Modal::WriteFailed {
+                path: path.clone(),
+                message: error.report(),
+            });
+        }
+    }
+
+    /// Logs a failed load of the config at `path` and tells the user why it failed
+    pub fn report_load_error(&mut self, path: &Path, error: Error) {
+        error!("Failed to load rebinds from {:?}. Reason: {}", path, error);
+        self.push(Modal::LoadFailed {
+            path: path.to_path_buf(),
+            message: error.report(),
+        });
+    }
+
+    /// Takes all modals out for rendering. Modals pushed while rendering go on top of the ones
+    /// handed back with [`Modals::restore`].
+    pub fn take(&mut self) -> Vec<Modal> {
+        std::mem::take(&mut self.stack)
+    }
+
+    /// Puts the modals that stay open after rendering back below the ones pushed meanwhile.
+    pub fn restore(&mut self, mut kept: Vec<Modal>) {
+        kept.append(&mut self.stack);
+        self.stack = kept;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::{
+        config::Config,
+        modal::{Modal, Modals},
+    };
+
+    fn conflict(path: &str) -> Modal {
+        Modal::SaveConflict(PathBuf::from(path))
+    }
+
+    fn top_path(modals: &Modals) -> Option<PathBuf> {
+        match modals.top()? {
+            Modal::SaveConflict(path) => Some(path.clone()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn modals_pushed_while_rendering_stay_on_top() {
+        let mut modals = Modals::default();
+        modals.push(conflict("a"));
+        modals.push(conflict("b"));
+
+        let rendering = modals.take();
+        modals.push(conflict("c"));
+        modals.restore(rendering);
+        assert_eq!(top_path(&modals), Some(PathBuf::from("c")));
+
+        modals.close_top();
+        assert_eq!(top_path(&modals), Some(PathBuf::from("b")));
+        modals.close_top();
+        modals.close_top();
+        assert!(modals.is_empty());
+        assert!(modals.close_top().is_none());
+    }
+
+    #[test]
+    fn failed_loads_explain_the_problem() {
+        let mut modals = Modals::default();
+        let error = Config::from_toml_str("rebinds = 3").unwrap_err();
+        modals.report_load_error(Path::new("bad.toml"), error);
+        match modals.top() {
+            Some(Modal::LoadFailed { path, message }) => {
+                assert_eq!(path, Path::new("bad.toml"));
+                assert!(message.starts_with("The file is not a valid config: "));
+                assert!(message.ends_with("(E311)"));
+            }
+            _ => panic!("expected a load failed modal"),
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Location of the configs and app settings.
+//!
+//! Everything is stored in the platform config dir, e.g. `%APPDATA%/rust-vjoy-manager` on Windows,
+//! instead of `Cfg/` in the working directory, which is not writable when installed into Program
+//! Files.
+
+use std::path::{Path, PathBuf};
+
+use directories::BaseDirs;
+use log::{info, warn};
+
+use crate::error::Error;
+
+const APP_DIR_NAME: &str = "rust-vjoy-manager";
+
+/// Files that were stored in `Cfg/` before the config dir was used
+const LEGACY_FILES: [&str; 4] = [
+    "config.toml",
+    "devices.toml",
+    "global.toml",
+    "previous.toml",
+];
+
+/// Platform config dir of the app, `Cfg/` in the working directory if the platform has none
+pub fn config_dir() -> Result<PathBuf, Error> {
+    match BaseDirs::new() {
+        Some(dirs) => Ok(dirs.config_dir().join(APP_DIR_NAME)),
+        None => legacy_dir(),
+    }
+}
+
+/// `Cfg/` in the working directory, where older versions stored everything
+pub fn legacy_dir() -> Result<PathBuf, Error> {
+    Ok(std::env::current_dir()?.join("Cfg"))
+}
+
+/// Writes `contents` to `path` and creates its dir, failures name the attempted path.
+pub fn write_file(path: &Path, contents: &str) -> Result<(), Error> {
+    let write = || {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, contents)
+    };
+    write().map_err(|source| Error::WriteFailed {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Legacy files copied into the config dir on startup
+#[derive(Debug, Default)]
+pub struct Migration {
+    /// Old and new path of each copied file
+    pub copied: Vec<(PathBuf, PathBuf)>,
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+impl Migration {
+    /// Copies the files of `legacy` that `dir` does not have yet. The legacy files are kept, so
+    /// older versions started from the same place still find them.
+    pub fn run(legacy: &Path, dir: &Path) -> Self {
+        let mut migration = Self::default();
+        if legacy == dir {
+            return migration;
+        }
+        for name in LEGACY_FILES {
+            let (from, to) = (legacy.join(name), dir.join(name));
+            if !from.is_file() || to.exists() {
+                continue;
+            }
+            let copy = std::fs::create_dir_all(dir).and_then(|_| std::fs::copy(&from, &to));
+            match copy {
+                Ok(_) => migration.copied.push((from, to)),
+                Err(e) => migration.failed.push((from, e.to_string())),
+            }
+        }
+        migration
+    }
+
+    /// Migrates from `Cfg/` in the working directory into [`config_dir`]
+    pub fn run_default() -> Self {
+        match (legacy_dir(), config_dir()) {
+            (Ok(legacy), Ok(dir)) => Self::run(&legacy, &dir),
+            _ => Self::default(),
+        }
+    }
+
+    /// New path of a migrated file, `path` itself otherwise
+    pub fn remap(&self, path: &Path) -> PathBuf {
+        self.copied
+            .iter()
+            .find(|(from, _)| from == path)
+            .map_or_else(|| path.to_path_buf(), |(_, to)| to.clone())
+    }
+
+    /// Logged once the logger is set up, which needs the migrated settings
+    pub fn log(&self) {
+        for (from, to) in self.copied.iter() {
+            info!("Migrated {:?} to {:?}", from, to);
+        }
+        for (from, reason) in self.failed.iter() {
+            warn!("Failed to migrate {:?}. Reason: {}", from, reason);
+        }
+    }
+}
+
+/// Logs the absolute paths in use, so users know where their files are
+pub fn log_paths() {
+    match config_dir() {
+        Ok(dir) => info!("Config dir: {:?}", dir),
+        Err(e) => warn!("Failed to locate the config dir. Reason: {e}"),
+    }
+    if let Ok(dir) = std::env::current_dir() {
+        info!("Working directory: {:?}", dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::paths::Migration;
+
+    #[test]
+    fn legacy_files_are_copied_once() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let (legacy, dir) = (root.join("Cfg"), root.join("config"));
+        std::fs::create_dir_all(&legacy).unwrap();
+        std::fs::write(legacy.join("config.toml"), "old").unwrap();
+        std::fs::write(legacy.join("previous.toml"), "old").unwrap();
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("previous.toml"), "new").unwrap();
+
+        let migration = Migration::run(&legacy, &dir);
+        assert_eq!(
+            migration.copied,
+            vec![(legacy.join("config.toml"), dir.join("config.toml"))]
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join("previous.toml")).unwrap(),
+            "new"
+        );
+        assert!(legacy.join("config.toml").exists());
+        assert_eq!(
+            migration.remap(&legacy.join("config.toml")),
+            dir.join("config.toml")
+        );
+
+        assert!(Migration::run(&legacy, &dir).copied.is_empty());
+    }
+}
@@ -0,0 +1,249 @@
+use std::time::Instant;
+
+use egui::{
+    plot::{Corner, HLine, Legend, Line, Plot, PlotPoints, VLine},
+    Color32, Grid, Ui,
+};
+use ringbuffer::{AllocRingBuffer, RingBufferExt, RingBufferWrite};
+
+/// Seconds of history shown in the performance tab
+pub const HISTORY_S: f64 = 10.0;
+
+/// Enough for [`HISTORY_S`] of input ticks at the 1 ms poll interval
+const HISTORY_CAPACITY: usize = 16384;
+
+const MARKER_CAPACITY: usize = 64;
+
+/// Durations in seconds with the time they were recorded at. Recording is a ring buffer push, the
+/// statistics are only computed while the performance tab is shown.
+#[derive(Debug, Clone)]
+pub struct TimingHistory {
+    samples: AllocRingBuffer<(f64, f64)>,
+}
+
+impl Default for TimingHistory {
+    fn default() -> Self {
+        Self {
+            samples: AllocRingBuffer::with_capacity(HISTORY_CAPACITY),
+        }
+    }
+}
+
+impl TimingHistory {
+    pub fn record(&mut self, time: f64, duration_s: f64) {
+        self.samples.push((time, duration_s));
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Samples of the last [`HISTORY_S`] before `now`
+    pub fn recent(&self, now: f64) -> impl Iterator<Item = &(f64, f64)> {
+        self.samples
+            .iter()
+            .filter(move |(time, _)| now - time <= HISTORY_S)
+    }
+
+    pub fn summary(&self, now: f64) -> Option<TimingSummary> {
+        let durations: Vec<f64> = self.recent(now).map(|(_, d)| *d).collect();
+        TimingSummary::new(durations)
+    }
+}
+
+/// Statistics of a [`TimingHistory`] in seconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimingSummary {
+    pub mean: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl TimingSummary {
+    pub fn new(mut durations: Vec<f64>) -> Option<Self> {
+        if durations.is_empty() {
+            return None;
+        }
+        durations.sort_by(f64::total_cmp);
+        // Nearest rank, so a single stutter among 100 frames shows up in p99
+        let percentile = |p: f64| {
+            let rank = (p / 100.0 * durations.len() as f64).ceil() as usize;
+            durations[rank.clamp(1, durations.len()) - 1]
+        };
+        Some(Self {
+            mean: durations.iter().sum::<f64>() / durations.len() as f64,
+            p95: percentile(95.0),
+            p99: percentile(99.0),
+            max: durations[durations.len() - 1],
+        })
+    }
+}
+
+/// Frame times and marked events like config loads, owned by the UI
+pub struct PerformanceStats {
+    start: Instant,
+    pub frames: TimingHistory,
+    markers: AllocRingBuffer<(f64, String)>,
+}
+
+impl PerformanceStats {
+    /// `start` is the time base of the input ticks, so both histories line up
+    pub fn new(start: Instant) -> Self {
+        Self {
+            start,
+            frames: TimingHistory::default(),
+            markers: AllocRingBuffer::with_capacity(MARKER_CAPACITY),
+        }
+    }
+
+    pub fn now(&self) -> f64 {
+        self.start.elapsed().as_secs_f64()
+    }
+
+    pub fn record_frame(&mut self, duration_s: f64) {
+        let now = self.now();
+        self.frames.record(now, duration_s);
+    }
+
+    /// Event shown as a vertical line in both graphs
+    pub fn mark(&mut self, label: impl Into<String>) {
+        let now = self.now();
+        self.markers.push((now, label.into()));
+    }
+}
+
+pub fn build_ui(ui: &mut Ui, stats: &PerformanceStats, processing: &TimingHistory) {
+    let now = stats.now();
+    let histories = [
+        ("Frame time", &stats.frames, Color32::LIGHT_BLUE),
+        ("Input processing", processing, Color32::LIGHT_GREEN),
+    ];
+
+    Grid::new("PerformanceSummary")
+        .num_columns(5)
+        .striped(true)
+        .show(ui, |ui| {
+            for header in ["", "mean", "p95", "p99", "max"] {
+                ui.strong(header);
+            }
+            ui.end_row();
+            for (name, history, _) in histories.iter() {
+                ui.label(*name);
+                match history.summary(now) {
+                    Some(summary) => {
+                        for value in [summary.mean, summary.p95, summary.p99, summary.max] {
+                            ui.label(format!("{:.2} ms", value * 1000.0));
+                        }
+                    }
+                    None => {
+                        ui.label("no samples");
+                    }
+                }
+                ui.end_row();
+            }
+        });
+    ui.separator();
+
+    let height = (ui.available_height() / 2.0 - ui.spacing().item_spacing.y).max(100.0);
+    for (name, history, color) in histories {
+        ui.label(name);
+        Plot::new(name)
+            .height(height)
+            .include_x(-HISTORY_S)
+            .include_x(0.0)
+            .include_y(0.0)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .legend(Legend::default().position(Corner::LeftTop))
+            .label_formatter(|_, point| format!("{:.1} s\n{:.3} ms", point.x, point.y))
+            .show(ui, |plot_ui| {
+                let points: PlotPoints = history
+                    .recent(now)
+                    .map(|(time, duration)| [time - now, duration * 1000.0])
+                    .collect();
+                plot_ui.line(Line::new(points).color(color).name(name));
+
+                if let Some(summary) = history.summary(now) {
+                    plot_ui.hline(
+                        HLine::new(summary.p95 * 1000.0)
+                            .color(Color32::YELLOW)
+                            .name(format!("p95 {:.2} ms", summary.p95 * 1000.0)),
+                    );
+                    plot_ui.hline(
+                        HLine::new(summary.p99 * 1000.0)
+                            .color(Color32::RED)
+                            .name(format!("p99 {:.2} ms", summary.p99 * 1000.0)),
+                    );
+                }
+                for (time, label) in stats.markers.iter() {
+                    if now - time <= HISTORY_S {
+                        plot_ui.vline(VLine::new(time - now).color(Color32::GRAY).name(label));
+                    }
+                }
+            });
+    }
+}
+
+/// Shown instead of the viewers in performance mode, with the mean tick time of both modes.
+/// Returns true if performance mode should be left.
+pub fn performance_mode_ui(ui: &mut Ui, (current, other): (Option<f64>, Option<f64>)) -> bool {
+    let mut leave = false;
+    ui.vertical_centered(|ui| {
+        ui.add_space(ui.available_height() / 3.0);
+        ui.heading("Performance mode active");
+        ui.label("Input is polled, processed and output. Plots and viewers are paused.");
+        ui.add_space(10.0);
+
+        let ms = |s: Option<f64>| match s {
+            Some(s) => format!("{:.3} ms", s * 1000.0),
+            None => "no samples".to_string(),
+        };
+        ui.label(format!("Tick time: {}", ms(current)));
+        ui.label(format!("Tick time with the viewers: {}", ms(other)));
+        if let (Some(current), Some(other)) = (current, other) {
+            let saved_ms = (other - current) * 1000.0;
+            match saved_ms >= 0.0 {
+                true => ui.strong(format!("{saved_ms:.3} ms less per tick")),
+                false => ui.strong(format!("{:.3} ms more per tick", -saved_ms)),
+            };
+        }
+
+        ui.add_space(10.0);
+        leave = ui
+            .button("Leave performance mode")
+            .on_hover_text("F2")
+            .clicked();
+    });
+    leave
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::performance::{TimingHistory, TimingSummary, HISTORY_S};
+
+    #[test]
+    fn percentiles_use_the_nearest_rank() {
+        let mut durations: Vec<f64> = (1..=100).map(|ms| ms as f64 / 1000.0).collect();
+        durations.reverse();
+        let summary = TimingSummary::new(durations).unwrap();
+        assert_eq!(summary.p95, 0.095);
+        assert_eq!(summary.p99, 0.099);
+        assert_eq!(summary.max, 0.1);
+        assert!((summary.mean - 0.0505).abs() < 1e-9);
+
+        let single = TimingSummary::new(vec![0.004]).unwrap();
+        assert_eq!((single.p95, single.p99), (0.004, 0.004));
+        assert_eq!(TimingSummary::new(Vec::new()), None);
+    }
+
+    #[test]
+    fn only_recent_samples_count() {
+        let mut history = TimingHistory::default();
+        history.record(0.0, 1.0);
+        history.record(HISTORY_S + 1.0, 0.002);
+        let summary = history.summary(HISTORY_S + 1.5).unwrap();
+        assert_eq!(summary.max, 0.002);
+    }
+}
@@ -1,28 +1,156 @@
+use std::path::{Path, PathBuf};
+
 use log::{info, warn};
 use serde::{Deserialize, Serialize};
 
-use crate::error::Error;
+use crate::{
+    error::Error,
+    input::stick_navigation::StickNavigationSettings,
+    logging::FileLogSettings,
+    paths::{self, Migration},
+    rebind::{axis_roles::AxisRoleTable, output_range::OutputRangeSettings},
+    split_view::SplitView,
+    update_check::UpdateCheckSettings,
+    view_state::ViewState,
+    visualization::VisualizationSettings,
+};
+
+pub const RECENT_CFG_PATHS_MAX: usize = 10;
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Default)]
 pub struct Previous {
     pub load_cfg_path: Option<String>,
+
+    /// Most recently loaded or saved configs, newest first
+    #[serde(default)]
+    pub recent_cfg_paths: Vec<String>,
+
+    #[serde(default)]
+    pub file_log: FileLogSettings,
+
+    /// Rebind processing time per tick before an overrun is reported. Half the poll interval if
+    /// not set.
+    #[serde(default)]
+    pub processing_budget_ms: Option<f64>,
+
+    /// Most rebinds writing the same virtual control per tick, unlimited if not set
+    #[serde(default)]
+    pub rewrite_limit: Option<u32>,
+
+    #[serde(default)]
+    pub visualization: VisualizationSettings,
+
+    /// Split between the rebind viewer and the input viewer
+    #[serde(default)]
+    pub split_view: SplitView,
+
+    /// Rebind edits stay pending until applied
+    #[serde(default)]
+    pub safe_edit: bool,
+
+    #[serde(default)]
+    pub stick_navigation: StickNavigationSettings,
+
+    /// Publish the virtual device state to shared memory for external programs
+    #[serde(default)]
+    pub shared_memory: bool,
+
+    /// MIDI output port the feedback table is sent to
+    #[serde(default)]
+    pub midi_port: Option<String>,
+
+    /// Set once the setup wizard is finished or skipped. Settings files from before the wizard
+    /// existed are no first run, so a missing entry counts as done.
+    #[serde(default = "setup_wizard_done_default")]
+    pub setup_wizard_done: bool,
+
+    /// GUIDs of the physical devices read through raw HID instead of SDL
+    #[serde(default)]
+    pub hid_devices: Vec<String>,
+
+    /// Inner window size at the last clean shutdown
+    #[serde(default)]
+    pub window_size: Option<[u32; 2]>,
+
+    /// Active tab and rebind list state at the last clean shutdown
+    #[serde(default)]
+    pub view_state: ViewState,
+
+    #[serde(default)]
+    pub update_check: UpdateCheckSettings,
+
+    /// vJoy axis per role, offered when picking the destination of an axis to axis rebind
+    #[serde(default)]
+    pub axis_roles: AxisRoleTable,
+
+    #[serde(default)]
+    pub output_range: OutputRangeSettings,
+
+    /// Start in performance mode, without plots and viewers
+    #[serde(default)]
+    pub performance_mode: bool,
+}
+
+fn setup_wizard_done_default() -> bool {
+    true
 }
 
 impl Previous {
+    /// Remembers `path` as the config to load on the next start and moves it to the front of the
+    /// recent configs.
+    pub fn set_current_cfg_path(&mut self, path: &Path) {
+        let path = path.to_string_lossy().to_string();
+        self.recent_cfg_paths.retain(|p| *p != path);
+        self.recent_cfg_paths.insert(0, path.clone());
+        self.recent_cfg_paths.truncate(RECENT_CFG_PATHS_MAX);
+        self.load_cfg_path = Some(path);
+    }
+
+    /// Removes recent configs whose files no longer exist.
+    pub fn prune_recent_cfg_paths(&mut self) {
+        self.recent_cfg_paths.retain(|p| Path::new(p).exists());
+    }
+
+    /// Location of the previous session file: `previous.toml` in the config dir
+    pub fn path() -> Result<PathBuf, Error> {
+        Ok(paths::config_dir()?.join("previous.toml"))
+    }
+
+    /// Points the config paths at the copies of migrated legacy files
+    pub fn remap_migrated(&mut self, migration: &Migration) {
+        let remap = |path: &String| {
+            migration
+                .remap(Path::new(path))
+                .to_string_lossy()
+                .to_string()
+        };
+        self.load_cfg_path = self.load_cfg_path.as_ref().map(remap);
+        self.recent_cfg_paths = self.recent_cfg_paths.iter().map(remap).collect();
+    }
+
     pub fn write(&self) -> Result<(), Error> {
+        self.write_to_path(&Self::path()?)
+    }
+
+    pub fn write_to_path(&self, path: &Path) -> Result<(), Error> {
         let ser_toml = toml::to_string_pretty(&self)?;
         info!("Successfully serialized previous toml file");
-        let path = std::env::current_dir()?.join("Cfg").join("previous.toml");
-        std::fs::write(path, ser_toml)?;
-
-        Ok(())
+        paths::write_file(path, &ser_toml)
     }
 
     pub fn read_or_default() -> Self {
-        let path = std::env::current_dir()
-            .unwrap()
-            .join("Cfg")
-            .join("previous.toml");
+        match Self::path() {
+            Ok(path) => Self::read_from_path_or_default(&path),
+            Err(e) => {
+                warn!("Failed to locate previous toml file. Reason: {e}. Loading default");
+                Self::default()
+            }
+        }
+    }
+
+    /// A missing or corrupt file falls back to the defaults. The corrupt file is overwritten on
+    /// the next write.
+    pub fn read_from_path_or_default(path: &Path) -> Self {
         match std::fs::read_to_string(path) {
             Ok(string) => match toml::from_str(&string) {
                 Ok(previous) => {
@@ -41,3 +169,95 @@ impl Previous {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{
+        previous::{Previous, RECENT_CFG_PATHS_MAX},
+        rebind::{axis_roles::VJoyAxis, shift_mode_mask::ShiftModeMask},
+        ui_data::ActiveTab,
+        view_state::ViewState,
+    };
+
+    #[test]
+    fn recent_paths_are_deduplicated_and_capped() {
+        let mut previous = Previous::default();
+        for i in 0..RECENT_CFG_PATHS_MAX + 2 {
+            previous.set_current_cfg_path(Path::new(&format!("cfg_{i}.toml")));
+        }
+        previous.set_current_cfg_path(Path::new("cfg_5.toml"));
+
+        assert_eq!(previous.recent_cfg_paths.len(), RECENT_CFG_PATHS_MAX);
+        assert_eq!(previous.recent_cfg_paths[0], "cfg_5.toml");
+        assert_eq!(previous.recent_cfg_paths[1], "cfg_11.toml");
+        assert_eq!(
+            previous
+                .recent_cfg_paths
+                .iter()
+                .filter(|p| *p == "cfg_5.toml")
+                .count(),
+            1
+        );
+        assert_eq!(previous.load_cfg_path.as_deref(), Some("cfg_5.toml"));
+    }
+
+    #[test]
+    fn setup_wizard_only_on_first_run() {
+        assert!(!Previous::default().setup_wizard_done);
+
+        let previous: Previous = toml::from_str(r#"load_cfg_path = "config.toml""#).unwrap();
+        assert!(previous.setup_wizard_done);
+    }
+
+    #[test]
+    fn session_round_trips_through_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("round_trip.toml");
+
+        let mut previous = Previous {
+            setup_wizard_done: true,
+            window_size: Some([1024, 768]),
+            ..Default::default()
+        };
+        previous.set_current_cfg_path(Path::new("Cfg/racing.toml"));
+        previous.view_state = ViewState {
+            active_tab: Some(ActiveTab::ConfigEditor),
+            cfg_path: previous.load_cfg_path.clone(),
+            rebind_scroll: 120.0,
+            rebind_mode_filter: Some(ShiftModeMask(0b00000010)),
+            ..Default::default()
+        };
+        previous
+            .view_state
+            .expanded_rebinds
+            .profile
+            .insert("pitch".to_string());
+        previous.axis_roles.yaw = VJoyAxis::Rx;
+        previous.output_range.unreachable_percent = 25.0;
+        previous.performance_mode = true;
+        previous.midi_port = Some("Button box".to_string());
+        previous.write_to_path(&path).unwrap();
+
+        assert_eq!(Previous::read_from_path_or_default(&path), previous);
+    }
+
+    #[test]
+    fn missing_or_corrupt_file_falls_back_to_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing.toml");
+        assert_eq!(
+            Previous::read_from_path_or_default(&missing),
+            Previous::default()
+        );
+
+        let corrupt = dir.path().join("corrupt.toml");
+        std::fs::write(&corrupt, "load_cfg_path = [not toml").unwrap();
+        let previous = Previous::read_from_path_or_default(&corrupt);
+        assert_eq!(previous, Previous::default());
+
+        previous.write_to_path(&corrupt).unwrap();
+        assert_eq!(Previous::read_from_path_or_default(&corrupt), previous);
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use log::{error, warn};
+
+/// Minimum time between two log lines with the same key
+pub const RATE_LIMITED_LOG_INTERVAL: f64 = 5.0;
+
+/// Kind of a recurring error and the index of the rebind or device it is about. Checked on every
+/// tick the error occurs, so it is cheap to build and the message is only formatted when emitted.
+pub type LogKey = (&'static str, usize);
+
+/// Logger for errors that recur every tick, e.g. a rebind referencing a disconnected device.
+///
+/// Each key is emitted at most once per interval. Suppressed repetitions are counted and
+/// appended as "repeated X times" to the next emitted line.
+pub struct RateLimitedLog {
+    interval: f64,
+    entries: HashMap<LogKey, RateLimitedEntry>,
+}
+
+struct RateLimitedEntry {
+    last_emitted: f64,
+    suppressed: u32,
+}
+
+impl Default for RateLimitedLog {
+    fn default() -> Self {
+        Self::new(RATE_LIMITED_LOG_INTERVAL)
+    }
+}
+
+impl RateLimitedLog {
+    pub fn new(interval: f64) -> Self {
+        Self {
+            interval,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Returns the number of suppressed repetitions if `key` should be emitted at `time`.
+    pub fn check(&mut self, key: LogKey, time: f64) -> Option<u32> {
+        let Some(entry) = self.entries.get_mut(&key) else {
+            self.entries.insert(
+                key,
+                RateLimitedEntry {
+                    last_emitted: time,
+                    suppressed: 0,
+                },
+            );
+            return Some(0);
+        };
+
+        if time - entry.last_emitted < self.interval {
+            entry.suppressed += 1;
+            return None;
+        }
+
+        let suppressed = entry.suppressed;
+        entry.last_emitted = time;
+        entry.suppressed = 0;
+        Some(suppressed)
+    }
+
+    pub fn warn(&mut self, key: LogKey, time: f64, message: impl FnOnce() -> String) {
+        if let Some(suppressed) = self.check(key, time) {
+            warn!("{}", with_repetitions(&message(), suppressed));
+        }
+    }
+
+    pub fn error(&mut self, key: LogKey, time: f64, message: impl FnOnce() -> String) {
+        if let Some(suppressed) = self.check(key, time) {
+            error!("{}", with_repetitions(&message(), suppressed));
+        }
+    }
+}
+
+fn with_repetitions(message: &str, suppressed: u32) -> String {
+    match suppressed {
+        0 => message.to_string(),
+        n => format!("{message} (repeated {n} times)"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rate_limited_log::{with_repetitions, RateLimitedLog};
+
+    #[test]
+    fn first_occurrence_is_emitted() {
+        let mut log = RateLimitedLog::new(5.0);
+        assert_eq!(log.check(("a", 0), 0.0), Some(0));
+        assert_eq!(log.check(("b", 0), 0.1), Some(0));
+        assert_eq!(log.check(("a", 1), 0.2), Some(0));
+    }
+
+    #[test]
+    fn repetitions_are_counted_until_interval_passed() {
+        let mut log = RateLimitedLog::new(5.0);
+        assert_eq!(log.check(("a", 0), 0.0), Some(0));
+        for i in 1..=100 {
+            assert_eq!(log.check(("a", 0), i as f64 * 0.01), None);
+        }
+        assert_eq!(log.check(("a", 0), 5.0), Some(100));
+        assert_eq!(log.check(("a", 0), 5.5), None);
+        assert_eq!(log.check(("a", 0), 10.0), Some(1));
+    }
+
+    #[test]
+    fn suffix_only_when_suppressed() {
+        assert_eq!(with_repetitions("failed", 0), "failed");
+        assert_eq!(with_repetitions("failed", 3), "failed (repeated 3 times)");
+    }
+}
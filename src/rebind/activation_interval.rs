@@ -1,9 +1,14 @@
-use egui::{Slider, Ui};
-use egui_extras::{Column, TableBuilder};
+use egui::{RichText, Slider, Ui};
+use egui_extras::Column;
 use serde::{Deserialize, Serialize};
 use std::ops::Range;
 
-use super::{TABLE_COLUMN_LEFT_WIDTH, TABLE_ROW_HEIGHT};
+use super::{rebind_table, TickContext, TABLE_ROW_HEIGHT};
+
+/// Upper end of the interval sliders in seconds
+const INTERVAL_MAX: f64 = 10.0;
+/// Smallest interval the sliders leave behind when they cross
+const MIN_INTERVAL_WIDTH: f64 = 0.01;
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct ActivationIntervalParams {
@@ -34,7 +39,8 @@ impl ActivationIntervalParams {
         }
     }
 
-    pub fn update(&mut self, state: bool, time: f64, use_sustain: bool) -> bool {
+    pub fn update(&mut self, state: bool, ctx: TickContext, use_sustain: bool) -> bool {
+        let time = ctx.time;
         let pressed_this_frame = state && !self.last_input;
         let released_this_frame = !state && self.last_input;
         self.last_input = state;
@@ -67,40 +73,129 @@ impl ActivationIntervalParams {
         }
     }
 
+    pub fn is_degenerate(&self) -> bool {
+        self.interval_start >= self.interval_end
+    }
+
+    pub fn debug_state(&self) -> Vec<(String, String)> {
+        vec![
+            ("last_input".to_string(), self.last_input.to_string()),
+            (
+                "activation_start".to_string(),
+                format!("{:.3} s", self.activation_start),
+            ),
+            (
+                "activation_end".to_string(),
+                format!("{:.3} s", self.activation_end),
+            ),
+        ]
+    }
+
+    pub fn take_runtime_state(&mut self, from: &mut Self) {
+        self.last_input = from.last_input;
+        self.activation_start = from.activation_start;
+        self.activation_end = from.activation_end;
+    }
+
+    /// Keeps the interval at least [`MIN_INTERVAL_WIDTH`] wide by moving the slider that was
+    /// not dragged, or the dragged one too when it sits at the end of the slider range.
+    fn correct_interval(&mut self, start_changed: bool) {
+        if !self.is_degenerate() {
+            return;
+        }
+
+        if start_changed {
+            self.interval_start = self.interval_start.min(INTERVAL_MAX - MIN_INTERVAL_WIDTH);
+            self.interval_end = self.interval_start + MIN_INTERVAL_WIDTH;
+        } else {
+            self.interval_end = self.interval_end.max(MIN_INTERVAL_WIDTH);
+            self.interval_start = self.interval_end - MIN_INTERVAL_WIDTH;
+        }
+    }
+
+    fn description(&self, show_sustain: bool) -> String {
+        let held = format!(
+            "held between {:.2} s and {:.2} s",
+            self.interval_start, self.interval_end
+        );
+        match (show_sustain, self.sustain) {
+            (true, Some(sustain)) => {
+                format!("Fires for {sustain:.2} s after being released when {held}")
+            }
+            (true, None) => format!("Fires for one tick after being released when {held}"),
+            (false, _) => format!("Toggles after being released when {held}"),
+        }
+    }
+
     pub fn widget(&mut self, ui: &mut Ui, show_sustain: bool) {
-        TableBuilder::new(ui)
-            .column(Column::exact(TABLE_COLUMN_LEFT_WIDTH))
+        let degenerate = self.is_degenerate();
+        let interval_color = match degenerate {
+            true => ui.visuals().error_fg_color,
+            false => ui.visuals().text_color(),
+        };
+        let degenerate_hint = "Interval start and end are equal: this rebind can never fire.";
+
+        rebind_table(ui)
             .column(Column::remainder())
             .body(|mut body| {
                 body.row(TABLE_ROW_HEIGHT, |mut row| {
                     row.col(|ui| {
-                        ui.label("Interval start:");
+                        let label =
+                            ui.label(RichText::new("Interval start:").color(interval_color));
+                        if degenerate {
+                            label.on_hover_text(degenerate_hint);
+                        }
                     });
                     row.col(|ui| {
-                        ui.add(Slider::new(&mut self.interval_start, 0.0..=10.0));
+                        let response = ui.add(
+                            Slider::new(&mut self.interval_start, 0.0..=INTERVAL_MAX).suffix(" s"),
+                        );
+                        if response.changed() {
+                            self.correct_interval(true);
+                        }
                     });
                 });
                 body.row(TABLE_ROW_HEIGHT, |mut row| {
                     row.col(|ui| {
-                        ui.label("Interval end:");
+                        let label = ui.label(RichText::new("Interval end:").color(interval_color));
+                        if degenerate {
+                            label.on_hover_text(degenerate_hint);
+                        }
                     });
                     row.col(|ui| {
-                        ui.add(Slider::new(&mut self.interval_end, 0.0..=10.0));
+                        let response = ui.add(
+                            Slider::new(&mut self.interval_end, 0.0..=INTERVAL_MAX).suffix(" s"),
+                        );
+                        if response.changed() {
+                            self.correct_interval(false);
+                        }
                     });
                 });
                 if show_sustain {
                     body.row(TABLE_ROW_HEIGHT, |mut row| {
                         row.col(|ui| {
-                            ui.label("Sustain");
+                            ui.label("Sustain:");
                         });
-                        row.col(|ui| match &mut self.sustain {
-                            Some(val) => {
-                                ui.add(Slider::new(val, 0.0..=1.0));
+                        row.col(|ui| {
+                            let mut use_sustain = self.sustain.is_some();
+                            if ui.checkbox(&mut use_sustain, "").changed() {
+                                self.sustain = match use_sustain {
+                                    true => Self::default().sustain,
+                                    false => None,
+                                };
+                            }
+                            if let Some(val) = &mut self.sustain {
+                                ui.add(Slider::new(val, 0.0..=1.0).suffix(" s"));
                             }
-                            None => (),
                         });
                     });
                 }
+                body.row(TABLE_ROW_HEIGHT, |mut row| {
+                    row.col(|_ui| {});
+                    row.col(|ui| {
+                        ui.label(RichText::new(self.description(show_sustain)).weak());
+                    });
+                });
             });
     }
 }
@@ -117,3 +212,84 @@ impl Default for ActivationIntervalParams {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::rebind::{
+        activation_interval::{ActivationIntervalParams, INTERVAL_MAX},
+        TickContext,
+    };
+
+    fn replay(ticks: &[(bool, TickContext)]) -> Vec<bool> {
+        let mut params = ActivationIntervalParams::new(0.1..0.5, None);
+        ticks
+            .iter()
+            .map(|(state, ctx)| params.update(*state, *ctx, true))
+            .collect()
+    }
+
+    #[test]
+    fn replaying_ticks_is_deterministic() {
+        let ticks: Vec<(bool, TickContext)> = (1..=10)
+            .map(|tick| {
+                let ctx = TickContext {
+                    tick,
+                    time: tick as f64 * 0.05,
+                    delta_t: 0.05,
+                };
+                (tick <= 4, ctx)
+            })
+            .collect();
+
+        let first = replay(&ticks);
+        assert_eq!(first, replay(&ticks));
+        assert_eq!(first.iter().filter(|activated| **activated).count(), 1);
+        assert!(first[4]);
+    }
+
+    #[test]
+    fn crossed_interval_is_corrected() {
+        let mut params = ActivationIntervalParams::new(0.5..1.0, None);
+        params.interval_start = 2.0;
+        params.correct_interval(true);
+        assert_eq!(params.interval_start, 2.0);
+        assert!(!params.is_degenerate());
+
+        params.interval_end = 1.5;
+        params.correct_interval(false);
+        assert_eq!(params.interval_end, 1.5);
+        assert!(!params.is_degenerate());
+
+        params.interval_start = INTERVAL_MAX;
+        params.correct_interval(true);
+        assert!(!params.is_degenerate());
+        assert!(params.interval_end <= INTERVAL_MAX);
+
+        params.interval_end = 0.0;
+        params.correct_interval(false);
+        assert!(!params.is_degenerate());
+        assert!(params.interval_start >= 0.0);
+    }
+
+    #[test]
+    fn debug_state_tracks_press_timestamps() {
+        let mut params = ActivationIntervalParams::new(0.1..0.5, None);
+        let ctx = |time| TickContext {
+            tick: 0,
+            time,
+            delta_t: 0.05,
+        };
+        params.update(true, ctx(1.0), true);
+        params.update(false, ctx(1.25), true);
+
+        let state = params.debug_state();
+        assert_eq!(
+            state,
+            vec![
+                ("last_input".to_string(), "false".to_string()),
+                ("activation_start".to_string(), "1.000 s".to_string()),
+                ("activation_end".to_string(), "1.250 s".to_string()),
+            ]
+        );
+    }
+}
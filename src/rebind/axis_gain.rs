@@ -0,0 +1,306 @@
+use egui::{Slider, Ui};
+use egui_extras::Column;
+use serde::{Deserialize, Serialize};
+use strum::{AsRefStr, EnumIter, EnumString, EnumVariantNames};
+
+use super::{
+    merge_axes::{control_source_helpers, flat_source},
+    rebind_table,
+    rebind_viewer::DevicesInfoMap,
+    resolved_slots::ResolvedSlots,
+    validate_value_virtual_button, EnumVariantDropdown, TickContext, VariantDescription,
+    TABLE_ROW_HEIGHT,
+};
+use crate::{
+    error::Error,
+    input::{PhysicalDevice, VirtualDevice},
+};
+
+/// Button that switches an [`AxisGain`] on while held, also the input of a
+/// [`super::reroute_rebind::RerouteRebind::ButtonToButton`] rebind
+///
+/// Virtual sources read the state written earlier in the same tick, so the producing rebind has to be processed first.
+#[derive(
+    Debug,
+    PartialEq,
+    Clone,
+    Serialize,
+    Deserialize,
+    AsRefStr,
+    EnumIter,
+    EnumString,
+    EnumVariantNames,
+)]
+#[serde(tag = "source")]
+pub enum ButtonSource {
+    Physical { device: String, button: u32 },
+    Virtual { device: u32, button: u32 },
+}
+
+impl Default for ButtonSource {
+    fn default() -> Self {
+        Self::Physical {
+            device: Default::default(),
+            button: Default::default(),
+        }
+    }
+}
+
+impl VariantDescription for ButtonSource {
+    fn description(&self) -> &'static str {
+        match self {
+            ButtonSource::Physical { .. } => "Button of a physical device",
+            ButtonSource::Virtual { .. } => {
+                "Button of a virtual device as written earlier in the same tick"
+            }
+        }
+    }
+}
+
+control_source_helpers!(ButtonSource, button, Button);
+
+impl ButtonSource {
+    pub fn button_widget(&mut self, ui: &mut Ui, devices_info_map: &DevicesInfoMap) {
+        match self {
+            ButtonSource::Physical { device, button } => {
+                devices_info_map.physical_button_widget(ui, device, button);
+            }
+            ButtonSource::Virtual { device, button } => {
+                devices_info_map.virtual_button_widget(ui, device, button);
+            }
+        }
+    }
+
+    /// Physical sources are read through the slot of source `index` in `resolved`
+    pub fn pressed(
+        &self,
+        resolved: &mut ResolvedSlots,
+        index: usize,
+        physical_devices: &[PhysicalDevice],
+        virtual_devices: &[VirtualDevice],
+    ) -> Result<bool, Error> {
+        match self {
+            ButtonSource::Physical { device, button } => {
+                resolved.physical_button(index, physical_devices, device, button)
+            }
+            ButtonSource::Virtual { device, button } => {
+                validate_value_virtual_button(virtual_devices, device, button)
+            }
+        }
+    }
+}
+
+flat_source!(
+    /// Flat `src_device`/`src_button` keys of a button to button rebind, see
+    /// [`super::merge_axes::flat_src_axis`]
+    flat_src_button,
+    ButtonSource,
+    button: src_button,
+    src_device,
+    src_virtual_device
+);
+
+/// Multiplies the output of an axis to axis rebind by `factor` while a button is held, e.g. 0.5 for
+/// a precise sniper mode or 2.0 for a boost.
+///
+/// The gain ramps linearly between 1.0 and `factor` over `ramp_time` seconds in both directions.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct AxisGain {
+    pub button: ButtonSource,
+    pub factor: f32,
+    pub ramp_time: f64,
+
+    #[serde(skip, default = "unity_gain")]
+    current: f32,
+}
+
+fn unity_gain() -> f32 {
+    1.0
+}
+
+impl Default for AxisGain {
+    fn default() -> Self {
+        Self {
+            button: ButtonSource::default(),
+            factor: 2.0,
+            ramp_time: 0.2,
+            current: unity_gain(),
+        }
+    }
+}
+
+impl AxisGain {
+    pub fn new(button: ButtonSource, factor: f32, ramp_time: f64) -> Self {
+        Self {
+            button,
+            factor,
+            ramp_time,
+            ..Default::default()
+        }
+    }
+
+    /// Gain applied in the last tick
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+
+    /// Drops back to unity gain, e.g. when the rebind is inactive in the current shift mode.
+    pub fn reset(&mut self) {
+        self.current = unity_gain();
+    }
+
+    pub fn debug_state(&self) -> Vec<(String, String)> {
+        vec![("gain".to_string(), format!("{:.3}", self.current))]
+    }
+
+    /// Ramps from the gain of `from` towards the new factor
+    pub fn take_runtime_state(&mut self, from: &mut Self) {
+        self.current = from.current;
+    }
+
+    /// Steps the gain towards its target and scales `value`. The result is not clamped, the
+    /// conversion to the vJoy range does that after the remaining steps.
+    // value range -32768..=32767
+    pub fn apply(&mut self, held: bool, value: i32, ctx: TickContext) -> i32 {
+        let target = match held {
+            true => self.factor,
+            false => unity_gain(),
+        };
+        self.current = match self.ramp_time > 0.0 {
+            true => {
+                let step = ((self.factor - 1.0).abs() as f64 * ctx.delta_t / self.ramp_time) as f32;
+                match self.current < target {
+                    true => (self.current + step).min(target),
+                    false => (self.current - step).max(target),
+                }
+            }
+            false => target,
+        };
+        (value as f32 * self.current).round() as i32
+    }
+
+    pub fn widget(&mut self, ui: &mut Ui, devices_info_map: &DevicesInfoMap) {
+        rebind_table(ui)
+            .column(Column::remainder())
+            .body(|mut body| {
+                body.row(TABLE_ROW_HEIGHT, |mut row| {
+                    row.col(|ui| {
+                        ui.label("Gain source:");
+                    });
+                    row.col(|ui| {
+                        ui.push_id("GainSource", |ui| {
+                            self.button.variant_dropdown_widget(ui);
+                        });
+                    });
+                });
+                body.row(TABLE_ROW_HEIGHT, |mut row| {
+                    row.col(|ui| {
+                        ui.label("Gain device:");
+                    });
+                    row.col(|ui| {
+                        ui.push_id("GainDevice", |ui| {
+                            self.button.device_widget(ui, devices_info_map);
+                        });
+                    });
+                });
+                body.row(TABLE_ROW_HEIGHT, |mut row| {
+                    row.col(|ui| {
+                        ui.label("Gain button:");
+                    });
+                    row.col(|ui| {
+                        ui.push_id("GainButton", |ui| {
+                            self.button.button_widget(ui, devices_info_map);
+                        });
+                    });
+                });
+                body.row(TABLE_ROW_HEIGHT, |mut row| {
+                    row.col(|ui| {
+                        ui.label("Gain factor:");
+                    });
+                    row.col(|ui| {
+                        ui.add(Slider::new(&mut self.factor, 0.1..=4.0))
+                            .on_hover_text("Below 1.0 for precision, above 1.0 for a boost");
+                    });
+                });
+                body.row(TABLE_ROW_HEIGHT, |mut row| {
+                    row.col(|ui| {
+                        ui.label("Gain ramp (s):");
+                    });
+                    row.col(|ui| {
+                        ui.add(Slider::new(&mut self.ramp_time, 0.0..=2.0));
+                    });
+                });
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        input::virtual_state::VirtualState,
+        rebind::{
+            axis_gain::{AxisGain, ButtonSource},
+            axis_to_axis::{apply_axis_modifier, AxisParams, AxisToAxisModifier},
+            TickContext,
+        },
+    };
+
+    fn step(delta_t: f64) -> TickContext {
+        TickContext {
+            delta_t,
+            ..Default::default()
+        }
+    }
+
+    fn gain(factor: f32, ramp_time: f64) -> AxisGain {
+        AxisGain::new(ButtonSource::default(), factor, ramp_time)
+    }
+
+    #[test]
+    fn ramp_takes_the_same_time_at_any_delta_t() {
+        for delta_t in [0.001_f64, 0.01, 0.05] {
+            let mut gain = gain(2.0, 0.2);
+            let ticks = (0.1 / delta_t).round() as usize;
+            for _ in 0..ticks {
+                gain.apply(true, 10000, step(delta_t));
+            }
+            assert!((gain.current() - 1.5).abs() < 1e-3, "{delta_t}");
+            for _ in 0..ticks {
+                gain.apply(true, 10000, step(delta_t));
+            }
+            assert_eq!(gain.apply(true, 10000, step(delta_t)), 20000);
+
+            // Ramps out at the same rate
+            for _ in 0..ticks {
+                gain.apply(false, 10000, step(delta_t));
+            }
+            assert!((gain.current() - 1.5).abs() < 1e-3, "{delta_t}");
+            for _ in 0..ticks {
+                gain.apply(false, 10000, step(delta_t));
+            }
+            assert_eq!(gain.apply(false, 10000, step(delta_t)), 10000);
+        }
+
+        let mut precise = gain(0.5, 0.0);
+        assert_eq!(precise.apply(true, -20000, step(0.01)), -10000);
+        assert_eq!(precise.apply(false, -20000, step(0.01)), -20000);
+    }
+
+    #[test]
+    fn gain_scales_the_inverted_curve_output() {
+        let state = VirtualState::new(1, 0, 1, 0, Default::default());
+        let output = state.axes().next().unwrap();
+        let mut modifier = AxisToAxisModifier::Parameterized {
+            params: AxisParams::new(0.0, 0.0, 1.0, true, 1.0, 0.0, 1),
+        };
+        let mut gain = gain(2.0, 0.0);
+
+        let curved = apply_axis_modifier(10000, output, &mut modifier);
+        assert_eq!(curved, -10000);
+        assert_eq!(gain.apply(true, curved, step(0.01)), -20000);
+
+        // Past the axis limits until the vJoy conversion clamps
+        let curved = apply_axis_modifier(-30000, output, &mut modifier);
+        assert_eq!(gain.apply(true, curved, step(0.01)), 60000);
+    }
+}
@@ -0,0 +1,95 @@
+use egui::{Slider, Ui};
+use egui_extras::Column;
+use serde::{Deserialize, Serialize};
+
+use super::{rebind_table, TABLE_ROW_HEIGHT};
+use crate::input::VirtualDevice;
+
+/// Neutral value of virtual axes without an entry
+pub const AXIS_CENTER: i32 = 16384;
+
+/// Value one virtual axis returns to whenever its output is reset, e.g. 0 for a throttle.
+///
+/// Applies to output inhibition, released mirrored axes, config loads, shutdown and two buttons to
+/// axis rebinds with neither button pressed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct AxisNeutral {
+    pub device: u32,
+    pub axis: u32,
+    /// vJoy range 0..=32767
+    pub value: i32,
+}
+
+impl AxisNeutral {
+    pub fn new(device: u32, axis: u32, value: i32) -> Self {
+        Self {
+            device,
+            axis,
+            value,
+        }
+    }
+}
+
+/// Neutral value of the axis, [`AXIS_CENTER`] if it has no entry
+pub fn neutral_value(neutrals: &[AxisNeutral], device: u32, axis: u32) -> i32 {
+    neutrals
+        .iter()
+        .find(|n| n.device == device && n.axis == axis)
+        .map_or(AXIS_CENTER, |n| n.value)
+}
+
+/// Hands the configured neutral values to the axes of the virtual devices
+pub fn apply_axis_neutrals(neutrals: &[AxisNeutral], virtual_devices: &mut [VirtualDevice]) {
+    for device in virtual_devices.iter_mut() {
+        let id = device.id;
+        for (index, axis) in device.handle.axes_mut().enumerate() {
+            axis.set_neutral(neutral_value(neutrals, id, index as u32 + 1));
+        }
+    }
+}
+
+/// Neutral value per axis of each virtual device, as (id, name, number of axes)
+pub fn axis_neutrals_widget(
+    ui: &mut Ui,
+    neutrals: &mut Vec<AxisNeutral>,
+    virtual_devices: &[(u32, String, u32)],
+) {
+    rebind_table(ui)
+        .column(Column::remainder())
+        .body(|mut body| {
+            for (id, name, num_axes) in virtual_devices.iter() {
+                for axis in 1..=*num_axes {
+                    let current = neutral_value(neutrals, *id, axis);
+                    let mut value = current;
+                    body.row(TABLE_ROW_HEIGHT, |mut row| {
+                        row.col(|ui| {
+                            ui.label(format!("{name} axis {axis}:"));
+                        });
+                        row.col(|ui| {
+                            ui.push_id(("AxisNeutral", id, axis), |ui| {
+                                ui.horizontal(|ui| {
+                                    ui.add(Slider::new(&mut value, 0..=32767));
+                                    if ui.small_button("Min").clicked() {
+                                        value = 0;
+                                    }
+                                    if ui.small_button("Center").clicked() {
+                                        value = AXIS_CENTER;
+                                    }
+                                    if ui.small_button("Max").clicked() {
+                                        value = 32767;
+                                    }
+                                });
+                            });
+                        });
+                    });
+
+                    if value != current {
+                        neutrals.retain(|n| n.device != *id || n.axis != axis);
+                        if value != AXIS_CENTER {
+                            neutrals.push(AxisNeutral::new(*id, axis, value));
+                        }
+                    }
+                }
+            }
+        });
+}
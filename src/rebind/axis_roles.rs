@@ -0,0 +1,190 @@
+use egui::{ComboBox, Grid, Ui};
+use serde::{Deserialize, Serialize};
+use strum::{AsRefStr, EnumIter, IntoEnumIterator};
+
+/// Axes of a vJoy device in their conventional order, `X` is axis 1 of a device with all axes
+/// enabled.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, AsRefStr, EnumIter)]
+pub enum VJoyAxis {
+    X,
+    Y,
+    Z,
+    Rx,
+    Ry,
+    Rz,
+    Slider0,
+    Slider1,
+}
+
+impl VJoyAxis {
+    /// Axis id on a device with all axes enabled
+    pub fn position(self) -> u32 {
+        self as u32 + 1
+    }
+
+    /// Whether `name` is the vJoy display name of this axis, e.g. "Rz" or "Dial" for `Slider1`
+    pub fn matches_name(self, name: &str) -> bool {
+        let name = name.trim().to_ascii_lowercase();
+        let aliases: &[&str] = match self {
+            VJoyAxis::Slider0 => &["slider0", "slider", "sl0"],
+            VJoyAxis::Slider1 => &["slider1", "dial", "sl1"],
+            _ => &[],
+        };
+        name == self.as_ref().to_ascii_lowercase() || aliases.contains(&name.as_str())
+    }
+}
+
+/// What a game expects on a virtual axis
+#[derive(Debug, PartialEq, Eq, Clone, Copy, EnumIter)]
+pub enum AxisRole {
+    Roll,
+    Pitch,
+    Yaw,
+    Throttle,
+    BrakeLeft,
+    BrakeRight,
+}
+
+impl AxisRole {
+    pub fn label(self) -> &'static str {
+        match self {
+            AxisRole::Roll => "Roll",
+            AxisRole::Pitch => "Pitch",
+            AxisRole::Yaw => "Yaw",
+            AxisRole::Throttle => "Throttle",
+            AxisRole::BrakeLeft => "Brake L",
+            AxisRole::BrakeRight => "Brake R",
+        }
+    }
+}
+
+/// vJoy axis per [`AxisRole`], persisted in the app settings. The defaults match what DCS, MSFS
+/// and most other sims expect, users whose games differ change them in the settings.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(default)]
+pub struct AxisRoleTable {
+    pub roll: VJoyAxis,
+    pub pitch: VJoyAxis,
+    pub yaw: VJoyAxis,
+    pub throttle: VJoyAxis,
+    pub brake_left: VJoyAxis,
+    pub brake_right: VJoyAxis,
+}
+
+impl Default for AxisRoleTable {
+    fn default() -> Self {
+        Self {
+            roll: VJoyAxis::X,
+            pitch: VJoyAxis::Y,
+            yaw: VJoyAxis::Rz,
+            throttle: VJoyAxis::Z,
+            brake_left: VJoyAxis::Slider0,
+            brake_right: VJoyAxis::Slider1,
+        }
+    }
+}
+
+impl AxisRoleTable {
+    pub fn get(&self, role: AxisRole) -> VJoyAxis {
+        match role {
+            AxisRole::Roll => self.roll,
+            AxisRole::Pitch => self.pitch,
+            AxisRole::Yaw => self.yaw,
+            AxisRole::Throttle => self.throttle,
+            AxisRole::BrakeLeft => self.brake_left,
+            AxisRole::BrakeRight => self.brake_right,
+        }
+    }
+
+    pub fn get_mut(&mut self, role: AxisRole) -> &mut VJoyAxis {
+        match role {
+            AxisRole::Roll => &mut self.roll,
+            AxisRole::Pitch => &mut self.pitch,
+            AxisRole::Yaw => &mut self.yaw,
+            AxisRole::Throttle => &mut self.throttle,
+            AxisRole::BrakeLeft => &mut self.brake_left,
+            AxisRole::BrakeRight => &mut self.brake_right,
+        }
+    }
+
+    /// Axis id of `role` on a virtual device. vJoy only numbers the enabled axes, so the axis is
+    /// looked up by its display name. Devices without names are assumed to have all axes enabled.
+    pub fn resolve(&self, role: AxisRole, axis_names: &[String], num_axes: usize) -> Option<u32> {
+        let axis = self.get(role);
+        if axis_names.is_empty() {
+            return (axis.position() as usize <= num_axes).then_some(axis.position());
+        }
+        axis_names
+            .iter()
+            .position(|name| axis.matches_name(name))
+            .map(|index| index as u32 + 1)
+    }
+
+    /// Role whose axis is `axis` on the device, if any
+    pub fn role_of(&self, axis: u32, axis_names: &[String], num_axes: usize) -> Option<AxisRole> {
+        AxisRole::iter().find(|role| self.resolve(*role, axis_names, num_axes) == Some(axis))
+    }
+
+    pub fn settings_ui(&mut self, ui: &mut Ui) {
+        ui.label("vJoy axis picked for each role in the axis to axis editor:");
+        Grid::new("axis_role_table").show(ui, |ui| {
+            for role in AxisRole::iter() {
+                ui.label(role.label());
+                let axis = self.get_mut(role);
+                ComboBox::from_id_source(role.label())
+                    .selected_text(axis.as_ref())
+                    .show_ui(ui, |ui| {
+                        for option in VJoyAxis::iter() {
+                            ui.selectable_value(axis, option, option.as_ref());
+                        }
+                    });
+                ui.end_row();
+            }
+        });
+        if ui.button("Default").clicked() {
+            *self = Self::default();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rebind::axis_roles::{AxisRole, AxisRoleTable, VJoyAxis};
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn roles_resolve_by_axis_name() {
+        let table = AxisRoleTable::default();
+        // X, Y, Rz and both sliders enabled, Z, Rx and Ry disabled
+        let axis_names = names(&["X", "Y", "Rz", "Slider", "Dial"]);
+        assert_eq!(table.resolve(AxisRole::Yaw, &axis_names, 5), Some(3));
+        assert_eq!(table.resolve(AxisRole::BrakeLeft, &axis_names, 5), Some(4));
+        assert_eq!(table.resolve(AxisRole::BrakeRight, &axis_names, 5), Some(5));
+        assert_eq!(table.resolve(AxisRole::Throttle, &axis_names, 5), None);
+        assert_eq!(table.role_of(2, &axis_names, 5), Some(AxisRole::Pitch));
+    }
+
+    #[test]
+    fn unnamed_devices_use_the_conventional_order() {
+        let mut table = AxisRoleTable::default();
+        assert_eq!(table.resolve(AxisRole::Yaw, &[], 8), Some(6));
+        assert_eq!(table.resolve(AxisRole::Yaw, &[], 4), None);
+
+        *table.get_mut(AxisRole::Yaw) = VJoyAxis::Rx;
+        assert_eq!(table.resolve(AxisRole::Yaw, &[], 4), Some(4));
+        assert_eq!(table.role_of(6, &[], 8), None);
+    }
+
+    #[test]
+    fn missing_roles_keep_their_default() {
+        let table: AxisRoleTable = toml::from_str("yaw = \"Rx\"").unwrap();
+        assert_eq!(table.yaw, VJoyAxis::Rx);
+        assert_eq!(table.pitch, AxisRoleTable::default().pitch);
+
+        let toml = toml::to_string(&table).unwrap();
+        assert_eq!(toml::from_str::<AxisRoleTable>(&toml).unwrap(), table);
+    }
+}
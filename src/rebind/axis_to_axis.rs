@@ -1,10 +1,16 @@
 use egui::{Checkbox, Slider, Ui};
-use egui_extras::{Column, TableBuilder};
+use egui_extras::Column;
 use serde::{Deserialize, Serialize};
 use strum::{AsRefStr, EnumIter, EnumString, EnumVariantNames};
-use vjoy::Axis;
 
-use super::{TABLE_COLUMN_LEFT_WIDTH, TABLE_ROW_HEIGHT};
+use super::{
+    output_range::OutputRange,
+    output_smoothing::{apply_axis_transition, AxisTransition},
+    rebind_table,
+    rebind_viewer::DevicesInfoMap,
+    TickContext, VariantDescription, TABLE_ROW_HEIGHT,
+};
+use crate::input::{input_state::AxisKind, virtual_state::VirtualAxis};
 
 /// Parameters (inverted, linearity etc.) and filter options for one input axis to single output axis rebinds
 ///
@@ -38,7 +44,21 @@ impl Default for AxisToAxisModifier {
     }
 }
 
+impl VariantDescription for AxisToAxisModifier {
+    fn description(&self) -> &'static str {
+        match self {
+            AxisToAxisModifier::Parameterized { .. } => "Deadzone, clamping, inversion, linearity, offset and averaging applied to the input axis",
+        }
+    }
+}
+
 impl AxisToAxisModifier {
+    pub fn for_axis_kind(kind: AxisKind) -> Self {
+        Self::Parameterized {
+            params: AxisParams::for_axis_kind(kind),
+        }
+    }
+
     pub fn widget(&mut self, ui: &mut Ui) {
         ui.vertical(|ui| match self {
             AxisToAxisModifier::Parameterized { params } => {
@@ -46,6 +66,27 @@ impl AxisToAxisModifier {
             }
         });
     }
+
+    pub fn debug_state(&self) -> Vec<(String, String)> {
+        match self {
+            AxisToAxisModifier::Parameterized { params } => params.debug_state(),
+        }
+    }
+
+    pub fn take_runtime_state(&mut self, from: &mut Self) {
+        match (self, from) {
+            (
+                AxisToAxisModifier::Parameterized { params },
+                AxisToAxisModifier::Parameterized { params: from },
+            ) => params.take_runtime_state(from),
+        }
+    }
+
+    pub fn output_range(&self) -> OutputRange {
+        match self {
+            AxisToAxisModifier::Parameterized { params } => params.output_range(),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
@@ -57,10 +98,20 @@ pub struct AxisParams {
     linearity: f32, //Sensitivity around x=0. > 1.0 => less sensitive. < 1.0 => more sensitive. Graph: https://www.desmos.com/calculator/utdryphfaa
     offset: f32,
     avg_filter: usize,
+    /// Number of discrete output levels after the curve, 0 disables quantization
+    #[serde(default)]
+    quantize_steps: u8,
+    /// Extra distance past the midpoint between two levels, in steps, before the level changes
+    #[serde(default)]
+    quantize_hysteresis: f32,
 
     #[serde(skip_serializing)]
     #[serde(default)]
     avg_data: (usize, Vec<i32>),
+
+    #[serde(skip_serializing)]
+    #[serde(default)]
+    quantize_level: Option<u8>,
 }
 
 impl Default for AxisParams {
@@ -73,12 +124,59 @@ impl Default for AxisParams {
             linearity: 1.0,
             offset: 0.0,
             avg_filter: 1,
+            quantize_steps: 0,
+            quantize_hysteresis: 0.0,
             avg_data: (0, Vec::new()),
+            quantize_level: None,
         }
     }
 }
 
 impl AxisParams {
+    /// Sticks get a small center deadzone. Triggers get none, map their rest position to the
+    /// minimum and ignore the first few percent of travel.
+    pub fn for_axis_kind(kind: AxisKind) -> Self {
+        match kind {
+            AxisKind::Stick => Self {
+                deadzone_center: 0.02,
+                ..Default::default()
+            },
+            AxisKind::Trigger { rest_at_max } => Self {
+                clamp_min: 0.02,
+                invert: rest_at_max,
+                ..Default::default()
+            },
+            AxisKind::Slider => Self::default(),
+        }
+    }
+
+    pub fn debug_state(&self) -> Vec<(String, String)> {
+        let (next, buffer) = &self.avg_data;
+        vec![
+            (
+                "avg_data".to_string(),
+                format!(
+                    "{}/{} filled, next slot {next}",
+                    buffer.len(),
+                    self.avg_filter
+                ),
+            ),
+            (
+                "quantize_level".to_string(),
+                match self.quantize_level {
+                    Some(level) => level.to_string(),
+                    None => "none".to_string(),
+                },
+            ),
+        ]
+    }
+
+    /// The averaging buffer is resized on the next tick if `avg_filter` changed
+    pub fn take_runtime_state(&mut self, from: &mut Self) {
+        self.avg_data = std::mem::take(&mut from.avg_data);
+        self.quantize_level = from.quantize_level;
+    }
+
     pub fn new(
         deadzone_center: f32,
         clamp_min: f32,
@@ -100,9 +198,60 @@ impl AxisParams {
         }
     }
 
+    /// Inverted, deadzone and clamping applied to `input`, then offset. The offset can move the
+    /// value out of the axis range.
+    fn offset_value(&self, input: f64) -> f64 {
+        let inverted_value = if self.invert { input * -1.0 } else { input };
+
+        let deadzone_center = self.deadzone_center as f64;
+        let deadzone_center_min = -32768.0 * deadzone_center;
+        let deadzone_center_max = 32767.0 * deadzone_center;
+        let deadzone_clamped_value =
+            if inverted_value >= deadzone_center_min && inverted_value <= deadzone_center_max {
+                0.0
+            } else {
+                inverted_value
+            };
+
+        let clamp_min = -32768.0 + 32768.0 * self.clamp_min as f64;
+        let clamp_max = 32767.0 * self.clamp_max as f64;
+        let minmax_clamped_value = if deadzone_clamped_value <= clamp_min {
+            -32768.0
+        } else if deadzone_clamped_value >= clamp_max {
+            32767.0
+        } else {
+            deadzone_clamped_value
+        };
+
+        minmax_clamped_value + (32767.0 * self.offset as f64)
+    }
+
+    /// Linearity curve applied to an offset value, limited to the axis range
+    fn curve(&self, offset_value: f64) -> i32 {
+        let linearity_value = offset_value.signum()
+            * (offset_value / 32767.0).abs().powf(self.linearity as f64)
+            * 32767.0;
+
+        linearity_value.floor().clamp(-32768.0, 32767.0) as i32
+    }
+
+    /// Achievable output without averaging, everything the offset pushes past the axis limits is
+    /// clipped
+    pub fn output_range(&self) -> OutputRange {
+        OutputRange::sampled(|input| {
+            let offset_value = self.offset_value(input as f64);
+            let value = quantize_axis(
+                self.curve(offset_value),
+                self.quantize_steps,
+                0.0,
+                &mut None,
+            );
+            (offset_value, value)
+        })
+    }
+
     pub fn widget(&mut self, ui: &mut Ui) {
-        TableBuilder::new(ui)
-            .column(Column::exact(TABLE_COLUMN_LEFT_WIDTH))
+        rebind_table(ui)
             .column(Column::remainder())
             .body(|mut body| {
                 body.row(TABLE_ROW_HEIGHT, |mut row| {
@@ -161,16 +310,206 @@ impl AxisParams {
                         ui.add(Slider::new(&mut self.avg_filter, 1..=32).integer());
                     });
                 });
+                body.row(TABLE_ROW_HEIGHT, |mut row| {
+                    row.col(|ui| {
+                        ui.label("Quantize steps:");
+                    });
+                    row.col(|ui| {
+                        ui.add(Slider::new(&mut self.quantize_steps, 0..=16))
+                            .on_hover_text("Discrete output levels, e.g. flap detents. 0 is off");
+                    });
+                });
+                body.row(TABLE_ROW_HEIGHT, |mut row| {
+                    row.col(|ui| {
+                        ui.label("Step hysteresis:");
+                    });
+                    row.col(|ui| {
+                        ui.add_enabled(
+                            self.quantize_steps >= 2,
+                            Slider::new(&mut self.quantize_hysteresis, 0.0..=0.45),
+                        )
+                        .on_hover_text(
+                            "Travel past the midpoint before the level changes, in steps",
+                        );
+                    });
+                });
+                if self.quantize_steps > 0 {
+                    body.row(TABLE_ROW_HEIGHT, |mut row| {
+                        row.col(|ui| {
+                            ui.label("Output levels:");
+                        });
+                        row.col(|ui| {
+                            let levels: Vec<String> = quantize_levels(self.quantize_steps)
+                                .into_iter()
+                                .map(|level| {
+                                    let vjoy = convert_axis_to_vjoy_range(level);
+                                    format!("{:.0}%", vjoy as f32 / 32767.0 * 100.0)
+                                })
+                                .collect();
+                            ui.label(levels.join(", "));
+                        });
+                    });
+                }
             });
     }
 }
 
-// input range -32768..=32767
-pub fn apply_axis_modifier(input: i32, _output: &Axis, modifier: &mut AxisToAxisModifier) -> i32 {
+/// Holds the output of an axis to axis rebind while a physical button is held.
+///
+/// On release the output either jumps back to the source or ramps to it over `resume_duration` seconds.
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct AxisFreeze {
+    pub device: String,
+    pub button: u32,
+    pub resume_duration: f64,
+
+    #[serde(skip)]
+    state: AxisFreezeState,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+enum AxisFreezeState {
+    #[default]
+    Tracking,
+    Frozen {
+        value: i32,
+    },
+    Resuming {
+        transition: AxisTransition,
+    },
+}
+
+impl AxisFreeze {
+    pub fn new(device: String, button: u32, resume_duration: f64) -> Self {
+        Self {
+            device,
+            button,
+            resume_duration,
+            ..Default::default()
+        }
+    }
+
+    /// False until a device and a button are picked. An unset freeze is inactive, so a freshly
+    /// enabled one doesn't fail the rebind.
+    pub fn is_set(&self) -> bool {
+        !self.device.is_empty() && self.button != 0
+    }
+
+    /// Captured value while frozen
+    pub fn frozen_value(&self) -> Option<i32> {
+        match self.state {
+            AxisFreezeState::Frozen { value } => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Drops a captured value, e.g. when the rebind is inactive in the current shift mode.
+    pub fn reset(&mut self) {
+        self.state = AxisFreezeState::Tracking;
+    }
+
+    pub fn debug_state(&self) -> Vec<(String, String)> {
+        let state = match self.state {
+            AxisFreezeState::Tracking => "tracking".to_string(),
+            AxisFreezeState::Frozen { value } => format!("frozen at {value}"),
+            AxisFreezeState::Resuming { transition } => format!(
+                "resuming from {} since {:.3} s",
+                transition.from, transition.start
+            ),
+        };
+        vec![("freeze".to_string(), state)]
+    }
+
+    pub fn take_runtime_state(&mut self, from: &mut Self) {
+        self.state = from.state;
+    }
+
+    // value range -32768..=32767
+    pub fn apply(&mut self, held: bool, value: i32, ctx: TickContext) -> i32 {
+        let time = ctx.time;
+        match (self.state, held) {
+            (AxisFreezeState::Frozen { value: frozen }, true) => frozen,
+            (_, true) => {
+                // Pressing during a resume ramp freezes the ramped value, not the source
+                let captured = self.ramped(value, time).unwrap_or(value);
+                self.state = AxisFreezeState::Frozen { value: captured };
+                captured
+            }
+            (AxisFreezeState::Frozen { value: frozen }, false) => {
+                self.state = AxisFreezeState::Resuming {
+                    transition: AxisTransition {
+                        from: frozen,
+                        start: time,
+                    },
+                };
+                self.ramped(value, time).unwrap_or(value)
+            }
+            (_, false) => self.ramped(value, time).unwrap_or(value),
+        }
+    }
+
+    fn ramped(&mut self, value: i32, time: f64) -> Option<i32> {
+        let AxisFreezeState::Resuming { transition } = self.state else {
+            return None;
+        };
+
+        let ramped = apply_axis_transition(&transition, value, self.resume_duration, time);
+        if ramped.is_none() {
+            self.state = AxisFreezeState::Tracking;
+        }
+        ramped
+    }
+
+    pub fn widget(&mut self, ui: &mut Ui, devices_info_map: &DevicesInfoMap) {
+        rebind_table(ui)
+            .column(Column::remainder())
+            .body(|mut body| {
+                body.row(TABLE_ROW_HEIGHT, |mut row| {
+                    row.col(|ui| {
+                        ui.label("Freeze device:");
+                    });
+                    row.col(|ui| {
+                        ui.push_id("FreezeDevice", |ui| {
+                            devices_info_map.physical_devices_widget(ui, &mut self.device);
+                        });
+                    });
+                });
+                body.row(TABLE_ROW_HEIGHT, |mut row| {
+                    row.col(|ui| {
+                        ui.label("Freeze button:");
+                    });
+                    row.col(|ui| {
+                        ui.push_id("FreezeButton", |ui| {
+                            devices_info_map.physical_button_widget(
+                                ui,
+                                &self.device,
+                                &mut self.button,
+                            );
+                        });
+                    });
+                });
+                body.row(TABLE_ROW_HEIGHT, |mut row| {
+                    row.col(|ui| {
+                        ui.label("Resume ramp (s):");
+                    });
+                    row.col(|ui| {
+                        ui.add(Slider::new(&mut self.resume_duration, 0.0..=5.0));
+                    });
+                });
+            });
+    }
+}
+
+// input range -32768..=32767, output clamped to the same range for any i32 input
+pub fn apply_axis_modifier(
+    input: i32,
+    _output: &VirtualAxis,
+    modifier: &mut AxisToAxisModifier,
+) -> i32 {
     match modifier {
         //TODO: deadzone jumping --> scale value inside deadzone
         AxisToAxisModifier::Parameterized { params } => {
-            let input_f32 = {
+            let input_f64 = {
                 if params.avg_filter != params.avg_data.1.len() {
                     params.avg_data.0 = 0;
                     params.avg_data.1.resize(params.avg_filter, 0);
@@ -190,52 +529,270 @@ pub fn apply_axis_modifier(input: i32, _output: &Axis, modifier: &mut AxisToAxis
                     *head = 0;
                 }
 
-                let count = data.len() as f32;
-                let sum = data.iter().sum::<i32>() as f32;
+                let count = data.len() as f64;
+                let sum = data.iter().map(|v| *v as i64).sum::<i64>() as f64;
                 sum / count
             };
 
-            let inverted_value = if params.invert {
-                input_f32 * -1.0
-            } else {
-                input_f32
-            };
+            let value = params.curve(params.offset_value(input_f64));
+            quantize_axis(
+                value,
+                params.quantize_steps,
+                params.quantize_hysteresis,
+                &mut params.quantize_level,
+            )
+        }
+    }
+}
 
-            let deadzone_center_min = -32768.0 * params.deadzone_center;
-            let deadzone_center_max = 32767.0 * params.deadzone_center;
-            let deadzone_clamped_value =
-                if inverted_value >= deadzone_center_min && inverted_value <= deadzone_center_max {
-                    0.0
-                } else {
-                    inverted_value
-                };
+/// Output values of `steps` evenly spaced levels, the center for a single level
+pub fn quantize_levels(steps: u8) -> Vec<i32> {
+    match steps {
+        0 => Vec::new(),
+        1 => vec![0],
+        n => (0..n)
+            .map(|k| (-32767.5 + 65535.0 * k as f64 / (n - 1) as f64).floor() as i32)
+            .collect(),
+    }
+}
 
-            let clamp_min = -32768.0 + 32768.0 * params.clamp_min;
-            let clamp_max = 32767.0 * params.clamp_max;
-            let minmax_clamped_value = if deadzone_clamped_value <= clamp_min {
-                -32768.0
-            } else if deadzone_clamped_value >= clamp_max {
-                32767.0
-            } else {
-                deadzone_clamped_value
+/// Snaps `value` to the nearest of `steps` levels. The current `level` only changes once the
+/// input is more than `hysteresis` steps past the midpoint to the next level.
+// value range -32768..=32767
+pub fn quantize_axis(value: i32, steps: u8, hysteresis: f32, level: &mut Option<u8>) -> i32 {
+    if steps == 0 {
+        *level = None;
+        return value;
+    }
+
+    let levels = quantize_levels(steps);
+    let position = (value as f64 + 32768.0) / 65535.0 * (steps - 1) as f64;
+    let nearest = (position.round() as u8).min(steps - 1);
+    let next = match *level {
+        Some(current)
+            if current < steps
+                && (position - current as f64).abs() <= 0.5 + hysteresis.max(0.0) as f64 =>
+        {
+            current
+        }
+        _ => nearest,
+    };
+    *level = Some(next);
+    levels[next as usize]
+}
+
+/// Maps `input` from `low1..=high1` to `low2..=high2`, rounded to the nearest value. Inputs out of
+/// range are clamped first, so both directions round-trip from the smaller range.
+fn map_range(input: i32, (low1, high1): (i64, i64), (low2, high2): (i64, i64)) -> i32 {
+    let input = (input as i64).clamp(low1, high1);
+    let span1 = high1 - low1;
+    let mapped_value = low2 + (2 * (input - low1) * (high2 - low2) + span1) / (2 * span1);
+    mapped_value.clamp(low2, high2) as i32
+}
+
+pub fn convert_axis_to_vjoy_range(input: i32) -> i32 {
+    map_range(input, (-32768, 32767), (0, 32767))
+}
+
+pub fn convert_vjoy_axis_to_physical_range(input: i32) -> i32 {
+    map_range(input, (0, 32767), (-32768, 32767))
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::{
+        input::virtual_state::VirtualState,
+        rebind::{
+            axis_to_axis::{
+                apply_axis_modifier, convert_axis_to_vjoy_range,
+                convert_vjoy_axis_to_physical_range, quantize_axis, quantize_levels, AxisFreeze,
+                AxisParams, AxisToAxisModifier,
+            },
+            merge_axes::{apply_merge_axes_modifier, MergeAxesModifier},
+            TickContext,
+        },
+    };
+
+    /// Any combination the parameter widgets allow
+    fn axis_params() -> impl Strategy<Value = AxisParams> {
+        (
+            0.0f32..=1.0,
+            0.0f32..=1.0,
+            0.0f32..=1.0,
+            any::<bool>(),
+            0.1f32..=10.0,
+            -1.0f32..=1.0,
+            1usize..=32,
+            0u8..=16,
+            0.0f32..=0.45,
+        )
+            .prop_map(
+                |(deadzone, min, max, invert, linearity, offset, avg, steps, hysteresis)| {
+                    AxisParams {
+                        quantize_steps: steps,
+                        quantize_hysteresis: hysteresis,
+                        ..AxisParams::new(deadzone, min, max, invert, linearity, offset, avg)
+                    }
+                },
+            )
+    }
+
+    fn apply(params: &mut AxisParams, input: i32) -> i32 {
+        let state = VirtualState::new(1, 0, 1, 0, Default::default());
+        let mut modifier = AxisToAxisModifier::Parameterized {
+            params: params.clone(),
+        };
+        let value = apply_axis_modifier(input, state.axes().next().unwrap(), &mut modifier);
+        let AxisToAxisModifier::Parameterized { params: updated } = modifier;
+        *params = updated;
+        value
+    }
+
+    proptest! {
+        #[test]
+        fn output_stays_in_range_for_any_input(
+            mut params in axis_params(),
+            inputs in prop::collection::vec(any::<i32>(), 1..64),
+        ) {
+            for input in inputs {
+                let value = apply(&mut params, input);
+                prop_assert!((-32768..=32767).contains(&value));
+                prop_assert!((0..=32767).contains(&convert_axis_to_vjoy_range(value)));
+            }
+        }
+
+        #[test]
+        fn curve_is_monotonic_without_invert_and_deadzone(
+            params in axis_params(),
+            a in any::<i32>(),
+            b in any::<i32>(),
+        ) {
+            let params = AxisParams {
+                deadzone_center: 0.0,
+                invert: false,
+                avg_filter: 1,
+                ..params
             };
+            let (low, high) = (a.min(b), a.max(b));
+            let low = convert_axis_to_vjoy_range(apply(&mut params.clone(), low));
+            let high = convert_axis_to_vjoy_range(apply(&mut params.clone(), high));
+            prop_assert!(low <= high, "{low} > {high}");
+        }
 
-            let offset_value = minmax_clamped_value + (32767.0 * params.offset);
+        #[test]
+        fn held_input_settles_on_one_output(mut params in axis_params(), input in any::<i32>()) {
+            for _ in 0..params.avg_filter {
+                apply(&mut params, input);
+            }
+            let settled = apply(&mut params, input);
+            prop_assert_eq!(apply(&mut params, input), settled);
+        }
 
-            let linearity_value = offset_value.signum()
-                * (offset_value / 32767.0).abs().powf(params.linearity)
-                * 32767.0;
+        #[test]
+        fn merged_output_stays_in_range(a in any::<i32>(), b in any::<i32>()) {
+            let merged = apply_merge_axes_modifier(a, b, &mut MergeAxesModifier::Add);
+            prop_assert!((0..=32767).contains(&convert_axis_to_vjoy_range(merged)));
+        }
 
-            linearity_value.floor() as i32
+        #[test]
+        fn vjoy_values_round_trip_through_the_physical_range(value in 0..=32767i32) {
+            let physical = convert_vjoy_axis_to_physical_range(value);
+            prop_assert_eq!(convert_axis_to_vjoy_range(physical), value);
         }
     }
-}
 
-pub fn convert_axis_to_vjoy_range(input: i32) -> i32 {
-    let low1 = -32768_i64;
-    let high1 = 32767_i64;
-    let low2 = 0_i64;
-    let high2 = 32767_i64;
-    let mapped_value = low2 + (input as i64 - low1) * (high2 - low2) / (high1 - low1);
-    mapped_value.clamp(low2, high2) as i32
+    #[test]
+    fn conversion_keeps_the_range_ends_and_center() {
+        assert_eq!(convert_axis_to_vjoy_range(i32::MIN), 0);
+        assert_eq!(convert_axis_to_vjoy_range(-32768), 0);
+        assert_eq!(convert_axis_to_vjoy_range(0), 16384);
+        assert_eq!(convert_axis_to_vjoy_range(32767), 32767);
+        assert_eq!(convert_axis_to_vjoy_range(i32::MAX), 32767);
+        assert_eq!(convert_vjoy_axis_to_physical_range(0), -32768);
+        assert_eq!(convert_vjoy_axis_to_physical_range(32767), 32767);
+    }
+
+    #[test]
+    fn average_of_extreme_inputs_does_not_overflow() {
+        let mut params = AxisParams::new(0.0, 0.0, 1.0, false, 1.0, 0.0, 4);
+        for _ in 0..4 {
+            assert_eq!(apply(&mut params, i32::MAX), 32767);
+        }
+    }
+
+    #[test]
+    fn quantize_snaps_at_midpoints() {
+        assert_eq!(quantize_levels(3), vec![-32768, 0, 32767]);
+
+        let mut level = None;
+        let mut quantize = |value| quantize_axis(value, 3, 0.0, &mut level);
+        assert_eq!(quantize(-32768), -32768);
+        assert_eq!(quantize(-16385), -32768);
+        assert_eq!(quantize(-16383), 0);
+        assert_eq!(quantize(16383), 0);
+        assert_eq!(quantize(16385), 32767);
+        assert_eq!(quantize(32767), 32767);
+    }
+
+    #[test]
+    fn quantize_hysteresis_holds_level_past_midpoint() {
+        let mut level = None;
+        // Steps are 32767.5 apart, 0.2 steps hysteresis is 6553.5
+        assert_eq!(quantize_axis(0, 3, 0.2, &mut level), 0);
+        assert_eq!(quantize_axis(20000, 3, 0.2, &mut level), 0);
+        assert_eq!(quantize_axis(23000, 3, 0.2, &mut level), 32767);
+        assert_eq!(quantize_axis(12000, 3, 0.2, &mut level), 32767);
+        assert_eq!(quantize_axis(9000, 3, 0.2, &mut level), 0);
+    }
+
+    #[test]
+    fn quantize_single_step_is_constant_and_zero_steps_is_off() {
+        let mut level = None;
+        for value in [-32768, -100, 0, 32767] {
+            assert_eq!(quantize_axis(value, 1, 0.0, &mut level), 0);
+            assert_eq!(quantize_axis(value, 0, 0.3, &mut level), value);
+        }
+        assert_eq!(level, None);
+    }
+
+    fn at(time: f64) -> TickContext {
+        TickContext {
+            time,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn press_during_motion_holds_captured_value() {
+        let mut freeze = AxisFreeze::new("guid".to_string(), 1, 0.0);
+        assert_eq!(freeze.apply(false, -1000, at(0.0)), -1000);
+        assert_eq!(freeze.apply(true, 2000, at(0.1)), 2000);
+        assert_eq!(freeze.apply(true, 8000, at(0.2)), 2000);
+        assert_eq!(freeze.apply(true, -30000, at(0.3)), 2000);
+        assert_eq!(freeze.frozen_value(), Some(2000));
+
+        assert_eq!(freeze.apply(false, -30000, at(0.4)), -30000);
+        assert_eq!(freeze.frozen_value(), None);
+    }
+
+    #[test]
+    fn release_after_large_move_ramps_to_source() {
+        let mut freeze = AxisFreeze::new("guid".to_string(), 1, 1.0);
+        freeze.apply(true, 0, at(0.0));
+        freeze.apply(true, 30000, at(5.0));
+
+        assert_eq!(freeze.apply(false, 30000, at(10.0)), 0);
+        assert_eq!(freeze.apply(false, 30000, at(10.5)), 15000);
+        assert_eq!(freeze.apply(false, 20000, at(10.75)), 15000);
+        assert_eq!(freeze.apply(false, 20000, at(11.0)), 20000);
+        assert_eq!(freeze.apply(false, -5000, at(11.1)), -5000);
+
+        // Pressing again mid-ramp freezes the ramped value
+        freeze.apply(true, 0, at(12.0));
+        freeze.apply(false, 10000, at(13.0));
+        assert_eq!(freeze.apply(true, 10000, at(13.5)), 5000);
+        assert_eq!(freeze.apply(true, 10000, at(14.0)), 5000);
+    }
 }
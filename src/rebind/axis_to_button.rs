@@ -0,0 +1,320 @@
+use egui::{Slider, Ui};
+use egui_extras::Column;
+use serde::{Deserialize, Serialize};
+use strum::{AsRefStr, EnumIter, EnumString, EnumVariantNames};
+
+use super::{rebind_table, VariantDescription, TABLE_ROW_HEIGHT};
+
+/// Activation type and conditions for single input axis to output button rebinds.
+/// Thresholds are normalized to -1..1 of the physical axis range.
+///
+/// ## Examples usages
+/// - Rebind a throttle to 'afterburner' --> pressed past 90%, released only below 80% so a noisy
+///   axis doesn't chatter at the threshold.
+/// - Rebind a throttle with detents to 'reverse' and 'afterburner' --> low region presses the
+///   first button, high region the second one, nothing in between.
+#[derive(
+    Debug,
+    PartialEq,
+    Clone,
+    Serialize,
+    Deserialize,
+    AsRefStr,
+    EnumIter,
+    EnumString,
+    EnumVariantNames,
+)]
+#[serde(tag = "modifier")]
+pub enum AxisToButtonModifier {
+    /// Pressed at or above `threshold`, released below `threshold - hysteresis`.
+    Threshold {
+        threshold: f64,
+        hysteresis: f64,
+
+        #[serde(skip_serializing)]
+        #[serde(default)]
+        pressed: bool,
+    },
+    /// The destination button is pressed at or below `low`, `high_button` at or above `high`.
+    /// Each one is released `hysteresis` back towards the middle.
+    Detents {
+        low: f64,
+        high: f64,
+        hysteresis: f64,
+        high_button: u32,
+
+        #[serde(skip_serializing)]
+        #[serde(default)]
+        low_pressed: bool,
+
+        #[serde(skip_serializing)]
+        #[serde(default)]
+        high_pressed: bool,
+    },
+}
+
+impl Default for AxisToButtonModifier {
+    fn default() -> Self {
+        Self::Threshold {
+            threshold: 0.5,
+            hysteresis: 0.05,
+            pressed: false,
+        }
+    }
+}
+
+impl VariantDescription for AxisToButtonModifier {
+    fn description(&self) -> &'static str {
+        match self {
+            AxisToButtonModifier::Threshold { .. } => {
+                "Button is pressed past the threshold and released a hysteresis band below it"
+            }
+            AxisToButtonModifier::Detents { .. } => {
+                "Low region presses the first button and high region the second, e.g. throttle detents"
+            }
+        }
+    }
+}
+
+impl AxisToButtonModifier {
+    pub fn widget(&mut self, ui: &mut Ui) {
+        ui.vertical(|ui| match self {
+            AxisToButtonModifier::Threshold {
+                threshold,
+                hysteresis,
+                pressed: _,
+            } => {
+                rebind_table(ui)
+                    .column(Column::remainder())
+                    .body(|mut body| {
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Threshold:");
+                            });
+                            row.col(|ui| {
+                                ui.push_id("Threshold", |ui| {
+                                    ui.add(Slider::new(threshold, -1.0..=1.0));
+                                });
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Hysteresis:").on_hover_text(
+                                    "How far the axis has to fall back below the threshold to release",
+                                );
+                            });
+                            row.col(|ui| {
+                                ui.push_id("Hysteresis", |ui| {
+                                    ui.add(Slider::new(hysteresis, 0.0..=1.0));
+                                });
+                            });
+                        });
+                    });
+            }
+            AxisToButtonModifier::Detents {
+                low,
+                high,
+                hysteresis,
+                ..
+            } => {
+                rebind_table(ui)
+                    .column(Column::remainder())
+                    .body(|mut body| {
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Low:");
+                            });
+                            row.col(|ui| {
+                                ui.push_id("Low", |ui| {
+                                    ui.add(Slider::new(low, -1.0..=1.0));
+                                });
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("High:");
+                            });
+                            row.col(|ui| {
+                                ui.push_id("High", |ui| {
+                                    ui.add(Slider::new(high, -1.0..=1.0));
+                                });
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Hysteresis:").on_hover_text(
+                                    "How far the axis has to move back towards the middle to release",
+                                );
+                            });
+                            row.col(|ui| {
+                                ui.push_id("Hysteresis", |ui| {
+                                    ui.add(Slider::new(hysteresis, 0.0..=1.0));
+                                });
+                            });
+                        });
+                    });
+            }
+        });
+    }
+
+    /// Second destination button of [`AxisToButtonModifier::Detents`]
+    pub fn high_button(&self) -> Option<u32> {
+        match self {
+            AxisToButtonModifier::Threshold { .. } => None,
+            AxisToButtonModifier::Detents { high_button, .. } => Some(*high_button),
+        }
+    }
+
+    pub fn high_button_mut(&mut self) -> Option<&mut u32> {
+        match self {
+            AxisToButtonModifier::Threshold { .. } => None,
+            AxisToButtonModifier::Detents { high_button, .. } => Some(high_button),
+        }
+    }
+
+    pub fn debug_state(&self) -> Vec<(String, String)> {
+        match self {
+            AxisToButtonModifier::Threshold { pressed, .. } => {
+                vec![("pressed".to_string(), pressed.to_string())]
+            }
+            AxisToButtonModifier::Detents {
+                low_pressed,
+                high_pressed,
+                ..
+            } => vec![
+                ("low_pressed".to_string(), low_pressed.to_string()),
+                ("high_pressed".to_string(), high_pressed.to_string()),
+            ],
+        }
+    }
+
+    pub fn take_runtime_state(&mut self, from: &mut Self) {
+        match (self, from) {
+            (
+                AxisToButtonModifier::Threshold { pressed, .. },
+                AxisToButtonModifier::Threshold { pressed: from, .. },
+            ) => *pressed = *from,
+            (
+                AxisToButtonModifier::Detents {
+                    low_pressed,
+                    high_pressed,
+                    ..
+                },
+                AxisToButtonModifier::Detents {
+                    low_pressed: from_low,
+                    high_pressed: from_high,
+                    ..
+                },
+            ) => {
+                *low_pressed = *from_low;
+                *high_pressed = *from_high;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Physical axis value as -1..1
+fn normalize_axis(value: i32) -> f64 {
+    (value as f64 / 32767.0).clamp(-1.0, 1.0)
+}
+
+/// Whether the destination button and, for detents, the high button are pressed for the physical
+/// axis value `input`
+pub fn apply_axis_to_button_modifier(
+    input: i32,
+    modifier: &mut AxisToButtonModifier,
+) -> (bool, Option<bool>) {
+    let value = normalize_axis(input);
+    match modifier {
+        AxisToButtonModifier::Threshold {
+            threshold,
+            hysteresis,
+            pressed,
+        } => {
+            *pressed = match *pressed {
+                true => value >= *threshold - *hysteresis,
+                false => value >= *threshold,
+            };
+            (*pressed, None)
+        }
+        AxisToButtonModifier::Detents {
+            low,
+            high,
+            hysteresis,
+            low_pressed,
+            high_pressed,
+            ..
+        } => {
+            *low_pressed = match *low_pressed {
+                true => value <= *low + *hysteresis,
+                false => value <= *low,
+            };
+            *high_pressed = match *high_pressed {
+                true => value >= *high - *hysteresis,
+                false => value >= *high,
+            };
+            (*low_pressed, Some(*high_pressed))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rebind::axis_to_button::{apply_axis_to_button_modifier, AxisToButtonModifier};
+
+    fn run(modifier: &mut AxisToButtonModifier, inputs: &[i32]) -> Vec<(bool, Option<bool>)> {
+        inputs
+            .iter()
+            .map(|&input| apply_axis_to_button_modifier(input, modifier))
+            .collect()
+    }
+
+    #[test]
+    fn threshold_releases_below_the_hysteresis_band() {
+        let mut modifier = AxisToButtonModifier::Threshold {
+            threshold: 0.5,
+            hysteresis: 0.1,
+            pressed: false,
+        };
+        // Noise around the threshold doesn't chatter once pressed
+        let pressed: Vec<bool> = run(
+            &mut modifier,
+            &[0, 16000, 16500, 15000, 16500, 13000, 15000],
+        )
+        .into_iter()
+        .map(|(pressed, high)| {
+            assert_eq!(high, None);
+            pressed
+        })
+        .collect();
+        assert_eq!(pressed, [false, false, true, true, true, false, false]);
+    }
+
+    #[test]
+    fn detents_press_low_and_high_regions() {
+        let mut modifier = AxisToButtonModifier::Detents {
+            low: -0.8,
+            high: 0.8,
+            hysteresis: 0.1,
+            high_button: 2,
+            low_pressed: false,
+            high_pressed: false,
+        };
+        let states = run(
+            &mut modifier,
+            &[i16::MIN as i32, -24000, -22000, 0, 27000, 22000],
+        );
+        assert_eq!(
+            states,
+            vec![
+                (true, Some(false)),
+                (true, Some(false)),
+                (false, Some(false)),
+                (false, Some(false)),
+                (false, Some(true)),
+                (false, Some(false)),
+            ]
+        );
+    }
+}
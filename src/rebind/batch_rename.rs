@@ -0,0 +1,504 @@
+use std::collections::{BTreeSet, HashMap};
+
+use egui::{Button, Context, Grid, RichText, ScrollArea, TextEdit, Ui};
+use log::info;
+
+use super::{rebind_id::RebindId, shift_mode_mask::ShiftModeMask, Rebind, RebindScope};
+use crate::{input::Input, ui_data::UIData};
+
+/// Rebinds of the config a batch rename applies to
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum RenameScope {
+    #[default]
+    All,
+    /// Rebinds shown under the current mode filter of the rebind list
+    Filtered,
+    /// Rebinds expanded in the rebind list
+    Expanded,
+}
+
+impl RenameScope {
+    const ALL: [RenameScope; 3] = [
+        RenameScope::All,
+        RenameScope::Filtered,
+        RenameScope::Expanded,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            RenameScope::All => "All",
+            RenameScope::Filtered => "Filtered",
+            RenameScope::Expanded => "Expanded",
+        }
+    }
+
+    /// Whether `rebind` is in scope under the mode `filter` and `expanded` rebinds of the list
+    pub fn contains(
+        self,
+        rebind: &Rebind,
+        filter: Option<ShiftModeMask>,
+        expanded: &BTreeSet<String>,
+    ) -> bool {
+        match self {
+            RenameScope::All => true,
+            RenameScope::Filtered => match filter {
+                Some(filter) => rebind.is_active(filter),
+                None => true,
+            },
+            RenameScope::Expanded => expanded.contains(&rebind.name),
+        }
+    }
+
+    pub fn widget(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Rebinds:");
+            for scope in RenameScope::ALL {
+                ui.selectable_value(self, scope, scope.name());
+            }
+        });
+    }
+}
+
+/// Find and replace on the names, and optionally the notes, of the config's rebinds.
+///
+/// The last rename can be undone for the rebinds that weren't edited since.
+#[derive(Debug, Default)]
+pub struct BatchRename {
+    pub open: bool,
+    pub find: String,
+    pub replace: String,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    pub include_notes: bool,
+    pub scope: RenameScope,
+    pub undo: Vec<RenameUndo>,
+}
+
+/// Name and notes of one rebind before and after the last rename
+#[derive(Debug, PartialEq, Clone)]
+pub struct RenameUndo {
+    id: RebindId,
+    old_name: String,
+    new_name: String,
+    old_notes: Option<String>,
+    new_notes: Option<String>,
+}
+
+impl RenameUndo {
+    fn name_applies(&self, rebind: &Rebind) -> bool {
+        rebind.id == self.id && self.new_name != self.old_name && rebind.name == self.new_name
+    }
+
+    fn notes_apply(&self, rebind: &Rebind) -> bool {
+        rebind.id == self.id && self.new_notes != self.old_notes && rebind.notes == self.new_notes
+    }
+}
+
+/// Whether any rebind still has a name or notes from the last rename
+pub fn can_undo(undo: &[RenameUndo], rebinds: &[Rebind]) -> bool {
+    undo.iter().any(|undo| {
+        rebinds
+            .iter()
+            .any(|rebind| undo.name_applies(rebind) || undo.notes_apply(rebind))
+    })
+}
+
+/// Restores the names and notes of the last rename that weren't edited since, returns the
+/// restored names as (new name, old name)
+pub fn undo_renames<'a>(
+    undo: &[RenameUndo],
+    rebinds: impl Iterator<Item = &'a mut Rebind>,
+) -> Vec<(String, String)> {
+    let mut restored = Vec::new();
+    for rebind in rebinds {
+        let Some(undo) = undo.iter().find(|undo| undo.id == rebind.id) else {
+            continue;
+        };
+        if undo.notes_apply(rebind) {
+            rebind.notes = undo.old_notes.clone();
+        }
+        if undo.name_applies(rebind) {
+            rebind.name = undo.old_name.clone();
+            restored.push((undo.new_name.clone(), undo.old_name.clone()));
+        }
+    }
+    restored
+}
+
+/// Result of a batch rename for one rebind that changes
+#[derive(Debug, PartialEq, Clone)]
+pub struct RenamePreview {
+    pub index: usize,
+    pub old_name: String,
+    pub new_name: String,
+    /// Changed notes, `None` if they stay as they are
+    pub notes: Option<String>,
+    /// The new name is also used by another rebind after the rename
+    pub duplicate: bool,
+}
+
+impl BatchRename {
+    /// `text` with all matches replaced, `None` if nothing matches
+    pub fn replace_in(&self, text: &str) -> Option<String> {
+        if self.find.is_empty() {
+            return None;
+        }
+        let mut replaced = String::with_capacity(text.len());
+        let mut matched = false;
+        let mut copied_to = 0;
+        let mut pos = 0;
+        while pos < text.len() {
+            match self.match_end(text, pos) {
+                Some(end) => {
+                    replaced.push_str(&text[copied_to..pos]);
+                    replaced.push_str(&self.replace);
+                    matched = true;
+                    copied_to = end;
+                    pos = end;
+                }
+                None => pos += text[pos..].chars().next().map_or(1, char::len_utf8),
+            }
+        }
+        replaced.push_str(&text[copied_to..]);
+        matched.then_some(replaced)
+    }
+
+    /// End of the match starting at byte `pos` of `text`
+    fn match_end(&self, text: &str, pos: usize) -> Option<usize> {
+        let mut chars = text[pos..].chars();
+        let mut end = pos;
+        for expected in self.find.chars() {
+            let c = chars.next()?;
+            let equal = match self.case_sensitive {
+                true => c == expected,
+                false => c.to_lowercase().eq(expected.to_lowercase()),
+            };
+            if !equal {
+                return None;
+            }
+            end += c.len_utf8();
+        }
+
+        if self.whole_word {
+            let before = text[..pos].chars().next_back();
+            let after = text[end..].chars().next();
+            if matches!(before, Some(c) if is_word_char(c))
+                || matches!(after, Some(c) if is_word_char(c))
+            {
+                return None;
+            }
+        }
+        Some(end)
+    }
+
+    /// Renames of the rebinds for which `in_scope` holds, duplicates are checked against all
+    /// `rebinds` after the rename
+    pub fn preview(
+        &self,
+        rebinds: &[Rebind],
+        in_scope: impl Fn(&Rebind) -> bool,
+    ) -> Vec<RenamePreview> {
+        let mut previews: Vec<RenamePreview> = rebinds
+            .iter()
+            .enumerate()
+            .filter(|(_, rebind)| in_scope(rebind))
+            .filter_map(|(index, rebind)| {
+                let new_name = self.replace_in(&rebind.name);
+                let notes = match (self.include_notes, &rebind.notes) {
+                    (true, Some(notes)) => self.replace_in(notes),
+                    _ => None,
+                };
+                if new_name.is_none() && notes.is_none() {
+                    return None;
+                }
+                Some(RenamePreview {
+                    index,
+                    old_name: rebind.name.clone(),
+                    new_name: new_name.unwrap_or_else(|| rebind.name.clone()),
+                    notes,
+                    duplicate: false,
+                })
+            })
+            .collect();
+
+        let renamed: HashMap<usize, &str> = previews
+            .iter()
+            .map(|preview| (preview.index, preview.new_name.as_str()))
+            .collect();
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for (index, rebind) in rebinds.iter().enumerate() {
+            let name = renamed.get(&index).copied().unwrap_or(&rebind.name);
+            *counts.entry(name).or_default() += 1;
+        }
+        let duplicates: BTreeSet<String> = counts
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .map(|(name, _)| name.to_string())
+            .collect();
+        for preview in previews.iter_mut() {
+            preview.duplicate = duplicates.contains(&preview.new_name);
+        }
+        previews
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+pub fn update_window(ctx: &Context, input: &mut Input, ui_data: &mut UIData) {
+    if !ui_data.batch_rename.open {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new("Rename rebinds")
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            build_ui(ui, input, ui_data);
+        });
+    ui_data.batch_rename.open = open;
+}
+
+fn build_ui(ui: &mut Ui, input: &mut Input, ui_data: &mut UIData) {
+    let rename = &mut ui_data.batch_rename;
+    Grid::new("batch_rename_fields").show(ui, |ui| {
+        ui.label("Find:");
+        ui.add(TextEdit::singleline(&mut rename.find));
+        ui.end_row();
+        ui.label("Replace with:");
+        ui.add(TextEdit::singleline(&mut rename.replace));
+        ui.end_row();
+    });
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut rename.case_sensitive, "Match case");
+        ui.checkbox(&mut rename.whole_word, "Whole word");
+        ui.checkbox(&mut rename.include_notes, "Notes too");
+    });
+    rename.scope.widget(ui);
+
+    let filter = ui_data.rebind_mode_filter;
+    let expanded = &ui_data.expanded_rebinds.profile;
+    let previews = rename.preview(&input.get_config().rebinds, |rebind| {
+        rename.scope.contains(rebind, filter, expanded)
+    });
+
+    ui.separator();
+    if previews.is_empty() {
+        ui.label("No rebind in scope matches.");
+    } else {
+        ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            Grid::new("batch_rename_preview")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label(RichText::new("Name").strong());
+                    ui.label(RichText::new("New name").strong());
+                    ui.end_row();
+                    for preview in previews.iter() {
+                        ui.label(&preview.old_name);
+                        ui.horizontal(|ui| {
+                            ui.label(&preview.new_name);
+                            if preview.notes.is_some() {
+                                ui.label("(notes)");
+                            }
+                            if preview.duplicate {
+                                ui.label(RichText::new("⚠").color(ui.visuals().warn_fg_color))
+                                    .on_hover_text("Another rebind has the same name after the rename. The rebind list tells rebinds apart by name.");
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+    let duplicates = previews.iter().filter(|p| p.duplicate).count();
+    if duplicates > 0 {
+        ui.label(
+            RichText::new(format!("{duplicates} new names are not unique"))
+                .color(ui.visuals().warn_fg_color),
+        );
+    }
+
+    ui.separator();
+    ui.horizontal(|ui| {
+        let count = previews.len();
+        if ui
+            .add_enabled(count > 0, Button::new(format!("Rename {count} rebinds")))
+            .clicked()
+        {
+            rename.undo.clear();
+            for (index, rebind) in input.get_active_rebinds(RebindScope::Profile).enumerate() {
+                let Some(preview) = previews.iter().find(|p| p.index == index) else {
+                    continue;
+                };
+                let old_notes = rebind.notes.clone();
+                rebind.name = preview.new_name.clone();
+                if let Some(notes) = &preview.notes {
+                    rebind.notes = Some(notes.clone());
+                }
+                rename.undo.push(RenameUndo {
+                    id: rebind.id,
+                    old_name: preview.old_name.clone(),
+                    new_name: preview.new_name.clone(),
+                    old_notes,
+                    new_notes: rebind.notes.clone(),
+                });
+            }
+            for preview in previews.iter() {
+                let expanded = ui_data.expanded_rebinds.profile.contains(&preview.old_name);
+                ui_data.expanded_rebinds.update(
+                    RebindScope::Profile,
+                    &preview.old_name,
+                    &preview.new_name,
+                    expanded,
+                );
+            }
+            info!("Renamed {count} rebinds");
+        }
+        let undo_enabled = can_undo(&rename.undo, &input.get_config().rebinds);
+        if ui
+            .add_enabled(undo_enabled, Button::new("Undo"))
+            .on_hover_text("Restore the old names and notes unless they were edited since")
+            .clicked()
+        {
+            let undo = std::mem::take(&mut rename.undo);
+            let rebinds = input.get_active_rebinds(RebindScope::Profile);
+            let restored = undo_renames(&undo, rebinds);
+            for (new_name, old_name) in restored.iter() {
+                let expanded = ui_data.expanded_rebinds.profile.contains(new_name);
+                ui_data
+                    .expanded_rebinds
+                    .update(RebindScope::Profile, new_name, old_name, expanded);
+            }
+            info!(
+                "Restored {} names from before the last rename",
+                restored.len()
+            );
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rebind::{
+        batch_rename::{can_undo, undo_renames, BatchRename, RenameUndo},
+        Rebind, RebindType,
+    };
+
+    fn find_replace(find: &str, replace: &str) -> BatchRename {
+        BatchRename {
+            find: find.to_string(),
+            replace: replace.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn rebind(name: &str, notes: Option<&str>) -> Rebind {
+        Rebind {
+            notes: notes.map(str::to_string),
+            ..Rebind::new(
+                name.to_string(),
+                Default::default(),
+                RebindType::Reroute {
+                    rebind: Default::default(),
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn replaces_every_match() {
+        let rename = find_replace("stick l", "Stick R");
+        assert_eq!(
+            rename.replace_in("Stick L trigger, stick L hat").as_deref(),
+            Some("Stick R trigger, Stick R hat")
+        );
+        assert_eq!(rename.replace_in("Throttle"), None);
+        assert_eq!(find_replace("", "x").replace_in("Stick L"), None);
+
+        let case_sensitive = BatchRename {
+            case_sensitive: true,
+            ..find_replace("stick", "grip")
+        };
+        assert_eq!(case_sensitive.replace_in("Stick"), None);
+        assert_eq!(
+            find_replace("Ä", "Ö").replace_in("Lärm ä").as_deref(),
+            Some("LÖrm Ö")
+        );
+    }
+
+    #[test]
+    fn whole_word_needs_word_boundaries() {
+        let rename = BatchRename {
+            whole_word: true,
+            ..find_replace("L", "R")
+        };
+        assert_eq!(
+            rename.replace_in("Stick L Lever_L L").as_deref(),
+            Some("Stick R Lever_L R")
+        );
+    }
+
+    #[test]
+    fn preview_flags_duplicate_names() {
+        let rebinds = vec![
+            rebind("Stick L trigger", Some("stick l only")),
+            rebind("Stick R trigger", None),
+            rebind("Stick L hat", None),
+        ];
+        let rename = BatchRename {
+            include_notes: true,
+            ..find_replace("Stick L", "Stick R")
+        };
+        let previews = rename.preview(&rebinds, |_| true);
+        assert_eq!(previews.len(), 2);
+        assert_eq!(previews[0].new_name, "Stick R trigger");
+        assert_eq!(previews[0].notes.as_deref(), Some("Stick R only"));
+        assert!(previews[0].duplicate);
+        assert_eq!(previews[1].new_name, "Stick R hat");
+        assert!(!previews[1].duplicate);
+
+        let previews = rename.preview(&rebinds, |rebind| rebind.name.ends_with("hat"));
+        assert_eq!(previews.len(), 1);
+        assert_eq!(previews[0].index, 2);
+    }
+
+    #[test]
+    fn undo_keeps_later_edits() {
+        let mut rebinds = vec![
+            rebind("Stick R trigger", Some("Stick R only")),
+            rebind("Stick R hat", None),
+            rebind("Throttle", None),
+        ];
+        let undo: Vec<_> = rebinds[..2]
+            .iter()
+            .map(|rebind| RenameUndo {
+                id: rebind.id,
+                old_name: rebind.name.replace("Stick R", "Stick L"),
+                new_name: rebind.name.clone(),
+                old_notes: rebind
+                    .notes
+                    .as_ref()
+                    .map(|n| n.replace("Stick R", "stick l")),
+                new_notes: rebind.notes.clone(),
+            })
+            .collect();
+        assert!(can_undo(&undo, &rebinds));
+
+        // Edited after the rename
+        rebinds[0].notes = Some("edited".to_string());
+        rebinds[1].name = "Hat".to_string();
+
+        let restored = undo_renames(&undo, rebinds.iter_mut());
+        assert_eq!(
+            restored,
+            vec![("Stick R trigger".to_string(), "Stick L trigger".to_string())]
+        );
+        assert_eq!(rebinds[0].name, "Stick L trigger");
+        assert_eq!(rebinds[0].notes.as_deref(), Some("edited"));
+        assert_eq!(rebinds[1].name, "Hat");
+        assert_eq!(rebinds[2].name, "Throttle");
+        assert!(!can_undo(&undo, &rebinds));
+    }
+}
@@ -1,8 +1,9 @@
-use super::activation_interval::ActivationIntervalParams;
+use super::{activation_interval::ActivationIntervalParams, TickContext, VariantDescription};
+use crate::input::virtual_state::VirtualButton;
 use egui::Ui;
 use serde::{Deserialize, Serialize};
 use strum::{AsRefStr, EnumIter, EnumString, EnumVariantNames};
-use vjoy::{Button, ButtonState};
+use vjoy::ButtonState;
 
 /// Activation type and conditions for single input button to single output button rebinds
 ///
@@ -28,7 +29,8 @@ pub enum ButtonToButtonModifier {
     Simple,
     /// Button toggles output button
     Toggle {
-        #[serde(flatten)]
+        #[serde(skip_serializing)]
+        #[serde(default)]
         last_input: bool,
     },
     /// Output button is only activated if the current activation duration falls within this min..max interval. Output button press is sustained.
@@ -49,6 +51,17 @@ impl Default for ButtonToButtonModifier {
     }
 }
 
+impl VariantDescription for ButtonToButtonModifier {
+    fn description(&self) -> &'static str {
+        match self {
+            ButtonToButtonModifier::Simple => "Button maps directly to the output button",
+            ButtonToButtonModifier::Toggle { .. } => "Each press toggles the output button",
+            ButtonToButtonModifier::ActivationIntervalSimple { .. } => "Output is only pressed if the press duration falls within the interval, e.g. short vs. long press",
+            ButtonToButtonModifier::ActivationIntervalToggle { .. } => "Activation interval, but a matching press toggles the output button",
+        }
+    }
+}
+
 impl ButtonToButtonModifier {
     pub fn widget(&mut self, ui: &mut Ui) {
         ui.vertical(|ui| match self {
@@ -65,13 +78,42 @@ impl ButtonToButtonModifier {
             }
         });
     }
+
+    pub fn debug_state(&self) -> Vec<(String, String)> {
+        match self {
+            ButtonToButtonModifier::Simple => Vec::new(),
+            ButtonToButtonModifier::Toggle { last_input } => {
+                vec![("last_input".to_string(), last_input.to_string())]
+            }
+            ButtonToButtonModifier::ActivationIntervalSimple { params }
+            | ButtonToButtonModifier::ActivationIntervalToggle { params } => params.debug_state(),
+        }
+    }
+
+    pub fn take_runtime_state(&mut self, from: &mut Self) {
+        match (self, from) {
+            (
+                ButtonToButtonModifier::Toggle { last_input },
+                ButtonToButtonModifier::Toggle { last_input: from },
+            ) => *last_input = *from,
+            (
+                ButtonToButtonModifier::ActivationIntervalSimple { params },
+                ButtonToButtonModifier::ActivationIntervalSimple { params: from },
+            )
+            | (
+                ButtonToButtonModifier::ActivationIntervalToggle { params },
+                ButtonToButtonModifier::ActivationIntervalToggle { params: from },
+            ) => params.take_runtime_state(from),
+            _ => {}
+        }
+    }
 }
 
 pub fn apply_button_modifier(
     input: bool,
-    output: &Button,
+    output: &VirtualButton,
     modifier: &mut ButtonToButtonModifier,
-    time: f64,
+    ctx: TickContext,
 ) -> ButtonState {
     match modifier {
         ButtonToButtonModifier::Simple => match input {
@@ -92,7 +134,7 @@ pub fn apply_button_modifier(
             }
         }
         ButtonToButtonModifier::ActivationIntervalSimple { params } => {
-            if params.update(input, time, true) {
+            if params.update(input, ctx, true) {
                 ButtonState::Pressed
             } else {
                 ButtonState::Released
@@ -100,7 +142,7 @@ pub fn apply_button_modifier(
         }
         ButtonToButtonModifier::ActivationIntervalToggle { params } => {
             let current_output_state = output.get();
-            if params.update(input, time, false) {
+            if params.update(input, ctx, false) {
                 match current_output_state {
                     ButtonState::Released => ButtonState::Pressed,
                     ButtonState::Pressed => ButtonState::Released,
@@ -0,0 +1,273 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use indexmap::IndexMap;
+
+use super::{rebind_viewer::DeviceInfo, Rebind, VirtualControl};
+
+const VJOY_CONF_CANDIDATES: [&str; 2] = [
+    r"C:\Program Files\vJoy\x64\vJoyConf.exe",
+    r"C:\Program Files\vJoy\x86\vJoyConf.exe",
+];
+
+/// Highest button/axis/hat ids a config references on one virtual device
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct VirtualDeviceRequirement {
+    pub buttons: u32,
+    pub axes: u32,
+    pub hats: u32,
+}
+
+/// Collects the capabilities every referenced virtual device needs, keyed by vJoy device id.
+///
+/// Unset ids (0) are ignored.
+pub fn required_virtual_devices<'a>(
+    rebinds: impl IntoIterator<Item = &'a Rebind>,
+) -> BTreeMap<u32, VirtualDeviceRequirement> {
+    let mut required: BTreeMap<u32, VirtualDeviceRequirement> = BTreeMap::new();
+    let controls = rebinds
+        .into_iter()
+        .flat_map(|r| r.virtual_writes().into_iter().chain(r.virtual_reads()));
+
+    for control in controls {
+        let (device, id) = match control {
+            VirtualControl::Button { device, button } => (device, button),
+            VirtualControl::Axis { device, axis } => (device, axis),
+            VirtualControl::Hat { device, hat } => (device, hat),
+        };
+        if device == 0 || id == 0 {
+            continue;
+        }
+
+        let requirement = required.entry(device).or_default();
+        match control {
+            VirtualControl::Button { .. } => requirement.buttons = requirement.buttons.max(id),
+            VirtualControl::Axis { .. } => requirement.axes = requirement.axes.max(id),
+            VirtualControl::Hat { .. } => requirement.hats = requirement.hats.max(id),
+        }
+    }
+
+    required
+}
+
+/// Human readable description of every requirement the available virtual devices don't meet
+pub fn missing_virtual_capabilities(
+    required: &BTreeMap<u32, VirtualDeviceRequirement>,
+    available: &IndexMap<u32, DeviceInfo>,
+) -> Vec<String> {
+    required
+        .iter()
+        .filter_map(|(id, req)| {
+            let needs = format!(
+                "Config needs vJoy #{id} with ≥{} buttons, {} axes and {} hats",
+                req.buttons, req.axes, req.hats
+            );
+            let Some(info) = available.get(id) else {
+                return Some(format!("{needs} — found none."));
+            };
+
+            let sufficient = info.num_buttons as u32 >= req.buttons
+                && info.num_axes as u32 >= req.axes
+                && info.num_hats as u32 >= req.hats;
+            match sufficient {
+                true => None,
+                false => Some(format!(
+                    "{needs} — found {} buttons, {} axes and {} hats.",
+                    info.num_buttons, info.num_axes, info.num_hats
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Path of the vJoy configuration utility if it is installed in the default location
+pub fn find_vjoy_conf() -> Option<PathBuf> {
+    VJOY_CONF_CANDIDATES
+        .iter()
+        .map(PathBuf::from)
+        .find(|path| path.exists())
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use crate::rebind::{
+        axis_gain::ButtonSource,
+        capabilities::{
+            missing_virtual_capabilities, required_virtual_devices, VirtualDeviceRequirement,
+        },
+        merge_axes::AxisSource,
+        multi_action::MacroAction,
+        rebind_viewer::DeviceInfo,
+        reroute_rebind::RerouteRebind,
+        shift_mode_mask::ShiftModeMask,
+        virtual_rebind::VirtualRebind,
+        Rebind, RebindType,
+    };
+
+    fn reroute(rebind: RerouteRebind) -> Rebind {
+        Rebind::new(
+            String::new(),
+            ShiftModeMask::default(),
+            RebindType::Reroute { rebind },
+        )
+    }
+
+    #[test]
+    fn reroute_variants() {
+        let rebinds = vec![
+            reroute(RerouteRebind::ButtonToButton {
+                src: ButtonSource::physical("guid".to_string(), 1),
+                dst_device: 1,
+                dst_button: 32,
+                modifier: Default::default(),
+            }),
+            reroute(RerouteRebind::TwoButtonsToAxis {
+                src_neg_device: "guid".to_string(),
+                src_neg_button: 1,
+                src_pos_device: "guid".to_string(),
+                src_pos_button: 2,
+                dst_device: 1,
+                dst_axis: 3,
+                modifier: Default::default(),
+            }),
+            reroute(RerouteRebind::HatToHat {
+                src_device: "guid".to_string(),
+                src_hat: 1,
+                dst_device: 2,
+                dst_hat: 4,
+                modifier: Default::default(),
+            }),
+            reroute(RerouteRebind::AxisToAxis {
+                src: AxisSource::physical("guid".to_string(), 1),
+                dst_device: 1,
+                dst_axis: 8,
+                modifier: Default::default(),
+                freeze: None,
+                gain: None,
+            }),
+            reroute(RerouteRebind::MergeAxes {
+                src_0: AxisSource::physical("guid".to_string(), 1),
+                src_1: AxisSource::Virtual { device: 3, axis: 6 },
+                dst_device: 2,
+                dst_axis: 2,
+                modifier: Default::default(),
+            }),
+            reroute(RerouteRebind::MultiAction {
+                src_device: "guid".to_string(),
+                src_button: 1,
+                actions: vec![
+                    MacroAction::ButtonPulse {
+                        device: 3,
+                        button: 5,
+                        duration: 0.2,
+                    },
+                    MacroAction::Delay { duration: 1.0 },
+                ],
+                state: Default::default(),
+            }),
+        ];
+
+        let required = required_virtual_devices(&rebinds);
+        assert_eq!(
+            required.get(&1),
+            Some(&VirtualDeviceRequirement {
+                buttons: 32,
+                axes: 8,
+                hats: 0
+            })
+        );
+        assert_eq!(
+            required.get(&2),
+            Some(&VirtualDeviceRequirement {
+                buttons: 0,
+                axes: 2,
+                hats: 4
+            })
+        );
+        assert_eq!(
+            required.get(&3),
+            Some(&VirtualDeviceRequirement {
+                buttons: 5,
+                axes: 6,
+                hats: 0
+            })
+        );
+    }
+
+    #[test]
+    fn virtual_variants_include_read_buttons() {
+        let rebinds = vec![Rebind::new(
+            String::new(),
+            ShiftModeMask::default(),
+            RebindType::Virtual {
+                rebind: VirtualRebind::VirtualAxisApplyButtonTrim {
+                    axis_device: 1,
+                    axis: 2,
+                    trim_neg_device: 1,
+                    trim_neg_button: 10,
+                    trim_pos_device: 1,
+                    trim_pos_button: 11,
+                    trim_reset_device: 2,
+                    trim_reset_button: 1,
+                    modifier: Default::default(),
+                },
+                phase: Default::default(),
+            },
+        )];
+
+        let required = required_virtual_devices(&rebinds);
+        assert_eq!(required[&1].buttons, 11);
+        assert_eq!(required[&1].axes, 2);
+        assert_eq!(required[&2].buttons, 1);
+    }
+
+    #[test]
+    fn unset_ids_are_ignored() {
+        let rebinds = vec![reroute(RerouteRebind::default())];
+        assert!(required_virtual_devices(&rebinds).is_empty());
+    }
+
+    #[test]
+    fn missing_and_insufficient_devices_are_reported() {
+        let required = [
+            (
+                1,
+                VirtualDeviceRequirement {
+                    buttons: 32,
+                    axes: 8,
+                    hats: 0,
+                },
+            ),
+            (
+                2,
+                VirtualDeviceRequirement {
+                    buttons: 1,
+                    axes: 0,
+                    hats: 0,
+                },
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut available = IndexMap::new();
+        available.insert(
+            1,
+            DeviceInfo {
+                name: "vJoy 1".to_string(),
+                num_buttons: 16,
+                num_axes: 8,
+                num_hats: 0,
+                axis_kinds: Vec::new(),
+                axis_names: Vec::new(),
+                button_labels: Default::default(),
+            },
+        );
+
+        let missing = missing_virtual_capabilities(&required, &available);
+        assert_eq!(missing.len(), 2);
+        assert!(missing[0].contains("vJoy #1") && missing[0].contains("found 16 buttons"));
+        assert!(missing[1].contains("vJoy #2") && missing[1].contains("found none"));
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use super::{Rebind, VirtualControl};
+
+/// Groups of rebinds that feed each other through virtual controls, e.g. axis 1 feeds axis 2
+/// feeds axis 1. Such loops oscillate at the poll rate.
+///
+/// Each group lists the indices of its rebinds in ascending order. A rebind that reads a control
+/// it writes itself is a group of one.
+pub fn dependency_cycles<'a>(rebinds: impl IntoIterator<Item = &'a Rebind>) -> Vec<Vec<usize>> {
+    let rebinds: Vec<&Rebind> = rebinds.into_iter().collect();
+
+    let mut writers: HashMap<VirtualControl, Vec<usize>> = HashMap::new();
+    for (index, rebind) in rebinds.iter().enumerate() {
+        for control in rebind.virtual_writes() {
+            writers.entry(control).or_default().push(index);
+        }
+    }
+
+    // Edge from each writer to the rebinds reading what it writes
+    let mut readers: Vec<Vec<usize>> = vec![Vec::new(); rebinds.len()];
+    for (index, rebind) in rebinds.iter().enumerate() {
+        for control in rebind.virtual_reads() {
+            for writer in writers.get(&control).into_iter().flatten() {
+                if !readers[*writer].contains(&index) {
+                    readers[*writer].push(index);
+                }
+            }
+        }
+    }
+
+    let reachable: Vec<Vec<bool>> = (0..rebinds.len())
+        .map(|start| reachable_from(start, &readers))
+        .collect();
+
+    let mut assigned = vec![false; rebinds.len()];
+    let mut cycles = Vec::new();
+    for index in 0..rebinds.len() {
+        if assigned[index] || !reachable[index][index] {
+            continue;
+        }
+        let cycle: Vec<usize> = (index..rebinds.len())
+            .filter(|other| reachable[index][*other] && reachable[*other][index])
+            .collect();
+        for member in cycle.iter() {
+            assigned[*member] = true;
+        }
+        cycles.push(cycle);
+    }
+
+    cycles
+}
+
+/// Rebinds reachable from `start` over at least one edge
+fn reachable_from(start: usize, readers: &[Vec<usize>]) -> Vec<bool> {
+    let mut reached = vec![false; readers.len()];
+    let mut stack = readers[start].clone();
+    while let Some(index) = stack.pop() {
+        if reached[index] {
+            continue;
+        }
+        reached[index] = true;
+        stack.extend(readers[index].iter().copied());
+    }
+    reached
+}
+
+/// Whether one of `controls` was already written `limit` times this tick. Counts the writes if
+/// not, returns the first control over the limit otherwise.
+pub fn exceeds_rewrite_limit(
+    write_counts: &mut HashMap<VirtualControl, u32>,
+    controls: &[VirtualControl],
+    limit: u32,
+) -> Option<VirtualControl> {
+    if let Some(control) = controls
+        .iter()
+        .find(|control| write_counts.get(control).copied().unwrap_or_default() >= limit)
+    {
+        return Some(*control);
+    }
+
+    for control in controls.iter() {
+        *write_counts.entry(*control).or_default() += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::rebind::{
+        dependencies::{dependency_cycles, exceeds_rewrite_limit},
+        merge_axes::{AxisSource, MergeAxesModifier},
+        reroute_rebind::RerouteRebind,
+        Rebind, RebindType, VirtualControl,
+    };
+
+    /// Merges virtual axis `src` with a physical axis into virtual axis `dst`, both on device 1
+    fn merge(name: &str, src: u32, dst: u32) -> Rebind {
+        Rebind::new(
+            name.to_string(),
+            Default::default(),
+            RebindType::Reroute {
+                rebind: RerouteRebind::MergeAxes {
+                    src_0: AxisSource::Virtual {
+                        device: 1,
+                        axis: src,
+                    },
+                    src_1: AxisSource::Physical {
+                        device: "stick".to_string(),
+                        axis: 1,
+                    },
+                    dst_device: 1,
+                    dst_axis: dst,
+                    modifier: MergeAxesModifier::Add,
+                },
+            },
+        )
+    }
+
+    #[test]
+    fn finds_loops_between_and_within_rebinds() {
+        let rebinds = vec![
+            merge("a", 1, 2),
+            merge("chain", 2, 3),
+            merge("b", 2, 1),
+            merge("self", 4, 4),
+        ];
+        assert_eq!(dependency_cycles(&rebinds), vec![vec![0, 2], vec![3]]);
+
+        let chain = vec![merge("a", 1, 2), merge("b", 2, 3)];
+        assert!(dependency_cycles(&chain).is_empty());
+    }
+
+    #[test]
+    fn rewrite_limit_counts_per_control() {
+        let axis = VirtualControl::Axis { device: 1, axis: 1 };
+        let mut counts = HashMap::new();
+        assert_eq!(exceeds_rewrite_limit(&mut counts, &[axis], 2), None);
+        assert_eq!(exceeds_rewrite_limit(&mut counts, &[axis], 2), None);
+        assert_eq!(exceeds_rewrite_limit(&mut counts, &[axis], 2), Some(axis));
+        assert_eq!(counts[&axis], 2);
+    }
+}
@@ -0,0 +1,572 @@
+use egui::{Button, ComboBox, Context, Grid, RichText, ScrollArea, Ui};
+use indexmap::IndexMap;
+use log::info;
+
+use super::{
+    batch_rename::RenameScope, overlay::Overlay, rebind_id::RebindId, rebind_viewer::DeviceInfo,
+    Rebind, RebindScope, RebindType,
+};
+use crate::{input::event_console::ControlKind, input::Input, ui_data::UIData};
+
+/// Points the physical sources of a group of rebinds from one device to another, e.g. to mirror
+/// the rebinds of a left hand stick for the right hand one.
+///
+/// The last swap can be undone for the rebinds and overrides whose source devices weren't edited
+/// since.
+#[derive(Debug, Default)]
+pub struct DeviceSwap {
+    pub open: bool,
+    pub from: String,
+    pub to: String,
+    /// Also points the sources on `to` at `from`
+    pub both_ways: bool,
+    pub scope: RenameScope,
+    pub undo: SwapUndo,
+}
+
+/// Source devices of a rebind or overlay override before and after a swap
+#[derive(Debug, PartialEq, Clone)]
+struct SourceDevices {
+    id: RebindId,
+    old: Vec<String>,
+    new: Vec<String>,
+}
+
+impl SourceDevices {
+    fn applies_to(&self, id: Option<RebindId>, rebind_type: &RebindType) -> bool {
+        id == Some(self.id) && source_devices(rebind_type) == self.new
+    }
+
+    fn restore(&self, rebind_type: &mut RebindType) {
+        for (guid, old) in rebind_type
+            .physical_devices_mut()
+            .into_iter()
+            .zip(&self.old)
+        {
+            *guid = old.clone();
+        }
+    }
+}
+
+fn source_devices(rebind_type: &RebindType) -> Vec<String> {
+    let mut rebind_type = rebind_type.clone();
+    let devices = rebind_type.physical_devices_mut();
+    devices.into_iter().map(|guid| guid.clone()).collect()
+}
+
+/// Source devices changed by the last swap
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct SwapUndo {
+    rebinds: Vec<SourceDevices>,
+    overrides: Vec<SourceDevices>,
+}
+
+impl SwapUndo {
+    /// Whether any rebind or override still reads the devices it was swapped to
+    pub fn is_possible(&self, rebinds: &[Rebind], overlays: &[Overlay]) -> bool {
+        let rebinds = self.rebinds.iter().any(|undo| {
+            rebinds
+                .iter()
+                .any(|rebind| undo.applies_to(Some(rebind.id), &rebind.rebind_type))
+        });
+        let overrides = self.overrides.iter().any(|undo| {
+            overlays
+                .iter()
+                .flat_map(|overlay| overlay.overrides.iter())
+                .any(|override_| undo.applies_to(override_.rebind_id, &override_.rebind_type))
+        });
+        rebinds || overrides
+    }
+
+    /// Points the rebinds that still read the devices they were swapped to back at their old
+    /// devices, returns the number of restored rebinds
+    pub fn undo_rebinds<'a>(&self, rebinds: impl Iterator<Item = &'a mut Rebind>) -> usize {
+        let mut restored = 0;
+        for rebind in rebinds {
+            let undo = self
+                .rebinds
+                .iter()
+                .find(|undo| undo.applies_to(Some(rebind.id), &rebind.rebind_type));
+            if let Some(undo) = undo {
+                undo.restore(&mut rebind.rebind_type);
+                restored += 1;
+            }
+        }
+        restored
+    }
+
+    /// Points the overrides that still read the devices they were swapped to back at their old
+    /// devices
+    pub fn undo_overrides(self, overlays: &mut [Overlay]) {
+        let mut overrides = self.overrides;
+        for override_ in overlays
+            .iter_mut()
+            .flat_map(|overlay| overlay.overrides.iter_mut())
+        {
+            let position = overrides
+                .iter()
+                .position(|undo| undo.applies_to(override_.rebind_id, &override_.rebind_type));
+            if let Some(position) = position {
+                overrides
+                    .swap_remove(position)
+                    .restore(&mut override_.rebind_type);
+            }
+        }
+    }
+}
+
+/// Result of a swap for one rebind that changes
+#[derive(Debug, PartialEq, Clone)]
+pub struct SwapPreview {
+    pub index: usize,
+    pub id: RebindId,
+    pub name: String,
+    /// Number of sources pointed at the other device
+    pub swapped: usize,
+    /// Sources whose id the new device lacks, e.g. "button 14 of 12"
+    pub out_of_range: Vec<String>,
+}
+
+impl DeviceSwap {
+    /// Device `guid` is pointed at by the swap, `None` if it stays
+    fn target(&self, guid: &str) -> Option<&str> {
+        if self.from.is_empty() || self.to.is_empty() || self.from == self.to {
+            return None;
+        }
+        if guid == self.from {
+            Some(&self.to)
+        } else if guid == self.to && self.both_ways {
+            Some(&self.from)
+        } else {
+            None
+        }
+    }
+
+    /// Rewrites the source devices of `rebind_type` and records the change in `undo`
+    fn swap_undoable(
+        &self,
+        id: RebindId,
+        rebind_type: &mut RebindType,
+        undo: &mut Vec<SourceDevices>,
+    ) {
+        let old = source_devices(rebind_type);
+        if self.swap(rebind_type) > 0 {
+            undo.push(SourceDevices {
+                id,
+                old,
+                new: source_devices(rebind_type),
+            });
+        }
+    }
+
+    /// Rewrites the source devices of `rebind_type` and returns the number of swapped sources
+    pub fn swap(&self, rebind_type: &mut RebindType) -> usize {
+        let mut swapped = 0;
+        for guid in rebind_type.physical_devices_mut() {
+            if let Some(target) = self.target(guid) {
+                *guid = target.to_string();
+                swapped += 1;
+            }
+        }
+        swapped
+    }
+
+    /// Swaps of the rebinds for which `in_scope` holds. Sources are checked against the layouts of
+    /// the connected `devices`, sources on disconnected devices are not checked.
+    pub fn preview(
+        &self,
+        rebinds: &[Rebind],
+        in_scope: impl Fn(&Rebind) -> bool,
+        devices: &IndexMap<String, DeviceInfo>,
+    ) -> Vec<SwapPreview> {
+        rebinds
+            .iter()
+            .enumerate()
+            .filter(|(_, rebind)| in_scope(rebind))
+            .filter_map(|(index, rebind)| {
+                let mut rebind_type = rebind.rebind_type.clone();
+                let swapped = self.swap(&mut rebind_type);
+                if swapped == 0 {
+                    return None;
+                }
+                let out_of_range = rebind_type
+                    .physical_reads()
+                    .into_iter()
+                    .filter(|read| read.device == self.from || read.device == self.to)
+                    .filter_map(|read| {
+                        let info = devices.get(read.device)?;
+                        let count = match read.kind {
+                            ControlKind::Button => info.num_buttons,
+                            ControlKind::Axis => info.num_axes,
+                            ControlKind::Hat => info.num_hats,
+                        };
+                        (read.id as usize > count)
+                            .then(|| format!("{} {} of {count}", read.kind, read.id))
+                    })
+                    .collect();
+                Some(SwapPreview {
+                    index,
+                    id: rebind.id,
+                    name: rebind.name.clone(),
+                    swapped,
+                    out_of_range,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Swaps the overlay overrides of the swapped rebinds along with them
+fn swap_overrides(
+    swap: &DeviceSwap,
+    overlays: &mut [Overlay],
+    previews: &[SwapPreview],
+) -> Vec<SourceDevices> {
+    let mut undo = Vec::new();
+    for override_ in overlays
+        .iter_mut()
+        .flat_map(|overlay| overlay.overrides.iter_mut())
+    {
+        let Some(id) = override_.rebind_id else {
+            continue;
+        };
+        if previews.iter().any(|p| p.id == id) {
+            swap.swap_undoable(id, &mut override_.rebind_type, &mut undo);
+        }
+    }
+    undo
+}
+
+pub fn update_window(ctx: &Context, input: &mut Input, ui_data: &mut UIData) {
+    if !ui_data.device_swap.open {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new("Swap source devices")
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            build_ui(ui, input, ui_data);
+        });
+    ui_data.device_swap.open = open;
+}
+
+fn device_combo(ui: &mut Ui, id: &str, guid: &mut String, devices: &IndexMap<String, DeviceInfo>) {
+    let label = |guid: &str| match devices.get(guid) {
+        Some(info) => info.name.clone(),
+        None if guid.is_empty() => "Select device".to_string(),
+        None => format!("{guid} (disconnected)"),
+    };
+    ComboBox::from_id_source(id)
+        .selected_text(label(guid))
+        .show_ui(ui, |ui| {
+            for (key, info) in devices.iter() {
+                ui.selectable_value(guid, key.clone(), &info.name);
+            }
+        });
+}
+
+fn build_ui(ui: &mut Ui, input: &mut Input, ui_data: &mut UIData) {
+    let devices = input.get_physical_device_info_map();
+    let swap = &mut ui_data.device_swap;
+    Grid::new("device_swap_fields").show(ui, |ui| {
+        ui.label("From:");
+        device_combo(ui, "device_swap_from", &mut swap.from, &devices);
+        ui.end_row();
+        ui.label("To:");
+        device_combo(ui, "device_swap_to", &mut swap.to, &devices);
+        ui.end_row();
+    });
+    ui.checkbox(&mut swap.both_ways, "Both ways")
+        .on_hover_text("Also point the rebinds reading the second device at the first one");
+    swap.scope.widget(ui);
+
+    let filter = ui_data.rebind_mode_filter;
+    let expanded = &ui_data.expanded_rebinds.profile;
+    let previews = swap.preview(
+        &input.get_config().rebinds,
+        |rebind| swap.scope.contains(rebind, filter, expanded),
+        &devices,
+    );
+
+    ui.separator();
+    if previews.is_empty() {
+        ui.label("No rebind in scope reads the selected devices.");
+    } else {
+        ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            Grid::new("device_swap_preview")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label(RichText::new("Rebind").strong());
+                    ui.label(RichText::new("Sources").strong());
+                    ui.end_row();
+                    for preview in previews.iter() {
+                        ui.label(&preview.name);
+                        ui.horizontal(|ui| {
+                            ui.label(preview.swapped.to_string());
+                            if !preview.out_of_range.is_empty() {
+                                ui.label(RichText::new("⚠").color(ui.visuals().warn_fg_color))
+                                    .on_hover_text(format!(
+                                        "Not on the new device: {}",
+                                        preview.out_of_range.join(", ")
+                                    ));
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+    let out_of_range = previews
+        .iter()
+        .filter(|p| !p.out_of_range.is_empty())
+        .count();
+    if out_of_range > 0 {
+        ui.label(
+            RichText::new(format!(
+                "{out_of_range} rebinds read controls the new device lacks"
+            ))
+            .color(ui.visuals().warn_fg_color),
+        );
+    }
+
+    ui.separator();
+    ui.horizontal(|ui| {
+        let count = previews.len();
+        if ui
+            .add_enabled(count > 0, Button::new(format!("Swap {count} rebinds")))
+            .clicked()
+        {
+            let mut rebinds = Vec::new();
+            for (index, rebind) in input.get_active_rebinds(RebindScope::Profile).enumerate() {
+                if previews.iter().any(|p| p.index == index) {
+                    swap.swap_undoable(rebind.id, &mut rebind.rebind_type, &mut rebinds);
+                }
+            }
+            let overrides = swap_overrides(swap, input.get_overlays_mut(), &previews);
+            swap.undo = SwapUndo { rebinds, overrides };
+            info!("Swapped the source devices of {count} rebinds");
+        }
+        let undo_enabled = swap
+            .undo
+            .is_possible(&input.get_config().rebinds, input.get_overlays());
+        if ui
+            .add_enabled(undo_enabled, Button::new("Undo"))
+            .on_hover_text(
+                "Point the swapped sources back at their old devices, unless they were edited since",
+            )
+            .clicked()
+        {
+            let undo = std::mem::take(&mut swap.undo);
+            let restored = undo.undo_rebinds(input.get_active_rebinds(RebindScope::Profile));
+            undo.undo_overrides(input.get_overlays_mut());
+            info!("Restored the source devices of {restored} rebinds");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use crate::rebind::{
+        axis_gain::ButtonSource,
+        device_swap::{swap_overrides, DeviceSwap, SwapUndo},
+        merge_axes::AxisSource,
+        overlay::{Overlay, OverlayOverride},
+        rebind_viewer::DeviceInfo,
+        reroute_rebind::RerouteRebind,
+        Rebind, RebindType,
+    };
+
+    fn rebind(name: &str, rebind: RerouteRebind) -> Rebind {
+        Rebind::new(
+            name.to_string(),
+            Default::default(),
+            RebindType::Reroute { rebind },
+        )
+    }
+
+    fn button(device: &str, button: u32) -> RerouteRebind {
+        RerouteRebind::ButtonToButton {
+            src: ButtonSource::physical(device.to_string(), button),
+            dst_device: 1,
+            dst_button: 1,
+            modifier: Default::default(),
+        }
+    }
+
+    fn merge(device_0: &str, device_1: &str) -> RerouteRebind {
+        RerouteRebind::MergeAxes {
+            src_0: AxisSource::physical(device_0.to_string(), 1),
+            src_1: AxisSource::physical(device_1.to_string(), 3),
+            dst_device: 1,
+            dst_axis: 1,
+            modifier: Default::default(),
+        }
+    }
+
+    fn swap(from: &str, to: &str, both_ways: bool) -> DeviceSwap {
+        DeviceSwap {
+            from: from.to_string(),
+            to: to.to_string(),
+            both_ways,
+            ..Default::default()
+        }
+    }
+
+    fn device(num_buttons: usize, num_axes: usize) -> DeviceInfo {
+        DeviceInfo {
+            name: "Stick".to_string(),
+            num_buttons,
+            num_axes,
+            num_hats: 0,
+            axis_kinds: Vec::new(),
+            axis_names: Vec::new(),
+            button_labels: Default::default(),
+        }
+    }
+
+    #[test]
+    fn one_way_and_true_swap() {
+        let mut rebind_type = RebindType::Reroute {
+            rebind: merge("left", "right"),
+        };
+        assert_eq!(swap("left", "right", false).swap(&mut rebind_type), 1);
+        assert_eq!(
+            rebind_type,
+            RebindType::Reroute {
+                rebind: merge("right", "right")
+            }
+        );
+
+        let mut rebind_type = RebindType::Reroute {
+            rebind: merge("left", "right"),
+        };
+        assert_eq!(swap("left", "right", true).swap(&mut rebind_type), 2);
+        assert_eq!(
+            rebind_type,
+            RebindType::Reroute {
+                rebind: merge("right", "left")
+            }
+        );
+
+        assert_eq!(swap("left", "left", true).swap(&mut rebind_type), 0);
+        assert_eq!(swap("", "left", true).swap(&mut rebind_type), 0);
+    }
+
+    #[test]
+    fn preview_flags_ids_the_new_device_lacks() {
+        let rebinds = vec![
+            rebind("Trigger", button("left", 1)),
+            rebind("Pinky", button("left", 14)),
+            rebind("Other stick", button("pedals", 14)),
+            rebind("Brakes", merge("left", "left")),
+        ];
+        let devices = IndexMap::from([
+            ("left".to_string(), device(16, 3)),
+            ("right".to_string(), device(12, 2)),
+        ]);
+        let previews = swap("left", "right", false).preview(&rebinds, |_| true, &devices);
+        assert_eq!(previews.len(), 3);
+        assert!(previews[0].out_of_range.is_empty());
+        assert_eq!(previews[1].out_of_range, vec!["button 14 of 12"]);
+        assert_eq!(previews[2].index, 3);
+        assert_eq!(previews[2].swapped, 2);
+        assert_eq!(previews[2].out_of_range, vec!["axis 3 of 2"]);
+
+        // Disconnected devices are not checked
+        let previews = swap("left", "gone", false).preview(&rebinds, |_| true, &devices);
+        assert!(previews.iter().all(|p| p.out_of_range.is_empty()));
+
+        let previews = swap("left", "right", false).preview(
+            &rebinds,
+            |rebind| rebind.name == "Pinky",
+            &devices,
+        );
+        assert_eq!(previews.len(), 1);
+    }
+
+    #[test]
+    fn overrides_follow_their_rebind_not_its_name() {
+        let rebinds = vec![
+            rebind("Trigger", button("left", 1)),
+            rebind("Trigger", button("pedals", 1)),
+        ];
+        let override_of = |rebind: &Rebind| OverlayOverride {
+            rebind_id: Some(rebind.id),
+            rebind_name: String::new(),
+            rebind_type: rebind.rebind_type.clone(),
+            resolved: Default::default(),
+        };
+        let mut overlays = vec![Overlay {
+            name: "Landing".to_string(),
+            overrides: rebinds.iter().map(override_of).collect(),
+        }];
+        let swap = swap("left", "right", false);
+        let previews = swap.preview(&rebinds, |_| true, &IndexMap::new());
+        swap_overrides(&swap, &mut overlays, &previews);
+        assert_eq!(
+            overlays[0].overrides[0].rebind_type,
+            RebindType::Reroute {
+                rebind: button("right", 1)
+            }
+        );
+        assert_eq!(overlays[0].overrides[1].rebind_type, rebinds[1].rebind_type);
+    }
+
+    #[test]
+    fn undo_keeps_later_edits() {
+        let mut rebinds = vec![
+            rebind("Trigger", button("left", 1)),
+            rebind("Pinky", button("left", 2)),
+            rebind("Brakes", merge("left", "right")),
+        ];
+        let mut overlays = vec![Overlay {
+            name: "Landing".to_string(),
+            overrides: vec![OverlayOverride {
+                rebind_id: Some(rebinds[0].id),
+                rebind_name: String::new(),
+                rebind_type: rebinds[0].rebind_type.clone(),
+                resolved: Default::default(),
+            }],
+        }];
+        let swap = swap("left", "right", false);
+        let previews = swap.preview(&rebinds, |_| true, &IndexMap::new());
+        let mut undo = SwapUndo::default();
+        for rebind in rebinds.iter_mut() {
+            swap.swap_undoable(rebind.id, &mut rebind.rebind_type, &mut undo.rebinds);
+        }
+        undo.overrides = swap_overrides(&swap, &mut overlays, &previews);
+        assert!(undo.is_possible(&rebinds, &overlays));
+
+        // Pointed at another device after the swap
+        rebinds[1].rebind_type = RebindType::Reroute {
+            rebind: button("pedals", 2),
+        };
+
+        assert_eq!(undo.undo_rebinds(rebinds.iter_mut()), 2);
+        undo.clone().undo_overrides(&mut overlays);
+        assert_eq!(
+            rebinds[0].rebind_type,
+            RebindType::Reroute {
+                rebind: button("left", 1)
+            }
+        );
+        assert_eq!(
+            rebinds[1].rebind_type,
+            RebindType::Reroute {
+                rebind: button("pedals", 2)
+            }
+        );
+        assert_eq!(
+            rebinds[2].rebind_type,
+            RebindType::Reroute {
+                rebind: merge("left", "right")
+            }
+        );
+        assert_eq!(overlays[0].overrides[0].rebind_type, rebinds[0].rebind_type);
+        assert!(!undo.is_possible(&rebinds, &overlays));
+    }
+}
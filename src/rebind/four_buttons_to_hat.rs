@@ -0,0 +1,236 @@
+use egui::{Checkbox, Ui};
+use egui_extras::Column;
+use serde::{Deserialize, Serialize};
+use strum::{AsRefStr, EnumIter, EnumString, EnumVariantNames};
+
+use super::{
+    hat_to_hat::convert_vjoy_hat_to_angle, rebind_table, VariantDescription, TABLE_ROW_HEIGHT,
+};
+use crate::input::virtual_state::VirtualHat;
+
+/// Activation type and conditions for four input buttons to single output hat rebinds.
+///
+/// ## Examples usages
+/// - Rebind a castle switch made of four separate buttons to a POV hat for view panning.
+#[derive(
+    Debug,
+    PartialEq,
+    Clone,
+    Serialize,
+    Deserialize,
+    AsRefStr,
+    EnumIter,
+    EnumString,
+    EnumVariantNames,
+)]
+#[serde(tag = "modifier")]
+pub enum FourButtonsToHatModifier {
+    /// Two adjacent buttons held give the diagonal between them.
+    EightWay { keep_direction: bool },
+    /// Only north, east, south and west. Of two adjacent buttons the first one pressed wins.
+    FourWay { keep_direction: bool },
+}
+
+impl Default for FourButtonsToHatModifier {
+    fn default() -> Self {
+        Self::EightWay {
+            keep_direction: false,
+        }
+    }
+}
+
+impl VariantDescription for FourButtonsToHatModifier {
+    fn description(&self) -> &'static str {
+        match self {
+            FourButtonsToHatModifier::EightWay { .. } => {
+                "Two adjacent buttons held give the diagonal between them, opposite buttons cancel"
+            }
+            FourButtonsToHatModifier::FourWay { .. } => {
+                "Only the four main directions, of two adjacent buttons the first one pressed wins"
+            }
+        }
+    }
+}
+
+impl FourButtonsToHatModifier {
+    pub fn widget(&mut self, ui: &mut Ui) {
+        ui.vertical(|ui| match self {
+            FourButtonsToHatModifier::EightWay { keep_direction }
+            | FourButtonsToHatModifier::FourWay { keep_direction } => {
+                rebind_table(ui)
+                    .column(Column::remainder())
+                    .body(|mut body| {
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Keep direction:").on_hover_text(
+                                    "Keep the last direction instead of centering on release",
+                                );
+                            });
+                            row.col(|ui| {
+                                ui.push_id("KeepDirection", |ui| {
+                                    ui.add(Checkbox::new(keep_direction, ""));
+                                });
+                            });
+                        });
+                    });
+            }
+        });
+    }
+}
+
+/// Hat angle in degrees, -1 if centered, for the buttons held in the order north, east, south,
+/// west.
+pub fn apply_four_buttons_to_hat_modifier(
+    input: [bool; 4],
+    output: &VirtualHat,
+    modifier: &FourButtonsToHatModifier,
+) -> i32 {
+    let [north, east, south, west] = input.map(i32::from);
+    let previous = convert_vjoy_hat_to_angle(output.get());
+    let (vertical, horizontal) = (north - south, east - west);
+    let angle = match (vertical, horizontal) {
+        (0, 0) => -1,
+        (1, 0) => 0,
+        (1, 1) => 45,
+        (0, 1) => 90,
+        (-1, 1) => 135,
+        (-1, 0) => 180,
+        (-1, -1) => 225,
+        (0, -1) => 270,
+        _ => 315,
+    };
+
+    let (angle, keep_direction) = match modifier {
+        FourButtonsToHatModifier::EightWay { keep_direction } => (angle, *keep_direction),
+        FourButtonsToHatModifier::FourWay { keep_direction } => {
+            let angle = match angle {
+                -1 | 0 | 90 | 180 | 270 => angle,
+                // Diagonal: stay on the direction held first, vertical if both came at once
+                _ if previous == (angle + 45) % 360 || previous == (angle + 315) % 360 => previous,
+                _ if vertical == 1 => 0,
+                _ => 180,
+            };
+            (angle, *keep_direction)
+        }
+    };
+
+    match (angle, keep_direction) {
+        (-1, true) => previous,
+        (angle, _) => angle,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vjoy::{FourWayHat, HatState};
+
+    use crate::{
+        input::virtual_state::VirtualState,
+        rebind::{
+            four_buttons_to_hat::{apply_four_buttons_to_hat_modifier, FourButtonsToHatModifier},
+            hat_to_hat::convert_hat_type_to_vjoy,
+        },
+    };
+
+    const NORTH: [bool; 4] = [true, false, false, false];
+    const NORTH_EAST: [bool; 4] = [true, true, false, false];
+    const EAST: [bool; 4] = [false, true, false, false];
+    const NONE: [bool; 4] = [false; 4];
+
+    /// Angles output for `presses` in turn, written back to a hat of `hat_type` after each one
+    fn run(
+        modifier: FourButtonsToHatModifier,
+        hat_type: HatState,
+        presses: &[[bool; 4]],
+    ) -> Vec<HatState> {
+        let mut state = VirtualState::new(1, 0, 0, 1, hat_type);
+        let output = state.hats_mut().next().unwrap();
+        presses
+            .iter()
+            .map(|&input| {
+                let angle = apply_four_buttons_to_hat_modifier(input, output, &modifier);
+                output.set(convert_hat_type_to_vjoy(output.get(), angle));
+                output.get()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn eight_way_gives_diagonals_and_centers() {
+        let modifier = FourButtonsToHatModifier::EightWay {
+            keep_direction: false,
+        };
+        let continuous = run(
+            modifier.clone(),
+            HatState::Continuous(0),
+            &[NORTH, NORTH_EAST, EAST, NONE],
+        );
+        assert_eq!(
+            continuous,
+            vec![
+                HatState::Continuous(0),
+                HatState::Continuous(4500),
+                HatState::Continuous(9000),
+                HatState::Continuous(u32::MAX),
+            ]
+        );
+
+        // Opposite buttons cancel
+        let cancelled = run(
+            modifier,
+            HatState::Continuous(0),
+            &[[true, false, true, false], [true, false, true, true]],
+        );
+        assert_eq!(
+            cancelled,
+            vec![HatState::Continuous(u32::MAX), HatState::Continuous(27000)]
+        );
+    }
+
+    #[test]
+    fn four_way_keeps_the_first_direction_held() {
+        let modifier = FourButtonsToHatModifier::FourWay {
+            keep_direction: false,
+        };
+        let discrete = run(
+            modifier.clone(),
+            HatState::Discrete(FourWayHat::Centered),
+            &[EAST, NORTH_EAST, NORTH, NORTH_EAST, NONE],
+        );
+        assert_eq!(
+            discrete,
+            [
+                FourWayHat::East,
+                FourWayHat::East,
+                FourWayHat::North,
+                FourWayHat::North,
+                FourWayHat::Centered
+            ]
+            .map(HatState::Discrete)
+        );
+
+        // Pressed together the vertical direction wins
+        let together = run(
+            modifier,
+            HatState::Continuous(0),
+            &[[false, false, true, true]],
+        );
+        assert_eq!(together, vec![HatState::Continuous(18000)]);
+    }
+
+    #[test]
+    fn keep_direction_holds_the_last_one() {
+        let modifier = FourButtonsToHatModifier::EightWay {
+            keep_direction: true,
+        };
+        let kept = run(modifier, HatState::Continuous(0), &[NONE, NORTH_EAST, NONE]);
+        assert_eq!(
+            kept,
+            vec![
+                HatState::Continuous(u32::MAX),
+                HatState::Continuous(4500),
+                HatState::Continuous(4500),
+            ]
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use egui::{ComboBox, Ui};
+use serde::{Deserialize, Serialize};
+use strum::{EnumIter, IntoEnumIterator};
+
+/// Hat position reduced to the four cardinal directions
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum HatDirection {
+    #[default]
+    Centered,
+    North,
+    East,
+    South,
+    West,
+}
+
+/// How a hat selector treats the diagonals of an 8-way or continuous hat
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize, EnumIter)]
+pub enum HatDiagonals {
+    /// Nearest cardinal, exact diagonals go clockwise like 4-way vJoy hats
+    #[default]
+    Round,
+    /// Diagonals keep the previous direction
+    Ignore,
+}
+
+impl HatDiagonals {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HatDiagonals::Round => "Round to nearest",
+            HatDiagonals::Ignore => "Ignore",
+        }
+    }
+
+    pub fn widget(&mut self, ui: &mut Ui) {
+        ComboBox::from_id_source("hat_diagonals_widget")
+            .selected_text(self.name())
+            .show_ui(ui, |ui| {
+                for diagonals in HatDiagonals::iter() {
+                    ui.selectable_value(self, diagonals, diagonals.name());
+                }
+            });
+    }
+}
+
+impl HatDirection {
+    /// Direction of a hat angle in degrees, -1 if centered. `None` for an ignored diagonal, i.e. an
+    /// angle closer to 45° off than to a cardinal.
+    pub fn from_angle(angle: i32, diagonals: HatDiagonals) -> Option<Self> {
+        if angle < 0 {
+            return Some(HatDirection::Centered);
+        }
+
+        let angle = angle % 360;
+        let offset = angle % 90;
+        if diagonals == HatDiagonals::Ignore && (23..=67).contains(&offset) {
+            return None;
+        }
+
+        match (angle + 45) / 90 % 4 {
+            0 => Some(HatDirection::North),
+            1 => Some(HatDirection::East),
+            2 => Some(HatDirection::South),
+            _ => Some(HatDirection::West),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rebind::hat_select::{HatDiagonals, HatDirection};
+
+    #[test]
+    fn diagonals_round_clockwise_or_are_ignored() {
+        let round = |angle| HatDirection::from_angle(angle, HatDiagonals::Round);
+        let ignore = |angle| HatDirection::from_angle(angle, HatDiagonals::Ignore);
+
+        assert_eq!(round(-1), Some(HatDirection::Centered));
+        assert_eq!(round(0), Some(HatDirection::North));
+        assert_eq!(round(45), Some(HatDirection::East));
+        assert_eq!(round(135), Some(HatDirection::South));
+        assert_eq!(round(225), Some(HatDirection::West));
+        assert_eq!(round(315), Some(HatDirection::North));
+        assert_eq!(round(100), Some(HatDirection::East));
+
+        assert_eq!(ignore(-1), Some(HatDirection::Centered));
+        assert_eq!(ignore(270), Some(HatDirection::West));
+        assert_eq!(ignore(10), Some(HatDirection::North));
+        for angle in [45, 135, 225, 315] {
+            assert_eq!(ignore(angle), None);
+        }
+    }
+}
@@ -1,11 +1,20 @@
-use egui::Ui;
+use egui::{Slider, Ui};
+use egui_extras::Column;
 use serde::{Deserialize, Serialize};
 use strum::{AsRefStr, EnumIter, EnumString, EnumVariantNames};
-use vjoy::{Hat, HatState};
+use vjoy::HatState;
+
+use super::{rebind_table, VariantDescription, TABLE_ROW_HEIGHT};
+use crate::input::virtual_state::VirtualHat;
+
+/// Half the angle between two neighboring hat directions, in degrees
+const HAT_SECTOR_HALF_ANGLE: f64 = 22.5;
 
 /// Activation type and conditions for single input hat to single output hat rebinds
 ///
 /// ## Examples usages
+/// - Worn hat that flickers between N and NE when pushed straight up --> a hysteresis of 25° keeps
+///   reporting N until the hat clearly moves on.
 #[derive(
     Debug,
     PartialEq,
@@ -19,27 +28,116 @@ use vjoy::{Hat, HatState};
 )]
 #[serde(tag = "modifier")]
 pub enum HatToHatModifier {
-    /// Hat maps directly to output hat
-    Simple,
+    /// Hat maps directly to output hat. Once a direction is reported, the hat has to move more
+    /// than `hysteresis` degrees past the boundary to a neighboring direction to switch to it.
+    Simple {
+        #[serde(default)]
+        hysteresis: f64,
+
+        /// Last reported angle, `None` while centered
+        #[serde(skip_serializing)]
+        #[serde(default)]
+        reported: Option<i32>,
+    },
 }
 
 impl Default for HatToHatModifier {
     fn default() -> Self {
-        Self::Simple
+        Self::Simple {
+            hysteresis: 0.0,
+            reported: None,
+        }
+    }
+}
+
+impl VariantDescription for HatToHatModifier {
+    fn description(&self) -> &'static str {
+        match self {
+            HatToHatModifier::Simple { .. } => "Hat maps directly to the output hat",
+        }
     }
 }
 
 impl HatToHatModifier {
     pub fn widget(&mut self, ui: &mut Ui) {
-        ui.vertical(|_ui| match self {
-            HatToHatModifier::Simple => {}
+        ui.vertical(|ui| match self {
+            HatToHatModifier::Simple { hysteresis, .. } => {
+                rebind_table(ui)
+                    .column(Column::remainder())
+                    .body(|mut body| {
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Hysteresis:").on_hover_text(
+                                    "Degrees the hat has to move past the boundary to a neighboring \
+                                    direction before switching to it, 0 to switch right away.\n\
+                                    Eight way hats step 22.5° past the boundary, 25° holds a \
+                                    flickering diagonal off until the hat moves on or is released.",
+                                );
+                            });
+                            row.col(|ui| {
+                                ui.push_id("Hysteresis", |ui| {
+                                    ui.add(Slider::new(hysteresis, 0.0..=60.0).suffix("°"));
+                                });
+                            });
+                        });
+                    });
+            }
         });
     }
+
+    pub fn debug_state(&self) -> Vec<(String, String)> {
+        match self {
+            HatToHatModifier::Simple { reported, .. } => vec![(
+                "reported".to_string(),
+                reported.map_or("centered".to_string(), |angle| format!("{angle}°")),
+            )],
+        }
+    }
+
+    pub fn take_runtime_state(&mut self, from: &mut Self) {
+        match (self, from) {
+            (
+                HatToHatModifier::Simple { reported, .. },
+                HatToHatModifier::Simple { reported: from, .. },
+            ) => *reported = *from,
+        }
+    }
+}
+
+/// Signed difference `to - from` in degrees, within -180..=180
+fn angle_difference(from: i32, to: i32) -> i32 {
+    let difference = (to - from).rem_euclid(360);
+    match difference > 180 {
+        true => difference - 360,
+        false => difference,
+    }
 }
 
-pub fn apply_hat_modifier(input: i32, _output: &Hat, modifier: &mut HatToHatModifier) -> i32 {
+pub fn apply_hat_modifier(
+    input: i32,
+    _output: &VirtualHat,
+    modifier: &mut HatToHatModifier,
+) -> i32 {
     match modifier {
-        HatToHatModifier::Simple => input,
+        HatToHatModifier::Simple {
+            hysteresis,
+            reported,
+        } => {
+            // Centering and leaving the center are never held back
+            *reported = match (input, *reported) {
+                (-1, _) => None,
+                (_, Some(last)) if *hysteresis > 0.0 => {
+                    let past_boundary =
+                        angle_difference(last, input).abs() as f64 - HAT_SECTOR_HALF_ANGLE;
+                    match past_boundary > *hysteresis {
+                        true => Some(input),
+                        false => Some(last),
+                    }
+                }
+                _ => Some(input),
+            };
+            reported.unwrap_or(-1)
+        }
     }
 }
 
@@ -70,3 +168,76 @@ pub fn convert_hat_type_to_vjoy(hat_type: HatState, state: i32) -> vjoy::HatStat
         }
     }
 }
+
+/// Hat angle in degrees of a vJoy hat state, -1 if centered
+pub fn convert_vjoy_hat_to_angle(state: HatState) -> i32 {
+    match state {
+        HatState::Continuous(u32::MAX) => -1,
+        HatState::Continuous(value) => value as i32 / 100,
+        HatState::Discrete(fourway) => match fourway {
+            vjoy::FourWayHat::Centered => -1,
+            vjoy::FourWayHat::North => 0,
+            vjoy::FourWayHat::East => 90,
+            vjoy::FourWayHat::South => 180,
+            vjoy::FourWayHat::West => 270,
+        },
+    }
+}
+
+/// Hat angle in degrees of a vJoy hat state with the 1/100° resolution of continuous hats, `None`
+/// if centered
+pub fn vjoy_hat_degrees(state: HatState) -> Option<f32> {
+    match state {
+        HatState::Continuous(u32::MAX) => None,
+        HatState::Continuous(value) => Some(value as f32 / 100.0),
+        HatState::Discrete(_) => match convert_vjoy_hat_to_angle(state) {
+            -1 => None,
+            angle => Some(angle as f32),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vjoy::HatState;
+
+    use crate::{
+        input::virtual_state::VirtualState,
+        rebind::hat_to_hat::{apply_hat_modifier, HatToHatModifier},
+    };
+
+    fn run(hysteresis: f64, inputs: &[i32]) -> Vec<i32> {
+        let state = VirtualState::new(1, 0, 0, 1, HatState::Continuous(u32::MAX));
+        let output = state.hats().next().unwrap();
+        let mut modifier = HatToHatModifier::Simple {
+            hysteresis,
+            reported: None,
+        };
+        inputs
+            .iter()
+            .map(|&input| apply_hat_modifier(input, output, &mut modifier))
+            .collect()
+    }
+
+    #[test]
+    fn zero_hysteresis_passes_through() {
+        let inputs = [-1, 0, 45, 0, 45, 90, -1, 315, 10, 20];
+        assert_eq!(run(0.0, &inputs), inputs);
+    }
+
+    #[test]
+    fn holds_direction_while_oscillating_at_a_boundary() {
+        // Eight way hat flickering between N and NE when pushed straight up
+        assert_eq!(run(25.0, &[0, 45, 0, 45, 45, 0]), [0, 0, 0, 0, 0, 0]);
+        // Moving on to E or releasing still gets through, across north too
+        assert_eq!(
+            run(25.0, &[0, 45, 90, 45, -1, 45, 0, 315, 270]),
+            [0, 0, 90, 90, -1, 45, 45, 315, 315]
+        );
+        // Continuous angles dithering around the N/NE boundary
+        assert_eq!(
+            run(10.0, &[10, 25, 20, 30, 40, 43, 35]),
+            [10, 10, 10, 10, 10, 43, 43]
+        );
+    }
+}
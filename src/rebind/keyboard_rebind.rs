@@ -0,0 +1,729 @@
+use std::fmt::Display;
+
+use egui::{ComboBox, RichText, Slider, Ui};
+use egui_extras::Column;
+use serde::{Deserialize, Serialize};
+use strum::{AsRefStr, EnumIter, EnumString, EnumVariantNames, IntoEnumIterator};
+
+use super::{
+    rebind_table, rebind_viewer::DevicesInfoMap, validate_value_physical_button,
+    EnumVariantDropdown, PhysicalRead, TickContext, VariantDescription, SECTION_SPACING,
+    TABLE_ROW_HEIGHT,
+};
+use crate::{
+    error::Error,
+    input::{event_console::ControlKind, PhysicalDevice},
+};
+
+/// Keyboard rebinds --> synthesized key presses instead of a virtual device, e.g. for games that
+/// don't support enough joystick buttons
+#[derive(
+    Debug,
+    PartialEq,
+    Clone,
+    Serialize,
+    Deserialize,
+    AsRefStr,
+    EnumIter,
+    EnumString,
+    EnumVariantNames,
+)]
+#[serde(tag = "variant")]
+pub enum KeyboardRebind {
+    /// Presses `key` together with the enabled modifier keys
+    ButtonToKey {
+        src_device: String,
+        src_button: u32,
+        key: Key,
+        #[serde(default)]
+        ctrl: bool,
+        #[serde(default)]
+        shift: bool,
+        #[serde(default)]
+        alt: bool,
+
+        #[serde(flatten)]
+        modifier: ButtonToKeyModifier,
+    },
+}
+
+impl Default for KeyboardRebind {
+    fn default() -> Self {
+        Self::ButtonToKey {
+            src_device: Default::default(),
+            src_button: Default::default(),
+            key: Default::default(),
+            ctrl: false,
+            shift: false,
+            alt: false,
+            modifier: Default::default(),
+        }
+    }
+}
+
+impl Display for KeyboardRebind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("KeyboardRebind")
+    }
+}
+
+impl VariantDescription for KeyboardRebind {
+    fn description(&self) -> &'static str {
+        match self {
+            KeyboardRebind::ButtonToKey { .. } => {
+                "One physical button presses a keyboard key, optionally with Ctrl, Shift and Alt"
+            }
+        }
+    }
+}
+
+/// Activation type of button to key rebinds
+#[derive(
+    Debug,
+    PartialEq,
+    Clone,
+    Serialize,
+    Deserialize,
+    AsRefStr,
+    EnumIter,
+    EnumString,
+    EnumVariantNames,
+)]
+#[serde(tag = "modifier")]
+pub enum ButtonToKeyModifier {
+    /// Key is held as long as the button
+    Hold,
+    /// Each press taps the key for `duration` seconds, at least for one tick
+    Tap {
+        duration: f64,
+
+        #[serde(skip_serializing)]
+        #[serde(default)]
+        last_input: bool,
+
+        #[serde(skip_serializing)]
+        #[serde(default)]
+        tap_start: Option<f64>,
+    },
+}
+
+impl Default for ButtonToKeyModifier {
+    fn default() -> Self {
+        Self::Hold
+    }
+}
+
+impl VariantDescription for ButtonToKeyModifier {
+    fn description(&self) -> &'static str {
+        match self {
+            ButtonToKeyModifier::Hold => "Key is pressed and released with the button",
+            ButtonToKeyModifier::Tap { .. } => {
+                "Each press taps the key for a fixed time, regardless of how long the button is held"
+            }
+        }
+    }
+}
+
+impl ButtonToKeyModifier {
+    pub fn widget(&mut self, ui: &mut Ui) {
+        ui.vertical(|ui| match self {
+            ButtonToKeyModifier::Hold => {}
+            ButtonToKeyModifier::Tap { duration, .. } => {
+                rebind_table(ui)
+                    .column(Column::remainder())
+                    .body(|mut body| {
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Hold for:").on_hover_text(
+                                    "How long each tap holds the key, 0 for a single tick. \
+                                    Some games miss taps shorter than a frame.",
+                                );
+                            });
+                            row.col(|ui| {
+                                ui.push_id("TapDuration", |ui| {
+                                    ui.add(Slider::new(duration, 0.0..=1.0).suffix(" s"));
+                                });
+                            });
+                        });
+                    });
+            }
+        });
+    }
+
+    /// Whether the key is held this tick for the button state `input`
+    fn update(&mut self, input: bool, ctx: TickContext) -> bool {
+        match self {
+            ButtonToKeyModifier::Hold => input,
+            ButtonToKeyModifier::Tap {
+                duration,
+                last_input,
+                tap_start,
+            } => {
+                if input && !*last_input {
+                    *tap_start = Some(ctx.time);
+                } else if tap_start.is_some_and(|start| ctx.time - start >= *duration) {
+                    *tap_start = None;
+                }
+                *last_input = input;
+                tap_start.is_some()
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        if let ButtonToKeyModifier::Tap {
+            last_input,
+            tap_start,
+            ..
+        } = self
+        {
+            *last_input = false;
+            *tap_start = None;
+        }
+    }
+
+    pub fn debug_state(&self) -> Vec<(String, String)> {
+        match self {
+            ButtonToKeyModifier::Hold => Vec::new(),
+            ButtonToKeyModifier::Tap {
+                last_input,
+                tap_start,
+                ..
+            } => vec![
+                ("last_input".to_string(), last_input.to_string()),
+                (
+                    "tap_start".to_string(),
+                    match tap_start {
+                        Some(start) => format!("{start:.3} s"),
+                        None => "idle".to_string(),
+                    },
+                ),
+            ],
+        }
+    }
+
+    fn take_runtime_state(&mut self, from: &mut Self) {
+        if let (
+            ButtonToKeyModifier::Tap {
+                last_input,
+                tap_start,
+                ..
+            },
+            ButtonToKeyModifier::Tap {
+                last_input: from_last_input,
+                tap_start: from_tap_start,
+                ..
+            },
+        ) = (self, from)
+        {
+            *last_input = *from_last_input;
+            *tap_start = *from_tap_start;
+        }
+    }
+}
+
+/// Key with the modifier keys held along with it
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct KeyChord {
+    pub key: Key,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyChord {
+    /// Modifier keys first, in the order they are pressed
+    pub fn keys(&self) -> impl Iterator<Item = Key> {
+        [
+            (self.ctrl, Key::LeftCtrl),
+            (self.shift, Key::LeftShift),
+            (self.alt, Key::LeftAlt),
+            (true, self.key),
+        ]
+        .into_iter()
+        .filter_map(|(held, key)| held.then_some(key))
+    }
+}
+
+impl KeyboardRebind {
+    pub fn content_widget(&mut self, ui: &mut Ui, devices_info_map: &mut DevicesInfoMap) {
+        ui.vertical(|ui| match self {
+            KeyboardRebind::ButtonToKey {
+                src_device,
+                src_button,
+                key,
+                ctrl,
+                shift,
+                alt,
+                modifier,
+            } => {
+                rebind_table(ui)
+                    .column(Column::remainder())
+                    .body(|mut body| {
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("From").strong());
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Device:");
+                            });
+                            row.col(|ui| {
+                                devices_info_map.physical_devices_widget(ui, src_device);
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Button:");
+                            });
+                            row.col(|ui| {
+                                devices_info_map.physical_button_widget(ui, src_device, src_button);
+                            });
+                        });
+                        body.row(SECTION_SPACING, |mut row| {
+                            row.col(|_| {});
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("To").strong());
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Key:");
+                            });
+                            row.col(|ui| {
+                                key.dropdown_widget(ui);
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("With:");
+                            });
+                            row.col(|ui| {
+                                ui.horizontal(|ui| {
+                                    ui.checkbox(ctrl, "Ctrl");
+                                    ui.checkbox(shift, "Shift");
+                                    ui.checkbox(alt, "Alt");
+                                });
+                            });
+                        });
+                        body.row(SECTION_SPACING, |mut row| {
+                            row.col(|_| {});
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("Modifier:").strong());
+                            });
+                            row.col(|ui| {
+                                modifier.variant_dropdown_widget(ui);
+                            });
+                        });
+                    });
+
+                modifier.variant_description_label(ui);
+                modifier.widget(ui);
+            }
+        });
+    }
+
+    /// GUIDs of the physical devices the rebind reads
+    pub fn physical_devices_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            KeyboardRebind::ButtonToKey { src_device, .. } => vec![src_device],
+        }
+    }
+
+    /// Controls of physical devices the rebind reads
+    pub fn physical_reads(&self) -> Vec<PhysicalRead<'_>> {
+        match self {
+            KeyboardRebind::ButtonToKey {
+                src_device,
+                src_button,
+                ..
+            } => vec![PhysicalRead::new(
+                src_device,
+                ControlKind::Button,
+                *src_button,
+            )],
+        }
+    }
+
+    pub fn debug_state(&self) -> Vec<(String, String)> {
+        match self {
+            KeyboardRebind::ButtonToKey { modifier, .. } => modifier.debug_state(),
+        }
+    }
+
+    pub fn take_runtime_state(&mut self, from: &mut Self) {
+        match (self, from) {
+            (
+                KeyboardRebind::ButtonToKey { modifier, .. },
+                KeyboardRebind::ButtonToKey { modifier: from, .. },
+            ) => modifier.take_runtime_state(from),
+        }
+    }
+
+    /// Appends the key chord to `held_keys` if it is held this tick. Keys not appended are
+    /// released by the keyboard output.
+    pub fn process(
+        &mut self,
+        physical_devices: &[PhysicalDevice],
+        held_keys: &mut Vec<KeyChord>,
+        ctx: TickContext,
+    ) -> Result<(), Error> {
+        match self {
+            KeyboardRebind::ButtonToKey {
+                src_device,
+                src_button,
+                key,
+                ctrl,
+                shift,
+                alt,
+                modifier,
+            } => {
+                let input =
+                    validate_value_physical_button(physical_devices, src_device, src_button)?;
+                if modifier.update(input, ctx) {
+                    held_keys.push(KeyChord {
+                        key: *key,
+                        ctrl: *ctrl,
+                        shift: *shift,
+                        alt: *alt,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Forgets running taps, e.g. when the rebind's shift mode is left. The keys are released
+    /// by not being processed.
+    pub fn deactivate(&mut self) {
+        match self {
+            KeyboardRebind::ButtonToKey { modifier, .. } => modifier.reset(),
+        }
+    }
+}
+
+/// Keyboard key, sent by its set 1 scan code so games reading raw input see it as well
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    Copy,
+    Hash,
+    Default,
+    Serialize,
+    Deserialize,
+    AsRefStr,
+    EnumIter,
+    EnumString,
+    EnumVariantNames,
+)]
+pub enum Key {
+    #[default]
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    S,
+    T,
+    U,
+    V,
+    W,
+    X,
+    Y,
+    Z,
+    Digit0,
+    Digit1,
+    Digit2,
+    Digit3,
+    Digit4,
+    Digit5,
+    Digit6,
+    Digit7,
+    Digit8,
+    Digit9,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    F13,
+    F14,
+    F15,
+    F16,
+    F17,
+    F18,
+    F19,
+    F20,
+    F21,
+    F22,
+    F23,
+    F24,
+    Escape,
+    Tab,
+    CapsLock,
+    Space,
+    Enter,
+    Backspace,
+    Insert,
+    Delete,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Up,
+    Down,
+    Left,
+    Right,
+    Minus,
+    Equal,
+    LeftBracket,
+    RightBracket,
+    Backslash,
+    Semicolon,
+    Quote,
+    Backquote,
+    Comma,
+    Period,
+    Slash,
+    Numpad0,
+    Numpad1,
+    Numpad2,
+    Numpad3,
+    Numpad4,
+    Numpad5,
+    Numpad6,
+    Numpad7,
+    Numpad8,
+    Numpad9,
+    NumpadAdd,
+    NumpadSubtract,
+    NumpadMultiply,
+    NumpadDivide,
+    NumpadDecimal,
+    NumpadEnter,
+    NumLock,
+    ScrollLock,
+    PrintScreen,
+    LeftShift,
+    RightShift,
+    LeftCtrl,
+    RightCtrl,
+    LeftAlt,
+    RightAlt,
+}
+
+impl Key {
+    /// Set 1 scan code and whether it needs the extended (E0) prefix
+    pub fn scan_code(self) -> (u16, bool) {
+        match self {
+            Key::Escape => (0x01, false),
+            Key::Digit1 => (0x02, false),
+            Key::Digit2 => (0x03, false),
+            Key::Digit3 => (0x04, false),
+            Key::Digit4 => (0x05, false),
+            Key::Digit5 => (0x06, false),
+            Key::Digit6 => (0x07, false),
+            Key::Digit7 => (0x08, false),
+            Key::Digit8 => (0x09, false),
+            Key::Digit9 => (0x0A, false),
+            Key::Digit0 => (0x0B, false),
+            Key::Minus => (0x0C, false),
+            Key::Equal => (0x0D, false),
+            Key::Backspace => (0x0E, false),
+            Key::Tab => (0x0F, false),
+            Key::Q => (0x10, false),
+            Key::W => (0x11, false),
+            Key::E => (0x12, false),
+            Key::R => (0x13, false),
+            Key::T => (0x14, false),
+            Key::Y => (0x15, false),
+            Key::U => (0x16, false),
+            Key::I => (0x17, false),
+            Key::O => (0x18, false),
+            Key::P => (0x19, false),
+            Key::LeftBracket => (0x1A, false),
+            Key::RightBracket => (0x1B, false),
+            Key::Enter => (0x1C, false),
+            Key::LeftCtrl => (0x1D, false),
+            Key::A => (0x1E, false),
+            Key::S => (0x1F, false),
+            Key::D => (0x20, false),
+            Key::F => (0x21, false),
+            Key::G => (0x22, false),
+            Key::H => (0x23, false),
+            Key::J => (0x24, false),
+            Key::K => (0x25, false),
+            Key::L => (0x26, false),
+            Key::Semicolon => (0x27, false),
+            Key::Quote => (0x28, false),
+            Key::Backquote => (0x29, false),
+            Key::LeftShift => (0x2A, false),
+            Key::Backslash => (0x2B, false),
+            Key::Z => (0x2C, false),
+            Key::X => (0x2D, false),
+            Key::C => (0x2E, false),
+            Key::V => (0x2F, false),
+            Key::B => (0x30, false),
+            Key::N => (0x31, false),
+            Key::M => (0x32, false),
+            Key::Comma => (0x33, false),
+            Key::Period => (0x34, false),
+            Key::Slash => (0x35, false),
+            Key::RightShift => (0x36, false),
+            Key::NumpadMultiply => (0x37, false),
+            Key::LeftAlt => (0x38, false),
+            Key::Space => (0x39, false),
+            Key::CapsLock => (0x3A, false),
+            Key::F1 => (0x3B, false),
+            Key::F2 => (0x3C, false),
+            Key::F3 => (0x3D, false),
+            Key::F4 => (0x3E, false),
+            Key::F5 => (0x3F, false),
+            Key::F6 => (0x40, false),
+            Key::F7 => (0x41, false),
+            Key::F8 => (0x42, false),
+            Key::F9 => (0x43, false),
+            Key::F10 => (0x44, false),
+            // Sent with the extended flag, without it Windows reads it as Pause
+            Key::NumLock => (0x45, true),
+            Key::ScrollLock => (0x46, false),
+            Key::Numpad7 => (0x47, false),
+            Key::Numpad8 => (0x48, false),
+            Key::Numpad9 => (0x49, false),
+            Key::NumpadSubtract => (0x4A, false),
+            Key::Numpad4 => (0x4B, false),
+            Key::Numpad5 => (0x4C, false),
+            Key::Numpad6 => (0x4D, false),
+            Key::NumpadAdd => (0x4E, false),
+            Key::Numpad1 => (0x4F, false),
+            Key::Numpad2 => (0x50, false),
+            Key::Numpad3 => (0x51, false),
+            Key::Numpad0 => (0x52, false),
+            Key::NumpadDecimal => (0x53, false),
+            Key::F11 => (0x57, false),
+            Key::F12 => (0x58, false),
+            Key::F13 => (0x64, false),
+            Key::F14 => (0x65, false),
+            Key::F15 => (0x66, false),
+            Key::F16 => (0x67, false),
+            Key::F17 => (0x68, false),
+            Key::F18 => (0x69, false),
+            Key::F19 => (0x6A, false),
+            Key::F20 => (0x6B, false),
+            Key::F21 => (0x6C, false),
+            Key::F22 => (0x6D, false),
+            Key::F23 => (0x6E, false),
+            Key::F24 => (0x76, false),
+            Key::NumpadEnter => (0x1C, true),
+            Key::RightCtrl => (0x1D, true),
+            Key::NumpadDivide => (0x35, true),
+            Key::PrintScreen => (0x37, true),
+            Key::RightAlt => (0x38, true),
+            Key::Home => (0x47, true),
+            Key::Up => (0x48, true),
+            Key::PageUp => (0x49, true),
+            Key::Left => (0x4B, true),
+            Key::Right => (0x4D, true),
+            Key::End => (0x4F, true),
+            Key::Down => (0x50, true),
+            Key::PageDown => (0x51, true),
+            Key::Insert => (0x52, true),
+            Key::Delete => (0x53, true),
+        }
+    }
+
+    pub fn dropdown_widget(&mut self, ui: &mut Ui) {
+        ComboBox::from_id_source("key_dropdown")
+            .selected_text(self.as_ref())
+            .show_ui(ui, |ui| {
+                for key in Key::iter() {
+                    ui.selectable_value(self, key, key.as_ref());
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use strum::IntoEnumIterator;
+
+    use crate::rebind::{
+        keyboard_rebind::{ButtonToKeyModifier, Key, KeyChord},
+        TickContext,
+    };
+
+    #[test]
+    fn scan_codes_are_unique() {
+        let codes: HashSet<(u16, bool)> = Key::iter().map(Key::scan_code).collect();
+        assert_eq!(codes.len(), Key::iter().count());
+    }
+
+    #[test]
+    fn tap_holds_for_the_duration_once_per_press() {
+        let mut modifier = ButtonToKeyModifier::Tap {
+            duration: 0.05,
+            last_input: false,
+            tap_start: None,
+        };
+        let held: Vec<bool> = [false, true, true, true, true, true, true, false, true]
+            .into_iter()
+            .enumerate()
+            .map(|(tick, input)| {
+                let ctx = TickContext {
+                    tick: tick as u64,
+                    time: tick as f64 * 0.02,
+                    delta_t: 0.02,
+                };
+                modifier.update(input, ctx)
+            })
+            .collect();
+        // Held from the press for 50 ms, not again until the next press
+        assert_eq!(
+            held,
+            [false, true, true, true, false, false, false, false, true]
+        );
+    }
+
+    #[test]
+    fn chord_presses_modifiers_before_the_key() {
+        let chord = KeyChord {
+            key: Key::F1,
+            ctrl: true,
+            shift: false,
+            alt: true,
+        };
+        assert_eq!(
+            chord.keys().collect::<Vec<_>>(),
+            [Key::LeftCtrl, Key::LeftAlt, Key::F1]
+        );
+    }
+}
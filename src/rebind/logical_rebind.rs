@@ -1,16 +1,23 @@
 use std::fmt::Display;
 
 use egui::{RichText, Ui};
-use egui_extras::{Column, TableBuilder};
+use egui_extras::Column;
 use serde::{Deserialize, Serialize};
 
 use strum::{AsRefStr, EnumIter, EnumString, EnumVariantNames};
 
 use super::{
-    rebind_viewer::DevicesInfoMap, shift_mode_mask::ShiftModeMask, validate_value_physical_button,
+    hat_select::{HatDiagonals, HatDirection},
+    rebind_viewer::DevicesInfoMap,
+    shift_mode_mask::ShiftModeMask,
+    validate_value_physical_button, validate_value_physical_hat, IDDropdown, PhysicalRead,
+    VariantDescription,
+};
+use super::{rebind_table, TABLE_ROW_HEIGHT};
+use crate::{
+    error::Error,
+    input::{event_console::ControlKind, PhysicalDevice},
 };
-use super::{IDDropdown, TABLE_COLUMN_LEFT_WIDTH, TABLE_ROW_HEIGHT};
-use crate::{error::Error, input::PhysicalDevice};
 
 ///Logical rebinds --> no routing to virtual device
 #[derive(
@@ -36,6 +43,36 @@ pub enum LogicalRebind {
         src_button: u32,
         shift_mask: ShiftModeMask,
     },
+    /// Dead man's switch: all virtual output is held neutral unless the button is held (released if `invert`).
+    /// Multiple switches AND together.
+    OutputEnableSwitch {
+        src_device: String,
+        src_button: u32,
+        invert: bool,
+    },
+    /// Processes the overlay's overrides instead of the rebinds they replace while the button is held.
+    /// The last held overlay switch wins.
+    MomentaryOverlay {
+        src_device: String,
+        src_button: u32,
+        overlay: String,
+    },
+    /// Applies the mask of the hat direction and clears the bits of the other directions' masks.
+    /// With `latch` the last direction stays selected after the hat returns to center.
+    HatSelectShiftMode {
+        src_device: String,
+        src_hat: u32,
+        north_mask: ShiftModeMask,
+        east_mask: ShiftModeMask,
+        south_mask: ShiftModeMask,
+        west_mask: ShiftModeMask,
+        centered_mask: ShiftModeMask,
+        latch: bool,
+        diagonals: HatDiagonals,
+
+        #[serde(skip)]
+        selected: HatDirection,
+    },
 }
 
 impl Default for LogicalRebind {
@@ -54,6 +91,28 @@ impl Display for LogicalRebind {
     }
 }
 
+impl VariantDescription for LogicalRebind {
+    fn description(&self) -> &'static str {
+        match self {
+            LogicalRebind::MomentaryEnableShiftMode { .. } => {
+                "Adds the shift mode bits while the button is held"
+            }
+            LogicalRebind::MomentaryDisableShiftMode { .. } => {
+                "Removes the shift mode bits while the button is held"
+            }
+            LogicalRebind::OutputEnableSwitch { .. } => {
+                "Dead man's switch: all virtual output is held neutral unless the button is held"
+            }
+            LogicalRebind::MomentaryOverlay { .. } => {
+                "Replaces rebinds with their versions from the overlay while the button is held"
+            }
+            LogicalRebind::HatSelectShiftMode { .. } => {
+                "Selects the shift mode with the direction of a hat"
+            }
+        }
+    }
+}
+
 impl LogicalRebind {
     pub fn content_widget(&mut self, ui: &mut Ui, devices_info_map: &mut DevicesInfoMap) {
         ui.vertical(|ui| match self {
@@ -62,8 +121,7 @@ impl LogicalRebind {
                 src_button,
                 shift_mask,
             } => {
-                TableBuilder::new(ui)
-                    .column(Column::exact(TABLE_COLUMN_LEFT_WIDTH))
+                rebind_table(ui)
                     .column(Column::remainder())
                     .body(|mut body| {
                         body.row(TABLE_ROW_HEIGHT, |mut row| {
@@ -84,8 +142,7 @@ impl LogicalRebind {
                                 ui.label("Button:");
                             });
                             row.col(|ui| {
-                                let max = devices_info_map.get_physical_limits(src_device).0;
-                                src_button.id_dropdown_widget(max, ui);
+                                devices_info_map.physical_button_widget(ui, src_device, src_button);
                             });
                         });
                         body.row(TABLE_ROW_HEIGHT, |mut row| {
@@ -112,8 +169,7 @@ impl LogicalRebind {
                 src_button,
                 shift_mask,
             } => {
-                TableBuilder::new(ui)
-                    .column(Column::exact(TABLE_COLUMN_LEFT_WIDTH))
+                rebind_table(ui)
                     .column(Column::remainder())
                     .body(|mut body| {
                         body.row(TABLE_ROW_HEIGHT, |mut row| {
@@ -134,8 +190,7 @@ impl LogicalRebind {
                                 ui.label("Button:");
                             });
                             row.col(|ui| {
-                                let max = devices_info_map.get_physical_limits(src_device).0;
-                                src_button.id_dropdown_widget(max, ui);
+                                devices_info_map.physical_button_widget(ui, src_device, src_button);
                             });
                         });
                         body.row(TABLE_ROW_HEIGHT, |mut row| {
@@ -156,13 +211,271 @@ impl LogicalRebind {
                         });
                     });
             }
+
+            LogicalRebind::OutputEnableSwitch {
+                src_device,
+                src_button,
+                invert,
+            } => {
+                rebind_table(ui)
+                    .column(Column::remainder())
+                    .body(|mut body| {
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("From").strong());
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Device:");
+                            });
+                            row.col(|ui| {
+                                devices_info_map.physical_devices_widget(ui, src_device);
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Button:");
+                            });
+                            row.col(|ui| {
+                                devices_info_map.physical_button_widget(ui, src_device, src_button);
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|_| {});
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("Effect").strong());
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Invert:");
+                            });
+                            row.col(|ui| {
+                                ui.checkbox(invert, "")
+                                    .on_hover_text("Enable output while the button is released");
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|_| {});
+                            row.col(|ui| {
+                                ui.label(
+                                    RichText::new("All virtual output is neutral unless enabled")
+                                        .weak(),
+                                );
+                            });
+                        });
+                    });
+            }
+
+            LogicalRebind::MomentaryOverlay {
+                src_device,
+                src_button,
+                overlay,
+            } => {
+                rebind_table(ui)
+                    .column(Column::remainder())
+                    .body(|mut body| {
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("From").strong());
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Device:");
+                            });
+                            row.col(|ui| {
+                                devices_info_map.physical_devices_widget(ui, src_device);
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Button:");
+                            });
+                            row.col(|ui| {
+                                devices_info_map.physical_button_widget(ui, src_device, src_button);
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|_| {});
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("Effect").strong());
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Overlay:");
+                            });
+                            row.col(|ui| {
+                                devices_info_map.overlay_widget(ui, overlay);
+                            });
+                        });
+                    });
+            }
+
+            LogicalRebind::HatSelectShiftMode {
+                src_device,
+                src_hat,
+                north_mask,
+                east_mask,
+                south_mask,
+                west_mask,
+                centered_mask,
+                latch,
+                diagonals,
+                selected,
+            } => {
+                rebind_table(ui)
+                    .column(Column::remainder())
+                    .body(|mut body| {
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("From").strong());
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Device:");
+                            });
+                            row.col(|ui| {
+                                devices_info_map.physical_devices_widget(ui, src_device);
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Hat:");
+                            });
+                            row.col(|ui| {
+                                let max = devices_info_map.get_physical_limits(src_device).2;
+                                src_hat.id_dropdown_widget(max, ui);
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Diagonals:");
+                            });
+                            row.col(|ui| {
+                                diagonals.widget(ui);
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Latch:");
+                            });
+                            row.col(|ui| {
+                                ui.checkbox(latch, "").on_hover_text(
+                                    "Keep the last direction selected when the hat is centered",
+                                );
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|_| {});
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("Effect").strong());
+                            });
+                        });
+                        let masks = [
+                            ("North:", HatDirection::North, north_mask),
+                            ("East:", HatDirection::East, east_mask),
+                            ("South:", HatDirection::South, south_mask),
+                            ("West:", HatDirection::West, west_mask),
+                            ("Centered:", HatDirection::Centered, centered_mask),
+                        ];
+                        for (label, direction, mask) in masks {
+                            body.row(TABLE_ROW_HEIGHT, |mut row| {
+                                row.col(|ui| {
+                                    //highlight the live direction
+                                    let mut text = RichText::new(label);
+                                    if *selected == direction {
+                                        text = text.strong().color(ui.visuals().selection.bg_fill);
+                                    }
+                                    ui.label(text);
+                                });
+                                row.col(|ui| {
+                                    mask.widget(ui);
+                                });
+                            });
+                        }
+                    });
+            }
         });
     }
 
+    /// GUIDs of the physical devices the rebind reads
+    pub fn physical_devices_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            LogicalRebind::MomentaryEnableShiftMode { src_device, .. }
+            | LogicalRebind::MomentaryDisableShiftMode { src_device, .. }
+            | LogicalRebind::OutputEnableSwitch { src_device, .. }
+            | LogicalRebind::MomentaryOverlay { src_device, .. }
+            | LogicalRebind::HatSelectShiftMode { src_device, .. } => vec![src_device],
+        }
+    }
+
+    /// Controls of physical devices the rebind reads
+    pub fn physical_reads(&self) -> Vec<PhysicalRead<'_>> {
+        let read = match self {
+            LogicalRebind::MomentaryEnableShiftMode {
+                src_device,
+                src_button,
+                ..
+            }
+            | LogicalRebind::MomentaryDisableShiftMode {
+                src_device,
+                src_button,
+                ..
+            }
+            | LogicalRebind::OutputEnableSwitch {
+                src_device,
+                src_button,
+                ..
+            }
+            | LogicalRebind::MomentaryOverlay {
+                src_device,
+                src_button,
+                ..
+            } => PhysicalRead::new(src_device, ControlKind::Button, *src_button),
+            LogicalRebind::HatSelectShiftMode {
+                src_device,
+                src_hat,
+                ..
+            } => PhysicalRead::new(src_device, ControlKind::Hat, *src_hat),
+        };
+        vec![read]
+    }
+
+    pub fn debug_state(&self) -> Vec<(String, String)> {
+        match self {
+            LogicalRebind::HatSelectShiftMode { selected, .. } => {
+                vec![("selected".to_string(), format!("{selected:?}"))]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn take_runtime_state(&mut self, from: &mut Self) {
+        if let (
+            LogicalRebind::HatSelectShiftMode { selected, .. },
+            LogicalRebind::HatSelectShiftMode { selected: from, .. },
+        ) = (self, from)
+        {
+            *selected = *from;
+        }
+    }
+
     pub fn process(
         &mut self,
         physical_devices: &[PhysicalDevice],
         active_shift_mode: &mut ShiftModeMask,
+        output_enabled: &mut bool,
+        active_overlay: &mut Option<String>,
     ) -> Result<(), Error> {
         match self {
             LogicalRebind::MomentaryEnableShiftMode {
@@ -192,6 +505,63 @@ impl LogicalRebind {
                     active_shift_mode.0 |= shift_mask.0;
                 }
             }
+
+            LogicalRebind::OutputEnableSwitch {
+                src_device,
+                src_button,
+                invert,
+            } => {
+                //fail closed: an unavailable switch inhibits output as well
+                let input =
+                    validate_value_physical_button(physical_devices, src_device, src_button)
+                        .map(|input| input != *invert);
+                if !matches!(input, Ok(true)) {
+                    *output_enabled = false;
+                }
+                input?;
+            }
+
+            LogicalRebind::MomentaryOverlay {
+                src_device,
+                src_button,
+                overlay,
+            } => {
+                let input =
+                    validate_value_physical_button(physical_devices, src_device, src_button)?;
+                if input {
+                    *active_overlay = Some(overlay.clone());
+                }
+            }
+
+            LogicalRebind::HatSelectShiftMode {
+                src_device,
+                src_hat,
+                north_mask,
+                east_mask,
+                south_mask,
+                west_mask,
+                centered_mask,
+                latch,
+                diagonals,
+                selected,
+            } => {
+                let input = validate_value_physical_hat(physical_devices, src_device, src_hat)?;
+                match HatDirection::from_angle(input, *diagonals) {
+                    Some(HatDirection::Centered) if *latch => (),
+                    Some(direction) => *selected = direction,
+                    None => (),
+                }
+
+                let mask = match selected {
+                    HatDirection::Centered => *centered_mask,
+                    HatDirection::North => *north_mask,
+                    HatDirection::East => *east_mask,
+                    HatDirection::South => *south_mask,
+                    HatDirection::West => *west_mask,
+                };
+                let all = north_mask.0 | east_mask.0 | south_mask.0 | west_mask.0 | centered_mask.0;
+                active_shift_mode.0 = (active_shift_mode.0 & !all) | mask.0;
+            }
         }
 
         Ok(())
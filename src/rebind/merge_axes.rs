@@ -2,6 +2,249 @@ use egui::Ui;
 use serde::{Deserialize, Serialize};
 use strum::{AsRefStr, EnumIter, EnumString, EnumVariantNames};
 
+use super::{
+    axis_to_axis::convert_vjoy_axis_to_physical_range, output_range::OutputRange,
+    rebind_viewer::DevicesInfoMap, resolved_slots::ResolvedSlots, validate_value_virtual_axis,
+    IDDropdown, VariantDescription,
+};
+use crate::{
+    error::Error,
+    input::{PhysicalDevice, VirtualDevice},
+};
+
+/// Input axis of a [`super::reroute_rebind::RerouteRebind::AxisToAxis`],
+/// [`super::reroute_rebind::RerouteRebind::MergeAxes`] or
+/// [`super::reroute_rebind::RerouteRebind::MixToTwoAxes`] rebind
+///
+/// Virtual sources read the value written earlier in the same tick, so the producing rebind has to be processed first.
+#[derive(
+    Debug,
+    PartialEq,
+    Clone,
+    Serialize,
+    Deserialize,
+    AsRefStr,
+    EnumIter,
+    EnumString,
+    EnumVariantNames,
+)]
+#[serde(tag = "source")]
+pub enum AxisSource {
+    Physical { device: String, axis: u32 },
+    Virtual { device: u32, axis: u32 },
+}
+
+impl Default for AxisSource {
+    fn default() -> Self {
+        Self::Physical {
+            device: Default::default(),
+            axis: Default::default(),
+        }
+    }
+}
+
+impl VariantDescription for AxisSource {
+    fn description(&self) -> &'static str {
+        match self {
+            AxisSource::Physical { .. } => "Axis of a physical device",
+            AxisSource::Virtual { .. } => {
+                "Axis of a virtual device as written earlier in the same tick"
+            }
+        }
+    }
+}
+
+/// Helpers shared by [`AxisSource`] and [`super::axis_gain::ButtonSource`]. `$id` is the field of
+/// the control id, `$kind` the matching `ControlKind` and `VirtualControl` variant.
+macro_rules! control_source_helpers {
+    ($source:ident, $id:ident, $kind:ident) => {
+        impl $source {
+            pub fn physical(device: String, $id: u32) -> Self {
+                Self::Physical { device, $id }
+            }
+
+            pub fn physical_device(&self) -> Option<&str> {
+                match self {
+                    Self::Physical { device, .. } => Some(device),
+                    Self::Virtual { .. } => None,
+                }
+            }
+
+            pub fn physical_device_mut(&mut self) -> Option<&mut String> {
+                match self {
+                    Self::Physical { device, .. } => Some(device),
+                    Self::Virtual { .. } => None,
+                }
+            }
+
+            pub fn physical_read(&self) -> Option<$crate::rebind::PhysicalRead<'_>> {
+                match self {
+                    Self::Physical { device, $id } => Some($crate::rebind::PhysicalRead::new(
+                        device,
+                        $crate::input::event_console::ControlKind::$kind,
+                        *$id,
+                    )),
+                    Self::Virtual { .. } => None,
+                }
+            }
+
+            pub fn device_widget(
+                &mut self,
+                ui: &mut egui::Ui,
+                devices_info_map: &$crate::rebind::rebind_viewer::DevicesInfoMap,
+            ) {
+                match self {
+                    Self::Physical { device, .. } => {
+                        devices_info_map.physical_devices_widget(ui, device);
+                    }
+                    Self::Virtual { device, .. } => {
+                        devices_info_map.virtual_devices_widget(ui, device);
+                    }
+                }
+            }
+
+            pub fn virtual_read(&self) -> Option<$crate::rebind::VirtualControl> {
+                match self {
+                    Self::Physical { .. } => None,
+                    Self::Virtual { device, $id } => Some($crate::rebind::VirtualControl::$kind {
+                        device: *device,
+                        $id: *$id,
+                    }),
+                }
+            }
+        }
+    };
+}
+pub(super) use control_source_helpers;
+
+/// Module `$name` with the serde functions to store a source as flat keys of its rebind:
+/// `$device`/`$id` for a physical control, `$virtual_device`/`$id` for a virtual one. Configs from
+/// before virtual sources only have the physical keys.
+macro_rules! flat_source {
+    (
+        $(#[$doc:meta])*
+        $name:ident,
+        $source:ident,
+        $field:ident: $id:ident,
+        $device:ident,
+        $virtual_device:ident
+    ) => {
+        $(#[$doc])*
+        pub mod $name {
+            use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+            use super::$source;
+
+            #[derive(Serialize, Deserialize)]
+            #[serde(untagged)]
+            enum FlatSource {
+                Virtual { $virtual_device: u32, $id: u32 },
+                Physical { $device: String, $id: u32 },
+            }
+
+            pub fn serialize<S: Serializer>(
+                source: &$source,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                match source.clone() {
+                    $source::Physical { device, $field } => FlatSource::Physical {
+                        $device: device,
+                        $id: $field,
+                    },
+                    $source::Virtual { device, $field } => FlatSource::Virtual {
+                        $virtual_device: device,
+                        $id: $field,
+                    },
+                }
+                .serialize(serializer)
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<$source, D::Error> {
+                Ok(match FlatSource::deserialize(deserializer)? {
+                    FlatSource::Physical { $device, $id } => $source::Physical {
+                        device: $device,
+                        $field: $id,
+                    },
+                    FlatSource::Virtual {
+                        $virtual_device,
+                        $id,
+                    } => $source::Virtual {
+                        device: $virtual_device,
+                        $field: $id,
+                    },
+                })
+            }
+        }
+    };
+}
+pub(super) use flat_source;
+
+control_source_helpers!(AxisSource, axis, Axis);
+
+impl AxisSource {
+    pub fn axis_widget(&mut self, ui: &mut Ui, devices_info_map: &DevicesInfoMap) {
+        match self {
+            AxisSource::Physical { device, axis } => {
+                devices_info_map.physical_axis_widget(ui, device, axis);
+            }
+            AxisSource::Virtual { device, axis } => {
+                let max = devices_info_map.get_virtual_limits(device).1;
+                axis.id_dropdown_widget(max, ui);
+            }
+        }
+    }
+
+    /// Current value in the physical axis range -32768..=32767. Physical sources are read through
+    /// the slot of source `index` in `resolved`.
+    pub fn value(
+        &self,
+        resolved: &mut ResolvedSlots,
+        index: usize,
+        physical_devices: &[PhysicalDevice],
+        virtual_devices: &[VirtualDevice],
+    ) -> Result<i32, Error> {
+        match self {
+            AxisSource::Physical { device, axis } => {
+                resolved.physical_axis(index, physical_devices, device, axis)
+            }
+            AxisSource::Virtual { device, axis } => {
+                validate_value_virtual_axis(virtual_devices, device, axis)
+                    .map(convert_vjoy_axis_to_physical_range)
+            }
+        }
+    }
+}
+
+flat_source!(
+    /// Flat `src_device`/`src_axis` keys of an axis to axis rebind, a virtual source uses
+    /// `src_virtual_device` instead of `src_device`.
+    flat_src_axis,
+    AxisSource,
+    axis: src_axis,
+    src_device,
+    src_virtual_device
+);
+
+flat_source!(
+    /// Flat `src_0_device`/`src_0_axis` keys of a merge axes rebind, see [`flat_src_axis`]
+    flat_src_0_axis,
+    AxisSource,
+    axis: src_0_axis,
+    src_0_device,
+    src_0_virtual_device
+);
+
+flat_source!(
+    /// Flat `src_1_device`/`src_1_axis` keys of a merge axes rebind, see [`flat_src_axis`]
+    flat_src_1_axis,
+    AxisSource,
+    axis: src_1_axis,
+    src_1_device,
+    src_1_virtual_device
+);
+
 #[derive(
     Debug,
     PartialEq,
@@ -24,12 +267,28 @@ impl Default for MergeAxesModifier {
     }
 }
 
+impl VariantDescription for MergeAxesModifier {
+    fn description(&self) -> &'static str {
+        match self {
+            MergeAxesModifier::Add => "Sum of both axes, saturating at the axis limits",
+        }
+    }
+}
+
 impl MergeAxesModifier {
     pub fn widget(&mut self, ui: &mut Ui) {
         ui.vertical(|_ui| match self {
             MergeAxesModifier::Add => {}
         });
     }
+
+    /// Achievable output for all combinations of both sources
+    pub fn output_range(&self) -> OutputRange {
+        OutputRange::sampled_2d(|a, b| {
+            let merged = apply_merge_axes_modifier(a, b, &mut self.clone());
+            (merged as f64, merged.clamp(-32768, 32767))
+        })
+    }
 }
 
 // input range -32768..=32767
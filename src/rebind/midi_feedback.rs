@@ -0,0 +1,262 @@
+use egui::{ComboBox, DragValue, Grid, Ui};
+use serde::{Deserialize, Serialize};
+use strum::{EnumIter, IntoEnumIterator};
+use vjoy::ButtonState;
+
+use super::{rebind_viewer::DevicesInfoMap, shift_mode_mask::ShiftModeMask};
+use crate::input::VirtualDevice;
+
+/// State mirrored to a MIDI message, e.g. to light a mode LED on a button box
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "event")]
+pub enum MidiEvent {
+    /// All bits of `mode_mask` are set in the active shift mode
+    ShiftMode { mode_mask: ShiftModeMask },
+    /// A virtual button is pressed after the tick, including toggles and manual overrides
+    VirtualButton { device: u32, button: u32 },
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize, EnumIter)]
+pub enum MidiMessageKind {
+    /// Note on with `on_value` as velocity, note off with `off_value`
+    #[default]
+    Note,
+    /// Control change with `on_value` or `off_value`
+    ControlChange,
+}
+
+impl MidiMessageKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            MidiMessageKind::Note => "Note",
+            MidiMessageKind::ControlChange => "CC",
+        }
+    }
+}
+
+/// One row of the MIDI feedback table: the message sent whenever `event` starts or stops
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct MidiFeedback {
+    #[serde(flatten)]
+    pub event: MidiEvent,
+    /// MIDI channel 1..=16
+    pub channel: u8,
+    #[serde(default)]
+    pub kind: MidiMessageKind,
+    /// Note or controller number 0..=127
+    pub number: u8,
+    pub on_value: u8,
+    pub off_value: u8,
+}
+
+impl MidiFeedback {
+    pub fn is_active(&self, shift_mode: ShiftModeMask, virtual_devices: &[VirtualDevice]) -> bool {
+        match self.event {
+            MidiEvent::ShiftMode { mode_mask } => shift_mode.0 & mode_mask.0 == mode_mask.0,
+            MidiEvent::VirtualButton { device, button } => virtual_devices
+                .iter()
+                .find(|d| d.id == device)
+                .and_then(|d| d.handle.buttons().nth(button.checked_sub(1)? as usize))
+                .map_or(false, |b| b.get() == ButtonState::Pressed),
+        }
+    }
+
+    /// Raw message for the event starting (`active`) or stopping. Out of range fields are
+    /// clamped, so a hand edited config can't produce an invalid message.
+    pub fn message(&self, active: bool) -> [u8; 3] {
+        let channel = self.channel.clamp(1, 16) - 1;
+        let value = match active {
+            true => self.on_value,
+            false => self.off_value,
+        };
+        let status = match (self.kind, active) {
+            (MidiMessageKind::Note, true) => 0x90,
+            (MidiMessageKind::Note, false) => 0x80,
+            (MidiMessageKind::ControlChange, _) => 0xB0,
+        };
+        [status | channel, self.number & 0x7F, value & 0x7F]
+    }
+}
+
+pub fn midi_feedback_widget(
+    ui: &mut Ui,
+    feedback: &mut Vec<MidiFeedback>,
+    devices_info_map: &DevicesInfoMap,
+) {
+    let mut remove = None;
+    Grid::new("MidiFeedback")
+        .num_columns(7)
+        .striped(true)
+        .show(ui, |ui| {
+            for (index, entry) in feedback.iter_mut().enumerate() {
+                ui.push_id(("MidiFeedbackEvent", index), |ui| match &mut entry.event {
+                    MidiEvent::ShiftMode { mode_mask } => mode_mask.widget(ui),
+                    MidiEvent::VirtualButton { device, button } => {
+                        ui.horizontal(|ui| {
+                            let device_name = devices_info_map
+                                .virtual_devices
+                                .get(device)
+                                .map_or_else(|| device.to_string(), |info| info.name.clone());
+                            ComboBox::from_id_source("MidiFeedbackDevice")
+                                .selected_text(device_name)
+                                .show_ui(ui, |ui| {
+                                    for (id, info) in devices_info_map.virtual_devices.iter() {
+                                        ui.selectable_value(device, *id, &info.name);
+                                    }
+                                });
+                            let num_buttons = devices_info_map
+                                .virtual_devices
+                                .get(device)
+                                .map_or(128, |info| info.num_buttons.max(1) as u32);
+                            ui.add(
+                                DragValue::new(button)
+                                    .clamp_range(1..=num_buttons)
+                                    .prefix("button "),
+                            );
+                        });
+                    }
+                });
+
+                ComboBox::from_id_source(("MidiFeedbackKind", index))
+                    .selected_text(entry.kind.label())
+                    .width(50.0)
+                    .show_ui(ui, |ui| {
+                        for kind in MidiMessageKind::iter() {
+                            ui.selectable_value(&mut entry.kind, kind, kind.label());
+                        }
+                    });
+                ui.add(
+                    DragValue::new(&mut entry.channel)
+                        .clamp_range(1..=16)
+                        .prefix("ch "),
+                );
+                ui.add(DragValue::new(&mut entry.number).clamp_range(0..=127))
+                    .on_hover_text("Note or controller number");
+                ui.add(
+                    DragValue::new(&mut entry.on_value)
+                        .clamp_range(0..=127)
+                        .prefix("on "),
+                );
+                ui.add(
+                    DragValue::new(&mut entry.off_value)
+                        .clamp_range(0..=127)
+                        .prefix("off "),
+                );
+                if ui.button("X").clicked() {
+                    remove = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+
+    if let Some(index) = remove {
+        feedback.remove(index);
+    }
+    let number = feedback.len().min(127) as u8;
+    let entry = |event| MidiFeedback {
+        event,
+        channel: 1,
+        kind: MidiMessageKind::Note,
+        number,
+        on_value: 127,
+        off_value: 0,
+    };
+    ui.horizontal(|ui| {
+        if ui.button("Add shift mode").clicked() {
+            feedback.push(entry(MidiEvent::ShiftMode {
+                mode_mask: ShiftModeMask(0b00000001),
+            }));
+        }
+        if ui.button("Add virtual button").clicked() {
+            feedback.push(entry(MidiEvent::VirtualButton {
+                device: devices_info_map
+                    .virtual_devices
+                    .keys()
+                    .next()
+                    .copied()
+                    .unwrap_or(1),
+                button: 1,
+            }));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rebind::{
+        midi_feedback::{MidiEvent, MidiFeedback, MidiMessageKind},
+        shift_mode_mask::ShiftModeMask,
+    };
+
+    fn feedback(kind: MidiMessageKind) -> MidiFeedback {
+        MidiFeedback {
+            event: MidiEvent::ShiftMode {
+                mode_mask: ShiftModeMask(0b00000010),
+            },
+            channel: 3,
+            kind,
+            number: 60,
+            on_value: 127,
+            off_value: 0,
+        }
+    }
+
+    #[test]
+    fn notes_and_control_changes_encode_channel_and_value() {
+        let note = feedback(MidiMessageKind::Note);
+        assert_eq!(note.message(true), [0x92, 60, 127]);
+        assert_eq!(note.message(false), [0x82, 60, 0]);
+
+        let cc = feedback(MidiMessageKind::ControlChange);
+        assert_eq!(cc.message(true), [0xB2, 60, 127]);
+        assert_eq!(cc.message(false), [0xB2, 60, 0]);
+
+        let out_of_range = MidiFeedback {
+            channel: 0,
+            number: 200,
+            on_value: 255,
+            ..note
+        };
+        assert_eq!(out_of_range.message(true), [0x90, 200 & 0x7F, 0x7F]);
+    }
+
+    #[test]
+    fn shift_mode_event_needs_all_bits() {
+        let note = feedback(MidiMessageKind::Note);
+        assert!(note.is_active(ShiftModeMask(0b00000010), &[]));
+        assert!(note.is_active(ShiftModeMask(0b00000011), &[]));
+        assert!(!note.is_active(ShiftModeMask(0b00000001), &[]));
+
+        let button = MidiFeedback {
+            event: MidiEvent::VirtualButton {
+                device: 1,
+                button: 0,
+            },
+            ..note
+        };
+        assert!(!button.is_active(ShiftModeMask(0), &[]));
+    }
+
+    #[test]
+    fn table_round_trips_through_toml() {
+        #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+        struct Table {
+            midi_feedback: Vec<MidiFeedback>,
+        }
+        let table = Table {
+            midi_feedback: vec![
+                feedback(MidiMessageKind::Note),
+                MidiFeedback {
+                    event: MidiEvent::VirtualButton {
+                        device: 2,
+                        button: 5,
+                    },
+                    ..feedback(MidiMessageKind::ControlChange)
+                },
+            ],
+        };
+        let toml = toml::to_string(&table).unwrap();
+        assert!(toml.contains("event = \"VirtualButton\""));
+        assert_eq!(toml::from_str::<Table>(&toml).unwrap(), table);
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::{HashMap, HashSet};
+
+use egui::{Checkbox, Ui};
+use egui_extras::Column;
+use serde::{Deserialize, Serialize};
+use vjoy::ButtonState;
+
+use super::{
+    axis_to_axis::convert_axis_to_vjoy_range, hat_to_hat::convert_hat_type_to_vjoy, rebind_table,
+    rebind_viewer::DevicesInfoMap, validate_physical_device, VirtualControl, TABLE_ROW_HEIGHT,
+};
+use crate::{
+    error::Error,
+    input::{PhysicalDevice, VirtualDevice},
+};
+
+/// Copies every input of one physical device 1:1 onto a virtual device.
+///
+/// Runs after the reroute rebinds and only writes controls none of them wrote this tick, so
+/// explicit rebinds take precedence. Inputs the virtual device doesn't have are skipped.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Default)]
+pub struct Mirror {
+    pub src_guid: String,
+    pub dst_id: u32,
+}
+
+impl Mirror {
+    /// Writes all unclaimed controls and returns them.
+    pub fn process(
+        &self,
+        physical_devices: &[PhysicalDevice],
+        virtual_devices: &mut [VirtualDevice],
+        written: &HashMap<VirtualControl, usize>,
+    ) -> Result<HashSet<VirtualControl>, Error> {
+        let src = validate_physical_device(physical_devices, &self.src_guid)?;
+        let Some(dst) = virtual_devices.iter_mut().find(|d| d.id == self.dst_id) else {
+            return Err(Error::RebindValidateVirtualDeviceFailed(self.dst_id));
+        };
+
+        let device = self.dst_id;
+        let mut mirrored = HashSet::new();
+        let mut claim = |control: VirtualControl| {
+            let free = !written.contains_key(&control);
+            if free {
+                mirrored.insert(control);
+            }
+            free
+        };
+
+        let buttons = src.input_state.buttons().zip(dst.handle.buttons_mut());
+        for (index, (input, output)) in buttons.enumerate() {
+            let button = index as u32 + 1;
+            if claim(VirtualControl::Button { device, button }) {
+                output.set(match input {
+                    true => ButtonState::Pressed,
+                    false => ButtonState::Released,
+                });
+            }
+        }
+
+        let axes = src.input_state.axes().zip(dst.handle.axes_mut());
+        for (index, (input, output)) in axes.enumerate() {
+            let axis = index as u32 + 1;
+            if claim(VirtualControl::Axis { device, axis }) {
+                output.set(convert_axis_to_vjoy_range(*input));
+            }
+        }
+
+        let hats = src.input_state.hats().zip(dst.handle.hats_mut());
+        for (index, (input, output)) in hats.enumerate() {
+            let hat = index as u32 + 1;
+            if claim(VirtualControl::Hat { device, hat }) {
+                output.set(convert_hat_type_to_vjoy(output.get(), *input));
+            }
+        }
+
+        Ok(mirrored)
+    }
+}
+
+/// Controls that were mirrored last tick but are neither mirrored nor written now.
+///
+/// They are reset to neutral instead of holding the last mirrored value.
+pub fn released_mirror_controls(
+    last: &HashSet<VirtualControl>,
+    current: &HashSet<VirtualControl>,
+    written: &HashMap<VirtualControl, usize>,
+) -> Vec<VirtualControl> {
+    last.difference(current)
+        .filter(|control| !written.contains_key(control))
+        .copied()
+        .collect()
+}
+
+/// Compact list of control ids grouped by kind, e.g. "buttons 1-3, 5 | axes 1 | hats 1"
+pub fn describe_controls(controls: &[VirtualControl]) -> String {
+    let mut buttons = Vec::new();
+    let mut axes = Vec::new();
+    let mut hats = Vec::new();
+    for control in controls {
+        match *control {
+            VirtualControl::Button { button, .. } => buttons.push(button),
+            VirtualControl::Axis { axis, .. } => axes.push(axis),
+            VirtualControl::Hat { hat, .. } => hats.push(hat),
+        }
+    }
+
+    let groups: Vec<String> = [("buttons", buttons), ("axes", axes), ("hats", hats)]
+        .into_iter()
+        .filter(|(_, ids)| !ids.is_empty())
+        .map(|(kind, ids)| format!("{kind} {}", id_ranges(ids)))
+        .collect();
+
+    match groups.is_empty() {
+        true => "none".to_string(),
+        false => groups.join(" | "),
+    }
+}
+
+fn id_ranges(mut ids: Vec<u32>) -> String {
+    ids.sort_unstable();
+    ids.dedup();
+
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for id in ids {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == id => *end = id,
+            _ => ranges.push((id, id)),
+        }
+    }
+
+    ranges
+        .into_iter()
+        .map(|(start, end)| match start == end {
+            true => start.to_string(),
+            false => format!("{start}-{end}"),
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+pub fn mirror_widget(ui: &mut Ui, mirror: &mut Option<Mirror>, devices_info_map: &DevicesInfoMap) {
+    let mut enabled = mirror.is_some();
+    rebind_table(ui)
+        .column(Column::remainder())
+        .body(|mut body| {
+            body.row(TABLE_ROW_HEIGHT, |mut row| {
+                row.col(|ui| {
+                    ui.label("Mirror:");
+                });
+                row.col(|ui| {
+                    ui.add(Checkbox::new(&mut enabled, "Enabled"));
+                });
+            });
+
+            let Some(mirror) = mirror else {
+                return;
+            };
+            body.row(TABLE_ROW_HEIGHT, |mut row| {
+                row.col(|ui| {
+                    ui.label("From device:");
+                });
+                row.col(|ui| {
+                    devices_info_map.physical_devices_widget(ui, &mut mirror.src_guid);
+                });
+            });
+            body.row(TABLE_ROW_HEIGHT, |mut row| {
+                row.col(|ui| {
+                    ui.label("To device:");
+                });
+                row.col(|ui| {
+                    devices_info_map.virtual_devices_widget(ui, &mut mirror.dst_id);
+                });
+            });
+        });
+
+    match (enabled, mirror.is_some()) {
+        (true, false) => *mirror = Some(Mirror::default()),
+        (false, true) => *mirror = None,
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use crate::rebind::{
+        mirror::{describe_controls, released_mirror_controls},
+        VirtualControl,
+    };
+
+    #[test]
+    fn describe_groups_ranges() {
+        let controls = [5, 1, 2, 3, 9]
+            .map(|button| VirtualControl::Button { device: 1, button })
+            .into_iter()
+            .chain([VirtualControl::Hat { device: 1, hat: 1 }])
+            .collect::<Vec<_>>();
+        assert_eq!(describe_controls(&controls), "buttons 1-3, 5, 9 | hats 1");
+        assert_eq!(describe_controls(&[]), "none");
+    }
+
+    #[test]
+    fn controls_taken_over_by_rebinds_are_not_released() {
+        let axis = |axis| VirtualControl::Axis { device: 1, axis };
+        let last = HashSet::from([axis(1), axis(2), axis(3)]);
+        let current = HashSet::from([axis(1)]);
+        let written = HashMap::from([(axis(2), 0)]);
+
+        assert_eq!(
+            released_mirror_controls(&last, &current, &written),
+            vec![axis(3)]
+        );
+    }
+}
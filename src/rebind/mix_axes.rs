@@ -0,0 +1,129 @@
+use egui::{ProgressBar, Slider, Ui};
+use serde::{Deserialize, Serialize};
+
+/// Gains and output inversion of a [`super::reroute_rebind::RerouteRebind::MixToTwoAxes`] rebind
+///
+/// The first output is `a * gains[0] + b * gains[1]`, the second `a * gains[2] - b * gains[3]`,
+/// e.g. pitch and roll to the left and right elevon. Inverting an output flips its sign after
+/// mixing, for mirrored control surfaces.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct AxisMix {
+    pub gains: [f32; 4],
+    pub invert: [bool; 2],
+}
+
+impl Default for AxisMix {
+    /// Either source alone reaches half travel, both together full travel
+    fn default() -> Self {
+        Self {
+            gains: [0.5; 4],
+            invert: [false; 2],
+        }
+    }
+}
+
+impl AxisMix {
+    /// Both outputs for the sources `a` and `b`, input and output range -32768..=32767
+    pub fn apply(&self, a: i32, b: i32) -> [i32; 2] {
+        self.mixed(a, b)
+            .map(|value| value.round().clamp(i16::MIN as f32, i16::MAX as f32) as i32)
+    }
+
+    /// Both outputs before they are rounded and limited to the axis range
+    fn mixed(&self, a: i32, b: i32) -> [f32; 2] {
+        let mix = |a_gain: f32, b_gain: f32, invert: bool| {
+            let value = a as f32 * a_gain + b as f32 * b_gain;
+            match invert {
+                true => -value,
+                false => value,
+            }
+        };
+        [
+            mix(self.gains[0], self.gains[1], self.invert[0]),
+            mix(self.gains[2], -self.gains[3], self.invert[1]),
+        ]
+    }
+
+    /// Achievable range of both outputs for all combinations of the sources
+    pub fn output_ranges(&self) -> [OutputRange; 2] {
+        [0, 1].map(|output| {
+            OutputRange::sampled_2d(|a, b| {
+                (self.mixed(a, b)[output] as f64, self.apply(a, b)[output])
+            })
+        })
+    }
+
+    /// Gain and inversion settings with both outputs previewed for adjustable source positions
+    pub fn widget(&mut self, ui: &mut Ui) {
+        let labels = [
+            "Output 1, gain A:",
+            "Output 1, gain B:",
+            "Output 2, gain A:",
+            "Output 2, gain B:",
+        ];
+        for (gain, label) in self.gains.iter_mut().zip(labels) {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                ui.add(Slider::new(gain, 0.0..=2.0));
+            });
+        }
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.invert[0], "Invert output 1");
+            ui.checkbox(&mut self.invert[1], "Invert output 2");
+        });
+
+        ui.separator();
+        ui.label("Preview");
+        let id = ui.id().with("MixPreview");
+        let mut sources = ui.data_mut(|d| *d.get_temp_mut_or_default::<[f32; 2]>(id));
+        for (source, label) in sources.iter_mut().zip(["Source A:", "Source B:"]) {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                ui.add(Slider::new(source, -1.0..=1.0));
+            });
+        }
+        ui.data_mut(|d| d.insert_temp(id, sources));
+
+        let to_axis = |source: f32| (source * i16::MAX as f32).round() as i32;
+        let outputs = self.apply(to_axis(sources[0]), to_axis(sources[1]));
+        for (index, output) in outputs.into_iter().enumerate() {
+            let fraction = (output - i16::MIN as i32) as f32 / u16::MAX as f32;
+            ui.add(ProgressBar::new(fraction).text(format!(
+                "Output {}: {:+.0} %",
+                index + 1,
+                output as f32 / 327.67
+            )));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rebind::mix_axes::AxisMix;
+
+    #[test]
+    fn outputs_saturate_with_both_sources_at_the_limits() {
+        let full = AxisMix {
+            gains: [1.0; 4],
+            invert: [false; 2],
+        };
+        assert_eq!(full.apply(32767, 32767), [32767, 0]);
+        assert_eq!(full.apply(-32768, 32767), [-1, -32768]);
+        assert_eq!(full.apply(32767, -32768), [-1, 32767]);
+        assert_eq!(full.apply(-32768, -32768), [-32768, 0]);
+
+        let half = AxisMix::default();
+        assert_eq!(half.apply(32767, 32767), [32767, 0]);
+        assert_eq!(half.apply(-32768, 32767), [-1, -32768]);
+    }
+
+    #[test]
+    fn inversion_flips_one_output_after_mixing() {
+        let mix = AxisMix {
+            gains: [1.0, 0.0, 1.0, 0.0],
+            invert: [false, true],
+        };
+        assert_eq!(mix.apply(16384, 32767), [16384, -16384]);
+        assert_eq!(mix.apply(-32768, 0), [-32768, 32767]);
+    }
+}
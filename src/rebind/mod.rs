@@ -1,31 +1,70 @@
 pub mod activation_interval;
+pub mod axis_gain;
+pub mod axis_neutral;
+pub mod axis_roles;
 pub mod axis_to_axis;
+pub mod axis_to_button;
+pub mod batch_rename;
 pub mod button_to_button;
+pub mod capabilities;
+pub mod dependencies;
+pub mod device_swap;
+pub mod four_buttons_to_hat;
+pub mod hat_select;
 pub mod hat_to_hat;
+pub mod keyboard_rebind;
 pub mod logical_rebind;
 pub mod merge_axes;
+pub mod midi_feedback;
+pub mod mirror;
+pub mod mix_axes;
+pub mod mode_chips;
+pub mod mode_presets;
+pub mod multi_action;
+pub mod output_range;
+pub mod output_smoothing;
+pub mod overlay;
+pub mod processing_budget;
+pub mod quick_map;
+pub mod rebind_id;
 pub mod rebind_processor;
 pub mod rebind_viewer;
 pub mod reroute_rebind;
+pub mod resolved_slots;
+pub mod safe_edit;
+pub mod shift_conflicts;
 pub mod shift_mode_mask;
+pub mod source_lost;
 pub mod two_buttons_to_axis;
 pub mod virtual_axis_trim;
 pub mod virtual_rebind;
 
-use std::fmt::Display;
+use std::{collections::HashMap, fmt::Display};
 
 use crate::{
-    error::Error,
-    input::{PhysicalDevice, VirtualDevice},
+    error::{DeviceContext, Error},
+    input::{
+        event_console::ControlKind,
+        virtual_state::{VirtualAxis, VirtualButton, VirtualHat},
+        PhysicalDevice, VirtualDevice,
+    },
 };
-use egui::{ComboBox, Ui};
+use egui::{ComboBox, Label, RichText, TextEdit, Ui};
 use egui_extras::{Column, TableBuilder};
 use serde::{Deserialize, Serialize};
-use vjoy::{Axis, Button, ButtonState, Hat, HatState};
+use vjoy::{ButtonState, HatState};
 
 use self::{
-    logical_rebind::LogicalRebind, rebind_viewer::DevicesInfoMap, reroute_rebind::RerouteRebind,
-    shift_mode_mask::ShiftModeMask, virtual_rebind::VirtualRebind,
+    keyboard_rebind::KeyboardRebind,
+    logical_rebind::LogicalRebind,
+    rebind_id::RebindId,
+    rebind_viewer::DevicesInfoMap,
+    reroute_rebind::RerouteRebind,
+    resolved_slots::ResolvedSlots,
+    safe_edit::PendingEdit,
+    shift_mode_mask::ShiftModeMask,
+    source_lost::{source_lost_widget, SourceLostPolicy},
+    virtual_rebind::{ProcessingPhase, VirtualRebind},
 };
 
 use strum::{AsRefStr, EnumIter, EnumString, EnumVariantNames};
@@ -35,20 +74,111 @@ pub const SECTION_SPACING: f32 = 10.0;
 pub const TABLE_COLUMN_LEFT_WIDTH: f32 = 120.0;
 pub const TABLE_ROW_HEIGHT: f32 = 20.0;
 pub const TABLE_TOP_BUTTONS_WIDTH: f32 = 80.0;
+/// Available width below which the label column of rebind tables shrinks to its content
+pub const NARROW_TABLE_WIDTH: f32 = 320.0;
+/// Most characters the name editor accepts. Longer names from hand-edited configs are kept.
+pub const REBIND_NAME_MAX_CHARS: usize = 100;
+
+/// Rebind name in single quotes for log lines and reports. Control characters are escaped, so a
+/// name can neither break a line nor fake another entry.
+pub struct QuotedName<'a>(pub &'a str);
+
+impl Display for QuotedName<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("'")?;
+        for c in self.0.chars() {
+            match c.is_control() {
+                true => write!(f, "{}", c.escape_default())?,
+                false => write!(f, "{c}")?,
+            }
+        }
+        f.write_str("'")
+    }
+}
+
+/// Table with the label column of the rebind widgets, followed by the columns the caller adds.
+/// Narrow layouts, e.g. a squeezed split view, size the label column to its content and clip it.
+pub fn rebind_table(ui: &mut Ui) -> TableBuilder<'_> {
+    let label_column = match ui.available_width() < NARROW_TABLE_WIDTH {
+        true => Column::auto().at_most(TABLE_COLUMN_LEFT_WIDTH).clip(true),
+        false => Column::exact(TABLE_COLUMN_LEFT_WIDTH),
+    };
+    TableBuilder::new(ui).column(label_column)
+}
 
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Rebind {
     pub name: String,
+    /// Generated when a config from before rebind ids is loaded
+    #[serde(default)]
+    pub id: RebindId,
     pub mode_mask: ShiftModeMask,
+    /// Inactive while any of these shift modes is active
+    #[serde(default)]
+    pub exclude_mask: ShiftModeMask,
+    /// Free text for the user, e.g. what the rebind is matched to in a game
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// Output of a reroute rebind while a physical source device is disconnected, `None` for the
+    /// default of the rebind type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_source_lost: Option<SourceLostPolicy>,
 
     #[serde(flatten)]
     pub rebind_type: RebindType,
+
+    #[serde(skip)]
+    pub resolved: ResolvedSlots,
+
+    #[serde(skip)]
+    pub pending: PendingEdit,
+}
+
+/// Compares what the user configured, neither the cached slots nor an unapplied edit are part of
+/// that. Destructured so a new field can't be left out by accident.
+impl PartialEq for Rebind {
+    fn eq(&self, other: &Self) -> bool {
+        let Rebind {
+            name,
+            id,
+            mode_mask,
+            exclude_mask,
+            notes,
+            on_source_lost,
+            rebind_type,
+            resolved: _,
+            pending: _,
+        } = self;
+        *name == other.name
+            && *id == other.id
+            && *mode_mask == other.mode_mask
+            && *exclude_mask == other.exclude_mask
+            && *notes == other.notes
+            && *on_source_lost == other.on_source_lost
+            && *rebind_type == other.rebind_type
+    }
 }
 
 impl Rebind {
-    pub fn widget(&mut self, ui: &mut Ui, devices_name_map: &mut DevicesInfoMap) {
-        TableBuilder::new(ui)
-            .column(Column::exact(TABLE_COLUMN_LEFT_WIDTH))
+    /// New id, active in `mode_mask` and every other setting at its default
+    pub fn new(name: String, mode_mask: ShiftModeMask, rebind_type: RebindType) -> Self {
+        Self {
+            name,
+            id: Default::default(),
+            mode_mask,
+            exclude_mask: Default::default(),
+            notes: None,
+            on_source_lost: None,
+            rebind_type,
+            resolved: Default::default(),
+            pending: Default::default(),
+        }
+    }
+
+    /// With `safe_edit`, changes to the rebind type are collected in [`Rebind::pending`] until
+    /// they are applied.
+    pub fn widget(&mut self, ui: &mut Ui, devices_name_map: &mut DevicesInfoMap, safe_edit: bool) {
+        rebind_table(ui)
             .column(Column::remainder())
             .body(|mut body| {
                 body.row(TABLE_ROW_HEIGHT, |mut row| {
@@ -56,7 +186,20 @@ impl Rebind {
                         ui.label("Name:");
                     });
                     row.col(|ui| {
-                        ui.text_edit_singleline(&mut self.name);
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                TextEdit::singleline(&mut self.name)
+                                    .char_limit(REBIND_NAME_MAX_CHARS),
+                            );
+                            let count = self.name.chars().count();
+                            let counter = format!("{count}/{REBIND_NAME_MAX_CHARS}");
+                            match count > REBIND_NAME_MAX_CHARS {
+                                true => ui.label(
+                                    RichText::new(counter).color(ui.visuals().warn_fg_color),
+                                ),
+                                false => ui.weak(counter),
+                            };
+                        });
                     });
                 });
                 body.row(TABLE_ROW_HEIGHT, |mut row| {
@@ -67,11 +210,61 @@ impl Rebind {
                         self.mode_mask.widget(ui);
                     });
                 });
+                body.row(TABLE_ROW_HEIGHT, |mut row| {
+                    row.col(|ui| {
+                        ui.label("Inactive mode:");
+                    });
+                    row.col(|ui| {
+                        self.exclude_mask.widget(ui);
+                    });
+                });
+                if let RebindType::Reroute { rebind } = &self.rebind_type {
+                    let default = SourceLostPolicy::default_for(rebind);
+                    body.row(TABLE_ROW_HEIGHT, |mut row| {
+                        row.col(|ui| {
+                            ui.label("Source lost:");
+                        });
+                        row.col(|ui| {
+                            source_lost_widget(ui, &mut self.on_source_lost, default);
+                        });
+                    });
+                }
             });
 
+        ui.horizontal_top(|ui| {
+            ui.add_sized(
+                [TABLE_COLUMN_LEFT_WIDTH, TABLE_ROW_HEIGHT],
+                Label::new("Notes:"),
+            );
+            let mut notes = self.notes.clone().unwrap_or_default();
+            if ui
+                .add(TextEdit::multiline(&mut notes).desired_rows(2))
+                .changed()
+            {
+                self.notes = (!notes.trim().is_empty()).then_some(notes);
+            }
+        });
+
         ui.add_space(SECTION_SPACING);
 
-        self.rebind_type.widget(ui, devices_name_map);
+        if !safe_edit && !self.pending.is_pending() {
+            self.rebind_type.widget(ui, devices_name_map);
+            return;
+        }
+
+        self.pending.widget(&self.rebind_type, ui, devices_name_map);
+        if self.pending.is_pending() {
+            ui.add_space(SECTION_SPACING);
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("Unapplied changes").color(ui.visuals().warn_fg_color));
+                if ui.button("Apply").clicked() {
+                    self.pending.apply(&mut self.rebind_type);
+                }
+                if ui.button("Revert").clicked() {
+                    self.pending.revert();
+                }
+            });
+        }
     }
 
     pub fn is_active(&self, active_shift_mode: ShiftModeMask) -> bool {
@@ -84,10 +277,84 @@ impl Rebind {
             }
         }
 
-        active
+        active && active_shift_mode.0 & self.exclude_mask.0 == 0
+    }
+
+    pub fn virtual_writes(&self) -> Vec<VirtualControl> {
+        match &self.rebind_type {
+            RebindType::Logical { .. } | RebindType::Keyboard { .. } => Vec::new(),
+            RebindType::Reroute { rebind } => rebind.virtual_writes(),
+            RebindType::Virtual { rebind, .. } => rebind.virtual_writes(),
+        }
+    }
+
+    pub fn virtual_reads(&self) -> Vec<VirtualControl> {
+        match &self.rebind_type {
+            RebindType::Reroute { rebind } => rebind.virtual_reads(),
+            RebindType::Virtual { rebind, .. } => rebind.virtual_reads(),
+            RebindType::Logical { .. } | RebindType::Keyboard { .. } => Vec::new(),
+        }
+    }
+
+    /// Internal state of the rebind's modifier as name/value pairs, empty for stateless modifiers.
+    ///
+    /// Shown in the rebind viewer's "Debug state" expander. Every rebind type and modifier with
+    /// runtime fields has a `debug_state` of the same shape, this one dispatches to them.
+    pub fn debug_state(&self) -> Vec<(String, String)> {
+        match &self.rebind_type {
+            RebindType::Logical { rebind } => rebind.debug_state(),
+            RebindType::Reroute { rebind } => rebind.debug_state(),
+            RebindType::Virtual { rebind, .. } => rebind.debug_state(),
+            RebindType::Keyboard { rebind } => rebind.debug_state(),
+        }
+    }
+}
+
+/// Control of a physical device a rebind reads, ids are 1-based like in the UI
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PhysicalRead<'a> {
+    pub device: &'a str,
+    pub kind: ControlKind,
+    pub id: u32,
+}
+
+impl<'a> PhysicalRead<'a> {
+    pub fn new(device: &'a str, kind: ControlKind, id: u32) -> Self {
+        Self { device, kind, id }
     }
 }
 
+/// List a rebind belongs to.
+///
+/// Global rebinds are loaded regardless of the config and processed before the config's rebinds.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum RebindScope {
+    Global,
+    Profile,
+}
+
+/// One output control of a virtual device, used to track which rebind writes where.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum VirtualControl {
+    Button { device: u32, button: u32 },
+    Axis { device: u32, axis: u32 },
+    Hat { device: u32, hat: u32 },
+}
+
+/// Rebinds writing to each virtual control as (scope, index within the scope's list, name).
+pub type VirtualUsage = HashMap<VirtualControl, Vec<(RebindScope, usize, String)>>;
+
+/// Timing of one processing tick, passed down to every rebind and modifier.
+///
+/// `tick` counts processed input polls and is monotonic for the lifetime of [`crate::input::Input`],
+/// so recorded input can be replayed tick by tick regardless of frame pacing.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct TickContext {
+    pub tick: u64,
+    pub time: f64,
+    pub delta_t: f64,
+}
+
 #[derive(
     Debug,
     PartialEq,
@@ -112,16 +379,63 @@ pub enum RebindType {
     Virtual {
         #[serde(flatten)]
         rebind: VirtualRebind,
+
+        #[serde(default)]
+        phase: ProcessingPhase,
+    },
+    Keyboard {
+        #[serde(flatten)]
+        rebind: KeyboardRebind,
     },
 }
 
 impl RebindType {
+    /// Moves the runtime state of `from` into `self` wherever both are the same variant, so edited
+    /// settings replace a running rebind without resetting it, e.g. a toggled output or a running
+    /// macro. Parts that changed variant start from their default state.
+    pub fn take_runtime_state(&mut self, from: &mut Self) {
+        match (self, from) {
+            (RebindType::Logical { rebind }, RebindType::Logical { rebind: from }) => {
+                rebind.take_runtime_state(from)
+            }
+            (RebindType::Reroute { rebind }, RebindType::Reroute { rebind: from }) => {
+                rebind.take_runtime_state(from)
+            }
+            (RebindType::Virtual { rebind, .. }, RebindType::Virtual { rebind: from, .. }) => {
+                rebind.take_runtime_state(from)
+            }
+            (RebindType::Keyboard { rebind }, RebindType::Keyboard { rebind: from }) => {
+                rebind.take_runtime_state(from)
+            }
+            _ => {}
+        }
+    }
+
+    /// GUIDs of the physical devices the rebind reads
+    pub fn physical_devices_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            RebindType::Logical { rebind } => rebind.physical_devices_mut(),
+            RebindType::Reroute { rebind } => rebind.physical_devices_mut(),
+            RebindType::Keyboard { rebind } => rebind.physical_devices_mut(),
+            RebindType::Virtual { .. } => Vec::new(),
+        }
+    }
+
+    /// Controls of physical devices the rebind reads
+    pub fn physical_reads(&self) -> Vec<PhysicalRead<'_>> {
+        match self {
+            RebindType::Logical { rebind } => rebind.physical_reads(),
+            RebindType::Reroute { rebind } => rebind.physical_reads(),
+            RebindType::Keyboard { rebind } => rebind.physical_reads(),
+            RebindType::Virtual { .. } => Vec::new(),
+        }
+    }
+
     pub fn widget(&mut self, ui: &mut Ui, devices_name_map: &mut DevicesInfoMap) {
         match self {
             RebindType::Logical { rebind } => {
                 ui.push_id("LogicalRebindMainTable", |ui| {
-                    TableBuilder::new(ui)
-                        .column(Column::exact(TABLE_COLUMN_LEFT_WIDTH))
+                    rebind_table(ui)
                         .column(Column::remainder())
                         .body(|mut body| {
                             body.row(TABLE_ROW_HEIGHT, |mut row| {
@@ -142,14 +456,14 @@ impl RebindType {
                             });
                         });
                 });
+                rebind.variant_description_label(ui);
                 ui.add_space(SECTION_SPACING);
                 rebind.content_widget(ui, devices_name_map);
             }
 
             RebindType::Reroute { rebind } => {
                 ui.push_id("RerouteRebindMainTable", |ui| {
-                    TableBuilder::new(ui)
-                        .column(Column::exact(TABLE_COLUMN_LEFT_WIDTH))
+                    rebind_table(ui)
                         .column(Column::remainder())
                         .body(|mut body| {
                             body.row(TABLE_ROW_HEIGHT, |mut row| {
@@ -170,14 +484,14 @@ impl RebindType {
                             });
                         });
                 });
+                rebind.variant_description_label(ui);
                 ui.add_space(SECTION_SPACING);
                 rebind.content_widget(ui, devices_name_map);
             }
 
-            RebindType::Virtual { rebind } => {
+            RebindType::Virtual { rebind, phase } => {
                 ui.push_id("VirtualRebindMainTable", |ui| {
-                    TableBuilder::new(ui)
-                        .column(Column::exact(TABLE_COLUMN_LEFT_WIDTH))
+                    rebind_table(ui)
                         .column(Column::remainder())
                         .body(|mut body| {
                             body.row(TABLE_ROW_HEIGHT, |mut row| {
@@ -196,8 +510,47 @@ impl RebindType {
                                     rebind.variant_dropdown_widget(ui);
                                 });
                             });
+                            body.row(TABLE_ROW_HEIGHT, |mut row| {
+                                row.col(|ui| {
+                                    ui.label("Phase:");
+                                });
+                                row.col(|ui| {
+                                    ui.push_id("VirtualRebindPhase", |ui| {
+                                        phase.variant_dropdown_widget(ui);
+                                    });
+                                });
+                            });
+                        });
+                });
+                rebind.variant_description_label(ui);
+                ui.add_space(SECTION_SPACING);
+                rebind.content_widget(ui, devices_name_map);
+            }
+
+            RebindType::Keyboard { rebind } => {
+                ui.push_id("KeyboardRebindMainTable", |ui| {
+                    rebind_table(ui)
+                        .column(Column::remainder())
+                        .body(|mut body| {
+                            body.row(TABLE_ROW_HEIGHT, |mut row| {
+                                row.col(|ui| {
+                                    ui.label("Type:");
+                                });
+                                row.col(|ui| {
+                                    ui.label("Keyboard");
+                                });
+                            });
+                            body.row(TABLE_ROW_HEIGHT, |mut row| {
+                                row.col(|ui| {
+                                    ui.label("Variant:");
+                                });
+                                row.col(|ui| {
+                                    rebind.variant_dropdown_widget(ui);
+                                });
+                            });
                         });
                 });
+                rebind.variant_description_label(ui);
                 ui.add_space(SECTION_SPACING);
                 rebind.content_widget(ui, devices_name_map);
             }
@@ -205,13 +558,19 @@ impl RebindType {
     }
 }
 
+/// Short user facing explanation of an enum variant, shown as tooltip and help text in the editors.
+pub trait VariantDescription {
+    fn description(&self) -> &'static str;
+}
+
 trait EnumVariantDropdown {
     fn variant_dropdown_widget(&mut self, ui: &mut Ui);
+    fn variant_description_label(&self, ui: &mut Ui);
 }
 
 impl<T> EnumVariantDropdown for T
 where
-    T: IntoEnumIterator + AsRef<str> + VariantNames + PartialEq,
+    T: IntoEnumIterator + AsRef<str> + VariantNames + PartialEq + VariantDescription,
 {
     fn variant_dropdown_widget(&mut self, ui: &mut Ui) {
         ui.horizontal(|ui| {
@@ -220,11 +579,19 @@ where
                 .selected_text(name_self)
                 .show_ui(ui, |ui| {
                     for (i, var) in Self::iter().enumerate() {
-                        ui.selectable_value(self, var, Self::VARIANTS[i]);
+                        let description = var.description();
+                        ui.selectable_value(self, var, Self::VARIANTS[i])
+                            .on_hover_text(description);
                     }
-                });
+                })
+                .response
+                .on_hover_text(self.description());
         });
     }
+
+    fn variant_description_label(&self, ui: &mut Ui) {
+        ui.label(RichText::new(self.description()).weak());
+    }
 }
 
 trait IDDropdown<T> {
@@ -243,19 +610,43 @@ impl IDDropdown<u32> for u32 {
     }
 }
 
+fn validate_physical_device<'a>(
+    physical_devices: &'a [PhysicalDevice],
+    src_device: &String,
+) -> Result<&'a PhysicalDevice, Error> {
+    if src_device.is_empty() {
+        return Err(Error::EmptyRebindOrInvalidID());
+    }
+
+    match physical_devices.iter().find(|d| d.guid == *src_device) {
+        Some(device) if device.connected => Ok(device),
+        // Unplugged this session, the name is still known
+        Some(device) => Err(Error::SourceDeviceDisconnected(device_context(device))),
+        None => Err(Error::SourceDeviceDisconnected(DeviceContext::new(
+            src_device, None,
+        ))),
+    }
+}
+
+fn device_context(device: &PhysicalDevice) -> DeviceContext {
+    DeviceContext::new(&device.guid, Some(device.name()))
+}
+
 fn validate_value_physical_button(
     physical_devices: &[PhysicalDevice],
     src_device: &String,
     src_button: &u32,
 ) -> Result<bool, Error> {
-    if *src_button == 0 || src_device.is_empty() {
+    if *src_button == 0 {
         return Err(Error::EmptyRebindOrInvalidID());
     }
 
-    let Some(button) = physical_devices.iter().find(|d|d.guid == *src_device)
-        .and_then(|d| d.input_state.buttons().nth(*src_button as usize - 1)
-    ) else {
-        return Err(Error::RebindValidatePhysicalButtonFailed(src_device.to_owned(), src_button.to_owned()))
+    let device = validate_physical_device(physical_devices, src_device)?;
+    let Some(button) = device.input_state.buttons().nth(*src_button as usize - 1) else {
+        return Err(Error::RebindValidatePhysicalButtonFailed(
+            device_context(device),
+            src_button.to_owned(),
+        ));
     };
     Ok(*button)
 }
@@ -265,14 +656,16 @@ fn validate_value_physical_hat(
     src_device: &String,
     src_hat: &u32,
 ) -> Result<i32, Error> {
-    if *src_hat == 0 || src_device.is_empty() {
+    if *src_hat == 0 {
         return Err(Error::EmptyRebindOrInvalidID());
     }
 
-    let Some(hat) = physical_devices.iter().find(|d|d.guid == *src_device)
-        .and_then(|d| d.input_state.hats().nth(*src_hat as usize - 1)
-    ) else {
-        return Err(Error::RebindValidatePhysicalHatFailed(src_device.to_owned(), src_hat.to_owned()))
+    let device = validate_physical_device(physical_devices, src_device)?;
+    let Some(hat) = device.input_state.hats().nth(*src_hat as usize - 1) else {
+        return Err(Error::RebindValidatePhysicalHatFailed(
+            device_context(device),
+            src_hat.to_owned(),
+        ));
     };
     Ok(*hat)
 }
@@ -282,14 +675,16 @@ fn validate_value_physical_axis(
     src_device: &String,
     src_axis: &u32,
 ) -> Result<i32, Error> {
-    if *src_axis == 0 || src_device.is_empty() {
+    if *src_axis == 0 {
         return Err(Error::EmptyRebindOrInvalidID());
     }
 
-    let Some(axis) = physical_devices.iter().find(|d|d.guid == *src_device)
-        .and_then(|d| d.input_state.axes().nth(*src_axis as usize - 1)
-    ) else {
-        return Err(Error::RebindValidatePhysicalAxisFailed(src_device.to_owned(), src_axis.to_owned()))
+    let device = validate_physical_device(physical_devices, src_device)?;
+    let Some(axis) = device.input_state.axes().nth(*src_axis as usize - 1) else {
+        return Err(Error::RebindValidatePhysicalAxisFailed(
+            device_context(device),
+            src_axis.to_owned(),
+        ));
     };
     Ok(*axis)
 }
@@ -353,7 +748,7 @@ fn validate_handle_virtual_button<'a>(
     virtual_devices: &'a mut [VirtualDevice],
     dst_device: &u32,
     dst_button: &u32,
-) -> Result<&'a mut Button, Error> {
+) -> Result<&'a mut VirtualButton, Error> {
     if *dst_button == 0 || *dst_device == 0 {
         return Err(Error::EmptyRebindOrInvalidID());
     }
@@ -370,7 +765,7 @@ fn validate_handle_virtual_hat<'a>(
     virtual_devices: &'a mut [VirtualDevice],
     dst_device: &u32,
     dst_hat: &u32,
-) -> Result<&'a mut Hat, Error> {
+) -> Result<&'a mut VirtualHat, Error> {
     if *dst_hat == 0 || *dst_device == 0 {
         return Err(Error::EmptyRebindOrInvalidID());
     }
@@ -387,7 +782,7 @@ fn validate_handle_virtual_axis<'a>(
     virtual_devices: &'a mut [VirtualDevice],
     dst_device: &u32,
     dst_axis: &u32,
-) -> Result<&'a mut Axis, Error> {
+) -> Result<&'a mut VirtualAxis, Error> {
     if *dst_axis == 0 || *dst_device == 0 {
         return Err(Error::EmptyRebindOrInvalidID());
     }
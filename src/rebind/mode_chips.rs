@@ -0,0 +1,157 @@
+use egui::{Color32, Response, RichText, Ui};
+
+use super::{shift_mode_mask::ShiftModeMask, Rebind};
+use crate::visualization::Palette;
+
+/// Shift modes referenced by a config, shown as colored chips. The color only depends on the mode,
+/// so a mode has the same color in the rebind headers, the filter bar and the side panel.
+#[derive(Debug, Clone)]
+pub struct ModeChips {
+    /// The base mode without shift bits, the default mode and the mode of each rebind
+    pub modes: Vec<ShiftModeMask>,
+    palette: Palette,
+}
+
+impl ModeChips {
+    pub fn new<'a>(
+        default_shift_mode: ShiftModeMask,
+        rebinds: impl IntoIterator<Item = &'a Rebind>,
+        palette: Palette,
+    ) -> Self {
+        let mut modes: Vec<ShiftModeMask> = [ShiftModeMask(0), default_shift_mode]
+            .into_iter()
+            .chain(rebinds.into_iter().map(|rebind| rebind.mode_mask))
+            .collect();
+        modes.sort_by_key(|mode| mode.0);
+        modes.dedup();
+        Self { modes, palette }
+    }
+
+    /// Referenced modes `rebind` is active in
+    pub fn active_in(&self, rebind: &Rebind) -> Vec<ShiftModeMask> {
+        self.modes
+            .iter()
+            .copied()
+            .filter(|mode| rebind.is_active(*mode))
+            .collect()
+    }
+
+    pub fn color(&self, mode: ShiftModeMask) -> Color32 {
+        mode_color(self.palette, mode)
+    }
+
+    /// Filter bar above the rebind list, clicking the selected chip again clears the filter
+    pub fn filter_bar(&self, ui: &mut Ui, filter: &mut Option<ShiftModeMask>) {
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Active in mode:");
+            if ui.selectable_label(filter.is_none(), "all").clicked() {
+                *filter = None;
+            }
+            for mode in self.modes.iter().copied() {
+                let selected = *filter == Some(mode);
+                let text = chip_text(mode, self.color(mode));
+                if ui
+                    .selectable_label(selected, text)
+                    .on_hover_text(format!("Show only rebinds active in mode {mode}"))
+                    .clicked()
+                {
+                    *filter = (!selected).then_some(mode);
+                }
+            }
+        });
+    }
+}
+
+pub fn mode_color(palette: Palette, mode: ShiftModeMask) -> Color32 {
+    palette.color(mode.0 as usize)
+}
+
+/// "base" for the mode without shift bits, otherwise the set bits numbered from the left as in the
+/// mask widget, e.g. "1+8" for `10000001`
+pub fn mode_label(mode: ShiftModeMask) -> String {
+    if mode.0 == 0 {
+        return "base".to_string();
+    }
+    (0..8)
+        .filter(|i| mode.0 & (0b10000000 >> i) != 0)
+        .map(|i| (i + 1).to_string())
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+fn chip_text(mode: ShiftModeMask, color: Color32) -> RichText {
+    let [r, g, b, _] = color.to_array();
+    let luminance = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+    let text_color = match luminance > 140.0 {
+        true => Color32::BLACK,
+        false => Color32::WHITE,
+    };
+    RichText::new(mode_label(mode))
+        .small()
+        .color(text_color)
+        .background_color(color)
+}
+
+/// Non-interactive chip with the full mask as tooltip
+pub fn chip(ui: &mut Ui, mode: ShiftModeMask, color: Color32) -> Response {
+    ui.label(chip_text(mode, color))
+        .on_hover_text(format!("Mode {mode}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rebind::{
+        axis_gain::ButtonSource,
+        button_to_button::ButtonToButtonModifier,
+        mode_chips::{mode_label, ModeChips},
+        reroute_rebind::RerouteRebind,
+        shift_mode_mask::ShiftModeMask,
+        Rebind, RebindType,
+    };
+    use crate::visualization::Palette;
+
+    #[test]
+    fn rebinds_get_the_referenced_modes_they_are_active_in() {
+        let rebind = |mode_mask: u8, exclude_mask: u8| Rebind {
+            exclude_mask: ShiftModeMask(exclude_mask),
+            ..Rebind::new(
+                String::new(),
+                ShiftModeMask(mode_mask),
+                RebindType::Reroute {
+                    rebind: RerouteRebind::ButtonToButton {
+                        src: ButtonSource::physical("stick".to_string(), 1),
+                        dst_device: 1,
+                        dst_button: 1,
+                        modifier: ButtonToButtonModifier::Simple,
+                    },
+                },
+            )
+        };
+        let rebinds = [
+            rebind(0, 0),
+            rebind(0b1, 0),
+            rebind(0b11, 0),
+            rebind(0, 0b10),
+        ];
+        let chips = ModeChips::new(ShiftModeMask(0b1), &rebinds, Palette::default());
+        assert_eq!(
+            chips.modes,
+            vec![ShiftModeMask(0), ShiftModeMask(0b1), ShiftModeMask(0b11)]
+        );
+
+        let active = |rebind: &Rebind| chips.active_in(rebind);
+        assert_eq!(active(&rebinds[0]), chips.modes);
+        assert_eq!(
+            active(&rebinds[1]),
+            vec![ShiftModeMask(0b1), ShiftModeMask(0b11)]
+        );
+        assert_eq!(active(&rebinds[2]), vec![ShiftModeMask(0b11)]);
+        assert_eq!(
+            active(&rebinds[3]),
+            vec![ShiftModeMask(0), ShiftModeMask(0b1)]
+        );
+
+        assert_eq!(mode_label(ShiftModeMask(0)), "base");
+        assert_eq!(mode_label(ShiftModeMask(0b10000001)), "1+8");
+    }
+}
@@ -0,0 +1,147 @@
+use egui::{ComboBox, DragValue, Grid, Slider, Ui};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    axis_neutral::AXIS_CENTER, rebind_viewer::DevicesInfoMap, shift_mode_mask::ShiftModeMask,
+    validate_handle_virtual_axis,
+};
+use crate::input::VirtualDevice;
+
+/// Value a virtual axis snaps to on the tick the shift mode switches into `mode_mask`, e.g. flaps
+/// to 50 % when entering a landing mode. Rebinds writing the axis afterwards overwrite it.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct ModeAxisPreset {
+    /// Required bits of the shift mode, like the mode mask of rebinds
+    pub mode_mask: ShiftModeMask,
+    pub device: u32,
+    pub axis: u32,
+    /// vJoy range 0..=32767
+    pub value: i32,
+}
+
+impl ModeAxisPreset {
+    fn matches(&self, mode: ShiftModeMask) -> bool {
+        mode.0 & self.mode_mask.0 == self.mode_mask.0
+    }
+}
+
+/// Presets whose mode is entered by switching from `from` to `to`
+pub fn entered_presets(
+    presets: &[ModeAxisPreset],
+    from: ShiftModeMask,
+    to: ShiftModeMask,
+) -> impl Iterator<Item = &ModeAxisPreset> {
+    presets
+        .iter()
+        .filter(move |preset| preset.matches(to) && !preset.matches(from))
+}
+
+/// Writes the presets entered by the shift mode switch and logs each one
+pub fn apply_mode_presets(
+    presets: &[ModeAxisPreset],
+    from: ShiftModeMask,
+    to: ShiftModeMask,
+    virtual_devices: &mut [VirtualDevice],
+) {
+    for preset in entered_presets(presets, from, to) {
+        match validate_handle_virtual_axis(virtual_devices, &preset.device, &preset.axis) {
+            Ok(axis) => {
+                axis.set(preset.value);
+                info!(
+                    "Shift mode {from} -> {to}: virtual device {} axis {} set to {}",
+                    preset.device, preset.axis, preset.value
+                );
+            }
+            Err(e) => info!("Shift mode {from} -> {to}: preset not applied. Reason: {e}"),
+        }
+    }
+}
+
+pub fn mode_presets_widget(
+    ui: &mut Ui,
+    presets: &mut Vec<ModeAxisPreset>,
+    devices_info_map: &DevicesInfoMap,
+) {
+    let mut remove = None;
+    Grid::new("ModeAxisPresets")
+        .num_columns(5)
+        .striped(true)
+        .show(ui, |ui| {
+            for (index, preset) in presets.iter_mut().enumerate() {
+                ui.push_id(("ModeAxisPreset", index), |ui| {
+                    preset.mode_mask.widget(ui);
+                });
+
+                let device_name = devices_info_map
+                    .virtual_devices
+                    .get(&preset.device)
+                    .map_or_else(|| preset.device.to_string(), |info| info.name.clone());
+                ComboBox::from_id_source(("ModeAxisPresetDevice", index))
+                    .selected_text(device_name)
+                    .show_ui(ui, |ui| {
+                        for (id, info) in devices_info_map.virtual_devices.iter() {
+                            ui.selectable_value(&mut preset.device, *id, &info.name);
+                        }
+                    });
+
+                let num_axes = devices_info_map
+                    .virtual_devices
+                    .get(&preset.device)
+                    .map_or(8, |info| info.num_axes.max(1) as u32);
+                ui.add(
+                    DragValue::new(&mut preset.axis)
+                        .clamp_range(1..=num_axes)
+                        .prefix("axis "),
+                );
+                ui.add(Slider::new(&mut preset.value, 0..=32767));
+                if ui.button("X").clicked() {
+                    remove = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+
+    if let Some(index) = remove {
+        presets.remove(index);
+    }
+    if ui.button("Add preset").clicked() {
+        presets.push(ModeAxisPreset {
+            mode_mask: ShiftModeMask(0b00000001),
+            device: devices_info_map
+                .virtual_devices
+                .keys()
+                .next()
+                .copied()
+                .unwrap_or(1),
+            axis: 1,
+            value: AXIS_CENTER,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rebind::{
+        mode_presets::{entered_presets, ModeAxisPreset},
+        shift_mode_mask::ShiftModeMask,
+    };
+
+    #[test]
+    fn applies_only_when_entering_the_mode() {
+        let presets = [ModeAxisPreset {
+            mode_mask: ShiftModeMask(0b00000010),
+            device: 1,
+            axis: 3,
+            value: 16384,
+        }];
+        let entered = |from: u8, to: u8| {
+            entered_presets(&presets, ShiftModeMask(from), ShiftModeMask(to)).count()
+        };
+
+        assert_eq!(entered(0b00000000, 0b00000010), 1);
+        assert_eq!(entered(0b00000001, 0b00000011), 1);
+        assert_eq!(entered(0b00000010, 0b00000011), 0);
+        assert_eq!(entered(0b00000010, 0b00000000), 0);
+    }
+}
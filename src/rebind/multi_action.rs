@@ -0,0 +1,525 @@
+use egui::{Button, ComboBox, Slider, Ui};
+use egui_extras::Column;
+use serde::{Deserialize, Serialize};
+use strum::{AsRefStr, EnumIter, EnumString, EnumVariantNames};
+use vjoy::ButtonState;
+
+use super::{
+    hat_to_hat::convert_hat_type_to_vjoy, rebind_table, rebind_viewer::DevicesInfoMap,
+    validate_handle_virtual_axis, validate_handle_virtual_button, validate_handle_virtual_hat,
+    EnumVariantDropdown, IDDropdown, TickContext, VariantDescription, VirtualControl,
+    TABLE_ROW_HEIGHT,
+};
+use crate::{error::Error, input::VirtualDevice};
+
+const HAT_DIRECTIONS: [(i32, &str); 9] = [
+    (-1, "Centered"),
+    (0, "North"),
+    (45, "North-East"),
+    (90, "East"),
+    (135, "South-East"),
+    (180, "South"),
+    (225, "South-West"),
+    (270, "West"),
+    (315, "North-West"),
+];
+
+/// One step of a [`super::reroute_rebind::RerouteRebind::MultiAction`] sequence
+///
+/// Steps run in order within the same tick until a `Delay` is reached.
+/// `ButtonPulse` does not hold up the sequence: the button is released in the background once its duration has passed.
+#[derive(
+    Debug,
+    PartialEq,
+    Clone,
+    Serialize,
+    Deserialize,
+    AsRefStr,
+    EnumIter,
+    EnumString,
+    EnumVariantNames,
+)]
+#[serde(tag = "action")]
+pub enum MacroAction {
+    /// Press a virtual button for `duration` seconds
+    ButtonPulse {
+        device: u32,
+        button: u32,
+        duration: f64,
+    },
+    /// Set a virtual axis to `percent` of its range
+    SetAxis {
+        device: u32,
+        axis: u32,
+        percent: f64,
+    },
+    /// Set a virtual hat to `direction` in degrees, -1 is centered
+    SetHat {
+        device: u32,
+        hat: u32,
+        direction: i32,
+    },
+    /// Wait `duration` seconds before running the next step
+    Delay { duration: f64 },
+}
+
+impl Default for MacroAction {
+    fn default() -> Self {
+        Self::ButtonPulse {
+            device: Default::default(),
+            button: Default::default(),
+            duration: 0.2,
+        }
+    }
+}
+
+/// Runtime state of a running macro
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct MultiActionState {
+    last_input: bool,
+    next_step: Option<usize>,
+    wait_until: f64,
+    pulses: Vec<(u32, u32, f64)>,
+    /// A sequence ran or a pulse was held during the last tick
+    running: bool,
+}
+
+impl MultiActionState {
+    pub fn debug_state(&self) -> Vec<(String, String)> {
+        vec![
+            ("last_input".to_string(), self.last_input.to_string()),
+            (
+                "next_step".to_string(),
+                match self.next_step {
+                    Some(step) => (step + 1).to_string(),
+                    None => "idle".to_string(),
+                },
+            ),
+            (
+                "wait_until".to_string(),
+                format!("{:.3} s", self.wait_until),
+            ),
+            ("pulses".to_string(), self.pulses.len().to_string()),
+        ]
+    }
+
+    /// Whether the macro wrote its targets during the last tick. An idle macro leaves them to the
+    /// mirror and other rebinds.
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+}
+
+impl VariantDescription for MacroAction {
+    fn description(&self) -> &'static str {
+        match self {
+            MacroAction::ButtonPulse { .. } => {
+                "Press a virtual button for a duration, the sequence continues immediately"
+            }
+            MacroAction::SetAxis { .. } => "Set a virtual axis to a percentage of its range",
+            MacroAction::SetHat { .. } => "Set a virtual hat to a direction",
+            MacroAction::Delay { .. } => "Wait before running the next step",
+        }
+    }
+}
+
+impl MacroAction {
+    pub fn widget(&mut self, ui: &mut Ui, devices_info_map: &mut DevicesInfoMap) {
+        rebind_table(ui)
+            .column(Column::remainder())
+            .body(|mut body| match self {
+                MacroAction::ButtonPulse {
+                    device,
+                    button,
+                    duration,
+                } => {
+                    body.row(TABLE_ROW_HEIGHT, |mut row| {
+                        row.col(|ui| {
+                            ui.label("Device:");
+                        });
+                        row.col(|ui| {
+                            devices_info_map.virtual_devices_widget(ui, device);
+                        });
+                    });
+                    body.row(TABLE_ROW_HEIGHT, |mut row| {
+                        row.col(|ui| {
+                            ui.label("Button:");
+                        });
+                        row.col(|ui| {
+                            let max = devices_info_map.get_virtual_limits(device).0;
+                            button.id_dropdown_widget(max, ui);
+                        });
+                    });
+                    body.row(TABLE_ROW_HEIGHT, |mut row| {
+                        row.col(|ui| {
+                            ui.label("Duration:");
+                        });
+                        row.col(|ui| {
+                            ui.add(Slider::new(duration, 0.0..=5.0).suffix(" s"));
+                        });
+                    });
+                }
+
+                MacroAction::SetAxis {
+                    device,
+                    axis,
+                    percent,
+                } => {
+                    body.row(TABLE_ROW_HEIGHT, |mut row| {
+                        row.col(|ui| {
+                            ui.label("Device:");
+                        });
+                        row.col(|ui| {
+                            devices_info_map.virtual_devices_widget(ui, device);
+                        });
+                    });
+                    body.row(TABLE_ROW_HEIGHT, |mut row| {
+                        row.col(|ui| {
+                            ui.label("Axis:");
+                        });
+                        row.col(|ui| {
+                            let max = devices_info_map.get_virtual_limits(device).1;
+                            axis.id_dropdown_widget(max, ui);
+                        });
+                    });
+                    body.row(TABLE_ROW_HEIGHT, |mut row| {
+                        row.col(|ui| {
+                            ui.label("Value:");
+                        });
+                        row.col(|ui| {
+                            ui.add(Slider::new(percent, 0.0..=100.0).suffix(" %"));
+                        });
+                    });
+                }
+
+                MacroAction::SetHat {
+                    device,
+                    hat,
+                    direction,
+                } => {
+                    body.row(TABLE_ROW_HEIGHT, |mut row| {
+                        row.col(|ui| {
+                            ui.label("Device:");
+                        });
+                        row.col(|ui| {
+                            devices_info_map.virtual_devices_widget(ui, device);
+                        });
+                    });
+                    body.row(TABLE_ROW_HEIGHT, |mut row| {
+                        row.col(|ui| {
+                            ui.label("Hat:");
+                        });
+                        row.col(|ui| {
+                            let max = devices_info_map.get_virtual_limits(device).2;
+                            hat.id_dropdown_widget(max, ui);
+                        });
+                    });
+                    body.row(TABLE_ROW_HEIGHT, |mut row| {
+                        row.col(|ui| {
+                            ui.label("Direction:");
+                        });
+                        row.col(|ui| {
+                            let selected = HAT_DIRECTIONS
+                                .iter()
+                                .find(|(value, _)| value == direction)
+                                .map(|(_, name)| *name)
+                                .unwrap_or_default();
+                            ComboBox::from_id_source("hat_direction_dropdown")
+                                .selected_text(selected)
+                                .show_ui(ui, |ui| {
+                                    for (value, name) in HAT_DIRECTIONS {
+                                        ui.selectable_value(direction, value, name);
+                                    }
+                                });
+                        });
+                    });
+                }
+
+                MacroAction::Delay { duration } => {
+                    body.row(TABLE_ROW_HEIGHT, |mut row| {
+                        row.col(|ui| {
+                            ui.label("Duration:");
+                        });
+                        row.col(|ui| {
+                            ui.add(Slider::new(duration, 0.0..=5.0).suffix(" s"));
+                        });
+                    });
+                }
+            });
+    }
+
+    pub fn virtual_write(&self) -> Option<VirtualControl> {
+        match self {
+            MacroAction::ButtonPulse { device, button, .. } => Some(VirtualControl::Button {
+                device: *device,
+                button: *button,
+            }),
+            MacroAction::SetAxis { device, axis, .. } => Some(VirtualControl::Axis {
+                device: *device,
+                axis: *axis,
+            }),
+            MacroAction::SetHat { device, hat, .. } => Some(VirtualControl::Hat {
+                device: *device,
+                hat: *hat,
+            }),
+            MacroAction::Delay { .. } => None,
+        }
+    }
+
+    fn validate(&self, virtual_devices: &mut [VirtualDevice]) -> Result<(), Error> {
+        match self {
+            MacroAction::ButtonPulse { device, button, .. } => {
+                validate_handle_virtual_button(virtual_devices, device, button)?;
+            }
+            MacroAction::SetAxis { device, axis, .. } => {
+                validate_handle_virtual_axis(virtual_devices, device, axis)?;
+            }
+            MacroAction::SetHat { device, hat, .. } => {
+                validate_handle_virtual_hat(virtual_devices, device, hat)?;
+            }
+            MacroAction::Delay { .. } => (),
+        }
+
+        Ok(())
+    }
+}
+
+/// Editor for the step list: add, remove and reorder steps
+pub fn multi_action_steps_widget(
+    ui: &mut Ui,
+    actions: &mut Vec<MacroAction>,
+    devices_info_map: &mut DevicesInfoMap,
+) {
+    let mut remove = None;
+    let mut mov = None;
+    let count = actions.len();
+    for (index, action) in actions.iter_mut().enumerate() {
+        ui.push_id(index, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("Step {}:", index + 1));
+                action.variant_dropdown_widget(ui);
+                if ui.add_enabled(index > 0, Button::new("⏶")).clicked() {
+                    mov = Some((index, -1));
+                }
+                if ui
+                    .add_enabled(index + 1 < count, Button::new("⏷"))
+                    .clicked()
+                {
+                    mov = Some((index, 1));
+                }
+                if ui.button("🗑").clicked() {
+                    remove = Some(index);
+                }
+            });
+            action.widget(ui, devices_info_map);
+        });
+        ui.separator();
+    }
+
+    if let Some(index) = remove {
+        actions.remove(index);
+    }
+    if let Some((index, mov)) = mov {
+        let target = (index as isize + mov) as usize;
+        actions.swap(index, target);
+    }
+    if ui.button("Add step").clicked() {
+        actions.push(MacroAction::default());
+    }
+}
+
+/// Advances the macro by one tick. Starts on the rising edge of `input` if no sequence is running.
+///
+/// All targets are validated before anything is written, so a macro either runs completely or not at all.
+pub fn process_multi_action(
+    input: bool,
+    actions: &[MacroAction],
+    state: &mut MultiActionState,
+    virtual_devices: &mut [VirtualDevice],
+    ctx: TickContext,
+) -> Result<(), Error> {
+    for action in actions.iter() {
+        action.validate(virtual_devices)?;
+    }
+
+    let pressed_this_tick = input && !state.last_input;
+    state.last_input = input;
+    if pressed_this_tick && state.next_step.is_none() && !actions.is_empty() {
+        state.next_step = Some(0);
+        state.wait_until = ctx.time;
+    }
+    state.running = state.next_step.is_some() || !state.pulses.is_empty();
+
+    while let Some(step) = state.next_step {
+        if ctx.time < state.wait_until {
+            break;
+        }
+
+        //Steps may have been removed in the editor while the sequence was running
+        let Some(action) = actions.get(step) else {
+            state.next_step = None;
+            break;
+        };
+        match action {
+            MacroAction::ButtonPulse {
+                device,
+                button,
+                duration,
+            } => {
+                state.pulses.retain(|p| (p.0, p.1) != (*device, *button));
+                state.pulses.push((*device, *button, ctx.time + duration));
+            }
+            MacroAction::SetAxis {
+                device,
+                axis,
+                percent,
+            } => {
+                let output = validate_handle_virtual_axis(virtual_devices, device, axis)?;
+                output.set((percent / 100.0 * 32767.0).round() as i32);
+            }
+            MacroAction::SetHat {
+                device,
+                hat,
+                direction,
+            } => {
+                let output = validate_handle_virtual_hat(virtual_devices, device, hat)?;
+                output.set(convert_hat_type_to_vjoy(output.get(), *direction));
+            }
+            MacroAction::Delay { duration } => {
+                state.wait_until = ctx.time + duration;
+            }
+        }
+
+        state.next_step = Some(step + 1).filter(|next| *next < actions.len());
+    }
+
+    for (device, button, release) in state.pulses.iter() {
+        let output = validate_handle_virtual_button(virtual_devices, device, button)?;
+        match ctx.time < *release {
+            true => output.set(ButtonState::Pressed),
+            false => output.set(ButtonState::Released),
+        }
+    }
+    state.pulses.retain(|(_, _, release)| ctx.time < *release);
+
+    Ok(())
+}
+
+/// Aborts a running macro and releases all buttons it still holds.
+pub fn cancel_multi_action(state: &mut MultiActionState, virtual_devices: &mut [VirtualDevice]) {
+    for (device, button, _) in state.pulses.drain(..) {
+        if let Ok(output) = validate_handle_virtual_button(virtual_devices, &device, &button) {
+            output.set(ButtonState::Released);
+        }
+    }
+    state.next_step = None;
+    state.running = false;
+    //a button still held when the rebind becomes active again must not start the macro
+    state.last_input = true;
+}
+
+#[cfg(test)]
+mod tests {
+    use vjoy::ButtonState;
+
+    use crate::{
+        input::{virtual_state::VirtualState, VirtualDevice},
+        rebind::{
+            multi_action::{
+                cancel_multi_action, process_multi_action, MacroAction, MultiActionState,
+            },
+            TickContext,
+        },
+    };
+
+    fn device() -> Vec<VirtualDevice> {
+        vec![VirtualDevice::new(VirtualState::new(
+            1,
+            1,
+            1,
+            0,
+            Default::default(),
+        ))]
+    }
+
+    fn at(time: f64) -> TickContext {
+        TickContext {
+            time,
+            ..Default::default()
+        }
+    }
+
+    fn button(devices: &[VirtualDevice]) -> ButtonState {
+        devices[0].handle.buttons().next().unwrap().get()
+    }
+
+    fn axis(devices: &[VirtualDevice]) -> i32 {
+        devices[0].handle.axes().next().unwrap().get()
+    }
+
+    const PULSE: MacroAction = MacroAction::ButtonPulse {
+        device: 1,
+        button: 1,
+        duration: 0.5,
+    };
+    const FULL_AXIS: MacroAction = MacroAction::SetAxis {
+        device: 1,
+        axis: 1,
+        percent: 100.0,
+    };
+
+    #[test]
+    fn pulse_releases_after_its_duration() {
+        let mut devices = device();
+        let mut state = MultiActionState::default();
+        let actions = [PULSE];
+
+        process_multi_action(true, &actions, &mut state, &mut devices, at(0.0)).unwrap();
+        assert_eq!(button(&devices), ButtonState::Pressed);
+        assert!(state.is_running());
+        process_multi_action(true, &actions, &mut state, &mut devices, at(0.4)).unwrap();
+        assert_eq!(button(&devices), ButtonState::Pressed);
+
+        process_multi_action(true, &actions, &mut state, &mut devices, at(0.5)).unwrap();
+        assert_eq!(button(&devices), ButtonState::Released);
+        process_multi_action(true, &actions, &mut state, &mut devices, at(0.6)).unwrap();
+        assert!(!state.is_running());
+    }
+
+    #[test]
+    fn losing_the_shift_mode_cancels_without_restarting() {
+        let mut devices = device();
+        let mut state = MultiActionState::default();
+        let actions = [PULSE, MacroAction::Delay { duration: 1.0 }, FULL_AXIS];
+        let neutral_axis = axis(&devices);
+
+        process_multi_action(true, &actions, &mut state, &mut devices, at(0.0)).unwrap();
+        assert_eq!(button(&devices), ButtonState::Pressed);
+
+        cancel_multi_action(&mut state, &mut devices);
+        assert_eq!(button(&devices), ButtonState::Released);
+        assert!(!state.is_running());
+
+        // The button is still held when the rebind becomes active again
+        process_multi_action(true, &actions, &mut state, &mut devices, at(2.0)).unwrap();
+        assert_eq!(button(&devices), ButtonState::Released);
+        assert_eq!(axis(&devices), neutral_axis);
+        assert!(!state.is_running());
+    }
+
+    #[test]
+    fn removing_steps_mid_run_ends_the_sequence() {
+        let mut devices = device();
+        let mut state = MultiActionState::default();
+        let mut actions = vec![MacroAction::Delay { duration: 1.0 }, FULL_AXIS];
+        let neutral_axis = axis(&devices);
+
+        process_multi_action(true, &actions, &mut state, &mut devices, at(0.0)).unwrap();
+        actions.pop();
+        process_multi_action(true, &actions, &mut state, &mut devices, at(1.0)).unwrap();
+        assert_eq!(axis(&devices), neutral_axis);
+
+        process_multi_action(false, &actions, &mut state, &mut devices, at(1.1)).unwrap();
+        assert!(!state.is_running());
+    }
+}
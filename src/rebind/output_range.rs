@@ -0,0 +1,246 @@
+use egui::{RichText, Slider, Ui};
+use serde::{Deserialize, Serialize};
+
+use super::axis_to_axis::convert_axis_to_vjoy_range;
+
+const AXIS_MIN: i32 = -32768;
+const AXIS_MAX: i32 = 32767;
+
+/// Inputs sampled per source axis. Both ends of the input range are always sampled, so the range
+/// of a monotonic curve is exact.
+const SAMPLES_1D: usize = 1025;
+const SAMPLES_2D: usize = 129;
+
+/// Thresholds of the range warnings in the rebind editor, persisted in the app settings
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(default)]
+pub struct OutputRangeSettings {
+    /// Warn once more than this share of the output range is unreachable, in percent
+    pub unreachable_percent: f32,
+}
+
+impl Default for OutputRangeSettings {
+    fn default() -> Self {
+        Self {
+            unreachable_percent: 10.0,
+        }
+    }
+}
+
+impl OutputRangeSettings {
+    pub fn settings_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Warn if unreachable:");
+            ui.add(Slider::new(&mut self.unreachable_percent, 0.0..=100.0).suffix(" %"));
+        });
+        if ui.button("Default").clicked() {
+            *self = Self::default();
+        }
+    }
+}
+
+/// Output an axis producing rebind can reach with its current parameters, computed from the
+/// parameters alone for full range sources. Values are in the physical axis range
+/// -32768..=32767.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct OutputRange {
+    pub min: i32,
+    pub max: i32,
+    /// Share of the input range whose output is cut off at the axis limits
+    pub clipped: f32,
+}
+
+impl OutputRange {
+    /// Range of `output` for evenly spaced inputs. `output` returns the value before it is
+    /// limited to the axis range and the final output.
+    pub fn sampled(output: impl Fn(i32) -> (f64, i32)) -> Self {
+        Self::from_samples(sample_inputs(SAMPLES_1D).map(output))
+    }
+
+    /// Like [`OutputRange::sampled`] for all combinations of two inputs
+    pub fn sampled_2d(output: impl Fn(i32, i32) -> (f64, i32)) -> Self {
+        Self::from_samples(
+            sample_inputs(SAMPLES_2D)
+                .flat_map(|a| sample_inputs(SAMPLES_2D).map(move |b| (a, b)))
+                .map(|(a, b)| output(a, b)),
+        )
+    }
+
+    fn from_samples(samples: impl Iterator<Item = (f64, i32)>) -> Self {
+        let mut min = AXIS_MAX;
+        let mut max = AXIS_MIN;
+        let mut clipped = 0;
+        let mut count = 0;
+        for (unlimited, value) in samples {
+            min = min.min(value);
+            max = max.max(value);
+            // Values that round into the range are not clipped
+            if unlimited < AXIS_MIN as f64 - 0.5 || unlimited > AXIS_MAX as f64 + 0.5 {
+                clipped += 1;
+            }
+            count += 1;
+        }
+        Self {
+            min,
+            max,
+            clipped: clipped as f32 / count.max(1) as f32,
+        }
+    }
+
+    /// Share of the axis range the output never reaches
+    pub fn unreachable(&self) -> f32 {
+        let reachable = (self.max - self.min).max(0) as f32 / (AXIS_MAX - AXIS_MIN) as f32;
+        1.0 - reachable
+    }
+
+    pub fn warnings(&self, settings: &OutputRangeSettings) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.clipped > 0.0 {
+            warnings.push(format!(
+                "{:.0}% of the input range is clipped at the axis limits",
+                (self.clipped * 100.0).max(1.0)
+            ));
+        }
+        let unreachable = self.unreachable() * 100.0;
+        if unreachable > settings.unreachable_percent {
+            warnings.push(format!(
+                "{unreachable:.0}% of the output range is unreachable"
+            ));
+        }
+        warnings
+    }
+
+    /// Effective range in percent of the vJoy axis with the warnings below
+    pub fn widget(&self, ui: &mut Ui, label: &str, settings: &OutputRangeSettings) {
+        let percent = |value| convert_axis_to_vjoy_range(value) as f32 / 327.67;
+        ui.horizontal(|ui| {
+            ui.label(label)
+                .on_hover_text("Output for full range sources, computed from the parameters");
+            ui.label(format!(
+                "{:.0}% to {:.0}%",
+                percent(self.min),
+                percent(self.max)
+            ));
+        });
+        for warning in self.warnings(settings) {
+            ui.label(RichText::new(format!("⚠ {warning}")).color(ui.visuals().warn_fg_color));
+        }
+    }
+}
+
+/// `count` evenly spaced values from the axis minimum to the axis maximum
+fn sample_inputs(count: usize) -> impl Iterator<Item = i32> + Clone {
+    let span = (AXIS_MAX - AXIS_MIN) as f64;
+    (0..count).map(move |k| AXIS_MIN + (span * k as f64 / (count - 1) as f64).round() as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rebind::{
+        axis_to_axis::AxisParams,
+        merge_axes::MergeAxesModifier,
+        mix_axes::AxisMix,
+        output_range::{sample_inputs, OutputRange, OutputRangeSettings},
+    };
+
+    fn assert_near(value: f32, expected: f32) {
+        assert!((value - expected).abs() < 0.01, "{value} != {expected}");
+    }
+
+    #[test]
+    fn samples_include_both_range_ends() {
+        let samples: Vec<i32> = sample_inputs(5).collect();
+        assert_eq!(samples, vec![-32768, -16384, 0, 16383, 32767]);
+    }
+
+    #[test]
+    fn default_curve_covers_the_full_range() {
+        let range = AxisParams::default().output_range();
+        assert_eq!((range.min, range.max), (-32768, 32767));
+        assert_eq!(range.clipped, 0.0);
+        assert!(range.warnings(&OutputRangeSettings::default()).is_empty());
+    }
+
+    #[test]
+    fn positive_offset_clips_the_top_and_lifts_the_bottom() {
+        // The offset adds 16383.5, inputs above 16384 leave the range: a quarter of the inputs
+        let range = AxisParams::new(0.0, 0.0, 1.0, false, 1.0, 0.5, 1).output_range();
+        assert_eq!((range.min, range.max), (-16385, 32767));
+        assert_near(range.clipped, 0.25);
+        assert_near(range.unreachable(), 0.25);
+        assert_eq!(range.warnings(&OutputRangeSettings::default()).len(), 2);
+    }
+
+    #[test]
+    fn negative_offset_makes_full_deflection_unreachable() {
+        // Inputs past 80% reach full deflection before the offset takes a tenth off the top and
+        // pushes the low end past the axis limit
+        let range = AxisParams::new(0.0, 0.0, 0.8, false, 1.0, -0.1, 1).output_range();
+        assert_eq!(range.max, 29490);
+        assert!(range.clipped > 0.0);
+        assert_near(range.unreachable(), 0.05);
+
+        let settings = OutputRangeSettings {
+            unreachable_percent: 4.0,
+        };
+        assert!(range
+            .warnings(&settings)
+            .iter()
+            .any(|w| w.contains("unreachable")));
+    }
+
+    #[test]
+    fn clamping_alone_keeps_the_full_range() {
+        let range = AxisParams::new(0.1, 0.2, 0.8, true, 2.0, 0.0, 1).output_range();
+        assert_eq!((range.min, range.max), (-32768, 32767));
+        assert_eq!(range.clipped, 0.0);
+    }
+
+    #[test]
+    fn adding_two_full_range_axes_clips_a_quarter() {
+        // a + b leaves the range in two corner triangles of an eighth of the input square each
+        let range = MergeAxesModifier::Add.output_range();
+        assert_eq!((range.min, range.max), (-32768, 32767));
+        assert_near(range.clipped, 0.25);
+        assert_eq!(range.unreachable(), 0.0);
+    }
+
+    #[test]
+    fn mix_clips_only_above_unit_gain() {
+        for range in AxisMix::default().output_ranges() {
+            assert_eq!((range.min, range.max), (-32768, 32767));
+            assert_eq!(range.clipped, 0.0);
+        }
+
+        let full = AxisMix {
+            gains: [1.0; 4],
+            invert: [false; 2],
+        };
+        for range in full.output_ranges() {
+            assert_near(range.clipped, 0.25);
+        }
+
+        let weak = AxisMix {
+            gains: [0.25; 4],
+            invert: [false; 2],
+        };
+        let [range, _] = weak.output_ranges();
+        assert_eq!((range.min, range.max), (-16384, 16384));
+        assert_near(range.unreachable(), 0.5);
+    }
+
+    #[test]
+    fn settings_default_when_missing() {
+        let settings: OutputRangeSettings = toml::from_str("").unwrap();
+        assert_eq!(settings, OutputRangeSettings::default());
+        assert_eq!(
+            OutputRange {
+                min: 0,
+                max: 0,
+                clipped: 0.0
+            }
+            .unreachable(),
+            1.0
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use egui::{Checkbox, Slider, Ui};
+use egui_extras::Column;
+use serde::{Deserialize, Serialize};
+
+use super::{rebind_table, rebind_viewer::DevicesInfoMap, TABLE_ROW_HEIGHT};
+
+/// Transition smoothing for one virtual axis.
+///
+/// When the rebind writing the axis changes (e.g. after a shift mode change), the output ramps from the
+/// previous output value to the new target over `duration` seconds instead of jumping.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct AxisSmoothing {
+    pub device: u32,
+    pub axis: u32,
+    pub duration: f64,
+}
+
+impl AxisSmoothing {
+    pub fn new(device: u32, axis: u32, duration: f64) -> Self {
+        Self {
+            device,
+            axis,
+            duration,
+        }
+    }
+}
+
+/// Running ramp of one smoothed axis.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct AxisTransition {
+    pub from: i32,
+    pub start: f64,
+}
+
+/// Blends the freshly written `target` with the value the axis had before the writer changed.
+///
+/// Returns `None` once the transition is complete.
+pub fn apply_axis_transition(
+    transition: &AxisTransition,
+    target: i32,
+    duration: f64,
+    time: f64,
+) -> Option<i32> {
+    if duration <= 0.0 {
+        return None;
+    }
+
+    let progress = (time - transition.start) / duration;
+    if progress >= 1.0 {
+        return None;
+    }
+
+    let progress = progress.max(0.0);
+    let blended = transition.from as f64 + (target - transition.from) as f64 * progress;
+    Some(blended.round() as i32)
+}
+
+pub fn axis_smoothing_widget(
+    ui: &mut Ui,
+    smoothing: &mut Vec<AxisSmoothing>,
+    devices_info_map: &DevicesInfoMap,
+) {
+    rebind_table(ui)
+        .column(Column::exact(60.0))
+        .column(Column::remainder())
+        .body(|mut body| {
+            for (id, info) in devices_info_map.virtual_devices.iter() {
+                for axis in 1..=info.num_axes as u32 {
+                    let position = smoothing
+                        .iter()
+                        .position(|s| s.device == *id && s.axis == axis);
+                    let mut enabled = position.is_some();
+
+                    body.row(TABLE_ROW_HEIGHT, |mut row| {
+                        row.col(|ui| {
+                            ui.label(format!("{} axis {}:", info.name, axis));
+                        });
+                        row.col(|ui| {
+                            ui.add(Checkbox::new(&mut enabled, "Smooth"));
+                        });
+                        row.col(|ui| {
+                            if let Some(index) = position {
+                                ui.add(
+                                    Slider::new(&mut smoothing[index].duration, 0.0..=5.0)
+                                        .suffix(" s"),
+                                );
+                            }
+                        });
+                    });
+
+                    match (enabled, position) {
+                        (true, None) => smoothing.push(AxisSmoothing::new(*id, axis, 0.5)),
+                        (false, Some(index)) => {
+                            smoothing.remove(index);
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rebind::output_smoothing::{apply_axis_transition, AxisTransition};
+
+    #[test]
+    fn transition_ramps_linearly() {
+        let transition = AxisTransition {
+            from: 0,
+            start: 1.0,
+        };
+
+        assert_eq!(apply_axis_transition(&transition, 32000, 2.0, 1.0), Some(0));
+        assert_eq!(
+            apply_axis_transition(&transition, 32000, 2.0, 2.0),
+            Some(16000)
+        );
+        assert_eq!(apply_axis_transition(&transition, 32000, 2.0, 3.0), None);
+    }
+
+    #[test]
+    fn zero_duration_snaps() {
+        let transition = AxisTransition {
+            from: 0,
+            start: 0.0,
+        };
+
+        assert_eq!(apply_axis_transition(&transition, 32000, 0.0, 0.0), None);
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+
+use egui::{CollapsingHeader, ComboBox, Ui};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    rebind_id::RebindId, rebind_viewer::DevicesInfoMap, resolved_slots::ResolvedSlots, Rebind,
+    RebindType,
+};
+
+/// Named set of replacement rebinds, applied while a
+/// [`LogicalRebind::MomentaryOverlay`](super::logical_rebind::LogicalRebind::MomentaryOverlay)
+/// button is held.
+///
+/// Unlike shift modes, an overlay doesn't change which rebinds run but how: each override is a
+/// separate instance with its own parameters and internal state that is processed in place of the
+/// rebind with its id.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Default)]
+pub struct Overlay {
+    pub name: String,
+
+    #[serde(default)]
+    pub overrides: Vec<OverlayOverride>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlayOverride {
+    /// Id of the replaced rebind, `None` if a config from before rebind ids named no such rebind
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rebind_id: Option<RebindId>,
+
+    /// Name of the replaced rebind in configs from before rebind ids, see
+    /// [`resolve_override_names`]
+    #[serde(rename = "rebind", default, skip_serializing_if = "String::is_empty")]
+    pub rebind_name: String,
+
+    #[serde(flatten)]
+    pub rebind_type: RebindType,
+
+    #[serde(skip)]
+    pub resolved: ResolvedSlots,
+}
+
+/// Leaves out the cached slots, like [`Rebind`] equality
+impl PartialEq for OverlayOverride {
+    fn eq(&self, other: &Self) -> bool {
+        let OverlayOverride {
+            rebind_id,
+            rebind_name,
+            rebind_type,
+            resolved: _,
+        } = self;
+        *rebind_id == other.rebind_id
+            && *rebind_name == other.rebind_name
+            && *rebind_type == other.rebind_type
+    }
+}
+
+/// Points the overrides of configs from before rebind ids at the first rebind with their name.
+pub fn resolve_override_names(overlays: &mut [Overlay], rebinds: &[Rebind]) {
+    for o in overlays
+        .iter_mut()
+        .flat_map(|overlay| overlay.overrides.iter_mut())
+        .filter(|o| o.rebind_id.is_none())
+    {
+        if let Some(rebind) = rebinds.iter().find(|r| r.name == o.rebind_name) {
+            o.rebind_id = Some(rebind.id);
+            o.rebind_name.clear();
+        }
+    }
+}
+
+/// Overrides of the active overlay by the id of the rebind they replace.
+///
+/// Looked up again only when the active overlay or the overlays change, so processing a rebind
+/// finds its override without searching. Logical rebinds are never overridden, they decide which
+/// overlay is active in the first place.
+#[derive(Debug, Default)]
+pub struct ActiveOverrides {
+    overlay: Option<usize>,
+    overrides: HashMap<RebindId, usize>,
+}
+
+impl ActiveOverrides {
+    pub fn resolve(overlays: &[Overlay], active: Option<&str>) -> Self {
+        let Some(overlay) =
+            active.and_then(|active| overlays.iter().position(|o| o.name == active))
+        else {
+            return Self::default();
+        };
+
+        let mut overrides = HashMap::new();
+        for (index, o) in overlays[overlay].overrides.iter().enumerate() {
+            if matches!(o.rebind_type, RebindType::Logical { .. }) {
+                continue;
+            }
+            if let Some(id) = o.rebind_id {
+                overrides.entry(id).or_insert(index);
+            }
+        }
+        Self {
+            overlay: Some(overlay),
+            overrides,
+        }
+    }
+
+    pub fn contains(&self, rebind: RebindId) -> bool {
+        self.overrides.contains_key(&rebind)
+    }
+
+    /// Override of `rebind`, `overlays` must be the ones this was resolved from
+    pub fn get<'a>(
+        &self,
+        overlays: &'a mut [Overlay],
+        rebind: RebindId,
+    ) -> Option<&'a mut OverlayOverride> {
+        let index = *self.overrides.get(&rebind)?;
+        overlays.get_mut(self.overlay?)?.overrides.get_mut(index)
+    }
+}
+
+/// Editor for all overlays. `rebinds` are id, name and type of the config's reroute and virtual
+/// rebinds, a new override starts as a copy of the rebind it replaces.
+pub fn overlays_widget(
+    ui: &mut Ui,
+    overlays: &mut Vec<Overlay>,
+    rebinds: &[(RebindId, String, RebindType)],
+    active: Option<&str>,
+    devices_info_map: &mut DevicesInfoMap,
+) {
+    let mut remove_overlay = None;
+    for (overlay_index, overlay) in overlays.iter_mut().enumerate() {
+        ui.push_id(overlay_index, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Name:");
+                ui.text_edit_singleline(&mut overlay.name);
+                if active == Some(overlay.name.as_str()) {
+                    ui.label("active");
+                }
+                if ui.button("Remove").clicked() {
+                    remove_overlay = Some(overlay_index);
+                }
+            });
+
+            let mut remove_override = None;
+            for (override_index, o) in overlay.overrides.iter_mut().enumerate() {
+                let target = match rebinds.iter().find(|(id, ..)| Some(*id) == o.rebind_id) {
+                    Some((_, name, _)) => format!("Overrides '{name}'"),
+                    None if o.rebind_name.is_empty() => "Overrides a removed rebind".to_string(),
+                    None => format!("Overrides '{}', not found", o.rebind_name),
+                };
+                ui.push_id(override_index, |ui| {
+                    CollapsingHeader::new(target)
+                        .id_source("OverlayOverride")
+                        .show(ui, |ui| {
+                            if ui.button("Remove override").clicked() {
+                                remove_override = Some(override_index);
+                            }
+                            o.rebind_type.widget(ui, devices_info_map);
+                        });
+                });
+            }
+            if let Some(index) = remove_override {
+                overlay.overrides.remove(index);
+            }
+
+            let mut add = None;
+            ComboBox::from_id_source("OverlayAddOverride")
+                .selected_text("Override rebind…")
+                .show_ui(ui, |ui| {
+                    let overridden =
+                        |id: &RebindId| overlay.overrides.iter().any(|o| o.rebind_id == Some(*id));
+                    for (id, name, rebind_type) in rebinds.iter().filter(|(id, ..)| !overridden(id))
+                    {
+                        if ui.selectable_label(false, name).clicked() {
+                            add = Some((*id, rebind_type.clone()));
+                        }
+                    }
+                });
+            if let Some((id, rebind_type)) = add {
+                overlay.overrides.push(OverlayOverride {
+                    rebind_id: Some(id),
+                    rebind_name: String::new(),
+                    rebind_type,
+                    resolved: Default::default(),
+                });
+            }
+        });
+        ui.separator();
+    }
+
+    if let Some(index) = remove_overlay {
+        overlays.remove(index);
+    }
+
+    if ui.button("Add overlay").clicked() {
+        overlays.push(Overlay {
+            name: format!("Overlay {}", overlays.len() + 1),
+            overrides: Vec::new(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rebind::{
+        overlay::{resolve_override_names, ActiveOverrides, Overlay, OverlayOverride},
+        rebind_id::RebindId,
+        reroute_rebind::RerouteRebind,
+        Rebind, RebindType,
+    };
+
+    fn override_of(rebind_id: Option<RebindId>, rebind_name: &str) -> OverlayOverride {
+        OverlayOverride {
+            rebind_id,
+            rebind_name: rebind_name.to_string(),
+            rebind_type: RebindType::Reroute {
+                rebind: RerouteRebind::default(),
+            },
+            resolved: Default::default(),
+        }
+    }
+
+    fn overlay(name: &str, rebinds: &[RebindId]) -> Overlay {
+        Overlay {
+            name: name.to_string(),
+            overrides: rebinds
+                .iter()
+                .map(|id| override_of(Some(*id), ""))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn overrides_only_apply_from_the_active_overlay() {
+        let (pitch, roll) = (RebindId::new(), RebindId::new());
+        let mut overlays = vec![overlay("cruise", &[pitch]), overlay("dogfight", &[roll])];
+
+        let none = ActiveOverrides::resolve(&overlays, None);
+        assert!(none.get(&mut overlays, pitch).is_none());
+        let cruise = ActiveOverrides::resolve(&overlays, Some("cruise"));
+        assert!(cruise.get(&mut overlays, pitch).is_some());
+        assert!(cruise.get(&mut overlays, roll).is_none());
+        let dogfight = ActiveOverrides::resolve(&overlays, Some("dogfight"));
+        assert!(dogfight.get(&mut overlays, roll).is_some());
+        let missing = ActiveOverrides::resolve(&overlays, Some("missing"));
+        assert!(missing.get(&mut overlays, roll).is_none());
+    }
+
+    #[test]
+    fn names_from_before_ids_resolve_to_the_first_rebind() {
+        let rebind = |name: &str| {
+            Rebind::new(
+                name.to_string(),
+                Default::default(),
+                RebindType::Reroute {
+                    rebind: RerouteRebind::default(),
+                },
+            )
+        };
+        let rebinds = vec![rebind("Fire"), rebind("Fire"), rebind("Gear")];
+        let mut overlays = vec![Overlay {
+            name: "cruise".to_string(),
+            overrides: vec![override_of(None, "Fire"), override_of(None, "Missing")],
+        }];
+
+        resolve_override_names(&mut overlays, &rebinds);
+        let overrides = &overlays[0].overrides;
+        assert_eq!(overrides[0].rebind_id, Some(rebinds[0].id));
+        assert!(overrides[0].rebind_name.is_empty());
+        assert_eq!(overrides[1].rebind_id, None);
+        assert_eq!(overrides[1].rebind_name, "Missing");
+    }
+}
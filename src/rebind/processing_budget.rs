@@ -0,0 +1,207 @@
+use std::time::Instant;
+
+use log::warn;
+
+use super::QuotedName;
+use crate::input::INPUT_POLL_INTERVAL;
+
+/// Ticks in a row over budget before an overrun is reported
+pub const OVERRUN_TICKS: u32 = 20;
+
+/// Number of rebinds named in an overrun report
+const REPORTED_REBINDS: usize = 3;
+
+/// Half the poll interval, leaving the other half for polling SDL and writing to vJoy
+pub fn default_budget_s() -> f64 {
+    INPUT_POLL_INTERVAL * 0.5
+}
+
+/// Processing time stayed over budget for [`OVERRUN_TICKS`] ticks in a row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Overrun {
+    /// Mean processing time over the overrun ticks
+    pub mean_s: f64,
+    pub budget_s: f64,
+    /// Most expensive rebinds as index, name and mean time per tick. Only filled with per-rebind
+    /// timing enabled.
+    pub rebinds: Vec<(usize, String, f64)>,
+}
+
+/// Watches the time spent in [`RebindProcessor::process`] per tick.
+///
+/// Per-rebind timing is off by default, so the normal case costs one comparison per rebind.
+///
+/// [`RebindProcessor::process`]: super::rebind_processor::RebindProcessor::process
+#[derive(Debug)]
+pub struct ProcessingBudget {
+    pub budget_s: f64,
+    pub per_rebind_timing: bool,
+    /// Skips the rebinds flagged by the last overrun until turned off again
+    pub safe_mode: bool,
+    /// Most rebinds writing the same virtual control per tick, later ones are skipped
+    pub rewrite_limit: Option<u32>,
+    consecutive: u32,
+    total_s: f64,
+    rebind_times: Vec<f64>,
+    flagged: Vec<(usize, String)>,
+    overrun: Option<Overrun>,
+    acknowledged: bool,
+}
+
+impl Default for ProcessingBudget {
+    fn default() -> Self {
+        Self {
+            budget_s: default_budget_s(),
+            per_rebind_timing: false,
+            safe_mode: false,
+            rewrite_limit: None,
+            consecutive: 0,
+            total_s: 0.0,
+            rebind_times: Vec::new(),
+            flagged: Vec::new(),
+            overrun: None,
+            acknowledged: false,
+        }
+    }
+}
+
+impl ProcessingBudget {
+    /// Start of a timed rebind, `None` without per-rebind timing.
+    pub fn start_rebind(&self) -> Option<Instant> {
+        match self.per_rebind_timing {
+            true => Some(Instant::now()),
+            false => None,
+        }
+    }
+
+    pub fn end_rebind(&mut self, index: usize, start: Option<Instant>) {
+        let Some(start) = start else {
+            return;
+        };
+        if self.rebind_times.len() <= index {
+            self.rebind_times.resize(index + 1, 0.0);
+        }
+        self.rebind_times[index] += start.elapsed().as_secs_f64();
+    }
+
+    /// Whether safe mode skips the rebind at `index`. The name guards against index shifts from
+    /// edits since the overrun.
+    pub fn is_disabled(&self, index: usize, name: &str) -> bool {
+        self.safe_mode && self.flagged.iter().any(|(i, n)| *i == index && n == name)
+    }
+
+    /// Records the processing time of one tick. `name` maps rebind indices to names for the
+    /// report. Returns true if this tick raised a new overrun.
+    pub fn record_tick(&mut self, duration_s: f64, name: impl Fn(usize) -> String) -> bool {
+        if duration_s <= self.budget_s {
+            self.consecutive = 0;
+            self.total_s = 0.0;
+            self.rebind_times.clear();
+            return false;
+        }
+
+        self.consecutive += 1;
+        self.total_s += duration_s;
+        if self.consecutive != OVERRUN_TICKS {
+            return false;
+        }
+
+        let ticks = self.consecutive as f64;
+        let mut slowest: Vec<(usize, f64)> = self
+            .rebind_times
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, time)| *time > 0.0)
+            .collect();
+        slowest.sort_by(|a, b| b.1.total_cmp(&a.1));
+        slowest.truncate(REPORTED_REBINDS);
+
+        let overrun = Overrun {
+            mean_s: self.total_s / ticks,
+            budget_s: self.budget_s,
+            rebinds: slowest
+                .into_iter()
+                .map(|(index, time)| (index, name(index), time / ticks))
+                .collect(),
+        };
+        warn!(
+            "rebind processing took {:.3} ms per tick for {OVERRUN_TICKS} ticks, budget is {:.3} ms",
+            overrun.mean_s * 1000.0,
+            overrun.budget_s * 1000.0
+        );
+        for (index, name, time) in overrun.rebinds.iter() {
+            warn!(
+                "  rebind {index} {}: {:.3} ms",
+                QuotedName(name),
+                time * 1000.0
+            );
+        }
+
+        if !self.safe_mode {
+            self.flagged = overrun
+                .rebinds
+                .iter()
+                .map(|(index, name, _)| (*index, name.clone()))
+                .collect();
+        }
+        self.overrun = Some(overrun);
+        self.acknowledged = false;
+        true
+    }
+
+    /// Last overrun, until it is acknowledged
+    pub fn overrun(&self) -> Option<&Overrun> {
+        match self.acknowledged {
+            true => None,
+            false => self.overrun.as_ref(),
+        }
+    }
+
+    pub fn acknowledge(&mut self) {
+        self.acknowledged = true;
+    }
+
+    /// Rebinds safe mode disables, as index and name
+    pub fn flagged(&self) -> &[(usize, String)] {
+        &self.flagged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rebind::processing_budget::{ProcessingBudget, OVERRUN_TICKS};
+
+    #[test]
+    fn overrun_needs_consecutive_ticks_and_flags_slowest() {
+        let mut budget = ProcessingBudget {
+            budget_s: 0.001,
+            ..Default::default()
+        };
+        let name = |index: usize| format!("rebind {index}");
+
+        for _ in 0..OVERRUN_TICKS - 1 {
+            assert!(!budget.record_tick(0.002, name));
+        }
+        assert!(!budget.record_tick(0.0005, name));
+        assert!(budget.overrun().is_none());
+
+        budget.rebind_times = vec![0.0, 0.003, 0.001];
+        for tick in 1..=OVERRUN_TICKS {
+            assert_eq!(budget.record_tick(0.002, name), tick == OVERRUN_TICKS);
+        }
+        let overrun = budget.overrun().unwrap();
+        assert!((overrun.mean_s - 0.002).abs() < 1e-9);
+        let flagged: Vec<usize> = overrun.rebinds.iter().map(|(i, _, _)| *i).collect();
+        assert_eq!(flagged, vec![1, 2]);
+
+        assert!(!budget.is_disabled(1, "rebind 1"));
+        budget.safe_mode = true;
+        assert!(budget.is_disabled(1, "rebind 1"));
+        assert!(!budget.is_disabled(1, "renamed"));
+        assert!(!budget.is_disabled(0, "rebind 0"));
+
+        budget.acknowledge();
+        assert!(budget.overrun().is_none());
+    }
+}
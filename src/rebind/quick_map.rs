@@ -0,0 +1,319 @@
+use egui::{Button, ComboBox, Context, Grid, RichText, Ui};
+use log::info;
+
+use super::{
+    axis_to_axis::AxisToAxisModifier, merge_axes::AxisSource, rebind_viewer::DevicesInfoMap,
+    reroute_rebind::RerouteRebind, shift_mode_mask::ShiftModeMask, Rebind, RebindScope, RebindType,
+    VirtualControl,
+};
+use crate::{config::Config, input::input_state::AxisKind, input::Input, ui_data::UIData};
+
+/// Modifier every generated rebind starts with
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum ModifierPreset {
+    /// Preset of the detected kind of each source axis
+    #[default]
+    Detected,
+    /// Preset of one axis kind for all source axes
+    Kind(AxisKind),
+    /// Unmodified passthrough
+    Plain,
+}
+
+impl ModifierPreset {
+    const ALL: [ModifierPreset; 6] = [
+        ModifierPreset::Detected,
+        ModifierPreset::Kind(AxisKind::Stick),
+        ModifierPreset::Kind(AxisKind::Trigger { rest_at_max: false }),
+        ModifierPreset::Kind(AxisKind::Trigger { rest_at_max: true }),
+        ModifierPreset::Kind(AxisKind::Slider),
+        ModifierPreset::Plain,
+    ];
+
+    pub fn name(&self) -> String {
+        match self {
+            ModifierPreset::Detected => "Detected axis kind".to_string(),
+            ModifierPreset::Kind(AxisKind::Trigger { rest_at_max: true }) => {
+                "trigger, resting at max".to_string()
+            }
+            ModifierPreset::Kind(kind) => kind.to_string(),
+            ModifierPreset::Plain => "None".to_string(),
+        }
+    }
+
+    fn modifier(&self, detected: Option<AxisKind>) -> AxisToAxisModifier {
+        match (*self, detected) {
+            (ModifierPreset::Detected, Some(kind)) | (ModifierPreset::Kind(kind), _) => {
+                AxisToAxisModifier::for_axis_kind(kind)
+            }
+            (ModifierPreset::Detected, None) | (ModifierPreset::Plain, _) => {
+                AxisToAxisModifier::default()
+            }
+        }
+    }
+}
+
+/// Maps all axes of one physical device to one virtual device in a single step.
+///
+/// The config from before the last generate is kept as a single undo snapshot.
+#[derive(Debug, Default)]
+pub struct QuickMap {
+    pub open: bool,
+    pub src_device: String,
+    pub dst_device: u32,
+    /// Destination axis of each source axis, `None` skips it
+    pub assignments: Vec<Option<u32>>,
+    pub preset: ModifierPreset,
+    pub undo: Option<Config>,
+    prefilled_for: Option<(String, u32)>,
+}
+
+impl QuickMap {
+    /// Rebinds for all assigned source axes, `axis_kinds` are the detected kinds of the source axes
+    pub fn generate(&self, axis_kinds: &[AxisKind]) -> Vec<Rebind> {
+        self.assignments
+            .iter()
+            .enumerate()
+            .filter_map(|(index, dst)| Some((index, (*dst)?)))
+            .map(|(index, dst_axis)| {
+                let src_axis = index as u32 + 1;
+                Rebind::new(
+                    format!("Axis_{}_To_{}", src_axis, dst_axis),
+                    ShiftModeMask(0b00000000),
+                    RebindType::Reroute {
+                        rebind: RerouteRebind::AxisToAxis {
+                            src: AxisSource::physical(self.src_device.clone(), src_axis),
+                            dst_device: self.dst_device,
+                            dst_axis,
+                            modifier: self.preset.modifier(axis_kinds.get(index).copied()),
+                            freeze: None,
+                            gain: None,
+                        },
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Destination axes assigned to more than one source axis
+    pub fn duplicates(&self) -> Vec<u32> {
+        let mut assigned: Vec<u32> = self.assignments.iter().flatten().copied().collect();
+        assigned.sort_unstable();
+        let mut duplicates: Vec<u32> = assigned
+            .windows(2)
+            .filter(|pair| pair[0] == pair[1])
+            .map(|pair| pair[0])
+            .collect();
+        duplicates.dedup();
+        duplicates
+    }
+}
+
+/// Free destinations in order for the source axes in order, i.e. 1:1 while nothing is in use.
+/// Source axes left without a free destination are skipped.
+pub fn prefill_assignments(
+    num_src_axes: usize,
+    num_dst_axes: u32,
+    in_use: impl Fn(u32) -> bool,
+) -> Vec<Option<u32>> {
+    let mut free = (1..=num_dst_axes).filter(|axis| !in_use(*axis));
+    (0..num_src_axes).map(|_| free.next()).collect()
+}
+
+pub fn update_window(ctx: &Context, input: &mut Input, ui_data: &mut UIData) {
+    if !ui_data.quick_map.open {
+        return;
+    }
+
+    let devices = DevicesInfoMap::from_input(input, false);
+    let quick_map = &mut ui_data.quick_map;
+    let mut open = true;
+    egui::Window::new("Quick map axes")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            build_ui(ui, input, &devices, quick_map);
+        });
+    quick_map.open = open;
+}
+
+fn build_ui(ui: &mut Ui, input: &mut Input, devices: &DevicesInfoMap, quick_map: &mut QuickMap) {
+    Grid::new("quick_map_devices").show(ui, |ui| {
+        ui.label("From:");
+        devices.physical_devices_widget(ui, &mut quick_map.src_device);
+        ui.end_row();
+        ui.label("To:");
+        devices.virtual_devices_widget(ui, &mut quick_map.dst_device);
+        ui.end_row();
+        ui.label("Modifier:");
+        ComboBox::from_id_source("quick_map_preset")
+            .selected_text(quick_map.preset.name())
+            .show_ui(ui, |ui| {
+                for preset in ModifierPreset::ALL {
+                    ui.selectable_value(&mut quick_map.preset, preset, preset.name());
+                }
+            });
+        ui.end_row();
+    });
+
+    let (_, num_src_axes, _) = devices.get_physical_limits(&quick_map.src_device);
+    let (_, num_dst_axes, _) = devices.get_virtual_limits(&quick_map.dst_device);
+    let dst_device = quick_map.dst_device;
+    let users = |axis: u32| {
+        devices.other_users(&VirtualControl::Axis {
+            device: dst_device,
+            axis,
+        })
+    };
+
+    let pair = (quick_map.src_device.clone(), dst_device);
+    if quick_map.prefilled_for.as_ref() != Some(&pair) {
+        quick_map.assignments = prefill_assignments(num_src_axes as usize, num_dst_axes, |axis| {
+            !users(axis).is_empty()
+        });
+        quick_map.prefilled_for = Some(pair);
+    }
+
+    ui.separator();
+    if num_src_axes == 0 || num_dst_axes == 0 {
+        ui.label("Select a source device and a destination device with axes.");
+    } else {
+        let label = |axis: Option<u32>| match axis {
+            None => "Skip".to_string(),
+            Some(axis) => match users(axis) {
+                users if users.is_empty() => axis.to_string(),
+                users => format!("{axis} (used by {})", users.join(", ")),
+            },
+        };
+        Grid::new("quick_map_axes").striped(true).show(ui, |ui| {
+            ui.label(RichText::new("Source axis").strong());
+            ui.label(RichText::new("Destination axis").strong());
+            ui.end_row();
+            for (index, assignment) in quick_map.assignments.iter_mut().enumerate() {
+                let src_axis = index as u32 + 1;
+                match devices.get_physical_axis_kind(&quick_map.src_device, src_axis) {
+                    Some(kind) => ui.label(format!("{src_axis} ({kind})")),
+                    None => ui.label(src_axis.to_string()),
+                };
+                ComboBox::from_id_source(("quick_map_axis", index))
+                    .selected_text(label(*assignment))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(assignment, None, label(None));
+                        for axis in 1..=num_dst_axes {
+                            ui.selectable_value(assignment, Some(axis), label(Some(axis)));
+                        }
+                    });
+                ui.end_row();
+            }
+        });
+    }
+
+    let duplicates = quick_map.duplicates();
+    if !duplicates.is_empty() {
+        ui.label(
+            RichText::new(format!("Destination axes assigned twice: {duplicates:?}"))
+                .color(ui.visuals().error_fg_color),
+        );
+    }
+
+    ui.separator();
+    let count = quick_map.assignments.iter().flatten().count();
+    ui.horizontal(|ui| {
+        let enabled = count > 0 && duplicates.is_empty();
+        if ui
+            .add_enabled(enabled, Button::new(format!("Generate {count} rebinds")))
+            .clicked()
+        {
+            let axis_kinds = devices
+                .physical_devices
+                .get(&quick_map.src_device)
+                .map(|info| info.axis_kinds.clone())
+                .unwrap_or_default();
+            quick_map.undo = Some(input.get_config().clone());
+            for rebind in quick_map.generate(&axis_kinds) {
+                input.add_rebind(RebindScope::Profile, rebind);
+            }
+            quick_map.prefilled_for = None;
+            info!("Quick mapped {count} axes");
+        }
+        if ui
+            .add_enabled(quick_map.undo.is_some(), Button::new("Undo"))
+            .on_hover_text("Restore the config from before the last generate")
+            .clicked()
+        {
+            if let Some(config) = quick_map.undo.take() {
+                input.replace_config(config);
+                quick_map.prefilled_for = None;
+                info!("Restored config from before the last quick map");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        input::input_state::AxisKind,
+        rebind::{
+            axis_to_axis::AxisToAxisModifier,
+            merge_axes::AxisSource,
+            quick_map::{prefill_assignments, ModifierPreset, QuickMap},
+            reroute_rebind::RerouteRebind,
+            RebindType,
+        },
+    };
+
+    #[test]
+    fn prefill_is_one_to_one_and_skips_used_destinations() {
+        assert_eq!(
+            prefill_assignments(3, 8, |_| false),
+            vec![Some(1), Some(2), Some(3)]
+        );
+        assert_eq!(
+            prefill_assignments(3, 4, |axis| axis == 2 || axis == 3),
+            vec![Some(1), Some(4), None]
+        );
+    }
+
+    #[test]
+    fn generates_assigned_axes_with_preset() {
+        let quick_map = QuickMap {
+            src_device: "pedals".to_string(),
+            dst_device: 2,
+            assignments: vec![Some(6), None, Some(3)],
+            preset: ModifierPreset::Detected,
+            ..Default::default()
+        };
+        let kinds = [AxisKind::Slider, AxisKind::Stick, AxisKind::Stick];
+        let rebinds = quick_map.generate(&kinds);
+
+        assert_eq!(rebinds.len(), 2);
+        assert_eq!(rebinds[1].name, "Axis_3_To_3");
+        let RebindType::Reroute {
+            rebind:
+                RerouteRebind::AxisToAxis {
+                    src,
+                    dst_device,
+                    dst_axis,
+                    modifier,
+                    ..
+                },
+        } = &rebinds[1].rebind_type
+        else {
+            panic!("expected an axis to axis rebind");
+        };
+        assert_eq!(*src, AxisSource::physical("pedals".to_string(), 3));
+        assert_eq!((*dst_device, *dst_axis), (2, 3));
+        assert_eq!(
+            *modifier,
+            AxisToAxisModifier::for_axis_kind(AxisKind::Stick)
+        );
+
+        let duplicated = QuickMap {
+            assignments: vec![Some(1), Some(1), Some(2)],
+            ..Default::default()
+        };
+        assert_eq!(duplicated.duplicates(), vec![1]);
+    }
+}
@@ -0,0 +1,47 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Identity of a rebind that survives renames and reordering. Overlay overrides refer to the
+/// rebind they replace by it.
+///
+/// Ids are random rather than counted up, so rebinds of different configs and the global rebinds
+/// don't collide.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RebindId(pub u64);
+
+impl RebindId {
+    pub fn new() -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+        // TOML integers are signed
+        Self(hasher.finish() >> 1)
+    }
+}
+
+/// A new id, rebinds from configs without ids get one when loaded
+impl Default for RebindId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::rebind::rebind_id::RebindId;
+
+    #[test]
+    fn new_ids_are_unique_and_fit_toml() {
+        let ids: HashSet<RebindId> = (0..1000).map(|_| RebindId::new()).collect();
+        assert_eq!(ids.len(), 1000);
+        assert!(ids.iter().all(|id| i64::try_from(id.0).is_ok()));
+    }
+}
@@ -1,127 +1,742 @@
-use std::path::Path;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 
-use log::{error, info};
+use log::{info, trace, warn};
+use vjoy::ButtonState;
 
 use crate::{
-    config::Config,
+    config::{Config, DeviceSettings, GlobalRebinds},
     error::Error,
-    input::{PhysicalDevice, VirtualDevice},
+    input::{
+        button_labels::ButtonLabelMap, device_transform::DeviceTransform, PhysicalDevice,
+        VirtualDevice,
+    },
+    launch::LaunchOptions,
+    paths,
     previous::Previous,
+    rate_limited_log::RateLimitedLog,
 };
 
-use super::{shift_mode_mask::ShiftModeMask, Rebind, RebindType};
+use super::{
+    axis_neutral::{apply_axis_neutrals, AxisNeutral},
+    dependencies::exceeds_rewrite_limit,
+    hat_to_hat::convert_hat_type_to_vjoy,
+    keyboard_rebind::KeyChord,
+    midi_feedback::MidiFeedback,
+    mirror::{released_mirror_controls, Mirror},
+    mode_presets::{apply_mode_presets, ModeAxisPreset},
+    output_smoothing::{apply_axis_transition, AxisSmoothing, AxisTransition},
+    overlay::{ActiveOverrides, Overlay},
+    processing_budget::ProcessingBudget,
+    rebind_id::RebindId,
+    shift_conflicts::{shift_conflicts, ShiftConflict},
+    shift_mode_mask::ShiftModeMask,
+    source_lost::SourceLostPolicy,
+    validate_handle_virtual_axis, validate_handle_virtual_button, validate_handle_virtual_hat,
+    validate_value_virtual_axis,
+    virtual_rebind::ProcessingPhase,
+    QuotedName, Rebind, RebindScope, RebindType, TickContext, VirtualControl, VirtualUsage,
+};
 
 pub struct RebindProcessor {
     config: Config,
+    global_rebinds: Vec<Rebind>,
+    config_path: Option<PathBuf>,
+    config_mtime: Option<SystemTime>,
+    /// Serialized config as of the last load or save
+    saved_toml: Option<String>,
+    device_settings: DeviceSettings,
+    /// Written along with the profile, `None` keeps the device settings in memory only
+    device_settings_path: Option<PathBuf>,
+    /// Device settings as of the last load or save
+    saved_device_settings: DeviceSettings,
     active_shift_mode: ShiftModeMask,
+    active_overlay: Option<String>,
+    active_overrides: ActiveOverrides,
+    /// The overlays changed, [`Self::active_overrides`] is resolved again before the next tick
+    overrides_pending: bool,
+    written: HashMap<VirtualControl, usize>,
+    last_written: HashMap<VirtualControl, usize>,
+    /// Rebinds that wrote each virtual control this tick, only counted with a rewrite limit
+    write_counts: HashMap<VirtualControl, u32>,
+    mirrored: HashSet<VirtualControl>,
+    /// Key chords the keyboard rebinds hold this tick
+    held_keys: Vec<KeyChord>,
+    axis_transitions: HashMap<(u32, u32), AxisTransition>,
+    log: RateLimitedLog,
+    output_enabled: bool,
+    slot_generation: u64,
+    resolve_pending: bool,
+    /// Outputs of the previous config are reset before the next tick is processed
+    reset_outputs_pending: bool,
+    budget: ProcessingBudget,
 }
 
 impl RebindProcessor {
-    pub fn new(previous: &Previous) -> Result<Self, Error> {
-        #[cfg(not(debug_assertions))]
-        {
-            let load_cfg_path = match &previous.load_cfg_path {
-                None => std::env::current_dir()?.join("Cfg").join("config.toml"),
-                Some(path) => {
-                    info!("Loading cfg from: {:?}", path);
-                    Path::new(&path).to_path_buf()
-                }
-            };
+    pub fn new(previous: &Previous, launch: &LaunchOptions) -> Result<Self, Error> {
+        let mut processor = Self::new_with_config(previous, launch)?;
+        processor.global_rebinds = Self::read_global_rebinds();
+        processor.device_settings_path = DeviceSettings::default_path()
+            .map_err(|e| warn!("Failed to locate the device settings. Reason: {e}"))
+            .ok();
+        if let Some(path) = &processor.device_settings_path {
+            processor.device_settings = Self::read_device_settings(path);
+            processor.saved_device_settings = processor.device_settings.clone();
+        }
+        processor.migrate_legacy_device_settings();
+        if let Some(budget_ms) = previous.processing_budget_ms {
+            processor.budget.budget_s = budget_ms / 1000.0;
+        }
+        processor.budget.rewrite_limit = previous.rewrite_limit;
+        Ok(processor)
+    }
 
-            return Ok(Self {
-                config: Config::read_from_path_or_default(&load_cfg_path),
-                active_shift_mode: ShiftModeMask(0b00000000),
-            });
+    fn new_with_config(previous: &Previous, launch: &LaunchOptions) -> Result<Self, Error> {
+        if launch.safe_mode {
+            info!("Safe mode: no config loaded");
+            return Ok(Self::from_config(Config::default()));
+        }
+        if launch.debug_config {
+            info!("Loading the built-in debug config");
+            return Ok(Self::from_config(Config::debug_xbox360_config()));
         }
 
-        #[cfg(debug_assertions)]
-        Ok(Self {
-            config: Config::debug_xbox360_config(),
+        let load_cfg_path = match (&launch.config, &previous.load_cfg_path) {
+            (Some(path), _) => {
+                info!("Loading cfg from the command line: {:?}", path);
+                path.clone()
+            }
+            (None, None) => paths::config_dir()?.join("config.toml"),
+            (None, Some(path)) => {
+                info!("Loading cfg from: {:?}", path);
+                Path::new(&path).to_path_buf()
+            }
+        };
+
+        let mut processor = Self::from_config(Config::read_from_path_or_default(&load_cfg_path));
+        processor.config_mtime = Config::modified_time(&load_cfg_path);
+        processor.config_path = Some(load_cfg_path);
+        Ok(processor)
+    }
+
+    pub fn from_config(config: Config) -> Self {
+        let saved_toml = config.to_toml_string().ok();
+        Self {
+            config,
+            global_rebinds: Vec::new(),
+            config_path: None,
+            config_mtime: None,
+            saved_toml,
+            device_settings: DeviceSettings::default(),
+            device_settings_path: None,
+            saved_device_settings: DeviceSettings::default(),
             active_shift_mode: ShiftModeMask(0b00000000),
-        })
+            active_overlay: None,
+            active_overrides: ActiveOverrides::default(),
+            overrides_pending: true,
+            written: HashMap::new(),
+            last_written: HashMap::new(),
+            write_counts: HashMap::new(),
+            mirrored: HashSet::new(),
+            held_keys: Vec::new(),
+            axis_transitions: HashMap::new(),
+            log: RateLimitedLog::default(),
+            output_enabled: true,
+            slot_generation: 1,
+            resolve_pending: true,
+            reset_outputs_pending: false,
+            budget: ProcessingBudget::default(),
+        }
     }
 
     #[profiling::function]
     pub fn add_debug_xbox360_config(&mut self) {
         self.config = Config::debug_xbox360_config();
+        self.config_path = None;
+        self.config_mtime = None;
+        self.saved_toml = self.config.to_toml_string().ok();
         self.active_shift_mode = self.config.default_shift_mode;
+        self.overrides_pending = true;
     }
 
+    /// Refuses to overwrite the current config file if it changed on disk since it was last loaded
+    /// or saved, unless `overwrite` is set.
     #[profiling::function]
-    pub fn save_rebinds(&self, path: &Path) -> Result<(), Error> {
-        self.config.write_to_path(path)
+    pub fn save_rebinds(&mut self, path: &Path, overwrite: bool) -> Result<(), Error> {
+        if !overwrite && self.is_modified_on_disk(path) {
+            return Err(Error::ConfigModifiedOnDisk(path.to_path_buf()));
+        }
+
+        self.config.write_to_path(path)?;
+        self.config_path = Some(path.to_path_buf());
+        self.config_mtime = Config::modified_time(path);
+        self.saved_toml = self.config.to_toml_string().ok();
+        if let Some(device_settings_path) = &self.device_settings_path {
+            self.device_settings.write_to_path(device_settings_path)?;
+            self.saved_device_settings = self.device_settings.clone();
+        }
+        Ok(())
     }
 
     pub fn load_rebinds(&mut self, path: &Path) -> Result<(), Error> {
         match Config::read_from_path(path) {
             Ok(config) => {
-                self.config = config;
-                self.active_shift_mode = self.config.default_shift_mode;
+                self.saved_toml = config.to_toml_string().ok();
+                self.replace_config(config);
+                self.config_path = Some(path.to_path_buf());
+                self.config_mtime = Config::modified_time(path);
                 Ok(())
             }
             Err(e) => Err(e),
         }
     }
 
+    fn read_device_settings(path: &Path) -> DeviceSettings {
+        if !path.exists() {
+            return DeviceSettings::default();
+        }
+
+        match DeviceSettings::read_from_path(path) {
+            Ok(settings) => {
+                info!("Loaded device settings from {:?}", path);
+                settings
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to load device settings from {:?}. Reason: {e}",
+                    path
+                );
+                DeviceSettings::default()
+            }
+        }
+    }
+
+    /// Moves the device settings of a profile in the single-file format into the device settings.
+    /// They are written to their own file on the next save.
+    fn migrate_legacy_device_settings(&mut self) {
+        let legacy = std::mem::take(&mut self.config.legacy_device_settings);
+        if legacy.is_empty() {
+            return;
+        }
+        let added = self.device_settings.merge(legacy);
+        info!("Moved {added} device settings out of the profile, save to complete the migration");
+    }
+
+    fn read_global_rebinds() -> Vec<Rebind> {
+        let path = match GlobalRebinds::default_path() {
+            Ok(path) => path,
+            Err(e) => {
+                warn!("Failed to locate global rebinds. Reason: {e}");
+                return Vec::new();
+            }
+        };
+
+        if !path.exists() {
+            return Vec::new();
+        }
+
+        match GlobalRebinds::read_from_path(&path) {
+            Ok(global) => {
+                info!(
+                    "Loaded {} global rebinds from {:?}",
+                    global.rebinds.len(),
+                    path
+                );
+                global.rebinds
+            }
+            Err(e) => {
+                warn!("Failed to load global rebinds from {:?}. Reason: {e}", path);
+                Vec::new()
+            }
+        }
+    }
+
+    pub fn save_global_rebinds(&self) -> Result<(), Error> {
+        let global = GlobalRebinds {
+            rebinds: self.global_rebinds.clone(),
+        };
+        global.write_to_path(&GlobalRebinds::default_path()?)
+    }
+
+    /// True if the config or the device settings changed since they were last loaded or saved.
+    /// Runtime state doesn't count.
+    pub fn has_unsaved_changes(&self) -> bool {
+        let config_changed = match &self.saved_toml {
+            Some(saved) => self.config.to_toml_string().ok().as_ref() != Some(saved),
+            None => false,
+        };
+        config_changed || self.device_settings != self.saved_device_settings
+    }
+
+    /// True if `path` is the current config file and it was written by someone else since.
+    pub fn is_modified_on_disk(&self, path: &Path) -> bool {
+        if self.config_path.as_deref() != Some(path) {
+            return false;
+        }
+
+        match Config::modified_time(path) {
+            Some(current) => self.config_mtime != Some(current),
+            None => false,
+        }
+    }
+
+    /// Swaps in a new config while keeping the config path, returns the replaced config.
+    pub fn replace_config(&mut self, config: Config) -> Config {
+        let replaced = std::mem::replace(&mut self.config, config);
+        self.migrate_legacy_device_settings();
+        self.active_shift_mode = self.config.default_shift_mode;
+        self.last_written.clear();
+        self.axis_transitions.clear();
+        self.resolve_pending = true;
+        self.overrides_pending = true;
+        self.reset_outputs_pending = true;
+        replaced
+    }
+
+    /// Marks all resolved device slots as stale, e.g. after the device lists were fetched again.
+    #[profiling::function]
+    pub fn invalidate_resolved_slots(&mut self) {
+        self.slot_generation += 1;
+        self.resolve_pending = true;
+    }
+
+    #[profiling::function]
+    pub fn get_config(&self) -> &Config {
+        &self.config
+    }
+
     #[profiling::function]
     pub fn get_active_shift_mode(&self) -> ShiftModeMask {
         self.active_shift_mode
     }
 
     #[profiling::function]
-    pub fn get_active_rebinds(&mut self) -> std::slice::IterMut<Rebind> {
-        self.config.rebinds.iter_mut()
+    pub fn set_active_shift_mode(&mut self, mode: ShiftModeMask) {
+        self.active_shift_mode = mode;
+    }
+
+    #[profiling::function]
+    pub fn is_output_enabled(&self) -> bool {
+        self.output_enabled
+    }
+
+    #[profiling::function]
+    pub fn get_config_path(&self) -> Option<&Path> {
+        self.config_path.as_deref()
+    }
+
+    #[profiling::function]
+    pub fn get_config_name(&self) -> &str {
+        &self.config.name
+    }
+
+    #[profiling::function]
+    pub fn get_config_name_mut(&mut self) -> &mut String {
+        &mut self.config.name
+    }
+
+    #[profiling::function]
+    pub fn get_default_shift_mode_mut(&mut self) -> &mut ShiftModeMask {
+        &mut self.config.default_shift_mode
+    }
+
+    #[profiling::function]
+    pub fn get_rebinds(&self, scope: RebindScope) -> &[Rebind] {
+        match scope {
+            RebindScope::Global => &self.global_rebinds,
+            RebindScope::Profile => &self.config.rebinds,
+        }
+    }
+
+    fn get_rebinds_mut(&mut self, scope: RebindScope) -> &mut Vec<Rebind> {
+        match scope {
+            RebindScope::Global => &mut self.global_rebinds,
+            RebindScope::Profile => &mut self.config.rebinds,
+        }
+    }
+
+    /// Global rebinds followed by the config's rebinds, i.e. in processing order.
+    pub fn get_all_rebinds(&self) -> impl Iterator<Item = &Rebind> {
+        self.global_rebinds.iter().chain(self.config.rebinds.iter())
+    }
+
+    #[profiling::function]
+    pub fn get_active_rebinds(&mut self, scope: RebindScope) -> std::slice::IterMut<Rebind> {
+        self.get_rebinds_mut(scope).iter_mut()
+    }
+
+    /// Virtual controls written by the rebinds of both lists, optionally only in the active shift mode.
+    pub fn virtual_usage(&self, current_mode_only: bool) -> VirtualUsage {
+        let mode = current_mode_only.then_some(self.active_shift_mode);
+        let mut usage = VirtualUsage::new();
+        for scope in [RebindScope::Global, RebindScope::Profile] {
+            add_virtual_usage(&mut usage, scope, self.get_rebinds(scope), mode);
+        }
+        usage
+    }
+
+    /// Read order warnings over both lists, keyed by the index within `scope`.
+    pub fn read_order_warnings(&self, scope: RebindScope) -> HashMap<usize, String> {
+        let num_global = self.global_rebinds.len();
+        read_order_warnings(self.get_all_rebinds())
+            .into_iter()
+            .filter_map(|(index, warning)| match scope {
+                RebindScope::Global => (index < num_global).then_some((index, warning)),
+                RebindScope::Profile => index.checked_sub(num_global).map(|index| (index, warning)),
+            })
+            .collect()
+    }
+
+    /// Shift mode conflicts over both lists, keyed by the index of the reroute rebind within `scope`.
+    /// Only the first conflict of each rebind is kept.
+    pub fn shift_conflicts(&self, scope: RebindScope) -> HashMap<usize, ShiftConflict> {
+        let num_global = self.global_rebinds.len();
+        let mut conflicts = HashMap::new();
+        for conflict in shift_conflicts(self.get_all_rebinds()) {
+            let index = match scope {
+                RebindScope::Global => {
+                    (conflict.reroute_rebind < num_global).then_some(conflict.reroute_rebind)
+                }
+                RebindScope::Profile => conflict.reroute_rebind.checked_sub(num_global),
+            };
+            if let Some(index) = index {
+                conflicts.entry(index).or_insert(conflict);
+            }
+        }
+        conflicts
+    }
+
+    #[profiling::function]
+    pub fn get_button_labels(&self) -> &BTreeMap<String, ButtonLabelMap> {
+        &self.device_settings.button_labels
+    }
+
+    #[profiling::function]
+    pub fn get_button_labels_mut(&mut self) -> &mut BTreeMap<String, ButtonLabelMap> {
+        &mut self.device_settings.button_labels
+    }
+
+    #[profiling::function]
+    pub fn get_device_transforms(&self) -> &BTreeMap<String, DeviceTransform> {
+        &self.device_settings.device_transforms
+    }
+
+    #[profiling::function]
+    pub fn get_device_transforms_mut(&mut self) -> &mut BTreeMap<String, DeviceTransform> {
+        &mut self.device_settings.device_transforms
+    }
+
+    #[profiling::function]
+    pub fn get_axis_smoothing_mut(&mut self) -> &mut Vec<AxisSmoothing> {
+        &mut self.config.axis_smoothing
+    }
+
+    pub fn get_axis_neutrals_mut(&mut self) -> &mut Vec<AxisNeutral> {
+        &mut self.config.axis_neutrals
+    }
+
+    pub fn get_mode_axis_presets_mut(&mut self) -> &mut Vec<ModeAxisPreset> {
+        &mut self.config.mode_axis_presets
+    }
+
+    pub fn get_mirror_mut(&mut self) -> &mut Option<Mirror> {
+        &mut self.config.mirror
+    }
+
+    pub fn get_midi_feedback(&self) -> &[MidiFeedback] {
+        &self.config.midi_feedback
+    }
+
+    pub fn get_midi_feedback_mut(&mut self) -> &mut Vec<MidiFeedback> {
+        &mut self.config.midi_feedback
+    }
+
+    pub fn get_overlays(&self) -> &[Overlay] {
+        &self.config.overlays
+    }
+
+    pub fn get_overlays_mut(&mut self) -> &mut Vec<Overlay> {
+        self.overrides_pending = true;
+        &mut self.config.overlays
+    }
+
+    pub fn get_active_overlay(&self) -> Option<&str> {
+        self.active_overlay.as_deref()
+    }
+
+    pub fn get_processing_budget(&self) -> &ProcessingBudget {
+        &self.budget
+    }
+
+    pub fn get_processing_budget_mut(&mut self) -> &mut ProcessingBudget {
+        &mut self.budget
+    }
+
+    /// Checks the time the last [`Self::process`] call took against the processing budget.
+    pub fn record_processing_time(&mut self, duration_s: f64) {
+        let global_rebinds = &self.global_rebinds;
+        let rebinds = &self.config.rebinds;
+        self.budget.record_tick(duration_s, |index| {
+            global_rebinds
+                .iter()
+                .chain(rebinds.iter())
+                .nth(index)
+                .map(|rebind| rebind.name.clone())
+                .unwrap_or_default()
+        });
+    }
+
+    /// Key chords the keyboard rebinds held last tick, empty while output is disabled
+    pub fn held_keys(&self) -> &[KeyChord] {
+        &self.held_keys
+    }
+
+    /// Controls of the mirror's virtual device that were mirrored and that rebinds wrote last tick
+    pub fn mirror_status(&self) -> Option<(Vec<VirtualControl>, Vec<VirtualControl>)> {
+        let device = self.config.mirror.as_ref()?.dst_id;
+        let on_device = |control: &&VirtualControl| match control {
+            VirtualControl::Button { device: d, .. }
+            | VirtualControl::Axis { device: d, .. }
+            | VirtualControl::Hat { device: d, .. } => *d == device,
+        };
+        let mirrored = self.mirrored.iter().filter(on_device).copied().collect();
+        let bound = self.written.keys().filter(on_device).copied().collect();
+        Some((mirrored, bound))
     }
 
     pub fn process(
         &mut self,
         physical_devices: &mut [PhysicalDevice],
         virtual_devices: &mut [VirtualDevice],
-        time: f64,
-        delta_t: f64,
+        ctx: TickContext,
     ) -> Result<(), Error> {
+        std::mem::swap(&mut self.written, &mut self.last_written);
+        self.written.clear();
+        self.write_counts.clear();
+        self.held_keys.clear();
+
+        if self.resolve_pending {
+            self.resolve_slots(physical_devices, virtual_devices);
+        }
+        if self.overrides_pending {
+            self.active_overrides =
+                ActiveOverrides::resolve(&self.config.overlays, self.active_overlay.as_deref());
+            self.overrides_pending = false;
+        }
+
+        apply_axis_neutrals(&self.config.axis_neutrals, virtual_devices);
+        if self.reset_outputs_pending {
+            write_neutral_outputs(virtual_devices);
+            self.reset_outputs_pending = false;
+        }
+
+        let previous_outputs: Vec<Option<i32>> = self
+            .config
+            .axis_smoothing
+            .iter()
+            .map(|s| validate_value_virtual_axis(virtual_devices, &s.device, &s.axis).ok())
+            .collect();
+
         //Process all logical rebinds first
-        for rebind in self.config.rebinds.iter_mut() {
-            if !rebind.is_active(self.active_shift_mode) {
+        let mode_before = self.active_shift_mode;
+        let mut output_enabled = true;
+        let mut active_overlay = None;
+        for (index, rebind) in
+            all_rebinds_mut(&mut self.global_rebinds, &mut self.config.rebinds).enumerate()
+        {
+            if !rebind.is_active(self.active_shift_mode)
+                || self.budget.is_disabled(index, &rebind.name)
+            {
                 continue;
             }
 
+            let name = &rebind.name;
             if let RebindType::Logical { rebind } = &mut rebind.rebind_type {
-                match rebind.process(physical_devices, &mut self.active_shift_mode) {
+                let start = self.budget.start_rebind();
+                match rebind.process(
+                    physical_devices,
+                    &mut self.active_shift_mode,
+                    &mut output_enabled,
+                    &mut active_overlay,
+                ) {
                     Ok(_) => (),
-                    Err(e) => match e {
-                        Error::RebindProcessingFailed(name) => error!("{name}"),
-                        Error::EmptyRebindOrInvalidID() => (),
-                        _ => (),
-                    },
+                    Err(e) => log_rebind_error(&mut self.log, index, name, e, ctx.time),
                 }
+                self.budget.end_rebind(index, start);
             }
         }
 
-        //Process all reroute rebinds second
-        for rebind in self.config.rebinds.iter_mut() {
-            if !rebind.is_active(self.active_shift_mode) {
-                continue;
+        //Snap the axes with a preset for the mode just entered
+        if self.active_shift_mode != mode_before {
+            apply_mode_presets(
+                &self.config.mode_axis_presets,
+                mode_before,
+                self.active_shift_mode,
+                virtual_devices,
+            );
+        }
+
+        //Hand over from the instances of the previous overlay to the new one
+        if active_overlay != self.active_overlay {
+            trace!(
+                "overlay changed: {:?} -> {:?}",
+                self.active_overlay,
+                active_overlay
+            );
+            self.switch_overlay(active_overlay, virtual_devices);
+        }
+
+        //Hold all virtual output neutral while an output enable switch is not satisfied
+        self.output_enabled = output_enabled;
+        if !output_enabled {
+            for rebind in all_rebinds_mut(&mut self.global_rebinds, &mut self.config.rebinds) {
+                match &mut rebind.rebind_type {
+                    RebindType::Reroute { rebind } => rebind.deactivate(virtual_devices),
+                    RebindType::Keyboard { rebind } => rebind.deactivate(),
+                    _ => (),
+                }
             }
+            for o in self
+                .config
+                .overlays
+                .iter_mut()
+                .flat_map(|o| o.overrides.iter_mut())
+            {
+                match &mut o.rebind_type {
+                    RebindType::Reroute { rebind } => rebind.deactivate(virtual_devices),
+                    RebindType::Keyboard { rebind } => rebind.deactivate(),
+                    _ => (),
+                }
+            }
+            write_neutral_outputs(virtual_devices);
+            self.axis_transitions.clear();
+            self.mirrored.clear();
+            return Ok(());
+        }
 
-            if let RebindType::Reroute { rebind } = &mut rebind.rebind_type {
-                match rebind.process(physical_devices, virtual_devices, time, delta_t) {
-                    Ok(_) => (),
-                    Err(_e) => (),
+        //Process the virtual rebinds of the pre reroute phase, then all reroute rebinds
+        self.process_virtual_rebinds(ProcessingPhase::PreReroute, virtual_devices, ctx);
+        for (index, rebind) in
+            all_rebinds_mut(&mut self.global_rebinds, &mut self.config.rebinds).enumerate()
+        {
+            let is_active = rebind.is_active(self.active_shift_mode)
+                && !self.budget.is_disabled(index, &rebind.name);
+            let on_source_lost = rebind.on_source_lost;
+            let overrides = &self.active_overrides;
+            let (name, rebind_type, resolved) =
+                match overrides.get(&mut self.config.overlays, rebind.id) {
+                    Some(o) => (&rebind.name, &mut o.rebind_type, &mut o.resolved),
+                    None => (&rebind.name, &mut rebind.rebind_type, &mut rebind.resolved),
+                };
+            if let RebindType::Reroute { rebind } = rebind_type {
+                if !is_active {
+                    rebind.deactivate(virtual_devices);
+                    continue;
+                }
+                if let Some(limit) = self.budget.rewrite_limit {
+                    let writes = rebind.virtual_writes();
+                    if let Some(control) =
+                        exceeds_rewrite_limit(&mut self.write_counts, &writes, limit)
+                    {
+                        log_rewrite_limit(&mut self.log, index, name, control, limit, ctx.time);
+                        continue;
+                    }
+                }
+
+                resolved.sync(self.slot_generation);
+                let start = self.budget.start_rebind();
+                match rebind.process(physical_devices, virtual_devices, resolved, ctx) {
+                    Ok(_) => {
+                        for control in rebind.claimed_writes() {
+                            self.written.insert(control, index);
+                        }
+                    }
+                    //The rebind keeps its outputs, so neither the mirror nor smoothing take over
+                    Err(e @ Error::SourceDeviceDisconnected(_)) => {
+                        on_source_lost
+                            .unwrap_or_else(|| SourceLostPolicy::default_for(rebind))
+                            .apply(rebind, virtual_devices);
+                        for control in rebind.claimed_writes() {
+                            self.written.insert(control, index);
+                        }
+                        log_rebind_error(&mut self.log, index, name, e, ctx.time);
+                    }
+                    Err(e) => log_rebind_error(&mut self.log, index, name, e, ctx.time),
                 }
+                self.budget.end_rebind(index, start);
+            }
+        }
+
+        //Collect the keys held by keyboard rebinds, they are sent after processing
+        self.process_keyboard_rebinds(physical_devices, ctx);
+
+        //Mirror the physical inputs no reroute rebind wrote
+        let last_mirrored = std::mem::take(&mut self.mirrored);
+        if let Some(mirror) = &self.config.mirror {
+            match mirror.process(physical_devices, virtual_devices, &self.written) {
+                Ok(mirrored) => self.mirrored = mirrored,
+                Err(e) => self
+                    .log
+                    .warn(("mirror", 0), ctx.time, || format!("mirror: {e}")),
             }
         }
+        for control in released_mirror_controls(&last_mirrored, &self.mirrored, &self.written) {
+            write_neutral_control(virtual_devices, control);
+        }
+
+        //Process the virtual rebinds of the post reroute phase last
+        self.process_virtual_rebinds(ProcessingPhase::PostReroute, virtual_devices, ctx);
+
+        //Virtual rebinds overwrite mirrored controls
+        self.mirrored
+            .retain(|control| !self.written.contains_key(control));
+
+        //Ramp smoothed axes whose writer changed this tick
+        for (smoothing, previous_output) in self.config.axis_smoothing.iter().zip(previous_outputs)
+        {
+            let key = (smoothing.device, smoothing.axis);
+            let control = VirtualControl::Axis {
+                device: smoothing.device,
+                axis: smoothing.axis,
+            };
 
-        //Process all virtual rebinds third
-        for rebind in self.config.rebinds.iter_mut() {
-            if !rebind.is_active(self.active_shift_mode) {
+            let writer = self.written.get(&control);
+            let last_writer = self.last_written.get(&control);
+            if writer.is_none() {
+                self.axis_transitions.remove(&key);
                 continue;
             }
 
-            if let RebindType::Virtual { rebind } = &mut rebind.rebind_type {
-                match rebind.process(virtual_devices, delta_t) {
-                    Ok(_) => (),
-                    Err(_e) => (),
+            if let (Some(_), Some(from)) = (last_writer, previous_output) {
+                if writer != last_writer {
+                    self.axis_transitions.insert(
+                        key,
+                        AxisTransition {
+                            from,
+                            start: ctx.time,
+                        },
+                    );
+                }
+            }
+
+            let Some(transition) = self.axis_transitions.get(&key) else {
+                continue;
+            };
+
+            let Ok(output) =
+                validate_handle_virtual_axis(virtual_devices, &smoothing.device, &smoothing.axis)
+            else {
+                self.axis_transitions.remove(&key);
+                continue;
+            };
+
+            match apply_axis_transition(transition, output.get(), smoothing.duration, ctx.time) {
+                Some(value) => output.set(value),
+                None => {
+                    self.axis_transitions.remove(&key);
                 }
             }
         }
@@ -129,31 +744,161 @@ impl RebindProcessor {
         Ok(())
     }
 
+    /// Processes the active virtual rebinds of one phase in list order.
+    fn process_virtual_rebinds(
+        &mut self,
+        phase: ProcessingPhase,
+        virtual_devices: &mut [VirtualDevice],
+        ctx: TickContext,
+    ) {
+        for (index, rebind) in
+            all_rebinds_mut(&mut self.global_rebinds, &mut self.config.rebinds).enumerate()
+        {
+            if !rebind.is_active(self.active_shift_mode)
+                || self.budget.is_disabled(index, &rebind.name)
+            {
+                continue;
+            }
+
+            let overrides = &self.active_overrides;
+            let (name, rebind_type) = match overrides.get(&mut self.config.overlays, rebind.id) {
+                Some(o) => (&rebind.name, &mut o.rebind_type),
+                None => (&rebind.name, &mut rebind.rebind_type),
+            };
+            let RebindType::Virtual {
+                rebind,
+                phase: rebind_phase,
+            } = rebind_type
+            else {
+                continue;
+            };
+            if *rebind_phase != phase {
+                continue;
+            }
+
+            if let Some(limit) = self.budget.rewrite_limit {
+                let writes = rebind.virtual_writes();
+                if let Some(control) = exceeds_rewrite_limit(&mut self.write_counts, &writes, limit)
+                {
+                    log_rewrite_limit(&mut self.log, index, name, control, limit, ctx.time);
+                    continue;
+                }
+            }
+            let start = self.budget.start_rebind();
+            match rebind.process(virtual_devices, ctx) {
+                // Virtual rebinds adjust the value in place, a reroute that wrote it stays its writer
+                Ok(_) => {
+                    for control in rebind.virtual_writes() {
+                        self.written.entry(control).or_insert(index);
+                    }
+                }
+                Err(e) => log_rebind_error(&mut self.log, index, name, e, ctx.time),
+            }
+            self.budget.end_rebind(index, start);
+        }
+    }
+
+    /// Processes the keyboard rebinds in list order into [`Self::held_keys`].
+    fn process_keyboard_rebinds(&mut self, physical_devices: &[PhysicalDevice], ctx: TickContext) {
+        for (index, rebind) in
+            all_rebinds_mut(&mut self.global_rebinds, &mut self.config.rebinds).enumerate()
+        {
+            let is_active = rebind.is_active(self.active_shift_mode)
+                && !self.budget.is_disabled(index, &rebind.name);
+            let overrides = &self.active_overrides;
+            let (name, rebind_type) = match overrides.get(&mut self.config.overlays, rebind.id) {
+                Some(o) => (&rebind.name, &mut o.rebind_type),
+                None => (&rebind.name, &mut rebind.rebind_type),
+            };
+            let RebindType::Keyboard { rebind } = rebind_type else {
+                continue;
+            };
+            if !is_active {
+                rebind.deactivate();
+                continue;
+            }
+
+            let start = self.budget.start_rebind();
+            if let Err(e) = rebind.process(physical_devices, &mut self.held_keys, ctx) {
+                log_rebind_error(&mut self.log, index, name, e, ctx.time);
+            }
+            self.budget.end_rebind(index, start);
+        }
+    }
+
+    /// Deactivates the outgoing instance of every rebind the old or the new overlay overrides, the
+    /// incoming instance continues with its own state from when it was last active.
+    fn switch_overlay(
+        &mut self,
+        active_overlay: Option<String>,
+        virtual_devices: &mut [VirtualDevice],
+    ) {
+        let incoming = ActiveOverrides::resolve(&self.config.overlays, active_overlay.as_deref());
+        let overlays = &mut self.config.overlays;
+        for rebind in all_rebinds_mut(&mut self.global_rebinds, &mut self.config.rebinds) {
+            let outgoing = match self.active_overrides.get(overlays, rebind.id) {
+                Some(o) => &mut o.rebind_type,
+                None if incoming.contains(rebind.id) => &mut rebind.rebind_type,
+                None => continue,
+            };
+            match outgoing {
+                RebindType::Reroute { rebind } => rebind.deactivate(virtual_devices),
+                RebindType::Keyboard { rebind } => rebind.deactivate(),
+                _ => (),
+            }
+        }
+        self.active_overlay = active_overlay;
+        self.active_overrides = incoming;
+    }
+
+    /// Resolution pass over all reroute rebinds, rebinds edited afterwards re-resolve lazily.
     #[profiling::function]
-    pub fn add_rebind(&mut self, rebind: Rebind) {
-        self.config.rebinds.push(rebind);
+    fn resolve_slots(
+        &mut self,
+        physical_devices: &[PhysicalDevice],
+        virtual_devices: &mut [VirtualDevice],
+    ) {
+        for rebind in all_rebinds_mut(&mut self.global_rebinds, &mut self.config.rebinds) {
+            if let RebindType::Reroute { rebind: reroute } = &rebind.rebind_type {
+                rebind.resolved.sync(self.slot_generation);
+                reroute.resolve(&mut rebind.resolved, physical_devices, virtual_devices);
+            }
+        }
+        self.resolve_pending = false;
+    }
+
+    #[profiling::function]
+    pub fn add_rebind(&mut self, scope: RebindScope, rebind: Rebind) {
+        self.get_rebinds_mut(scope).push(rebind);
     }
 
     #[profiling::function]
-    pub fn remove_rebinds_from_keep(&mut self, keep: &[bool]) {
+    pub fn remove_rebinds_from_keep(&mut self, scope: RebindScope, keep: &[bool]) {
         let mut keep_iter = keep.iter();
-        self.config
-            .rebinds
+        self.get_rebinds_mut(scope)
             .retain(|_| *keep_iter.next().unwrap_or(&true));
     }
 
+    /// The copies get new ids, overrides keep replacing only the original
     #[profiling::function]
-    pub fn duplicate_rebinds_from_copy(&mut self, copy: Vec<Rebind>) {
+    pub fn duplicate_rebinds_from_copy(&mut self, scope: RebindScope, copy: Vec<Rebind>) {
         for rebind in copy.into_iter() {
-            self.add_rebind(rebind);
+            self.add_rebind(
+                scope,
+                Rebind {
+                    id: RebindId::new(),
+                    ..rebind
+                },
+            );
         }
     }
 
     #[profiling::function]
-    pub fn move_rebind(&mut self, index: usize, mov: isize) {
+    pub fn move_rebind(&mut self, scope: RebindScope, index: usize, mov: isize) {
+        let rebinds = self.get_rebinds_mut(scope);
         let swap_index = (index as isize + mov).max(0) as usize;
-        if swap_index < self.config.rebinds.len() && swap_index != index {
-            self.config.rebinds.swap(index, swap_index);
+        if swap_index < rebinds.len() && swap_index != index {
+            rebinds.swap(index, swap_index);
         }
     }
 
@@ -162,3 +907,408 @@ impl RebindProcessor {
         self.config.rebinds.clear();
     }
 }
+
+/// Global rebinds are processed before the config's rebinds within every phase.
+fn all_rebinds_mut<'a>(
+    global: &'a mut [Rebind],
+    profile: &'a mut [Rebind],
+) -> impl Iterator<Item = &'a mut Rebind> {
+    global.iter_mut().chain(profile.iter_mut())
+}
+
+/// Position of a rebind in the processing order: logical, virtual of the pre reroute phase,
+/// reroute, keyboard, then virtual of the post reroute phase, each in list order
+fn processing_order(rebind: &Rebind, index: usize) -> (u8, usize) {
+    match rebind.rebind_type {
+        RebindType::Logical { .. } => (0, index),
+        RebindType::Virtual {
+            phase: ProcessingPhase::PreReroute,
+            ..
+        } => (1, index),
+        RebindType::Reroute { .. } => (2, index),
+        RebindType::Keyboard { .. } => (3, index),
+        RebindType::Virtual { .. } => (4, index),
+    }
+}
+
+/// Reverse index of [`Rebind::virtual_writes`] over one rebind list.
+///
+/// With `mode` set, only rebinds active in that shift mode are counted.
+pub fn add_virtual_usage<'a>(
+    usage: &mut VirtualUsage,
+    scope: RebindScope,
+    rebinds: impl IntoIterator<Item = &'a Rebind>,
+    mode: Option<ShiftModeMask>,
+) {
+    for (index, rebind) in rebinds.into_iter().enumerate() {
+        if mode.is_some_and(|mode| !rebind.is_active(mode)) {
+            continue;
+        }
+        for control in rebind.virtual_writes() {
+            usage
+                .entry(control)
+                .or_default()
+                .push((scope, index, rebind.name.clone()));
+        }
+    }
+}
+
+/// Warnings for rebinds that read a virtual control which is written by a rebind processed after them.
+///
+/// Such reads see the value of the previous tick.
+pub fn read_order_warnings<'a>(
+    rebinds: impl IntoIterator<Item = &'a Rebind>,
+) -> HashMap<usize, String> {
+    let rebinds: Vec<&Rebind> = rebinds.into_iter().collect();
+    let mut warnings = HashMap::new();
+    for (consumer_index, consumer) in rebinds.iter().enumerate() {
+        let consumer_order = processing_order(consumer, consumer_index);
+        for control in consumer.virtual_reads() {
+            let late_producer = rebinds.iter().enumerate().find(|(index, producer)| {
+                processing_order(producer, *index) > consumer_order
+                    && producer.virtual_writes().contains(&control)
+            });
+
+            if let Some((index, producer)) = late_producer {
+                let warning = if processing_order(producer, index).0 == consumer_order.0 {
+                    format!(
+                        "Reads a value written by {}, which is processed later. Move it above this rebind.",
+                        QuotedName(&producer.name)
+                    )
+                } else {
+                    format!(
+                        "Reads a value written by {}, which is processed in a later pass. Change the phase of the virtual rebind.",
+                        QuotedName(&producer.name)
+                    )
+                };
+                warnings.insert(consumer_index, warning);
+            }
+        }
+    }
+
+    warnings
+}
+
+fn log_rebind_error(log: &mut RateLimitedLog, index: usize, name: &str, e: Error, time: f64) {
+    let key = (e.code(), index);
+    match e {
+        Error::EmptyRebindOrInvalidID() => (),
+        Error::RebindProcessingFailed(_) => log.error(key, time, || e.to_string()),
+        _ => log.warn(key, time, || {
+            format!("rebind {}: {e} [{}]", QuotedName(name), e.code())
+        }),
+    }
+}
+
+fn log_rewrite_limit(
+    log: &mut RateLimitedLog,
+    index: usize,
+    name: &str,
+    control: VirtualControl,
+    limit: u32,
+    time: f64,
+) {
+    log.warn(("rewrite limit", index), time, || {
+        format!(
+            "rebind {} skipped: {control:?} was already written {limit} times this tick",
+            QuotedName(name)
+        )
+    });
+}
+
+pub fn write_neutral_control(virtual_devices: &mut [VirtualDevice], control: VirtualControl) {
+    match control {
+        VirtualControl::Button { device, button } => {
+            if let Ok(output) = validate_handle_virtual_button(virtual_devices, &device, &button) {
+                output.set(ButtonState::Released);
+            }
+        }
+        VirtualControl::Axis { device, axis } => {
+            if let Ok(output) = validate_handle_virtual_axis(virtual_devices, &device, &axis) {
+                output.set(output.neutral());
+            }
+        }
+        VirtualControl::Hat { device, hat } => {
+            if let Ok(output) = validate_handle_virtual_hat(virtual_devices, &device, &hat) {
+                output.set(convert_hat_type_to_vjoy(output.get(), -1));
+            }
+        }
+    }
+}
+
+/// Writes `value` to `control`: pressed unless 0 for buttons, 0..=32767 for axes and the angle in
+/// degrees or -1 for centered for hats
+pub fn write_control(virtual_devices: &mut [VirtualDevice], control: VirtualControl, value: i32) {
+    match control {
+        VirtualControl::Button { device, button } => {
+            if let Ok(output) = validate_handle_virtual_button(virtual_devices, &device, &button) {
+                output.set(match value {
+                    0 => ButtonState::Released,
+                    _ => ButtonState::Pressed,
+                });
+            }
+        }
+        VirtualControl::Axis { device, axis } => {
+            if let Ok(output) = validate_handle_virtual_axis(virtual_devices, &device, &axis) {
+                output.set(value);
+            }
+        }
+        VirtualControl::Hat { device, hat } => {
+            if let Ok(output) = validate_handle_virtual_hat(virtual_devices, &device, &hat) {
+                output.set(convert_hat_type_to_vjoy(output.get(), value));
+            }
+        }
+    }
+}
+
+/// Releases all buttons and hats and returns all axes to their neutral value
+pub fn write_neutral_outputs(virtual_devices: &mut [VirtualDevice]) {
+    for device in virtual_devices.iter_mut() {
+        for button in device.handle.buttons_mut() {
+            button.set(ButtonState::Released);
+        }
+        for axis in device.handle.axes_mut() {
+            axis.set(axis.neutral());
+        }
+        for hat in device.handle.hats_mut() {
+            hat.set(convert_hat_type_to_vjoy(hat.get(), -1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use crate::{
+        config::Config,
+        error::Error,
+        rebind::{
+            merge_axes::AxisSource,
+            rebind_processor::{
+                add_virtual_usage, processing_order, read_order_warnings, RebindProcessor,
+            },
+            reroute_rebind::RerouteRebind,
+            shift_mode_mask::ShiftModeMask,
+            virtual_rebind::{ProcessingPhase, VirtualRebind},
+            Rebind, RebindScope, RebindType, VirtualControl, VirtualUsage,
+        },
+    };
+
+    fn axis_to_axis(name: &str, dst_axis: u32) -> Rebind {
+        Rebind::new(
+            name.to_string(),
+            ShiftModeMask::default(),
+            RebindType::Reroute {
+                rebind: RerouteRebind::AxisToAxis {
+                    src: AxisSource::physical("guid".to_string(), 1),
+                    dst_device: 1,
+                    dst_axis,
+                    modifier: Default::default(),
+                    freeze: None,
+                    gain: None,
+                },
+            },
+        )
+    }
+
+    #[test]
+    fn virtual_usage_in_all_or_current_mode() {
+        let mut shifted = axis_to_axis("shifted", 2);
+        shifted.mode_mask = ShiftModeMask(0b00000001);
+        let rebinds = vec![axis_to_axis("pitch", 1), shifted];
+        let axis = |axis| VirtualControl::Axis { device: 1, axis };
+
+        let mut all = VirtualUsage::new();
+        add_virtual_usage(&mut all, RebindScope::Profile, &rebinds, None);
+        assert_eq!(
+            all[&axis(1)],
+            vec![(RebindScope::Profile, 0, "pitch".to_string())]
+        );
+        assert_eq!(all[&axis(2)][0].2, "shifted");
+
+        let mut current = VirtualUsage::new();
+        add_virtual_usage(
+            &mut current,
+            RebindScope::Profile,
+            &rebinds,
+            Some(ShiftModeMask(0)),
+        );
+        assert!(current.contains_key(&axis(1)));
+        assert!(!current.contains_key(&axis(2)));
+    }
+
+    fn merge_virtual(axis: u32) -> Rebind {
+        Rebind::new(
+            "merge".to_string(),
+            ShiftModeMask::default(),
+            RebindType::Reroute {
+                rebind: RerouteRebind::MergeAxes {
+                    src_0: AxisSource::physical("guid".to_string(), 2),
+                    src_1: AxisSource::Virtual { device: 1, axis },
+                    dst_device: 1,
+                    dst_axis: 8,
+                    modifier: Default::default(),
+                },
+            },
+        )
+    }
+
+    fn trim(phase: ProcessingPhase, axis: u32) -> Rebind {
+        Rebind::new(
+            "trim".to_string(),
+            ShiftModeMask::default(),
+            RebindType::Virtual {
+                rebind: VirtualRebind::VirtualAxisApplyButtonTrim {
+                    axis_device: 1,
+                    axis,
+                    trim_neg_device: 1,
+                    trim_neg_button: 1,
+                    trim_pos_device: 1,
+                    trim_pos_button: 2,
+                    trim_reset_device: 1,
+                    trim_reset_button: 3,
+                    modifier: Default::default(),
+                },
+                phase,
+            },
+        )
+    }
+
+    #[test]
+    fn virtual_phases_wrap_the_reroute_pass() {
+        let pre = processing_order(&trim(ProcessingPhase::PreReroute, 3), 2);
+        let reroute = processing_order(&axis_to_axis("reroute", 3), 1);
+        let post = processing_order(&trim(ProcessingPhase::PostReroute, 3), 0);
+        assert!(pre < reroute);
+        assert!(reroute < post);
+        assert_eq!(ProcessingPhase::default(), ProcessingPhase::PostReroute);
+    }
+
+    #[test]
+    fn pre_reroute_trim_is_read_in_the_same_tick() {
+        let rebinds = vec![merge_virtual(3), trim(ProcessingPhase::PreReroute, 3)];
+        assert!(read_order_warnings(&rebinds).is_empty());
+
+        let rebinds = vec![trim(ProcessingPhase::PostReroute, 3), merge_virtual(3)];
+        let warnings = read_order_warnings(&rebinds);
+        assert!(warnings[&1].contains("'trim'"));
+        assert!(warnings[&1].contains("phase"));
+    }
+
+    #[test]
+    fn producer_above_consumer_is_fine() {
+        let rebinds = vec![axis_to_axis("producer", 3), merge_virtual(3)];
+        assert!(read_order_warnings(&rebinds).is_empty());
+    }
+
+    #[test]
+    fn producer_below_consumer_warns() {
+        let rebinds = vec![merge_virtual(3), axis_to_axis("producer", 3)];
+        let warnings = read_order_warnings(&rebinds);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[&0].contains("'producer'"));
+    }
+
+    #[test]
+    fn virtual_axis_source_warns_when_producer_below() {
+        let mut second_instance = axis_to_axis("second instance", 1);
+        if let RebindType::Reroute {
+            rebind: RerouteRebind::AxisToAxis {
+                src, dst_device, ..
+            },
+        } = &mut second_instance.rebind_type
+        {
+            *src = AxisSource::Virtual { device: 1, axis: 3 };
+            *dst_device = 2;
+        }
+
+        let rebinds = vec![axis_to_axis("producer", 3), second_instance.clone()];
+        assert!(read_order_warnings(&rebinds).is_empty());
+
+        let rebinds = vec![second_instance, axis_to_axis("producer", 3)];
+        assert!(read_order_warnings(&rebinds)[&0].contains("'producer'"));
+    }
+
+    #[test]
+    fn save_refuses_to_overwrite_external_edits() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let mut processor = RebindProcessor::from_config(Config::default());
+        processor.save_rebinds(&path, false).unwrap();
+        processor.save_rebinds(&path, false).unwrap();
+
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        drop(file);
+
+        assert!(matches!(
+            processor.save_rebinds(&path, false),
+            Err(Error::ConfigModifiedOnDisk(_))
+        ));
+        processor.save_rebinds(&path, true).unwrap();
+        assert!(!processor.is_modified_on_disk(&path));
+    }
+
+    #[test]
+    fn unsaved_changes_until_saved() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let mut processor = RebindProcessor::from_config(Config::default());
+        assert!(!processor.has_unsaved_changes());
+
+        processor.add_rebind(RebindScope::Profile, merge_virtual(3));
+        assert!(processor.has_unsaved_changes());
+
+        processor.save_rebinds(&path, true).unwrap();
+        assert!(!processor.has_unsaved_changes());
+    }
+
+    #[test]
+    fn profile_and_device_settings_are_saved_apart() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let profile_path = dir.join("profile.toml");
+        let mut processor = RebindProcessor::from_config(Config::debug_xbox360_config());
+        processor.device_settings_path = Some(dir.join("devices.toml"));
+        processor
+            .get_button_labels_mut()
+            .entry("guid".to_string())
+            .or_default()
+            .set(1, "Trigger");
+        assert!(processor.has_unsaved_changes());
+        processor.save_rebinds(&profile_path, true).unwrap();
+        assert!(!processor.has_unsaved_changes());
+
+        let profile = std::fs::read_to_string(&profile_path).unwrap();
+        assert!(!profile.contains("Trigger"));
+
+        let mut readback = RebindProcessor::from_config(Config::default());
+        readback.device_settings_path = processor.device_settings_path.clone();
+        readback.device_settings = RebindProcessor::read_device_settings(&dir.join("devices.toml"));
+        readback.load_rebinds(&profile_path).unwrap();
+
+        assert_eq!(readback.get_config(), processor.get_config());
+        assert_eq!(readback.get_button_labels(), processor.get_button_labels());
+    }
+
+    #[test]
+    fn global_rebinds_are_processed_first() {
+        let mut processor = RebindProcessor::from_config(Config::default());
+        processor.add_rebind(RebindScope::Global, merge_virtual(3));
+        processor.add_rebind(RebindScope::Profile, axis_to_axis("producer", 3));
+
+        let global = processor.read_order_warnings(RebindScope::Global);
+        assert!(global[&0].contains("'producer'"));
+        assert!(processor
+            .read_order_warnings(RebindScope::Profile)
+            .is_empty());
+
+        processor.add_rebind(RebindScope::Global, axis_to_axis("global producer", 4));
+        processor.add_rebind(RebindScope::Profile, merge_virtual(4));
+        assert!(!processor
+            .read_order_warnings(RebindScope::Profile)
+            .contains_key(&1));
+    }
+}
@@ -1,45 +1,110 @@
-use egui::{Align, CollapsingHeader, ComboBox, Layout, RichText, ScrollArea, Ui, Vec2};
+use egui::{
+    Align, Button, CollapsingHeader, Color32, ComboBox, Grid, Layout, RichText, ScrollArea,
+    SelectableLabel, Ui, Vec2,
+};
 use egui_extras::{Column, TableBuilder};
 use indexmap::IndexMap;
+use log::info;
+use strum::IntoEnumIterator;
 
 use super::{
-    shift_mode_mask::ShiftModeMask, Rebind, RebindType, TABLE_ROW_HEIGHT, TABLE_TOP_BUTTONS_WIDTH,
+    axis_roles::{AxisRole, AxisRoleTable},
+    midi_feedback::midi_feedback_widget,
+    mirror::{describe_controls, mirror_widget},
+    mode_chips::{self, ModeChips},
+    mode_presets::mode_presets_widget,
+    output_range::OutputRangeSettings,
+    output_smoothing::axis_smoothing_widget,
+    overlay::overlays_widget,
+    rebind_id::RebindId,
+    rebind_table,
+    shift_conflicts::ShiftConflict,
+    shift_mode_mask::ShiftModeMask,
+    IDDropdown, Rebind, RebindScope, RebindType, VirtualControl, VirtualUsage, SECTION_SPACING,
+    TABLE_ROW_HEIGHT, TABLE_TOP_BUTTONS_WIDTH,
 };
 use crate::{
-    input::{Input, PhysicalDevice, VirtualDevice},
+    input::{
+        button_labels::ButtonLabelMap, input_state::AxisKind, Input, PhysicalDevice, VirtualDevice,
+    },
     ui_data::UIData,
 };
 
+/// Evaluation order of one tick, kept in sync with `RebindProcessor::process`
+const PROCESSING_ORDER_HELP: &str = "Each tick the rebinds are processed in five passes, global rebinds before the config's rebinds and each pass in list order:
+1. Logical rebinds
+2. Virtual rebinds of the pre reroute phase
+3. Reroute rebinds
+4. Keyboard rebinds
+5. Virtual rebinds of the post reroute phase
+A rebind reading a virtual control written in a later pass sees the value of the previous tick.";
+
 pub struct RebindUIWrapped<'a> {
     pub inner: &'a mut Rebind,
     pub index: usize,
     pub keep: bool,
     pub copy: bool,
     pub mov: isize,
+    pub warning: Option<String>,
+    pub shift_conflict: Option<ShiftConflict>,
+    pub show_debug_state: bool,
+    pub safe_edit: bool,
+    /// Referenced shift modes the rebind is active in, with their chip color
+    pub mode_chips: Vec<(ShiftModeMask, Color32)>,
 }
 
 impl<'a> RebindUIWrapped<'a> {
+    /// `expanded` drives the open state of the header and is toggled by clicks on it
     pub fn widget(
         &mut self,
         ui: &mut Ui,
-        override_open: Option<bool>,
+        expanded: &mut bool,
         devices_name_map: &mut DevicesInfoMap,
     ) {
         ui.allocate_ui_with_layout(
             Vec2 { x: 400.0, y: 600.0 },
             Layout::left_to_right(Align::TOP),
             |ui| {
-                CollapsingHeader::new(&self.inner.name)
+                let header = CollapsingHeader::new(&self.inner.name)
                     .id_source(self.index)
-                    .open(override_open)
+                    .open(Some(*expanded))
                     .show_background(true)
                     .show(ui, |ui| {
                         ui.add_space(5.0);
-                        self.inner.widget(ui, devices_name_map);
+                        self.inner.widget(ui, devices_name_map, self.safe_edit);
+                        if self.show_debug_state {
+                            self.debug_state_widget(ui);
+                        }
                         ui.separator();
                     });
+                if header.header_response.clicked() {
+                    *expanded = !*expanded;
+                }
 
                 ui.with_layout(Layout::right_to_left(Align::TOP), |ui| {
+                    if let Some(notes) = &self.inner.notes {
+                        ui.label("🗒").on_hover_text(notes);
+                    }
+                    if let Some(warning) = &self.warning {
+                        ui.label(RichText::new("⚠").color(ui.visuals().warn_fg_color))
+                            .on_hover_text(warning);
+                    }
+                    if let Some(conflict) = &self.shift_conflict {
+                        if ui
+                            .button(RichText::new("Fix").color(ui.visuals().warn_fg_color))
+                            .on_hover_text(format!(
+                                "{}\nClick to exclude the shift mode from this rebind",
+                                conflict.message()
+                            ))
+                            .clicked()
+                        {
+                            conflict.fix(self.inner);
+                        }
+                    }
+                    if self.inner.pending.is_pending() {
+                        ui.label(RichText::new("unapplied").color(ui.visuals().warn_fg_color))
+                            .on_hover_text("Unapplied changes, apply or revert them in the rebind");
+                    }
                     if ui.button("X").clicked() {
                         self.keep = false;
                     }
@@ -52,20 +117,69 @@ impl<'a> RebindUIWrapped<'a> {
                     if ui.button("up").clicked() {
                         self.mov = -1;
                     }
+                    for (mode, color) in self.mode_chips.iter().rev() {
+                        mode_chips::chip(ui, *mode, *color);
+                    }
                 });
 
                 ui.add_space(5.0);
             },
         );
     }
+
+    fn debug_state_widget(&self, ui: &mut Ui) {
+        let state = self.inner.debug_state();
+        ui.add_space(SECTION_SPACING);
+        CollapsingHeader::new("Debug state")
+            .id_source(("RebindDebugState", self.index))
+            .show(ui, |ui| {
+                if state.is_empty() {
+                    ui.label("no internal state");
+                    return;
+                }
+                Grid::new(("RebindDebugStateGrid", self.index))
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (name, value) in state {
+                            ui.label(RichText::new(name).monospace());
+                            ui.label(RichText::new(value).monospace());
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
 }
 
 pub struct DevicesInfoMap {
     pub physical_devices: IndexMap<String, DeviceInfo>,
     pub virtual_devices: IndexMap<u32, DeviceInfo>,
+    pub virtual_usage: VirtualUsage,
+    /// Rebind whose widget is currently drawn, excluded from the usage annotations
+    pub editing: Option<(RebindScope, usize)>,
+    pub overlay_names: Vec<String>,
+    /// Set from the app settings by the rebind viewer, defaults elsewhere
+    pub axis_roles: AxisRoleTable,
+    pub output_range: OutputRangeSettings,
 }
 
 impl DevicesInfoMap {
+    pub fn from_input(input: &Input, usage_current_mode_only: bool) -> Self {
+        Self {
+            physical_devices: input.get_physical_device_info_map(),
+            virtual_devices: input.get_virtual_device_info_map(),
+            virtual_usage: input.get_virtual_usage(usage_current_mode_only),
+            editing: None,
+            overlay_names: input
+                .get_overlays()
+                .iter()
+                .map(|o| o.name.clone())
+                .collect(),
+            axis_roles: AxisRoleTable::default(),
+            output_range: OutputRangeSettings::default(),
+        }
+    }
+
     pub fn get_physical_name(&self, guid: &String) -> &str {
         let Some(found) = self.physical_devices.get(guid) else {
             return "Unset";
@@ -110,6 +224,19 @@ impl DevicesInfoMap {
         });
     }
 
+    pub fn overlay_widget(&self, ui: &mut Ui, selected: &mut String) {
+        ui.horizontal(|ui| {
+            ui.set_min_width(200.0);
+            ComboBox::from_id_source("overlay_widget")
+                .selected_text(selected.as_str())
+                .show_ui(ui, |ui| {
+                    for name in self.overlay_names.iter() {
+                        ui.selectable_value(selected, name.to_owned(), name);
+                    }
+                });
+        });
+    }
+
     pub fn get_physical_limits(&self, guid: &String) -> (u32, u32, u32) {
         let Some(found) = self.physical_devices.get(guid) else {
             return (0, 0, 0)
@@ -122,6 +249,149 @@ impl DevicesInfoMap {
         )
     }
 
+    pub fn get_physical_axis_kind(&self, guid: &String, axis: u32) -> Option<AxisKind> {
+        let index = axis.checked_sub(1)? as usize;
+        self.physical_devices
+            .get(guid)
+            .and_then(|found| found.axis_kinds.get(index))
+            .copied()
+    }
+
+    /// Button id dropdown that shows the custom label of each button if one is set
+    pub fn physical_button_widget(&self, ui: &mut Ui, guid: &String, selected_button: &mut u32) {
+        let Some(found) = self.physical_devices.get(guid) else {
+            selected_button.id_dropdown_widget(0, ui);
+            return;
+        };
+
+        let labels = &found.button_labels;
+        ComboBox::from_id_source("physical_button_widget")
+            .selected_text(labels.display(*selected_button))
+            .show_ui(ui, |ui| {
+                for i in 1..=found.num_buttons as u32 {
+                    ui.selectable_value(selected_button, i, labels.display(i));
+                }
+            });
+    }
+
+    /// Axis id dropdown that shows the classification next to each id, e.g. "3 (trigger)"
+    pub fn physical_axis_widget(&self, ui: &mut Ui, guid: &String, selected_axis: &mut u32) {
+        let label = |axis: u32| match self.get_physical_axis_kind(guid, axis) {
+            Some(kind) => format!("{axis} ({kind})"),
+            None => axis.to_string(),
+        };
+
+        let max = self.get_physical_limits(guid).1;
+        ComboBox::from_id_source("physical_axis_widget")
+            .selected_text(label(*selected_axis))
+            .show_ui(ui, |ui| {
+                for i in 1..=max {
+                    ui.selectable_value(selected_axis, i, label(i));
+                }
+            });
+    }
+
+    /// Names of the rebinds other than the edited one that write to `control`
+    pub fn other_users(&self, control: &VirtualControl) -> Vec<&str> {
+        let Some(users) = self.virtual_usage.get(control) else {
+            return Vec::new();
+        };
+
+        users
+            .iter()
+            .filter(|(scope, index, _)| self.editing != Some((*scope, *index)))
+            .map(|(_, _, name)| name.as_str())
+            .collect()
+    }
+
+    /// Lowest id in 1..=max that no other rebind writes to
+    fn next_free(&self, max: u32, control: impl Fn(u32) -> VirtualControl) -> Option<u32> {
+        (1..=max).find(|id| self.other_users(&control(*id)).is_empty())
+    }
+
+    /// Id dropdown that marks ids already written by other rebinds, e.g. "12 (used by Gear Toggle)"
+    fn virtual_control_widget(
+        &self,
+        ui: &mut Ui,
+        max: u32,
+        selected: &mut u32,
+        control: impl Fn(u32) -> VirtualControl,
+    ) {
+        let label = |id: u32| {
+            let users = self.other_users(&control(id));
+            match users.is_empty() {
+                true => id.to_string(),
+                false => format!("{id} (used by {})", users.join(", ")),
+            }
+        };
+
+        ui.horizontal(|ui| {
+            ComboBox::from_id_source("virtual_control_widget")
+                .selected_text(label(*selected))
+                .show_ui(ui, |ui| {
+                    for i in 1..=max {
+                        ui.selectable_value(selected, i, label(i));
+                    }
+                });
+
+            let next_free = self.next_free(max, &control);
+            if ui
+                .add_enabled(next_free.is_some(), Button::new("next free").small())
+                .on_hover_text("Select the lowest id no other rebind writes to")
+                .clicked()
+            {
+                *selected = next_free.unwrap_or(*selected);
+            }
+        });
+    }
+
+    pub fn virtual_button_widget(&self, ui: &mut Ui, device: &u32, selected_button: &mut u32) {
+        let max = self.get_virtual_limits(device).0;
+        self.virtual_control_widget(ui, max, selected_button, |button| VirtualControl::Button {
+            device: *device,
+            button,
+        });
+    }
+
+    pub fn virtual_axis_widget(&self, ui: &mut Ui, device: &u32, selected_axis: &mut u32) {
+        let max = self.get_virtual_limits(device).1;
+        self.virtual_control_widget(ui, max, selected_axis, |axis| VirtualControl::Axis {
+            device: *device,
+            axis,
+        });
+    }
+
+    /// Role dropdown that picks the conventional axis of the role on `device`, shows the role of
+    /// the selected axis. Roles whose axis the device lacks are disabled.
+    pub fn virtual_axis_role_widget(&self, ui: &mut Ui, device: &u32, selected_axis: &mut u32) {
+        let (axis_names, num_axes) = match self.virtual_devices.get(device) {
+            Some(info) => (info.axis_names.as_slice(), info.num_axes),
+            None => (&[][..], 0),
+        };
+        let selected_role = self
+            .axis_roles
+            .role_of(*selected_axis, axis_names, num_axes);
+        ComboBox::from_id_source("virtual_axis_role_widget")
+            .selected_text(selected_role.map_or("Custom", AxisRole::label))
+            .show_ui(ui, |ui| {
+                for role in AxisRole::iter() {
+                    let axis = self.axis_roles.resolve(role, axis_names, num_axes);
+                    let response = ui
+                        .add_enabled(
+                            axis.is_some(),
+                            SelectableLabel::new(selected_role == Some(role), role.label()),
+                        )
+                        .on_disabled_hover_text(format!(
+                            "The device has no {} axis",
+                            self.axis_roles.get(role).as_ref()
+                        ));
+                    if let (true, Some(axis)) = (response.clicked(), axis) {
+                        *selected_axis = axis;
+                    }
+                }
+            });
+    }
+
     pub fn get_virtual_limits(&self, id: &u32) -> (u32, u32, u32) {
         let Some(found) = self.virtual_devices.get(id) else {
             return (0, 0, 0)
@@ -140,15 +410,22 @@ pub struct DeviceInfo {
     pub num_buttons: usize,
     pub num_axes: usize,
     pub num_hats: usize,
+    pub axis_kinds: Vec<AxisKind>,
+    /// vJoy display name per axis, empty for physical devices
+    pub axis_names: Vec<String>,
+    pub button_labels: ButtonLabelMap,
 }
 
 impl DeviceInfo {
-    pub fn from_physical(device: &PhysicalDevice) -> Self {
+    pub fn from_physical(device: &PhysicalDevice, button_labels: ButtonLabelMap) -> Self {
         Self {
             name: device.name(),
             num_buttons: device.num_buttons(),
             num_axes: device.num_axes(),
             num_hats: device.num_hats(),
+            axis_kinds: device.input_state.axis_kinds().copied().collect(),
+            axis_names: Vec::new(),
+            button_labels,
         }
     }
 
@@ -158,20 +435,32 @@ impl DeviceInfo {
             num_buttons: device.num_buttons(),
             num_axes: device.num_axes(),
             num_hats: device.num_hats(),
+            axis_kinds: Vec::new(),
+            axis_names: (0..device.num_axes())
+                .map_while(|index| device.handle.axis_name(index).map(str::to_string))
+                .collect(),
+            button_labels: ButtonLabelMap::default(),
         }
     }
 }
 
 #[profiling::function]
-pub(crate) fn build_ui(input: &mut Input, ui: &mut Ui, _ui_data: &mut UIData) {
+pub(crate) fn build_ui(input: &mut Input, ui: &mut Ui, ui_data: &mut UIData) {
     ui.set_height(ui.available_height());
-    let physical_devices = input.get_physical_device_info_map();
-    let virtual_devices = input.get_virtual_device_info_map();
-    let mut devices_name_map = DevicesInfoMap {
-        physical_devices,
-        virtual_devices,
-    };
-    let mut override_open = None;
+    let mut devices_name_map = DevicesInfoMap::from_input(input, ui_data.usage_current_mode_only);
+    devices_name_map.axis_roles = ui_data.axis_roles.clone();
+    devices_name_map.output_range = ui_data.output_range.clone();
+    let config = input.get_config();
+    let mode_chips = ModeChips::new(
+        config.default_shift_mode,
+        &config.rebinds,
+        ui_data.visualization.palette,
+    );
+    if let Some(filter) = ui_data.rebind_mode_filter {
+        if !mode_chips.modes.contains(&filter) {
+            ui_data.rebind_mode_filter = None;
+        }
+    }
 
     ui.with_layout(Layout::top_down(Align::LEFT), |ui| {
         ui.with_layout(Layout::top_down(Align::LEFT), |ui| {
@@ -179,121 +468,456 @@ pub(crate) fn build_ui(input: &mut Input, ui: &mut Ui, _ui_data: &mut UIData) {
                 .column(Column::exact(TABLE_TOP_BUTTONS_WIDTH))
                 .column(Column::exact(TABLE_TOP_BUTTONS_WIDTH))
                 .column(Column::exact(TABLE_TOP_BUTTONS_WIDTH))
+                .column(Column::exact(TABLE_TOP_BUTTONS_WIDTH))
                 .body(|mut body| {
                     body.row(TABLE_ROW_HEIGHT, |mut row| {
                         row.col(|ui| {
-                            ui.label(RichText::new("Active rebinds").strong());
+                            ui.label(RichText::new("Active rebinds").strong())
+                                .on_hover_text(PROCESSING_ORDER_HELP);
                         });
                         row.col(|ui| {
                             if ui.button("Collapse all").clicked() {
-                                override_open = Some(false);
+                                ui_data.expanded_rebinds = Default::default();
                             }
                         });
                         row.col(|ui| {
                             if ui.button("Maximize all").clicked() {
-                                override_open = Some(true);
+                                for scope in [RebindScope::Global, RebindScope::Profile] {
+                                    let names =
+                                        input.get_active_rebinds(scope).map(|r| r.name.clone());
+                                    ui_data.expanded_rebinds.get_mut(scope).extend(names);
+                                }
                             }
                         });
                     });
                     body.row(TABLE_ROW_HEIGHT, |mut row| {
                         row.col(|ui| {
                             if ui.button("Add logical").clicked() {
-                                input.add_rebind(Rebind {
-                                    name: "New logical rebind".to_string(),
-                                    mode_mask: ShiftModeMask::default(),
-                                    rebind_type: RebindType::Logical {
-                                        rebind: Default::default(),
-                                    },
-                                });
+                                input.add_rebind(
+                                    RebindScope::Profile,
+                                    Rebind::new(
+                                        "New logical rebind".to_string(),
+                                        ShiftModeMask::default(),
+                                        RebindType::Logical {
+                                            rebind: Default::default(),
+                                        },
+                                    ),
+                                );
                             }
                         });
                         row.col(|ui| {
                             if ui.button("Add reroute").clicked() {
-                                input.add_rebind(Rebind {
-                                    name: "New reroute rebind".to_string(),
-                                    mode_mask: ShiftModeMask::default(),
-                                    rebind_type: RebindType::Reroute {
-                                        rebind: Default::default(),
-                                    },
-                                });
+                                input.add_rebind(
+                                    RebindScope::Profile,
+                                    Rebind::new(
+                                        "New reroute rebind".to_string(),
+                                        ShiftModeMask::default(),
+                                        RebindType::Reroute {
+                                            rebind: Default::default(),
+                                        },
+                                    ),
+                                );
                             }
                         });
                         row.col(|ui| {
                             if ui.button("Add virtual").clicked() {
-                                input.add_rebind(Rebind {
-                                    name: "New virtual rebind".to_string(),
-                                    mode_mask: ShiftModeMask::default(),
-                                    rebind_type: RebindType::Virtual {
-                                        rebind: Default::default(),
-                                    },
-                                });
+                                input.add_rebind(
+                                    RebindScope::Profile,
+                                    Rebind::new(
+                                        "New virtual rebind".to_string(),
+                                        ShiftModeMask::default(),
+                                        RebindType::Virtual {
+                                            rebind: Default::default(),
+                                            phase: Default::default(),
+                                        },
+                                    ),
+                                );
                             }
                         });
+                        row.col(|ui| {
+                            if ui.button("Add keyboard").clicked() {
+                                input.add_rebind(
+                                    RebindScope::Profile,
+                                    Rebind::new(
+                                        "New keyboard rebind".to_string(),
+                                        ShiftModeMask::default(),
+                                        RebindType::Keyboard {
+                                            rebind: Default::default(),
+                                        },
+                                    ),
+                                );
+                            }
+                        });
+                    });
+                    body.row(TABLE_ROW_HEIGHT, |mut row| {
+                        row.col(|ui| {
+                            ui.horizontal(|ui| {
+                                if ui
+                                    .button("Quick map axes")
+                                    .on_hover_text(
+                                        "Map all axes of a device to a virtual device at once",
+                                    )
+                                    .clicked()
+                                {
+                                    ui_data.quick_map.open = true;
+                                }
+                                if ui
+                                    .button("Rename rebinds")
+                                    .on_hover_text("Find and replace in the names of the rebinds")
+                                    .clicked()
+                                {
+                                    ui_data.batch_rename.open = true;
+                                }
+                                if ui
+                                    .button("Swap devices")
+                                    .on_hover_text(
+                                        "Point the rebinds reading one device at another one",
+                                    )
+                                    .clicked()
+                                {
+                                    ui_data.device_swap.open = true;
+                                }
+                                if ui
+                                    .button("Test script")
+                                    .on_hover_text(
+                                        "Run scripted presses and mode changes against the rebinds",
+                                    )
+                                    .clicked()
+                                {
+                                    ui_data.test_script.open = true;
+                                }
+                            });
+                        });
+                        row.col(|ui| {
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut ui_data.show_rebind_debug_state, "Debug")
+                                    .on_hover_text(
+                                        "Show the internal state of each expanded rebind",
+                                    );
+                                ui.checkbox(&mut ui_data.safe_edit, "Safe edit").on_hover_text(
+                                    "Collect rebind changes until they are applied",
+                                );
+                            });
+                        });
                     })
                 });
 
             ui.add_space(10.0);
 
-            if input.get_active_rebinds().peekable().peek().is_none() {
-                ui.label("no active rebinds");
-                return;
-            }
+            CollapsingHeader::new("Config")
+                .id_source("ConfigHeader")
+                .default_open(true)
+                .show_background(true)
+                .show(ui, |ui| {
+                    rebind_table(ui)
+                        .column(Column::remainder())
+                        .body(|mut body| {
+                            body.row(TABLE_ROW_HEIGHT, |mut row| {
+                                row.col(|ui| {
+                                    ui.label("Name:");
+                                });
+                                row.col(|ui| {
+                                    ui.text_edit_singleline(input.get_config_name_mut());
+                                });
+                            });
+                            body.row(TABLE_ROW_HEIGHT, |mut row| {
+                                row.col(|ui| {
+                                    ui.label("Default shift mode:");
+                                });
+                                row.col(|ui| {
+                                    input.get_default_shift_mode_mut().widget(ui);
+                                });
+                            });
+                            body.row(TABLE_ROW_HEIGHT, |mut row| {
+                                row.col(|_ui| {});
+                                row.col(|ui| {
+                                    if ui
+                                        .button("Apply now")
+                                        .on_hover_text("Set the active shift mode to the default")
+                                        .clicked()
+                                    {
+                                        let default_shift_mode =
+                                            *input.get_default_shift_mode_mut();
+                                        input.set_active_shift_mode(default_shift_mode);
+                                    }
+                                });
+                            });
+                            body.row(TABLE_ROW_HEIGHT, |mut row| {
+                                row.col(|ui| {
+                                    ui.label("Used outputs:");
+                                });
+                                row.col(|ui| {
+                                    ui.checkbox(
+                                        &mut ui_data.usage_current_mode_only,
+                                        "Current shift mode only",
+                                    )
+                                    .on_hover_text(
+                                        "Only mark outputs of rebinds active in the current shift mode as used",
+                                    );
+                                });
+                            });
+                        });
 
-            ui.with_layout(Layout::top_down(Align::LEFT), |ui| {
-                let active_rebinds = input.get_active_rebinds().peekable();
-                let mut active_rebinds_ui_wrapped: Vec<RebindUIWrapped> = active_rebinds
-                    .enumerate()
-                    .map(|(index, r)| RebindUIWrapped {
-                        inner: r,
-                        index,
-                        keep: true,
-                        copy: false,
-                        mov: 0,
-                    })
-                    .collect();
+                    ui.add_space(SECTION_SPACING);
+                    ui.label("Axis presets, applied when the shift mode switches into their mode:");
+                    mode_presets_widget(
+                        ui,
+                        input.get_mode_axis_presets_mut(),
+                        &devices_name_map,
+                    );
+                });
 
-                ScrollArea::vertical()
-                    .always_show_scroll(true)
-                    .show(ui, |ui| {
-                        for rebind in active_rebinds_ui_wrapped.iter_mut() {
-                            rebind.widget(ui, override_open, &mut devices_name_map);
-                            ui.add_space(10.0);
+            ui.add_space(10.0);
+
+            CollapsingHeader::new("Output smoothing")
+                .id_source("OutputSmoothing")
+                .show_background(true)
+                .show(ui, |ui| {
+                    ui.label("Ramp virtual axes to the new value when the writing rebind changes.");
+                    axis_smoothing_widget(ui, input.get_axis_smoothing_mut(), &devices_name_map);
+                });
+
+            ui.add_space(10.0);
+
+            CollapsingHeader::new("Mirror device")
+                .id_source("MirrorDevice")
+                .show_background(true)
+                .show(ui, |ui| {
+                    ui.label("Copy a physical device 1:1 to a virtual device wherever no reroute rebind writes.");
+                    mirror_widget(ui, input.get_mirror_mut(), &devices_name_map);
+                    if let Some((mirrored, bound)) = input.get_mirror_status() {
+                        ui.checkbox(&mut ui_data.show_mirror_status, "Show mirrored inputs");
+                        if ui_data.show_mirror_status {
+                            ui.label(format!("Mirrored: {}", describe_controls(&mirrored)));
+                            ui.label(format!("Bound by rebinds: {}", describe_controls(&bound)));
                         }
+                    }
+                });
 
-                        ui.add_space(ui.available_height());
-                    });
+            ui.add_space(10.0);
+
+            CollapsingHeader::new("MIDI feedback")
+                .id_source("MidiFeedback")
+                .show_background(true)
+                .show(ui, |ui| {
+                    ui.label("Send a MIDI message when a shift mode or virtual button turns on or off, e.g. for button box LEDs. The port is picked in System > MIDI feedback.");
+                    midi_feedback_widget(ui, input.get_midi_feedback_mut(), &devices_name_map);
+                });
+
+            ui.add_space(10.0);
 
-                {
-                    profiling::scope!("RebindViewer::build_ui::PostProcess");
-                    let keep: Vec<bool> =
-                        active_rebinds_ui_wrapped.iter().map(|r| r.keep).collect();
-                    let copy: Vec<Rebind> = active_rebinds_ui_wrapped
+            CollapsingHeader::new("Overlays")
+                .id_source("Overlays")
+                .show_background(true)
+                .show(ui, |ui| {
+                    ui.label("Replace rebinds with alternate instances while a momentary overlay button is held.");
+                    let rebinds: Vec<(RebindId, String, RebindType)> = input
+                        .get_config()
+                        .rebinds
                         .iter()
-                        .filter_map(|r| match r.copy {
-                            false => None,
-                            true => Some(r.inner.clone()),
-                        })
+                        .filter(|r| !matches!(r.rebind_type, RebindType::Logical { .. }))
+                        .map(|r| (r.id, r.name.clone(), r.rebind_type.clone()))
                         .collect();
-                    let index_mov: Vec<(usize, isize)> = active_rebinds_ui_wrapped
-                        .iter()
-                        .enumerate()
-                        .filter_map(|(index, r)| {
-                            if r.mov == 0 {
-                                None
-                            } else {
-                                Some((index, r.mov))
+                    let active = input.get_active_overlay().map(str::to_string);
+                    devices_name_map.editing = None;
+                    overlays_widget(
+                        ui,
+                        input.get_overlays_mut(),
+                        &rebinds,
+                        active.as_deref(),
+                        &mut devices_name_map,
+                    );
+                });
+
+            ui.add_space(10.0);
+
+            CollapsingHeader::new("Global rebinds")
+                .id_source("GlobalRebinds")
+                .show_background(true)
+                .show(ui, |ui| {
+                    ui.label("Active with every config and processed before its rebinds.");
+                    ui.horizontal(|ui| {
+                        if ui.button("Add logical").clicked() {
+                            input.add_rebind(
+                                RebindScope::Global,
+                                Rebind::new(
+                                    "New global logical rebind".to_string(),
+                                    ShiftModeMask::default(),
+                                    RebindType::Logical {
+                                        rebind: Default::default(),
+                                    },
+                                ),
+                            );
+                        }
+                        if ui.button("Add reroute").clicked() {
+                            input.add_rebind(
+                                RebindScope::Global,
+                                Rebind::new(
+                                    "New global reroute rebind".to_string(),
+                                    ShiftModeMask::default(),
+                                    RebindType::Reroute {
+                                        rebind: Default::default(),
+                                    },
+                                ),
+                            );
+                        }
+                        if ui.button("Add virtual").clicked() {
+                            input.add_rebind(
+                                RebindScope::Global,
+                                Rebind::new(
+                                    "New global virtual rebind".to_string(),
+                                    ShiftModeMask::default(),
+                                    RebindType::Virtual {
+                                        rebind: Default::default(),
+                                        phase: Default::default(),
+                                    },
+                                ),
+                            );
+                        }
+                        if ui.button("Add keyboard").clicked() {
+                            input.add_rebind(
+                                RebindScope::Global,
+                                Rebind::new(
+                                    "New global keyboard rebind".to_string(),
+                                    ShiftModeMask::default(),
+                                    RebindType::Keyboard {
+                                        rebind: Default::default(),
+                                    },
+                                ),
+                            );
+                        }
+                        if ui.button("Save").clicked() {
+                            match input.save_global_rebinds() {
+                                Ok(_) => info!("Saved global rebinds"),
+                                Err(e) => ui_data.modals.report_save_error("global rebinds", e),
                             }
-                        })
-                        .collect();
+                        }
+                    });
+                    ui.add_space(10.0);
+                    rebind_list_ui(
+                        ui,
+                        input,
+                        RebindScope::Global,
+                        ui_data,
+                        &mode_chips,
+                        &mut devices_name_map,
+                    );
+                });
 
-                    input.remove_rebinds_from_keep(&keep);
-                    for (index, mov) in index_mov {
-                        input.move_rebind(index, mov);
-                    }
+            ui.add_space(10.0);
+            ui.separator();
+            ui.add_space(10.0);
 
-                    input.duplicate_rebinds_from_copy(copy);
+            if input
+                .get_active_rebinds(RebindScope::Profile)
+                .peekable()
+                .peek()
+                .is_none()
+            {
+                ui.label("no active rebinds");
+                return;
+            }
+            mode_chips.filter_bar(ui, &mut ui_data.rebind_mode_filter);
+            ui.add_space(5.0);
+
+            ui.with_layout(Layout::top_down(Align::LEFT), |ui| {
+                let mut scroll_area = ScrollArea::vertical().always_show_scroll(true);
+                if let Some(offset) = ui_data.restore_rebind_scroll.take() {
+                    scroll_area = scroll_area.vertical_scroll_offset(offset);
                 }
+                let output = scroll_area.show(ui, |ui| {
+                    rebind_list_ui(
+                        ui,
+                        input,
+                        RebindScope::Profile,
+                        ui_data,
+                        &mode_chips,
+                        &mut devices_name_map,
+                    );
+                    ui.add_space(ui.available_height());
+                });
+                ui_data.rebind_scroll = output.state.offset.y;
             });
         });
     });
 }
+
+/// Rebind widgets of one list, then applies the remove, move and clone requests made in them.
+fn rebind_list_ui(
+    ui: &mut Ui,
+    input: &mut Input,
+    scope: RebindScope,
+    ui_data: &mut UIData,
+    mode_chips: &ModeChips,
+    devices_name_map: &mut DevicesInfoMap,
+) {
+    let mut warnings = input.get_read_order_warnings(scope);
+    let mut shift_conflicts = input.get_shift_conflicts(scope);
+    let mut rebinds_ui_wrapped: Vec<RebindUIWrapped> = input
+        .get_active_rebinds(scope)
+        .enumerate()
+        .map(|(index, r)| {
+            let chips = mode_chips
+                .active_in(r)
+                .into_iter()
+                .map(|mode| (mode, mode_chips.color(mode)))
+                .collect();
+            RebindUIWrapped {
+                inner: r,
+                index,
+                keep: true,
+                copy: false,
+                mov: 0,
+                warning: warnings.remove(&index),
+                shift_conflict: shift_conflicts.remove(&index),
+                show_debug_state: ui_data.show_rebind_debug_state,
+                safe_edit: ui_data.safe_edit,
+                mode_chips: chips,
+            }
+        })
+        .collect();
+
+    for rebind in rebinds_ui_wrapped.iter_mut() {
+        if let Some(filter) = ui_data.rebind_mode_filter {
+            if !rebind.inner.is_active(filter) {
+                continue;
+            }
+        }
+        devices_name_map.editing = Some((scope, rebind.index));
+        let name = rebind.inner.name.clone();
+        let mut expanded = ui_data.expanded_rebinds.get(scope).contains(&name);
+        rebind.widget(ui, &mut expanded, devices_name_map);
+        ui_data
+            .expanded_rebinds
+            .update(scope, &name, &rebind.inner.name, expanded);
+        ui.add_space(10.0);
+    }
+
+    profiling::scope!("RebindViewer::build_ui::PostProcess");
+    let keep: Vec<bool> = rebinds_ui_wrapped.iter().map(|r| r.keep).collect();
+    let copy: Vec<Rebind> = rebinds_ui_wrapped
+        .iter()
+        .filter_map(|r| match r.copy {
+            false => None,
+            true => Some(r.inner.clone()),
+        })
+        .collect();
+    let index_mov: Vec<(usize, isize)> = rebinds_ui_wrapped
+        .iter()
+        .enumerate()
+        .filter_map(|(index, r)| {
+            if r.mov == 0 {
+                None
+            } else {
+                Some((index, r.mov))
+            }
+        })
+        .collect();
+
+    input.remove_rebinds_from_keep(scope, &keep);
+    for (index, mov) in index_mov {
+        input.move_rebind(scope, index, mov);
+    }
+
+    input.duplicate_rebinds_from_copy(scope, copy);
+}
@@ -1,14 +1,28 @@
-use egui::{RichText, Ui};
+use egui::{RichText, Slider, Ui};
 use serde::{Deserialize, Serialize};
 
 use strum::{AsRefStr, EnumIter, EnumString, EnumVariantNames};
 
 use super::{
-    axis_to_axis::{apply_axis_modifier, convert_axis_to_vjoy_range, AxisToAxisModifier},
+    axis_gain::{flat_src_button, AxisGain, ButtonSource},
+    axis_to_axis::{
+        apply_axis_modifier, convert_axis_to_vjoy_range, AxisFreeze, AxisToAxisModifier,
+    },
+    axis_to_button::{apply_axis_to_button_modifier, AxisToButtonModifier},
     button_to_button::{apply_button_modifier, ButtonToButtonModifier},
+    four_buttons_to_hat::{apply_four_buttons_to_hat_modifier, FourButtonsToHatModifier},
     hat_to_hat::{apply_hat_modifier, convert_hat_type_to_vjoy, HatToHatModifier},
-    merge_axes::{apply_merge_axes_modifier, MergeAxesModifier},
+    merge_axes::{
+        apply_merge_axes_modifier, flat_src_0_axis, flat_src_1_axis, flat_src_axis, AxisSource,
+        MergeAxesModifier,
+    },
+    mix_axes::AxisMix,
+    multi_action::{
+        cancel_multi_action, multi_action_steps_widget, process_multi_action, MacroAction,
+        MultiActionState,
+    },
     rebind_viewer::DevicesInfoMap,
+    resolved_slots::ResolvedSlots,
     two_buttons_to_axis::{apply_two_buttons_to_axis_modifier, TwoButtonsToAxisModifier},
     *,
 };
@@ -32,8 +46,8 @@ use crate::{
 #[serde(tag = "variant")]
 pub enum RerouteRebind {
     ButtonToButton {
-        src_device: String,
-        src_button: u32,
+        #[serde(flatten, with = "flat_src_button")]
+        src: ButtonSource,
         dst_device: u32,
         dst_button: u32,
 
@@ -60,33 +74,85 @@ pub enum RerouteRebind {
         #[serde(flatten)]
         modifier: HatToHatModifier,
     },
-    AxisToAxis {
+    FourButtonsToHat {
+        src_device: String,
+        src_button_north: u32,
+        src_button_east: u32,
+        src_button_south: u32,
+        src_button_west: u32,
+        dst_device: u32,
+        dst_hat: u32,
+
+        #[serde(flatten)]
+        modifier: FourButtonsToHatModifier,
+    },
+    AxisToButton {
         src_device: String,
         src_axis: u32,
         dst_device: u32,
+        dst_button: u32,
+
+        #[serde(flatten)]
+        modifier: AxisToButtonModifier,
+    },
+    AxisToAxis {
+        #[serde(flatten, with = "flat_src_axis")]
+        src: AxisSource,
+        dst_device: u32,
         dst_axis: u32,
 
         #[serde(flatten)]
         modifier: AxisToAxisModifier,
+
+        #[serde(default)]
+        freeze: Option<AxisFreeze>,
+
+        #[serde(default)]
+        gain: Option<AxisGain>,
     },
     MergeAxes {
-        src_0_device: String,
-        src_0_axis: u32,
-        src_1_device: String,
-        src_1_axis: u32,
+        #[serde(flatten, with = "flat_src_0_axis")]
+        src_0: AxisSource,
+        #[serde(flatten, with = "flat_src_1_axis")]
+        src_1: AxisSource,
         dst_device: u32,
         dst_axis: u32,
 
         #[serde(flatten)]
         modifier: MergeAxesModifier,
     },
+    MixToTwoAxes {
+        src_a: AxisSource,
+        src_b: AxisSource,
+        dst_1_device: u32,
+        dst_1_axis: u32,
+        dst_2_device: u32,
+        dst_2_axis: u32,
+
+        #[serde(flatten)]
+        mix: AxisMix,
+    },
+    MultiAction {
+        src_device: String,
+        src_button: u32,
+        actions: Vec<MacroAction>,
+
+        #[serde(skip_serializing)]
+        #[serde(default)]
+        state: MultiActionState,
+    },
+    ActivityToButton {
+        src_device: String,
+        timeout_s: f64,
+        dst_device: u32,
+        dst_button: u32,
+    },
 }
 
 impl Default for RerouteRebind {
     fn default() -> Self {
         Self::ButtonToButton {
-            src_device: Default::default(),
-            src_button: Default::default(),
+            src: Default::default(),
             dst_device: Default::default(),
             dst_button: Default::default(),
             modifier: Default::default(),
@@ -100,18 +166,49 @@ impl Display for RerouteRebind {
     }
 }
 
+impl VariantDescription for RerouteRebind {
+    fn description(&self) -> &'static str {
+        match self {
+            RerouteRebind::ButtonToButton { .. } => "One physical or virtual button drives one virtual button",
+            RerouteRebind::TwoButtonsToAxis { .. } => {
+                "Two physical buttons drive one virtual axis, e.g. +/- keys to throttle"
+            }
+            RerouteRebind::HatToHat { .. } => "One physical hat drives one virtual hat",
+            RerouteRebind::FourButtonsToHat { .. } => {
+                "Four physical buttons drive one virtual hat, e.g. a castle switch without a hat"
+            }
+            RerouteRebind::AxisToButton { .. } => {
+                "One physical axis presses one or two virtual buttons past a threshold"
+            }
+            RerouteRebind::AxisToAxis { .. } => {
+                "One physical or virtual axis drives one virtual axis with deadzone, curve and filter options"
+            }
+            RerouteRebind::MergeAxes { .. } => {
+                "Two axes are combined into one virtual axis, e.g. toe brakes to rudder"
+            }
+            RerouteRebind::MixToTwoAxes { .. } => {
+                "Two axes are mixed into two virtual axes, e.g. pitch and roll to elevons"
+            }
+            RerouteRebind::MultiAction { .. } => {
+                "One physical button runs a sequence of virtual button, axis and hat steps"
+            }
+            RerouteRebind::ActivityToButton { .. } => {
+                "Virtual button is held while any input of a physical device changed within the timeout"
+            }
+        }
+    }
+}
+
 impl RerouteRebind {
     pub fn content_widget(&mut self, ui: &mut Ui, devices_info_map: &mut DevicesInfoMap) {
         ui.vertical(|ui| match self {
             RerouteRebind::ButtonToButton {
-                src_device,
-                src_button,
+                src,
                 dst_device,
                 dst_button,
                 modifier,
             } => {
-                TableBuilder::new(ui)
-                    .column(Column::exact(TABLE_COLUMN_LEFT_WIDTH))
+                rebind_table(ui)
                     .column(Column::remainder())
                     .body(|mut body| {
                         body.row(TABLE_ROW_HEIGHT, |mut row| {
@@ -119,12 +216,22 @@ impl RerouteRebind {
                                 ui.label(RichText::new("From").strong());
                             });
                         });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Source:");
+                            });
+                            row.col(|ui| {
+                                ui.push_id("FromSource", |ui| {
+                                    src.variant_dropdown_widget(ui);
+                                });
+                            });
+                        });
                         body.row(TABLE_ROW_HEIGHT, |mut row| {
                             row.col(|ui| {
                                 ui.label("Device:");
                             });
                             row.col(|ui| {
-                                devices_info_map.physical_devices_widget(ui, src_device);
+                                src.device_widget(ui, devices_info_map);
                             });
                         });
                         body.row(TABLE_ROW_HEIGHT, |mut row| {
@@ -133,8 +240,7 @@ impl RerouteRebind {
                             });
                             row.col(|ui| {
                                 ui.push_id("FromButton", |ui| {
-                                    let max = devices_info_map.get_physical_limits(src_device).0;
-                                    src_button.id_dropdown_widget(max, ui);
+                                    src.button_widget(ui, devices_info_map);
                                 });
                             });
                         });
@@ -160,8 +266,8 @@ impl RerouteRebind {
                             });
                             row.col(|ui| {
                                 ui.push_id("ToButton", |ui| {
-                                    let max = devices_info_map.get_virtual_limits(dst_device).0;
-                                    dst_button.id_dropdown_widget(max, ui);
+                                    devices_info_map
+                                        .virtual_button_widget(ui, dst_device, dst_button);
                                 });
                             });
                         });
@@ -178,6 +284,7 @@ impl RerouteRebind {
                         });
                     });
 
+                modifier.variant_description_label(ui);
                 modifier.widget(ui);
             }
 
@@ -190,8 +297,7 @@ impl RerouteRebind {
                 dst_axis,
                 modifier,
             } => {
-                TableBuilder::new(ui)
-                    .column(Column::exact(TABLE_COLUMN_LEFT_WIDTH))
+                rebind_table(ui)
                     .column(Column::remainder())
                     .body(|mut body| {
                         body.row(TABLE_ROW_HEIGHT, |mut row| {
@@ -215,9 +321,11 @@ impl RerouteRebind {
                             });
                             row.col(|ui| {
                                 ui.push_id("FromButtonPos", |ui| {
-                                    let max =
-                                        devices_info_map.get_physical_limits(src_pos_device).0;
-                                    src_pos_button.id_dropdown_widget(max, ui);
+                                    devices_info_map.physical_button_widget(
+                                        ui,
+                                        src_pos_device,
+                                        src_pos_button,
+                                    );
                                 });
                             });
                         });
@@ -237,9 +345,11 @@ impl RerouteRebind {
                             });
                             row.col(|ui| {
                                 ui.push_id("FromButtonNeg", |ui| {
-                                    let max =
-                                        devices_info_map.get_physical_limits(src_neg_device).0;
-                                    src_neg_button.id_dropdown_widget(max, ui);
+                                    devices_info_map.physical_button_widget(
+                                        ui,
+                                        src_neg_device,
+                                        src_neg_button,
+                                    );
                                 });
                             });
                         });
@@ -265,8 +375,7 @@ impl RerouteRebind {
                             });
                             row.col(|ui| {
                                 ui.push_id("ToAxis", |ui| {
-                                    let max = devices_info_map.get_virtual_limits(dst_device).1;
-                                    dst_axis.id_dropdown_widget(max, ui);
+                                    devices_info_map.virtual_axis_widget(ui, dst_device, dst_axis);
                                 });
                             });
                         });
@@ -283,6 +392,7 @@ impl RerouteRebind {
                         });
                     });
 
+                modifier.variant_description_label(ui);
                 modifier.widget(ui);
             }
 
@@ -293,8 +403,7 @@ impl RerouteRebind {
                 dst_hat,
                 modifier,
             } => {
-                TableBuilder::new(ui)
-                    .column(Column::exact(TABLE_COLUMN_LEFT_WIDTH))
+                rebind_table(ui)
                     .column(Column::remainder())
                     .body(|mut body| {
                         body.row(TABLE_ROW_HEIGHT, |mut row| {
@@ -361,18 +470,106 @@ impl RerouteRebind {
                         });
                     });
 
+                modifier.variant_description_label(ui);
                 modifier.widget(ui);
             }
 
-            RerouteRebind::AxisToAxis {
+            RerouteRebind::FourButtonsToHat {
+                src_device,
+                src_button_north,
+                src_button_east,
+                src_button_south,
+                src_button_west,
+                dst_device,
+                dst_hat,
+                modifier,
+            } => {
+                rebind_table(ui)
+                    .column(Column::remainder())
+                    .body(|mut body| {
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("From").strong());
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Device:");
+                            });
+                            row.col(|ui| {
+                                devices_info_map.physical_devices_widget(ui, src_device);
+                            });
+                        });
+                        for (label, id, button) in [
+                            ("North button:", "FromButtonNorth", src_button_north),
+                            ("East button:", "FromButtonEast", src_button_east),
+                            ("South button:", "FromButtonSouth", src_button_south),
+                            ("West button:", "FromButtonWest", src_button_west),
+                        ] {
+                            body.row(TABLE_ROW_HEIGHT, |mut row| {
+                                row.col(|ui| {
+                                    ui.label(label);
+                                });
+                                row.col(|ui| {
+                                    ui.push_id(id, |ui| {
+                                        devices_info_map
+                                            .physical_button_widget(ui, src_device, button);
+                                    });
+                                });
+                            });
+                        }
+                        body.row(SECTION_SPACING, |mut row| {
+                            row.col(|_| {});
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("To").strong());
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Device:");
+                            });
+                            row.col(|ui| {
+                                devices_info_map.virtual_devices_widget(ui, dst_device);
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Hat:");
+                            });
+                            row.col(|ui| {
+                                ui.push_id("ToHat", |ui| {
+                                    let max = devices_info_map.get_virtual_limits(dst_device).2;
+                                    dst_hat.id_dropdown_widget(max, ui);
+                                });
+                            });
+                        });
+                        body.row(SECTION_SPACING, |mut row| {
+                            row.col(|_| {});
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("Modifier:").strong());
+                            });
+                            row.col(|ui| {
+                                modifier.variant_dropdown_widget(ui);
+                            });
+                        });
+                    });
+
+                modifier.variant_description_label(ui);
+                modifier.widget(ui);
+            }
+
+            RerouteRebind::AxisToButton {
                 src_device,
                 src_axis,
                 dst_device,
-                dst_axis,
+                dst_button,
                 modifier,
             } => {
-                TableBuilder::new(ui)
-                    .column(Column::exact(TABLE_COLUMN_LEFT_WIDTH))
+                rebind_table(ui)
                     .column(Column::remainder())
                     .body(|mut body| {
                         body.row(TABLE_ROW_HEIGHT, |mut row| {
@@ -394,8 +591,113 @@ impl RerouteRebind {
                             });
                             row.col(|ui| {
                                 ui.push_id("FromAxis", |ui| {
-                                    let max = devices_info_map.get_physical_limits(src_device).1;
-                                    src_axis.id_dropdown_widget(max, ui);
+                                    devices_info_map.physical_axis_widget(ui, src_device, src_axis);
+                                });
+                            });
+                        });
+                        body.row(SECTION_SPACING, |mut row| {
+                            row.col(|_| {});
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("To").strong());
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Device:");
+                            });
+                            row.col(|ui| {
+                                devices_info_map.virtual_devices_widget(ui, dst_device);
+                            });
+                        });
+                        let detents = modifier.high_button().is_some();
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(if detents { "Low button:" } else { "Button:" });
+                            });
+                            row.col(|ui| {
+                                ui.push_id("ToButton", |ui| {
+                                    devices_info_map
+                                        .virtual_button_widget(ui, dst_device, dst_button);
+                                });
+                            });
+                        });
+                        if let Some(high_button) = modifier.high_button_mut() {
+                            body.row(TABLE_ROW_HEIGHT, |mut row| {
+                                row.col(|ui| {
+                                    ui.label("High button:");
+                                });
+                                row.col(|ui| {
+                                    ui.push_id("ToHighButton", |ui| {
+                                        devices_info_map
+                                            .virtual_button_widget(ui, dst_device, high_button);
+                                    });
+                                });
+                            });
+                        }
+                        body.row(SECTION_SPACING, |mut row| {
+                            row.col(|_| {});
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("Modifier:").strong());
+                            });
+                            row.col(|ui| {
+                                modifier.variant_dropdown_widget(ui);
+                            });
+                        });
+                    });
+
+                modifier.variant_description_label(ui);
+                modifier.widget(ui);
+            }
+
+            RerouteRebind::AxisToAxis {
+                src,
+                dst_device,
+                dst_axis,
+                modifier,
+                freeze,
+                gain,
+            } => {
+                let source_was_unset = match src {
+                    AxisSource::Physical { device, axis } => device.is_empty() || *axis == 0,
+                    AxisSource::Virtual { .. } => false,
+                };
+                rebind_table(ui)
+                    .column(Column::remainder())
+                    .body(|mut body| {
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("From").strong());
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Source:");
+                            });
+                            row.col(|ui| {
+                                ui.push_id("FromSource", |ui| {
+                                    src.variant_dropdown_widget(ui);
+                                });
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Device:");
+                            });
+                            row.col(|ui| {
+                                src.device_widget(ui, devices_info_map);
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Axis:");
+                            });
+                            row.col(|ui| {
+                                ui.push_id("FromAxis", |ui| {
+                                    src.axis_widget(ui, devices_info_map);
                                 });
                             });
                         });
@@ -415,14 +717,23 @@ impl RerouteRebind {
                                 devices_info_map.virtual_devices_widget(ui, dst_device);
                             });
                         });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Role:").on_hover_text(
+                                    "Picks the axis games expect for the role, see System > Axis roles",
+                                );
+                            });
+                            row.col(|ui| {
+                                devices_info_map.virtual_axis_role_widget(ui, dst_device, dst_axis);
+                            });
+                        });
                         body.row(TABLE_ROW_HEIGHT, |mut row| {
                             row.col(|ui| {
                                 ui.label("Axis:");
                             });
                             row.col(|ui| {
                                 ui.push_id("ToAxis", |ui| {
-                                    let max = devices_info_map.get_virtual_limits(dst_device).1;
-                                    dst_axis.id_dropdown_widget(max, ui);
+                                    devices_info_map.virtual_axis_widget(ui, dst_device, dst_axis);
                                 });
                             });
                         });
@@ -439,20 +750,53 @@ impl RerouteRebind {
                         });
                     });
 
+                //Pick defaults matching the axis kind once the source of a new rebind is set
+                if let (true, AxisSource::Physical { device, axis }) = (source_was_unset, &*src) {
+                    if let Some(kind) = devices_info_map.get_physical_axis_kind(device, *axis) {
+                        *modifier = AxisToAxisModifier::for_axis_kind(kind);
+                    }
+                }
+
+                modifier.variant_description_label(ui);
                 modifier.widget(ui);
+                modifier.output_range().widget(
+                    ui,
+                    "Output range:",
+                    &devices_info_map.output_range,
+                );
+
+                ui.add_space(SECTION_SPACING);
+                let mut freeze_enabled = freeze.is_some();
+                if ui
+                    .checkbox(&mut freeze_enabled, "Hold value while button held")
+                    .changed()
+                {
+                    *freeze = freeze_enabled.then(AxisFreeze::default);
+                }
+                if let Some(freeze) = freeze {
+                    freeze.widget(ui, devices_info_map);
+                }
+
+                let mut gain_enabled = gain.is_some();
+                if ui
+                    .checkbox(&mut gain_enabled, "Scale output while button held")
+                    .changed()
+                {
+                    *gain = gain_enabled.then(AxisGain::default);
+                }
+                if let Some(gain) = gain {
+                    gain.widget(ui, devices_info_map);
+                }
             }
 
             RerouteRebind::MergeAxes {
-                src_0_device,
-                src_0_axis,
-                src_1_device,
-                src_1_axis,
+                src_0,
+                src_1,
                 dst_device,
                 dst_axis,
                 modifier,
             } => {
-                TableBuilder::new(ui)
-                    .column(Column::exact(TABLE_COLUMN_LEFT_WIDTH))
+                rebind_table(ui)
                     .column(Column::remainder())
                     .body(|mut body| {
                         body.row(TABLE_ROW_HEIGHT, |mut row| {
@@ -460,13 +804,23 @@ impl RerouteRebind {
                                 ui.label(RichText::new("From").strong());
                             });
                         });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("First source:");
+                            });
+                            row.col(|ui| {
+                                ui.push_id("MergeFirstSource", |ui| {
+                                    src_0.variant_dropdown_widget(ui);
+                                });
+                            });
+                        });
                         body.row(TABLE_ROW_HEIGHT, |mut row| {
                             row.col(|ui| {
                                 ui.label("First device:");
                             });
                             row.col(|ui| {
                                 ui.push_id("MergeFirstDevice", |ui| {
-                                    devices_info_map.physical_devices_widget(ui, src_0_device);
+                                    src_0.device_widget(ui, devices_info_map);
                                 });
                             });
                         });
@@ -476,8 +830,17 @@ impl RerouteRebind {
                             });
                             row.col(|ui| {
                                 ui.push_id("FromAxisFirst", |ui| {
-                                    let max = devices_info_map.get_physical_limits(src_0_device).1;
-                                    src_0_axis.id_dropdown_widget(max, ui);
+                                    src_0.axis_widget(ui, devices_info_map);
+                                });
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Second source:");
+                            });
+                            row.col(|ui| {
+                                ui.push_id("MergeSecondSource", |ui| {
+                                    src_1.variant_dropdown_widget(ui);
                                 });
                             });
                         });
@@ -487,7 +850,7 @@ impl RerouteRebind {
                             });
                             row.col(|ui| {
                                 ui.push_id("MergeSecondDevice", |ui| {
-                                    devices_info_map.physical_devices_widget(ui, src_1_device);
+                                    src_1.device_widget(ui, devices_info_map);
                                 });
                             });
                         });
@@ -497,8 +860,7 @@ impl RerouteRebind {
                             });
                             row.col(|ui| {
                                 ui.push_id("FromAxisSecond", |ui| {
-                                    let max = devices_info_map.get_physical_limits(src_1_device).1;
-                                    src_1_axis.id_dropdown_widget(max, ui);
+                                    src_1.axis_widget(ui, devices_info_map);
                                 });
                             });
                         });
@@ -524,8 +886,7 @@ impl RerouteRebind {
                             });
                             row.col(|ui| {
                                 ui.push_id("ToAxis", |ui| {
-                                    let max = devices_info_map.get_virtual_limits(dst_device).1;
-                                    dst_axis.id_dropdown_widget(max, ui);
+                                    devices_info_map.virtual_axis_widget(ui, dst_device, dst_axis);
                                 });
                             });
                         });
@@ -542,81 +903,818 @@ impl RerouteRebind {
                         });
                     });
 
+                modifier.variant_description_label(ui);
                 modifier.widget(ui);
-            }
-        });
-    }
-
-    pub fn process(
-        &mut self,
-        physical_devices: &[PhysicalDevice],
-        virtual_devices: &mut [VirtualDevice],
-        time: f64,
-        delta_t: f64,
-    ) -> Result<(), Error> {
-        match self {
-            RerouteRebind::ButtonToButton {
-                src_device,
-                src_button,
-                dst_device,
-                dst_button,
-                modifier,
-            } => {
-                let input =
-                    validate_value_physical_button(physical_devices, src_device, src_button)?;
-                let output =
-                    validate_handle_virtual_button(virtual_devices, dst_device, dst_button)?;
-                let modified_state = apply_button_modifier(input, output, modifier, time);
-                output.set(modified_state);
+                modifier.output_range().widget(
+                    ui,
+                    "Output range:",
+                    &devices_info_map.output_range,
+                );
             }
 
-            RerouteRebind::HatToHat {
-                src_device,
-                src_hat,
-                dst_device,
-                dst_hat,
-                modifier,
+            RerouteRebind::MixToTwoAxes {
+                src_a,
+                src_b,
+                dst_1_device,
+                dst_1_axis,
+                dst_2_device,
+                dst_2_axis,
+                mix,
             } => {
-                let input = validate_value_physical_hat(physical_devices, src_device, src_hat)?;
-                let output = validate_handle_virtual_hat(virtual_devices, dst_device, dst_hat)?;
-                let modified_state = apply_hat_modifier(input, output, modifier);
-                let converted_state = convert_hat_type_to_vjoy(output.get(), modified_state);
-                output.set(converted_state);
-            }
-
+                rebind_table(ui)
+                    .column(Column::remainder())
+                    .body(|mut body| {
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("From").strong());
+                            });
+                        });
+                        for (src, name) in [(src_a, "A"), (src_b, "B")] {
+                            body.row(TABLE_ROW_HEIGHT, |mut row| {
+                                row.col(|ui| {
+                                    ui.label(format!("Source {name}:"));
+                                });
+                                row.col(|ui| {
+                                    ui.push_id(("MixSource", name), |ui| {
+                                        src.variant_dropdown_widget(ui);
+                                    });
+                                });
+                            });
+                            body.row(TABLE_ROW_HEIGHT, |mut row| {
+                                row.col(|ui| {
+                                    ui.label(format!("Device {name}:"));
+                                });
+                                row.col(|ui| {
+                                    ui.push_id(("MixDevice", name), |ui| {
+                                        src.device_widget(ui, devices_info_map);
+                                    });
+                                });
+                            });
+                            body.row(TABLE_ROW_HEIGHT, |mut row| {
+                                row.col(|ui| {
+                                    ui.label(format!("Axis {name}:"));
+                                });
+                                row.col(|ui| {
+                                    ui.push_id(("MixAxis", name), |ui| {
+                                        src.axis_widget(ui, devices_info_map);
+                                    });
+                                });
+                            });
+                        }
+                        body.row(SECTION_SPACING, |mut row| {
+                            row.col(|_| {});
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("To").strong());
+                            });
+                        });
+                        for (dst_device, dst_axis, name) in [
+                            (dst_1_device, dst_1_axis, "1"),
+                            (dst_2_device, dst_2_axis, "2"),
+                        ] {
+                            body.row(TABLE_ROW_HEIGHT, |mut row| {
+                                row.col(|ui| {
+                                    ui.label(format!("Device {name}:"));
+                                });
+                                row.col(|ui| {
+                                    ui.push_id(("MixToDevice", name), |ui| {
+                                        devices_info_map.virtual_devices_widget(ui, dst_device);
+                                    });
+                                });
+                            });
+                            body.row(TABLE_ROW_HEIGHT, |mut row| {
+                                row.col(|ui| {
+                                    ui.label(format!("Axis {name}:"));
+                                });
+                                row.col(|ui| {
+                                    ui.push_id(("MixToAxis", name), |ui| {
+                                        devices_info_map
+                                            .virtual_axis_widget(ui, dst_device, dst_axis);
+                                    });
+                                });
+                            });
+                        }
+                    });
+
+                mix.widget(ui);
+                let [range_1, range_2] = mix.output_ranges();
+                range_1.widget(ui, "Output 1 range:", &devices_info_map.output_range);
+                range_2.widget(ui, "Output 2 range:", &devices_info_map.output_range);
+            }
+
+            RerouteRebind::MultiAction {
+                src_device,
+                src_button,
+                actions,
+                ..
+            } => {
+                rebind_table(ui)
+                    .column(Column::remainder())
+                    .body(|mut body| {
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("From").strong());
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Device:");
+                            });
+                            row.col(|ui| {
+                                devices_info_map.physical_devices_widget(ui, src_device);
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Button:");
+                            });
+                            row.col(|ui| {
+                                ui.push_id("FromButton", |ui| {
+                                    devices_info_map
+                                        .physical_button_widget(ui, src_device, src_button);
+                                });
+                            });
+                        });
+                        body.row(SECTION_SPACING, |mut row| {
+                            row.col(|_| {});
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("Steps").strong());
+                            });
+                        });
+                    });
+
+                multi_action_steps_widget(ui, actions, devices_info_map);
+            }
+
+            RerouteRebind::ActivityToButton {
+                src_device,
+                timeout_s,
+                dst_device,
+                dst_button,
+            } => {
+                rebind_table(ui)
+                    .column(Column::remainder())
+                    .body(|mut body| {
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("From").strong());
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Device:");
+                            });
+                            row.col(|ui| {
+                                devices_info_map.physical_devices_widget(ui, src_device);
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Timeout:");
+                            });
+                            row.col(|ui| {
+                                ui.add(
+                                    Slider::new(timeout_s, 0.0..=600.0)
+                                        .logarithmic(true)
+                                        .suffix(" s"),
+                                );
+                            });
+                        });
+                        body.row(SECTION_SPACING, |mut row| {
+                            row.col(|_| {});
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label(RichText::new("To").strong());
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Device:");
+                            });
+                            row.col(|ui| {
+                                devices_info_map.virtual_devices_widget(ui, dst_device);
+                            });
+                        });
+                        body.row(TABLE_ROW_HEIGHT, |mut row| {
+                            row.col(|ui| {
+                                ui.label("Button:");
+                            });
+                            row.col(|ui| {
+                                ui.push_id("ToButton", |ui| {
+                                    devices_info_map
+                                        .virtual_button_widget(ui, dst_device, dst_button);
+                                });
+                            });
+                        });
+                    });
+            }
+        });
+    }
+
+    /// Every control the rebind can write. Validation, the dependency order and the capability
+    /// checks work on the config, so they see the targets of a macro even while it is idle.
+    pub fn virtual_writes(&self) -> Vec<VirtualControl> {
+        match self {
+            RerouteRebind::ButtonToButton {
+                dst_device,
+                dst_button,
+                ..
+            }
+            | RerouteRebind::ActivityToButton {
+                dst_device,
+                dst_button,
+                ..
+            } => vec![VirtualControl::Button {
+                device: *dst_device,
+                button: *dst_button,
+            }],
+            RerouteRebind::HatToHat {
+                dst_device,
+                dst_hat,
+                ..
+            }
+            | RerouteRebind::FourButtonsToHat {
+                dst_device,
+                dst_hat,
+                ..
+            } => vec![VirtualControl::Hat {
+                device: *dst_device,
+                hat: *dst_hat,
+            }],
             RerouteRebind::AxisToAxis {
+                dst_device,
+                dst_axis,
+                ..
+            }
+            | RerouteRebind::MergeAxes {
+                dst_device,
+                dst_axis,
+                ..
+            }
+            | RerouteRebind::TwoButtonsToAxis {
+                dst_device,
+                dst_axis,
+                ..
+            } => vec![VirtualControl::Axis {
+                device: *dst_device,
+                axis: *dst_axis,
+            }],
+            RerouteRebind::MixToTwoAxes {
+                dst_1_device,
+                dst_1_axis,
+                dst_2_device,
+                dst_2_axis,
+                ..
+            } => vec![
+                VirtualControl::Axis {
+                    device: *dst_1_device,
+                    axis: *dst_1_axis,
+                },
+                VirtualControl::Axis {
+                    device: *dst_2_device,
+                    axis: *dst_2_axis,
+                },
+            ],
+            RerouteRebind::AxisToButton {
+                dst_device,
+                dst_button,
+                modifier,
+                ..
+            } => [Some(*dst_button), modifier.high_button()]
+                .into_iter()
+                .flatten()
+                .map(|button| VirtualControl::Button {
+                    device: *dst_device,
+                    button,
+                })
+                .collect(),
+            RerouteRebind::MultiAction { actions, .. } => actions
+                .iter()
+                .filter_map(|action| action.virtual_write())
+                .collect(),
+        }
+    }
+
+    /// Controls the rebind owns after processing a tick. An idle macro claims none of its targets,
+    /// so the mirror and smoothing treat them as unwritten.
+    pub fn claimed_writes(&self) -> Vec<VirtualControl> {
+        match self {
+            RerouteRebind::MultiAction { state, .. } if !state.is_running() => Vec::new(),
+            _ => self.virtual_writes(),
+        }
+    }
+
+    pub fn virtual_reads(&self) -> Vec<VirtualControl> {
+        match self {
+            RerouteRebind::MergeAxes { src_0, src_1, .. }
+            | RerouteRebind::MixToTwoAxes {
+                src_a: src_0,
+                src_b: src_1,
+                ..
+            } => [src_0, src_1]
+                .iter()
+                .filter_map(|s| s.virtual_read())
+                .collect(),
+            RerouteRebind::ButtonToButton { src, .. } => src.virtual_read().into_iter().collect(),
+            RerouteRebind::AxisToAxis { src, gain, .. } => src
+                .virtual_read()
+                .into_iter()
+                .chain(gain.as_ref().and_then(|gain| gain.button.virtual_read()))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn debug_state(&self) -> Vec<(String, String)> {
+        match self {
+            RerouteRebind::ButtonToButton { modifier, .. } => modifier.debug_state(),
+            RerouteRebind::TwoButtonsToAxis { modifier, .. } => modifier.debug_state(),
+            RerouteRebind::AxisToButton { modifier, .. } => modifier.debug_state(),
+            RerouteRebind::HatToHat { modifier, .. } => modifier.debug_state(),
+            RerouteRebind::AxisToAxis {
+                modifier,
+                freeze,
+                gain,
+                ..
+            } => {
+                let mut state = modifier.debug_state();
+                if let Some(freeze) = freeze {
+                    state.extend(freeze.debug_state());
+                }
+                if let Some(gain) = gain {
+                    state.extend(gain.debug_state());
+                }
+                state
+            }
+            RerouteRebind::MultiAction { state, .. } => state.debug_state(),
+            RerouteRebind::FourButtonsToHat { .. }
+            | RerouteRebind::MergeAxes { .. }
+            | RerouteRebind::MixToTwoAxes { .. }
+            | RerouteRebind::ActivityToButton { .. } => Vec::new(),
+        }
+    }
+
+    /// A running macro carries on with the edited steps and ends if its next step was removed
+    pub fn take_runtime_state(&mut self, from: &mut Self) {
+        match (self, from) {
+            (
+                RerouteRebind::ButtonToButton { modifier, .. },
+                RerouteRebind::ButtonToButton { modifier: from, .. },
+            ) => modifier.take_runtime_state(from),
+            (
+                RerouteRebind::TwoButtonsToAxis { modifier, .. },
+                RerouteRebind::TwoButtonsToAxis { modifier: from, .. },
+            ) => modifier.take_runtime_state(from),
+            (
+                RerouteRebind::AxisToButton { modifier, .. },
+                RerouteRebind::AxisToButton { modifier: from, .. },
+            ) => modifier.take_runtime_state(from),
+            (
+                RerouteRebind::HatToHat { modifier, .. },
+                RerouteRebind::HatToHat { modifier: from, .. },
+            ) => modifier.take_runtime_state(from),
+            (
+                RerouteRebind::AxisToAxis {
+                    modifier,
+                    freeze,
+                    gain,
+                    ..
+                },
+                RerouteRebind::AxisToAxis {
+                    modifier: from_modifier,
+                    freeze: from_freeze,
+                    gain: from_gain,
+                    ..
+                },
+            ) => {
+                modifier.take_runtime_state(from_modifier);
+                if let (Some(freeze), Some(from)) = (freeze, from_freeze) {
+                    freeze.take_runtime_state(from);
+                }
+                if let (Some(gain), Some(from)) = (gain, from_gain) {
+                    gain.take_runtime_state(from);
+                }
+            }
+            (
+                RerouteRebind::MultiAction { state, .. },
+                RerouteRebind::MultiAction { state: from, .. },
+            ) => *state = std::mem::take(from),
+            _ => {}
+        }
+    }
+
+    /// GUIDs of the physical devices the rebind reads
+    pub fn physical_devices_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            RerouteRebind::ButtonToButton { src, .. } => {
+                src.physical_device_mut().into_iter().collect()
+            }
+            RerouteRebind::HatToHat { src_device, .. }
+            | RerouteRebind::FourButtonsToHat { src_device, .. }
+            | RerouteRebind::AxisToButton { src_device, .. }
+            | RerouteRebind::MultiAction { src_device, .. }
+            | RerouteRebind::ActivityToButton { src_device, .. } => vec![src_device],
+            RerouteRebind::TwoButtonsToAxis {
+                src_neg_device,
+                src_pos_device,
+                ..
+            } => vec![src_neg_device, src_pos_device],
+            RerouteRebind::AxisToAxis {
+                src, freeze, gain, ..
+            } => {
+                let mut devices: Vec<_> = src.physical_device_mut().into_iter().collect();
+                devices.extend(freeze.as_mut().map(|freeze| &mut freeze.device));
+                devices.extend(
+                    gain.as_mut()
+                        .and_then(|gain| gain.button.physical_device_mut()),
+                );
+                devices
+            }
+            RerouteRebind::MergeAxes { src_0, src_1, .. }
+            | RerouteRebind::MixToTwoAxes {
+                src_a: src_0,
+                src_b: src_1,
+                ..
+            } => [src_0, src_1]
+                .into_iter()
+                .filter_map(|s| s.physical_device_mut())
+                .collect(),
+        }
+    }
+
+    /// Controls of physical devices the rebind reads
+    pub fn physical_reads(&self) -> Vec<PhysicalRead<'_>> {
+        match self {
+            RerouteRebind::ButtonToButton { src, .. } => src.physical_read().into_iter().collect(),
+            RerouteRebind::TwoButtonsToAxis {
+                src_neg_device,
+                src_neg_button,
+                src_pos_device,
+                src_pos_button,
+                ..
+            } => vec![
+                PhysicalRead::new(src_neg_device, ControlKind::Button, *src_neg_button),
+                PhysicalRead::new(src_pos_device, ControlKind::Button, *src_pos_button),
+            ],
+            RerouteRebind::HatToHat {
+                src_device,
+                src_hat,
+                ..
+            } => vec![PhysicalRead::new(src_device, ControlKind::Hat, *src_hat)],
+            RerouteRebind::FourButtonsToHat {
+                src_device,
+                src_button_north,
+                src_button_east,
+                src_button_south,
+                src_button_west,
+                ..
+            } => [
+                src_button_north,
+                src_button_east,
+                src_button_south,
+                src_button_west,
+            ]
+            .into_iter()
+            .map(|button| PhysicalRead::new(src_device, ControlKind::Button, *button))
+            .collect(),
+            RerouteRebind::AxisToButton {
+                src_device,
+                src_axis,
+                ..
+            } => vec![PhysicalRead::new(src_device, ControlKind::Axis, *src_axis)],
+            RerouteRebind::AxisToAxis {
+                src, freeze, gain, ..
+            } => {
+                let mut reads: Vec<_> = src.physical_read().into_iter().collect();
+                reads.extend(
+                    freeze
+                        .as_ref()
+                        .filter(|freeze| freeze.is_set())
+                        .map(|freeze| {
+                            PhysicalRead::new(&freeze.device, ControlKind::Button, freeze.button)
+                        }),
+                );
+                reads.extend(gain.as_ref().and_then(|gain| gain.button.physical_read()));
+                reads
+            }
+            RerouteRebind::MergeAxes { src_0, src_1, .. }
+            | RerouteRebind::MixToTwoAxes {
+                src_a: src_0,
+                src_b: src_1,
+                ..
+            } => [src_0, src_1]
+                .into_iter()
+                .filter_map(|s| s.physical_read())
+                .collect(),
+            RerouteRebind::MultiAction {
+                src_device,
+                src_button,
+                ..
+            } => vec![PhysicalRead::new(
+                src_device,
+                ControlKind::Button,
+                *src_button,
+            )],
+            // Any activity of the device counts, no single control is read
+            RerouteRebind::ActivityToButton { .. } => Vec::new(),
+        }
+    }
+
+    /// Called instead of [`Self::process`] while the rebind is not active in the current shift mode.
+    pub fn deactivate(&mut self, virtual_devices: &mut [VirtualDevice]) {
+        match self {
+            RerouteRebind::MultiAction { state, .. } => {
+                cancel_multi_action(state, virtual_devices);
+            }
+            RerouteRebind::AxisToAxis { freeze, gain, .. } => {
+                if let Some(freeze) = freeze {
+                    freeze.reset();
+                }
+                if let Some(gain) = gain {
+                    gain.reset();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Resolves the device slots of all sources and the destination of this rebind. The offsets
+    /// of the source controls are resolved on their first read.
+    pub fn resolve(
+        &self,
+        resolved: &mut ResolvedSlots,
+        physical_devices: &[PhysicalDevice],
+        virtual_devices: &mut [VirtualDevice],
+    ) {
+        match self {
+            RerouteRebind::ButtonToButton {
+                src, dst_device, ..
+            } => {
+                if let Some(device) = src.physical_device() {
+                    resolved.physical(0, physical_devices, device);
+                }
+                resolved.virtual_dst(virtual_devices, *dst_device);
+            }
+
+            RerouteRebind::HatToHat {
+                src_device,
+                dst_device,
+                ..
+            }
+            | RerouteRebind::AxisToButton {
+                src_device,
+                dst_device,
+                ..
+            }
+            | RerouteRebind::ActivityToButton {
+                src_device,
+                dst_device,
+                ..
+            } => {
+                resolved.physical(0, physical_devices, src_device);
+                resolved.virtual_dst(virtual_devices, *dst_device);
+            }
+
+            RerouteRebind::FourButtonsToHat {
+                src_device,
+                dst_device,
+                ..
+            } => {
+                for index in 0..4 {
+                    resolved.physical(index, physical_devices, src_device);
+                }
+                resolved.virtual_dst(virtual_devices, *dst_device);
+            }
+
+            RerouteRebind::AxisToAxis {
+                src,
+                dst_device,
+                freeze,
+                gain,
+                ..
+            } => {
+                if let Some(device) = src.physical_device() {
+                    resolved.physical(0, physical_devices, device);
+                }
+                if let Some(freeze) = freeze.as_ref().filter(|freeze| freeze.is_set()) {
+                    resolved.physical(1, physical_devices, &freeze.device);
+                }
+                if let Some(device) = gain.as_ref().and_then(|g| g.button.physical_device()) {
+                    resolved.physical(2, physical_devices, device);
+                }
+                resolved.virtual_dst(virtual_devices, *dst_device);
+            }
+
+            RerouteRebind::MergeAxes {
+                src_0,
+                src_1,
+                dst_device,
+                ..
+            }
+            | RerouteRebind::MixToTwoAxes {
+                src_a: src_0,
+                src_b: src_1,
+                dst_1_device: dst_device,
+                ..
+            } => {
+                for (index, src) in [src_0, src_1].into_iter().enumerate() {
+                    if let Some(device) = src.physical_device() {
+                        resolved.physical(index, physical_devices, device);
+                    }
+                }
+                resolved.virtual_dst(virtual_devices, *dst_device);
+            }
+
+            RerouteRebind::TwoButtonsToAxis {
+                src_neg_device,
+                src_pos_device,
+                dst_device,
+                ..
+            } => {
+                resolved.physical(0, physical_devices, src_neg_device);
+                resolved.physical(1, physical_devices, src_pos_device);
+                resolved.virtual_dst(virtual_devices, *dst_device);
+            }
+
+            RerouteRebind::MultiAction { src_device, .. } => {
+                resolved.physical(0, physical_devices, src_device);
+            }
+        }
+    }
+
+    pub fn process(
+        &mut self,
+        physical_devices: &[PhysicalDevice],
+        virtual_devices: &mut [VirtualDevice],
+        resolved: &mut ResolvedSlots,
+        ctx: TickContext,
+    ) -> Result<(), Error> {
+        match self {
+            RerouteRebind::ButtonToButton {
+                src,
+                dst_device,
+                dst_button,
+                modifier,
+            } => {
+                let input = src.pressed(resolved, 0, physical_devices, virtual_devices)?;
+                let dst_devices = resolved.virtual_dst(virtual_devices, *dst_device);
+                let output = validate_handle_virtual_button(dst_devices, dst_device, dst_button)?;
+                let modified_state = apply_button_modifier(input, output, modifier, ctx);
+                output.set(modified_state);
+            }
+
+            RerouteRebind::HatToHat {
+                src_device,
+                src_hat,
+                dst_device,
+                dst_hat,
+                modifier,
+            } => {
+                let input = resolved.physical_hat(0, physical_devices, src_device, src_hat)?;
+                let dst_devices = resolved.virtual_dst(virtual_devices, *dst_device);
+                let output = validate_handle_virtual_hat(dst_devices, dst_device, dst_hat)?;
+                let modified_state = apply_hat_modifier(input, output, modifier);
+                let converted_state = convert_hat_type_to_vjoy(output.get(), modified_state);
+                output.set(converted_state);
+            }
+
+            RerouteRebind::FourButtonsToHat {
+                src_device,
+                src_button_north,
+                src_button_east,
+                src_button_south,
+                src_button_west,
+                dst_device,
+                dst_hat,
+                modifier,
+            } => {
+                let mut input = [false; 4];
+                for (index, (held, button)) in input
+                    .iter_mut()
+                    .zip([
+                        src_button_north,
+                        src_button_east,
+                        src_button_south,
+                        src_button_west,
+                    ])
+                    .enumerate()
+                {
+                    *held =
+                        resolved.physical_button(index, physical_devices, src_device, button)?;
+                }
+                let dst_devices = resolved.virtual_dst(virtual_devices, *dst_device);
+                let output = validate_handle_virtual_hat(dst_devices, dst_device, dst_hat)?;
+                let modified_state = apply_four_buttons_to_hat_modifier(input, output, modifier);
+                let converted_state = convert_hat_type_to_vjoy(output.get(), modified_state);
+                output.set(converted_state);
+            }
+
+            RerouteRebind::AxisToButton {
                 src_device,
                 src_axis,
                 dst_device,
+                dst_button,
+                modifier,
+            } => {
+                let input = resolved.physical_axis(0, physical_devices, src_device, src_axis)?;
+                let (pressed, high_pressed) = apply_axis_to_button_modifier(input, modifier);
+                let dst_devices = resolved.virtual_dst(virtual_devices, *dst_device);
+                let output = validate_handle_virtual_button(dst_devices, dst_device, dst_button)?;
+                output.set(match pressed {
+                    true => ButtonState::Pressed,
+                    false => ButtonState::Released,
+                });
+                if let (Some(high_button), Some(high_pressed)) =
+                    (modifier.high_button(), high_pressed)
+                {
+                    let output =
+                        validate_handle_virtual_button(dst_devices, dst_device, &high_button)?;
+                    output.set(match high_pressed {
+                        true => ButtonState::Pressed,
+                        false => ButtonState::Released,
+                    });
+                }
+            }
+
+            RerouteRebind::AxisToAxis {
+                src,
+                dst_device,
                 dst_axis,
                 modifier,
+                freeze,
+                gain,
             } => {
-                let input = validate_value_physical_axis(physical_devices, src_device, src_axis)?;
-                let output = validate_handle_virtual_axis(virtual_devices, dst_device, dst_axis)?;
-                let modified_state = apply_axis_modifier(input, output, modifier);
+                let input = src.value(resolved, 0, physical_devices, virtual_devices)?;
+                let held_for_gain = match gain {
+                    Some(gain) => {
+                        Some(
+                            gain.button
+                                .pressed(resolved, 2, physical_devices, virtual_devices)?,
+                        )
+                    }
+                    None => None,
+                };
+                let dst_devices = resolved.virtual_dst(virtual_devices, *dst_device);
+                let output = validate_handle_virtual_axis(dst_devices, dst_device, dst_axis)?;
+                let mut modified_state = apply_axis_modifier(input, output, modifier);
+                if let (Some(gain), Some(held)) = (gain, held_for_gain) {
+                    modified_state = gain.apply(held, modified_state, ctx);
+                }
+                if let Some(freeze) = freeze.as_mut().filter(|freeze| freeze.is_set()) {
+                    let held = resolved.physical_button(
+                        1,
+                        physical_devices,
+                        &freeze.device,
+                        &freeze.button,
+                    )?;
+                    modified_state = freeze.apply(held, modified_state, ctx);
+                }
                 let converted_state = convert_axis_to_vjoy_range(modified_state);
                 output.set(converted_state);
             }
 
             RerouteRebind::MergeAxes {
-                src_0_device,
-                src_0_axis,
-                src_1_device,
-                src_1_axis,
+                src_0,
+                src_1,
                 dst_device,
                 dst_axis,
                 modifier,
             } => {
-                let input_0 =
-                    validate_value_physical_axis(physical_devices, src_0_device, src_0_axis)?;
-                let input_1 =
-                    validate_value_physical_axis(physical_devices, src_1_device, src_1_axis)?;
-                let output = validate_handle_virtual_axis(virtual_devices, dst_device, dst_axis)?;
+                let input_0 = src_0.value(resolved, 0, physical_devices, virtual_devices)?;
+                let input_1 = src_1.value(resolved, 1, physical_devices, virtual_devices)?;
+                let dst_devices = resolved.virtual_dst(virtual_devices, *dst_device);
+                let output = validate_handle_virtual_axis(dst_devices, dst_device, dst_axis)?;
                 let modified_state = apply_merge_axes_modifier(input_0, input_1, modifier);
                 let converted_state = convert_axis_to_vjoy_range(modified_state);
                 output.set(converted_state);
             }
 
+            RerouteRebind::MixToTwoAxes {
+                src_a,
+                src_b,
+                dst_1_device,
+                dst_1_axis,
+                dst_2_device,
+                dst_2_axis,
+                mix,
+            } => {
+                let input_a = src_a.value(resolved, 0, physical_devices, virtual_devices)?;
+                let input_b = src_b.value(resolved, 1, physical_devices, virtual_devices)?;
+                let [state_1, state_2] = mix.apply(input_a, input_b);
+
+                // Only the first destination has a cached slot, the second one is looked up
+                let dst_devices = resolved.virtual_dst(virtual_devices, *dst_1_device);
+                let output = validate_handle_virtual_axis(dst_devices, dst_1_device, dst_1_axis)?;
+                output.set(convert_axis_to_vjoy_range(state_1));
+                let output =
+                    validate_handle_virtual_axis(virtual_devices, dst_2_device, dst_2_axis)?;
+                output.set(convert_axis_to_vjoy_range(state_2));
+            }
+
             RerouteRebind::TwoButtonsToAxis {
                 src_neg_device,
                 src_neg_button,
@@ -626,22 +1724,55 @@ impl RerouteRebind {
                 dst_axis,
                 modifier,
             } => {
-                let input_neg = validate_value_physical_button(
+                let input_neg = resolved.physical_button(
+                    0,
                     physical_devices,
                     src_neg_device,
                     src_neg_button,
                 )?;
-                let input_pos = validate_value_physical_button(
+                let input_pos = resolved.physical_button(
+                    1,
                     physical_devices,
                     src_pos_device,
                     src_pos_button,
                 )?;
-                let output = validate_handle_virtual_axis(virtual_devices, dst_device, dst_axis)?;
-                let modified_state = apply_two_buttons_to_axis_modifier(
-                    input_neg, input_pos, output, modifier, delta_t,
-                );
+                let dst_devices = resolved.virtual_dst(virtual_devices, *dst_device);
+                let output = validate_handle_virtual_axis(dst_devices, dst_device, dst_axis)?;
+                let modified_state =
+                    apply_two_buttons_to_axis_modifier(input_neg, input_pos, output, modifier, ctx);
                 output.set(modified_state);
             }
+
+            RerouteRebind::MultiAction {
+                src_device,
+                src_button,
+                actions,
+                state,
+            } => {
+                let input =
+                    resolved.physical_button(0, physical_devices, src_device, src_button)?;
+                process_multi_action(input, actions, state, virtual_devices, ctx)?;
+            }
+
+            RerouteRebind::ActivityToButton {
+                src_device,
+                timeout_s,
+                dst_device,
+                dst_button,
+            } => {
+                let src_devices = resolved.physical(0, physical_devices, src_device);
+                let dst_devices = resolved.virtual_dst(virtual_devices, *dst_device);
+                let input = validate_physical_device(src_devices, src_device)?;
+                let output = validate_handle_virtual_button(dst_devices, dst_device, dst_button)?;
+                let active = input
+                    .input_state
+                    .activity()
+                    .is_active_within(*timeout_s, ctx.time);
+                output.set(match active {
+                    true => ButtonState::Pressed,
+                    false => ButtonState::Released,
+                });
+            }
         }
 
         Ok(())
@@ -0,0 +1,241 @@
+use super::{
+    validate_value_physical_axis, validate_value_physical_button, validate_value_physical_hat,
+};
+use crate::{
+    error::Error,
+    input::{PhysicalDevice, VirtualDevice},
+};
+
+/// Cached device slots and control offsets of a reroute rebind.
+///
+/// Slots narrow the device slices handed to the validate helpers down to a single device, so
+/// the per-tick lookup no longer scans every connected device. Once a physical control was read
+/// through the validate helpers, its offset is cached too and later ticks index the controls of
+/// the device directly. A slot that no longer holds the referenced device (hot-plug, edits in the
+/// UI) falls back to the full lookup and is re-resolved.
+///
+/// The cache is no setting, [`Rebind`](super::Rebind) equality leaves it out.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ResolvedSlots {
+    generation: u64,
+    /// By source: the sources, then the hold buttons of e.g. an axis freeze or gain
+    physical: [Option<PhysicalSlot>; 4],
+    virtual_dst: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PhysicalSlot {
+    device: usize,
+    /// Offset of the control last read successfully, within the control count of the device
+    control: Option<usize>,
+}
+
+impl ResolvedSlots {
+    /// Clears all slots that were resolved for an older device list.
+    pub fn sync(&mut self, generation: u64) {
+        if self.generation != generation {
+            *self = Self {
+                generation,
+                ..Default::default()
+            };
+        }
+    }
+
+    /// Physical devices to search for `guid`, a single device if the slot is resolved.
+    pub fn physical<'a>(
+        &mut self,
+        index: usize,
+        devices: &'a [PhysicalDevice],
+        guid: &str,
+    ) -> &'a [PhysicalDevice] {
+        let slot = self.physical[index]
+            .filter(|slot| matches!(devices.get(slot.device), Some(d) if d.guid == guid))
+            .or_else(|| {
+                let device = devices.iter().position(|d| d.guid == guid)?;
+                Some(PhysicalSlot {
+                    device,
+                    control: None,
+                })
+            });
+        self.physical[index] = slot;
+
+        match slot {
+            Some(slot) => &devices[slot.device..=slot.device],
+            None => devices,
+        }
+    }
+
+    pub fn physical_button(
+        &mut self,
+        index: usize,
+        devices: &[PhysicalDevice],
+        guid: &String,
+        button: &u32,
+    ) -> Result<bool, Error> {
+        self.physical_control(
+            index,
+            devices,
+            guid,
+            button,
+            |device| device.input_state.buttons().as_slice(),
+            validate_value_physical_button,
+        )
+    }
+
+    pub fn physical_axis(
+        &mut self,
+        index: usize,
+        devices: &[PhysicalDevice],
+        guid: &String,
+        axis: &u32,
+    ) -> Result<i32, Error> {
+        self.physical_control(
+            index,
+            devices,
+            guid,
+            axis,
+            |device| device.input_state.axes().as_slice(),
+            validate_value_physical_axis,
+        )
+    }
+
+    pub fn physical_hat(
+        &mut self,
+        index: usize,
+        devices: &[PhysicalDevice],
+        guid: &String,
+        hat: &u32,
+    ) -> Result<i32, Error> {
+        self.physical_control(
+            index,
+            devices,
+            guid,
+            hat,
+            |device| device.input_state.hats().as_slice(),
+            validate_value_physical_hat,
+        )
+    }
+
+    /// Control `id` of device `guid`, read at the cached offset if it is still the same control.
+    /// Otherwise `validate` reads it and caches the offset on success.
+    fn physical_control<T: Copy>(
+        &mut self,
+        index: usize,
+        devices: &[PhysicalDevice],
+        guid: &String,
+        id: &u32,
+        controls: fn(&PhysicalDevice) -> &[T],
+        validate: fn(&[PhysicalDevice], &String, &u32) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let devices = self.physical(index, devices, guid);
+        let offset = id.checked_sub(1).map(|offset| offset as usize);
+        if let (
+            Some(PhysicalSlot {
+                control: Some(control),
+                ..
+            }),
+            [device],
+        ) = (self.physical[index], devices)
+        {
+            if Some(control) == offset && device.connected {
+                if let Some(value) = controls(device).get(control) {
+                    return Ok(*value);
+                }
+            }
+        }
+
+        let value = validate(devices, guid, id)?;
+        if let Some(slot) = &mut self.physical[index] {
+            slot.control = offset;
+        }
+        Ok(value)
+    }
+
+    /// Virtual devices to search for `id`, a single device if the slot is resolved.
+    pub fn virtual_dst<'a>(
+        &mut self,
+        devices: &'a mut [VirtualDevice],
+        id: u32,
+    ) -> &'a mut [VirtualDevice] {
+        let slot = self
+            .virtual_dst
+            .filter(|slot| matches!(devices.get(*slot), Some(d) if d.id == id))
+            .or_else(|| devices.iter().position(|d| d.id == id));
+        self.virtual_dst = slot;
+
+        match slot {
+            Some(slot) => &mut devices[slot..=slot],
+            None => devices,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::Error,
+        input::{backend::fake::FakeJoystick, event_console::ControlKind, PhysicalDevice},
+        rebind::resolved_slots::ResolvedSlots,
+    };
+
+    fn device(guid: &str) -> PhysicalDevice {
+        PhysicalDevice::new(
+            guid.to_string(),
+            0,
+            Box::new(FakeJoystick::new(guid, 4, 2, 1)),
+        )
+    }
+
+    fn cached(slots: &ResolvedSlots, index: usize) -> Option<(usize, Option<usize>)> {
+        slots.physical[index].map(|slot| (slot.device, slot.control))
+    }
+
+    #[test]
+    fn slots_follow_hot_plugged_devices() {
+        let stick = "stick".to_string();
+        let mut devices = vec![device("stick"), device("pedals")];
+        devices[0].input_state.inject(ControlKind::Button, 2, 1);
+        let mut slots = ResolvedSlots::default();
+        slots.sync(1);
+
+        assert!(slots.physical_button(0, &devices, &stick, &2).unwrap());
+        assert_eq!(cached(&slots, 0), Some((0, Some(1))));
+
+        // A device plugged in before the stick moves it to another slot
+        devices.insert(0, device("throttle"));
+        assert!(slots.physical_button(0, &devices, &stick, &2).unwrap());
+        assert_eq!(cached(&slots, 0), Some((1, Some(1))));
+
+        // The cached offset is not read while the stick is unplugged
+        devices[1].connected = false;
+        assert!(matches!(
+            slots.physical_button(0, &devices, &stick, &2),
+            Err(Error::SourceDeviceDisconnected(_))
+        ));
+
+        // An edited id is checked again
+        devices[1].connected = true;
+        assert!(slots.physical_button(0, &devices, &stick, &9).is_err());
+        assert!(!slots.physical_button(0, &devices, &stick, &1).unwrap());
+        assert_eq!(cached(&slots, 0), Some((1, Some(0))));
+    }
+
+    #[test]
+    fn newer_generations_clear_the_slots() {
+        let stick = "stick".to_string();
+        let devices = vec![device("stick")];
+        let mut slots = ResolvedSlots::default();
+        slots.sync(1);
+        slots.physical_axis(0, &devices, &stick, &1).unwrap();
+        slots.physical_hat(1, &devices, &stick, &1).unwrap();
+
+        slots.sync(1);
+        assert_eq!(cached(&slots, 0), Some((0, Some(0))));
+        assert_eq!(cached(&slots, 1), Some((0, Some(0))));
+
+        slots.sync(2);
+        assert_eq!(slots.generation, 2);
+        assert_eq!(cached(&slots, 0), None);
+        assert_eq!(cached(&slots, 1), None);
+    }
+}
@@ -0,0 +1,114 @@
+use egui::Ui;
+
+use super::{rebind_viewer::DevicesInfoMap, RebindType};
+
+/// Unapplied copy of a rebind's settings, edited instead of the live rebind while safe edit is
+/// enabled. The live rebind keeps processing with its old settings until the copy is applied, so
+/// dragging a slider through extreme values never reaches the output.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct PendingEdit(Option<Box<RebindType>>);
+
+impl PendingEdit {
+    pub fn is_pending(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Widget of the pending copy. Without one, edits a scratch copy of `live` that becomes the
+    /// pending copy once it differs.
+    pub fn widget(
+        &mut self,
+        live: &RebindType,
+        ui: &mut Ui,
+        devices_name_map: &mut DevicesInfoMap,
+    ) {
+        match &mut self.0 {
+            Some(pending) => pending.widget(ui, devices_name_map),
+            None => {
+                // Cloned and compared within one frame, so the runtime state of both is the same
+                let mut scratch = live.clone();
+                scratch.widget(ui, devices_name_map);
+                if scratch != *live {
+                    self.0 = Some(Box::new(scratch));
+                }
+            }
+        }
+    }
+
+    /// Replaces the settings of `live` with the pending copy. The runtime state of the copy is from
+    /// when editing started, so `live` keeps its own. The UI runs between two processing ticks, so
+    /// no tick sees a partial edit.
+    pub fn apply(&mut self, live: &mut RebindType) {
+        if let Some(mut pending) = self.0.take() {
+            pending.take_runtime_state(live);
+            *live = *pending;
+        }
+    }
+
+    pub fn revert(&mut self) {
+        self.0 = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rebind::{
+        axis_gain::ButtonSource,
+        button_to_button::ButtonToButtonModifier,
+        merge_axes::{AxisSource, MergeAxesModifier},
+        reroute_rebind::RerouteRebind,
+        safe_edit::PendingEdit,
+        RebindType,
+    };
+
+    fn merge(dst_axis: u32) -> RebindType {
+        RebindType::Reroute {
+            rebind: RerouteRebind::MergeAxes {
+                src_0: AxisSource::Physical {
+                    device: "stick".to_string(),
+                    axis: 1,
+                },
+                src_1: AxisSource::Physical {
+                    device: "stick".to_string(),
+                    axis: 2,
+                },
+                dst_device: 1,
+                dst_axis,
+                modifier: MergeAxesModifier::Add,
+            },
+        }
+    }
+
+    #[test]
+    fn live_rebind_changes_only_on_apply() {
+        let mut live = merge(1);
+
+        let mut pending = PendingEdit(Some(Box::new(merge(2))));
+        pending.revert();
+        assert!(!pending.is_pending());
+        pending.apply(&mut live);
+        assert_eq!(live, merge(1));
+
+        let mut pending = PendingEdit(Some(Box::new(merge(2))));
+        assert_eq!(live, merge(1));
+        pending.apply(&mut live);
+        assert!(!pending.is_pending());
+        assert_eq!(live, merge(2));
+    }
+
+    #[test]
+    fn live_runtime_state_survives_apply() {
+        let toggle = |dst_button, last_input| RebindType::Reroute {
+            rebind: RerouteRebind::ButtonToButton {
+                src: ButtonSource::physical("stick".to_string(), 1),
+                dst_device: 1,
+                dst_button,
+                modifier: ButtonToButtonModifier::Toggle { last_input },
+            },
+        };
+        // Editing started while the button was released, it is held by now
+        let mut live = toggle(1, true);
+        let mut pending = PendingEdit(Some(Box::new(toggle(2, false))));
+        pending.apply(&mut live);
+        assert_eq!(live, toggle(2, true));
+    }
+}
@@ -0,0 +1,179 @@
+use super::{
+    axis_gain::ButtonSource, logical_rebind::LogicalRebind, reroute_rebind::RerouteRebind,
+    shift_mode_mask::ShiftModeMask, QuotedName, Rebind, RebindType,
+};
+
+/// A physical button that switches a shift mode and is also mapped by a reroute rebind active in
+/// both states of that mode, so every mode switch also triggers the mapping.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ShiftConflict {
+    pub shift_rebind: usize,
+    pub shift_name: String,
+    pub reroute_rebind: usize,
+    pub device: String,
+    pub button: u32,
+    /// Shift mode bits the button changes
+    pub shift_mask: ShiftModeMask,
+    /// The button clears the bits instead of setting them
+    pub disables: bool,
+}
+
+impl ShiftConflict {
+    pub fn message(&self) -> String {
+        format!(
+            "button {} of {} also switches the shift mode in rebind {}. Exclude that mode from this rebind or remove the duplicate mapping.",
+            self.button, self.device, QuotedName(&self.shift_name)
+        )
+    }
+
+    /// Limits the reroute rebind to the state of the shift mode with the button released
+    pub fn fix(&self, rebind: &mut Rebind) {
+        match self.disables {
+            true => rebind.mode_mask.0 |= self.shift_mask.0,
+            false => rebind.exclude_mask.0 |= self.shift_mask.0,
+        }
+    }
+}
+
+/// Button, mask and whether the button disables the mask, for momentary shift mode rebinds
+fn shift_button(rebind: &Rebind) -> Option<(&str, u32, ShiftModeMask, bool)> {
+    let RebindType::Logical { rebind } = &rebind.rebind_type else {
+        return None;
+    };
+    match rebind {
+        LogicalRebind::MomentaryEnableShiftMode {
+            src_device,
+            src_button,
+            shift_mask,
+        } => Some((src_device, *src_button, *shift_mask, false)),
+        LogicalRebind::MomentaryDisableShiftMode {
+            src_device,
+            src_button,
+            shift_mask,
+        } => Some((src_device, *src_button, *shift_mask, true)),
+        _ => None,
+    }
+}
+
+fn reroute_buttons(rebind: &Rebind) -> Vec<(&str, u32)> {
+    let RebindType::Reroute { rebind } = &rebind.rebind_type else {
+        return Vec::new();
+    };
+    match rebind {
+        RerouteRebind::ButtonToButton {
+            src: ButtonSource::Physical { device, button },
+            ..
+        } => vec![(device, *button)],
+        RerouteRebind::MultiAction {
+            src_device,
+            src_button,
+            ..
+        } => vec![(src_device, *src_button)],
+        RerouteRebind::TwoButtonsToAxis {
+            src_neg_device,
+            src_neg_button,
+            src_pos_device,
+            src_pos_button,
+            ..
+        } => vec![
+            (src_neg_device, *src_neg_button),
+            (src_pos_device, *src_pos_button),
+        ],
+        RerouteRebind::FourButtonsToHat {
+            src_device,
+            src_button_north,
+            src_button_east,
+            src_button_south,
+            src_button_west,
+            ..
+        } => [
+            src_button_north,
+            src_button_east,
+            src_button_south,
+            src_button_west,
+        ]
+        .into_iter()
+        .map(|button| (src_device.as_str(), *button))
+        .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Conflicts between shift mode rebinds and the reroute rebinds mapping the same button. A reroute
+/// rebind whose masks already depend on the shift mode bits is no conflict.
+pub fn shift_conflicts<'a>(rebinds: impl IntoIterator<Item = &'a Rebind>) -> Vec<ShiftConflict> {
+    let rebinds: Vec<&Rebind> = rebinds.into_iter().collect();
+    let mut conflicts = Vec::new();
+    for (shift_index, shift) in rebinds.iter().enumerate() {
+        let Some((device, button, shift_mask, disables)) = shift_button(shift) else {
+            continue;
+        };
+        if shift_mask.0 == 0 {
+            continue;
+        }
+        for (reroute_index, reroute) in rebinds.iter().enumerate() {
+            let gated = (reroute.mode_mask.0 | reroute.exclude_mask.0) & shift_mask.0 != 0;
+            if !gated && reroute_buttons(reroute).contains(&(device, button)) {
+                conflicts.push(ShiftConflict {
+                    shift_rebind: shift_index,
+                    shift_name: shift.name.clone(),
+                    reroute_rebind: reroute_index,
+                    device: device.to_string(),
+                    button,
+                    shift_mask,
+                    disables,
+                });
+            }
+        }
+    }
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rebind::{
+        axis_gain::ButtonSource, button_to_button::ButtonToButtonModifier,
+        logical_rebind::LogicalRebind, reroute_rebind::RerouteRebind,
+        shift_conflicts::shift_conflicts, shift_mode_mask::ShiftModeMask, Rebind, RebindType,
+    };
+
+    fn rebind(rebind_type: RebindType) -> Rebind {
+        Rebind::new(String::new(), Default::default(), rebind_type)
+    }
+
+    fn fire(src_button: u32) -> Rebind {
+        rebind(RebindType::Reroute {
+            rebind: RerouteRebind::ButtonToButton {
+                src: ButtonSource::physical("stick".to_string(), src_button),
+                dst_device: 1,
+                dst_button: 1,
+                modifier: ButtonToButtonModifier::Simple,
+            },
+        })
+    }
+
+    #[test]
+    fn fix_gates_the_reroute_rebind_on_the_shift_mode() {
+        let mut rebinds = vec![
+            rebind(RebindType::Logical {
+                rebind: LogicalRebind::MomentaryEnableShiftMode {
+                    src_device: "stick".to_string(),
+                    src_button: 1,
+                    shift_mask: ShiftModeMask(0b00000010),
+                },
+            }),
+            fire(1),
+            fire(2),
+        ];
+
+        let conflicts = shift_conflicts(&rebinds);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].reroute_rebind, 1);
+
+        conflicts[0].fix(&mut rebinds[1]);
+        assert_eq!(rebinds[1].exclude_mask, ShiftModeMask(0b00000010));
+        assert!(rebinds[1].is_active(ShiftModeMask(0)));
+        assert!(!rebinds[1].is_active(ShiftModeMask(0b00000010)));
+        assert!(shift_conflicts(&rebinds).is_empty());
+    }
+}
@@ -0,0 +1,132 @@
+use egui::{ComboBox, Ui};
+use serde::{Deserialize, Serialize};
+use strum::{EnumIter, IntoEnumIterator};
+
+use super::{
+    rebind_processor::write_neutral_control, reroute_rebind::RerouteRebind, VirtualControl,
+};
+use crate::input::VirtualDevice;
+
+/// Output of a reroute rebind while a physical device it reads is disconnected
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, EnumIter)]
+pub enum SourceLostPolicy {
+    /// Keep the last written output, e.g. a throttle stays where it was
+    FreezeOutput,
+    /// Release buttons, center hats and move axes to their neutral
+    NeutralOutput,
+}
+
+impl SourceLostPolicy {
+    /// Freeze for rebinds that only write axes, neutral for everything writing buttons or hats
+    pub fn default_for(rebind: &RerouteRebind) -> Self {
+        let writes = rebind.virtual_writes();
+        let axes_only = !writes.is_empty()
+            && writes
+                .iter()
+                .all(|control| matches!(control, VirtualControl::Axis { .. }));
+        match axes_only {
+            true => SourceLostPolicy::FreezeOutput,
+            false => SourceLostPolicy::NeutralOutput,
+        }
+    }
+
+    /// Applies the policy to the outputs of `rebind` in place of processing it
+    pub fn apply(self, rebind: &mut RerouteRebind, virtual_devices: &mut [VirtualDevice]) {
+        if self == SourceLostPolicy::NeutralOutput {
+            let writes = rebind.claimed_writes();
+            rebind.deactivate(virtual_devices);
+            for control in writes {
+                write_neutral_control(virtual_devices, control);
+            }
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SourceLostPolicy::FreezeOutput => "Freeze output",
+            SourceLostPolicy::NeutralOutput => "Neutral output",
+        }
+    }
+}
+
+/// Policy picker of the rebind editor, `None` follows the default of the rebind type
+pub fn source_lost_widget(
+    ui: &mut Ui,
+    policy: &mut Option<SourceLostPolicy>,
+    default: SourceLostPolicy,
+) {
+    let default_label = format!("Default ({})", default.label());
+    ComboBox::from_id_source("SourceLostPolicy")
+        .selected_text(policy.map_or(default_label.clone(), |p| p.label().to_string()))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(policy, None, default_label);
+            for option in SourceLostPolicy::iter() {
+                ui.selectable_value(policy, Some(option), option.label());
+            }
+        })
+        .response
+        .on_hover_text("Output while a source device of this rebind is disconnected");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::Config,
+        rebind::{
+            axis_gain::ButtonSource, merge_axes::AxisSource, reroute_rebind::RerouteRebind,
+            source_lost::SourceLostPolicy, RebindType,
+        },
+    };
+
+    #[test]
+    fn axes_freeze_and_buttons_go_neutral_by_default() {
+        let axis = RerouteRebind::AxisToAxis {
+            src: AxisSource::physical("stick".to_string(), 1),
+            dst_device: 1,
+            dst_axis: 1,
+            modifier: Default::default(),
+            freeze: None,
+            gain: None,
+        };
+        let button = RerouteRebind::ButtonToButton {
+            src: ButtonSource::physical("stick".to_string(), 1),
+            dst_device: 1,
+            dst_button: 1,
+            modifier: Default::default(),
+        };
+        let hat = RerouteRebind::HatToHat {
+            src_device: "stick".to_string(),
+            src_hat: 1,
+            dst_device: 1,
+            dst_hat: 1,
+            modifier: Default::default(),
+        };
+        assert_eq!(
+            SourceLostPolicy::default_for(&axis),
+            SourceLostPolicy::FreezeOutput
+        );
+        assert_eq!(
+            SourceLostPolicy::default_for(&button),
+            SourceLostPolicy::NeutralOutput
+        );
+        assert_eq!(
+            SourceLostPolicy::default_for(&hat),
+            SourceLostPolicy::NeutralOutput
+        );
+    }
+
+    #[test]
+    fn policy_is_only_written_when_set() {
+        let mut config = Config::debug_xbox360_config();
+        config
+            .rebinds
+            .retain(|r| matches!(r.rebind_type, RebindType::Reroute { .. }));
+        let toml = config.to_toml_string().unwrap();
+        assert!(!toml.contains("on_source_lost"));
+
+        config.rebinds[0].on_source_lost = Some(SourceLostPolicy::NeutralOutput);
+        let toml = config.to_toml_string().unwrap();
+        assert!(toml.contains("on_source_lost = \"NeutralOutput\""));
+        assert_eq!(Config::from_toml_str(&toml).unwrap(), config);
+    }
+}
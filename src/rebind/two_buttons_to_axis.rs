@@ -1,10 +1,10 @@
 use egui::{Checkbox, Slider, Ui};
-use egui_extras::{Column, TableBuilder};
+use egui_extras::Column;
 use serde::{Deserialize, Serialize};
 use strum::{AsRefStr, EnumIter, EnumString, EnumVariantNames};
-use vjoy::Axis;
 
-use super::{TABLE_COLUMN_LEFT_WIDTH, TABLE_ROW_HEIGHT};
+use super::{rebind_table, TickContext, VariantDescription, TABLE_ROW_HEIGHT};
+use crate::input::virtual_state::VirtualAxis;
 
 /// Activation type and conditions for two input button to single output axis rebinds.
 ///
@@ -47,6 +47,16 @@ impl Default for TwoButtonsToAxisModifier {
     }
 }
 
+impl VariantDescription for TwoButtonsToAxisModifier {
+    fn description(&self) -> &'static str {
+        match self {
+            TwoButtonsToAxisModifier::Absolute => "Buttons map to the axis min/max values, neutral when neither is pressed",
+            TwoButtonsToAxisModifier::Linear { .. } => "Held buttons move the axis at a constant rate, it keeps its value or returns to neutral on release",
+            TwoButtonsToAxisModifier::Click { .. } => "Each press moves the axis by a fixed step",
+        }
+    }
+}
+
 impl TwoButtonsToAxisModifier {
     pub fn widget(&mut self, ui: &mut Ui) {
         ui.vertical(|ui| match self {
@@ -55,8 +65,7 @@ impl TwoButtonsToAxisModifier {
                 coefficient,
                 keep_value,
             } => {
-                TableBuilder::new(ui)
-                    .column(Column::exact(TABLE_COLUMN_LEFT_WIDTH))
+                rebind_table(ui)
                     .column(Column::remainder())
                     .body(|mut body| {
                         body.row(TABLE_ROW_HEIGHT, |mut row| {
@@ -86,8 +95,7 @@ impl TwoButtonsToAxisModifier {
                 last_input_neg: _,
                 last_input_pos: _,
             } => {
-                TableBuilder::new(ui)
-                    .column(Column::exact(TABLE_COLUMN_LEFT_WIDTH))
+                rebind_table(ui)
                     .column(Column::remainder())
                     .body(|mut body| {
                         body.row(TABLE_ROW_HEIGHT, |mut row| {
@@ -102,21 +110,57 @@ impl TwoButtonsToAxisModifier {
             }
         });
     }
+
+    pub fn debug_state(&self) -> Vec<(String, String)> {
+        match self {
+            TwoButtonsToAxisModifier::Absolute | TwoButtonsToAxisModifier::Linear { .. } => {
+                Vec::new()
+            }
+            TwoButtonsToAxisModifier::Click {
+                last_input_neg,
+                last_input_pos,
+                ..
+            } => vec![
+                ("last_input_neg".to_string(), last_input_neg.to_string()),
+                ("last_input_pos".to_string(), last_input_pos.to_string()),
+            ],
+        }
+    }
+
+    pub fn take_runtime_state(&mut self, from: &mut Self) {
+        if let (
+            TwoButtonsToAxisModifier::Click {
+                last_input_neg,
+                last_input_pos,
+                ..
+            },
+            TwoButtonsToAxisModifier::Click {
+                last_input_neg: from_neg,
+                last_input_pos: from_pos,
+                ..
+            },
+        ) = (self, from)
+        {
+            *last_input_neg = *from_neg;
+            *last_input_pos = *from_pos;
+        }
+    }
 }
 
 // output range 0..=32767
 pub fn apply_two_buttons_to_axis_modifier(
     input_neg: bool,
     input_pos: bool,
-    output: &Axis,
+    output: &VirtualAxis,
     modifier: &mut TwoButtonsToAxisModifier,
-    delta_t: f64,
+    ctx: TickContext,
 ) -> i32 {
+    let neutral = output.neutral();
     let value = match modifier {
         TwoButtonsToAxisModifier::Absolute => match (input_neg, input_pos) {
             (true, false) => 0,
-            (true, true) => 16384,
-            (false, false) => 16384,
+            (true, true) => neutral,
+            (false, false) => neutral,
             (false, true) => 32767,
         },
         TwoButtonsToAxisModifier::Linear {
@@ -125,25 +169,29 @@ pub fn apply_two_buttons_to_axis_modifier(
         } => {
             let mut current_output_value = output.get();
             let min_change = 1;
-            let delta = (*coefficient * 32767.0 * delta_t) as i32;
+            let delta = (*coefficient * 32767.0 * ctx.delta_t) as i32;
             match (input_neg, input_pos) {
                 (true, false) => {
-                    current_output_value -= delta.max(min_change);
+                    current_output_value =
+                        current_output_value.saturating_sub(delta.max(min_change));
                 }
                 (false, true) => {
-                    current_output_value += delta.max(min_change);
+                    current_output_value =
+                        current_output_value.saturating_add(delta.max(min_change));
                 }
                 (false, false) => {
                     if *keep_value {
-                        return current_output_value;
+                        return current_output_value.clamp(0, 32767);
                     }
 
-                    let center_distance = 16384_i32.abs_diff(current_output_value);
-                    if center_distance <= delta as u32 {
-                        current_output_value = 16384_i32;
+                    let neutral_distance = neutral.abs_diff(current_output_value);
+                    if neutral_distance <= delta.max(0) as u32 {
+                        current_output_value = neutral;
                     } else {
-                        current_output_value -=
-                            delta.max(min_change) * (current_output_value - 16384).signum();
+                        current_output_value = current_output_value.saturating_sub(
+                            delta.max(min_change)
+                                * current_output_value.saturating_sub(neutral).signum(),
+                        );
                     }
                 }
                 (true, true) => (),
@@ -163,12 +211,12 @@ pub fn apply_two_buttons_to_axis_modifier(
 
             let should_click_neg = input_neg && input_neg != *last_input_neg;
             if should_click_neg {
-                current_output_value -= delta.max(min_change);
+                current_output_value = current_output_value.saturating_sub(delta.max(min_change));
             }
 
             let should_click_pos = input_pos && input_pos != *last_input_pos;
             if should_click_pos {
-                current_output_value += delta.max(min_change);
+                current_output_value = current_output_value.saturating_add(delta.max(min_change));
             }
 
             *last_input_neg = input_neg;
@@ -180,3 +228,95 @@ pub fn apply_two_buttons_to_axis_modifier(
 
     value.clamp(0, 32767)
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::{
+        input::virtual_state::VirtualState,
+        rebind::{
+            two_buttons_to_axis::{apply_two_buttons_to_axis_modifier, TwoButtonsToAxisModifier},
+            TickContext,
+        },
+    };
+
+    fn modifier() -> impl Strategy<Value = TwoButtonsToAxisModifier> {
+        prop_oneof![
+            Just(TwoButtonsToAxisModifier::Absolute),
+            (0.0f64..=10.0, any::<bool>()).prop_map(|(coefficient, keep_value)| {
+                TwoButtonsToAxisModifier::Linear {
+                    coefficient,
+                    keep_value,
+                }
+            }),
+            (0.0f64..=1.0).prop_map(|coefficient| TwoButtonsToAxisModifier::Click {
+                coefficient,
+                last_input_neg: false,
+                last_input_pos: false,
+            }),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn output_stays_in_range(
+            mut modifier in modifier(),
+            start in any::<i32>(),
+            neutral in 0..=32767i32,
+            presses in prop::collection::vec((any::<bool>(), any::<bool>(), 0.0f64..=1e6), 1..32),
+        ) {
+            let mut state = VirtualState::new(1, 0, 1, 0, Default::default());
+            let output = state.axes_mut().next().unwrap();
+            output.set(start);
+            output.set_neutral(neutral);
+            for (neg, pos, delta_t) in presses {
+                let ctx = TickContext {
+                    delta_t,
+                    ..Default::default()
+                };
+                let value = apply_two_buttons_to_axis_modifier(neg, pos, output, &mut modifier, ctx);
+                prop_assert!((0..=32767).contains(&value));
+                output.set(value);
+            }
+        }
+    }
+
+    /// Axis values after each of `presses`, one tick of `delta_t` each, starting at `start`
+    fn replay(presses: &[(bool, bool)], start: f64, delta_t: f64) -> Vec<i32> {
+        let mut state = VirtualState::new(1, 0, 1, 0, Default::default());
+        let output = state.axes_mut().next().unwrap();
+        output.set_neutral(16384);
+        output.set(16384);
+        let mut modifier = TwoButtonsToAxisModifier::Linear {
+            coefficient: 1.0,
+            keep_value: false,
+        };
+        presses
+            .iter()
+            .zip(1..)
+            .map(|(&(neg, pos), tick)| {
+                let ctx = TickContext {
+                    tick,
+                    time: start + tick as f64 * delta_t,
+                    delta_t,
+                };
+                let value =
+                    apply_two_buttons_to_axis_modifier(neg, pos, output, &mut modifier, ctx);
+                output.set(value);
+                value
+            })
+            .collect()
+    }
+
+    #[test]
+    fn linear_replays_ticks_regardless_of_wall_clock() {
+        let presses = [[(false, true); 10], [(false, false); 10]].concat();
+        let recorded = replay(&presses, 0.0, 0.01);
+        assert_eq!(replay(&presses, 1234.5, 0.01), recorded);
+
+        // 327 per tick of 10 ms at a coefficient of 1
+        assert_eq!(recorded[9], 16384 + 10 * 327);
+        assert_eq!(recorded[19], 16384);
+    }
+}
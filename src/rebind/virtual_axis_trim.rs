@@ -1,9 +1,9 @@
 use egui::{Slider, Ui};
-use egui_extras::{Column, TableBuilder};
+use egui_extras::Column;
 use serde::{Deserialize, Serialize};
 use strum::{AsRefStr, EnumIter, EnumString, EnumVariantNames};
 
-use super::{TABLE_COLUMN_LEFT_WIDTH, TABLE_ROW_HEIGHT};
+use super::{rebind_table, TickContext, VariantDescription, TABLE_ROW_HEIGHT};
 
 #[derive(
     Debug,
@@ -36,6 +36,19 @@ impl Default for VirtualAxisTrimModifier {
     }
 }
 
+impl VariantDescription for VirtualAxisTrimModifier {
+    fn description(&self) -> &'static str {
+        match self {
+            VirtualAxisTrimModifier::Click { .. } => {
+                "Each press of a trim button moves the trim by a fixed step"
+            }
+            VirtualAxisTrimModifier::Linear { .. } => {
+                "Held trim buttons move the trim at a constant rate"
+            }
+        }
+    }
+}
+
 impl VirtualAxisTrimModifier {
     pub fn widget(&mut self, ui: &mut Ui) {
         ui.vertical(|ui| match self {
@@ -48,6 +61,27 @@ impl VirtualAxisTrimModifier {
             }
         });
     }
+
+    pub fn debug_state(&self) -> Vec<(String, String)> {
+        match self {
+            VirtualAxisTrimModifier::Click { params }
+            | VirtualAxisTrimModifier::Linear { params } => params.debug_state(),
+        }
+    }
+
+    pub fn take_runtime_state(&mut self, from: &mut Self) {
+        match (self, from) {
+            (
+                VirtualAxisTrimModifier::Click { params },
+                VirtualAxisTrimModifier::Click { params: from },
+            )
+            | (
+                VirtualAxisTrimModifier::Linear { params },
+                VirtualAxisTrimModifier::Linear { params: from },
+            ) => params.take_runtime_state(from),
+            _ => {}
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Default)]
@@ -75,9 +109,31 @@ impl VirtualAxisTrimParams {
         }
     }
 
+    pub fn debug_state(&self) -> Vec<(String, String)> {
+        vec![
+            (
+                "accumulated".to_string(),
+                format!("{:.4}", self.accumulated),
+            ),
+            (
+                "last_input_neg".to_string(),
+                self.last_input_neg.to_string(),
+            ),
+            (
+                "last_input_pos".to_string(),
+                self.last_input_pos.to_string(),
+            ),
+        ]
+    }
+
+    pub fn take_runtime_state(&mut self, from: &mut Self) {
+        self.accumulated = from.accumulated;
+        self.last_input_neg = from.last_input_neg;
+        self.last_input_pos = from.last_input_pos;
+    }
+
     pub fn widget(&mut self, ui: &mut Ui) {
-        TableBuilder::new(ui)
-            .column(Column::exact(TABLE_COLUMN_LEFT_WIDTH))
+        rebind_table(ui)
             .column(Column::remainder())
             .body(|mut body| {
                 body.row(TABLE_ROW_HEIGHT, |mut row| {
@@ -97,7 +153,7 @@ pub fn apply_virtual_axis_trim_modifier(
     trim_neg: bool,
     trim_pos: bool,
     trim_reset: bool,
-    delta_t: f64,
+    ctx: TickContext,
     modifier: &mut VirtualAxisTrimModifier,
 ) -> i32 {
     let mut axis_normalized_value = input as f64 / 32767.0; //0.0..=1.0
@@ -124,11 +180,11 @@ pub fn apply_virtual_axis_trim_modifier(
 
         VirtualAxisTrimModifier::Linear { params } => {
             if trim_neg {
-                params.accumulated -= params.value_normalized * delta_t;
+                params.accumulated -= params.value_normalized * ctx.delta_t;
             }
 
             if trim_pos {
-                params.accumulated += params.value_normalized * delta_t;
+                params.accumulated += params.value_normalized * ctx.delta_t;
             }
 
             params.accumulated = params.accumulated.clamp(-0.5, 0.5);
@@ -39,6 +39,42 @@ pub enum VirtualRebind {
     },
 }
 
+/// When a virtual rebind is processed relative to the reroute rebinds of the same tick
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    Copy,
+    Default,
+    Serialize,
+    Deserialize,
+    AsRefStr,
+    EnumIter,
+    EnumString,
+    EnumVariantNames,
+)]
+pub enum ProcessingPhase {
+    /// Before the reroute rebinds, which read its output in the same tick
+    PreReroute,
+    /// After the reroute rebinds, modifying their output
+    #[default]
+    PostReroute,
+}
+
+impl VariantDescription for ProcessingPhase {
+    fn description(&self) -> &'static str {
+        match self {
+            ProcessingPhase::PreReroute => {
+                "Before the reroute rebinds, which see its output in the same tick. Reroute rebinds writing the same control overwrite it."
+            }
+            ProcessingPhase::PostReroute => {
+                "After the reroute rebinds, modifying their output of the same tick"
+            }
+        }
+    }
+}
+
 impl Default for VirtualRebind {
     fn default() -> Self {
         Self::VirtualAxisApplyButtonTrim {
@@ -61,6 +97,16 @@ impl Display for VirtualRebind {
     }
 }
 
+impl VariantDescription for VirtualRebind {
+    fn description(&self) -> &'static str {
+        match self {
+            VirtualRebind::VirtualAxisApplyButtonTrim { .. } => {
+                "Trim a virtual axis up/down with two buttons and reset it with a third"
+            }
+        }
+    }
+}
+
 impl VirtualRebind {
     pub fn content_widget(&mut self, ui: &mut Ui, devices_info_map: &mut DevicesInfoMap) {
         ui.vertical(|ui| match self {
@@ -75,8 +121,7 @@ impl VirtualRebind {
                 trim_reset_button,
                 modifier,
             } => {
-                TableBuilder::new(ui)
-                    .column(Column::exact(TABLE_COLUMN_LEFT_WIDTH))
+                rebind_table(ui)
                     .column(Column::remainder())
                     .body(|mut body| {
                         body.row(TABLE_ROW_HEIGHT, |mut row| {
@@ -100,8 +145,7 @@ impl VirtualRebind {
                             });
                             row.col(|ui| {
                                 ui.push_id("VirtualAxisSrcAxis", |ui| {
-                                    let max = devices_info_map.get_virtual_limits(axis_device).1;
-                                    axis.id_dropdown_widget(max, ui);
+                                    devices_info_map.virtual_axis_widget(ui, axis_device, axis);
                                 });
                             });
                         });
@@ -198,16 +242,70 @@ impl VirtualRebind {
                         });
                     });
 
+                modifier.variant_description_label(ui);
                 ui.add_space(SECTION_SPACING);
                 modifier.widget(ui);
             }
         });
     }
 
+    pub fn virtual_writes(&self) -> Vec<VirtualControl> {
+        match self {
+            VirtualRebind::VirtualAxisApplyButtonTrim {
+                axis_device, axis, ..
+            } => vec![VirtualControl::Axis {
+                device: *axis_device,
+                axis: *axis,
+            }],
+        }
+    }
+
+    pub fn virtual_reads(&self) -> Vec<VirtualControl> {
+        match self {
+            VirtualRebind::VirtualAxisApplyButtonTrim {
+                trim_neg_device,
+                trim_neg_button,
+                trim_pos_device,
+                trim_pos_button,
+                trim_reset_device,
+                trim_reset_button,
+                ..
+            } => vec![
+                VirtualControl::Button {
+                    device: *trim_neg_device,
+                    button: *trim_neg_button,
+                },
+                VirtualControl::Button {
+                    device: *trim_pos_device,
+                    button: *trim_pos_button,
+                },
+                VirtualControl::Button {
+                    device: *trim_reset_device,
+                    button: *trim_reset_button,
+                },
+            ],
+        }
+    }
+
+    pub fn debug_state(&self) -> Vec<(String, String)> {
+        match self {
+            VirtualRebind::VirtualAxisApplyButtonTrim { modifier, .. } => modifier.debug_state(),
+        }
+    }
+
+    pub fn take_runtime_state(&mut self, from: &mut Self) {
+        match (self, from) {
+            (
+                VirtualRebind::VirtualAxisApplyButtonTrim { modifier, .. },
+                VirtualRebind::VirtualAxisApplyButtonTrim { modifier: from, .. },
+            ) => modifier.take_runtime_state(from),
+        }
+    }
+
     pub fn process(
         &mut self,
         virtual_devices: &mut [VirtualDevice],
-        delta_t: f64,
+        ctx: TickContext,
     ) -> Result<(), Error> {
         match self {
             VirtualRebind::VirtualAxisApplyButtonTrim {
@@ -242,7 +340,7 @@ impl VirtualRebind {
                     trim_neg,
                     trim_pos,
                     trim_reset,
-                    delta_t,
+                    ctx,
                     modifier,
                 );
                 output.set(modified_state);
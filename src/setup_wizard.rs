@@ -0,0 +1,229 @@
+use egui::{Align2, Context, RichText, Ui};
+use log::{error, info};
+
+use crate::{
+    config::Config,
+    input::{identifiers::capability_summary, Input},
+    previous::Previous,
+    rebind::capabilities::find_vjoy_conf,
+    ui_data::UIData,
+};
+
+/// Virtual device the passthrough template writes to
+const TEMPLATE_VJOY_ID: u32 = 1;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum SetupStep {
+    #[default]
+    VJoy,
+    PrimaryDevice,
+    Template,
+    Tabs,
+}
+
+impl SetupStep {
+    fn next(self) -> Option<Self> {
+        match self {
+            SetupStep::VJoy => Some(SetupStep::PrimaryDevice),
+            SetupStep::PrimaryDevice => Some(SetupStep::Template),
+            SetupStep::Template => Some(SetupStep::Tabs),
+            SetupStep::Tabs => None,
+        }
+    }
+
+    fn previous(self) -> Option<Self> {
+        match self {
+            SetupStep::VJoy => None,
+            SetupStep::PrimaryDevice => Some(SetupStep::VJoy),
+            SetupStep::Template => Some(SetupStep::PrimaryDevice),
+            SetupStep::Tabs => Some(SetupStep::Template),
+        }
+    }
+}
+
+/// First-run walkthrough: vJoy check, primary device, passthrough template and a tour of the tabs.
+///
+/// Opens on its own while no settings file exists. Finishing or skipping it is remembered in
+/// [`Previous::setup_wizard_done`], the System menu reopens it.
+#[derive(Debug, Default)]
+pub struct SetupWizard {
+    pub step: SetupStep,
+    pub primary_device: Option<String>,
+    pub generated_rebinds: Option<usize>,
+}
+
+pub fn update_window(
+    ctx: &Context,
+    previous: &mut Previous,
+    input: &mut Input,
+    ui_data: &mut UIData,
+) {
+    let Some(wizard) = &mut ui_data.setup_wizard else {
+        return;
+    };
+
+    let mut open = true;
+    let mut done = false;
+    egui::Window::new("Setup wizard")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            match wizard.step {
+                SetupStep::VJoy => vjoy_step(ui, input, &ui_data.capability_issues),
+                SetupStep::PrimaryDevice => primary_device_step(ui, input, wizard),
+                SetupStep::Template => template_step(ui, input, wizard),
+                SetupStep::Tabs => tabs_step(ui),
+            }
+
+            ui.add_space(10.0);
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Skip setup").clicked() {
+                    done = true;
+                }
+                if let Some(step) = wizard.step.previous() {
+                    if ui.button("Back").clicked() {
+                        wizard.step = step;
+                    }
+                }
+                match wizard.step.next() {
+                    Some(step) => {
+                        if ui.button("Next").clicked() {
+                            wizard.step = step;
+                        }
+                    }
+                    None => {
+                        if ui.button("Finish").clicked() {
+                            done = true;
+                        }
+                    }
+                }
+            });
+        });
+
+    if !open || done {
+        ui_data.setup_wizard = None;
+        previous.setup_wizard_done = true;
+        info!("Setup wizard closed");
+    }
+}
+
+fn vjoy_step(ui: &mut Ui, input: &Input, capability_issues: &[String]) {
+    ui.heading("vJoy");
+    ui.label("Rebinds write to vJoy devices, other programs read those instead of your physical devices.");
+    ui.add_space(5.0);
+
+    if input.virtual_devices_count() == 0 {
+        ui.label(RichText::new("No enabled vJoy device found.").color(ui.visuals().error_fg_color));
+        ui.label("Install vJoy and enable at least one device with vJoyConf.");
+    } else {
+        for device in input.virtual_devices() {
+            ui.label(format!(
+                "{}: {}",
+                device.name(),
+                capability_summary(device.num_buttons(), device.num_axes(), device.num_hats())
+            ));
+        }
+    }
+    for issue in capability_issues {
+        ui.label(RichText::new(issue).color(ui.visuals().warn_fg_color));
+    }
+
+    if let Some(path) = find_vjoy_conf() {
+        if ui.button("Open vJoyConf").clicked() {
+            if let Err(e) = std::process::Command::new(&path).spawn() {
+                error!("Failed to launch {:?}. Reason: {}", path, e);
+            }
+        }
+    }
+}
+
+fn primary_device_step(ui: &mut Ui, input: &Input, wizard: &mut SetupWizard) {
+    ui.heading("Primary device");
+    ui.label("Select the device you want to remap first, e.g. your stick.");
+    ui.add_space(5.0);
+
+    if input.physical_devices_count() == 0 {
+        ui.label("No physical device connected. Plug one in and it shows up here.");
+        return;
+    }
+
+    for device in input.physical_devices() {
+        let selected = wizard.primary_device.as_deref() == Some(device.guid.as_str());
+        let label = format!(
+            "{} ({})",
+            device.name(),
+            capability_summary(device.num_buttons(), device.num_axes(), device.num_hats())
+        );
+        if ui.radio(selected, label).clicked() {
+            wizard.primary_device = Some(device.guid.clone());
+            wizard.generated_rebinds = None;
+        }
+    }
+}
+
+fn template_step(ui: &mut Ui, input: &mut Input, wizard: &mut SetupWizard) {
+    ui.heading("Passthrough template");
+    ui.label(format!(
+        "Generates a config that routes every button, axis and hat of the primary device 1:1 to vJoy device {TEMPLATE_VJOY_ID}. Edit the rebinds afterwards to your liking."
+    ));
+    ui.add_space(5.0);
+
+    let physical = input.get_physical_device_info_map();
+    let virtual_devices = input.get_virtual_device_info_map();
+    let src = wizard
+        .primary_device
+        .as_ref()
+        .and_then(|guid| Some((guid, physical.get(guid)?)));
+    let Some((guid, src)) = src else {
+        ui.label("Select a primary device in the previous step to generate a template.");
+        return;
+    };
+    let Some(dst) = virtual_devices.get(&TEMPLATE_VJOY_ID) else {
+        ui.label(format!(
+            "vJoy device {TEMPLATE_VJOY_ID} is not enabled, configure it with vJoyConf first."
+        ));
+        return;
+    };
+
+    let existing = input.get_config().rebinds.len();
+    if existing > 0 && wizard.generated_rebinds.is_none() {
+        ui.label(
+            RichText::new(format!(
+                "Replaces the current config with its {existing} rebinds. Reload it from disk to undo."
+            ))
+            .color(ui.visuals().warn_fg_color),
+        );
+    }
+
+    if ui.button("Generate passthrough config").clicked() {
+        let config = Config::passthrough(guid, src, TEMPLATE_VJOY_ID, dst);
+        wizard.generated_rebinds = Some(config.rebinds.len());
+        input.replace_config(config);
+        info!("Generated passthrough config for {}", src.name);
+    }
+    if let Some(count) = wizard.generated_rebinds {
+        ui.label(format!(
+            "Generated {count} rebinds. Save them with System > Save config as…"
+        ));
+    }
+}
+
+fn tabs_step(ui: &mut Ui) {
+    ui.heading("Where to find things");
+    ui.add_space(5.0);
+    let items = [
+        ("Left panel", "Connected devices. Select the ones to show in the input viewer."),
+        ("Input viewer | Rebind", "Live inputs and outputs next to the rebind list of the config."),
+        ("Config editor", "The config as TOML, for bulk edits."),
+        ("System", "Load and save configs, settings and this wizard."),
+        ("Shift modes", "Logical rebinds switch modes while a button is held, every rebind runs only in the modes of its mask."),
+    ];
+    for (title, text) in items {
+        ui.label(RichText::new(title).strong());
+        ui.label(text);
+        ui.add_space(3.0);
+    }
+}
@@ -0,0 +1,185 @@
+use egui::{pos2, vec2, Align, Context, CursorIcon, Key, Layout, Modifiers, Rect, Sense, Ui};
+use serde::{Deserialize, Serialize};
+
+/// Width of the drag handle between both sides
+const HANDLE_WIDTH: f32 = 8.0;
+/// Width of a collapsed side
+const STRIP_WIDTH: f32 = 28.0;
+/// A side dragged narrower than this collapses to its strip
+pub const MIN_SIDE_WIDTH: f32 = 240.0;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum SplitSide {
+    Rebinds,
+    Inputs,
+}
+
+impl SplitSide {
+    fn name(&self) -> &'static str {
+        match self {
+            SplitSide::Rebinds => "rebinds",
+            SplitSide::Inputs => "input viewer",
+        }
+    }
+
+    fn shortcut(&self) -> &'static str {
+        match self {
+            SplitSide::Rebinds => "Ctrl+1",
+            SplitSide::Inputs => "Ctrl+2",
+        }
+    }
+}
+
+/// Rebind viewer on the left, input viewer on the right, owned by the UI and persisted in the app
+/// settings.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(default)]
+pub struct SplitView {
+    /// Share of the width for the rebind viewer while neither side is collapsed
+    pub fraction: f32,
+    pub collapsed: Option<SplitSide>,
+}
+
+impl Default for SplitView {
+    fn default() -> Self {
+        Self {
+            fraction: 0.5,
+            collapsed: None,
+        }
+    }
+}
+
+impl SplitView {
+    /// Widths of the rebind and the input side for `total` width, without the handle
+    pub fn widths(&self, total: f32) -> [f32; 2] {
+        let content = (total - HANDLE_WIDTH).max(0.0);
+        let left = match self.collapsed {
+            Some(SplitSide::Rebinds) => STRIP_WIDTH.min(content),
+            Some(SplitSide::Inputs) => (content - STRIP_WIDTH).max(0.0),
+            None => content * self.fraction.clamp(0.0, 1.0),
+        };
+        [left, content - left]
+    }
+
+    /// Moves the handle so the rebind side is `left` wide. A side narrower than
+    /// [`MIN_SIDE_WIDTH`] collapses and keeps the previous split for when it is expanded again.
+    pub fn drag_to(&mut self, left: f32, total: f32) {
+        let content = total - HANDLE_WIDTH;
+        if content <= 0.0 {
+            return;
+        }
+        self.collapsed = if left < MIN_SIDE_WIDTH {
+            Some(SplitSide::Rebinds)
+        } else if content - left < MIN_SIDE_WIDTH {
+            Some(SplitSide::Inputs)
+        } else {
+            self.fraction = left / content;
+            None
+        };
+    }
+
+    /// Collapses `side`, or expands it if it already is collapsed
+    pub fn toggle(&mut self, side: SplitSide) {
+        self.collapsed = match self.collapsed == Some(side) {
+            true => None,
+            false => Some(side),
+        };
+    }
+
+    /// Ctrl+1 collapses or expands the rebind viewer, Ctrl+2 the input viewer
+    pub fn handle_shortcuts(&mut self, ctx: &Context) {
+        for (key, side) in [
+            (Key::Num1, SplitSide::Rebinds),
+            (Key::Num2, SplitSide::Inputs),
+        ] {
+            if ctx.input_mut(|i| i.consume_key(Modifiers::COMMAND, key)) {
+                self.toggle(side);
+            }
+        }
+    }
+
+    /// Calls `add_contents` for each side that is not collapsed
+    pub fn show(&mut self, ui: &mut Ui, mut add_contents: impl FnMut(&mut Ui, SplitSide)) {
+        let rect = ui.available_rect_before_wrap();
+        let [left, _] = self.widths(rect.width());
+        let handle_rect = Rect::from_min_size(
+            pos2(rect.left() + left, rect.top()),
+            vec2(HANDLE_WIDTH, rect.height()),
+        );
+
+        let handle = ui.interact(handle_rect, ui.id().with("SplitHandle"), Sense::drag());
+        if let (true, Some(pointer)) = (handle.dragged(), handle.interact_pointer_pos()) {
+            self.drag_to(pointer.x - rect.left() - HANDLE_WIDTH / 2.0, rect.width());
+        }
+        let active = handle.hovered() || handle.dragged();
+        if active {
+            ui.ctx().set_cursor_icon(CursorIcon::ResizeHorizontal);
+        }
+        let stroke = match active {
+            true => ui.visuals().widgets.hovered.fg_stroke,
+            false => ui.visuals().widgets.noninteractive.bg_stroke,
+        };
+        ui.painter()
+            .vline(handle_rect.center().x, handle_rect.y_range(), stroke);
+
+        let [left, _] = self.widths(rect.width());
+        let left_rect = Rect::from_min_size(rect.min, vec2(left, rect.height()));
+        let right_rect =
+            Rect::from_min_max(pos2(left_rect.right() + HANDLE_WIDTH, rect.top()), rect.max);
+        self.side(ui, SplitSide::Rebinds, left_rect, &mut add_contents);
+        self.side(ui, SplitSide::Inputs, right_rect, &mut add_contents);
+        ui.allocate_rect(rect, Sense::hover());
+    }
+
+    fn side(
+        &mut self,
+        ui: &mut Ui,
+        side: SplitSide,
+        rect: Rect,
+        add_contents: &mut impl FnMut(&mut Ui, SplitSide),
+    ) {
+        let mut child = ui.child_ui_with_id_source(rect, Layout::top_down(Align::LEFT), side);
+        child.set_clip_rect(rect.intersect(ui.clip_rect()));
+
+        if self.collapsed != Some(side) {
+            add_contents(&mut child, side);
+            return;
+        }
+        let arrow = match side {
+            SplitSide::Rebinds => "▶",
+            SplitSide::Inputs => "◀",
+        };
+        if child
+            .button(arrow)
+            .on_hover_text(format!("Show {} ({})", side.name(), side.shortcut()))
+            .clicked()
+        {
+            self.toggle(side);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::split_view::{SplitSide, SplitView, HANDLE_WIDTH, MIN_SIDE_WIDTH};
+
+    #[test]
+    fn narrow_sides_collapse_and_keep_the_split() {
+        let total = 1000.0 + HANDLE_WIDTH;
+        let mut split = SplitView::default();
+        assert_eq!(split.widths(total), [500.0, 500.0]);
+
+        split.drag_to(700.0, total);
+        assert_eq!(split.collapsed, None);
+        assert_eq!(split.widths(total), [700.0, 300.0]);
+
+        split.drag_to(1000.0 - MIN_SIDE_WIDTH + 1.0, total);
+        assert_eq!(split.collapsed, Some(SplitSide::Inputs));
+        split.toggle(SplitSide::Inputs);
+        assert_eq!(split.widths(total), [700.0, 300.0]);
+
+        split.drag_to(MIN_SIDE_WIDTH - 1.0, total);
+        assert_eq!(split.collapsed, Some(SplitSide::Rebinds));
+        assert!(split.widths(total)[0] < MIN_SIDE_WIDTH);
+    }
+}
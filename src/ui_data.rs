@@ -1,8 +1,28 @@
-use crate::graphics_backend::ColorTest;
+use crate::{
+    config_editor::ConfigEditor,
+    error::Error,
+    graphics_backend::ColorTest,
+    input::{
+        device_panel::DeviceSelection, plot_legend::PlotLegend, stick_navigation::StickNavigation,
+        test_script::TestScriptEditor,
+    },
+    modal::Modals,
+    performance::PerformanceStats,
+    rebind::{
+        axis_roles::AxisRoleTable, batch_rename::BatchRename, device_swap::DeviceSwap,
+        output_range::OutputRangeSettings, quick_map::QuickMap, shift_mode_mask::ShiftModeMask,
+    },
+    setup_wizard::SetupWizard,
+    split_view::SplitView,
+    update_check::UpdateCheck,
+    view_state::ExpandedRebinds,
+    visualization::VisualizationSettings,
+};
 use egui::{ColorImage, Context, TextureHandle, TextureOptions};
 use egui_file::FileDialog;
 use ringbuffer::AllocRingBuffer;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, time::Instant};
 
 const HAT_SWITCH: [(i32, &str); 9] = [
     (0, "north"),
@@ -18,13 +38,63 @@ const HAT_SWITCH: [(i32, &str); 9] = [
 
 pub struct UIData {
     pub active_tab: ActiveTab,
-    pub load_file_dialog: Option<FileDialog>,
-    pub save_file_dialog: Option<FileDialog>,
+    pub modals: Modals,
+    pub button_labels_import_dialog: Option<(String, FileDialog)>,
+    pub button_labels_export_dialog: Option<(String, FileDialog)>,
     pub ferris: TextureHandle,
     pub button: TextureHandle,
     pub hat_switches: HashMap<i32, TextureHandle>,
+    pub heatmap_textures: HashMap<String, Option<(TextureHandle, f64)>>,
+    /// Axes plot legend per physical device GUID or virtual device name
+    pub plot_legends: HashMap<String, PlotLegend>,
     pub should_close: bool,
+    pub show_about: bool,
+    pub show_changelog: bool,
+    pub update_check: UpdateCheck,
+    pub background_mode_enabled: bool,
+    pub background_mode: bool,
+    pub check_capabilities: bool,
+    pub capability_issues: Vec<String>,
+    pub usage_current_mode_only: bool,
+    /// Only rebinds active in this shift mode are listed
+    pub rebind_mode_filter: Option<ShiftModeMask>,
+    pub expanded_rebinds: ExpandedRebinds,
+    /// Vertical scroll offset of the config's rebind list in the last frame
+    pub rebind_scroll: f32,
+    /// Scroll offset applied to the rebind list in the next frame, set when restoring the session
+    pub restore_rebind_scroll: Option<f32>,
+    pub device_colors: bool,
+    pub show_mirror_status: bool,
+    /// Shows the modifier state of expanded rebinds in the rebind viewer
+    pub show_rebind_debug_state: bool,
+    /// Rebind edits stay pending until applied
+    pub safe_edit: bool,
+    /// Started with `--safe-mode` or Shift held, shows the banner until output is resumed
+    pub safe_mode: bool,
+    pub pixel_grid: bool,
+    /// Shows the button of the performance tab in the top bar
+    pub show_performance_tab: bool,
+    pub performance: PerformanceStats,
     pub color_test: ColorTest,
+    pub config_editor: ConfigEditor,
+    pub device_selection: DeviceSelection,
+    pub toast: Option<Toast>,
+    pub setup_wizard: Option<SetupWizard>,
+    pub quick_map: QuickMap,
+    pub batch_rename: BatchRename,
+    pub device_swap: DeviceSwap,
+    pub test_script: TestScriptEditor,
+    pub stick_navigation: StickNavigation,
+    /// GUID of the physical device whose axis transform is being edited
+    pub device_transform_editor: Option<String>,
+    /// Id of the virtual device shown in the test panel
+    pub test_panel: Option<u32>,
+    pub visualization: VisualizationSettings,
+    pub axis_roles: AxisRoleTable,
+    pub output_range: OutputRangeSettings,
+    /// MIDI output ports, listed when the MIDI feedback menu is first opened
+    pub midi_ports: Option<Vec<String>>,
+    pub split_view: SplitView,
     pub frame_s: f64,
     pub frame_s_buffer: AllocRingBuffer<Option<f64>>,
 }
@@ -74,22 +144,95 @@ impl UIData {
 
         UIData {
             active_tab: ActiveTab::InputViewerRebind,
-            save_file_dialog: None,
-            load_file_dialog: None,
+            modals: Modals::default(),
+            button_labels_import_dialog: None,
+            button_labels_export_dialog: None,
             ferris,
             button,
             hat_switches,
+            heatmap_textures: HashMap::new(),
+            plot_legends: HashMap::new(),
             should_close: false,
+            show_about: false,
+            show_changelog: false,
+            update_check: UpdateCheck::default(),
+            background_mode_enabled: true,
+            background_mode: false,
+            check_capabilities: true,
+            capability_issues: Vec::new(),
+            usage_current_mode_only: false,
+            rebind_mode_filter: None,
+            expanded_rebinds: ExpandedRebinds::default(),
+            rebind_scroll: 0.0,
+            restore_rebind_scroll: None,
+            device_colors: true,
+            show_mirror_status: false,
+            show_rebind_debug_state: false,
+            safe_edit: false,
+            safe_mode: false,
+            pixel_grid: false,
+            show_performance_tab: false,
+            performance: PerformanceStats::new(Instant::now()),
             color_test,
+            config_editor: ConfigEditor::default(),
+            device_selection: DeviceSelection::default(),
+            toast: None,
+            setup_wizard: None,
+            quick_map: QuickMap::default(),
+            batch_rename: BatchRename::default(),
+            device_swap: DeviceSwap::default(),
+            test_script: TestScriptEditor::default(),
+            stick_navigation: StickNavigation::default(),
+            device_transform_editor: None,
+            test_panel: None,
+            visualization: VisualizationSettings::default(),
+            axis_roles: AxisRoleTable::default(),
+            output_range: OutputRangeSettings::default(),
+            midi_ports: None,
+            split_view: SplitView::default(),
             frame_s: 0.0,
             frame_s_buffer: AllocRingBuffer::with_capacity(16),
         }
     }
 }
 
-#[derive(PartialEq, Debug, Clone)]
+/// Short notice shown at the bottom of the window, e.g. after copying to the clipboard
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub text: String,
+    pub until: f64,
+}
+
+impl Toast {
+    const DURATION_S: f64 = 1.5;
+    const WARNING_DURATION_S: f64 = 8.0;
+
+    pub fn new(text: String, time: f64) -> Self {
+        Self {
+            text,
+            until: time + Self::DURATION_S,
+        }
+    }
+
+    /// Stays long enough to be noticed while looking elsewhere
+    pub fn warning(text: String, time: f64) -> Self {
+        Self {
+            text,
+            until: time + Self::WARNING_DURATION_S,
+        }
+    }
+
+    /// User message and code of `error`, shown as long as a warning
+    pub fn error(error: &Error, time: f64) -> Self {
+        Self::warning(error.report(), time)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 pub enum ActiveTab {
     #[cfg(debug_assertions)]
     ColorTest,
     InputViewerRebind,
+    ConfigEditor,
+    Performance,
 }
@@ -0,0 +1,265 @@
+//! Opt-in check for a newer release on GitHub. Requires the default `update-check` feature,
+//! builds without it never check.
+
+#[cfg(feature = "update-check")]
+use std::time::Duration;
+use std::{
+    sync::mpsc::{self, Receiver, TryRecvError},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use egui::{Context, RichText, ScrollArea};
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+/// Whether this build can check for updates
+pub const UPDATE_CHECK_AVAILABLE: bool = cfg!(feature = "update-check");
+
+#[cfg(feature = "update-check")]
+const RELEASES_API_URL: &str =
+    "https://api.github.com/repos/ArrowMaxGithub/Rust-vJoy-Manager/releases/latest";
+
+/// At most one check per day, counted from the start of the last check
+const CHECK_INTERVAL_S: u64 = 24 * 60 * 60;
+
+#[cfg(feature = "update-check")]
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Bundled with the build, so it matches the running version
+pub const CHANGELOG: &str = include_str!("../CHANGELOG.md");
+
+/// Update check settings and the result of the last check, persisted in the app settings. Off by
+/// default, nothing is sent until the user enables it.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(default)]
+pub struct UpdateCheckSettings {
+    pub enabled: bool,
+    /// Unix time of the last started check, failed checks count as well
+    pub last_check: Option<u64>,
+    /// Newest release found by the last successful check
+    pub latest: Option<Release>,
+}
+
+impl UpdateCheckSettings {
+    pub fn is_due(&self, now: u64) -> bool {
+        match self.last_check {
+            _ if !self.enabled => false,
+            Some(last) => now.saturating_sub(last) >= CHECK_INTERVAL_S,
+            None => true,
+        }
+    }
+
+    /// The latest known release if it is newer than the running build
+    pub fn available_update(&self) -> Option<&Release> {
+        self.latest
+            .as_ref()
+            .filter(|r| is_newer(&r.version, env!("CARGO_PKG_VERSION")))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Release {
+    pub version: String,
+    pub url: String,
+}
+
+/// Response of the GitHub releases API, only the used fields
+#[cfg(feature = "update-check")]
+#[derive(Deserialize)]
+struct GitHubRelease {
+    tag_name: String,
+    html_url: String,
+}
+
+/// Runs the request on a background thread, so neither startup nor a frame ever waits for the
+/// network. Failures are only logged at debug level.
+#[derive(Default)]
+pub struct UpdateCheck {
+    receiver: Option<Receiver<Release>>,
+}
+
+impl UpdateCheck {
+    /// Starts a check if enabled and the last one is at least a day ago
+    pub fn start_if_due(&mut self, settings: &mut UpdateCheckSettings) {
+        let now = unix_time();
+        if !UPDATE_CHECK_AVAILABLE || self.receiver.is_some() || !settings.is_due(now) {
+            return;
+        }
+
+        settings.last_check = Some(now);
+        let (sender, receiver) = mpsc::channel();
+        let spawned = std::thread::Builder::new()
+            .name("update check".to_string())
+            .spawn(move || match fetch_latest_release() {
+                Ok(release) => {
+                    let _ = sender.send(release);
+                }
+                Err(e) => debug!("Update check failed. Reason: {e}"),
+            });
+        match spawned {
+            Ok(_) => self.receiver = Some(receiver),
+            Err(e) => debug!("Failed to start the update check. Reason: {e}"),
+        }
+    }
+
+    /// Stores the result of a finished check in `settings`
+    pub fn poll(&mut self, settings: &mut UpdateCheckSettings) {
+        let Some(receiver) = &self.receiver else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(release) => {
+                debug!("Latest release: {}", release.version);
+                settings.latest = Some(release);
+                self.receiver = None;
+            }
+            Err(TryRecvError::Empty) => (),
+            Err(TryRecvError::Disconnected) => self.receiver = None,
+        }
+    }
+}
+
+#[cfg(feature = "update-check")]
+fn fetch_latest_release() -> Result<Release, Box<dyn std::error::Error>> {
+    let release: GitHubRelease = ureq::AgentBuilder::new()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(concat!("rust-vjoy-manager/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .get(RELEASES_API_URL)
+        .call()?
+        .into_json()?;
+    Ok(Release {
+        version: release.tag_name,
+        url: release.html_url,
+    })
+}
+
+#[cfg(not(feature = "update-check"))]
+fn fetch_latest_release() -> Result<Release, Box<dyn std::error::Error>> {
+    Err("this build has no update check, enable the update-check feature".into())
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Numeric components of a version like `v1.2.3-beta`, `None` if it has none
+fn version_numbers(version: &str) -> Option<Vec<u64>> {
+    let version = version.trim().trim_start_matches(['v', 'V']);
+    let version = version.split(['-', '+']).next()?;
+    version.split('.').map(|n| n.parse().ok()).collect()
+}
+
+/// Compares release versions by their numeric components, unparsable versions are never newer
+pub fn is_newer(latest: &str, current: &str) -> bool {
+    match (version_numbers(latest), version_numbers(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+/// Section of `changelog` for `version`, from its `## ` heading to the next one
+pub fn changelog_section<'a>(changelog: &'a str, version: &str) -> Option<&'a str> {
+    let mut offset = 0;
+    let mut start = None;
+    for line in changelog.split_inclusive('\n') {
+        if let Some(heading) = line.strip_prefix("## ") {
+            if start.is_some() {
+                return start.map(|start| changelog[start..offset].trim_end());
+            }
+            let heading = heading
+                .trim()
+                .trim_start_matches('[')
+                .trim_start_matches('v');
+            if heading.starts_with(version)
+                && !heading[version.len()..].starts_with(|c: char| c.is_ascii_digit())
+            {
+                start = Some(offset);
+            }
+        }
+        offset += line.len();
+    }
+    start.map(|start| changelog[start..].trim_end())
+}
+
+/// Changelog of the running version with the full changelog below
+pub fn update_changelog_window(ctx: &Context, open: &mut bool) {
+    egui::Window::new("Changelog")
+        .open(open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            ScrollArea::vertical().show(ui, |ui| {
+                let current = changelog_section(CHANGELOG, env!("CARGO_PKG_VERSION"));
+                for line in current.unwrap_or(CHANGELOG).lines() {
+                    changelog_line(ui, line);
+                }
+                if current.is_some() {
+                    ui.add_space(10.0);
+                    ui.collapsing("All versions", |ui| {
+                        for line in CHANGELOG.lines() {
+                            changelog_line(ui, line);
+                        }
+                    });
+                }
+            });
+        });
+}
+
+fn changelog_line(ui: &mut egui::Ui, line: &str) {
+    if let Some(heading) = line.strip_prefix("# ") {
+        ui.heading(heading);
+    } else if let Some(heading) = line.strip_prefix("## ") {
+        ui.label(RichText::new(heading).strong());
+    } else if !line.trim().is_empty() {
+        ui.label(line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::update_check::{
+        changelog_section, is_newer, UpdateCheckSettings, CHANGELOG, CHECK_INTERVAL_S,
+    };
+
+    #[test]
+    fn versions_compare_numerically() {
+        assert!(is_newer("v0.10.0", "0.9.3"));
+        assert!(is_newer("1.0.0-beta", "0.1.0"));
+        assert!(is_newer("0.1.1", "0.1"));
+        assert!(!is_newer("v0.1.0", "0.1.0"));
+        assert!(!is_newer("0.0.9", "0.1.0"));
+        assert!(!is_newer("nightly", "0.1.0"));
+    }
+
+    #[test]
+    fn checks_at_most_once_per_day_when_enabled() {
+        let mut settings = UpdateCheckSettings::default();
+        assert!(!settings.is_due(0));
+
+        settings.enabled = true;
+        assert!(settings.is_due(1000));
+        settings.last_check = Some(1000);
+        assert!(!settings.is_due(1000 + CHECK_INTERVAL_S - 1));
+        assert!(settings.is_due(1000 + CHECK_INTERVAL_S));
+    }
+
+    #[test]
+    fn changelog_section_of_one_version() {
+        let changelog =
+            "# Changelog\n\n## v0.2.0\n- new\n\n## 0.1.10\n- newer\n\n## 0.1.1\n- old\n";
+        assert_eq!(
+            changelog_section(changelog, "0.2.0"),
+            Some("## v0.2.0\n- new")
+        );
+        assert_eq!(
+            changelog_section(changelog, "0.1.1"),
+            Some("## 0.1.1\n- old")
+        );
+        assert_eq!(changelog_section(changelog, "0.3.0"), None);
+
+        assert!(changelog_section(CHANGELOG, env!("CARGO_PKG_VERSION")).is_some());
+    }
+}
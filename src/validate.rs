@@ -0,0 +1,434 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use indexmap::IndexMap;
+
+use crate::{
+    config::Config,
+    input::button_labels::ButtonLabelMap,
+    rebind::{
+        capabilities::{
+            missing_virtual_capabilities, required_virtual_devices, VirtualDeviceRequirement,
+        },
+        dependencies::dependency_cycles,
+        rebind_processor::read_order_warnings,
+        rebind_viewer::DeviceInfo,
+        shift_conflicts::shift_conflicts,
+        QuotedName, VirtualControl,
+    },
+};
+
+/// Upper limits of a single vJoy device, used when no capability hint is given
+const VJOY_MAX_BUTTONS: usize = 128;
+const VJOY_MAX_AXES: usize = 8;
+const VJOY_MAX_HATS: usize = 4;
+
+const USAGE: &str = "usage: rust-vjoy-manager --validate <config.toml> [--json] [--vjoy <id>:<n>buttons:<n>axes:<n>hats]...";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Issue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Issue {
+    fn error(message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            message,
+        }
+    }
+
+    fn warning(message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message,
+        }
+    }
+}
+
+/// Result of the static checks on one config file.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ValidationReport {
+    pub path: PathBuf,
+    pub issues: Vec<Issue>,
+}
+
+impl ValidationReport {
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|i| i.severity == Severity::Error)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}\n", self.path.display());
+        for issue in self.issues.iter() {
+            let severity = match issue.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            let _ = writeln!(text, "  {severity}: {}", issue.message);
+        }
+        let errors = self
+            .issues
+            .iter()
+            .filter(|i| i.severity == Severity::Error)
+            .count();
+        let _ = write!(
+            text,
+            "{errors} error(s), {} warning(s)",
+            self.issues.len() - errors
+        );
+        text
+    }
+
+    pub fn to_json(&self) -> String {
+        let issues: Vec<String> = self
+            .issues
+            .iter()
+            .map(|issue| {
+                let severity = match issue.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                };
+                format!(
+                    r#"{{"severity":"{severity}","message":"{}"}}"#,
+                    json_escape(&issue.message)
+                )
+            })
+            .collect();
+        format!(
+            r#"{{"path":"{}","valid":{},"issues":[{}]}}"#,
+            json_escape(&self.path.to_string_lossy()),
+            !self.has_errors(),
+            issues.join(",")
+        )
+    }
+}
+
+/// Runs the static checks on a config file. Needs no SDL, vJoy or window.
+///
+/// Virtual devices are checked against `hints` (vJoy id -> capabilities). Without hints only the
+/// vJoy limits per device are checked.
+pub fn validate_config_file(path: &Path, hints: &IndexMap<u32, DeviceInfo>) -> ValidationReport {
+    let issues = match Config::read_from_path(path) {
+        Ok(config) => validate_config(&config, hints),
+        Err(e) => vec![Issue::error(e.to_string())],
+    };
+
+    ValidationReport {
+        path: path.to_path_buf(),
+        issues,
+    }
+}
+
+pub fn validate_config(config: &Config, hints: &IndexMap<u32, DeviceInfo>) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    let mut names: HashMap<&str, usize> = HashMap::new();
+    for rebind in config.rebinds.iter() {
+        *names.entry(rebind.name.as_str()).or_default() += 1;
+    }
+    let mut duplicates: Vec<(&str, usize)> = names.into_iter().filter(|(_, n)| *n > 1).collect();
+    duplicates.sort();
+    for (name, count) in duplicates {
+        issues.push(Issue::warning(format!(
+            "rebind name {} is used {count} times",
+            QuotedName(name)
+        )));
+    }
+
+    for (index, rebind) in config.rebinds.iter().enumerate() {
+        let unset = rebind
+            .virtual_writes()
+            .into_iter()
+            .chain(rebind.virtual_reads())
+            .any(|control| {
+                let (device, id) = match control {
+                    VirtualControl::Button { device, button } => (device, button),
+                    VirtualControl::Axis { device, axis } => (device, axis),
+                    VirtualControl::Hat { device, hat } => (device, hat),
+                };
+                device == 0 || id == 0
+            });
+        if unset {
+            issues.push(Issue::warning(format!(
+                "rebind {index} {} references an unset virtual device or id",
+                QuotedName(&rebind.name)
+            )));
+        }
+    }
+
+    for overlay in config.overlays.iter() {
+        for (index, override_) in overlay.overrides.iter().enumerate() {
+            let found = override_
+                .rebind_id
+                .is_some_and(|id| config.rebinds.iter().any(|rebind| rebind.id == id));
+            if !found {
+                issues.push(Issue::warning(format!(
+                    "overlay {} override {} replaces no rebind",
+                    QuotedName(&overlay.name),
+                    index + 1
+                )));
+            }
+        }
+    }
+
+    let mut warnings: Vec<(usize, String)> =
+        read_order_warnings(&config.rebinds).into_iter().collect();
+    warnings.sort();
+    for (index, warning) in warnings {
+        let name = QuotedName(&config.rebinds[index].name);
+        issues.push(Issue::warning(format!("rebind {index} {name}: {warning}")));
+    }
+
+    for cycle in dependency_cycles(&config.rebinds) {
+        let names: Vec<String> = cycle
+            .iter()
+            .map(|index| format!("{index} {}", QuotedName(&config.rebinds[*index].name)))
+            .collect();
+        issues.push(Issue::warning(format!(
+            "feedback loop through virtual controls, the output will oscillate: rebinds {}",
+            names.join(", ")
+        )));
+    }
+
+    for conflict in shift_conflicts(&config.rebinds) {
+        let name = QuotedName(&config.rebinds[conflict.reroute_rebind].name);
+        issues.push(Issue::warning(format!(
+            "rebind {} {name}: {}",
+            conflict.reroute_rebind,
+            conflict.message()
+        )));
+    }
+
+    let required = required_virtual_devices(&config.rebinds);
+    let limits;
+    let available = match hints.is_empty() {
+        true => {
+            limits = vjoy_limits(&required);
+            &limits
+        }
+        false => hints,
+    };
+    issues.extend(
+        missing_virtual_capabilities(&required, available)
+            .into_iter()
+            .map(Issue::error),
+    );
+
+    issues
+}
+
+/// Capabilities of a fully configured vJoy device for every required device id
+fn vjoy_limits(required: &BTreeMap<u32, VirtualDeviceRequirement>) -> IndexMap<u32, DeviceInfo> {
+    required
+        .keys()
+        .map(|id| {
+            (
+                *id,
+                device_hint(*id, VJOY_MAX_BUTTONS, VJOY_MAX_AXES, VJOY_MAX_HATS),
+            )
+        })
+        .collect()
+}
+
+fn device_hint(id: u32, num_buttons: usize, num_axes: usize, num_hats: usize) -> DeviceInfo {
+    DeviceInfo {
+        name: format!("vJoy device {id}"),
+        num_buttons,
+        num_axes,
+        num_hats,
+        axis_kinds: Vec::new(),
+        axis_names: Vec::new(),
+        button_labels: ButtonLabelMap::default(),
+    }
+}
+
+/// Parses a capability hint like `1:32buttons:8axes:1hat`. Omitted parts count as 0.
+pub fn parse_vjoy_hint(hint: &str) -> Result<(u32, DeviceInfo), String> {
+    let mut parts = hint.split(':');
+    let id: u32 = parts
+        .next()
+        .and_then(|id| id.parse().ok())
+        .filter(|id| *id > 0)
+        .ok_or_else(|| format!("invalid vJoy id in '{hint}'"))?;
+
+    let (mut buttons, mut axes, mut hats) = (0, 0, 0);
+    for part in parts {
+        let split = part
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(part.len());
+        let (count, kind) = part.split_at(split);
+        let count: usize = count
+            .parse()
+            .map_err(|_| format!("invalid count '{part}' in '{hint}'"))?;
+        match kind {
+            "button" | "buttons" => buttons = count,
+            "axis" | "axes" => axes = count,
+            "hat" | "hats" => hats = count,
+            _ => return Err(format!("unknown capability '{part}' in '{hint}'")),
+        }
+    }
+
+    Ok((id, device_hint(id, buttons, axes, hats)))
+}
+
+/// Entry point for `--validate`. `args` are the arguments after the flag. Returns the exit code.
+pub fn run_cli(args: &[String]) -> i32 {
+    let mut path = None;
+    let mut json = false;
+    let mut hints = IndexMap::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--vjoy" => match args.next().map(|hint| parse_vjoy_hint(hint)) {
+                Some(Ok((id, info))) => {
+                    hints.insert(id, info);
+                }
+                Some(Err(e)) => {
+                    eprintln!("{e}\n{USAGE}");
+                    return 2;
+                }
+                None => {
+                    eprintln!("--vjoy needs a value\n{USAGE}");
+                    return 2;
+                }
+            },
+            other if path.is_none() && !other.starts_with("--") => {
+                path = Some(PathBuf::from(other))
+            }
+            other => {
+                eprintln!("unexpected argument '{other}'\n{USAGE}");
+                return 2;
+            }
+        }
+    }
+
+    let Some(path) = path else {
+        eprintln!("{USAGE}");
+        return 2;
+    };
+
+    let report = validate_config_file(&path, &hints);
+    match json {
+        true => println!("{}", report.to_json()),
+        false => println!("{}", report.to_text()),
+    }
+
+    i32::from(report.has_errors())
+}
+
+fn json_escape(string: &str) -> String {
+    let mut escaped = String::with_capacity(string.len());
+    for c in string.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use crate::{
+        config::Config,
+        rebind::{
+            axis_gain::ButtonSource, reroute_rebind::RerouteRebind, shift_mode_mask::ShiftModeMask,
+            Rebind, RebindType,
+        },
+        validate::{parse_vjoy_hint, validate_config, Severity, ValidationReport},
+    };
+
+    #[test]
+    fn vjoy_hints() {
+        let (id, info) = parse_vjoy_hint("2:32buttons:8axes:1hat").unwrap();
+        assert_eq!(id, 2);
+        assert_eq!((info.num_buttons, info.num_axes, info.num_hats), (32, 8, 1));
+
+        let (_, info) = parse_vjoy_hint("1:4axes").unwrap();
+        assert_eq!((info.num_buttons, info.num_axes, info.num_hats), (0, 4, 0));
+
+        assert!(parse_vjoy_hint("0:8axes").is_err());
+        assert!(parse_vjoy_hint("1:8sliders").is_err());
+        assert!(parse_vjoy_hint("1:axes").is_err());
+    }
+
+    #[test]
+    fn capability_hints_turn_into_errors() {
+        let button = |name: &str, dst_button| {
+            Rebind::new(
+                name.to_string(),
+                ShiftModeMask::default(),
+                RebindType::Reroute {
+                    rebind: RerouteRebind::ButtonToButton {
+                        src: ButtonSource::physical("guid".to_string(), 1),
+                        dst_device: 1,
+                        dst_button,
+                        modifier: Default::default(),
+                    },
+                },
+            )
+        };
+        let config = Config {
+            rebinds: vec![button("Gear", 12), button("Gear", 32)],
+            ..Default::default()
+        };
+
+        let issues = validate_config(&config, &IndexMap::new());
+        assert!(issues.iter().all(|i| i.severity == Severity::Warning));
+        assert!(issues.iter().any(|i| i.message.contains("is used 2 times")));
+
+        let hints = IndexMap::from([parse_vjoy_hint("1:16buttons").unwrap()]);
+        let issues = validate_config(&config, &hints);
+        assert!(issues.iter().any(|i| i.severity == Severity::Error));
+    }
+
+    #[test]
+    fn names_cannot_break_report_lines() {
+        let mut config = Config::debug_xbox360_config();
+        let mut rebind = config.rebinds[0].clone();
+        rebind.name = "Gear'\nerror: fake\r\t".to_string();
+        config.rebinds = vec![rebind.clone(), rebind];
+
+        let issues = validate_config(&config, &IndexMap::new());
+        let duplicate = issues
+            .iter()
+            .find(|i| i.message.contains("is used 2 times"))
+            .unwrap();
+        assert!(!duplicate.message.contains(['\n', '\r', '\t']));
+        assert!(duplicate.message.contains("'Gear'\\nerror: fake\\r\\t'"));
+    }
+
+    #[test]
+    fn json_report_is_escaped() {
+        let report = ValidationReport {
+            path: "Cfg/\"quoted\".toml".into(),
+            issues: Vec::new(),
+        };
+        assert_eq!(
+            report.to_json(),
+            r#"{"path":"Cfg/\"quoted\".toml","valid":true,"issues":[]}"#
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use std::{collections::BTreeSet, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    input::Input,
+    rebind::{shift_mode_mask::ShiftModeMask, RebindScope},
+    ui_data::{ActiveTab, UIData},
+};
+
+/// Names of the expanded rebinds of both lists, drives the open state of their headers
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(default)]
+pub struct ExpandedRebinds {
+    pub global: BTreeSet<String>,
+    pub profile: BTreeSet<String>,
+}
+
+impl ExpandedRebinds {
+    pub fn get(&self, scope: RebindScope) -> &BTreeSet<String> {
+        match scope {
+            RebindScope::Global => &self.global,
+            RebindScope::Profile => &self.profile,
+        }
+    }
+
+    pub fn get_mut(&mut self, scope: RebindScope) -> &mut BTreeSet<String> {
+        match scope {
+            RebindScope::Global => &mut self.global,
+            RebindScope::Profile => &mut self.profile,
+        }
+    }
+
+    /// Records the open state of the rebind shown as `name`, which may have been renamed to
+    /// `new_name` in the same frame.
+    pub fn update(&mut self, scope: RebindScope, name: &str, new_name: &str, expanded: bool) {
+        let names = self.get_mut(scope);
+        if expanded == names.contains(name) && name == new_name {
+            return;
+        }
+        names.remove(name);
+        if expanded {
+            names.insert(new_name.to_string());
+        }
+    }
+}
+
+/// Where the UI was left at the last shutdown, persisted in the app settings.
+///
+/// The state of the config's rebind list is only restored if the same config is loaded on the
+/// next start, the global rebinds do not depend on the config.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(default)]
+pub struct ViewState {
+    pub active_tab: Option<ActiveTab>,
+    /// Config the rebind list state belongs to
+    pub cfg_path: Option<String>,
+    pub expanded_rebinds: ExpandedRebinds,
+    /// Vertical scroll offset of the config's rebind list
+    pub rebind_scroll: f32,
+    pub rebind_mode_filter: Option<ShiftModeMask>,
+}
+
+impl ViewState {
+    pub fn capture(ui_data: &UIData, cfg_path: Option<&Path>) -> Self {
+        let active_tab = match ui_data.active_tab {
+            #[cfg(debug_assertions)]
+            ActiveTab::ColorTest => None,
+            ref tab => Some(tab.clone()),
+        };
+        Self {
+            active_tab,
+            cfg_path: cfg_path.map(|p| p.to_string_lossy().to_string()),
+            expanded_rebinds: ui_data.expanded_rebinds.clone(),
+            rebind_scroll: ui_data.rebind_scroll,
+            rebind_mode_filter: ui_data.rebind_mode_filter,
+        }
+    }
+
+    pub fn restore(&self, ui_data: &mut UIData, input: &Input) {
+        match &self.active_tab {
+            Some(ActiveTab::ConfigEditor) => ui_data.config_editor.enter(input),
+            Some(ActiveTab::Performance) => ui_data.show_performance_tab = true,
+            _ => (),
+        }
+        if let Some(tab) = &self.active_tab {
+            ui_data.active_tab = tab.clone();
+        }
+
+        ui_data.expanded_rebinds.global = self.expanded_rebinds.global.clone();
+        if self.is_same_config(input.get_config_path()) {
+            ui_data.expanded_rebinds.profile = self.expanded_rebinds.profile.clone();
+            ui_data.restore_rebind_scroll = Some(self.rebind_scroll);
+            ui_data.rebind_mode_filter = self.rebind_mode_filter;
+        }
+    }
+
+    fn is_same_config(&self, cfg_path: Option<&Path>) -> bool {
+        match (&self.cfg_path, cfg_path) {
+            (Some(saved), Some(path)) => Path::new(saved) == path,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{
+        rebind::RebindScope,
+        view_state::{ExpandedRebinds, ViewState},
+    };
+
+    #[test]
+    fn renamed_rebinds_stay_expanded() {
+        let mut expanded = ExpandedRebinds::default();
+        expanded.update(RebindScope::Profile, "pitch", "pitch", true);
+        expanded.update(RebindScope::Profile, "pitch", "pitch trim", true);
+        assert!(expanded.profile.contains("pitch trim"));
+        assert!(!expanded.profile.contains("pitch"));
+        assert!(expanded.global.is_empty());
+
+        expanded.update(RebindScope::Profile, "pitch trim", "pitch trim", false);
+        assert!(expanded.profile.is_empty());
+    }
+
+    #[test]
+    fn rebind_list_state_belongs_to_its_config() {
+        let state = ViewState {
+            cfg_path: Some("Cfg/racing.toml".to_string()),
+            ..Default::default()
+        };
+        assert!(state.is_same_config(Some(Path::new("Cfg/racing.toml"))));
+        assert!(!state.is_same_config(Some(Path::new("Cfg/flight.toml"))));
+        assert!(!state.is_same_config(None));
+        assert!(!ViewState::default().is_same_config(None));
+    }
+}
@@ -0,0 +1,114 @@
+use egui::{Color32, Slider, Ui};
+use serde::{Deserialize, Serialize};
+use strum::{EnumIter, IntoEnumIterator};
+
+use crate::auto_color;
+
+/// Okabe-Ito palette without black, distinguishable with the common color vision deficiencies
+const OKABE_ITO: [Color32; 7] = [
+    Color32::from_rgb(0xe6, 0x9f, 0x00),
+    Color32::from_rgb(0x56, 0xb4, 0xe9),
+    Color32::from_rgb(0x00, 0x9e, 0x73),
+    Color32::from_rgb(0xf0, 0xe4, 0x42),
+    Color32::from_rgb(0x00, 0x72, 0xb2),
+    Color32::from_rgb(0xd5, 0x5e, 0x00),
+    Color32::from_rgb(0xcc, 0x79, 0xa7),
+];
+
+/// Fully saturated colors that stand out on both the dark and the light theme
+const HIGH_CONTRAST: [Color32; 6] = [
+    Color32::from_rgb(0xff, 0x00, 0x00),
+    Color32::from_rgb(0x00, 0xc8, 0xff),
+    Color32::from_rgb(0xff, 0xd0, 0x00),
+    Color32::from_rgb(0xff, 0x00, 0xff),
+    Color32::from_rgb(0x00, 0xe0, 0x00),
+    Color32::from_rgb(0xff, 0x80, 0x00),
+];
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default, EnumIter)]
+pub enum Palette {
+    /// Golden ratio hue wheel, never repeats but neighbouring hues can be close
+    #[default]
+    Default,
+    OkabeIto,
+    HighContrast,
+}
+
+impl Palette {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Palette::Default => "Default",
+            Palette::OkabeIto => "Colorblind-safe (Okabe-Ito)",
+            Palette::HighContrast => "High contrast",
+        }
+    }
+
+    /// Color of the `i`-th trace. Fixed palettes repeat after their last color.
+    pub fn color(&self, i: usize) -> Color32 {
+        match self {
+            Palette::Default => auto_color(i),
+            Palette::OkabeIto => OKABE_ITO[i % OKABE_ITO.len()],
+            Palette::HighContrast => HIGH_CONTRAST[i % HIGH_CONTRAST.len()],
+        }
+    }
+}
+
+/// Colors and line styles of the input viewer, owned by the UI and persisted in the app settings.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(default)]
+pub struct VisualizationSettings {
+    pub palette: Palette,
+    pub line_width: f32,
+    /// Pressed buttons are filled instead of only getting a stronger outline
+    pub filled_buttons: bool,
+}
+
+impl Default for VisualizationSettings {
+    fn default() -> Self {
+        Self {
+            palette: Palette::Default,
+            line_width: 2.0,
+            filled_buttons: false,
+        }
+    }
+}
+
+impl VisualizationSettings {
+    pub fn color(&self, i: usize) -> Color32 {
+        self.palette.color(i)
+    }
+
+    pub fn settings_ui(&mut self, ui: &mut Ui) {
+        ui.label("Palette:");
+        for palette in Palette::iter() {
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.palette, palette, palette.name());
+                for i in 0..6 {
+                    ui.colored_label(palette.color(i), "■");
+                }
+            });
+        }
+        ui.separator();
+        ui.add(Slider::new(&mut self.line_width, 0.5..=6.0).text("Plot line width"));
+        ui.checkbox(&mut self.filled_buttons, "Fill pressed buttons");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use strum::IntoEnumIterator;
+
+    use crate::visualization::Palette;
+
+    #[test]
+    fn fixed_palettes_repeat_distinct_colors() {
+        for palette in Palette::iter().filter(|p| *p != Palette::Default) {
+            let colors: Vec<_> = (0..32).map(|i| palette.color(i)).collect();
+            let distinct: HashSet<_> = colors.iter().collect();
+            assert!(distinct.len() >= 6, "{palette:?}");
+            assert_eq!(colors[0], colors[distinct.len()], "{palette:?}");
+        }
+    }
+}